@@ -0,0 +1,255 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generates typed call builders and result decoders from the Sierra ABIs of the contracts in
+//! `abi/`, one module per contract, included by `src/contracts/impls/abi.rs`.
+
+use serde_json::Value;
+use std::{env, fmt::Write, fs, path::Path};
+
+fn main() {
+    println!("cargo:rerun-if-changed=abi");
+    // The database migrations are embedded by `sqlx::migrate!`.
+    println!("cargo:rerun-if-changed=migrations");
+
+    let mut paths = fs::read_dir("abi")
+        .expect("Failed to read the abi directory")
+        .map(|entry| entry.expect("Failed to read the abi directory").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    let mut out = String::new();
+    for path in paths {
+        println!("cargo:rerun-if-changed={}", path.display());
+        let contract = path.file_stem().and_then(|stem| stem.to_str()).expect("Invalid ABI name");
+        let abi: Vec<Value> = serde_json::from_str(
+            &fs::read_to_string(&path).expect("Failed to read the contract ABI"),
+        )
+        .unwrap_or_else(|e| panic!("Invalid ABI {}: {e}", path.display()));
+        generate(&mut out, contract, &abi);
+    }
+
+    let dest = Path::new(&env::var("OUT_DIR").expect("OUT_DIR is not set")).join("abi.rs");
+    fs::write(dest, out).expect("Failed to write the generated ABI bindings");
+}
+
+/// Generate the module of a contract.
+fn generate(out: &mut String, contract: &str, abi: &[Value]) {
+    writeln!(
+        out,
+        "/// Calls and results of the `{contract}` contract, generated from `abi/{contract}.json`."
+    )
+    .unwrap();
+    writeln!(out, "pub mod {contract} {{").unwrap();
+    writeln!(out, "    #[allow(unused_imports)]").unwrap();
+    writeln!(out, "    use crate::contracts::impls::codec::{{decode, Decode, Encode}};").unwrap();
+    writeln!(out, "    #[allow(unused_imports)]").unwrap();
+    writeln!(out, "    use crate::contracts::impls::calldata::CalldataBuilder;").unwrap();
+    writeln!(out, "    #[allow(unused_imports)]").unwrap();
+    writeln!(out, "    use anyhow::Result;").unwrap();
+    writeln!(out, "    #[allow(unused_imports)]").unwrap();
+    writeln!(out, "    use num_bigint::BigUint;").unwrap();
+    writeln!(out, "    #[allow(unused_imports)]").unwrap();
+    writeln!(out, "    use starknet::{{core::types::{{Call, Felt}}, macros::selector}};").unwrap();
+    writeln!(out, "    #[allow(unused_imports)]").unwrap();
+    writeln!(out, "    use std::slice::Iter;").unwrap();
+
+    for item in abi {
+        match str_field(item, "type") {
+            "struct" => generate_struct(out, item),
+            "event" if str_field(item, "kind") == "struct" => generate_event(out, item),
+            "function" => generate_function(out, item),
+            "interface" => {
+                for item in item["items"].as_array().into_iter().flatten() {
+                    generate_function(out, item);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    writeln!(out, "}}\n").unwrap();
+}
+
+/// Generate a struct, serialized as its members in order.
+fn generate_struct(out: &mut String, item: &Value) {
+    let name = last_segment(str_field(item, "name"));
+    let members = members(item);
+
+    writeln!(out, "\n    #[derive(Debug, Clone)]").unwrap();
+    writeln!(out, "    pub struct {name} {{").unwrap();
+    for (member, ty, _) in &members {
+        writeln!(out, "        pub {member}: {ty},").unwrap();
+    }
+    writeln!(out, "    }}\n").unwrap();
+
+    writeln!(out, "    impl Encode for {name} {{").unwrap();
+    writeln!(out, "        fn encode(&self, calldata: &mut Vec<Felt>) -> Result<()> {{").unwrap();
+    for (member, _, _) in &members {
+        writeln!(out, "            self.{member}.encode(calldata)?;").unwrap();
+    }
+    writeln!(out, "            Ok(())").unwrap();
+    writeln!(out, "        }}").unwrap();
+    writeln!(out, "    }}\n").unwrap();
+
+    writeln!(out, "    impl Decode for {name} {{").unwrap();
+    writeln!(out, "        fn decode(result: &mut Iter<'_, Felt>) -> Result<Self> {{").unwrap();
+    writeln!(out, "            Ok(Self {{").unwrap();
+    for (member, _, _) in &members {
+        writeln!(out, "                {member}: Decode::decode(result)?,").unwrap();
+    }
+    writeln!(out, "            }})").unwrap();
+    writeln!(out, "        }}").unwrap();
+    writeln!(out, "    }}").unwrap();
+}
+
+/// Generate an event with its selector, decoded from the keys and data of an emitted event.
+fn generate_event(out: &mut String, item: &Value) {
+    let name = last_segment(str_field(item, "name"));
+    let members = members(item);
+
+    writeln!(out, "\n    #[derive(Debug, Clone)]").unwrap();
+    writeln!(out, "    pub struct {name} {{").unwrap();
+    for (member, ty, _) in &members {
+        writeln!(out, "        pub {member}: {ty},").unwrap();
+    }
+    writeln!(out, "    }}\n").unwrap();
+
+    writeln!(out, "    impl {name} {{").unwrap();
+    writeln!(out, "        pub const SELECTOR: Felt = selector!(\"{name}\");\n").unwrap();
+    writeln!(out, "        /// Decode the event from its keys, led by its selector, and its data.")
+        .unwrap();
+    writeln!(out, "        pub fn decode(keys: &[Felt], data: &[Felt]) -> Result<Self> {{")
+        .unwrap();
+    writeln!(out, "            let mut keys = keys.iter();").unwrap();
+    writeln!(out, "            keys.next();").unwrap();
+    writeln!(out, "            let mut data = data.iter();").unwrap();
+    writeln!(out, "            Ok(Self {{").unwrap();
+    for (member, _, kind) in &members {
+        let source = if kind == "key" { "keys" } else { "data" };
+        writeln!(out, "                {member}: Decode::decode(&mut {source})?,").unwrap();
+    }
+    writeln!(out, "            }})").unwrap();
+    writeln!(out, "        }}").unwrap();
+    writeln!(out, "    }}").unwrap();
+}
+
+/// Generate the call builder of a function, and the decoder of its result for views.
+fn generate_function(out: &mut String, item: &Value) {
+    if str_field(item, "type") != "function" {
+        return;
+    }
+    let name = str_field(item, "name");
+    let inputs = item["inputs"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|input| (str_field(input, "name").to_string(), rust_type(str_field(input, "type"))))
+        .collect::<Vec<_>>();
+
+    let params = inputs.iter().map(|(input, ty)| format!(", {input}: {ty}")).collect::<String>();
+    writeln!(out, "\n    /// Call of `{name}`.").unwrap();
+    writeln!(out, "    pub fn {name}(to: Felt{params}) -> Result<Call> {{").unwrap();
+    let pushes = inputs.iter().map(|(input, _)| format!(".push(&{input})?")).collect::<String>();
+    writeln!(out, "        let calldata = CalldataBuilder::new(){pushes}.build();").unwrap();
+    writeln!(out, "        Ok(Call {{ to, selector: selector!(\"{name}\"), calldata }})").unwrap();
+    writeln!(out, "    }}").unwrap();
+
+    let outputs = item["outputs"].as_array().map(Vec::as_slice).unwrap_or_default();
+    if str_field(item, "state_mutability") == "view" {
+        if let [output] = outputs {
+            let ty = rust_type(str_field(output, "type"));
+            writeln!(out, "\n    /// Decode the result of `{name}`.").unwrap();
+            writeln!(out, "    pub fn decode_{name}(result: &[Felt]) -> Result<{ty}> {{").unwrap();
+            writeln!(out, "        decode(result)").unwrap();
+            writeln!(out, "    }}").unwrap();
+        }
+    }
+}
+
+/// The members of a struct or event, with their Rust type and kind.
+fn members(item: &Value) -> Vec<(String, String, String)> {
+    item["members"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|member| {
+            (
+                str_field(member, "name").to_string(),
+                rust_type(str_field(member, "type")),
+                str_field(member, "kind").to_string(),
+            )
+        })
+        .collect()
+}
+
+/// Map a Cairo type to the Rust type it is encoded from and decoded into.
+fn rust_type(ty: &str) -> String {
+    match ty {
+        "core::felt252"
+        | "core::starknet::contract_address::ContractAddress"
+        | "core::starknet::class_hash::ClassHash" => "Felt".to_string(),
+        "core::bool" => "bool".to_string(),
+        "core::integer::u8"
+        | "core::integer::u16"
+        | "core::integer::u32"
+        | "core::integer::u64"
+        | "core::integer::u128" => last_segment(ty).to_string(),
+        "core::integer::u256" => "BigUint".to_string(),
+        _ => {
+            if let Some(item) = ty
+                .strip_prefix("core::array::Array::<")
+                .or_else(|| ty.strip_prefix("core::array::Span::<"))
+                .and_then(|ty| ty.strip_suffix('>'))
+            {
+                format!("Vec<{}>", rust_type(item))
+            } else if let Some(items) = ty.strip_prefix('(').and_then(|ty| ty.strip_suffix(')')) {
+                let items = split_top_level(items).into_iter().map(rust_type).collect::<Vec<_>>();
+                format!("({},)", items.join(", "))
+            } else if ty.contains('<') {
+                panic!("Unsupported ABI type `{ty}`")
+            } else {
+                last_segment(ty).to_string()
+            }
+        }
+    }
+}
+
+/// Split the items of a tuple type on the commas which are not nested.
+fn split_top_level(items: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0, 0);
+    for (i, c) in items.char_indices() {
+        match c {
+            '<' | '(' => depth += 1,
+            '>' | ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(items[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(items[start..].trim());
+    parts.into_iter().filter(|part| !part.is_empty()).collect()
+}
+
+fn last_segment(path: &str) -> &str {
+    path.rsplit("::").next().unwrap_or(path)
+}
+
+fn str_field<'a>(item: &'a Value, field: &str) -> &'a str {
+    item[field].as_str().unwrap_or_default()
+}
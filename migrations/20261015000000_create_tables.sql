@@ -0,0 +1,99 @@
+-- The state of the workflows, complementing the chain, which settles their payouts.
+
+CREATE TABLE workflows (
+    id              UUID PRIMARY KEY,
+    project         TEXT NOT NULL,
+    organization    TEXT,
+    snapshot_id     UUID,
+    wallet_address  TEXT,
+    created_at      TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX workflows_project_idx ON workflows (project);
+
+-- The signatures collected by the workflows, once recorded on chain.
+CREATE TABLE signatures (
+    workflow_id     UUID NOT NULL REFERENCES workflows (id) ON DELETE CASCADE,
+    signer          TEXT NOT NULL,
+    signature_hash  TEXT NOT NULL,
+    tx_hash         TEXT NOT NULL,
+    signed_at       TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (workflow_id, tx_hash)
+);
+
+-- The dependencies created on chain by the workflows, keyed by their id on chain.
+CREATE TABLE dependencies (
+    id              TEXT PRIMARY KEY,
+    workflow_id     TEXT NOT NULL,
+    github_owner    TEXT NOT NULL,
+    name            TEXT NOT NULL,
+    repository_url  TEXT NOT NULL,
+    license         TEXT NOT NULL,
+    metadata_json   TEXT NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX dependencies_workflow_id_idx ON dependencies (workflow_id);
+
+-- The contributions of the workflows, the scores of the dependencies of their snapshots.
+CREATE TABLE contributions (
+    workflow_id     UUID NOT NULL,
+    snapshot_id     UUID NOT NULL,
+    ecosystem       TEXT NOT NULL,
+    name            TEXT NOT NULL,
+    version         TEXT NOT NULL,
+    score           DOUBLE PRECISION NOT NULL,
+    computed_at     TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (workflow_id, snapshot_id, ecosystem, name)
+);
+
+-- The allocations followed by the execution worker, keyed by their id on chain.
+CREATE TABLE allocations (
+    id              TEXT PRIMARY KEY,
+    workflow_id     UUID NOT NULL,
+    contributor     TEXT,
+    ecosystem       TEXT,
+    package         TEXT,
+    snapshot_id     UUID,
+    recipient       TEXT NOT NULL,
+    amount          TEXT NOT NULL,
+    denomination    TEXT NOT NULL,
+    token           TEXT NOT NULL,
+    usd_rate        TEXT,
+    token_amount    TEXT,
+    status          TEXT NOT NULL,
+    tx_hash         TEXT,
+    submitted_at    TIMESTAMPTZ,
+    executed_at     TIMESTAMPTZ,
+    attempts        INTEGER NOT NULL DEFAULT 0,
+    error           TEXT,
+    reallocate      BOOLEAN NOT NULL DEFAULT TRUE
+);
+
+CREATE INDEX allocations_workflow_id_idx ON allocations (workflow_id);
+CREATE INDEX allocations_status_idx ON allocations (status);
+
+-- The receipts created on chain, with the hash and URI of their stored metadata.
+CREATE TABLE receipts (
+    id              TEXT PRIMARY KEY,
+    workflow_id     TEXT NOT NULL,
+    repository_url  TEXT NOT NULL,
+    name            TEXT NOT NULL,
+    version         TEXT NOT NULL,
+    license         TEXT NOT NULL,
+    metadata_hash   TEXT NOT NULL,
+    metadata_uri    TEXT NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX receipts_workflow_id_idx ON receipts (workflow_id);
+
+-- The airdrops, and the wallet addresses submitted by their recipients.
+CREATE TABLE airdrops (
+    id              UUID PRIMARY KEY,
+    workflow_id     UUID,
+    recipient       TEXT NOT NULL,
+    wallet_address  TEXT,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT now(),
+    submitted_at    TIMESTAMPTZ
+);
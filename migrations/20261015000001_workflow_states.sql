@@ -0,0 +1,21 @@
+-- The orchestration of the workflows, and the transitions between their states.
+
+ALTER TABLE workflows
+    ADD COLUMN chain_id TEXT,
+    ADD COLUMN state TEXT NOT NULL DEFAULT 'created',
+    ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+CREATE INDEX workflows_state_idx ON workflows (state);
+
+CREATE TABLE workflow_transitions (
+    workflow_id       UUID NOT NULL REFERENCES workflows (id) ON DELETE CASCADE,
+    position          INTEGER NOT NULL,
+    from_state        TEXT NOT NULL,
+    to_state          TEXT NOT NULL,
+    error             TEXT,
+    step              TEXT,
+    attested_tx_hash  TEXT NOT NULL,
+    tx_hash           TEXT,
+    transitioned_at   TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (workflow_id, position)
+);
@@ -0,0 +1,28 @@
+-- The outbound webhooks, and the queue of their deliveries.
+
+CREATE TABLE webhooks (
+    id          UUID PRIMARY KEY,
+    owner       TEXT NOT NULL,
+    url         TEXT NOT NULL,
+    secret      TEXT NOT NULL,
+    events      TEXT[] NOT NULL,
+    created_at  TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX webhooks_owner_idx ON webhooks (owner);
+
+CREATE TABLE webhook_deliveries (
+    id               UUID PRIMARY KEY,
+    webhook_id       UUID NOT NULL REFERENCES webhooks (id) ON DELETE CASCADE,
+    event            TEXT NOT NULL,
+    payload          TEXT NOT NULL,
+    status           TEXT NOT NULL,
+    attempts         INTEGER NOT NULL,
+    next_attempt_at  TIMESTAMPTZ NOT NULL,
+    error            TEXT,
+    created_at       TIMESTAMPTZ NOT NULL,
+    delivered_at     TIMESTAMPTZ
+);
+
+CREATE INDEX webhook_deliveries_pending_idx ON webhook_deliveries (next_attempt_at)
+    WHERE status = 'pending';
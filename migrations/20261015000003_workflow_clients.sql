@@ -0,0 +1,4 @@
+-- The API key which created each workflow, which the events of the workflow are posted
+-- to the webhooks of.
+
+ALTER TABLE workflows ADD COLUMN client TEXT;
@@ -0,0 +1,9 @@
+-- What each workflow was created to analyze: the repository, its revision and the
+-- ranking profile.
+
+ALTER TABLE workflows
+    ADD COLUMN repo TEXT NOT NULL DEFAULT '',
+    ADD COLUMN branch TEXT,
+    ADD COLUMN tag TEXT,
+    ADD COLUMN rev TEXT,
+    ADD COLUMN profile TEXT NOT NULL DEFAULT 'default';
@@ -0,0 +1,16 @@
+-- The double-entry ledger of the funds paid out by the workflows, append only: a payout
+-- removed from the chain is reversed by the opposite entries.
+
+CREATE TABLE ledger_entries (
+    id              BIGSERIAL PRIMARY KEY,
+    workflow_id     UUID NOT NULL,
+    account         TEXT NOT NULL,
+    side            TEXT NOT NULL,
+    amount          TEXT NOT NULL,
+    token           TEXT NOT NULL,
+    allocation_id   TEXT NOT NULL,
+    tx_hash         TEXT,
+    created_at      TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX ledger_entries_workflow_idx ON ledger_entries (workflow_id);
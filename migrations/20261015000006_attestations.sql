@@ -0,0 +1,22 @@
+-- The published versions of the recipient terms, and who accepted which version, when
+-- and from which IP address, for the compliance exports.
+
+CREATE TABLE terms (
+    version         TEXT PRIMARY KEY,
+    title           TEXT NOT NULL,
+    content         TEXT NOT NULL,
+    content_hash    TEXT NOT NULL,
+    published_at    TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE attestations (
+    id              BIGSERIAL PRIMARY KEY,
+    address         TEXT NOT NULL,
+    username        TEXT,
+    version         TEXT NOT NULL REFERENCES terms (version),
+    content_hash    TEXT NOT NULL,
+    ip              TEXT,
+    accepted_at     TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX attestations_address_idx ON attestations (address);
@@ -0,0 +1,18 @@
+-- The organizations owning the projects and workflows of GitHub owners, and their
+-- members.
+
+CREATE TABLE organizations (
+    name            TEXT PRIMARY KEY,
+    display_name    TEXT NOT NULL,
+    owners          TEXT[] NOT NULL,
+    treasury        TEXT,
+    created_at      TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE organization_members (
+    organization    TEXT NOT NULL REFERENCES organizations (name) ON DELETE CASCADE,
+    username        TEXT NOT NULL,
+    role            TEXT NOT NULL,
+    address         TEXT,
+    PRIMARY KEY (organization, username)
+);
@@ -0,0 +1,4 @@
+-- The digest of the API key each member of an organization reads and manages its
+-- workflows with.
+
+ALTER TABLE organization_members ADD COLUMN client TEXT;
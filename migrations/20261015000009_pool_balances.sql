@@ -0,0 +1,12 @@
+-- The budget released by the failed allocations of each workflow, per token and
+-- denomination: available to re-allocate in a follow-up round, or clawed back to the
+-- treasury. The amounts are in whole tokens.
+
+CREATE TABLE pool_balances (
+    workflow_id     UUID NOT NULL,
+    token           TEXT NOT NULL,
+    denomination    TEXT NOT NULL,
+    available       TEXT NOT NULL,
+    clawed_back     TEXT NOT NULL,
+    PRIMARY KEY (workflow_id, token, denomination)
+);
@@ -0,0 +1,26 @@
+-- The snapshots of the ranking runs, which the workflows reference, never modified once
+-- recorded. The weights, ranked dependencies and resolved graph are stored as JSON.
+
+CREATE TABLE snapshots (
+    id                  UUID PRIMARY KEY,
+    project             TEXT NOT NULL,
+    commit_hash         TEXT NOT NULL,
+    algorithm_version   TEXT NOT NULL,
+    profile             TEXT NOT NULL,
+    weights_json        TEXT NOT NULL,
+    dependencies_json   TEXT NOT NULL,
+    graph_json          TEXT NOT NULL,
+    created_at          TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX snapshots_project_idx ON snapshots (project);
+
+-- The address books of the contributors, by lowercase GitHub username: their payout
+-- address on each chain, one of them possibly preferred.
+CREATE TABLE contributor_addresses (
+    username    TEXT NOT NULL,
+    chain       TEXT NOT NULL,
+    address     TEXT NOT NULL,
+    preferred   BOOLEAN NOT NULL DEFAULT FALSE,
+    PRIMARY KEY (username, chain)
+);
@@ -0,0 +1,422 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use roxmltree::{Document, Node};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Component, Path},
+};
+
+use super::AnalyzedDependency;
+use crate::responses::dependency::{DependencyKind, Ecosystem};
+
+/// Maven Central, which the builds do not name when artifacts resolve from it
+pub const MAVEN_CENTRAL: &str = "https://repo.maven.apache.org/maven2/";
+
+/// The build files of Maven and Gradle projects
+const BUILD_FILES: [&str; 5] =
+    ["pom.xml", "build.gradle", "build.gradle.kts", "settings.gradle", "settings.gradle.kts"];
+
+/// The licenses of the artifacts, by the names their POMs give them, eg. `The Apache
+/// Software License, Version 2.0`
+const LICENSES: [(&str, &str); 12] = [
+    ("apache", "Apache-2.0"),
+    ("mit license", "MIT"),
+    ("bsd-3", "BSD-3-Clause"),
+    ("bsd 3", "BSD-3-Clause"),
+    ("new bsd", "BSD-3-Clause"),
+    ("revised bsd", "BSD-3-Clause"),
+    ("bsd-2", "BSD-2-Clause"),
+    ("simplified bsd", "BSD-2-Clause"),
+    ("eclipse public license - v 2.0", "EPL-2.0"),
+    ("eclipse public license 2.0", "EPL-2.0"),
+    ("eclipse public license - v 1.0", "EPL-1.0"),
+    ("mozilla public license, version 2.0", "MPL-2.0"),
+];
+
+/// A dependency declared by a build file
+#[derive(Debug, Clone)]
+struct DeclaredDependency {
+    /// The coordinates of the artifact, eg. `com.google.guava:guava`
+    name: String,
+    version: Option<String>,
+    kind: DependencyKind,
+}
+
+/// A POM, as far as its dependencies go
+#[derive(Debug, Default, Clone)]
+struct Pom {
+    properties: HashMap<String, String>,
+    /// The versions of the dependency management section, by coordinates
+    managed: HashMap<String, String>,
+}
+
+/// Check if the directory holds a Maven or Gradle project
+pub fn is_project(dir: &Path) -> bool {
+    BUILD_FILES.iter().any(|name| dir.join(name).is_file())
+}
+
+/// List the artifacts a Maven or Gradle project depends on
+///
+/// Builds are never run, so the POMs of the artifacts are not resolved: the dependencies
+/// of a Maven project are the ones its POMs declare, and the ones of a Gradle project are
+/// the ones its gradle.lockfile locks, or else the ones its build files declare. The
+/// licenses and repositories are left unknown.
+pub fn locked_dependencies(project_dir: &Path) -> Result<Vec<AnalyzedDependency>> {
+    let (packages, direct) = if project_dir.join("pom.xml").is_file() {
+        let mut declared = Vec::new();
+        let mut local_artifacts = HashSet::new();
+        parse_pom(project_dir, &Pom::default(), &mut declared, &mut local_artifacts)?;
+        declared.retain(|dependency| !local_artifacts.contains(&dependency.name));
+        (declared, None)
+    } else {
+        let declared = gradle_dependencies(project_dir)?;
+        match fs::read_to_string(project_dir.join("gradle.lockfile")) {
+            Ok(content) => (parse_gradle_lockfile(&content), Some(declared)),
+            Err(_) => (declared, None),
+        }
+    };
+
+    // Lock files record the artifacts but not the dependency graph
+    let direct: HashMap<String, DependencyKind> = direct
+        .unwrap_or_else(|| packages.clone())
+        .into_iter()
+        .map(|dependency| (dependency.name, dependency.kind))
+        .collect();
+
+    let mut seen = HashSet::new();
+    Ok(packages
+        .into_iter()
+        .filter_map(|package| Some((package.version?, package.name, package.kind)))
+        .filter(|(version, name, _)| seen.insert((name.clone(), version.clone())))
+        .map(|(version, name, kind)| {
+            let declared = direct.get(&name).copied();
+            AnalyzedDependency {
+                ecosystem: Ecosystem::Maven,
+                kind: declared.unwrap_or(kind),
+                direct: declared.is_some(),
+                depth: if declared.is_some() { 1 } else { 2 },
+                name,
+                version,
+                source: Some(MAVEN_CENTRAL.to_string()),
+                license: None,
+                repository: None,
+                dependencies: Vec::new(),
+            }
+        })
+        .collect())
+}
+
+/// Parse the pom.xml of a directory and of its modules, which inherit its properties and
+/// managed versions
+fn parse_pom(
+    dir: &Path,
+    parent: &Pom,
+    declared: &mut Vec<DeclaredDependency>,
+    local_artifacts: &mut HashSet<String>,
+) -> Result<()> {
+    let content = fs::read_to_string(dir.join("pom.xml"))?;
+    let document = Document::parse(&content)?;
+    let project = document.root_element();
+    let mut pom = parent.clone();
+
+    let parent_node = child(project, "parent");
+    let group = text(project, "groupId").or_else(|| parent_node.and_then(|p| text(p, "groupId")));
+    let version = text(project, "version").or_else(|| parent_node.and_then(|p| text(p, "version")));
+    if let (Some(group), Some(artifact)) = (&group, text(project, "artifactId")) {
+        local_artifacts.insert(format!("{group}:{artifact}"));
+    }
+    for (name, value) in [("project.groupId", group), ("project.version", version)] {
+        if let Some(value) = value {
+            pom.properties.insert(name.to_string(), value);
+        }
+    }
+    for property in child(project, "properties").into_iter().flat_map(|p| p.children()) {
+        if let Some(value) = property.is_element().then(|| property.text()).flatten() {
+            pom.properties.insert(property.tag_name().name().to_string(), value.trim().to_string());
+        }
+    }
+
+    let management = child(project, "dependencyManagement").and_then(|m| child(m, "dependencies"));
+    for dependency in management.into_iter().flat_map(|d| d.children()).filter(|d| d.is_element()) {
+        let (Some(name), Some(version)) =
+            (coordinates(dependency, &pom), text(dependency, "version"))
+        else {
+            continue;
+        };
+        pom.managed.insert(name, interpolate(&version, &pom.properties));
+    }
+
+    let dependencies = child(project, "dependencies");
+    for dependency in dependencies.into_iter().flat_map(|d| d.children()).filter(|d| d.is_element())
+    {
+        let Some(name) = coordinates(dependency, &pom) else {
+            continue;
+        };
+        let version = text(dependency, "version")
+            .map(|version| interpolate(&version, &pom.properties))
+            .or_else(|| pom.managed.get(&name).cloned())
+            .filter(|version| !version.contains("${"));
+        let optional = text(dependency, "optional").is_some_and(|o| o == "true");
+        let kind = match text(dependency, "scope").as_deref() {
+            Some("test") => DependencyKind::Dev,
+            Some("provided") | Some("system") => DependencyKind::Build,
+            _ if optional => DependencyKind::Optional,
+            _ => DependencyKind::Runtime,
+        };
+        declared.push(DeclaredDependency { name, version, kind });
+    }
+
+    let modules = child(project, "modules");
+    let modules = modules.into_iter().flat_map(|m| m.children()).filter(|m| m.is_element());
+    for module in modules.filter_map(|m| m.text()) {
+        // Modules are nested, which also keeps a module from including itself
+        let module = Path::new(module.trim());
+        if !module.components().all(|c| matches!(c, Component::Normal(_))) {
+            continue;
+        }
+        let module_dir = dir.join(module);
+        // Modules may be missing from the checkout, eg. when they are generated
+        if module_dir.join("pom.xml").is_file() {
+            parse_pom(&module_dir, &pom, declared, local_artifacts)?;
+        }
+    }
+    Ok(())
+}
+
+/// The coordinates of a dependency of a POM, eg. `com.google.guava:guava`
+fn coordinates(dependency: Node, pom: &Pom) -> Option<String> {
+    let group = interpolate(&text(dependency, "groupId")?, &pom.properties);
+    let artifact = interpolate(&text(dependency, "artifactId")?, &pom.properties);
+    Some(format!("{group}:{artifact}"))
+}
+
+/// Replace the properties referenced by a value, eg. `${guava.version}`, leaving the
+/// unknown ones
+fn interpolate(value: &str, properties: &HashMap<String, String>) -> String {
+    let mut value = value.to_string();
+    // Properties may reference other properties, but not endlessly
+    for _ in 0..8 {
+        let Some(start) = value.find("${") else {
+            break;
+        };
+        let Some(end) = value[start..].find('}').map(|end| start + end) else {
+            break;
+        };
+        let Some(replacement) = properties.get(&value[start + 2..end]) else {
+            break;
+        };
+        value.replace_range(start..=end, replacement);
+    }
+    value
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| child.tag_name().name() == name)
+}
+
+fn text(node: Node, name: &str) -> Option<String> {
+    child(node, name).and_then(|child| child.text()).map(|text| text.trim().to_string())
+}
+
+/// List the dependencies declared by the build files of a Gradle project and of the
+/// subprojects its settings include
+fn gradle_dependencies(project_dir: &Path) -> Result<Vec<DeclaredDependency>> {
+    let catalog = fs::read_to_string(project_dir.join("gradle").join("libs.versions.toml"))
+        .ok()
+        .and_then(|content| content.parse::<toml::Value>().ok())
+        .map(|catalog| version_catalog(&catalog))
+        .unwrap_or_default();
+
+    let settings = ["settings.gradle", "settings.gradle.kts"]
+        .iter()
+        .find_map(|name| fs::read_to_string(project_dir.join(name)).ok())
+        .unwrap_or_default();
+    let include = Regex::new(r#"['"]:?([\w\-.:]+)['"]"#).unwrap();
+    let subprojects =
+        settings.lines().filter(|line| line.trim_start().starts_with("include")).flat_map(|line| {
+            include.captures_iter(line).map(|c| c[1].replace(':', "/")).collect::<Vec<_>>()
+        });
+
+    // eg. implementation("com.google.guava:guava:33.0.0-jre") or testImplementation libs.junit
+    let declaration = Regex::new(
+        r#"(?m)^\s*(\w+)\s*\(?\s*(?:["']([^"':\s]+):([^"':\s]+):([^"'@\s]+)["']|libs\.([\w.]+))"#,
+    )
+    .unwrap();
+
+    let mut found = false;
+    let mut declared = Vec::new();
+    for dir in std::iter::once(String::new()).chain(subprojects) {
+        let build = ["build.gradle", "build.gradle.kts"]
+            .iter()
+            .find_map(|name| fs::read_to_string(project_dir.join(&dir).join(name)).ok());
+        let Some(build) = build else {
+            continue;
+        };
+        found = true;
+
+        for captures in declaration.captures_iter(&build) {
+            let Some(kind) = configuration_kind(&captures[1]) else {
+                continue;
+            };
+            let (name, version) = match (captures.get(2), captures.get(3), captures.get(4)) {
+                (Some(group), Some(artifact), Some(version)) => (
+                    format!("{}:{}", group.as_str(), artifact.as_str()),
+                    Some(version.as_str().to_string()),
+                ),
+                _ => {
+                    // Accessors of the catalog separate the parts of the aliases with dots
+                    let alias = captures[5].replace(['-', '_'], ".");
+                    let Some((name, version)) = catalog.get(&alias) else {
+                        continue;
+                    };
+                    (name.clone(), version.clone())
+                }
+            };
+            // Dynamic versions and versions from variables are not resolved
+            let version = version.filter(|version| !version.contains(['$', '+', '[', '(']));
+            declared.push(DeclaredDependency { name, version, kind });
+        }
+    }
+
+    if !found {
+        return Err(anyhow!("Could not find pom.xml, build.gradle or build.gradle.kts file"));
+    }
+    Ok(declared)
+}
+
+/// The kind of the dependencies of a Gradle configuration, if it declares dependencies
+fn configuration_kind(configuration: &str) -> Option<DependencyKind> {
+    match configuration {
+        "implementation" | "api" | "runtimeOnly" | "compile" | "runtime" => {
+            Some(DependencyKind::Runtime)
+        }
+        "compileOnly" | "compileOnlyApi" | "annotationProcessor" | "kapt" | "ksp" => {
+            Some(DependencyKind::Build)
+        }
+        configuration if configuration.starts_with("test") => Some(DependencyKind::Dev),
+        _ => None,
+    }
+}
+
+/// Parse the libraries of a Gradle version catalog, by their alias with dots, eg.
+/// `guava = { module = "com.google.guava:guava", version.ref = "guava" }`
+fn version_catalog(catalog: &toml::Value) -> HashMap<String, (String, Option<String>)> {
+    let versions = catalog.get("versions").and_then(|v| v.as_table());
+    let libraries = catalog.get("libraries").and_then(|l| l.as_table());
+
+    let mut entries = HashMap::new();
+    for (alias, library) in libraries.into_iter().flatten() {
+        let (name, version) = match library {
+            toml::Value::String(notation) => {
+                let mut parts = notation.splitn(3, ':');
+                let (Some(group), Some(artifact)) = (parts.next(), parts.next()) else {
+                    continue;
+                };
+                (format!("{group}:{artifact}"), parts.next().map(toml::Value::from))
+            }
+            toml::Value::Table(fields) => {
+                let module = fields.get("module").and_then(|m| m.as_str()).map(str::to_string);
+                let group = fields.get("group").and_then(|g| g.as_str());
+                let artifact = fields.get("name").and_then(|n| n.as_str());
+                let name = module.or_else(|| Some(format!("{}:{}", group?, artifact?)));
+                let Some(name) = name else {
+                    continue;
+                };
+                (name, fields.get("version").cloned())
+            }
+            _ => continue,
+        };
+        // Versions are literal, or reference the versions section
+        let version = match version {
+            Some(toml::Value::String(version)) => Some(version),
+            Some(toml::Value::Table(version)) => {
+                let reference = version.get("ref").and_then(|r| r.as_str());
+                let version = reference.and_then(|r| versions?.get(r)).and_then(|v| v.as_str());
+                version.map(str::to_string)
+            }
+            _ => None,
+        };
+        entries.insert(alias.replace(['-', '_'], "."), (name, version));
+    }
+    entries
+}
+
+/// Parse a gradle.lockfile, eg. `com.google.guava:guava:33.0.0-jre=compileClasspath`
+fn parse_gradle_lockfile(content: &str) -> Vec<DeclaredDependency> {
+    content
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let (coordinates, configurations) = line.trim().split_once('=')?;
+            let (name, version) = coordinates.rsplit_once(':')?;
+            if !name.contains(':') {
+                return None; // eg. `empty=`
+            }
+            // Artifacts only test configurations resolve are dev dependencies
+            let kind = if configurations.split(',').all(|c| c.to_ascii_lowercase().contains("test"))
+            {
+                DependencyKind::Dev
+            } else if configurations.split(',').all(|c| c.contains("annotationProcessor")) {
+                DependencyKind::Build
+            } else {
+                DependencyKind::Runtime
+            };
+            Some(DeclaredDependency {
+                name: name.to_string(),
+                version: Some(version.to_string()),
+                kind,
+            })
+        })
+        .collect()
+}
+
+/// The SPDX license expression of an artifact and the links to its source code, as
+/// published in its POM
+pub fn published_info(pom: &str) -> Result<(Option<String>, Vec<String>)> {
+    let document = Document::parse(pom)?;
+    let project = document.root_element();
+
+    let licenses = child(project, "licenses");
+    let names: Vec<_> = licenses
+        .into_iter()
+        .flat_map(|l| l.children())
+        .filter_map(|license| text(license, "name"))
+        .collect();
+    let licenses: Option<Vec<_>> = names.iter().map(|name| spdx_license(name)).collect();
+    // Artifacts under several licenses let their users choose
+    let license = licenses.filter(|l| !l.is_empty()).map(|l| l.join(" OR "));
+
+    let scm = child(project, "scm");
+    let urls = [
+        scm.and_then(|s| text(s, "url")),
+        scm.and_then(|s| text(s, "connection")),
+        text(project, "url"),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|url| url.trim_start_matches("scm:git:").trim_start_matches("git+").to_string())
+    .collect();
+    Ok((license, urls))
+}
+
+/// The SPDX identifier of a license, by its name in a POM
+fn spdx_license(name: &str) -> Option<&'static str> {
+    // Some POMs name the license by its identifier already
+    let known = LICENSES.iter().find(|(_, spdx)| spdx.eq_ignore_ascii_case(name.trim()));
+    let name = name.to_ascii_lowercase();
+    known.or_else(|| LICENSES.iter().find(|(part, _)| name.contains(part))).map(|(_, spdx)| *spdx)
+}
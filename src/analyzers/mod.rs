@@ -12,4 +12,236 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+//! Analyzers of the dependencies of a project, one per ecosystem.
+//!
+//! Each analyzer resolves the packages a project locks, and normalizes them into
+//! [`AnalyzedDependency`], from which the metadata of the dependencies written on chain
+//! and the unscored dependencies of a snapshot are built. A repository may hold projects
+//! of several ecosystems, eg. a Rust backend and a JavaScript frontend: they are
+//! discovered by walking it, and their dependencies merged.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+
+use serde::{Deserialize, Serialize};
+
+use crate::responses::dependency::{DependencyKind, DependencyResponse, Ecosystem};
+
+pub mod maven;
+pub mod npm;
+pub mod python;
 pub mod rust;
+
+/// Directories of vendored sources, build outputs and binaries, never analyzed
+const SKIPPED_DIRS: [&str; 6] = [".git", "node_modules", "target", "vendor", "third_party", "dist"];
+
+/// A package resolved by the lock file of a project.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalyzedDependency {
+    pub ecosystem: Ecosystem,
+    pub name: String,
+    pub version: String,
+    /// The source of the package in the lock file, eg. its registry
+    pub source: Option<String>,
+    /// SPDX license expression, eg. `MIT OR Apache-2.0`
+    pub license: Option<String>,
+    /// Source code repository of the package
+    pub repository: Option<String>,
+    pub kind: DependencyKind,
+    /// Whether a manifest of the project declares the package
+    pub direct: bool,
+    /// Depth in the dependency tree, 1 for direct dependencies
+    pub depth: u32,
+    /// The names of the packages of its ecosystem it depends on
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+}
+
+impl AnalyzedDependency {
+    /// The key of the package in the dependency graph.
+    pub fn key(&self) -> PackageKey {
+        (self.ecosystem, self.name.clone())
+    }
+
+    /// The dependency of a snapshot, before it is scored.
+    pub fn to_response(&self) -> DependencyResponse {
+        DependencyResponse {
+            name: self.name.clone(),
+            version: self.version.clone(),
+            ecosystem: self.ecosystem,
+            license: self.license.clone(),
+            repository: self.repository.clone(),
+            registry: self.source.clone(),
+            kind: self.kind,
+            score: 0.0,
+            explanation: None,
+            excluded_by_policy: false,
+            flags: Vec::new(),
+        }
+    }
+}
+
+/// A project found in a repository, analyzed by the analyzer of its ecosystem.
+#[derive(Debug, Clone)]
+pub struct DiscoveredProject {
+    pub ecosystem: Ecosystem,
+    pub dir: PathBuf,
+}
+
+impl DiscoveredProject {
+    /// List the packages the project locks.
+    pub fn analyze(&self) -> Result<Vec<AnalyzedDependency>> {
+        match self.ecosystem {
+            Ecosystem::Cargo => rust::locked_dependencies(&self.dir),
+            Ecosystem::Npm => npm::locked_dependencies(&self.dir),
+            Ecosystem::Pypi => python::locked_dependencies(&self.dir),
+            Ecosystem::Maven => maven::locked_dependencies(&self.dir),
+        }
+    }
+}
+
+/// Walk a repository for the projects of every supported ecosystem.
+///
+/// A Maven or Gradle project covers its modules and subprojects, which are not discovered
+/// again.
+pub fn discover(root: &Path) -> Result<Vec<DiscoveredProject>> {
+    let mut projects = Vec::new();
+    discover_dir(root, false, &mut projects)?;
+    Ok(projects)
+}
+
+fn discover_dir(
+    dir: &Path,
+    in_jvm_project: bool,
+    projects: &mut Vec<DiscoveredProject>,
+) -> Result<()> {
+    let detected = [
+        (Ecosystem::Cargo, rust::is_project(dir)),
+        (Ecosystem::Npm, npm::is_project(dir)),
+        (Ecosystem::Pypi, python::is_project(dir)),
+        (Ecosystem::Maven, !in_jvm_project && maven::is_project(dir)),
+    ];
+    for (ecosystem, _) in detected.into_iter().filter(|(_, detected)| *detected) {
+        projects.push(DiscoveredProject { ecosystem, dir: dir.to_path_buf() });
+    }
+    let in_jvm_project = in_jvm_project || detected[3].1;
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        // Symbolic links are never followed
+        if entry.file_type()?.is_dir() && !is_skipped_dir(&entry.path()) {
+            discover_dir(&entry.path(), in_jvm_project, projects)?;
+        }
+    }
+    Ok(())
+}
+
+/// Check if directory holds vendored sources, build outputs or binaries
+pub fn is_skipped_dir(path: &Path) -> bool {
+    path.file_name().and_then(|n| n.to_str()).is_some_and(|name| SKIPPED_DIRS.contains(&name))
+}
+
+/// Merge the dependencies of the projects of a repository, a package several projects
+/// depend on getting its most essential kind and its shortest depth.
+pub fn merge(dependencies: Vec<AnalyzedDependency>) -> Vec<AnalyzedDependency> {
+    let mut merged: Vec<AnalyzedDependency> = Vec::new();
+    let mut indices = HashMap::new();
+    for dependency in dependencies {
+        let key = (dependency.ecosystem, dependency.name.clone(), dependency.version.clone());
+        let Some(&index) = indices.get(&key) else {
+            indices.insert(key, merged.len());
+            merged.push(dependency);
+            continue;
+        };
+        let existing = &mut merged[index];
+        existing.kind = existing.kind.max(dependency.kind);
+        existing.depth = existing.depth.min(dependency.depth);
+        existing.direct |= dependency.direct;
+        existing.source = existing.source.take().or(dependency.source);
+        existing.license = existing.license.take().or(dependency.license);
+        existing.repository = existing.repository.take().or(dependency.repository);
+        for name in dependency.dependencies {
+            if !existing.dependencies.contains(&name) {
+                existing.dependencies.push(name);
+            }
+        }
+    }
+    merged
+}
+
+/// A package of the dependency graph, names being unique within an ecosystem only.
+pub type PackageKey = (Ecosystem, String);
+
+/// The dependency graph of a project, as resolved by the analyzers, by ecosystem and
+/// package name.
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedGraph {
+    /// The packages the project depends on directly
+    pub direct: BTreeSet<PackageKey>,
+    /// The packages each package depends on, among the dependencies of the project
+    pub edges: BTreeMap<PackageKey, BTreeSet<PackageKey>>,
+}
+
+impl ResolvedGraph {
+    pub fn new(dependencies: &[AnalyzedDependency]) -> Self {
+        let keys: HashSet<_> = dependencies.iter().map(AnalyzedDependency::key).collect();
+        let mut graph = Self::default();
+        for dependency in dependencies {
+            if dependency.direct {
+                graph.direct.insert(dependency.key());
+            }
+            // A package only depends on packages of its own ecosystem
+            let depends_on = dependency
+                .dependencies
+                .iter()
+                .map(|name| (dependency.ecosystem, name.clone()))
+                .filter(|key| keys.contains(key));
+            graph.edges.entry(dependency.key()).or_default().extend(depends_on);
+        }
+        graph
+    }
+}
+
+/// Propagate the kinds and depths of the direct dependencies of a project to the
+/// transitive ones, along the edges of the dependency graph.
+///
+/// A package reached in several ways gets its most essential kind and its shortest depth.
+/// The local packages, the members of the project, are never dependencies.
+pub fn propagate(
+    direct: Vec<(String, DependencyKind)>,
+    local_packages: &HashSet<String>,
+    edges: &HashMap<String, Vec<String>>,
+) -> (HashMap<String, DependencyKind>, HashMap<String, u32>) {
+    // Propagate the depths to the transitive dependencies, breadth first
+    let mut depths = HashMap::new();
+    let mut queue: VecDeque<_> = direct.iter().map(|(name, _)| (name.clone(), 1)).collect();
+    while let Some((name, depth)) = queue.pop_front() {
+        if local_packages.contains(&name) || depths.contains_key(&name) {
+            continue;
+        }
+        depths.insert(name.clone(), depth);
+        for dependency in edges.get(&name).into_iter().flatten() {
+            queue.push_back((dependency.clone(), depth + 1));
+        }
+    }
+
+    // Propagate the kinds to the transitive dependencies
+    let mut kinds: HashMap<String, DependencyKind> = HashMap::new();
+    let mut pending = direct;
+    while let Some((name, kind)) = pending.pop() {
+        if local_packages.contains(&name) || kinds.get(&name).is_some_and(|k| *k >= kind) {
+            continue;
+        }
+        kinds.insert(name.clone(), kind);
+        for dependency in edges.get(&name).into_iter().flatten() {
+            pending.push((dependency.clone(), kind));
+        }
+    }
+
+    (kinds, depths)
+}
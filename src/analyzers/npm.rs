@@ -0,0 +1,418 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
+
+use super::AnalyzedDependency;
+use crate::responses::dependency::{DependencyKind, Ecosystem};
+
+/// The npm registry, which the lock files do not name when packages resolve from it
+pub const NPM_REGISTRY: &str = "https://registry.npmjs.org/";
+
+/// The Yarn mirror of the npm registry, written in place of it in yarn.lock files
+const YARN_REGISTRY: &str = "https://registry.yarnpkg.com/";
+
+/// A package resolved by a lock file
+#[derive(Debug)]
+struct LockedPackage {
+    name: String,
+    version: String,
+    source: Option<String>,
+    license: Option<String>,
+    /// The names of the packages it depends on
+    dependencies: Vec<String>,
+}
+
+/// The lock files of npm, Yarn and pnpm, in the order they are preferred
+const LOCK_FILES: [&str; 4] =
+    ["package-lock.json", "npm-shrinkwrap.json", "yarn.lock", "pnpm-lock.yaml"];
+
+/// Check if the directory holds a JavaScript project with a lock file, the root of its
+/// workspace if it has one
+pub fn is_project(dir: &Path) -> bool {
+    dir.join("package.json").is_file() && LOCK_FILES.iter().any(|name| dir.join(name).is_file())
+}
+
+/// List the packages locked by the lock file of a JavaScript project, from npm, Yarn
+/// or pnpm, but its workspace members
+///
+/// The licenses are read from the lock file when it records them, and the repositories
+/// are left unknown.
+pub fn locked_dependencies(project_dir: &Path) -> Result<Vec<AnalyzedDependency>> {
+    let manifest: Value =
+        serde_json::from_str(&fs::read_to_string(project_dir.join("package.json"))?)?;
+
+    let mut local_packages = HashSet::new();
+    if let Some(name) = manifest.get("name").and_then(Value::as_str) {
+        local_packages.insert(name.to_string());
+    }
+    let packages = if let Ok(content) =
+        read_first(project_dir, &["package-lock.json", "npm-shrinkwrap.json"])
+    {
+        parse_package_lock(&content, &mut local_packages)?
+    } else if let Ok(content) = fs::read_to_string(project_dir.join("yarn.lock")) {
+        if content.contains("__metadata:") {
+            parse_yarn_berry_lock(&content, &mut local_packages)?
+        } else {
+            parse_yarn_lock(&content)
+        }
+    } else if let Ok(content) = fs::read_to_string(project_dir.join("pnpm-lock.yaml")) {
+        parse_pnpm_lock(&content)?
+    } else {
+        return Err(anyhow!("Could not find package-lock.json, yarn.lock or pnpm-lock.yaml file"));
+    };
+
+    let mut edges: HashMap<String, Vec<String>> = HashMap::new();
+    for package in &packages {
+        edges.entry(package.name.clone()).or_default().extend(package.dependencies.iter().cloned());
+    }
+    let (kinds, depths) =
+        super::propagate(manifest_dependencies(&manifest), &local_packages, &edges);
+
+    let mut seen = HashSet::new();
+    Ok(packages
+        .into_iter()
+        .filter(|package| !local_packages.contains(&package.name))
+        .filter(|package| seen.insert((package.name.clone(), package.version.clone())))
+        .map(|package| {
+            let depth = depths.get(&package.name).copied().unwrap_or(1);
+            AnalyzedDependency {
+                ecosystem: Ecosystem::Npm,
+                kind: kinds.get(&package.name).copied().unwrap_or_default(),
+                direct: depth == 1,
+                depth,
+                name: package.name,
+                version: package.version,
+                source: package.source,
+                license: package.license,
+                repository: None,
+                dependencies: package.dependencies,
+            }
+        })
+        .collect())
+}
+
+/// Read the first of the files found in the directory
+fn read_first(dir: &Path, names: &[&str]) -> Result<String> {
+    names
+        .iter()
+        .find_map(|name| fs::read_to_string(dir.join(name)).ok())
+        .ok_or_else(|| anyhow!("Could not find any of {}", names.join(", ")))
+}
+
+/// List the dependencies declared by a package.json manifest, with their kind
+fn manifest_dependencies(manifest: &Value) -> Vec<(String, DependencyKind)> {
+    const SECTIONS: [(&str, DependencyKind); 4] = [
+        ("dependencies", DependencyKind::Runtime),
+        ("peerDependencies", DependencyKind::Runtime),
+        ("optionalDependencies", DependencyKind::Optional),
+        ("devDependencies", DependencyKind::Dev),
+    ];
+
+    let mut dependencies = Vec::new();
+    for (section, kind) in SECTIONS {
+        let declared = manifest.get(section).and_then(Value::as_object);
+        dependencies.extend(declared.into_iter().flatten().map(|(name, _)| (name.clone(), kind)));
+    }
+    dependencies
+}
+
+/// Parse a package-lock.json or npm-shrinkwrap.json file, of any lockfile version
+fn parse_package_lock(
+    content: &str,
+    local_packages: &mut HashSet<String>,
+) -> Result<Vec<LockedPackage>> {
+    let lock: Value = serde_json::from_str(content)?;
+    let mut packages = Vec::new();
+
+    // Lockfile versions 2 and 3 key the packages by their path in node_modules
+    if let Some(entries) = lock.get("packages").and_then(Value::as_object) {
+        for (path, entry) in entries {
+            // The root and the workspace members are not installed in node_modules
+            let Some((_, installed)) = path.rsplit_once("node_modules/") else {
+                continue;
+            };
+            let name = entry.get("name").and_then(Value::as_str).unwrap_or(installed);
+            if entry.get("link").and_then(Value::as_bool).unwrap_or(false) {
+                local_packages.insert(name.to_string());
+                continue;
+            }
+            let Some(version) = entry.get("version").and_then(Value::as_str) else {
+                continue;
+            };
+            packages.push(LockedPackage {
+                name: name.to_string(),
+                version: version.to_string(),
+                source: entry.get("resolved").and_then(Value::as_str).map(registry_source),
+                license: entry.get("license").and_then(Value::as_str).map(str::to_string),
+                dependencies: ["dependencies", "optionalDependencies", "peerDependencies"]
+                    .iter()
+                    .filter_map(|section| entry.get(section).and_then(Value::as_object))
+                    .flat_map(|declared| declared.keys().cloned())
+                    .collect(),
+            });
+        }
+        return Ok(packages);
+    }
+
+    // Lockfile version 1 nests the packages not hoisted under the ones requiring them
+    fn visit(entries: &serde_json::Map<String, Value>, packages: &mut Vec<LockedPackage>) {
+        for (name, entry) in entries {
+            if let Some(version) = entry.get("version").and_then(Value::as_str) {
+                let requires = entry.get("requires").and_then(Value::as_object);
+                packages.push(LockedPackage {
+                    name: name.clone(),
+                    version: version.to_string(),
+                    source: entry.get("resolved").and_then(Value::as_str).map(registry_source),
+                    license: None,
+                    dependencies: requires.into_iter().flat_map(|r| r.keys().cloned()).collect(),
+                });
+            }
+            if let Some(nested) = entry.get("dependencies").and_then(Value::as_object) {
+                visit(nested, packages);
+            }
+        }
+    }
+    if let Some(entries) = lock.get("dependencies").and_then(Value::as_object) {
+        visit(entries, &mut packages);
+    }
+    Ok(packages)
+}
+
+/// Parse a yarn.lock file of Yarn 1, in its own format
+fn parse_yarn_lock(content: &str) -> Vec<LockedPackage> {
+    let mut packages = Vec::new();
+    let mut current: Option<LockedPackage> = None;
+    let mut in_dependencies = false;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+
+        // Entries start with the descriptors they resolve, eg. `"lodash@^4.17.0", lodash@^4.17.21:`
+        if indent == 0 {
+            packages.extend(current.take().filter(|package| !package.version.is_empty()));
+            let descriptor = trimmed.trim_end_matches(':').split(", ").next().unwrap_or_default();
+            current = package_name(descriptor.trim_matches('"')).map(|name| LockedPackage {
+                name: name.to_string(),
+                version: String::new(),
+                source: None,
+                license: None,
+                dependencies: Vec::new(),
+            });
+            in_dependencies = false;
+            continue;
+        }
+        let Some(package) = current.as_mut() else {
+            continue;
+        };
+
+        if indent == 2 {
+            let (key, value) = trimmed.split_once(' ').unwrap_or((trimmed, ""));
+            let value = value.trim().trim_matches('"');
+            in_dependencies = matches!(key, "dependencies:" | "optionalDependencies:");
+            match key {
+                "version" => package.version = value.to_string(),
+                "resolved" => package.source = Some(registry_source(value)),
+                _ => {}
+            }
+        } else if in_dependencies {
+            let name = trimmed.split_whitespace().next().unwrap_or_default();
+            package.dependencies.push(name.trim_matches('"').to_string());
+        }
+    }
+    packages.extend(current.filter(|package| !package.version.is_empty()));
+    packages
+}
+
+/// Parse a yarn.lock file of Yarn 2 or later, in YAML
+fn parse_yarn_berry_lock(
+    content: &str,
+    local_packages: &mut HashSet<String>,
+) -> Result<Vec<LockedPackage>> {
+    let lock: serde_yaml::Mapping = serde_yaml::from_str(content)?;
+    let mut packages = Vec::new();
+
+    for (descriptors, entry) in &lock {
+        if descriptors.as_str() == Some("__metadata") {
+            continue;
+        }
+        // Resolutions name the package and its reference, eg. `lodash@npm:4.17.21`
+        let Some(resolution) = entry.get("resolution").and_then(serde_yaml::Value::as_str) else {
+            continue;
+        };
+        let Some(name) = package_name(resolution) else {
+            continue;
+        };
+        let reference = &resolution[name.len() + 1..];
+        if reference.starts_with("workspace:") {
+            local_packages.insert(name.to_string());
+            continue;
+        }
+        let Some(version) = entry.get("version").and_then(yaml_text) else {
+            continue;
+        };
+        let dependencies = ["dependencies", "optionalDependencies", "peerDependencies"]
+            .iter()
+            .filter_map(|section| entry.get(section).and_then(serde_yaml::Value::as_mapping))
+            .flat_map(|declared| declared.keys().filter_map(yaml_text))
+            .collect();
+        packages.push(LockedPackage {
+            name: name.to_string(),
+            version,
+            source: Some(if reference.starts_with("npm:") {
+                NPM_REGISTRY.to_string()
+            } else {
+                reference.to_string()
+            }),
+            license: None,
+            dependencies,
+        });
+    }
+    Ok(packages)
+}
+
+/// Parse a pnpm-lock.yaml file, of any lockfile version
+fn parse_pnpm_lock(content: &str) -> Result<Vec<LockedPackage>> {
+    let lock: serde_yaml::Value = serde_yaml::from_str(content)?;
+    let mut packages: Vec<LockedPackage> = Vec::new();
+    let mut indices: HashMap<String, usize> = HashMap::new();
+
+    // Version 9 moves the dependencies of the packages to the snapshots
+    let sections = ["packages", "snapshots"]
+        .map(|section| lock.get(section).and_then(serde_yaml::Value::as_mapping));
+    for (key, entry) in sections.into_iter().flatten().flatten() {
+        let Some((name, version)) = key.as_str().and_then(pnpm_package) else {
+            continue;
+        };
+        let index = *indices.entry(format!("{name}@{version}")).or_insert_with(|| {
+            let resolution = entry.get("resolution");
+            let source =
+                match resolution.and_then(|r| r.get("tarball")).and_then(serde_yaml::Value::as_str)
+                {
+                    Some(tarball) => registry_source(tarball),
+                    None => match resolution
+                        .and_then(|r| r.get("repo"))
+                        .and_then(serde_yaml::Value::as_str)
+                    {
+                        Some(repo) => format!("git+{repo}"),
+                        None => NPM_REGISTRY.to_string(),
+                    },
+                };
+            packages.push(LockedPackage {
+                name: name.to_string(),
+                version: version.to_string(),
+                source: Some(source),
+                license: None,
+                dependencies: Vec::new(),
+            });
+            packages.len() - 1
+        });
+        for section in ["dependencies", "optionalDependencies"] {
+            let declared = entry.get(section).and_then(serde_yaml::Value::as_mapping);
+            packages[index]
+                .dependencies
+                .extend(declared.into_iter().flat_map(|d| d.keys().filter_map(yaml_text)));
+        }
+    }
+    Ok(packages)
+}
+
+/// Split the key of a pnpm package into its name and version, eg. `/@scope/name/1.0.0_peer@2.0.0`
+/// of version 5, `/name@1.0.0(peer@2.0.0)` of version 6 or `name@1.0.0` of version 9
+fn pnpm_package(key: &str) -> Option<(&str, &str)> {
+    let key = key.strip_prefix('/').unwrap_or(key);
+    // Names of version 5 are followed by a slash, and may contain an @ in the peer suffix
+    let name = package_name(key)
+        .filter(|name| name.matches('/').count() == usize::from(name.starts_with('@')));
+    let (name, version) = match name {
+        Some(name) => (name, &key[name.len() + 1..]),
+        None => {
+            let slash = key.match_indices('/').nth(usize::from(key.starts_with('@')))?.0;
+            (&key[..slash], &key[slash + 1..])
+        }
+    };
+    let version = version.split(['(', '_']).next()?;
+    (!name.is_empty() && !version.is_empty()).then_some((name, version))
+}
+
+/// The name of the package of a descriptor, eg. `@scope/name` of `@scope/name@^1.0.0`
+fn package_name(descriptor: &str) -> Option<&str> {
+    // Scoped names start with @, which is not the separator
+    let separator = descriptor.get(1..)?.find('@')? + 1;
+    Some(&descriptor[..separator])
+}
+
+/// The source of a package resolved from a URL, the npm registry when the URL is one of
+/// its tarballs or of the Yarn mirror
+fn registry_source(resolved: &str) -> String {
+    match resolved.strip_prefix(YARN_REGISTRY) {
+        Some(path) => format!("{NPM_REGISTRY}{path}"),
+        None => resolved.to_string(),
+    }
+}
+
+/// The text of a YAML scalar, which unquoted versions may be parsed as numbers
+fn yaml_text(value: &serde_yaml::Value) -> Option<String> {
+    match value {
+        serde_yaml::Value::String(text) => Some(text.clone()),
+        serde_yaml::Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+/// Normalize the repository of a package.json manifest into an https URL, from a URL, a
+/// `{ "type": "git", "url": ... }` object or a shorthand, eg. `github:user/repo`
+pub fn repository_url(repository: &Value) -> Option<String> {
+    let url = match repository {
+        Value::String(url) => url.as_str(),
+        Value::Object(fields) => fields.get("url")?.as_str()?,
+        _ => return None,
+    };
+    let url = url.trim().strip_prefix("git+").unwrap_or(url.trim());
+
+    let url = if let Some((host, path)) =
+        ["github:", "gitlab:", "bitbucket:"].iter().find_map(|prefix| {
+            url.strip_prefix(prefix).map(|path| (prefix.trim_end_matches(':'), path))
+        }) {
+        let domain = match host {
+            "bitbucket" => "bitbucket.org".to_string(),
+            host => format!("{host}.com"),
+        };
+        format!("https://{domain}/{path}")
+    } else if let Some(rest) = url.strip_prefix("git@") {
+        // scp-like addresses, eg. `git@github.com:user/repo.git`
+        format!("https://{}", rest.replacen(':', "/", 1))
+    } else if let Some(rest) = ["git://", "ssh://git@", "ssh://", "http://", "https://"]
+        .iter()
+        .find_map(|scheme| url.strip_prefix(scheme))
+    {
+        format!("https://{rest}")
+    } else if url.split('/').count() == 2 && !url.contains(':') {
+        format!("https://github.com/{url}")
+    } else {
+        return None;
+    };
+
+    let url = url.split('#').next().unwrap_or_default().trim_end_matches('/');
+    Some(url.strip_suffix(".git").unwrap_or(url).to_string())
+}
@@ -0,0 +1,363 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
+
+use super::AnalyzedDependency;
+use crate::responses::dependency::{DependencyKind, Ecosystem};
+
+/// The Python Package Index, which the lock files do not name when packages resolve from it
+pub const PYPI_REGISTRY: &str = "https://pypi.org/simple/";
+
+/// The lock files and requirements, in the order they are preferred
+const LOCK_FILES: [&str; 3] = ["poetry.lock", "Pipfile.lock", "requirements.txt"];
+
+/// A package resolved by a lock file
+#[derive(Debug)]
+struct LockedPackage {
+    name: String,
+    version: String,
+    source: Option<String>,
+    /// The kind the lock file records, when it does not record the dependency graph
+    kind: Option<DependencyKind>,
+    /// The names of the packages it depends on
+    dependencies: Vec<String>,
+}
+
+/// Check if the directory holds a Python project
+pub fn is_project(dir: &Path) -> bool {
+    LOCK_FILES.iter().any(|name| dir.join(name).is_file())
+}
+
+/// List the packages locked by the poetry.lock or Pipfile.lock of a Python project, or
+/// pinned by its requirements.txt
+///
+/// Requirements not pinned to a single version are not resolved, and skipped. The
+/// licenses and repositories are left unknown.
+pub fn locked_dependencies(project_dir: &Path) -> Result<Vec<AnalyzedDependency>> {
+    let (packages, direct) = if let Ok(content) =
+        fs::read_to_string(project_dir.join("poetry.lock"))
+    {
+        let pyproject = fs::read_to_string(project_dir.join("pyproject.toml")).unwrap_or_default();
+        (parse_poetry_lock(&content)?, pyproject_dependencies(&pyproject.parse()?))
+    } else if let Ok(content) = fs::read_to_string(project_dir.join("Pipfile.lock")) {
+        let pipfile = fs::read_to_string(project_dir.join("Pipfile")).unwrap_or_default();
+        (parse_pipfile_lock(&content)?, pipfile_dependencies(&pipfile.parse()?))
+    } else if let Ok(content) = fs::read_to_string(project_dir.join("requirements.txt")) {
+        parse_requirements(&content)
+    } else {
+        return Err(anyhow!("Could not find poetry.lock, Pipfile.lock or requirements.txt file"));
+    };
+
+    let mut edges: HashMap<String, Vec<String>> = HashMap::new();
+    for package in &packages {
+        edges.entry(package.name.clone()).or_default().extend(package.dependencies.iter().cloned());
+    }
+    let (kinds, depths) = super::propagate(direct, &HashSet::new(), &edges);
+
+    Ok(packages
+        .into_iter()
+        .map(|package| {
+            // Packages the declared dependencies do not reach are transitive ones of a lock
+            // file not recording the dependency graph
+            let depth = depths.get(&package.name).copied().unwrap_or(2);
+            AnalyzedDependency {
+                ecosystem: Ecosystem::Pypi,
+                kind: kinds.get(&package.name).copied().or(package.kind).unwrap_or_default(),
+                direct: depth == 1,
+                depth,
+                name: package.name,
+                version: package.version,
+                source: package.source,
+                license: None,
+                repository: None,
+                dependencies: package.dependencies,
+            }
+        })
+        .collect())
+}
+
+/// Parse a poetry.lock file
+fn parse_poetry_lock(content: &str) -> Result<Vec<LockedPackage>> {
+    let lock: toml::Value = content.parse()?;
+    let entries = lock.get("package").and_then(|p| p.as_array());
+
+    Ok(entries
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.get("name")?.as_str()?;
+            let version = entry.get("version")?.as_str()?;
+            // Packages from another index or a repository record their source
+            let source = entry.get("source");
+            let url = source.and_then(|s| s.get("url")).and_then(|u| u.as_str());
+            let source = match source.and_then(|s| s.get("type")).and_then(|t| t.as_str()) {
+                None => Some(PYPI_REGISTRY.to_string()),
+                Some("git") => url.map(|url| format!("git+{url}")),
+                Some(_) => url.map(str::to_string),
+            };
+            let dependencies = entry.get("dependencies").and_then(|d| d.as_table());
+            Some(LockedPackage {
+                name: normalize(name),
+                version: version.to_string(),
+                source,
+                kind: None,
+                dependencies: dependencies
+                    .into_iter()
+                    .flat_map(|d| d.keys())
+                    .map(|d| normalize(d))
+                    .collect(),
+            })
+        })
+        .collect())
+}
+
+/// List the dependencies declared by a pyproject.toml manifest, with their kind, by
+/// Poetry or as standardized
+fn pyproject_dependencies(manifest: &toml::Value) -> Vec<(String, DependencyKind)> {
+    let mut dependencies = Vec::new();
+
+    let poetry = manifest.get("tool").and_then(|t| t.get("poetry"));
+    let groups = poetry.and_then(|p| p.get("group")).and_then(|g| g.as_table());
+    let sections = [
+        (poetry.and_then(|p| p.get("dependencies")), DependencyKind::Runtime),
+        (poetry.and_then(|p| p.get("dev-dependencies")), DependencyKind::Dev),
+    ]
+    .into_iter()
+    .chain(
+        groups
+            .into_iter()
+            .flat_map(|g| g.values())
+            .map(|g| (g.get("dependencies"), DependencyKind::Dev)),
+    );
+    for (section, kind) in sections {
+        for (name, spec) in section.and_then(|s| s.as_table()).into_iter().flatten() {
+            // The Python version is declared as a dependency
+            if name == "python" {
+                continue;
+            }
+            let optional = spec.get("optional").and_then(|o| o.as_bool()).unwrap_or(false);
+            let kind = if optional && kind == DependencyKind::Runtime {
+                DependencyKind::Optional
+            } else {
+                kind
+            };
+            dependencies.push((normalize(name), kind));
+        }
+    }
+
+    // Requirements of PEP 621, eg. `requests>=2.31`
+    let project = manifest.get("project");
+    let required = project.and_then(|p| p.get("dependencies")).and_then(|d| d.as_array());
+    for requirement in required.into_iter().flatten().filter_map(|r| r.as_str()) {
+        dependencies.push((normalize(requirement_name(requirement)), DependencyKind::Runtime));
+    }
+    let extras = project.and_then(|p| p.get("optional-dependencies")).and_then(|o| o.as_table());
+    for requirement in
+        extras.into_iter().flat_map(|e| e.values()).filter_map(|r| r.as_array()).flatten()
+    {
+        if let Some(requirement) = requirement.as_str() {
+            dependencies.push((normalize(requirement_name(requirement)), DependencyKind::Optional));
+        }
+    }
+    dependencies
+}
+
+/// Parse a Pipfile.lock file, which records the kind of the packages but not their
+/// dependencies
+fn parse_pipfile_lock(content: &str) -> Result<Vec<LockedPackage>> {
+    let lock: Value = serde_json::from_str(content)?;
+
+    // Packages name the index they resolve from
+    let sources = lock.get("_meta").and_then(|m| m.get("sources")).and_then(Value::as_array);
+    let indexes: HashMap<&str, &str> = sources
+        .into_iter()
+        .flatten()
+        .filter_map(|s| Some((s.get("name")?.as_str()?, s.get("url")?.as_str()?)))
+        .collect();
+
+    let mut packages = Vec::new();
+    for (section, kind) in [("default", DependencyKind::Runtime), ("develop", DependencyKind::Dev)]
+    {
+        for (name, entry) in lock.get(section).and_then(Value::as_object).into_iter().flatten() {
+            let source = match (
+                entry.get("git").and_then(Value::as_str),
+                entry.get("index").and_then(Value::as_str),
+            ) {
+                (Some(git), _) => Some(format!("git+{git}")),
+                (None, Some(index)) => indexes.get(index).map(|url| url.to_string()),
+                (None, None) => None,
+            };
+            let Some(version) =
+                entry.get("version").and_then(Value::as_str).and_then(pinned_version)
+            else {
+                continue;
+            };
+            packages.push(LockedPackage {
+                name: normalize(name),
+                version: version.to_string(),
+                source,
+                kind: Some(kind),
+                dependencies: Vec::new(),
+            });
+        }
+    }
+    Ok(packages)
+}
+
+/// List the dependencies declared by a Pipfile, with their kind
+fn pipfile_dependencies(manifest: &toml::Value) -> Vec<(String, DependencyKind)> {
+    [("packages", DependencyKind::Runtime), ("dev-packages", DependencyKind::Dev)]
+        .into_iter()
+        .flat_map(|(section, kind)| {
+            let declared = manifest.get(section).and_then(|s| s.as_table());
+            declared.into_iter().flat_map(|d| d.keys()).map(move |name| (normalize(name), kind))
+        })
+        .collect()
+}
+
+/// Parse a requirements.txt file, and the dependency graph its `# via` comments record
+/// when pip-compile wrote it
+///
+/// Without these comments, every requirement is a direct dependency.
+fn parse_requirements(content: &str) -> (Vec<LockedPackage>, Vec<(String, DependencyKind)>) {
+    let mut packages: Vec<LockedPackage> = Vec::new();
+    let mut direct = Vec::new();
+    let mut required_by: Vec<(String, String)> = Vec::new();
+    let mut has_via = false;
+    let mut in_via = false;
+    let mut index = PYPI_REGISTRY.to_string();
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+
+        // pip-compile lists who requires a package after it, eg. `# via requests`, or on
+        // the following comment lines after a bare `# via`
+        if let Some(comment) = trimmed.strip_prefix('#') {
+            let comment = comment.trim();
+            let via = match comment.strip_prefix("via") {
+                Some(via) if via.is_empty() || via.starts_with(' ') => {
+                    has_via = true;
+                    in_via = via.is_empty();
+                    via.trim()
+                }
+                _ if in_via => comment,
+                _ => continue,
+            };
+            let Some(package) = packages.last() else {
+                continue;
+            };
+            if via.starts_with("-r") || via.starts_with("-c") {
+                direct.push((package.name.clone(), DependencyKind::Runtime));
+            } else if !via.is_empty() {
+                required_by.push((
+                    normalize(via.split_whitespace().next().unwrap_or(via)),
+                    package.name.clone(),
+                ));
+            }
+            continue;
+        }
+        in_via = false;
+
+        let requirement = trimmed.split(" #").next().unwrap_or_default().trim();
+        if let Some(url) =
+            requirement.strip_prefix("--index-url").or_else(|| requirement.strip_prefix("-i"))
+        {
+            index = url.trim_start_matches([' ', '=']).to_string();
+            continue;
+        }
+        // Skip the empty lines, the options and the requirements of editable or local packages
+        if requirement.is_empty() || requirement.starts_with('-') || requirement.starts_with('.') {
+            continue;
+        }
+
+        // Drop the environment markers, eg. `; python_version < "3.11"`
+        let requirement = requirement.split(';').next().unwrap_or_default().trim();
+        let name = requirement_name(requirement);
+        let rest = requirement[name.len()..].trim_start();
+        let rest = match rest.strip_prefix('[') {
+            Some(extras) => {
+                extras.split_once(']').map(|(_, rest)| rest.trim_start()).unwrap_or_default()
+            }
+            None => rest,
+        };
+        // Direct references, eg. `name @ git+https://github.com/user/repo@v1.0`
+        let (version, source) = match rest.strip_prefix('@') {
+            Some(url) => {
+                (url.rsplit_once('@').map(|(_, reference)| reference), Some(url.trim().to_string()))
+            }
+            None => (pinned_version(rest), Some(index.clone())),
+        };
+        let Some(version) = version.filter(|_| !name.is_empty()) else {
+            continue;
+        };
+        packages.push(LockedPackage {
+            name: normalize(name),
+            version: version.trim().to_string(),
+            source,
+            kind: None,
+            dependencies: Vec::new(),
+        });
+    }
+
+    if !has_via {
+        direct = packages
+            .iter()
+            .map(|package| (package.name.clone(), DependencyKind::Runtime))
+            .collect();
+    }
+    for (parent, child) in required_by {
+        if let Some(package) = packages.iter_mut().find(|package| package.name == parent) {
+            package.dependencies.push(child);
+        }
+    }
+    (packages, direct)
+}
+
+/// The name of the package of a requirement, eg. `requests` of `requests[socks]>=2.31`
+fn requirement_name(requirement: &str) -> &str {
+    let end = requirement
+        .find(|c: char| !(c.is_ascii_alphanumeric() || "-_.".contains(c)))
+        .unwrap_or(requirement.len());
+    &requirement[..end]
+}
+
+/// The version a specifier pins, eg. `2.31.0` of `==2.31.0`
+fn pinned_version(specifier: &str) -> Option<&str> {
+    let version =
+        specifier.trim().strip_prefix("===").or_else(|| specifier.trim().strip_prefix("=="))?;
+    let version = version.trim();
+    (!version.is_empty() && !version.contains([',', '*'])).then_some(version)
+}
+
+/// Normalize the name of a package as PyPI does, eg. `Flask_SQLAlchemy` into
+/// `flask-sqlalchemy`
+pub fn normalize(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    for c in name.trim().chars() {
+        if "-_.".contains(c) {
+            if !normalized.ends_with('-') {
+                normalized.push('-');
+            }
+        } else {
+            normalized.push(c.to_ascii_lowercase());
+        }
+    }
+    normalized
+}
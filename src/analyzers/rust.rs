@@ -22,6 +22,12 @@ use std::{
 };
 use toml;
 
+use super::{is_skipped_dir, AnalyzedDependency};
+use crate::responses::dependency::{DependencyKind, Ecosystem};
+
+/// Files larger than this are generated or binary, and never analyzed
+const MAX_CODE_FILE_SIZE: u64 = 1024 * 1024;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CodeFile {
     pub file_path: String,
@@ -44,14 +50,32 @@ pub struct ProjectAnalysis {
 pub struct DependencyUsage {
     pub name: String,
     pub version: String,
+    pub kind: DependencyKind,
+    pub depth: u32,          // Depth in the dependency tree, 1 for direct dependencies
     pub used_lines: usize,   // Number of lines using this library
     pub percentage: f64,     // Percentage of total code
     pub import_count: usize, // Number of import statements (retaining original information)
+    /// The source of the package in the lock file, eg. its registry
+    pub source: Option<String>,
+}
+
+/// A package resolved by a lock file
+#[derive(Debug)]
+struct LockedPackage {
+    name: String,
+    version: String,
+    kind: DependencyKind,
+    depth: u32, // Depth in the dependency tree, 1 for direct dependencies
+    source: Option<String>,
+    /// The names of the packages it depends on
+    dependencies: Vec<String>,
 }
+
 /// Simplified dependency usage for API response
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LibraryUsage {
-    pub name: String,      // Library name
+    pub name: String, // Library name
+    pub kind: DependencyKind,
     pub used_lines: usize, // Number of lines using this library
     pub percentage: f64,   // Percentage of total code
 }
@@ -84,7 +108,7 @@ pub fn analyze_code(relative_path: &str) -> Result<ProjectAnalysis> {
 
     // Create usage records for each dependency
     let mut dependency_usage_map: HashMap<String, HashMap<String, HashSet<usize>>> = HashMap::new();
-    for (name, _) in &dependencies {
+    for LockedPackage { name, .. } in &dependencies {
         dependency_usage_map.insert(name.clone(), HashMap::new());
     }
 
@@ -93,10 +117,13 @@ pub fn analyze_code(relative_path: &str) -> Result<ProjectAnalysis> {
 
     // Traverse all files in directory
     visit_dirs(path, &mut |entry_path| {
-        // Skip directories and non-code files
+        // Skip directories, non-code files and generated files
         if entry_path.is_dir() || !is_code_file(entry_path) {
             return Ok(());
         }
+        if fs::metadata(entry_path).is_ok_and(|m| m.len() > MAX_CODE_FILE_SIZE) {
+            return Ok(());
+        }
 
         // Read file content
         let content = match fs::read_to_string(entry_path) {
@@ -132,7 +159,7 @@ pub fn analyze_code(relative_path: &str) -> Result<ProjectAnalysis> {
         total_use_statements += use_count;
 
         // Count usage for each dependency
-        for (name, _) in &dependencies {
+        for LockedPackage { name, .. } in &dependencies {
             // Identify lines using this dependency and store as set to avoid duplicate counting
             let used_lines = identify_dependency_usage_lines(name, &lines, &extension);
 
@@ -165,7 +192,7 @@ pub fn analyze_code(relative_path: &str) -> Result<ProjectAnalysis> {
 
     // Build dependency usage
     let mut dependency_usage = Vec::new();
-    for (name, version) in dependencies {
+    for LockedPackage { name, version, kind, depth, source, .. } in dependencies {
         // Calculate total unique lines using this dependency across all files
         let mut total_used_lines = 0;
         let mut import_count = 0;
@@ -198,25 +225,106 @@ pub fn analyze_code(relative_path: &str) -> Result<ProjectAnalysis> {
         dependency_usage.push(DependencyUsage {
             name,
             version,
+            kind,
+            depth,
             used_lines: total_used_lines,
             percentage,
             import_count,
+            source,
         });
     }
 
     // Sort by usage lines
-    dependency_usage.sort_by(|a, b| b.used_lines.cmp(&a.used_lines));
+    dependency_usage.sort_by_key(|dep| std::cmp::Reverse(dep.used_lines));
 
     Ok(ProjectAnalysis { files: code_files, dependency_usage, total_use_statements, project_type })
 }
 
-/// Recursively traverse directory
+/// Check if the directory holds a Rust workspace, whose Cargo.lock is at its root
+pub fn is_project(dir: &Path) -> bool {
+    dir.join("Cargo.lock").is_file()
+}
+
+/// List the packages locked by the Cargo.lock of a project, but its workspace members
+///
+/// The license and repository of a package are read from its manifest when its sources
+/// are vendored in the project or unpacked in the local Cargo registry, and left unknown
+/// otherwise.
+pub fn locked_dependencies(project_dir: &Path) -> Result<Vec<AnalyzedDependency>> {
+    let lock_path = find_cargo_lock(project_dir)?;
+    let root = lock_path.parent().unwrap_or(Path::new("."));
+    let registries = registry_sources();
+
+    Ok(parse_cargo_lock(&lock_path)?
+        .into_iter()
+        .filter(|package| package.source.is_some()) // Skip the workspace members
+        .map(|package| {
+            let manifest = package_manifest(root, &registries, &package.name, &package.version);
+            let field = |name: &str| {
+                let package = manifest.as_ref().and_then(|m| m.get("package"));
+                package.and_then(|p| p.get(name)).and_then(|v| v.as_str()).map(str::to_string)
+            };
+            AnalyzedDependency {
+                ecosystem: Ecosystem::Cargo,
+                license: field("license"),
+                repository: field("repository"),
+                direct: package.depth == 1,
+                name: package.name,
+                version: package.version,
+                source: package.source,
+                kind: package.kind,
+                depth: package.depth,
+                dependencies: package.dependencies,
+            }
+        })
+        .collect())
+}
+
+/// Find the manifest of a package, vendored in the project or unpacked in the local
+/// Cargo registry
+fn package_manifest(
+    root: &Path,
+    registries: &[PathBuf],
+    name: &str,
+    version: &str,
+) -> Option<toml::Value> {
+    let vendored =
+        [root.join("vendor").join(name), root.join("vendor").join(format!("{name}-{version}"))];
+    let unpacked = registries.iter().map(|registry| registry.join(format!("{name}-{version}")));
+    vendored.into_iter().chain(unpacked).find_map(|dir| {
+        let manifest = fs::read_to_string(dir.join("Cargo.toml")).ok()?;
+        let manifest: toml::Value = manifest.parse().ok()?;
+        // A vendored directory may hold another version of the package
+        let same_version = manifest
+            .get("package")
+            .and_then(|p| p.get("version"))
+            .is_some_and(|v| v.as_str() == Some(version));
+        same_version.then_some(manifest)
+    })
+}
+
+/// List the directories the local Cargo registries unpack the packages in
+fn registry_sources() -> Vec<PathBuf> {
+    let cargo_home = std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cargo")));
+    let Some(Ok(entries)) = cargo_home.map(|home| fs::read_dir(home.join("registry").join("src")))
+    else {
+        return Vec::new();
+    };
+    entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect()
+}
+
+/// Recursively traverse directory, skipping vendored and binary directories
 fn visit_dirs(dir: &Path, cb: &mut dyn FnMut(&Path) -> Result<()>) -> Result<()> {
     if dir.is_dir() {
         for entry in fs::read_dir(dir)? {
             let entry = entry?;
             let path = entry.path();
             if path.is_dir() {
+                if is_skipped_dir(&path) {
+                    continue;
+                }
                 visit_dirs(&path, cb)?;
             } else {
                 cb(&path)?;
@@ -244,10 +352,12 @@ fn find_cargo_lock(start_dir: &Path) -> Result<PathBuf> {
     Err(anyhow!("Could not find Cargo.lock file"))
 }
 
-/// Parse Cargo.lock file to get dependency names and versions
-fn parse_cargo_lock(lock_path: &Path) -> Result<Vec<(String, String)>> {
+/// Parse Cargo.lock file to get dependency names, versions, kinds and depths
+fn parse_cargo_lock(lock_path: &Path) -> Result<Vec<LockedPackage>> {
     let content = fs::read_to_string(lock_path)?;
     let mut packages = Vec::new();
+    let mut edges: HashMap<String, Vec<String>> = HashMap::new();
+    let mut local_packages = HashSet::new();
 
     // Use toml library to parse Cargo.lock file
     let lock_file: toml::Value = content.parse()?;
@@ -259,12 +369,110 @@ fn parse_cargo_lock(lock_path: &Path) -> Result<Vec<(String, String)>> {
                 package.get("name").and_then(|n| n.as_str()),
                 package.get("version").and_then(|v| v.as_str()),
             ) {
-                packages.push((name.to_string(), version.to_string()));
+                // Workspace members have no source
+                let source = package.get("source").and_then(|s| s.as_str());
+                if source.is_none() {
+                    local_packages.insert(name.to_string());
+                }
+                packages.push((name.to_string(), version.to_string(), source.map(str::to_string)));
+
+                // Dependencies are listed as "name", "name version" or "name version (source)"
+                let dependencies = package.get("dependencies").and_then(|d| d.as_array());
+                let names = dependencies.into_iter().flatten().filter_map(|d| d.as_str());
+                edges
+                    .entry(name.to_string())
+                    .or_default()
+                    .extend(names.filter_map(|d| d.split_whitespace().next()).map(str::to_string));
             }
         }
     }
 
-    Ok(packages)
+    let root = lock_path.parent().unwrap_or(Path::new("."));
+    let (kinds, depths) = classify_dependencies(root, &local_packages, &edges)?;
+
+    Ok(packages
+        .into_iter()
+        .map(|(name, version, source)| LockedPackage {
+            kind: kinds.get(&name).copied().unwrap_or_default(),
+            depth: depths.get(&name).copied().unwrap_or(1),
+            dependencies: edges.get(&name).cloned().unwrap_or_default(),
+            name,
+            version,
+            source,
+        })
+        .collect())
+}
+
+/// Classify the packages of a workspace by how its members depend on them, and find
+/// how deep in the dependency tree they are
+///
+/// The direct dependencies are classified by the section of the manifests declaring them,
+/// and the transitive ones inherit the kind of the packages depending on them. A package
+/// reached in several ways gets its most essential kind and its shortest depth.
+fn classify_dependencies(
+    root: &Path,
+    local_packages: &HashSet<String>,
+    edges: &HashMap<String, Vec<String>>,
+) -> Result<(HashMap<String, DependencyKind>, HashMap<String, u32>)> {
+    let mut direct = Vec::new();
+
+    visit_dirs(root, &mut |entry_path| {
+        if entry_path.file_name().and_then(|n| n.to_str()) != Some("Cargo.toml") {
+            return Ok(());
+        }
+        let Ok(manifest) = fs::read_to_string(entry_path) else {
+            return Ok(()); // Skip unreadable manifests
+        };
+        let Ok(manifest) = manifest.parse::<toml::Value>() else {
+            return Ok(()); // Skip invalid manifests
+        };
+
+        // Skip the manifests of vendored packages
+        let name = manifest.get("package").and_then(|p| p.get("name")).and_then(|n| n.as_str());
+        if !name.is_some_and(|name| local_packages.contains(name)) {
+            return Ok(());
+        }
+
+        direct.extend(manifest_dependencies(&manifest));
+        Ok(())
+    })?;
+
+    Ok(super::propagate(direct, local_packages, edges))
+}
+
+/// List the dependencies declared by a Cargo.toml manifest, with their kind
+fn manifest_dependencies(manifest: &toml::Value) -> Vec<(String, DependencyKind)> {
+    const SECTIONS: [(&str, DependencyKind); 3] = [
+        ("dependencies", DependencyKind::Runtime),
+        ("build-dependencies", DependencyKind::Build),
+        ("dev-dependencies", DependencyKind::Dev),
+    ];
+
+    // Dependencies are declared at the top level or per target, eg.
+    // [target.'cfg(unix)'.dependencies]
+    let targets = manifest.get("target").and_then(|t| t.as_table());
+    let tables = std::iter::once(manifest).chain(targets.into_iter().flat_map(|t| t.values()));
+
+    let mut dependencies = Vec::new();
+    for table in tables {
+        for (section, kind) in SECTIONS {
+            let Some(declared) = table.get(section).and_then(|s| s.as_table()) else {
+                continue;
+            };
+            for (key, spec) in declared {
+                // Renamed dependencies name the package separately
+                let name = spec.get("package").and_then(|p| p.as_str()).unwrap_or(key);
+                let optional = spec.get("optional").and_then(|o| o.as_bool()).unwrap_or(false);
+                let kind = if optional && kind == DependencyKind::Runtime {
+                    DependencyKind::Optional
+                } else {
+                    kind
+                };
+                dependencies.push((name.to_string(), kind));
+            }
+        }
+    }
+    dependencies
 }
 
 #[allow(dead_code)]
@@ -327,7 +535,7 @@ fn has_function_definitions(content: &str, extension: &str) -> bool {
 fn detect_project_and_dependencies(
     path: &Path,
     project_type: &mut String,
-) -> Result<Vec<(String, String)>> {
+) -> Result<Vec<LockedPackage>> {
     // Try to detect Rust project
     if let Ok(cargo_lock_path) = find_cargo_lock(path) {
         *project_type = "rust".to_string();
@@ -375,8 +583,8 @@ fn count_actual_code_lines(lines: &[&str], extension: &str) -> usize {
                 }
 
                 // Skip single-line comments, but count code+comment lines
-                if !(trimmed.starts_with("//") ||
-                    trimmed.starts_with("/*") && trimmed.contains("*/"))
+                if !(trimmed.starts_with("//")
+                    || trimmed.starts_with("/*") && trimmed.contains("*/"))
                 {
                     count += 1;
                 }
@@ -397,8 +605,8 @@ fn count_actual_code_lines(lines: &[&str], extension: &str) -> usize {
                 }
 
                 // Skip single-line comments
-                if !(trimmed.starts_with("//") ||
-                    trimmed.starts_with("/*") && trimmed.contains("*/"))
+                if !(trimmed.starts_with("//")
+                    || trimmed.starts_with("/*") && trimmed.contains("*/"))
                 {
                     count += 1;
                 }
@@ -608,6 +816,7 @@ pub fn to_library_usage(analysis: &ProjectAnalysis) -> Vec<LibraryUsage> {
         .iter()
         .map(|dep| LibraryUsage {
             name: dep.name.clone(),
+            kind: dep.kind,
             used_lines: dep.used_lines,
             percentage: dep.percentage,
         })
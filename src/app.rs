@@ -14,21 +14,93 @@
 
 use std::{net::SocketAddr, sync::Arc};
 
-use crate::{context::Context, routes, swagger};
+use axum::middleware;
+
+use crate::{context::Context, middlewares, routes, swagger, workers};
 
 pub async fn run(ctx: Arc<Context>) {
     let port = ctx.config.port;
 
+    // start the background workers
+    workers::execution::spawn(ctx.clone());
+    workers::recovery::spawn(ctx.clone());
+    workers::reconciliation::spawn(ctx.clone());
+    workers::ranking::spawn(ctx.clone());
+    workers::indexer::spawn(ctx.clone());
+    workers::orchestration::spawn(ctx.clone());
+    workers::webhook::spawn(ctx.clone());
+    workers::cleanup::spawn(ctx.clone());
+    workers::probe::spawn(ctx.clone());
+
+    // the public read-only API, rate limited more strictly than the authenticated one
+    let public = routes::public()
+        .merge(swagger::build())
+        .layer(middleware::from_fn_with_state(ctx.clone(), middlewares::usage::record))
+        .layer(middleware::from_fn_with_state(ctx.clone(), middlewares::ratelimit::limit_public));
+    let callbacks = routes::callbacks()
+        .layer(middleware::from_fn_with_state(ctx.clone(), middlewares::usage::record))
+        .layer(middleware::from_fn_with_state(ctx.clone(), middlewares::ratelimit::limit));
+    let authenticated = routes::authenticated()
+        .layer(middleware::from_fn_with_state(ctx.clone(), middlewares::usage::record))
+        .layer(middleware::from_fn_with_state(ctx.clone(), middlewares::auth::authenticate))
+        .layer(middleware::from_fn_with_state(ctx.clone(), middlewares::ratelimit::limit));
+
+    // the changes queued for the database are flushed once the server stops
+    let db = ctx.db.clone();
+
     // build our application with a route
-    let app = routes::build().merge(swagger::build()).with_state(ctx);
+    let app = public
+        .merge(callbacks)
+        .merge(authenticated)
+        .layer(middleware::from_fn(middlewares::etag::conditional))
+        .layer(middleware::from_fn(middlewares::cache::control))
+        .layer(middlewares::compression::layer(&ctx.config.compression_config))
+        .layer(middleware::from_fn(middlewares::trace::propagate))
+        .with_state(ctx);
 
     // run our app with hyper, and serve it over HTTP
     let addr = SocketAddr::from(([0, 0, 0, 0], port));
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
 
-    // Run this server for ... forever!
-    if let Err(err) = axum::serve(listener, app).await {
+    // Run this server until it is asked to stop
+    let app = app.into_make_service_with_connect_info::<SocketAddr>();
+    if let Err(err) = axum::serve(listener, app).with_graceful_shutdown(shutdown()).await {
         tracing::error!("Server error: {}", err);
         std::process::exit(1)
     }
+
+    if let Some(db) = db {
+        tracing::info!("Writing the changes still queued for the database");
+        db.flush().await;
+    }
+}
+
+/// Wait for Ctrl+C or, on Unix, SIGTERM.
+async fn shutdown() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl+C: {e}");
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {e}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    tracing::info!("Shutting down");
 }
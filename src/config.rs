@@ -28,7 +28,28 @@
 
 use std::path::PathBuf;
 
+#[cfg(all(feature = "evm", not(feature = "mock")))]
+use crate::contracts::impls::evm::EvmConfig;
+#[cfg(not(any(feature = "evm", feature = "mock")))]
 use crate::contracts::impls::starknet::StarknetConfig;
+use crate::{
+    db::DatabaseConfig,
+    middlewares::{compression::CompressionConfig, ratelimit::RateLimitConfig},
+    notifiers::email::EmailConfig,
+    services::{
+        address::AddressConfig, artifact::ArtifactConfig, batching::BatchingConfig,
+        breaker::BreakerConfig, claim::PaymasterConfig, contributor::AttributionConfig,
+        credential::CredentialConfig, distribution::DistributionConfig, github::GitHubAppConfig,
+        price::PriceConfig, quota::QuotaConfig, ranking::RankingConfig, storage::StorageConfig,
+        transaction::JournalConfig, treasury::TreasuryConfig,
+    },
+    stores::MetadataStoreConfig,
+    workers::{
+        cleanup::CleanupConfig, execution::ExecutionConfig, indexer::IndexerConfig,
+        orchestration::OrchestrationConfig, reconciliation::ReconciliationConfig,
+        recovery::RecoveryConfig, webhook::WebhookConfig,
+    },
+};
 
 #[derive(Clone, clap::Parser)]
 pub struct Config {
@@ -37,9 +58,27 @@ pub struct Config {
     pub port: u16,
 
     /// The Starknet configuration.
+    #[cfg(not(any(feature = "evm", feature = "mock")))]
     #[clap(flatten)]
     pub starknet_config: StarknetConfig,
 
+    /// The EVM configuration, replacing the Starknet one when built with the `evm` feature.
+    #[cfg(all(feature = "evm", not(feature = "mock")))]
+    #[clap(flatten)]
+    pub evm_config: EvmConfig,
+
+    /// The chain provider circuit breaker configuration.
+    #[clap(flatten)]
+    pub breaker_config: BreakerConfig,
+
+    /// The transaction journal configuration.
+    #[clap(flatten)]
+    pub journal_config: JournalConfig,
+
+    /// The database configuration.
+    #[clap(flatten)]
+    pub database_config: DatabaseConfig,
+
     /// Base directory for storing cached repositories
     #[clap(long, env = "CACHE_DIR")]
     pub cache_dir: PathBuf,
@@ -47,4 +86,108 @@ pub struct Config {
     /// A personal token to use for authentication.
     #[clap(long, env = "GITHUB_TOKEN")]
     pub github_token: Option<String>,
+
+    /// The repository download configuration.
+    #[clap(flatten)]
+    pub storage_config: StorageConfig,
+
+    /// The analysis artifact configuration.
+    #[clap(flatten)]
+    pub artifact_config: ArtifactConfig,
+
+    /// The API key granting access to the admin endpoints.
+    #[clap(long, env = "DRK_ADMIN_API_KEY")]
+    pub admin_api_key: Option<String>,
+
+    /// Comma separated API keys accepted by the authenticated API, any key if unset.
+    #[clap(long, env = "DRK_API_KEYS", value_delimiter = ',')]
+    pub api_keys: Vec<String>,
+
+    /// The response compression configuration.
+    #[clap(flatten)]
+    pub compression_config: CompressionConfig,
+
+    /// The rate limit configuration.
+    #[clap(flatten)]
+    pub rate_limit_config: RateLimitConfig,
+
+    /// The per-client quota configuration.
+    #[clap(flatten)]
+    pub quota_config: QuotaConfig,
+
+    /// The allocation execution configuration.
+    #[clap(flatten)]
+    pub execution_config: ExecutionConfig,
+
+    /// The transaction recovery configuration.
+    #[clap(flatten)]
+    pub recovery_config: RecoveryConfig,
+
+    /// The allocation reconciliation configuration.
+    #[clap(flatten)]
+    pub reconciliation_config: ReconciliationConfig,
+
+    /// The step indexer configuration.
+    #[clap(flatten)]
+    pub indexer_config: IndexerConfig,
+
+    /// The workflow orchestration configuration.
+    #[clap(flatten)]
+    pub orchestration_config: OrchestrationConfig,
+
+    /// The outbound webhook configuration.
+    #[clap(flatten)]
+    pub webhook_config: WebhookConfig,
+
+    /// The checkout cleanup configuration.
+    #[clap(flatten)]
+    pub cleanup_config: CleanupConfig,
+
+    /// The payout batching configuration.
+    #[clap(flatten)]
+    pub batching_config: BatchingConfig,
+
+    /// The contributor attribution configuration, of the deep analysis.
+    #[clap(flatten)]
+    pub attribution_config: AttributionConfig,
+
+    /// The global ranking configuration.
+    #[clap(flatten)]
+    pub ranking_config: RankingConfig,
+
+    /// The treasury pre-flight configuration.
+    #[clap(flatten)]
+    pub treasury_config: TreasuryConfig,
+
+    /// The budget distribution configuration.
+    #[clap(flatten)]
+    pub distribution_config: DistributionConfig,
+
+    /// The token price oracle configuration.
+    #[clap(flatten)]
+    pub price_config: PriceConfig,
+
+    /// The paymaster configuration, sponsoring claims.
+    #[clap(flatten)]
+    pub paymaster_config: PaymasterConfig,
+
+    /// The contributor address book configuration.
+    #[clap(flatten)]
+    pub address_config: AddressConfig,
+
+    /// The email notification configuration.
+    #[clap(flatten)]
+    pub email_config: EmailConfig,
+
+    /// The receipt metadata storage configuration.
+    #[clap(flatten)]
+    pub metadata_store_config: MetadataStoreConfig,
+
+    /// The GitHub App configuration.
+    #[clap(flatten)]
+    pub github_app_config: GitHubAppConfig,
+
+    /// The funding credential configuration.
+    #[clap(flatten)]
+    pub credential_config: CredentialConfig,
 }
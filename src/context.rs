@@ -12,7 +12,50 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::config::Config;
+use std::sync::Arc;
+
+use crate::{
+    config::Config,
+    contracts::transaction::TransactionContract,
+    db::Database,
+    middlewares::ratelimit::RateLimiter,
+    notifiers::{
+        email::EmailNotifier,
+        webhook::{WebhookChannel, WebhookNotifier},
+        Notifier,
+    },
+    services::{
+        address::AddressBook,
+        allocation::AllocationStore,
+        analyzer::AnalyzerService,
+        attestation::AttestationStore,
+        claim::Paymaster,
+        contract::ContractService,
+        contributor::AttributionStore,
+        credential::CredentialIssuer,
+        enrichment::EnrichmentStore,
+        event::EventBus,
+        github::GitHubApp,
+        identity::IdentityStore,
+        ledger::Ledger,
+        notification::NotificationStore,
+        organization::OrganizationStore,
+        policy::PolicyStore,
+        pool::BudgetPool,
+        price::PriceOracle,
+        profile::ProfileStore,
+        quota::QuotaTracker,
+        ranking::{DependencyGraph, RankingStore},
+        snapshot::SnapshotStore,
+        step::StepIndex,
+        storage::DownloadLimiter,
+        transaction::TransactionJournal,
+        usage::UsageTracker,
+        webhook::WebhookStore,
+        workflow::WorkflowStore,
+    },
+    stores::{self, MetadataStore},
+};
 
 /// The core type through which handler functions can access common API state.
 ///
@@ -25,10 +68,176 @@ use crate::config::Config;
 #[derive(Clone)]
 pub struct Context {
     pub config: Config,
+    /// The database the stores are written through to, if configured
+    pub db: Option<Database>,
+    pub rate_limiter: RateLimiter,
+    /// The stricter rate limiter of the public API
+    pub public_rate_limiter: RateLimiter,
+    pub usage: UsageTracker,
+    pub quotas: QuotaTracker,
+    pub contract: Arc<ContractService>,
+    pub allocations: AllocationStore,
+    pub prices: PriceOracle,
+    pub ledger: Ledger,
+    pub pool: BudgetPool,
+    pub paymaster: Paymaster,
+    pub attestations: AttestationStore,
+    pub addresses: AddressBook,
+    pub graph: DependencyGraph,
+    pub rankings: RankingStore,
+    pub profiles: ProfileStore,
+    pub snapshots: SnapshotStore,
+    pub workflows: WorkflowStore,
+    /// The steps recorded on chain, as indexed
+    pub steps: StepIndex,
+    pub policies: PolicyStore,
+    pub organizations: OrganizationStore,
+    pub enrichments: EnrichmentStore,
+    /// The contributor shares of the deeply analyzed projects
+    pub attributions: AttributionStore,
+    /// The emails and usernames merged into contributors
+    pub identities: IdentityStore,
+    /// The repository downloads in progress, shared by the storage services
+    pub downloads: DownloadLimiter,
+    /// Resolves the dependencies of the fetched repositories
+    pub analyzer: AnalyzerService,
+    /// The email channel, if configured
+    pub email: Option<Arc<dyn Notifier>>,
+    pub discord: Arc<dyn Notifier>,
+    pub slack: Arc<dyn Notifier>,
+    /// Where the metadata of the receipts is stored, if configured
+    pub metadata_store: Option<Arc<dyn MetadataStore>>,
+    pub notifications: NotificationStore,
+    /// The changes to workflows, sent to the WebSocket clients
+    pub events: EventBus,
+    /// The outbound webhooks, and their deliveries still to post
+    pub webhooks: WebhookStore,
+    /// The GitHub App, if configured
+    pub github_app: Option<GitHubApp>,
+    /// The issuer of the funding credentials, if configured
+    pub credentials: Option<CredentialIssuer>,
 }
 
 impl Context {
     pub async fn new(config: Config) -> anyhow::Result<Context> {
-        Ok(Context { config })
+        let journal = TransactionJournal::load(&config.journal_config)?;
+        let contract = Arc::new(ContractService::new(&config, journal));
+        // Transactions signed for another network would only be rejected once sent.
+        contract.check_chain_id().await?;
+        Self::with_contract(config, contract).await
+    }
+
+    /// Build the context around a contract service, eg. one wrapping the in-memory
+    /// contract.
+    pub async fn with_contract(
+        config: Config,
+        contract: Arc<ContractService>,
+    ) -> anyhow::Result<Context> {
+        let rate_limiter = RateLimiter::new(&config.rate_limit_config);
+        let public_rate_limiter = RateLimiter::public(&config.rate_limit_config);
+        let prices = PriceOracle::new(&config.price_config);
+        let paymaster = Paymaster::new(&config.paymaster_config);
+        let email = EmailNotifier::new(&config.email_config)?
+            .map(|notifier| Arc::new(notifier) as Arc<dyn Notifier>);
+        let metadata_store = stores::new(&config.metadata_store_config)?;
+        let github_app = GitHubApp::new(&config.github_app_config)?;
+        let credentials = CredentialIssuer::new(&config.credential_config)?;
+        let downloads = DownloadLimiter::new(&config.storage_config);
+        let analyzer = AnalyzerService::new(&config.cache_dir);
+        let db = Database::connect(&config.database_config).await?;
+        let events = EventBus::default();
+        let allocations = AllocationStore::load(db.as_ref(), events.clone()).await?;
+        let workflows = WorkflowStore::load(db.as_ref()).await?;
+        let webhooks = WebhookStore::load(db.as_ref()).await?;
+        let ledger = Ledger::load(db.as_ref()).await?;
+        let pool = BudgetPool::load(db.as_ref()).await?;
+        let addresses = AddressBook::load(db.as_ref()).await?;
+        let snapshots = SnapshotStore::load(db.as_ref()).await?;
+        let attestations = AttestationStore::load(db.as_ref()).await?;
+        let organizations = OrganizationStore::load(db.as_ref()).await?;
+        let steps = match &config.indexer_config.indexer_state_path {
+            Some(path) => StepIndex::load(path)?,
+            None => StepIndex::default(),
+        };
+
+        Ok(Context {
+            config,
+            db,
+            rate_limiter,
+            public_rate_limiter,
+            usage: UsageTracker::default(),
+            quotas: QuotaTracker::default(),
+            contract,
+            allocations,
+            prices,
+            ledger,
+            pool,
+            paymaster,
+            attestations,
+            addresses,
+            graph: DependencyGraph::default(),
+            rankings: RankingStore::default(),
+            profiles: ProfileStore::default(),
+            snapshots,
+            workflows,
+            steps,
+            policies: PolicyStore::default(),
+            organizations,
+            enrichments: EnrichmentStore::default(),
+            attributions: AttributionStore::default(),
+            identities: IdentityStore::default(),
+            downloads,
+            analyzer,
+            email,
+            discord: Arc::new(WebhookNotifier::new(WebhookChannel::Discord)),
+            slack: Arc::new(WebhookNotifier::new(WebhookChannel::Slack)),
+            metadata_store,
+            notifications: NotificationStore::default(),
+            events,
+            webhooks,
+            github_app,
+            credentials,
+        })
+    }
+}
+
+#[cfg(test)]
+impl Context {
+    /// A context without database, backed by the in-memory contract.
+    pub async fn for_tests() -> Context {
+        use clap::Parser;
+
+        use crate::contracts::impls::mock::MockContract;
+
+        let cache_dir = std::env::temp_dir().join(format!("deprank-{}", uuid::Uuid::new_v4()));
+        // The in-memory contract needs no settings of its own.
+        #[cfg_attr(feature = "mock", allow(unused_mut))]
+        let mut args =
+            vec!["deprank-server".to_string(), format!("--cache-dir={}", cache_dir.display())];
+        // The settings of the chain the in-memory contract stands in for
+        #[cfg(not(any(feature = "evm", feature = "mock")))]
+        args.extend(
+            [
+                "--starknet-rpc-url=http://localhost:5050",
+                "--starknet-account-address=0x1",
+                "--starknet-chain-id=SN_SEPOLIA",
+                "--starknet-private-key=0x1",
+            ]
+            .map(String::from),
+        );
+        #[cfg(all(feature = "evm", not(feature = "mock")))]
+        args.extend(
+            ["--evm-rpc-url=http://localhost:8545", "--evm-private-key=0x1"].map(String::from),
+        );
+        #[cfg(not(feature = "mock"))]
+        args.extend(
+            ["allocation", "inquire", "receipt", "sign", "workflow"]
+                .map(|contract| format!("--{contract}-contract-address=0x1")),
+        );
+        let config = Config::parse_from(args);
+        let journal = TransactionJournal::default();
+        let contract =
+            ContractService::with_instance(Box::new(MockContract::new()), &config, journal);
+        Self::with_contract(config, Arc::new(contract)).await.unwrap()
     }
 }
@@ -12,62 +12,143 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use anyhow::Result;
-use std::future::Future;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fmt;
 
-use super::types::{Address, Hash, Id, Number};
+use super::types::{Address, Hash, Id, Number, RawCall};
 
-#[allow(dead_code)]
+#[derive(Debug, Clone)]
 pub struct Allocation {
-    workflow_id: Id,
-    sign_id: Id,
-    recipient: Address,
-    amount: Number,
-    token_address: Address,
-    tx_hash: Hash,
-    created_at: u64,
-    status: Status,
+    pub workflow_id: Id,
+    pub sign_id: Id,
+    pub recipient: Address,
+    pub amount: Number,
+    pub token_address: Address,
+    pub tx_hash: Hash,
+    pub created_at: u64,
+    pub status: Status,
 }
 
+/// An allocation to create in a batch
+#[derive(Debug, Clone)]
+pub struct AllocationInput {
+    pub workflow_id: Id,
+    pub sign_id: Id,
+    pub recipient: Address,
+    pub amount: Number,
+    pub token_address: Address,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Status {
     Pending,
     Executed,
     Failed,
 }
 
-impl std::fmt::Display for Status {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Status {
+    /// The code of the status, as stored on chain
+    pub fn code(self) -> u64 {
         match self {
-            Self::Pending => write!(f, "0"),
-            Self::Executed => write!(f, "1"),
-            Self::Failed => write!(f, "2"),
+            Self::Pending => 0,
+            Self::Executed => 1,
+            Self::Failed => 2,
+        }
+    }
+
+    /// Parse the code of a status, as stored on chain
+    pub fn from_code(code: u64) -> Option<Self> {
+        match code {
+            0 => Some(Self::Pending),
+            1 => Some(Self::Executed),
+            2 => Some(Self::Failed),
+            _ => None,
         }
     }
 }
 
+impl TryFrom<u64> for Status {
+    type Error = anyhow::Error;
+
+    fn try_from(code: u64) -> Result<Self> {
+        Self::from_code(code).ok_or_else(|| anyhow!("Unknown allocation status `{code}`"))
+    }
+}
+
+impl TryFrom<&str> for Status {
+    type Error = anyhow::Error;
+
+    fn try_from(code: &str) -> Result<Self> {
+        code.parse::<u64>().map_err(|_| anyhow!("Unknown allocation status `{code}`"))?.try_into()
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Pending => "Pending",
+            Self::Executed => "Executed",
+            Self::Failed => "Failed",
+        })
+    }
+}
+
 /// Allocation Contract Interface
+#[async_trait]
 pub trait AllocationContract {
-    /// Create allocation record
-    fn create_allocation(
+    /// Create allocation record, returning its id unless it is only known once the
+    /// transaction is executed
+    async fn create_allocation(
         &self,
         workflow_id: Id,
         sign_id: Id,
         recipient: Address,
         amount: Number,
         token_address: Address,
-    ) -> impl Future<Output = Result<Id>>;
+    ) -> Result<Option<Id>>;
+
+    /// Create allocation records in a single transaction where the network allows it,
+    /// returning their ids in the order of `allocations`. An id is `None` when it is only
+    /// known once the transaction is executed, like the one of `create_allocation`
+    async fn create_allocations_batch(
+        &self,
+        allocations: Vec<AllocationInput>,
+    ) -> Result<Vec<Option<Id>>>;
 
     /// Update allocation status
-    fn update_allocation_status(
+    async fn update_allocation_status(&self, allocation_id: Id, status: Status) -> Result<bool>;
+
+    /// Approve the allocation contract to spend `amount` of the token and have it
+    /// transfer the allocation to its recipient, in a single transaction
+    async fn execute_allocation(
+        &self,
+        allocation_id: Id,
+        token_address: Address,
+        amount: Number,
+    ) -> Result<Hash>;
+
+    /// Build the calls of `execute_allocation`, to batch them with other payouts
+    fn execute_allocation_calls(
         &self,
         allocation_id: Id,
-        status: Status,
-    ) -> impl Future<Output = Result<bool>>;
+        token_address: Address,
+        amount: Number,
+    ) -> Result<Vec<RawCall>>;
+
+    /// Update the hash of the transaction which paid out the allocation
+    async fn update_allocation_tx_hash(&self, allocation_id: Id, tx_hash: Hash) -> Result<()>;
+
+    /// Build the call of `update_allocation_tx_hash`, to batch it with other updates
+    fn update_allocation_tx_hash_call(&self, allocation_id: Id, tx_hash: Hash) -> Result<RawCall>;
+
+    /// Build the call with which the recipient claims an allocation from their own account
+    fn claim_call(&self, allocation_id: Id) -> Result<RawCall>;
 
     /// Get allocation details
-    fn get_allocation_details(&self, allocation_id: Id)
-        -> impl Future<Output = Result<Allocation>>;
+    async fn get_allocation_details(&self, allocation_id: Id) -> Result<Allocation>;
 
     /// Get allocation ID by sign ID
-    fn get_allocation_by_sign(&self, sign_id: Id) -> impl Future<Output = Result<Id>>;
+    async fn get_allocation_by_sign(&self, sign_id: Id) -> Result<Id>;
 }
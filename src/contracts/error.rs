@@ -0,0 +1,71 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use thiserror::Error;
+
+use super::types::Hash;
+
+/// Why a contract call could not be made, or failed on chain.
+///
+/// The input variants are the caller's fault and are never worth retrying, unlike `RpcError`
+/// which is returned when the node could not be reached or answered with an unexpected error.
+#[derive(Debug, Error)]
+pub enum ContractError {
+    #[error("invalid address `{0}`")]
+    InvalidAddress(String),
+
+    #[error("invalid {kind} `{value}`")]
+    InvalidId { kind: &'static str, value: String },
+
+    #[error("invalid amount `{0}`")]
+    InvalidAmount(String),
+
+    #[error("invalid hash `{0}`")]
+    InvalidHash(String),
+
+    #[error("invalid owner `{0}`, it must be ASCII letters, digits and dashes")]
+    InvalidOwner(String),
+
+    #[error(
+        "{field} `{value}` cannot be encoded on chain, it must be at most 31 ASCII characters"
+    )]
+    EncodingTooLong { field: &'static str, value: String },
+
+    #[error("RPC error: {0}")]
+    RpcError(String),
+
+    #[error("transaction reverted: {0}")]
+    Reverted(String),
+
+    #[error("dry run, transaction {0} was simulated but not sent")]
+    DryRun(Hash),
+
+    #[error("transaction {tx_hash} may have been sent: {reason}")]
+    Ambiguous { tx_hash: Hash, reason: String },
+}
+
+impl ContractError {
+    /// Whether the error comes from the arguments of the call rather than the chain.
+    pub fn is_invalid_input(&self) -> bool {
+        !matches!(
+            self,
+            Self::RpcError(_) | Self::Reverted(_) | Self::DryRun(_) | Self::Ambiguous { .. }
+        )
+    }
+
+    /// Whether the error means the chain could not be reached.
+    pub fn is_unreachable(&self) -> bool {
+        matches!(self, Self::RpcError(_))
+    }
+}
@@ -0,0 +1,92 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Canonical serialization and hashing of the metadata documents written on chain.
+//!
+//! The hash of a document is stored in a single word of the network: the Keccak-256
+//! hash on EVM networks, and on Starknet the Starknet Keccak, Keccak-256 truncated to
+//! 250 bits so it fits a felt. Documents are hashed in canonical form, compact with
+//! the keys of their objects sorted, so the hash is recomputed from any copy of a
+//! document, whatever its formatting. The payloads signed by the accounts are hashed the
+//! same way.
+
+use serde_json::{json, Value};
+
+use super::{
+    inquire::Inquire,
+    types::{Hash, Id},
+};
+
+/// Serialize a JSON document in canonical form.
+pub fn canonical_json(document: &Value) -> String {
+    match document {
+        Value::Object(fields) => {
+            let mut fields: Vec<_> = fields.iter().collect();
+            fields.sort_by_key(|(a, _)| *a);
+            let fields: Vec<_> = fields
+                .into_iter()
+                .map(|(name, value)| {
+                    format!("{}:{}", Value::String(name.clone()), canonical_json(value))
+                })
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        Value::Array(items) => {
+            let items: Vec<_> = items.iter().map(canonical_json).collect();
+            format!("[{}]", items.join(","))
+        }
+        value => value.to_string(),
+    }
+}
+
+/// The hash of a document in canonical form, as stored on chain, hex encoded.
+#[cfg(feature = "evm")]
+pub fn metadata_hash(metadata_json: &str) -> Hash {
+    let hash = alloy::primitives::keccak256(metadata_json.as_bytes());
+    Hash::new(hash.to_string()).expect("a Keccak-256 hash is a valid hash")
+}
+
+/// The hash of a document in canonical form, as stored on chain, hex encoded.
+#[cfg(not(feature = "evm"))]
+pub fn metadata_hash(metadata_json: &str) -> Hash {
+    let hash = starknet::core::utils::starknet_keccak(metadata_json.as_bytes());
+    Hash::new(format!("{hash:#x}")).expect("a Starknet Keccak hash is a felt")
+}
+
+/// The hash of the payload of an inquire, which the signature of its answer is over.
+pub fn inquire_hash(inquire_id: &Id, inquire: &Inquire) -> Hash {
+    let payload = json!({
+        "inquire_id": inquire_id,
+        "workflow_id": inquire.workflow_id,
+        "inquirer": inquire.inquirer,
+        "inquiree": inquire.inquiree,
+        "question": inquire.question,
+        "response": inquire.response,
+    });
+    metadata_hash(&canonical_json(&payload))
+}
+
+/// The hash of a signature, recorded on chain in its place.
+pub fn signature_hash(signature: &[String]) -> Hash {
+    metadata_hash(&canonical_json(&json!(signature)))
+}
+
+/// Whether two hex encoded hashes are the same value, whatever their case and padding.
+pub fn same_hash(a: &str, b: &str) -> bool {
+    let digits = |hash: &str| {
+        let hash = hash.strip_prefix("0x").unwrap_or(hash);
+        hash.trim_start_matches('0').to_ascii_lowercase()
+    };
+    digits(a) == digits(b)
+}
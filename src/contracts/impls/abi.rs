@@ -0,0 +1,20 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Typed call builders and result decoders of the contracts, generated by `build.rs` from
+//! their ABIs in `abi/`.
+
+#![allow(dead_code, clippy::too_many_arguments)]
+
+include!(concat!(env!("OUT_DIR"), "/abi.rs"));
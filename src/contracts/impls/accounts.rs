@@ -0,0 +1,138 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A pool of Starknet accounts sending the transactions.
+//!
+//! An account sends one transaction at a time, since the nonce of a transaction is only
+//! known once the previous one was received by the node, so a single account bounds the
+//! write throughput. The accounts of the pool, all controlled by the key of the signer,
+//! share the transactions, each one sending its own in turn. The first account is the
+//! treasury, holding the tokens paid out, and sends the calls spending them.
+
+use std::{
+    ops::Deref,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use starknet::{
+    accounts::{Account, SingleOwnerAccount},
+    providers::jsonrpc::JsonRpcClient,
+};
+use tokio::sync::{Mutex, MutexGuard};
+use tracing::debug;
+
+use crate::contracts::impls::{pool::ProviderPool, signer::AccountSigner};
+
+/// A Starknet account signed by the signer of the backend.
+pub type StarknetAccount = SingleOwnerAccount<JsonRpcClient<ProviderPool>, AccountSigner>;
+
+/// How the transactions are shared between the accounts of the pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum AccountStrategy {
+    /// Each account in turn
+    RoundRobin,
+    /// The account with the fewest transactions being sent or waiting to be
+    LeastPending,
+}
+
+/// The accounts sending the transactions, the treasury first.
+pub struct AccountPool {
+    accounts: Vec<PooledAccount>,
+    strategy: AccountStrategy,
+    /// The account the next transaction goes to, in round-robin
+    next: AtomicUsize,
+}
+
+struct PooledAccount {
+    account: StarknetAccount,
+    /// The transactions being sent or waiting to be
+    pending: AtomicUsize,
+    /// Held while a transaction is sent, from the nonce being fetched to its reception
+    sending: Mutex<()>,
+}
+
+/// The exclusive use of an account of the pool, to send a single transaction.
+pub struct Lease<'a> {
+    account: &'a StarknetAccount,
+    _pending: Pending<'a>,
+    _sending: MutexGuard<'a, ()>,
+}
+
+/// Counts a transaction as pending on an account until dropped, cancelled while waiting
+/// for the account included.
+struct Pending<'a>(&'a AtomicUsize);
+
+impl AccountPool {
+    pub fn new(accounts: Vec<StarknetAccount>, strategy: AccountStrategy) -> Self {
+        assert!(!accounts.is_empty(), "At least one Starknet account is required");
+        Self {
+            accounts: accounts
+                .into_iter()
+                .map(|account| PooledAccount {
+                    account,
+                    pending: AtomicUsize::new(0),
+                    sending: Mutex::new(()),
+                })
+                .collect(),
+            strategy,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// The treasury account, paying out the tokens.
+    pub fn treasury(&self) -> &StarknetAccount {
+        &self.accounts[0].account
+    }
+
+    /// Wait for the treasury account to be free.
+    pub async fn lease_treasury(&self) -> Lease<'_> {
+        self.lease(0).await
+    }
+
+    /// Wait for the account picked by the strategy to be free.
+    pub async fn lease_any(&self) -> Lease<'_> {
+        let index = match self.strategy {
+            AccountStrategy::RoundRobin => {
+                self.next.fetch_add(1, Ordering::Relaxed) % self.accounts.len()
+            }
+            AccountStrategy::LeastPending => (0..self.accounts.len())
+                .min_by_key(|&i| self.accounts[i].pending.load(Ordering::Relaxed))
+                .unwrap_or_default(),
+        };
+        self.lease(index).await
+    }
+
+    async fn lease(&self, index: usize) -> Lease<'_> {
+        let account = &self.accounts[index];
+        account.pending.fetch_add(1, Ordering::Relaxed);
+        let pending = Pending(&account.pending);
+        let sending = account.sending.lock().await;
+        debug!(account = index, "Sending with account {:#x}", account.account.address());
+        Lease { account: &account.account, _pending: pending, _sending: sending }
+    }
+}
+
+impl Deref for Lease<'_> {
+    type Target = StarknetAccount;
+
+    fn deref(&self) -> &StarknetAccount {
+        self.account
+    }
+}
+
+impl Drop for Pending<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
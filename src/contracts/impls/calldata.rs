@@ -0,0 +1,214 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Construction of the calldata of the calls to the contracts.
+//!
+//! The values are parsed from their text form, as received by the API, and serialized in
+//! order with their Cairo encoding, so the calls built by hand and the ones generated from
+//! the ABIs encode their arguments the same way.
+
+use std::str::FromStr;
+
+use anyhow::Result;
+use num_bigint::BigUint;
+use starknet::core::{types::Felt, utils::cairo_short_string_to_felt};
+
+use crate::contracts::{error::ContractError, impls::codec::Encode};
+
+/// Builds the calldata of a call, one argument at a time.
+#[derive(Debug, Default, Clone)]
+pub struct CalldataBuilder {
+    calldata: Vec<Felt>,
+}
+
+impl CalldataBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Push any value with a Cairo encoding.
+    pub fn push<T: Encode + ?Sized>(mut self, value: &T) -> Result<Self> {
+        value.encode(&mut self.calldata)?;
+        Ok(self)
+    }
+
+    /// Push a felt as is.
+    pub fn push_felt(mut self, felt: Felt) -> Self {
+        self.calldata.push(felt);
+        self
+    }
+
+    /// Push an id, either decimal or hex encoded.
+    pub fn push_id(self, kind: &'static str, id: &str) -> Result<Self, ContractError> {
+        Ok(self.push_felt(parse_id(kind, id)?))
+    }
+
+    /// Push a hex encoded address.
+    pub fn push_address(self, address: &str) -> Result<Self, ContractError> {
+        Ok(self.push_felt(parse_address(address)?))
+    }
+
+    /// Push a hex encoded hash.
+    pub fn push_hash(self, hash: &str) -> Result<Self, ContractError> {
+        Ok(self.push_felt(parse_hash(hash)?))
+    }
+
+    /// Push a text as a Cairo short string, of at most 31 ASCII characters.
+    pub fn push_short_str(self, field: &'static str, text: &str) -> Result<Self, ContractError> {
+        Ok(self.push_felt(parse_text(field, text)?))
+    }
+
+    /// Push a decimal amount as a Cairo `u256`, its low then high 128 bits.
+    pub fn push_u256(mut self, amount: &str) -> Result<Self, ContractError> {
+        parse_amount(amount)?
+            .encode(&mut self.calldata)
+            .map_err(|_| ContractError::InvalidAmount(amount.to_owned()))?;
+        Ok(self)
+    }
+
+    /// The calldata pushed so far.
+    pub fn build(self) -> Vec<Felt> {
+        self.calldata
+    }
+}
+
+/// Parse a decimal amount, encoded as a Cairo `u256`.
+pub fn parse_amount(amount: &str) -> Result<BigUint, ContractError> {
+    amount.parse().map_err(|_| ContractError::InvalidAmount(amount.to_owned()))
+}
+
+/// Parse an id, either decimal or hex encoded.
+pub fn parse_id(kind: &'static str, id: &str) -> Result<Felt, ContractError> {
+    Felt::from_str(id).map_err(|_| ContractError::InvalidId { kind, value: id.to_owned() })
+}
+
+/// Parse a hex encoded address.
+pub fn parse_address(address: &str) -> Result<Felt, ContractError> {
+    Felt::from_hex(address).map_err(|_| ContractError::InvalidAddress(address.to_owned()))
+}
+
+/// Parse a hex encoded hash.
+pub fn parse_hash(hash: &str) -> Result<Felt, ContractError> {
+    Felt::from_hex(hash).map_err(|_| ContractError::InvalidHash(hash.to_owned()))
+}
+
+/// Encode a text as a Cairo short string, even when it reads as a number, so distinct texts
+/// never share a felt and every text decodes back as it was.
+pub fn parse_text(field: &'static str, text: &str) -> Result<Felt, ContractError> {
+    cairo_short_string_to_felt(text)
+        .map_err(|_| ContractError::EncodingTooLong { field, value: text.to_owned() })
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use starknet::core::utils::parse_cairo_short_string;
+
+    use super::*;
+    use crate::contracts::impls::codec::decode;
+
+    /// Any felt, below the field prime as its top bits are cleared.
+    fn felt() -> impl Strategy<Value = Felt> {
+        any::<[u8; 32]>().prop_map(|mut bytes| {
+            bytes[0] &= 0x03;
+            Felt::from_bytes_be(&bytes)
+        })
+    }
+
+    /// Any amount fitting a Cairo `u256`.
+    fn amount() -> impl Strategy<Value = BigUint> {
+        any::<[u8; 32]>().prop_map(|bytes| BigUint::from_bytes_be(&bytes))
+    }
+
+    proptest! {
+        #[test]
+        fn ids_round_trip(id in any::<u64>(), hex in any::<bool>()) {
+            let text = if hex { format!("{id:#x}") } else { id.to_string() };
+            let calldata = CalldataBuilder::new().push_id("id", &text)?.build();
+            prop_assert_eq!(decode::<u64>(&calldata).unwrap(), id);
+        }
+
+        #[test]
+        fn addresses_round_trip(address in felt()) {
+            let calldata = CalldataBuilder::new().push_address(&format!("{address:#x}"))?.build();
+            prop_assert_eq!(decode::<Felt>(&calldata).unwrap(), address);
+        }
+
+        #[test]
+        fn hashes_round_trip(hash in felt()) {
+            let calldata = CalldataBuilder::new().push_hash(&format!("{hash:#x}"))?.build();
+            prop_assert_eq!(decode::<Felt>(&calldata).unwrap(), hash);
+        }
+
+        #[test]
+        fn short_strings_round_trip(text in "[ -~]{0,31}") {
+            let calldata = CalldataBuilder::new().push_short_str("text", &text)?.build();
+            let felt = decode::<Felt>(&calldata).unwrap();
+            prop_assert_eq!(parse_cairo_short_string(&felt)?, text);
+        }
+
+        #[test]
+        fn numeric_texts_round_trip_as_text(number in any::<u64>(), hex in any::<bool>()) {
+            let text = if hex { format!("{number:#x}") } else { number.to_string() };
+            let felt = parse_text("text", &text)?;
+            prop_assert_eq!(parse_cairo_short_string(&felt)?, text);
+        }
+
+        #[test]
+        fn long_strings_are_rejected(text in "[ -~]{32,64}") {
+            let result = CalldataBuilder::new().push_short_str("text", &text);
+            prop_assert!(
+                matches!(result, Err(ContractError::EncodingTooLong { .. })),
+                "expected EncodingTooLong"
+            );
+        }
+
+        #[test]
+        fn amounts_round_trip(amount in amount()) {
+            let calldata = CalldataBuilder::new().push_u256(&amount.to_string())?.build();
+            prop_assert_eq!(calldata.len(), 2);
+            prop_assert_eq!(decode::<BigUint>(&calldata).unwrap(), amount);
+        }
+
+        #[test]
+        fn amounts_above_u256_are_rejected(amount in amount(), bits in 256u32..512) {
+            let amount = amount | (BigUint::from(1u8) << bits);
+            let result = CalldataBuilder::new().push_u256(&amount.to_string());
+            prop_assert!(
+                matches!(result, Err(ContractError::InvalidAmount(_))),
+                "expected InvalidAmount"
+            );
+        }
+
+        #[test]
+        fn values_round_trip(
+            items in proptest::collection::vec(any::<u128>(), 0..16),
+            flag in any::<bool>(),
+            number in any::<u32>(),
+        ) {
+            let calldata =
+                CalldataBuilder::new().push(&items).unwrap().push(&(flag, number)).unwrap().build();
+            prop_assert_eq!(calldata.len(), items.len() + 3);
+            let decoded = decode::<(Vec<u128>, bool, u32)>(&calldata).unwrap();
+            prop_assert_eq!(decoded, (items, flag, number));
+        }
+    }
+
+    #[test]
+    fn texts_reading_as_felts_do_not_collide() {
+        let text = parse_text("owner", "a").unwrap();
+        let hex = parse_text("owner", "0x61").unwrap();
+        assert_ne!(text, hex);
+    }
+}
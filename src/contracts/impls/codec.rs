@@ -0,0 +1,275 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Cairo serialization of the values passed to and returned by the contracts, used by the
+//! call builders and decoders generated from their ABIs.
+
+use anyhow::{anyhow, Result};
+use num_bigint::BigUint;
+use starknet::core::types::Felt;
+use std::slice::Iter;
+
+use crate::contracts::{
+    allocation::Status as AllocationStatus,
+    inquire::Status as InquireStatus,
+    workflow::{Status as WorkflowStatus, StepType},
+};
+
+/// A value which can be serialized into calldata.
+pub trait Encode {
+    fn encode(&self, calldata: &mut Vec<Felt>) -> Result<()>;
+}
+
+/// A value which can be deserialized from a call result, an event keys or data.
+pub trait Decode: Sized {
+    fn decode(result: &mut Iter<'_, Felt>) -> Result<Self>;
+}
+
+/// Decode a value from the start of a call result.
+pub fn decode<T: Decode>(result: &[Felt]) -> Result<T> {
+    T::decode(&mut result.iter())
+}
+
+impl Encode for Felt {
+    fn encode(&self, calldata: &mut Vec<Felt>) -> Result<()> {
+        calldata.push(*self);
+        Ok(())
+    }
+}
+
+impl Decode for Felt {
+    fn decode(result: &mut Iter<'_, Felt>) -> Result<Self> {
+        result.next().copied().ok_or_else(|| anyhow!("Unexpected end of result"))
+    }
+}
+
+macro_rules! impl_integer {
+    ($($ty:ty),+) => {
+        $(
+            impl Encode for $ty {
+                fn encode(&self, calldata: &mut Vec<Felt>) -> Result<()> {
+                    calldata.push(Felt::from(*self));
+                    Ok(())
+                }
+            }
+
+            impl Decode for $ty {
+                fn decode(result: &mut Iter<'_, Felt>) -> Result<Self> {
+                    let felt = Felt::decode(result)?;
+                    <$ty>::try_from(felt)
+                        .map_err(|_| anyhow!("Invalid {} {felt:#x}", stringify!($ty)))
+                }
+            }
+        )+
+    };
+}
+
+impl_integer!(u8, u16, u32, u64, u128);
+
+impl Encode for bool {
+    fn encode(&self, calldata: &mut Vec<Felt>) -> Result<()> {
+        calldata.push(if *self { Felt::ONE } else { Felt::ZERO });
+        Ok(())
+    }
+}
+
+impl Decode for bool {
+    fn decode(result: &mut Iter<'_, Felt>) -> Result<Self> {
+        match Felt::decode(result)? {
+            felt if felt == Felt::ZERO => Ok(false),
+            felt if felt == Felt::ONE => Ok(true),
+            felt => Err(anyhow!("Invalid bool {felt:#x}")),
+        }
+    }
+}
+
+/// A Cairo `u256`, serialized as its `low` and `high` 128 bits.
+impl Encode for BigUint {
+    fn encode(&self, calldata: &mut Vec<Felt>) -> Result<()> {
+        if self.bits() > 256 {
+            return Err(anyhow!("Amount `{self}` overflows u256"));
+        }
+        let mask = (BigUint::from(1u8) << 128) - 1u8;
+        let low = u128::try_from(self & &mask)?;
+        let high = u128::try_from(self >> 128)?;
+        calldata.extend([Felt::from(low), Felt::from(high)]);
+        Ok(())
+    }
+}
+
+impl Decode for BigUint {
+    fn decode(result: &mut Iter<'_, Felt>) -> Result<Self> {
+        let low = u128::decode(result)?;
+        let high = u128::decode(result)?;
+        Ok((BigUint::from(high) << 128u32) + low)
+    }
+}
+
+/// A Cairo `Array` or `Span`, serialized as its length followed by its items.
+impl<T: Encode> Encode for Vec<T> {
+    fn encode(&self, calldata: &mut Vec<Felt>) -> Result<()> {
+        calldata.push(Felt::from(self.len() as u64));
+        self.iter().try_for_each(|item| item.encode(calldata))
+    }
+}
+
+impl<T: Decode> Decode for Vec<T> {
+    fn decode(result: &mut Iter<'_, Felt>) -> Result<Self> {
+        let len = u32::decode(result)?;
+        (0..len).map(|_| T::decode(result)).collect()
+    }
+}
+
+macro_rules! impl_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: Encode),+> Encode for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn encode(&self, calldata: &mut Vec<Felt>) -> Result<()> {
+                let ($($name,)+) = self;
+                $($name.encode(calldata)?;)+
+                Ok(())
+            }
+        }
+
+        impl<$($name: Decode),+> Decode for ($($name,)+) {
+            fn decode(result: &mut Iter<'_, Felt>) -> Result<Self> {
+                Ok(($($name::decode(result)?,)+))
+            }
+        }
+    };
+}
+
+impl_tuple!(A, B);
+impl_tuple!(A, B, C);
+impl_tuple!(A, B, C, D);
+
+macro_rules! impl_code {
+    ($($ty:ty => $name:literal),+) => {
+        $(
+            impl From<$ty> for Felt {
+                fn from(value: $ty) -> Self {
+                    Felt::from(value.code())
+                }
+            }
+
+            impl TryFrom<Felt> for $ty {
+                type Error = anyhow::Error;
+
+                fn try_from(felt: Felt) -> Result<Self> {
+                    u64::try_from(felt)
+                        .map_err(|_| anyhow!("Unknown {} {felt:#x}", $name))?
+                        .try_into()
+                }
+            }
+
+            impl Encode for $ty {
+                fn encode(&self, calldata: &mut Vec<Felt>) -> Result<()> {
+                    calldata.push(Felt::from(*self));
+                    Ok(())
+                }
+            }
+
+            impl Decode for $ty {
+                fn decode(result: &mut Iter<'_, Felt>) -> Result<Self> {
+                    Felt::decode(result)?.try_into()
+                }
+            }
+        )+
+    };
+}
+
+impl_code!(
+    AllocationStatus => "allocation status",
+    InquireStatus => "inquire status",
+    WorkflowStatus => "workflow status",
+    StepType => "step type"
+);
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+
+    use super::*;
+
+    /// Encode every variant to its felt and its code, and decode them back.
+    fn round_trip<T>(variants: &[T])
+    where
+        T: Copy + PartialEq + Debug + Encode + Decode + TryFrom<Felt, Error = anyhow::Error>,
+        T: for<'a> TryFrom<&'a str, Error = anyhow::Error>,
+        Felt: From<T>,
+    {
+        for &variant in variants {
+            let felt = Felt::from(variant);
+            assert_eq!(T::try_from(felt).unwrap(), variant);
+
+            let mut calldata = vec![];
+            variant.encode(&mut calldata).unwrap();
+            assert_eq!(calldata, [felt]);
+            assert_eq!(decode::<T>(&calldata).unwrap(), variant);
+
+            let code = u64::try_from(felt).unwrap().to_string();
+            assert_eq!(T::try_from(code.as_str()).unwrap(), variant);
+        }
+    }
+
+    /// Reject the codes of no variant, as felts and as text.
+    fn rejects<T>(codes: &[u64])
+    where
+        T: Debug + Decode + TryFrom<Felt, Error = anyhow::Error>,
+        T: for<'a> TryFrom<&'a str, Error = anyhow::Error>,
+    {
+        for &code in codes {
+            assert!(T::try_from(Felt::from(code)).is_err(), "accepted {code}");
+            assert!(decode::<T>(&[Felt::from(code)]).is_err(), "decoded {code}");
+            assert!(T::try_from(code.to_string().as_str()).is_err(), "parsed {code}");
+        }
+        assert!(T::try_from(Felt::MAX).is_err());
+        assert!(decode::<T>(&[]).is_err());
+        for text in ["", "-1", "0x1", "Pending", "1.0"] {
+            assert!(T::try_from(text).is_err(), "parsed `{text}`");
+        }
+    }
+
+    #[test]
+    fn allocation_statuses_round_trip() {
+        round_trip(&[
+            AllocationStatus::Pending,
+            AllocationStatus::Executed,
+            AllocationStatus::Failed,
+        ]);
+        rejects::<AllocationStatus>(&[3, 4, 255, u64::MAX]);
+    }
+
+    #[test]
+    fn inquire_statuses_round_trip() {
+        round_trip(&[InquireStatus::Pending, InquireStatus::Responded, InquireStatus::Rejected]);
+        rejects::<InquireStatus>(&[3, 4, 255, u64::MAX]);
+    }
+
+    #[test]
+    fn workflow_statuses_round_trip() {
+        round_trip(&[
+            WorkflowStatus::Created,
+            WorkflowStatus::InProgress,
+            WorkflowStatus::Completed,
+        ]);
+        rejects::<WorkflowStatus>(&[3, 4, 255, u64::MAX]);
+    }
+
+    #[test]
+    fn step_types_round_trip() {
+        round_trip(&[StepType::Receipt, StepType::Inquire, StepType::Sign, StepType::Allocation]);
+        rejects::<StepType>(&[0, 5, 255, u64::MAX]);
+    }
+}
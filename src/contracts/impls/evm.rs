@@ -0,0 +1,1718 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloy::{
+    network::{EthereumWallet, TransactionBuilder},
+    primitives::{Address as EvmAddress, FixedBytes, B256, U256},
+    providers::{DynProvider, Provider, ProviderBuilder},
+    rpc::types::{Filter, Log, TransactionRequest},
+    signers::local::PrivateKeySigner,
+    sol,
+    sol_types::{SolCall, SolEvent},
+};
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use std::str::FromStr;
+use tracing::{debug, info, instrument, warn};
+use url::Url;
+
+use crate::{
+    contracts::{
+        allocation::{Allocation, AllocationContract, AllocationInput, Status as AllocationStatus},
+        error::ContractError,
+        inquire::{Inquire, InquireContract, Status as InquireStatus},
+        receipt::{Receipt, ReceiptContract, ReceiptMetadata},
+        sign::{Sign, SignContract},
+        token::{NativeToken, TokenContract},
+        transaction::{TransactionContract, TransactionStatus},
+        types::*,
+        workflow::{
+            Dependency, DependencyReceipt, EmittedWorkflowEvent, Status as WorkflowStatus, Step,
+            StepType, Workflow, WorkflowContract, WorkflowEvent, WorkflowEventPage,
+        },
+        Contract,
+    },
+    services::transaction::TransactionJournal,
+};
+
+/// The address conventionally standing for the native token, which has no contract.
+const NATIVE_TOKEN_ADDRESS: EvmAddress =
+    alloy::primitives::address!("0xEeeeeEeeeEeEeeEeEeEeeEEEeeeeEeeeeeeeEEeE");
+
+/// Decimals of the native token.
+const NATIVE_TOKEN_DECIMALS: u8 = 18;
+
+/// Returned by `isValidSignature` for a valid signature, per ERC-1271.
+const ERC1271_MAGIC_VALUE: FixedBytes<4> = alloy::primitives::fixed_bytes!("0x1626ba7e");
+
+/// Number of blocks whose events are fetched per page.
+const EVENTS_CHUNK_SIZE: u64 = 1000;
+
+sol! {
+    /// The Allocation contract, a Solidity port keeping the names of the Cairo one
+    interface IAllocation {
+        struct AllocationDetails {
+            uint256 workflow_id;
+            uint256 sign_id;
+            address recipient;
+            uint256 amount;
+            address token_address;
+            bytes32 tx_hash;
+            uint64 created_at;
+            uint8 status;
+        }
+
+        function create_allocation(uint256 workflow_id, uint256 sign_id, address recipient, uint256 amount, address token_address) external;
+        function update_allocation_status(uint256 allocation_id, uint8 status) external;
+        function execute_allocation(uint256 allocation_id) external;
+        function update_tx_hash(uint256 allocation_id, bytes32 tx_hash) external;
+        function claim(uint256 allocation_id) external;
+        function get_allocation_details(uint256 allocation_id) external view returns (AllocationDetails memory);
+        function get_allocation_by_sign(uint256 sign_id) external view returns (uint256);
+    }
+
+    /// The ERC-20 token standard
+    interface IErc20 {
+        function approve(address spender, uint256 amount) external returns (bool);
+        function transfer(address recipient, uint256 amount) external returns (bool);
+        function transferFrom(address sender, address recipient, uint256 amount) external returns (bool);
+        function balanceOf(address account) external view returns (uint256);
+        function allowance(address owner, address spender) external view returns (uint256);
+        function decimals() external view returns (uint8);
+    }
+
+    /// The signature verification of smart accounts
+    interface IErc1271 {
+        function isValidSignature(bytes32 hash, bytes signature) external view returns (bytes4);
+    }
+
+    /// The Inquire contract, a Solidity port keeping the names of the Cairo one
+    interface IInquire {
+        struct InquireDetails {
+            uint256 workflow_id;
+            address inquirer;
+            address inquiree;
+            string question;
+            string response;
+            uint8 status;
+            uint64 created_at;
+            uint64 responded_at;
+        }
+
+        function create_inquire(uint256 workflow_id, address inquirer, address inquiree, string question) external;
+        function respond_to_inquire(uint256 inquire_id, string response) external;
+        function reject_inquire(uint256 inquire_id) external;
+        function get_inquire_details(uint256 inquire_id) external view returns (InquireDetails memory);
+    }
+
+    /// The Receipt contract, a Solidity port keeping the names of the Cairo one
+    interface IReceipt {
+        struct ReceiptDetails {
+            uint256 workflow_id;
+            string dependency_url;
+            bytes32 tx_hash;
+            uint64 created_at;
+            bytes32 metadata_hash;
+            string metadata_uri;
+        }
+
+        struct ReceiptMetadata {
+            string name;
+            string version;
+            string author;
+            string license;
+        }
+
+        function create_receipt(uint256 workflow_id, string dependency_url, ReceiptMetadata metadata, bytes32 metadata_hash, string metadata_uri) external;
+        function get_receipt_details(uint256 receipt_id) external view returns (ReceiptDetails memory details, ReceiptMetadata memory metadata);
+        function verify_metadata(uint256 receipt_id, bytes32 provided_hash) external view returns (bool);
+        function update_tx_hash(uint256 receipt_id, bytes32 tx_hash) external;
+    }
+
+    /// The Sign contract, a Solidity port keeping the names of the Cairo one
+    interface ISign {
+        struct SignDetails {
+            uint256 workflow_id;
+            uint256 inquire_id;
+            address signer;
+            bytes32 signature_hash;
+            bytes32 tx_hash;
+            uint64 created_at;
+        }
+
+        function create_sign(uint256 workflow_id, uint256 inquire_id, address signer, bytes32 signature_hash) external;
+        function get_sign_details(uint256 sign_id) external view returns (SignDetails memory);
+        function get_sign_by_inquire(uint256 inquire_id) external view returns (uint256);
+    }
+
+    /// The Workflow contract, a Solidity port keeping the names of the Cairo one
+    #[allow(clippy::too_many_arguments)]
+    interface IWorkflow {
+        struct WorkflowDetails {
+            bytes32 owner;
+            address wallet_address;
+            uint8 status;
+            uint64 created_at;
+            uint64 last_updated_at;
+        }
+
+        struct DependencyDetails {
+            string name;
+            string repository_url;
+            string license;
+            string metadata_json;
+            uint8 status;
+            uint64 created_at;
+            uint64 last_updated_at;
+        }
+
+        struct StepDetails {
+            uint8 step_type;
+            bytes32 tx_hash;
+            uint256 related_entity_id;
+            uint64 timestamp;
+            uint256 prev_step_index;
+        }
+
+        event DependencyCreated(bytes32 indexed github_owner, uint256 indexed workflow_id, uint256 dependency_idx, string name, string repository_url);
+        event StepAdded(bytes32 indexed github_owner, uint256 indexed workflow_id, uint256 indexed dependency_idx, uint256 step_index, uint8 step_type, bytes32 tx_hash, uint256 related_entity_id, uint64 timestamp);
+
+        function create_workflow(bytes32 github_owner, address wallet_address) external;
+        function create_dependency(bytes32 github_owner, uint256 workflow_id, string name, string repository_url, string license, string metadata_json) external;
+        function add_step(bytes32 github_owner, uint256 workflow_id, uint256 dependency_idx, uint8 step_type, bytes32 tx_hash, uint256 related_entity_id) external;
+        function finish_dependency(bytes32 github_owner, uint256 workflow_id, uint256 dependency_idx) external;
+        function finish_workflow(bytes32 github_owner, uint256 workflow_id) external;
+        function get_workflow_status(bytes32 github_owner, uint256 workflow_id) external view returns (WorkflowDetails memory);
+        function get_dependencies(bytes32 github_owner, uint256 workflow_id) external view returns (DependencyDetails[] memory);
+        function get_dependencies_page(bytes32 github_owner, uint256 workflow_id, uint64 offset, uint64 limit) external view returns (DependencyDetails[] memory);
+        function get_steps(bytes32 github_owner, uint256 workflow_id, uint256 dependency_idx) external view returns (StepDetails[] memory);
+        function get_step_by_tx_hash(bytes32 tx_hash) external view returns (bytes32 github_owner, uint256 workflow_id, uint256 dependency_idx, uint256 step_index);
+        function get_complete_transaction_chain(bytes32 github_owner, uint256 workflow_id, uint256 dependency_idx) external view returns (bytes32[] memory);
+        function get_workflow_count(bytes32 github_owner) external view returns (uint256);
+        function get_all_workflows(bytes32 github_owner) external view returns (uint256[] memory ids, WorkflowDetails[] memory workflows);
+        function get_workflows_page(bytes32 github_owner, uint64 offset, uint64 limit) external view returns (uint256[] memory ids, WorkflowDetails[] memory workflows);
+        function bind_wallet_address(bytes32 github_owner, uint256 workflow_id, address wallet_address) external;
+        function unbind_wallet_address(bytes32 github_owner, uint256 workflow_id) external;
+        function change_wallet_address(bytes32 github_owner, uint256 workflow_id, address new_wallet_address) external;
+    }
+}
+
+#[derive(Clone, clap::Parser)]
+pub struct EvmConfig {
+    /// URL of the EVM JSON-RPC endpoint
+    #[clap(long, env = "EVM_RPC_URL")]
+    pub evm_rpc_url: String,
+
+    /// Private key of the EVM account
+    #[clap(long, env = "EVM_PRIVATE_KEY")]
+    pub evm_private_key: String,
+
+    /// Chain ID of the EVM network, eg. 11155111 for Ethereum Sepolia
+    #[clap(long, env = "EVM_CHAIN_ID")]
+    pub evm_chain_id: u64,
+
+    /// Base URL of the block explorer of the EVM network
+    #[clap(long, env = "EVM_EXPLORER_URL", default_value = "https://sepolia.etherscan.io")]
+    pub evm_explorer_url: String,
+
+    /// Address of the STRK token contract on the EVM network, on Ethereum mainnet by default
+    #[clap(
+        long,
+        env = "EVM_STRK_TOKEN_ADDRESS",
+        default_value = "0xCa14007Eff0dB1f8135f4C25B34De49AB0d42766"
+    )]
+    pub evm_strk_token_address: String,
+
+    /// Address of the Allocation contract
+    #[clap(long, env = "ALLOCATION_CONTRACT_ADDRESS")]
+    pub allocation_contract_address: String,
+
+    /// Address of the Inquire contract
+    #[clap(long, env = "INQUIRE_CONTRACT_ADDRESS")]
+    pub inquire_contract_address: String,
+
+    /// Address of the Receipt contract
+    #[clap(long, env = "RECEIPT_CONTRACT_ADDRESS")]
+    pub receipt_contract_address: String,
+
+    /// Address of the Sign contract
+    #[clap(long, env = "SIGN_CONTRACT_ADDRESS")]
+    pub sign_contract_address: String,
+
+    /// Address of the Workflow contract
+    #[clap(long, env = "WORKFLOW_CONTRACT_ADDRESS")]
+    pub workflow_contract_address: String,
+}
+
+/// EVM implementation of the Contract trait
+///
+/// This struct provides concrete implementations for all contract operations on
+/// Ethereum and its L2s, against Solidity ports of the Cairo contracts. Externally owned
+/// accounts cannot batch calls, so the calls of a transaction are sent one after the
+/// other, each one waiting for the previous one to be included.
+pub struct EvmContract {
+    /// JSON-RPC provider for the EVM network, signing with the account
+    provider: DynProvider,
+
+    /// Address of the account paying out allocations
+    account: EvmAddress,
+
+    /// Chain ID of the EVM network
+    chain_id: u64,
+
+    /// Base URL of the block explorer
+    explorer_url: String,
+
+    /// Address of the STRK token contract
+    strk_token_address: EvmAddress,
+
+    /// Address of the Allocation contract
+    allocation_contract_address: EvmAddress,
+
+    /// Address of the Inquire contract
+    inquire_contract_address: EvmAddress,
+
+    /// Address of the Receipt contract
+    receipt_contract_address: EvmAddress,
+
+    /// Address of the Sign contract
+    sign_contract_address: EvmAddress,
+
+    /// Address of the Workflow contract
+    workflow_contract_address: EvmAddress,
+
+    /// Journal of the sent transactions
+    journal: TransactionJournal,
+}
+
+impl EvmContract {
+    pub fn new(config: &EvmConfig, journal: TransactionJournal) -> Self {
+        let signer =
+            PrivateKeySigner::from_str(&config.evm_private_key).expect("Invalid EVM private key");
+        let account = signer.address();
+
+        // Create provider used to access to the EVM network, signing with the account.
+        let provider = ProviderBuilder::new()
+            .with_chain_id(config.evm_chain_id)
+            .wallet(EthereumWallet::from(signer))
+            .connect_http(Url::parse(&config.evm_rpc_url).expect("Invalid EVM RPC URL format"))
+            .erased();
+
+        let parse = |address: &str, what: &str| {
+            EvmAddress::from_str(address).unwrap_or_else(|_| panic!("Invalid {what} address"))
+        };
+
+        Self {
+            provider,
+            account,
+            chain_id: config.evm_chain_id,
+            explorer_url: config.evm_explorer_url.trim_end_matches('/').to_string(),
+            strk_token_address: parse(&config.evm_strk_token_address, "STRK token"),
+            allocation_contract_address: parse(
+                &config.allocation_contract_address,
+                "allocation contract",
+            ),
+            inquire_contract_address: parse(&config.inquire_contract_address, "inquire contract"),
+            receipt_contract_address: parse(&config.receipt_contract_address, "receipt contract"),
+            sign_contract_address: parse(&config.sign_contract_address, "sign contract"),
+            workflow_contract_address: parse(
+                &config.workflow_contract_address,
+                "workflow contract",
+            ),
+            journal,
+        }
+    }
+
+    /// Call contract function (read-only operation)
+    #[instrument(skip_all, fields(contract = %to, function = C::SIGNATURE))]
+    async fn call<C: SolCall>(&self, to: EvmAddress, call: C) -> Result<C::Return> {
+        let request = TransactionRequest::default().with_to(to).with_input(call.abi_encode());
+
+        let output = self
+            .provider
+            .call(request)
+            .await
+            .map_err(|e| anyhow!("Contract call failed: {:?}", e))?;
+
+        C::abi_decode_returns(&output)
+            .map_err(|e| anyhow!("Malformed {} result: {e}", C::SIGNATURE))
+    }
+
+    /// Send several calls, one transaction each, returning the hash of the last one
+    ///
+    /// Every transaction is recorded in the journal.
+    #[instrument(skip_all, fields(calls = calls.len()))]
+    async fn multicall(&self, calls: Vec<TransactionRequest>) -> Result<B256> {
+        let count = calls.len();
+        let mut tx_hash = None;
+        for (i, call) in calls.into_iter().enumerate() {
+            debug!("Execute transaction, to: {:?}, input: {:?}", call.to, call.input.input());
+
+            let journaled = [journaled(&call)];
+            let pending = match self.provider.send_transaction(call).await {
+                Ok(pending) => pending,
+                Err(e) => {
+                    let e = anyhow!("Failed to send transaction: {:?}", e);
+                    self.journal.failed(&journaled, &e);
+                    return Err(e);
+                }
+            };
+            let hash = *pending.tx_hash();
+            self.journal.submitted(&journaled, hash.to_string().parse()?);
+            info!("Transaction sent! Transaction hash: {hash}");
+
+            // The next calls may depend on this one, eg. a transfer on its approval.
+            if i + 1 < count {
+                let receipt = pending
+                    .get_receipt()
+                    .await
+                    .map_err(|e| anyhow!("Failed to get the receipt of {hash}: {:?}", e))?;
+                if !receipt.status() {
+                    bail!("Transaction {hash} reverted");
+                }
+            }
+            tx_hash = Some(hash);
+        }
+
+        tx_hash.ok_or_else(|| anyhow!("No call to execute"))
+    }
+}
+
+/// Build a transaction calling a contract function.
+fn transaction<C: SolCall>(to: EvmAddress, call: C) -> TransactionRequest {
+    TransactionRequest::default().with_to(to).with_input(call.abi_encode())
+}
+
+/// Encode a call with hex encoded fields, the ABI encoded arguments as a single item of
+/// the calldata.
+fn to_raw_call<C: SolCall>(to: EvmAddress, call: C) -> RawCall {
+    RawCall {
+        to: to_address(to),
+        selector: format!("0x{}", hex::encode(C::SELECTOR)),
+        calldata: vec![format!("0x{}", hex::encode(&call.abi_encode()[4..]))],
+    }
+}
+
+/// Encode a transaction as journaled, its input split into the selector and 32 bytes words.
+fn journaled(call: &TransactionRequest) -> RawCall {
+    let to = call.to.as_ref().and_then(|to| to.to()).copied().unwrap_or_default();
+    let input = call.input.input().map(|input| input.as_ref()).unwrap_or_default();
+    let (selector, arguments) = input.split_at(input.len().min(4));
+
+    RawCall {
+        to: to_address(to),
+        selector: format!("0x{}", hex::encode(selector)),
+        calldata: arguments.chunks(32).map(|word| format!("0x{}", hex::encode(word))).collect(),
+    }
+}
+
+/// An address as encoded in the calls and the values of the contract traits.
+fn to_address(address: EvmAddress) -> Address {
+    address.to_string().parse().expect("an EVM address is a valid address")
+}
+
+/// Decode a call with hex encoded fields.
+fn from_raw_call(call: RawCall) -> Result<TransactionRequest> {
+    let to = address(&call.to)?;
+    let mut input = hex::decode(call.selector.trim_start_matches("0x"))?;
+    for data in &call.calldata {
+        input.extend(hex::decode(data.trim_start_matches("0x"))?);
+    }
+
+    Ok(TransactionRequest::default().with_to(to).with_input(input))
+}
+
+/// Parse an address.
+fn address(address: &str) -> Result<EvmAddress, ContractError> {
+    EvmAddress::from_str(address).map_err(|_| ContractError::InvalidAddress(address.to_owned()))
+}
+
+/// Parse a decimal amount, a `uint256` in the smallest unit of its token.
+fn parse_amount(amount: &str) -> Result<U256, ContractError> {
+    U256::from_str_radix(amount, 10).map_err(|_| ContractError::InvalidAmount(amount.to_owned()))
+}
+
+/// Parse a decimal or hex number, like an id.
+fn number(number: &str) -> Result<U256, ContractError> {
+    U256::from_str(number)
+        .map_err(|_| ContractError::InvalidId { kind: "number", value: number.to_owned() })
+}
+
+/// Parse a 32 bytes word, like a hash, or a number stored as a word.
+fn word(word: &str) -> Result<B256, ContractError> {
+    B256::from_str(word)
+        .or_else(|_| U256::from_str(word).map(B256::from))
+        .map_err(|_| ContractError::InvalidId { kind: "word", value: word.to_owned() })
+}
+
+/// Encode a GitHub owner as a word, the number its characters encode like a Cairo short
+/// string, so owners are the same on every chain.
+fn owner_word(owner: &Owner) -> B256 {
+    B256::left_padding_from(owner.as_bytes())
+}
+
+/// Decode a GitHub owner encoded by `owner_word`.
+fn owner(word: B256) -> Result<Owner, ContractError> {
+    let bytes = word.iter().copied().skip_while(|byte| *byte == 0).collect();
+    String::from_utf8(bytes).map_err(|_| ContractError::InvalidOwner(word.to_string()))?.parse()
+}
+
+/// Decode an event of the Workflow contract, `None` for the events which are not indexed.
+fn decode_workflow_event(log: Log) -> Result<Option<EmittedWorkflowEvent>> {
+    let hex = |number: U256| format!("{number:#x}");
+    let topic = log.topics().first().copied().unwrap_or_default();
+
+    let decoded = if topic == IWorkflow::DependencyCreated::SIGNATURE_HASH {
+        let created = log
+            .log_decode::<IWorkflow::DependencyCreated>()
+            .map_err(|e| anyhow!("Malformed DependencyCreated event: {e}"))?
+            .inner
+            .data;
+        WorkflowEvent::DependencyCreated {
+            github_owner: owner(created.github_owner)?,
+            workflow_id: hex(created.workflow_id).parse()?,
+            dependency_idx: hex(created.dependency_idx).parse()?,
+            name: created.name,
+            repository_url: created.repository_url,
+        }
+    } else if topic == IWorkflow::StepAdded::SIGNATURE_HASH {
+        let added = log
+            .log_decode::<IWorkflow::StepAdded>()
+            .map_err(|e| anyhow!("Malformed StepAdded event: {e}"))?
+            .inner
+            .data;
+        WorkflowEvent::StepAdded {
+            github_owner: owner(added.github_owner)?,
+            workflow_id: hex(added.workflow_id).parse()?,
+            dependency_idx: hex(added.dependency_idx).parse()?,
+            step_index: hex(added.step_index).parse()?,
+            step_type: StepType::try_from(u64::from(added.step_type))?,
+            tx_hash: added.tx_hash.to_string().parse()?,
+            related_entity_id: hex(added.related_entity_id).parse()?,
+            timestamp: added.timestamp,
+        }
+    } else {
+        return Ok(None);
+    };
+
+    Ok(Some(EmittedWorkflowEvent {
+        event: decoded,
+        block_number: log.block_number.unwrap_or_default(),
+        transaction_hash: log.transaction_hash.unwrap_or_default().to_string().parse()?,
+    }))
+}
+
+fn to_workflow(details: IWorkflow::WorkflowDetails) -> Result<Workflow> {
+    Ok(Workflow {
+        owner: owner(details.owner)?,
+        wallet_address: details.wallet_address.to_string().parse()?,
+        status: WorkflowStatus::try_from(u64::from(details.status))?,
+        created_at: details.created_at,
+        last_updated_at: details.last_updated_at,
+    })
+}
+
+fn to_dependency(details: IWorkflow::DependencyDetails) -> Result<Dependency> {
+    Ok(Dependency {
+        name: details.name,
+        repository_url: details.repository_url,
+        license: details.license,
+        metadata_json: details.metadata_json,
+        status: WorkflowStatus::try_from(u64::from(details.status))?,
+        created_at: details.created_at,
+        last_updated_at: details.last_updated_at,
+    })
+}
+
+impl Contract for EvmContract {
+    fn chain(&self) -> &'static str {
+        "EVM"
+    }
+
+    fn chain_id(&self) -> String {
+        self.chain_id.to_string()
+    }
+
+    fn explorer_tx_url(&self, tx_hash: &str) -> String {
+        format!("{}/tx/{tx_hash}", self.explorer_url)
+    }
+
+    fn explorer_contract_url(&self, address: &str) -> String {
+        format!("{}/address/{address}", self.explorer_url)
+    }
+}
+
+#[async_trait]
+impl AllocationContract for EvmContract {
+    #[instrument(skip_all, fields(workflow_id = %workflow_id, sign_id = %sign_id))]
+    async fn create_allocation(
+        &self,
+        workflow_id: Id,
+        sign_id: Id,
+        recipient: Address,
+        amount: Number,
+        token_address: Address,
+    ) -> Result<Option<Id>> {
+        info!("Starting allocation creation");
+
+        let call = IAllocation::create_allocationCall {
+            workflow_id: number(&workflow_id)?,
+            sign_id: number(&sign_id)?,
+            recipient: address(&recipient)?,
+            amount: parse_amount(&amount)?,
+            token_address: address(&token_address)?,
+        };
+        let _ = self.multicall(vec![transaction(self.allocation_contract_address, call)]).await?;
+
+        Ok(None)
+    }
+
+    #[instrument(skip_all, fields(count = allocations.len()))]
+    async fn create_allocations_batch(
+        &self,
+        allocations: Vec<AllocationInput>,
+    ) -> Result<Vec<Option<Id>>> {
+        info!("Starting batch allocation creation");
+
+        let mut calls = Vec::with_capacity(allocations.len());
+        for allocation in &allocations {
+            let call = IAllocation::create_allocationCall {
+                workflow_id: number(&allocation.workflow_id)?,
+                sign_id: number(&allocation.sign_id)?,
+                recipient: address(&allocation.recipient)?,
+                amount: parse_amount(&allocation.amount)?,
+                token_address: address(&allocation.token_address)?,
+            };
+            calls.push(transaction(self.allocation_contract_address, call));
+        }
+        // There is no multicall contract: the allocations are created one transaction each.
+        let _ = self.multicall(calls).await?;
+
+        Ok(vec![None; allocations.len()])
+    }
+
+    #[instrument(skip_all, fields(allocation_id = %allocation_id))]
+    async fn update_allocation_status(
+        &self,
+        allocation_id: Id,
+        status: AllocationStatus,
+    ) -> Result<bool> {
+        info!("Starting update allocation status");
+
+        let call = IAllocation::update_allocation_statusCall {
+            allocation_id: number(&allocation_id)?,
+            status: status.code() as u8,
+        };
+        let _ = self.multicall(vec![transaction(self.allocation_contract_address, call)]).await?;
+
+        Ok(true)
+    }
+
+    #[instrument(skip_all, fields(allocation_id = %allocation_id, token = %token_address))]
+    async fn execute_allocation(
+        &self,
+        allocation_id: Id,
+        token_address: Address,
+        amount: Number,
+    ) -> Result<Hash> {
+        info!("Starting execute allocation");
+
+        let calls = self.execute_allocation_calls(allocation_id, token_address, amount)?;
+        self.execute_calls(calls).await
+    }
+
+    fn execute_allocation_calls(
+        &self,
+        allocation_id: Id,
+        token_address: Address,
+        amount: Number,
+    ) -> Result<Vec<RawCall>> {
+        let allocation_id = number(&allocation_id)?;
+        let token_address = address(&token_address)?;
+        if token_address == NATIVE_TOKEN_ADDRESS {
+            bail!("The native token cannot be approved, allocations are paid in ERC-20 tokens");
+        }
+
+        Ok(vec![
+            to_raw_call(
+                token_address,
+                IErc20::approveCall {
+                    spender: self.allocation_contract_address,
+                    amount: parse_amount(&amount)?,
+                },
+            ),
+            to_raw_call(
+                self.allocation_contract_address,
+                IAllocation::execute_allocationCall { allocation_id },
+            ),
+        ])
+    }
+
+    #[instrument(skip_all, fields(allocation_id = %allocation_id, tx_hash = %tx_hash))]
+    async fn update_allocation_tx_hash(&self, allocation_id: Id, tx_hash: Hash) -> Result<()> {
+        info!("Starting update allocation tx hash");
+
+        let call = self.update_allocation_tx_hash_call(allocation_id, tx_hash)?;
+        let _ = self.execute_calls(vec![call]).await?;
+
+        Ok(())
+    }
+
+    fn update_allocation_tx_hash_call(&self, allocation_id: Id, tx_hash: Hash) -> Result<RawCall> {
+        Ok(to_raw_call(
+            self.allocation_contract_address,
+            IAllocation::update_tx_hashCall {
+                allocation_id: number(&allocation_id)?,
+                tx_hash: word(&tx_hash)?,
+            },
+        ))
+    }
+
+    fn claim_call(&self, allocation_id: Id) -> Result<RawCall> {
+        Ok(to_raw_call(
+            self.allocation_contract_address,
+            IAllocation::claimCall { allocation_id: number(&allocation_id)? },
+        ))
+    }
+
+    #[instrument(skip_all, fields(allocation_id = %allocation_id))]
+    async fn get_allocation_details(&self, allocation_id: Id) -> Result<Allocation> {
+        info!("Starting get allocation details");
+
+        let details = self
+            .call(
+                self.allocation_contract_address,
+                IAllocation::get_allocation_detailsCall { allocation_id: number(&allocation_id)? },
+            )
+            .await?;
+
+        Ok(Allocation {
+            workflow_id: details.workflow_id.to_string().parse()?,
+            sign_id: details.sign_id.to_string().parse()?,
+            recipient: details.recipient.to_string().parse()?,
+            amount: details.amount.to_string(),
+            token_address: details.token_address.to_string().parse()?,
+            tx_hash: details.tx_hash.to_string().parse()?,
+            created_at: details.created_at,
+            status: AllocationStatus::try_from(u64::from(details.status))?,
+        })
+    }
+
+    #[instrument(skip_all, fields(sign_id = %sign_id))]
+    async fn get_allocation_by_sign(&self, sign_id: Id) -> Result<Id> {
+        info!("Starting get allocation by sign");
+
+        let allocation_id = self
+            .call(
+                self.allocation_contract_address,
+                IAllocation::get_allocation_by_signCall { sign_id: number(&sign_id)? },
+            )
+            .await?;
+
+        Ok(allocation_id.to_string().parse()?)
+    }
+}
+
+#[async_trait]
+impl TokenContract for EvmContract {
+    fn account_address(&self) -> Address {
+        to_address(self.account)
+    }
+
+    fn is_valid_address(&self, address: &Address) -> bool {
+        EvmAddress::from_str(address).is_ok_and(|address| !address.is_zero())
+    }
+
+    fn native_token_address(&self, token: NativeToken) -> Address {
+        match token {
+            NativeToken::Strk => to_address(self.strk_token_address),
+            NativeToken::Eth => to_address(NATIVE_TOKEN_ADDRESS),
+        }
+    }
+
+    fn fee_token(&self) -> NativeToken {
+        NativeToken::Eth
+    }
+
+    #[instrument(skip_all, fields(token = %token_address))]
+    async fn decimals(&self, token_address: Address) -> Result<u8> {
+        let token_address = address(&token_address)?;
+        if token_address == NATIVE_TOKEN_ADDRESS {
+            return Ok(NATIVE_TOKEN_DECIMALS);
+        }
+
+        self.call(token_address, IErc20::decimalsCall {}).await
+    }
+
+    #[instrument(skip_all, fields(token = %token_address, owner = %owner))]
+    async fn balance_of(&self, token_address: Address, owner: Address) -> Result<Number> {
+        info!("Starting get balance");
+
+        let token_address = address(&token_address)?;
+        let owner = address(&owner)?;
+
+        let balance = if token_address == NATIVE_TOKEN_ADDRESS {
+            self.provider
+                .get_balance(owner)
+                .await
+                .map_err(|e| anyhow!("Failed to get balance: {:?}", e))?
+        } else {
+            self.call(token_address, IErc20::balanceOfCall { account: owner }).await?
+        };
+
+        Ok(balance.to_string())
+    }
+
+    #[instrument(skip_all, fields(token = %token_address, owner = %owner, spender = %spender))]
+    async fn allowance(
+        &self,
+        token_address: Address,
+        owner: Address,
+        spender: Address,
+    ) -> Result<Number> {
+        let token_address = address(&token_address)?;
+        if token_address == NATIVE_TOKEN_ADDRESS {
+            bail!("Native tokens have no allowances");
+        }
+
+        let allowance = self
+            .call(
+                token_address,
+                IErc20::allowanceCall { owner: address(&owner)?, spender: address(&spender)? },
+            )
+            .await?;
+
+        Ok(allowance.to_string())
+    }
+
+    #[instrument(skip_all, fields(token = %token_address, recipient = %recipient, amount = %amount))]
+    async fn transfer(
+        &self,
+        token_address: Address,
+        recipient: Address,
+        amount: Number,
+    ) -> Result<Hash> {
+        info!("Starting token transfer");
+
+        if address(&token_address)? == NATIVE_TOKEN_ADDRESS {
+            let transfer = TransactionRequest::default()
+                .with_to(address(&recipient)?)
+                .with_value(parse_amount(&amount)?);
+            return Ok(self.multicall(vec![transfer]).await?.to_string().parse()?);
+        }
+
+        let call = self.transfer_call(token_address, recipient, amount)?;
+        self.execute_calls(vec![call]).await
+    }
+
+    fn transfer_call(
+        &self,
+        token_address: Address,
+        recipient: Address,
+        amount: Number,
+    ) -> Result<RawCall> {
+        let token_address = address(&token_address)?;
+        if token_address == NATIVE_TOKEN_ADDRESS {
+            bail!("Native token transfers carry a value, they cannot be batched as calls");
+        }
+
+        Ok(to_raw_call(
+            token_address,
+            IErc20::transferCall {
+                recipient: address(&recipient)?,
+                amount: parse_amount(&amount)?,
+            },
+        ))
+    }
+
+    fn transfer_from_call(
+        &self,
+        token_address: Address,
+        owner: Address,
+        recipient: Address,
+        amount: Number,
+    ) -> Result<RawCall> {
+        let token_address = address(&token_address)?;
+        if token_address == NATIVE_TOKEN_ADDRESS {
+            bail!("Native tokens cannot be transferred on behalf of another account");
+        }
+
+        Ok(to_raw_call(
+            token_address,
+            IErc20::transferFromCall {
+                sender: address(&owner)?,
+                recipient: address(&recipient)?,
+                amount: parse_amount(&amount)?,
+            },
+        ))
+    }
+}
+
+#[async_trait]
+impl TransactionContract for EvmContract {
+    #[instrument(skip_all, fields(tx_hash = %tx_hash))]
+    async fn transaction_status(&self, tx_hash: Hash) -> Result<TransactionStatus> {
+        let tx_hash = word(&tx_hash)?;
+
+        let receipt = self
+            .provider
+            .get_transaction_receipt(tx_hash)
+            .await
+            .map_err(|e| anyhow!("Failed to get transaction receipt: {:?}", e))?;
+        if let Some(receipt) = receipt {
+            return Ok(match receipt.status() {
+                true => TransactionStatus::Succeeded,
+                // Receipts do not keep the revert reason.
+                false => TransactionStatus::Reverted("execution reverted".to_string()),
+            });
+        }
+
+        let transaction = self
+            .provider
+            .get_transaction_by_hash(tx_hash)
+            .await
+            .map_err(|e| anyhow!("Failed to get transaction: {:?}", e))?;
+        Ok(match transaction {
+            Some(_) => TransactionStatus::Pending,
+            None => TransactionStatus::NotFound,
+        })
+    }
+
+    async fn execute_calls(&self, calls: Vec<RawCall>) -> Result<Hash> {
+        let calls = calls.into_iter().map(from_raw_call).collect::<Result<_>>()?;
+        let tx_hash = self.multicall(calls).await?;
+
+        Ok(tx_hash.to_string().parse()?)
+    }
+
+    /// Estimate every call on its own, so a call depending on a previous one, eg. on its
+    /// approval, fails to be estimated.
+    #[instrument(skip_all, fields(calls = calls.len()))]
+    async fn estimate_fee(&self, calls: Vec<RawCall>) -> Result<Number> {
+        let gas_price = self
+            .provider
+            .get_gas_price()
+            .await
+            .map_err(|e| anyhow!("Failed to get gas price: {:?}", e))?;
+
+        let mut gas = 0u64;
+        for call in calls {
+            let call = from_raw_call(call)?.with_from(self.account);
+            gas += self
+                .provider
+                .estimate_gas(call)
+                .await
+                .map_err(|e| anyhow!("Failed to estimate fee: {:?}", e))?;
+        }
+
+        Ok((U256::from(gas) * U256::from(gas_price)).to_string())
+    }
+
+    async fn block_number(&self) -> Result<u64> {
+        self.provider
+            .get_block_number()
+            .await
+            .map_err(|e| anyhow!("Failed to get block number: {:?}", e))
+    }
+
+    /// The endpoint is not checked when it cannot be reached, with a warning.
+    async fn check_chain_id(&self) -> Result<()> {
+        let chain_id = match self.provider.get_chain_id().await {
+            Ok(chain_id) => chain_id,
+            Err(e) => {
+                warn!("Failed to get the chain id of the RPC endpoint: {:?}", e);
+                return Ok(());
+            }
+        };
+        if chain_id != self.chain_id {
+            bail!("EVM_CHAIN_ID is {} but the RPC endpoint is on {chain_id}", self.chain_id);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl InquireContract for EvmContract {
+    #[instrument(skip_all, fields(workflow_id = %workflow_id))]
+    async fn create_inquire(
+        &self,
+        workflow_id: Id,
+        inquirer: Address,
+        inquiree: Address,
+        question: String,
+    ) -> Result<Option<Id>> {
+        info!("Starting inquire creation");
+
+        let call = IInquire::create_inquireCall {
+            workflow_id: number(&workflow_id)?,
+            inquirer: address(&inquirer)?,
+            inquiree: address(&inquiree)?,
+            question,
+        };
+        let _ = self.multicall(vec![transaction(self.inquire_contract_address, call)]).await?;
+
+        Ok(None)
+    }
+
+    #[instrument(skip_all, fields(inquire_id = %inquire_id))]
+    async fn respond_to_inquire(&self, inquire_id: Id, response: String) -> Result<bool> {
+        info!("Starting respond to inquire");
+
+        let call = IInquire::respond_to_inquireCall { inquire_id: number(&inquire_id)?, response };
+        let _ = self.multicall(vec![transaction(self.inquire_contract_address, call)]).await?;
+
+        Ok(true)
+    }
+
+    #[instrument(skip_all, fields(inquire_id = %inquire_id))]
+    async fn reject_inquire(&self, inquire_id: Id) -> Result<bool> {
+        info!("Starting reject inquire");
+
+        let call = IInquire::reject_inquireCall { inquire_id: number(&inquire_id)? };
+        let _ = self.multicall(vec![transaction(self.inquire_contract_address, call)]).await?;
+
+        Ok(true)
+    }
+
+    #[instrument(skip_all, fields(inquire_id = %inquire_id))]
+    async fn get_inquire_details(&self, inquire_id: Id) -> Result<Inquire> {
+        info!("Starting get inquire details");
+
+        let details = self
+            .call(
+                self.inquire_contract_address,
+                IInquire::get_inquire_detailsCall { inquire_id: number(&inquire_id)? },
+            )
+            .await?;
+
+        Ok(Inquire {
+            workflow_id: details.workflow_id.to_string().parse()?,
+            inquirer: details.inquirer.to_string().parse()?,
+            inquiree: details.inquiree.to_string().parse()?,
+            question: details.question,
+            response: details.response,
+            status: InquireStatus::try_from(u64::from(details.status))?,
+            created_at: details.created_at,
+            responded_at: details.responded_at,
+        })
+    }
+}
+
+#[async_trait]
+impl ReceiptContract for EvmContract {
+    #[instrument(skip_all, fields(workflow_id = %workflow_id))]
+    async fn create_receipt(
+        &self,
+        workflow_id: Id,
+        dependency_url: String,
+        metadata: ReceiptMetadata,
+        metadata_hash: Hash,
+        metadata_uri: String,
+    ) -> Result<Option<Id>> {
+        info!("Starting receipt creation");
+
+        let call = self.create_receipt_call(
+            workflow_id,
+            dependency_url,
+            metadata,
+            metadata_hash,
+            metadata_uri,
+        )?;
+        let _ = self.execute_calls(vec![call]).await?;
+
+        Ok(None)
+    }
+
+    fn create_receipt_call(
+        &self,
+        workflow_id: Id,
+        dependency_url: String,
+        metadata: ReceiptMetadata,
+        metadata_hash: Hash,
+        metadata_uri: String,
+    ) -> Result<RawCall> {
+        let ReceiptMetadata { name, version, author, license } = metadata;
+
+        Ok(to_raw_call(
+            self.receipt_contract_address,
+            IReceipt::create_receiptCall {
+                workflow_id: number(&workflow_id)?,
+                dependency_url,
+                metadata: IReceipt::ReceiptMetadata { name, version, author, license },
+                metadata_hash: word(&metadata_hash)?,
+                metadata_uri: metadata_uri.to_string(),
+            },
+        ))
+    }
+
+    #[instrument(skip_all, fields(receipt_id = %receipt_id))]
+    async fn get_receipt_details(&self, receipt_id: Id) -> Result<(Receipt, ReceiptMetadata)> {
+        info!("Starting get receipt details");
+
+        let IReceipt::get_receipt_detailsReturn { details, metadata } = self
+            .call(
+                self.receipt_contract_address,
+                IReceipt::get_receipt_detailsCall { receipt_id: number(&receipt_id)? },
+            )
+            .await?;
+
+        let receipt = Receipt {
+            workflow_id: details.workflow_id.to_string().parse()?,
+            dependency_url: details.dependency_url,
+            tx_hash: details.tx_hash.to_string().parse()?,
+            created_at: details.created_at,
+            metadata_hash: details.metadata_hash.to_string().parse()?,
+            metadata_uri: details.metadata_uri,
+        };
+        let IReceipt::ReceiptMetadata { name, version, author, license } = metadata;
+        Ok((receipt, ReceiptMetadata { name, version, author, license }))
+    }
+
+    #[instrument(skip_all, fields(receipt_id = %receipt_id))]
+    async fn verify_metadata(&self, receipt_id: Id, provided_hash: Hash) -> Result<bool> {
+        info!("Starting verify metadata");
+
+        self.call(
+            self.receipt_contract_address,
+            IReceipt::verify_metadataCall {
+                receipt_id: number(&receipt_id)?,
+                provided_hash: word(&provided_hash)?,
+            },
+        )
+        .await
+    }
+
+    #[instrument(skip_all, fields(receipt_id = %receipt_id))]
+    async fn update_tx_hash(&self, receipt_id: Id, tx_hash: Hash) -> Result<()> {
+        info!("Starting update tx hash");
+
+        let call = IReceipt::update_tx_hashCall {
+            receipt_id: number(&receipt_id)?,
+            tx_hash: word(&tx_hash)?,
+        };
+        let _ = self.multicall(vec![transaction(self.receipt_contract_address, call)]).await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SignContract for EvmContract {
+    #[instrument(skip_all, fields(workflow_id = %workflow_id, inquire_id = %inquire_id))]
+    async fn create_sign(
+        &self,
+        workflow_id: Id,
+        inquire_id: Id,
+        signer: Address,
+        signature_hash: Hash,
+    ) -> Result<Option<Id>> {
+        info!("Starting sign creation");
+
+        let call = ISign::create_signCall {
+            workflow_id: number(&workflow_id)?,
+            inquire_id: number(&inquire_id)?,
+            signer: address(&signer)?,
+            signature_hash: word(&signature_hash)?,
+        };
+        let _ = self.multicall(vec![transaction(self.sign_contract_address, call)]).await?;
+
+        Ok(None)
+    }
+
+    #[instrument(skip_all, fields(sign_id = %sign_id))]
+    async fn get_sign_details(&self, sign_id: Id) -> Result<Sign> {
+        info!("Starting get sign details");
+
+        let details = self
+            .call(
+                self.sign_contract_address,
+                ISign::get_sign_detailsCall { sign_id: number(&sign_id)? },
+            )
+            .await?;
+
+        Ok(Sign {
+            workflow_id: details.workflow_id.to_string().parse()?,
+            inquire_id: details.inquire_id.to_string().parse()?,
+            signer: details.signer.to_string().parse()?,
+            signature_hash: details.signature_hash.to_string().parse()?,
+            tx_hash: details.tx_hash.to_string().parse()?,
+            created_at: details.created_at,
+        })
+    }
+
+    #[instrument(skip_all, fields(inquire_id = %inquire_id))]
+    async fn get_sign_by_inquire(&self, inquire_id: Id) -> Result<Id> {
+        info!("Starting get sign by inquire");
+
+        let sign_id = self
+            .call(
+                self.sign_contract_address,
+                ISign::get_sign_by_inquireCall { inquire_id: number(&inquire_id)? },
+            )
+            .await?;
+
+        Ok(sign_id.to_string().parse()?)
+    }
+
+    /// An externally owned account signs the message hash with its key, a smart account
+    /// checks the signature per ERC-1271.
+    #[instrument(skip_all, fields(signer = %signer))]
+    async fn is_valid_signature(
+        &self,
+        signer: Address,
+        message_hash: Hash,
+        signature: Vec<String>,
+    ) -> Result<bool> {
+        info!("Starting signature verification");
+
+        let signer = address(&signer)?;
+        let message_hash = word(&message_hash)?;
+        let [signature] = signature.as_slice() else {
+            return Ok(false);
+        };
+        let Ok(signature) = hex::decode(signature.trim_start_matches("0x")) else {
+            return Ok(false);
+        };
+
+        let code = self
+            .provider
+            .get_code_at(signer)
+            .await
+            .map_err(|e| anyhow!("Failed to get the code of the signer: {:?}", e))?;
+        if code.is_empty() {
+            return Ok(alloy::primitives::Signature::from_raw(&signature)
+                .and_then(|signature| signature.recover_address_from_prehash(&message_hash))
+                .is_ok_and(|recovered| recovered == signer));
+        }
+
+        let call =
+            IErc1271::isValidSignatureCall { hash: message_hash, signature: signature.into() };
+        Ok(self.call(signer, call).await.is_ok_and(|magic| magic == ERC1271_MAGIC_VALUE))
+    }
+}
+
+#[async_trait]
+impl WorkflowContract for EvmContract {
+    #[instrument(skip_all, fields(owner = %github_owner))]
+    async fn create_workflow(
+        &self,
+        github_owner: Owner,
+        wallet_address: Address,
+    ) -> Result<Option<Id>> {
+        info!("Starting workflow creation");
+
+        let call = IWorkflow::create_workflowCall {
+            github_owner: owner_word(&github_owner),
+            wallet_address: address(&wallet_address)?,
+        };
+        let _ = self.multicall(vec![transaction(self.workflow_contract_address, call)]).await?;
+
+        Ok(None)
+    }
+
+    #[instrument(skip_all, fields(owner = %github_owner, workflow_id = %workflow_id))]
+    async fn create_dependency(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        name: String,
+        repository_url: String,
+        license: String,
+        metadata_json: String,
+    ) -> Result<Option<Id>> {
+        info!("Starting dependency creation");
+
+        let call = self.create_dependency_call(
+            github_owner,
+            workflow_id,
+            name,
+            repository_url,
+            license,
+            metadata_json,
+        )?;
+        let _ = self.execute_calls(vec![call]).await?;
+
+        Ok(None)
+    }
+
+    fn create_dependency_call(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        name: String,
+        repository_url: String,
+        license: String,
+        metadata_json: String,
+    ) -> Result<RawCall> {
+        Ok(to_raw_call(
+            self.workflow_contract_address,
+            IWorkflow::create_dependencyCall {
+                github_owner: owner_word(&github_owner),
+                workflow_id: number(&workflow_id)?,
+                name,
+                repository_url,
+                license,
+                metadata_json,
+            },
+        ))
+    }
+
+    #[instrument(
+        skip_all,
+        fields(owner = %github_owner, workflow_id = %workflow_id, dependency_idx = %dependency_idx)
+    )]
+    async fn add_step(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        dependency_idx: Id,
+        step_type: StepType,
+        tx_hash: Hash,
+        related_entity_id: Id,
+    ) -> Result<Option<Id>> {
+        info!("Starting add step");
+
+        let call = self.add_step_call(
+            github_owner,
+            workflow_id,
+            dependency_idx,
+            step_type,
+            tx_hash,
+            related_entity_id,
+        )?;
+        let _ = self.execute_calls(vec![call]).await?;
+
+        Ok(None)
+    }
+
+    fn add_step_call(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        dependency_idx: Id,
+        step_type: StepType,
+        tx_hash: Hash,
+        related_entity_id: Id,
+    ) -> Result<RawCall> {
+        Ok(to_raw_call(
+            self.workflow_contract_address,
+            IWorkflow::add_stepCall {
+                github_owner: owner_word(&github_owner),
+                workflow_id: number(&workflow_id)?,
+                dependency_idx: number(&dependency_idx)?,
+                step_type: step_type.code() as u8,
+                tx_hash: word(&tx_hash)?,
+                related_entity_id: number(&related_entity_id)?,
+            },
+        ))
+    }
+
+    /// The three calls are sent one after the other, see `multicall`, so the dependency
+    /// can be created without its receipt.
+    #[instrument(
+        skip_all,
+        fields(owner = %github_owner, workflow_id = %workflow_id, dependency_idx = %dependency_idx)
+    )]
+    async fn record_dependency_with_receipt(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        dependency_idx: Id,
+        record: DependencyReceipt,
+    ) -> Result<Hash> {
+        info!("Starting record dependency with receipt");
+
+        let calls = vec![
+            self.create_dependency_call(
+                github_owner.clone(),
+                workflow_id.clone(),
+                record.name,
+                record.repository_url,
+                record.license,
+                record.metadata_json,
+            )?,
+            self.create_receipt_call(
+                workflow_id.clone(),
+                record.dependency_url,
+                record.metadata,
+                record.metadata_hash.clone(),
+                record.metadata_uri,
+            )?,
+            self.add_step_call(
+                github_owner,
+                workflow_id,
+                dependency_idx,
+                StepType::Receipt,
+                Hash::zero(),
+                record.metadata_hash.into_inner().parse()?,
+            )?,
+        ];
+        self.execute_calls(calls).await
+    }
+
+    #[instrument(
+        skip_all,
+        fields(owner = %github_owner, workflow_id = %workflow_id, dependency_idx = %dependency_idx)
+    )]
+    async fn finish_dependency(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        dependency_idx: Id,
+    ) -> Result<bool> {
+        info!("Starting finish dependency");
+
+        let call = IWorkflow::finish_dependencyCall {
+            github_owner: owner_word(&github_owner),
+            workflow_id: number(&workflow_id)?,
+            dependency_idx: number(&dependency_idx)?,
+        };
+        let _ = self.multicall(vec![transaction(self.workflow_contract_address, call)]).await?;
+
+        Ok(true)
+    }
+
+    #[instrument(skip_all, fields(owner = %github_owner, workflow_id = %workflow_id))]
+    async fn finish_workflow(&self, github_owner: Owner, workflow_id: Id) -> Result<bool> {
+        info!("Starting finish workflow");
+
+        let call = IWorkflow::finish_workflowCall {
+            github_owner: owner_word(&github_owner),
+            workflow_id: number(&workflow_id)?,
+        };
+        let _ = self.multicall(vec![transaction(self.workflow_contract_address, call)]).await?;
+
+        Ok(true)
+    }
+
+    fn finish_workflow_call(&self, github_owner: Owner, workflow_id: Id) -> Result<RawCall> {
+        Ok(to_raw_call(
+            self.workflow_contract_address,
+            IWorkflow::finish_workflowCall {
+                github_owner: owner_word(&github_owner),
+                workflow_id: number(&workflow_id)?,
+            },
+        ))
+    }
+
+    #[instrument(skip_all, fields(owner = %github_owner, workflow_id = %workflow_id))]
+    async fn get_workflow_status(&self, github_owner: Owner, workflow_id: Id) -> Result<Workflow> {
+        info!("Starting get workflow status");
+
+        let details = self
+            .call(
+                self.workflow_contract_address,
+                IWorkflow::get_workflow_statusCall {
+                    github_owner: owner_word(&github_owner),
+                    workflow_id: number(&workflow_id)?,
+                },
+            )
+            .await?;
+
+        to_workflow(details)
+    }
+
+    #[instrument(skip_all, fields(owner = %github_owner, workflow_id = %workflow_id))]
+    async fn get_dependencies(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+    ) -> Result<Vec<Dependency>> {
+        info!("Starting get dependencies");
+
+        let dependencies = self
+            .call(
+                self.workflow_contract_address,
+                IWorkflow::get_dependenciesCall {
+                    github_owner: owner_word(&github_owner),
+                    workflow_id: number(&workflow_id)?,
+                },
+            )
+            .await?;
+
+        dependencies.into_iter().map(to_dependency).collect()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(owner = %github_owner, workflow_id = %workflow_id, offset, limit)
+    )]
+    async fn get_dependencies_page(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<Dependency>> {
+        info!("Starting get dependencies page");
+
+        let dependencies = self
+            .call(
+                self.workflow_contract_address,
+                IWorkflow::get_dependencies_pageCall {
+                    github_owner: owner_word(&github_owner),
+                    workflow_id: number(&workflow_id)?,
+                    offset,
+                    limit,
+                },
+            )
+            .await?;
+
+        dependencies.into_iter().map(to_dependency).collect()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(owner = %github_owner, workflow_id = %workflow_id, dependency_idx = %dependency_idx)
+    )]
+    async fn get_steps(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        dependency_idx: Id,
+    ) -> Result<Vec<Step>> {
+        info!("Starting get steps");
+
+        let steps = self
+            .call(
+                self.workflow_contract_address,
+                IWorkflow::get_stepsCall {
+                    github_owner: owner_word(&github_owner),
+                    workflow_id: number(&workflow_id)?,
+                    dependency_idx: number(&dependency_idx)?,
+                },
+            )
+            .await?;
+
+        steps
+            .into_iter()
+            .map(|details| {
+                Ok(Step {
+                    step_type: StepType::try_from(u64::from(details.step_type))?,
+                    tx_hash: details.tx_hash.to_string().parse()?,
+                    related_entity_id: details.related_entity_id.to_string().parse()?,
+                    timestamp: details.timestamp,
+                    prev_step_index: details.prev_step_index.to_string().parse()?,
+                })
+            })
+            .collect()
+    }
+
+    #[instrument(skip_all, fields(tx_hash = %tx_hash))]
+    async fn get_step_by_tx_hash(&self, tx_hash: Hash) -> Result<Option<(Owner, Id, Id, Id)>> {
+        info!("Starting get step by tx hash");
+
+        let step = self
+            .call(
+                self.workflow_contract_address,
+                IWorkflow::get_step_by_tx_hashCall { tx_hash: word(&tx_hash)? },
+            )
+            .await?;
+
+        // Unknown transactions map to the zero owner.
+        if step.github_owner.is_zero() {
+            return Ok(None);
+        }
+        Ok(Some((
+            owner(step.github_owner)?,
+            step.workflow_id.to_string().parse()?,
+            step.dependency_idx.to_string().parse()?,
+            step.step_index.to_string().parse()?,
+        )))
+    }
+
+    #[instrument(
+        skip_all,
+        fields(owner = %github_owner, workflow_id = %workflow_id, dependency_idx = %dependency_idx)
+    )]
+    async fn get_complete_transaction_chain(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        dependency_idx: Id,
+    ) -> Result<Vec<Hash>> {
+        info!("Starting get complete transaction chain");
+
+        let chain = self
+            .call(
+                self.workflow_contract_address,
+                IWorkflow::get_complete_transaction_chainCall {
+                    github_owner: owner_word(&github_owner),
+                    workflow_id: number(&workflow_id)?,
+                    dependency_idx: number(&dependency_idx)?,
+                },
+            )
+            .await?;
+
+        Ok(chain.iter().map(|tx_hash| tx_hash.to_string().parse()).collect::<Result<_, _>>()?)
+    }
+
+    /// Nodes do not paginate logs, so the pages are ranges of blocks instead, the
+    /// continuation token being the first block of the next page.
+    #[instrument(skip_all, fields(%from_block, %to_block))]
+    async fn get_events(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        continuation_token: Option<String>,
+    ) -> Result<WorkflowEventPage> {
+        let from_block = match continuation_token {
+            Some(token) => {
+                token.parse().map_err(|_| anyhow!("Invalid continuation token `{token}`"))?
+            }
+            None => from_block,
+        };
+        if from_block > to_block {
+            return Ok(WorkflowEventPage { events: Vec::new(), continuation_token: None });
+        }
+        let last_block = to_block.min(from_block.saturating_add(EVENTS_CHUNK_SIZE - 1));
+
+        let filter = Filter::new()
+            .address(self.workflow_contract_address)
+            .event_signature(vec![
+                IWorkflow::DependencyCreated::SIGNATURE_HASH,
+                IWorkflow::StepAdded::SIGNATURE_HASH,
+            ])
+            .from_block(from_block)
+            .to_block(last_block);
+        let logs = self
+            .provider
+            .get_logs(&filter)
+            .await
+            .map_err(|e| anyhow!("Failed to get events: {:?}", e))?;
+
+        let mut events = Vec::with_capacity(logs.len());
+        for log in logs {
+            if let Some(event) = decode_workflow_event(log)? {
+                events.push(event);
+            }
+        }
+        let continuation_token = (last_block < to_block).then(|| (last_block + 1).to_string());
+        Ok(WorkflowEventPage { events, continuation_token })
+    }
+
+    #[instrument(skip_all, fields(owner = %github_owner))]
+    async fn get_workflow_count(&self, github_owner: Owner) -> Result<Number> {
+        info!("Starting get workflow count");
+
+        let count = self
+            .call(
+                self.workflow_contract_address,
+                IWorkflow::get_workflow_countCall { github_owner: owner_word(&github_owner) },
+            )
+            .await?;
+
+        Ok(count.to_string())
+    }
+
+    #[instrument(skip_all, fields(owner = %github_owner))]
+    async fn get_all_workflows(&self, github_owner: Owner) -> Result<Vec<(Number, Workflow)>> {
+        info!("Starting get all workflows");
+
+        let IWorkflow::get_all_workflowsReturn { ids, workflows } = self
+            .call(
+                self.workflow_contract_address,
+                IWorkflow::get_all_workflowsCall { github_owner: owner_word(&github_owner) },
+            )
+            .await?;
+        if ids.len() != workflows.len() {
+            bail!("Got {} workflow ids for {} workflows", ids.len(), workflows.len());
+        }
+
+        ids.into_iter()
+            .zip(workflows)
+            .map(|(id, details)| Ok((id.to_string(), to_workflow(details)?)))
+            .collect()
+    }
+
+    #[instrument(skip_all, fields(owner = %github_owner, offset, limit))]
+    async fn get_workflows_page(
+        &self,
+        github_owner: Owner,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<(Number, Workflow)>> {
+        info!("Starting get workflows page");
+
+        let IWorkflow::get_workflows_pageReturn { ids, workflows } = self
+            .call(
+                self.workflow_contract_address,
+                IWorkflow::get_workflows_pageCall {
+                    github_owner: owner_word(&github_owner),
+                    offset,
+                    limit,
+                },
+            )
+            .await?;
+        if ids.len() != workflows.len() {
+            bail!("Got {} workflow ids for {} workflows", ids.len(), workflows.len());
+        }
+
+        ids.into_iter()
+            .zip(workflows)
+            .map(|(id, details)| Ok((id.to_string(), to_workflow(details)?)))
+            .collect()
+    }
+
+    #[instrument(skip_all, fields(owner = %github_owner, workflow_id = %workflow_id))]
+    async fn bind_wallet_address(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        wallet_address: Address,
+    ) -> Result<bool> {
+        info!("Starting bind wallet address");
+
+        let call = IWorkflow::bind_wallet_addressCall {
+            github_owner: owner_word(&github_owner),
+            workflow_id: number(&workflow_id)?,
+            wallet_address: address(&wallet_address)?,
+        };
+        let _ = self.multicall(vec![transaction(self.workflow_contract_address, call)]).await?;
+
+        Ok(true)
+    }
+
+    #[instrument(skip_all, fields(owner = %github_owner, workflow_id = %workflow_id))]
+    async fn unbind_wallet_address(&self, github_owner: Owner, workflow_id: Id) -> Result<bool> {
+        info!("Starting unbind wallet address");
+
+        let call = IWorkflow::unbind_wallet_addressCall {
+            github_owner: owner_word(&github_owner),
+            workflow_id: number(&workflow_id)?,
+        };
+        let _ = self.multicall(vec![transaction(self.workflow_contract_address, call)]).await?;
+
+        Ok(true)
+    }
+
+    #[instrument(skip_all, fields(owner = %github_owner, workflow_id = %workflow_id))]
+    async fn change_wallet_address(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        new_wallet_address: Address,
+    ) -> Result<bool> {
+        info!("Starting change wallet address");
+
+        let call = IWorkflow::change_wallet_addressCall {
+            github_owner: owner_word(&github_owner),
+            workflow_id: number(&workflow_id)?,
+            new_wallet_address: address(&new_wallet_address)?,
+        };
+        let _ = self.multicall(vec![transaction(self.workflow_contract_address, call)]).await?;
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,1322 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An in-memory implementation of the contract traits, for tests and local development.
+//!
+//! Every write is a transaction of its own, included right away in a new block, and ids,
+//! transaction hashes and blocks are sequential, so runs are reproducible. The calls built
+//! by the `*_call` methods name the function in their selector and carry their arguments as
+//! is, `execute_calls` interprets them and applies them atomically.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use num_bigint::BigUint;
+use tracing::{debug, instrument};
+
+use crate::{
+    contracts::{
+        allocation::{Allocation, AllocationContract, AllocationInput, Status as AllocationStatus},
+        error::ContractError,
+        inquire::{Inquire, InquireContract, Status as InquireStatus},
+        receipt::{Receipt, ReceiptContract, ReceiptMetadata},
+        sign::{Sign, SignContract},
+        token::{NativeToken, TokenContract},
+        transaction::{TransactionContract, TransactionStatus},
+        types::*,
+        workflow::{
+            Dependency, DependencyReceipt, EmittedWorkflowEvent, Status as WorkflowStatus, Step,
+            StepType, Workflow, WorkflowContract, WorkflowEvent, WorkflowEventPage,
+        },
+        Contract,
+    },
+    services::transaction::TransactionJournal,
+};
+
+/// Address of the account paying out allocations.
+const ACCOUNT_ADDRESS: &str = "0xacc0";
+
+/// Addresses of the contracts.
+const ALLOCATION_CONTRACT_ADDRESS: &str = "0xa1";
+const RECEIPT_CONTRACT_ADDRESS: &str = "0xa2";
+const WORKFLOW_CONTRACT_ADDRESS: &str = "0xa3";
+
+/// Addresses of the native tokens.
+const STRK_TOKEN_ADDRESS: &str = "0x5752b";
+const ETH_TOKEN_ADDRESS: &str = "0xe7";
+
+/// Decimals of every token.
+const TOKEN_DECIMALS: u8 = 18;
+
+/// Balance of the account in every token when the mock is created, 1M whole tokens.
+const ACCOUNT_BALANCE: u128 = 1_000_000_000_000_000_000_000_000;
+
+/// Fee of a call, 0.001 whole token.
+const FEE_PER_CALL: u128 = 1_000_000_000_000_000;
+
+/// Maximum number of events per page.
+const EVENTS_CHUNK_SIZE: usize = 100;
+
+/// A workflow, with its dependencies and their steps.
+#[derive(Clone)]
+struct WorkflowEntry {
+    workflow: Workflow,
+    dependencies: Vec<(Dependency, Vec<Step>)>,
+}
+
+/// The state of the chain.
+#[derive(Clone, Default)]
+struct State {
+    block_number: u64,
+    transactions: HashMap<Hash, TransactionStatus>,
+    allocations: Vec<Allocation>,
+    inquires: Vec<Inquire>,
+    receipts: Vec<(Receipt, ReceiptMetadata)>,
+    signs: Vec<Sign>,
+    workflows: BTreeMap<Owner, Vec<WorkflowEntry>>,
+    steps: HashMap<Hash, (Owner, Id, Id, Id)>,
+    events: Vec<EmittedWorkflowEvent>,
+    /// Balances by token and owner, both normalized
+    balances: HashMap<(Address, Address), BigUint>,
+}
+
+/// The transaction being applied, with the block it is included in.
+struct Transaction {
+    hash: Hash,
+    block_number: u64,
+    timestamp: u64,
+}
+
+impl Transaction {
+    /// The next transaction, alone in the next block.
+    fn next(state: &State) -> Self {
+        let block_number = state.block_number + 1;
+        Self {
+            hash: valid(format!("{block_number:#066x}")),
+            block_number,
+            timestamp: chrono::Utc::now().timestamp() as u64,
+        }
+    }
+}
+
+/// In-memory implementation of the Contract trait
+///
+/// Clones share the same state, so a test can keep a handle on the mock it hands over to
+/// the services. The calls executed through `execute_calls` are recorded in the journal.
+#[derive(Clone)]
+pub struct MockContract {
+    state: Arc<Mutex<State>>,
+    journal: TransactionJournal,
+}
+
+impl Default for MockContract {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockContract {
+    /// Create an empty chain, the account holding `ACCOUNT_BALANCE` of both native tokens.
+    pub fn new() -> Self {
+        Self::with_journal(TransactionJournal::default())
+    }
+
+    /// Create an empty chain recording its transactions in `journal`.
+    pub fn with_journal(journal: TransactionJournal) -> Self {
+        let mut state = State::default();
+        for token in [STRK_TOKEN_ADDRESS, ETH_TOKEN_ADDRESS] {
+            state.balances.insert((valid(token), valid(ACCOUNT_ADDRESS)), ACCOUNT_BALANCE.into());
+        }
+
+        Self { state: Arc::new(Mutex::new(state)), journal }
+    }
+
+    /// Credit an account with tokens, amount in the smallest unit of the token.
+    pub fn mint(&self, token_address: &str, owner: &str, amount: &str) -> Result<()> {
+        let key = (normalize(token_address)?, normalize(owner)?);
+        let amount = parse_amount(amount)?;
+
+        let mut state = self.state.lock().unwrap();
+        *state.balances.entry(key).or_default() += amount;
+        Ok(())
+    }
+
+    /// Apply a transaction to a copy of the state, committed only if it succeeds.
+    fn transact<T>(&self, apply: impl FnOnce(&mut State, &Transaction) -> Result<T>) -> Result<T> {
+        let mut state = self.state.lock().unwrap();
+
+        let tx = Transaction::next(&state);
+        let block_number = tx.block_number;
+
+        let mut next = state.clone();
+        let result = apply(&mut next, &tx)?;
+        next.block_number = block_number;
+        next.transactions.insert(tx.hash.clone(), TransactionStatus::Succeeded);
+        *state = next;
+
+        debug!("Transaction included, hash: {}, block: {block_number}", tx.hash);
+        Ok(result)
+    }
+
+    /// Read the state.
+    fn read<T>(&self, read: impl FnOnce(&State) -> Result<T>) -> Result<T> {
+        read(&self.state.lock().unwrap())
+    }
+}
+
+impl State {
+    fn allocation(&mut self, allocation_id: &str) -> Result<&mut Allocation> {
+        let index = index(allocation_id)?;
+        self.allocations
+            .get_mut(index)
+            .ok_or_else(|| anyhow!("Unknown allocation `{allocation_id}`"))
+    }
+
+    fn inquire(&mut self, inquire_id: &str) -> Result<&mut Inquire> {
+        let index = index(inquire_id)?;
+        self.inquires.get_mut(index).ok_or_else(|| anyhow!("Unknown inquire `{inquire_id}`"))
+    }
+
+    fn receipt(&mut self, receipt_id: &str) -> Result<&mut (Receipt, ReceiptMetadata)> {
+        let index = index(receipt_id)?;
+        self.receipts.get_mut(index).ok_or_else(|| anyhow!("Unknown receipt `{receipt_id}`"))
+    }
+
+    fn workflow(&mut self, github_owner: &str, workflow_id: &str) -> Result<&mut WorkflowEntry> {
+        let index = index(workflow_id)?;
+        self.workflows
+            .get_mut(github_owner)
+            .and_then(|workflows| workflows.get_mut(index))
+            .ok_or_else(|| anyhow!("Unknown workflow `{workflow_id}` of `{github_owner}`"))
+    }
+
+    fn dependency(
+        &mut self,
+        github_owner: &str,
+        workflow_id: &str,
+        dependency_idx: &str,
+    ) -> Result<&mut (Dependency, Vec<Step>)> {
+        let index = index(dependency_idx)?;
+        self.workflow(github_owner, workflow_id)?
+            .dependencies
+            .get_mut(index)
+            .ok_or_else(|| anyhow!("Unknown dependency `{dependency_idx}`"))
+    }
+
+    /// Move tokens between two accounts, failing if the sender cannot afford it.
+    fn move_tokens(&mut self, token: &str, from: &str, to: &str, amount: &str) -> Result<()> {
+        let token = normalize(token)?;
+        let from = (token.clone(), normalize(from)?);
+        let to = (token, normalize(to)?);
+        let amount = parse_amount(amount)?;
+
+        let balance = self.balances.entry(from).or_default();
+        if *balance < amount {
+            bail!("Insufficient balance, {balance} for a transfer of {amount}");
+        }
+        *balance -= &amount;
+        *self.balances.entry(to).or_default() += amount;
+        Ok(())
+    }
+
+    /// Pay out an allocation from the account to its recipient.
+    fn execute_allocation(&mut self, allocation_id: &str) -> Result<()> {
+        let allocation = self.allocation(allocation_id)?.clone();
+        if allocation.status != AllocationStatus::Pending {
+            bail!("Allocation `{allocation_id}` is not pending");
+        }
+
+        self.move_tokens(
+            &allocation.token_address,
+            ACCOUNT_ADDRESS,
+            &allocation.recipient,
+            &allocation.amount,
+        )?;
+        self.allocation(allocation_id)?.status = AllocationStatus::Executed;
+        Ok(())
+    }
+
+    fn create_receipt(
+        &mut self,
+        tx: &Transaction,
+        workflow_id: Id,
+        dependency_url: String,
+        metadata: ReceiptMetadata,
+        metadata_hash: Hash,
+        metadata_uri: String,
+    ) -> Id {
+        let receipt = Receipt {
+            workflow_id,
+            dependency_url,
+            tx_hash: tx.hash.clone(),
+            created_at: tx.timestamp,
+            metadata_hash,
+            metadata_uri,
+        };
+        self.receipts.push((receipt, metadata));
+        valid(self.receipts.len())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn create_dependency(
+        &mut self,
+        tx: &Transaction,
+        github_owner: Owner,
+        workflow_id: Id,
+        name: String,
+        repository_url: String,
+        license: String,
+        metadata_json: String,
+    ) -> Result<Id> {
+        let workflow = self.workflow(&github_owner, &workflow_id)?;
+        workflow.dependencies.push((
+            Dependency {
+                name: name.clone(),
+                repository_url: repository_url.clone(),
+                license,
+                metadata_json,
+                status: WorkflowStatus::Created,
+                created_at: tx.timestamp,
+                last_updated_at: tx.timestamp,
+            },
+            Vec::new(),
+        ));
+        let dependency_idx: Id = valid(workflow.dependencies.len() - 1);
+
+        self.emit(
+            tx,
+            WorkflowEvent::DependencyCreated {
+                github_owner,
+                workflow_id,
+                dependency_idx: dependency_idx.clone(),
+                name,
+                repository_url,
+            },
+        );
+        Ok(dependency_idx)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn add_step(
+        &mut self,
+        tx: &Transaction,
+        github_owner: Owner,
+        workflow_id: Id,
+        dependency_idx: Id,
+        step_type: StepType,
+        tx_hash: Hash,
+        related_entity_id: Id,
+    ) -> Result<Id> {
+        let workflow = self.workflow(&github_owner, &workflow_id)?;
+        workflow.workflow.status = WorkflowStatus::InProgress;
+        workflow.workflow.last_updated_at = tx.timestamp;
+
+        let (dependency, steps) = self.dependency(&github_owner, &workflow_id, &dependency_idx)?;
+        if dependency.status == WorkflowStatus::Completed {
+            bail!("Dependency `{dependency_idx}` is completed");
+        }
+        dependency.status = WorkflowStatus::InProgress;
+        dependency.last_updated_at = tx.timestamp;
+        steps.push(Step {
+            step_type,
+            tx_hash: tx_hash.clone(),
+            related_entity_id: related_entity_id.clone(),
+            timestamp: tx.timestamp,
+            prev_step_index: valid(steps.len().saturating_sub(1)),
+        });
+        let step_index: Id = valid(steps.len() - 1);
+
+        self.steps.insert(
+            tx_hash.clone(),
+            (github_owner.clone(), workflow_id.clone(), dependency_idx.clone(), step_index.clone()),
+        );
+        self.emit(
+            tx,
+            WorkflowEvent::StepAdded {
+                github_owner,
+                workflow_id,
+                dependency_idx,
+                step_index: step_index.clone(),
+                step_type,
+                tx_hash,
+                related_entity_id,
+                timestamp: tx.timestamp,
+            },
+        );
+        Ok(step_index)
+    }
+
+    fn finish_workflow(
+        &mut self,
+        tx: &Transaction,
+        github_owner: &str,
+        workflow_id: &str,
+    ) -> Result<()> {
+        let workflow = self.workflow(github_owner, workflow_id)?;
+        workflow.workflow.status = WorkflowStatus::Completed;
+        workflow.workflow.last_updated_at = tx.timestamp;
+        Ok(())
+    }
+
+    fn emit(&mut self, tx: &Transaction, event: WorkflowEvent) {
+        self.events.push(EmittedWorkflowEvent {
+            event,
+            block_number: tx.block_number,
+            transaction_hash: tx.hash.clone(),
+        });
+    }
+
+    /// Apply a call built by one of the `*_call` methods.
+    fn apply(&mut self, tx: &Transaction, call: RawCall) -> Result<()> {
+        let RawCall { to, selector, calldata } = call;
+        let arg = |i: usize| {
+            calldata.get(i).cloned().ok_or_else(|| anyhow!("Missing argument {i} of `{selector}`"))
+        };
+
+        match (normalize(&to)?.as_str(), selector.as_str()) {
+            (ALLOCATION_CONTRACT_ADDRESS, "execute_allocation" | "claim") => {
+                self.execute_allocation(&arg(0)?)
+            }
+            (ALLOCATION_CONTRACT_ADDRESS, "update_tx_hash") => {
+                self.allocation(&arg(0)?)?.tx_hash = arg(1)?.parse()?;
+                Ok(())
+            }
+            (RECEIPT_CONTRACT_ADDRESS, "create_receipt") => {
+                let metadata = ReceiptMetadata {
+                    name: arg(2)?,
+                    version: arg(3)?,
+                    author: arg(4)?,
+                    license: arg(5)?,
+                };
+                let (workflow_id, metadata_hash) = (arg(0)?.parse()?, arg(6)?.parse()?);
+                self.create_receipt(tx, workflow_id, arg(1)?, metadata, metadata_hash, arg(7)?);
+                Ok(())
+            }
+            (WORKFLOW_CONTRACT_ADDRESS, "create_dependency") => self
+                .create_dependency(
+                    tx,
+                    arg(0)?.parse()?,
+                    arg(1)?.parse()?,
+                    arg(2)?,
+                    arg(3)?,
+                    arg(4)?,
+                    arg(5)?,
+                )
+                .map(drop),
+            (WORKFLOW_CONTRACT_ADDRESS, "add_step") => {
+                let step_type = StepType::try_from(arg(3)?.as_str())?;
+                self.add_step(
+                    tx,
+                    arg(0)?.parse()?,
+                    arg(1)?.parse()?,
+                    arg(2)?.parse()?,
+                    step_type,
+                    arg(4)?.parse()?,
+                    arg(5)?.parse()?,
+                )
+                .map(drop)
+            }
+            (WORKFLOW_CONTRACT_ADDRESS, "finish_workflow") => {
+                self.finish_workflow(tx, &arg(0)?, &arg(1)?)
+            }
+            // Allocations are paid from the account, approvals are not tracked.
+            (_, "approve") => Ok(()),
+            (token, "transfer") => self.move_tokens(token, ACCOUNT_ADDRESS, &arg(0)?, &arg(1)?),
+            (token, "transfer_from") => self.move_tokens(token, &arg(0)?, &arg(1)?, &arg(2)?),
+            (to, selector) => bail!("Unknown function `{selector}` of `{to}`"),
+        }
+    }
+}
+
+/// The items of a page, at most `limit` from the `offset`-th one.
+fn page<T>(items: Vec<T>, offset: u64, limit: u64) -> Vec<T> {
+    let offset = usize::try_from(offset).unwrap_or(usize::MAX);
+    let limit = usize::try_from(limit).unwrap_or(usize::MAX);
+    items.into_iter().skip(offset).take(limit).collect()
+}
+
+/// Build a call, the selector being the name of the function.
+fn raw_call(to: &str, function: &str, calldata: Vec<String>) -> RawCall {
+    RawCall { to: valid(to), selector: function.to_string(), calldata }
+}
+
+/// A value of the mock chain, valid by construction.
+fn valid<T: FromStr<Err = ContractError>>(value: impl ToString) -> T {
+    value.to_string().parse().expect("invalid value of the mock chain")
+}
+
+/// Normalize a hex address, lowercase without leading zeros.
+fn normalize(address: &str) -> Result<Address, ContractError> {
+    let digits = address
+        .strip_prefix("0x")
+        .filter(|digits| !digits.is_empty() && digits.len() <= 64)
+        .filter(|digits| digits.chars().all(|c| c.is_ascii_hexdigit()))
+        .ok_or_else(|| ContractError::InvalidAddress(address.to_owned()))?;
+
+    let digits = digits.trim_start_matches('0').to_lowercase();
+    format!("0x{}", if digits.is_empty() { "0" } else { &digits }).parse()
+}
+
+/// Parse an id, sequential from 1, into an index.
+fn index(id: &str) -> Result<usize, ContractError> {
+    match usize::from_str(id) {
+        Ok(id) if id > 0 => Ok(id - 1),
+        _ => Err(ContractError::InvalidId { kind: "id", value: id.to_owned() }),
+    }
+}
+
+/// Parse a decimal amount.
+fn parse_amount(amount: &str) -> Result<BigUint, ContractError> {
+    amount.parse().map_err(|_| ContractError::InvalidAmount(amount.to_owned()))
+}
+
+impl Contract for MockContract {
+    /// The mock uses Starknet-like addresses.
+    fn chain(&self) -> &'static str {
+        "Starknet"
+    }
+
+    fn chain_id(&self) -> String {
+        "MOCK".to_string()
+    }
+
+    fn explorer_tx_url(&self, tx_hash: &str) -> String {
+        format!("mock://tx/{tx_hash}")
+    }
+
+    fn explorer_contract_url(&self, address: &str) -> String {
+        format!("mock://contract/{address}")
+    }
+}
+
+#[async_trait]
+impl AllocationContract for MockContract {
+    #[instrument(skip_all, fields(workflow_id = %workflow_id, sign_id = %sign_id))]
+    async fn create_allocation(
+        &self,
+        workflow_id: Id,
+        sign_id: Id,
+        recipient: Address,
+        amount: Number,
+        token_address: Address,
+    ) -> Result<Option<Id>> {
+        let _ = parse_amount(&amount)?;
+        let recipient = normalize(&recipient)?;
+        let token_address = normalize(&token_address)?;
+
+        self.transact(|state, tx| {
+            state.allocations.push(Allocation {
+                workflow_id,
+                sign_id,
+                recipient,
+                amount,
+                token_address,
+                tx_hash: Hash::zero(),
+                created_at: tx.timestamp,
+                status: AllocationStatus::Pending,
+            });
+            Ok(Some(valid(state.allocations.len())))
+        })
+    }
+
+    #[instrument(skip_all, fields(count = allocations.len()))]
+    async fn create_allocations_batch(
+        &self,
+        allocations: Vec<AllocationInput>,
+    ) -> Result<Vec<Option<Id>>> {
+        let mut checked = Vec::with_capacity(allocations.len());
+        for allocation in allocations {
+            let _ = parse_amount(&allocation.amount)?;
+            let recipient = normalize(&allocation.recipient)?;
+            let token_address = normalize(&allocation.token_address)?;
+            checked.push(AllocationInput { recipient, token_address, ..allocation });
+        }
+
+        self.transact(|state, tx| {
+            let mut ids = Vec::with_capacity(checked.len());
+            for allocation in checked {
+                state.allocations.push(Allocation {
+                    workflow_id: allocation.workflow_id,
+                    sign_id: allocation.sign_id,
+                    recipient: allocation.recipient,
+                    amount: allocation.amount,
+                    token_address: allocation.token_address,
+                    tx_hash: Hash::zero(),
+                    created_at: tx.timestamp,
+                    status: AllocationStatus::Pending,
+                });
+                ids.push(Some(valid(state.allocations.len())));
+            }
+            Ok(ids)
+        })
+    }
+
+    #[instrument(skip_all, fields(allocation_id = %allocation_id))]
+    async fn update_allocation_status(
+        &self,
+        allocation_id: Id,
+        status: AllocationStatus,
+    ) -> Result<bool> {
+        self.transact(|state, _| {
+            state.allocation(&allocation_id)?.status = status;
+            Ok(true)
+        })
+    }
+
+    #[instrument(skip_all, fields(allocation_id = %allocation_id, token = %token_address))]
+    async fn execute_allocation(
+        &self,
+        allocation_id: Id,
+        token_address: Address,
+        amount: Number,
+    ) -> Result<Hash> {
+        let calls = self.execute_allocation_calls(allocation_id, token_address, amount)?;
+        self.execute_calls(calls).await
+    }
+
+    fn execute_allocation_calls(
+        &self,
+        allocation_id: Id,
+        token_address: Address,
+        amount: Number,
+    ) -> Result<Vec<RawCall>> {
+        Ok(vec![
+            raw_call(
+                &token_address,
+                "approve",
+                vec![ALLOCATION_CONTRACT_ADDRESS.to_string(), amount],
+            ),
+            raw_call(
+                ALLOCATION_CONTRACT_ADDRESS,
+                "execute_allocation",
+                vec![allocation_id.to_string()],
+            ),
+        ])
+    }
+
+    #[instrument(skip_all, fields(allocation_id = %allocation_id, tx_hash = %tx_hash))]
+    async fn update_allocation_tx_hash(&self, allocation_id: Id, tx_hash: Hash) -> Result<()> {
+        let call = self.update_allocation_tx_hash_call(allocation_id, tx_hash)?;
+        let _ = self.execute_calls(vec![call]).await?;
+
+        Ok(())
+    }
+
+    fn update_allocation_tx_hash_call(&self, allocation_id: Id, tx_hash: Hash) -> Result<RawCall> {
+        Ok(raw_call(
+            ALLOCATION_CONTRACT_ADDRESS,
+            "update_tx_hash",
+            vec![allocation_id.to_string(), tx_hash.to_string()],
+        ))
+    }
+
+    fn claim_call(&self, allocation_id: Id) -> Result<RawCall> {
+        Ok(raw_call(ALLOCATION_CONTRACT_ADDRESS, "claim", vec![allocation_id.to_string()]))
+    }
+
+    async fn get_allocation_details(&self, allocation_id: Id) -> Result<Allocation> {
+        self.read(|state| {
+            state
+                .allocations
+                .get(index(&allocation_id)?)
+                .cloned()
+                .ok_or_else(|| anyhow!("Unknown allocation `{allocation_id}`"))
+        })
+    }
+
+    async fn get_allocation_by_sign(&self, sign_id: Id) -> Result<Id> {
+        self.read(|state| {
+            let position = state.allocations.iter().position(|a| a.sign_id == sign_id);
+            // Like the contract, an unknown sign maps to the zero id.
+            Ok(valid(position.map_or(0, |index| index + 1)))
+        })
+    }
+}
+
+#[async_trait]
+impl TokenContract for MockContract {
+    fn account_address(&self) -> Address {
+        valid(ACCOUNT_ADDRESS)
+    }
+
+    fn is_valid_address(&self, address: &Address) -> bool {
+        normalize(address).is_ok_and(|address| address != "0x0")
+    }
+
+    fn native_token_address(&self, token: NativeToken) -> Address {
+        match token {
+            NativeToken::Strk => valid(STRK_TOKEN_ADDRESS),
+            NativeToken::Eth => valid(ETH_TOKEN_ADDRESS),
+        }
+    }
+
+    fn fee_token(&self) -> NativeToken {
+        NativeToken::Strk
+    }
+
+    async fn decimals(&self, token_address: Address) -> Result<u8> {
+        let _ = normalize(&token_address)?;
+
+        Ok(TOKEN_DECIMALS)
+    }
+
+    async fn balance_of(&self, token_address: Address, owner: Address) -> Result<Number> {
+        let key = (normalize(&token_address)?, normalize(&owner)?);
+
+        self.read(|state| Ok(state.balances.get(&key).cloned().unwrap_or_default().to_string()))
+    }
+
+    async fn allowance(
+        &self,
+        token_address: Address,
+        owner: Address,
+        spender: Address,
+    ) -> Result<Number> {
+        let _ = normalize(&spender)?;
+
+        // Approvals are not tracked, owners allow their whole balance.
+        self.balance_of(token_address, owner).await
+    }
+
+    #[instrument(skip_all, fields(token = %token_address, recipient = %recipient, amount = %amount))]
+    async fn transfer(
+        &self,
+        token_address: Address,
+        recipient: Address,
+        amount: Number,
+    ) -> Result<Hash> {
+        let call = self.transfer_call(token_address, recipient, amount)?;
+        self.execute_calls(vec![call]).await
+    }
+
+    fn transfer_call(
+        &self,
+        token_address: Address,
+        recipient: Address,
+        amount: Number,
+    ) -> Result<RawCall> {
+        Ok(raw_call(&token_address, "transfer", vec![recipient.to_string(), amount]))
+    }
+
+    fn transfer_from_call(
+        &self,
+        token_address: Address,
+        owner: Address,
+        recipient: Address,
+        amount: Number,
+    ) -> Result<RawCall> {
+        Ok(raw_call(
+            &token_address,
+            "transfer_from",
+            vec![owner.to_string(), recipient.to_string(), amount],
+        ))
+    }
+}
+
+#[async_trait]
+impl TransactionContract for MockContract {
+    async fn transaction_status(&self, tx_hash: Hash) -> Result<TransactionStatus> {
+        self.read(|state| {
+            Ok(state.transactions.get(&tx_hash).cloned().unwrap_or(TransactionStatus::NotFound))
+        })
+    }
+
+    #[instrument(skip_all, fields(calls = calls.len()))]
+    async fn execute_calls(&self, calls: Vec<RawCall>) -> Result<Hash> {
+        let result = self.transact(|state, tx| {
+            for call in calls.iter().cloned() {
+                state.apply(tx, call)?;
+            }
+            Ok(tx.hash.clone())
+        });
+        match &result {
+            Ok(tx_hash) => self.journal.submitted(&calls, tx_hash.clone()),
+            Err(e) => self.journal.failed(&calls, e),
+        }
+        result
+    }
+
+    /// Apply the calls to a copy of the state, so calls which would fail are not estimated.
+    async fn estimate_fee(&self, calls: Vec<RawCall>) -> Result<Number> {
+        let count = calls.len() as u128;
+        self.read(|state| {
+            let tx = Transaction::next(state);
+            let mut state = state.clone();
+            for call in calls {
+                state.apply(&tx, call)?;
+            }
+            Ok((count * FEE_PER_CALL).to_string())
+        })
+    }
+
+    async fn block_number(&self) -> Result<u64> {
+        self.read(|state| Ok(state.block_number))
+    }
+
+    async fn check_chain_id(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl InquireContract for MockContract {
+    #[instrument(skip_all, fields(workflow_id = %workflow_id))]
+    async fn create_inquire(
+        &self,
+        workflow_id: Id,
+        inquirer: Address,
+        inquiree: Address,
+        question: String,
+    ) -> Result<Option<Id>> {
+        let inquirer = normalize(&inquirer)?;
+        let inquiree = normalize(&inquiree)?;
+
+        self.transact(|state, tx| {
+            state.inquires.push(Inquire {
+                workflow_id,
+                inquirer,
+                inquiree,
+                question,
+                response: String::new(),
+                status: InquireStatus::Pending,
+                created_at: tx.timestamp,
+                responded_at: 0,
+            });
+            Ok(Some(valid(state.inquires.len())))
+        })
+    }
+
+    #[instrument(skip_all, fields(inquire_id = %inquire_id))]
+    async fn respond_to_inquire(&self, inquire_id: Id, response: String) -> Result<bool> {
+        self.transact(|state, tx| {
+            let inquire = state.inquire(&inquire_id)?;
+            if inquire.status != InquireStatus::Pending {
+                bail!("Inquire `{inquire_id}` is not pending");
+            }
+            inquire.response = response;
+            inquire.status = InquireStatus::Responded;
+            inquire.responded_at = tx.timestamp;
+            Ok(true)
+        })
+    }
+
+    #[instrument(skip_all, fields(inquire_id = %inquire_id))]
+    async fn reject_inquire(&self, inquire_id: Id) -> Result<bool> {
+        self.transact(|state, tx| {
+            let inquire = state.inquire(&inquire_id)?;
+            if inquire.status != InquireStatus::Pending {
+                bail!("Inquire `{inquire_id}` is not pending");
+            }
+            inquire.status = InquireStatus::Rejected;
+            inquire.responded_at = tx.timestamp;
+            Ok(true)
+        })
+    }
+
+    async fn get_inquire_details(&self, inquire_id: Id) -> Result<Inquire> {
+        self.read(|state| {
+            state
+                .inquires
+                .get(index(&inquire_id)?)
+                .cloned()
+                .ok_or_else(|| anyhow!("Unknown inquire `{inquire_id}`"))
+        })
+    }
+}
+
+#[async_trait]
+impl ReceiptContract for MockContract {
+    #[instrument(skip_all, fields(workflow_id = %workflow_id))]
+    async fn create_receipt(
+        &self,
+        workflow_id: Id,
+        dependency_url: String,
+        metadata: ReceiptMetadata,
+        metadata_hash: Hash,
+        metadata_uri: String,
+    ) -> Result<Option<Id>> {
+        self.transact(|state, tx| {
+            Ok(Some(state.create_receipt(
+                tx,
+                workflow_id,
+                dependency_url,
+                metadata,
+                metadata_hash,
+                metadata_uri,
+            )))
+        })
+    }
+
+    fn create_receipt_call(
+        &self,
+        workflow_id: Id,
+        dependency_url: String,
+        metadata: ReceiptMetadata,
+        metadata_hash: Hash,
+        metadata_uri: String,
+    ) -> Result<RawCall> {
+        let ReceiptMetadata { name, version, author, license } = metadata;
+
+        Ok(raw_call(
+            RECEIPT_CONTRACT_ADDRESS,
+            "create_receipt",
+            vec![
+                workflow_id.to_string(),
+                dependency_url,
+                name,
+                version,
+                author,
+                license,
+                metadata_hash.to_string(),
+                metadata_uri,
+            ],
+        ))
+    }
+
+    async fn get_receipt_details(&self, receipt_id: Id) -> Result<(Receipt, ReceiptMetadata)> {
+        self.read(|state| {
+            state
+                .receipts
+                .get(index(&receipt_id)?)
+                .cloned()
+                .ok_or_else(|| anyhow!("Unknown receipt `{receipt_id}`"))
+        })
+    }
+
+    async fn verify_metadata(&self, receipt_id: Id, provided_hash: Hash) -> Result<bool> {
+        let (receipt, _) = self.get_receipt_details(receipt_id).await?;
+
+        Ok(receipt.metadata_hash.eq_ignore_ascii_case(&provided_hash))
+    }
+
+    #[instrument(skip_all, fields(receipt_id = %receipt_id))]
+    async fn update_tx_hash(&self, receipt_id: Id, tx_hash: Hash) -> Result<()> {
+        self.transact(|state, _| {
+            state.receipt(&receipt_id)?.0.tx_hash = tx_hash;
+            Ok(())
+        })
+    }
+}
+
+#[async_trait]
+impl SignContract for MockContract {
+    #[instrument(skip_all, fields(workflow_id = %workflow_id, inquire_id = %inquire_id))]
+    async fn create_sign(
+        &self,
+        workflow_id: Id,
+        inquire_id: Id,
+        signer: Address,
+        signature_hash: Hash,
+    ) -> Result<Option<Id>> {
+        let signer = normalize(&signer)?;
+
+        self.transact(|state, tx| {
+            let _ = state.inquire(&inquire_id)?;
+            state.signs.push(Sign {
+                workflow_id,
+                inquire_id,
+                signer,
+                signature_hash,
+                tx_hash: tx.hash.clone(),
+                created_at: tx.timestamp,
+            });
+            Ok(Some(valid(state.signs.len())))
+        })
+    }
+
+    async fn get_sign_details(&self, sign_id: Id) -> Result<Sign> {
+        self.read(|state| {
+            state
+                .signs
+                .get(index(&sign_id)?)
+                .cloned()
+                .ok_or_else(|| anyhow!("Unknown sign `{sign_id}`"))
+        })
+    }
+
+    async fn get_sign_by_inquire(&self, inquire_id: Id) -> Result<Id> {
+        self.read(|state| {
+            let position = state.signs.iter().position(|sign| sign.inquire_id == inquire_id);
+            Ok(valid(position.map_or(0, |index| index + 1)))
+        })
+    }
+
+    async fn is_valid_signature(
+        &self,
+        signer: Address,
+        message_hash: Hash,
+        signature: Vec<String>,
+    ) -> Result<bool> {
+        // The mock accounts sign a message with the message hash itself.
+        normalize(&signer)?;
+        Ok(signature.len() == 1 && signature[0].eq_ignore_ascii_case(&message_hash))
+    }
+}
+
+#[async_trait]
+impl WorkflowContract for MockContract {
+    #[instrument(skip_all, fields(owner = %github_owner))]
+    async fn create_workflow(
+        &self,
+        github_owner: Owner,
+        wallet_address: Address,
+    ) -> Result<Option<Id>> {
+        let wallet_address = normalize(&wallet_address)?;
+
+        self.transact(|state, tx| {
+            let workflows = state.workflows.entry(github_owner.clone()).or_default();
+            workflows.push(WorkflowEntry {
+                workflow: Workflow {
+                    owner: github_owner,
+                    wallet_address,
+                    status: WorkflowStatus::Created,
+                    created_at: tx.timestamp,
+                    last_updated_at: tx.timestamp,
+                },
+                dependencies: Vec::new(),
+            });
+            Ok(Some(valid(workflows.len())))
+        })
+    }
+
+    #[instrument(skip_all, fields(owner = %github_owner, workflow_id = %workflow_id))]
+    async fn create_dependency(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        name: String,
+        repository_url: String,
+        license: String,
+        metadata_json: String,
+    ) -> Result<Option<Id>> {
+        self.transact(|state, tx| {
+            state
+                .create_dependency(
+                    tx,
+                    github_owner,
+                    workflow_id,
+                    name,
+                    repository_url,
+                    license,
+                    metadata_json,
+                )
+                .map(Some)
+        })
+    }
+
+    fn create_dependency_call(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        name: String,
+        repository_url: String,
+        license: String,
+        metadata_json: String,
+    ) -> Result<RawCall> {
+        Ok(raw_call(
+            WORKFLOW_CONTRACT_ADDRESS,
+            "create_dependency",
+            vec![
+                github_owner.to_string(),
+                workflow_id.to_string(),
+                name,
+                repository_url,
+                license,
+                metadata_json,
+            ],
+        ))
+    }
+
+    #[instrument(
+        skip_all,
+        fields(owner = %github_owner, workflow_id = %workflow_id, dependency_idx = %dependency_idx)
+    )]
+    async fn add_step(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        dependency_idx: Id,
+        step_type: StepType,
+        tx_hash: Hash,
+        related_entity_id: Id,
+    ) -> Result<Option<Id>> {
+        self.transact(|state, tx| {
+            state
+                .add_step(
+                    tx,
+                    github_owner,
+                    workflow_id,
+                    dependency_idx,
+                    step_type,
+                    tx_hash,
+                    related_entity_id,
+                )
+                .map(Some)
+        })
+    }
+
+    fn add_step_call(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        dependency_idx: Id,
+        step_type: StepType,
+        tx_hash: Hash,
+        related_entity_id: Id,
+    ) -> Result<RawCall> {
+        Ok(raw_call(
+            WORKFLOW_CONTRACT_ADDRESS,
+            "add_step",
+            vec![
+                github_owner.to_string(),
+                workflow_id.to_string(),
+                dependency_idx.into_inner(),
+                step_type.code().to_string(),
+                tx_hash.into_inner(),
+                related_entity_id.into_inner(),
+            ],
+        ))
+    }
+
+    #[instrument(
+        skip_all,
+        fields(owner = %github_owner, workflow_id = %workflow_id, dependency_idx = %dependency_idx)
+    )]
+    async fn record_dependency_with_receipt(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        dependency_idx: Id,
+        record: DependencyReceipt,
+    ) -> Result<Hash> {
+        let calls = vec![
+            self.create_dependency_call(
+                github_owner.clone(),
+                workflow_id.clone(),
+                record.name,
+                record.repository_url,
+                record.license,
+                record.metadata_json,
+            )?,
+            self.create_receipt_call(
+                workflow_id.clone(),
+                record.dependency_url,
+                record.metadata,
+                record.metadata_hash.clone(),
+                record.metadata_uri,
+            )?,
+            self.add_step_call(
+                github_owner,
+                workflow_id,
+                dependency_idx,
+                StepType::Receipt,
+                Hash::zero(),
+                record.metadata_hash.into_inner().parse()?,
+            )?,
+        ];
+        self.execute_calls(calls).await
+    }
+
+    async fn finish_dependency(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        dependency_idx: Id,
+    ) -> Result<bool> {
+        self.transact(|state, tx| {
+            let (dependency, _) = state.dependency(&github_owner, &workflow_id, &dependency_idx)?;
+            dependency.status = WorkflowStatus::Completed;
+            dependency.last_updated_at = tx.timestamp;
+            Ok(true)
+        })
+    }
+
+    async fn finish_workflow(&self, github_owner: Owner, workflow_id: Id) -> Result<bool> {
+        self.transact(|state, tx| {
+            state.finish_workflow(tx, &github_owner, &workflow_id)?;
+            Ok(true)
+        })
+    }
+
+    fn finish_workflow_call(&self, github_owner: Owner, workflow_id: Id) -> Result<RawCall> {
+        Ok(raw_call(
+            WORKFLOW_CONTRACT_ADDRESS,
+            "finish_workflow",
+            vec![github_owner.to_string(), workflow_id.to_string()],
+        ))
+    }
+
+    async fn get_workflow_status(&self, github_owner: Owner, workflow_id: Id) -> Result<Workflow> {
+        self.read(|state| {
+            state
+                .workflows
+                .get(&github_owner)
+                .and_then(|workflows| workflows.get(index(&workflow_id).ok()?))
+                .map(|entry| entry.workflow.clone())
+                .ok_or_else(|| anyhow!("Unknown workflow `{workflow_id}` of `{github_owner}`"))
+        })
+    }
+
+    async fn get_dependencies(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+    ) -> Result<Vec<Dependency>> {
+        let mut state = self.state.lock().unwrap();
+        let workflow = state.workflow(&github_owner, &workflow_id)?;
+
+        Ok(workflow.dependencies.iter().map(|(dependency, _)| dependency.clone()).collect())
+    }
+
+    async fn get_dependencies_page(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<Dependency>> {
+        let dependencies = self.get_dependencies(github_owner, workflow_id).await?;
+
+        Ok(page(dependencies, offset, limit))
+    }
+
+    async fn get_steps(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        dependency_idx: Id,
+    ) -> Result<Vec<Step>> {
+        let mut state = self.state.lock().unwrap();
+        let (_, steps) = state.dependency(&github_owner, &workflow_id, &dependency_idx)?;
+
+        Ok(steps.clone())
+    }
+
+    async fn get_step_by_tx_hash(&self, tx_hash: Hash) -> Result<Option<(Owner, Id, Id, Id)>> {
+        self.read(|state| Ok(state.steps.get(&tx_hash).cloned()))
+    }
+
+    async fn get_complete_transaction_chain(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        dependency_idx: Id,
+    ) -> Result<Vec<Hash>> {
+        let steps = self.get_steps(github_owner, workflow_id, dependency_idx).await?;
+
+        Ok(steps.into_iter().map(|step| step.tx_hash).collect())
+    }
+
+    /// The continuation token is the number of events of the range already returned.
+    async fn get_events(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        continuation_token: Option<String>,
+    ) -> Result<WorkflowEventPage> {
+        let offset = match continuation_token {
+            Some(token) => {
+                token.parse().map_err(|_| anyhow!("Invalid continuation token `{token}`"))?
+            }
+            None => 0,
+        };
+
+        self.read(|state| {
+            let mut events = state
+                .events
+                .iter()
+                .filter(|event| (from_block..=to_block).contains(&event.block_number))
+                .skip(offset);
+            let page: Vec<_> = events.by_ref().take(EVENTS_CHUNK_SIZE).cloned().collect();
+            let continuation_token =
+                events.next().map(|_| (offset + EVENTS_CHUNK_SIZE).to_string());
+
+            Ok(WorkflowEventPage { events: page, continuation_token })
+        })
+    }
+
+    async fn get_workflow_count(&self, github_owner: Owner) -> Result<Number> {
+        self.read(|state| Ok(state.workflows.get(&github_owner).map_or(0, Vec::len).to_string()))
+    }
+
+    async fn get_all_workflows(&self, github_owner: Owner) -> Result<Vec<(Number, Workflow)>> {
+        self.read(|state| {
+            let workflows = state.workflows.get(&github_owner).into_iter().flatten();
+            Ok(workflows
+                .enumerate()
+                .map(|(index, entry)| ((index + 1).to_string(), entry.workflow.clone()))
+                .collect())
+        })
+    }
+
+    async fn get_workflows_page(
+        &self,
+        github_owner: Owner,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<(Number, Workflow)>> {
+        let workflows = self.get_all_workflows(github_owner).await?;
+
+        Ok(page(workflows, offset, limit))
+    }
+
+    async fn bind_wallet_address(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        wallet_address: Address,
+    ) -> Result<bool> {
+        let wallet_address = normalize(&wallet_address)?;
+
+        self.transact(|state, tx| {
+            let workflow = &mut state.workflow(&github_owner, &workflow_id)?.workflow;
+            if !workflow.wallet_address.is_empty() && workflow.wallet_address != "0x0" {
+                bail!("Workflow `{workflow_id}` is already bound to a wallet");
+            }
+            workflow.wallet_address = wallet_address;
+            workflow.last_updated_at = tx.timestamp;
+            Ok(true)
+        })
+    }
+
+    async fn unbind_wallet_address(&self, github_owner: Owner, workflow_id: Id) -> Result<bool> {
+        self.transact(|state, tx| {
+            let workflow = &mut state.workflow(&github_owner, &workflow_id)?.workflow;
+            workflow.wallet_address = valid("0x0");
+            workflow.last_updated_at = tx.timestamp;
+            Ok(true)
+        })
+    }
+
+    async fn change_wallet_address(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        new_wallet_address: Address,
+    ) -> Result<bool> {
+        let new_wallet_address = normalize(&new_wallet_address)?;
+
+        self.transact(|state, tx| {
+            let workflow = &mut state.workflow(&github_owner, &workflow_id)?.workflow;
+            workflow.wallet_address = new_wallet_address;
+            workflow.last_updated_at = tx.timestamp;
+            Ok(true)
+        })
+    }
+}
@@ -12,4 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod abi;
+pub mod accounts;
+pub mod calldata;
+pub mod codec;
+#[cfg(feature = "evm")]
+pub mod evm;
+pub mod mock;
+pub mod pool;
+pub mod retry;
+pub mod signer;
 pub mod starknet;
@@ -0,0 +1,224 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A pool of fallback RPC endpoints.
+//!
+//! The requests go to the first endpoint of the pool which is healthy, and fail over to the
+//! next ones when it cannot be reached, so a single flaky node does not take the backend down.
+//! After a number of consecutive failed requests an endpoint is taken out of the pool, and
+//! once the cooldown elapsed it is health-checked before being used again.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+use starknet::{
+    core::types::Felt,
+    providers::{
+        jsonrpc::{HttpTransportError, JsonRpcMethod, JsonRpcResponse, JsonRpcTransport},
+        ProviderRequestData, Url,
+    },
+};
+use tracing::{info, warn};
+
+use crate::contracts::impls::starknet::TracingTransport;
+
+#[derive(Clone, clap::Parser)]
+pub struct ProviderPoolConfig {
+    /// Consecutive failed requests taking an RPC endpoint out of the pool, 0 to never do so
+    #[clap(long, env = "DRK_RPC_ENDPOINT_THRESHOLD", default_value_t = 3)]
+    pub rpc_endpoint_threshold: u32,
+
+    /// Seconds before an RPC endpoint taken out of the pool is health-checked again
+    #[clap(long, env = "DRK_RPC_ENDPOINT_COOLDOWN", default_value_t = 30)]
+    pub rpc_endpoint_cooldown: u64,
+}
+
+/// JSON-RPC transport failing over between several endpoints, in order of preference.
+///
+/// The clones of a pool share the state of its endpoints.
+#[derive(Debug, Clone)]
+pub struct ProviderPool {
+    endpoints: Arc<Vec<Endpoint>>,
+    threshold: u32,
+    cooldown: Duration,
+}
+
+#[derive(Debug)]
+struct Endpoint {
+    url: Url,
+    transport: TracingTransport,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// The failed requests since the last successful one
+    failures: u32,
+    /// When the endpoint was taken out of the pool, `None` while it is in it
+    tripped_at: Option<Instant>,
+}
+
+impl ProviderPool {
+    pub fn new(urls: Vec<Url>, config: &ProviderPoolConfig) -> Self {
+        assert!(!urls.is_empty(), "At least one RPC endpoint is required");
+        Self {
+            endpoints: Arc::new(
+                urls.into_iter()
+                    .map(|url| Endpoint {
+                        transport: TracingTransport::new(url.clone()),
+                        url,
+                        state: Mutex::default(),
+                    })
+                    .collect(),
+            ),
+            threshold: config.rpc_endpoint_threshold,
+            cooldown: Duration::from_secs(config.rpc_endpoint_cooldown),
+        }
+    }
+
+    /// The endpoints to try, in order of preference, health-checking the endpoints out of the
+    /// pool whose cooldown elapsed. Every endpoint is tried when none is healthy.
+    async fn candidates(&self) -> Vec<&Endpoint> {
+        let mut candidates = Vec::with_capacity(self.endpoints.len());
+        for endpoint in self.endpoints.iter() {
+            let tripped_at = endpoint.state.lock().unwrap().tripped_at;
+            match tripped_at {
+                None => candidates.push(endpoint),
+                Some(since) if since.elapsed() >= self.cooldown => {
+                    if self.health_check(endpoint).await {
+                        candidates.push(endpoint);
+                    }
+                }
+                Some(_) => {}
+            }
+        }
+        if candidates.is_empty() {
+            candidates.extend(self.endpoints.iter());
+        }
+        candidates
+    }
+
+    /// Ask an endpoint out of the pool for its latest block, putting it back when it answers.
+    async fn health_check(&self, endpoint: &Endpoint) -> bool {
+        let response = endpoint
+            .transport
+            .send_request::<_, Value>(JsonRpcMethod::BlockNumber, json!([]))
+            .await;
+        let healthy = matches!(response, Ok(JsonRpcResponse::Success { .. }));
+        let mut state = endpoint.state.lock().unwrap();
+        if healthy {
+            info!(url = %endpoint.url, "RPC endpoint healthy again, putting it back in the pool");
+            *state = State::default();
+        } else {
+            state.tripped_at = Some(Instant::now());
+        }
+        healthy
+    }
+
+    /// Ask every endpoint for the id of its network, in order of preference.
+    pub async fn chain_ids(&self) -> Vec<(Url, anyhow::Result<Felt>)> {
+        let mut chain_ids = Vec::with_capacity(self.endpoints.len());
+        for endpoint in self.endpoints.iter() {
+            let response =
+                endpoint.transport.send_request::<_, Felt>(JsonRpcMethod::ChainId, json!([])).await;
+            let chain_id = match response {
+                Ok(JsonRpcResponse::Success { result, .. }) => Ok(result),
+                Ok(JsonRpcResponse::Error { error, .. }) => Err(anyhow!("{}", error.message)),
+                Err(e) => Err(anyhow!("{e}")),
+            };
+            chain_ids.push((endpoint.url.clone(), chain_id));
+        }
+        chain_ids
+    }
+
+    /// Record the outcome of a request sent to an endpoint.
+    fn record(&self, endpoint: &Endpoint, success: bool) {
+        let mut state = endpoint.state.lock().unwrap();
+        if success {
+            *state = State::default();
+            return;
+        }
+
+        state.failures += 1;
+        if state.tripped_at.is_none() && self.threshold > 0 && state.failures >= self.threshold {
+            warn!(
+                url = %endpoint.url,
+                failures = state.failures,
+                "RPC endpoint failing, taking it out of the pool"
+            );
+            state.tripped_at = Some(Instant::now());
+        }
+    }
+}
+
+#[async_trait]
+impl JsonRpcTransport for ProviderPool {
+    type Error = HttpTransportError;
+
+    async fn send_request<P, R>(
+        &self,
+        method: JsonRpcMethod,
+        params: P,
+    ) -> Result<JsonRpcResponse<R>, Self::Error>
+    where
+        P: Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let mut last_error = None;
+        for endpoint in self.candidates().await {
+            match endpoint.transport.send_request(method, &params).await {
+                Ok(response) => {
+                    self.record(endpoint, true);
+                    return Ok(response);
+                }
+                Err(e) => {
+                    warn!(url = %endpoint.url, ?method, "RPC endpoint failed, failing over: {e}");
+                    self.record(endpoint, false);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.expect("the pool has at least one endpoint"))
+    }
+
+    async fn send_requests<R>(
+        &self,
+        requests: R,
+    ) -> Result<Vec<JsonRpcResponse<Value>>, Self::Error>
+    where
+        R: AsRef<[ProviderRequestData]> + Send + Sync,
+    {
+        let mut last_error = None;
+        for endpoint in self.candidates().await {
+            match endpoint.transport.send_requests(requests.as_ref()).await {
+                Ok(responses) => {
+                    self.record(endpoint, true);
+                    return Ok(responses);
+                }
+                Err(e) => {
+                    warn!(url = %endpoint.url, "RPC endpoint failed, failing over: {e}");
+                    self.record(endpoint, false);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.expect("the pool has at least one endpoint"))
+    }
+}
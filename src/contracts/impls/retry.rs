@@ -0,0 +1,95 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Retries of the calls to the chain provider.
+//!
+//! A call failing with a transient error, such as a timeout or a rate limit of the RPC
+//! gateway, is retried with an exponential backoff, the delays being randomized by the
+//! jitter so that concurrent calls do not retry in lockstep. Other errors, such as a
+//! reverted call, are returned right away.
+
+use std::{fmt::Debug, future::Future, time::Duration};
+
+use tracing::warn;
+
+#[derive(Clone, clap::Parser)]
+pub struct RetryConfig {
+    /// Attempts of a chain call failing with a transient error, 1 to never retry it
+    #[clap(long, env = "DRK_RPC_MAX_ATTEMPTS", default_value_t = 3)]
+    pub rpc_max_attempts: u32,
+
+    /// Milliseconds before the first retry of a chain call, doubled at every retry
+    #[clap(long, env = "DRK_RPC_BACKOFF_BASE", default_value_t = 250)]
+    pub rpc_backoff_base: u64,
+
+    /// Maximum milliseconds between two attempts of a chain call
+    #[clap(long, env = "DRK_RPC_BACKOFF_MAX", default_value_t = 5000)]
+    pub rpc_backoff_max: u64,
+
+    /// Fraction of the delay before a retry which is randomized, between 0 and 1
+    #[clap(long, env = "DRK_RPC_BACKOFF_JITTER", default_value_t = 0.5)]
+    pub rpc_backoff_jitter: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Retry {
+    max_attempts: u32,
+    base: Duration,
+    max: Duration,
+    jitter: f64,
+}
+
+impl Retry {
+    pub fn new(config: &RetryConfig) -> Self {
+        Self {
+            max_attempts: config.rpc_max_attempts.max(1),
+            base: Duration::from_millis(config.rpc_backoff_base),
+            max: Duration::from_millis(config.rpc_backoff_max),
+            jitter: config.rpc_backoff_jitter.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Run an operation, retrying it while it fails with an error `is_transient` accepts.
+    pub async fn run<T, E, F, Fut>(
+        &self,
+        operation: &str,
+        is_transient: impl Fn(&E) -> bool,
+        mut attempt: F,
+    ) -> Result<T, E>
+    where
+        E: Debug,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut attempts = 1;
+        loop {
+            match attempt().await {
+                Err(e) if attempts < self.max_attempts && is_transient(&e) => {
+                    let delay = self.delay(attempts);
+                    warn!(operation, attempts, ?delay, "Transient chain error, retrying: {e:?}");
+                    tokio::time::sleep(delay).await;
+                    attempts += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// The delay before the retry following the given attempt, the jitter randomizing
+    /// its last fraction.
+    fn delay(&self, attempts: u32) -> Duration {
+        let backoff = self.base.saturating_mul(1 << (attempts - 1).min(16)).min(self.max);
+        backoff.mul_f64(1.0 - self.jitter * rand::random::<f64>())
+    }
+}
@@ -0,0 +1,248 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The signer of the transactions of the Starknet account.
+//!
+//! The transactions are signed either in process, with a key read from the
+//! `STARKNET_PRIVATE_KEY` variable or from an encrypted keystore file, each source
+//! implementing `KeySource` so other ones can be added, or by a remote signing service such
+//! as an HSM gateway, the key never being held by the backend.
+//!
+//! The remote signing service exposes two endpoints:
+//! - `GET {url}/public-key`, answering `{"public_key": "0x..."}`
+//! - `POST {url}/sign` with `{"hash": "0x..."}`, answering `{"r": "0x...", "s": "0x..."}`
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context as _, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use starknet::{
+    core::{crypto::Signature, types::Felt},
+    signers::{LocalWallet, Signer, SignerInteractivityContext, SigningKey, VerifyingKey},
+};
+use thiserror::Error;
+use tracing::instrument;
+
+#[derive(Clone, clap::Parser)]
+pub struct SignerConfig {
+    /// Private key of the Starknet account
+    #[clap(
+        long,
+        env = "STARKNET_PRIVATE_KEY",
+        required_unless_present_any = ["starknet_keystore_path", "starknet_remote_signer_url"],
+        conflicts_with_all = ["starknet_keystore_path", "starknet_remote_signer_url"]
+    )]
+    pub starknet_private_key: Option<String>,
+
+    /// Path of the encrypted keystore file holding the private key of the Starknet account
+    #[clap(
+        long,
+        env = "STARKNET_KEYSTORE_PATH",
+        requires = "starknet_keystore_password",
+        conflicts_with = "starknet_remote_signer_url"
+    )]
+    pub starknet_keystore_path: Option<PathBuf>,
+
+    /// Passphrase decrypting the keystore file
+    #[clap(long, env = "STARKNET_KEYSTORE_PASSWORD")]
+    pub starknet_keystore_password: Option<String>,
+
+    /// Base URL of the remote service signing the transactions of the Starknet account
+    #[clap(long, env = "STARKNET_REMOTE_SIGNER_URL")]
+    pub starknet_remote_signer_url: Option<String>,
+
+    /// Bearer token authenticating with the remote signing service
+    #[clap(long, env = "STARKNET_REMOTE_SIGNER_TOKEN")]
+    pub starknet_remote_signer_token: Option<String>,
+}
+
+/// A source of the key signing the transactions.
+pub trait KeySource {
+    /// Load the signing key.
+    fn signing_key(&self) -> Result<SigningKey>;
+}
+
+/// A private key, hex encoded.
+pub struct PrivateKey(pub String);
+
+impl KeySource for PrivateKey {
+    fn signing_key(&self) -> Result<SigningKey> {
+        let secret =
+            Felt::from_hex(&self.0).map_err(|_| anyhow!("Invalid Starknet private key"))?;
+        Ok(SigningKey::from_secret_scalar(secret))
+    }
+}
+
+/// An encrypted keystore file, as created by `starkli signer keystore new`.
+pub struct Keystore {
+    pub path: PathBuf,
+    pub password: String,
+}
+
+impl KeySource for Keystore {
+    fn signing_key(&self) -> Result<SigningKey> {
+        SigningKey::from_keystore(&self.path, &self.password)
+            .with_context(|| format!("Failed to decrypt keystore `{}`", self.path.display()))
+    }
+}
+
+impl SignerConfig {
+    /// The configured signer, remote or holding the key in process.
+    pub fn signer(&self) -> Result<AccountSigner> {
+        match &self.starknet_remote_signer_url {
+            Some(url) => Ok(AccountSigner::Remote(RemoteSigner::new(
+                url,
+                self.starknet_remote_signer_token.clone(),
+            ))),
+            None => Ok(AccountSigner::Local(LocalWallet::from_signing_key(
+                self.key_source().signing_key()?,
+            ))),
+        }
+    }
+
+    /// The configured source of the signing key.
+    pub fn key_source(&self) -> Box<dyn KeySource> {
+        match (&self.starknet_keystore_path, &self.starknet_private_key) {
+            (Some(path), _) => Box::new(Keystore {
+                path: path.clone(),
+                password: self.starknet_keystore_password.clone().unwrap_or_default(),
+            }),
+            (None, Some(private_key)) => Box::new(PrivateKey(private_key.clone())),
+            (None, None) => panic!("Either a Starknet private key or a keystore is required"),
+        }
+    }
+}
+
+/// The signer of the account, selected by the configuration.
+#[derive(Debug, Clone)]
+pub enum AccountSigner {
+    /// Signs in process, holding the key in memory
+    Local(LocalWallet),
+    /// Delegates the signatures to a remote service
+    Remote(RemoteSigner),
+}
+
+#[derive(Debug, Error)]
+pub enum SignerError {
+    #[error("local signer: {0}")]
+    Local(String),
+
+    #[error("remote signer: {0}")]
+    Remote(String),
+}
+
+#[async_trait]
+impl Signer for AccountSigner {
+    type GetPublicKeyError = SignerError;
+    type SignError = SignerError;
+
+    async fn get_public_key(&self) -> Result<VerifyingKey, Self::GetPublicKeyError> {
+        match self {
+            Self::Local(wallet) => {
+                wallet.get_public_key().await.map_err(|e| SignerError::Local(e.to_string()))
+            }
+            Self::Remote(remote) => remote.public_key().await,
+        }
+    }
+
+    async fn sign_hash(&self, hash: &Felt) -> Result<Signature, Self::SignError> {
+        match self {
+            Self::Local(wallet) => {
+                wallet.sign_hash(hash).await.map_err(|e| SignerError::Local(e.to_string()))
+            }
+            Self::Remote(remote) => remote.sign(hash).await,
+        }
+    }
+
+    /// A remote signature is a round trip to the signing service, so only the transactions
+    /// which are sent are signed, the fee estimations skipping the validation instead.
+    fn is_interactive(&self, context: SignerInteractivityContext<'_>) -> bool {
+        match self {
+            Self::Local(wallet) => wallet.is_interactive(context),
+            Self::Remote(_) => true,
+        }
+    }
+}
+
+/// Delegates the signatures to a remote signing service.
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    client: reqwest::Client,
+    url: String,
+    token: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SignRequest {
+    hash: String,
+}
+
+#[derive(Deserialize)]
+struct SignResponse {
+    r: String,
+    s: String,
+}
+
+#[derive(Deserialize)]
+struct PublicKeyResponse {
+    public_key: String,
+}
+
+impl RemoteSigner {
+    pub fn new(url: &str, token: Option<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.trim_end_matches('/').to_string(),
+            token: token.filter(|token| !token.is_empty()),
+        }
+    }
+
+    async fn public_key(&self) -> Result<VerifyingKey, SignerError> {
+        let request = self.client.get(format!("{}/public-key", self.url));
+        let response: PublicKeyResponse = self.send(request).await?;
+
+        Ok(VerifyingKey::from_scalar(felt(&response.public_key)?))
+    }
+
+    #[instrument(skip_all, fields(%hash))]
+    async fn sign(&self, hash: &Felt) -> Result<Signature, SignerError> {
+        let request = self
+            .client
+            .post(format!("{}/sign", self.url))
+            .json(&SignRequest { hash: format!("{hash:#x}") });
+        let response: SignResponse = self.send(request).await?;
+
+        Ok(Signature { r: felt(&response.r)?, s: felt(&response.s)? })
+    }
+
+    async fn send<T: serde::de::DeserializeOwned>(
+        &self,
+        mut request: reqwest::RequestBuilder,
+    ) -> Result<T, SignerError> {
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let response = request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| SignerError::Remote(e.to_string()))?;
+        response.json().await.map_err(|e| SignerError::Remote(format!("malformed response: {e}")))
+    }
+}
+
+fn felt(value: &str) -> Result<Felt, SignerError> {
+    Felt::from_hex(value).map_err(|_| SignerError::Remote(format!("malformed felt `{value}`")))
+}
@@ -12,136 +12,111 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use anyhow::{anyhow, Result};
-use serde::{Deserialize, Serialize};
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
 use starknet::{
-    accounts::{Account, ExecutionEncoding, SingleOwnerAccount},
-    core::types::{BlockId, BlockTag, Call, Felt, FunctionCall, InvokeTransactionResult},
-    macros::selector,
+    accounts::{Account, AccountError, ConnectedAccount, ExecutionEncoding, SingleOwnerAccount},
+    core::{
+        types::{
+            BlockId, BlockTag, Call, EmittedEvent, EventFilter, ExecuteInvocation, ExecutionResult,
+            FeeEstimate, Felt, FunctionCall, InvokeTransactionResult, InvokeTransactionTrace,
+            StarknetError, TransactionStatus as StarknetTransactionStatus, TransactionTrace,
+        },
+        utils::{cairo_short_string_to_felt, parse_cairo_short_string},
+    },
     providers::{
-        jsonrpc::{HttpTransport, JsonRpcClient},
-        Provider, Url,
+        jsonrpc::{
+            HttpTransport, HttpTransportError, JsonRpcClient, JsonRpcMethod, JsonRpcResponse,
+            JsonRpcTransport,
+        },
+        Provider, ProviderError, ProviderRequestData, Url,
     },
-    signers::{LocalWallet, SigningKey},
 };
-use starknet_ff::FieldElement;
 use std::str::FromStr;
-use tracing::{debug, info};
-
-use crate::contracts::{
-    allocation::{Allocation, AllocationContract, Status as AllocationStatus},
-    inquire::{Inquire, InquireContract},
-    receipt::{Receipt, ReceiptContract, ReceiptMetadata},
-    sign::{Sign, SignContract},
-    types::*,
-    workflow::{Dependency, Step, StepType, Workflow, WorkflowContract},
-    Contract,
+use tracing::{debug, info, instrument, warn};
+
+use crate::{
+    contracts::{
+        allocation::{Allocation, AllocationContract, AllocationInput, Status as AllocationStatus},
+        error::ContractError,
+        impls::{
+            abi::{
+                self,
+                workflow::{DependencyCreated, StepAdded},
+            },
+            accounts::{AccountPool, AccountStrategy, StarknetAccount},
+            calldata::{parse_address, parse_amount, parse_hash, parse_id, parse_text},
+            pool::{ProviderPool, ProviderPoolConfig},
+            retry::{Retry, RetryConfig},
+            signer::SignerConfig,
+        },
+        inquire::{Inquire, InquireContract, Status as InquireStatus},
+        receipt::{Receipt, ReceiptContract, ReceiptMetadata},
+        sign::{Sign, SignContract},
+        token::{NativeToken, TokenContract},
+        transaction::{TransactionContract, TransactionStatus},
+        types::*,
+        workflow::{
+            Dependency, DependencyReceipt, EmittedWorkflowEvent, Status as WorkflowStatus, Step,
+            StepType, Workflow, WorkflowContract, WorkflowEvent, WorkflowEventPage,
+        },
+        Contract,
+    },
+    services::transaction::TransactionJournal,
+    telemetry,
 };
 
-// Struct definitions corresponding to contract structs
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct WorkflowDetails {
-    pub owner: FieldElement,
-    pub wallet_address: FieldElement,
-    pub status: FieldElement,
-    pub created_at: u64,
-    pub last_updated_at: u64,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DependencyDetails {
-    pub name: FieldElement,
-    pub repository_url: FieldElement,
-    pub license: FieldElement,
-    pub metadata_json: FieldElement,
-    pub status: FieldElement,
-    pub created_at: u64,
-    pub last_updated_at: u64,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StepDetails {
-    pub step_type: FieldElement,
-    pub tx_hash: FieldElement,
-    pub related_entity_id: FieldElement,
-    pub timestamp: u64,
-    pub prev_step_index: FieldElement,
-}
-
-// Allocation related struct definitions
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AllocationDetails {
-    pub workflow_id: FieldElement,
-    pub sign_id: FieldElement,
-    pub recipient: FieldElement,
-    pub amount: FieldElement,
-    pub token_address: FieldElement,
-    pub tx_hash: FieldElement,
-    pub created_at: u64,
-    pub status: FieldElement, // 0: pending, 1: executed, 2: failed
-}
-
-// Inquire related struct definitions
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct InquireDetails {
-    pub workflow_id: FieldElement,
-    pub inquirer: FieldElement,
-    pub inquiree: FieldElement,
-    pub question: FieldElement,
-    pub response: FieldElement,
-    pub status: FieldElement, // 0: pending, 1: responded, 2: rejected
-    pub created_at: u64,
-    pub responded_at: u64,
-}
+/// Address of the STRK token contract, the same on mainnet and sepolia.
+const STRK_TOKEN_ADDRESS: &str =
+    "0x04718f5a0fc34cc1af16a1cdee98ffb20c31f5cd61d6ab07201858f4287c938d";
 
-// Receipt related struct definitions
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ReceiptDetails {
-    pub workflow_id: FieldElement,
-    pub dependency_url: FieldElement,
-    pub tx_hash: FieldElement,
-    pub created_at: u64,
-    pub metadata_hash: FieldElement,
-    pub metadata_uri: FieldElement,
-}
+/// Address of the ETH token contract, the same on mainnet and sepolia.
+const ETH_TOKEN_ADDRESS: &str =
+    "0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7";
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StarkReceiptMetadata {
-    pub name: FieldElement,
-    pub version: FieldElement,
-    pub author: FieldElement,
-    pub license: FieldElement,
-}
+/// The Starknet mainnet chain id.
+const MAINNET_CHAIN_ID: &str = "SN_MAIN";
 
-// Sign related struct definitions
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SignDetails {
-    pub workflow_id: FieldElement,
-    pub inquire_id: FieldElement,
-    pub signer: FieldElement,
-    pub signature_hash: FieldElement,
-    pub tx_hash: FieldElement,
-    pub created_at: u64,
-}
+/// Number of events fetched per page.
+const EVENTS_CHUNK_SIZE: u64 = 100;
 
 #[derive(Clone, clap::Parser)]
 pub struct StarknetConfig {
-    /// URL of the Starknet JSON-RPC endpoint
-    #[clap(long, env = "STARKNET_RPC_URL")]
-    pub starknet_rpc_url: String,
+    /// URLs of the Starknet JSON-RPC endpoints, comma separated, in order of preference
+    #[clap(long, env = "STARKNET_RPC_URL", value_delimiter = ',', required = true)]
+    pub starknet_rpc_url: Vec<String>,
 
-    /// Private key of the Starknet account
-    #[clap(long, env = "STARKNET_PRIVATE_KEY")]
-    pub starknet_private_key: String,
+    /// The key signing the transactions of the account.
+    #[clap(flatten)]
+    pub signer_config: SignerConfig,
 
-    /// Address of the Starknet account
+    /// Address of the Starknet account, the treasury paying out the allocations
     #[clap(long, env = "STARKNET_ACCOUNT_ADDRESS")]
     pub starknet_account_address: String,
 
+    /// Addresses of further Starknet accounts controlled by the same key, comma separated,
+    /// sharing the transactions with the treasury account to increase the write throughput
+    #[clap(long, env = "STARKNET_POOL_ACCOUNT_ADDRESSES", value_delimiter = ',')]
+    pub starknet_pool_account_addresses: Vec<String>,
+
+    /// How the transactions are shared between the accounts
+    #[clap(
+        long,
+        env = "STARKNET_ACCOUNT_STRATEGY",
+        value_enum,
+        default_value_t = AccountStrategy::RoundRobin
+    )]
+    pub starknet_account_strategy: AccountStrategy,
+
     /// Chain ID of the Starknet network
     #[clap(long, env = "STARKNET_CHAIN_ID")]
     pub starknet_chain_id: String,
 
+    /// Base URL of the block explorer, Starkscan on the network of the chain ID by default
+    #[clap(long, env = "STARKNET_EXPLORER_URL")]
+    pub starknet_explorer_url: Option<String>,
+
     /// Address of the Allocation contract
     #[clap(long, env = "ALLOCATION_CONTRACT_ADDRESS")]
     pub allocation_contract_address: String,
@@ -161,6 +136,84 @@ pub struct StarknetConfig {
     /// Address of the Workflow contract
     #[clap(long, env = "WORKFLOW_CONTRACT_ADDRESS")]
     pub workflow_contract_address: String,
+
+    /// Multiplier of the estimated gas amounts, bounding the gas a transaction can consume
+    #[clap(long, env = "STARKNET_FEE_GAS_MULTIPLIER", default_value_t = 1.5)]
+    pub starknet_fee_gas_multiplier: f64,
+
+    /// Multiplier of the estimated gas prices, bounding the price a transaction can pay
+    #[clap(long, env = "STARKNET_FEE_PRICE_MULTIPLIER", default_value_t = 1.5)]
+    pub starknet_fee_price_multiplier: f64,
+
+    /// Maximum fee of a transaction, in FRI, transactions which could cost more are rejected
+    #[clap(long, env = "STARKNET_MAX_FEE")]
+    pub starknet_max_fee: Option<u128>,
+
+    /// Simulate transactions against the pending block before sending them, rejecting those
+    /// which would revert
+    #[clap(long, env = "STARKNET_SIMULATE")]
+    pub starknet_simulate: bool,
+
+    /// Simulate transactions without ever sending them, to validate the calldata of workflows
+    /// without spending fees
+    #[clap(long, env = "STARKNET_DRY_RUN")]
+    pub starknet_dry_run: bool,
+
+    /// The retries of the calls to the RPC endpoint.
+    #[clap(flatten)]
+    pub retry_config: RetryConfig,
+
+    /// The failover between the RPC endpoints.
+    #[clap(flatten)]
+    pub pool_config: ProviderPoolConfig,
+}
+
+/// HTTP transport which forwards the current trace context to the RPC node.
+#[derive(Debug, Clone)]
+pub struct TracingTransport {
+    inner: HttpTransport,
+}
+
+impl TracingTransport {
+    pub fn new(url: Url) -> Self {
+        Self { inner: HttpTransport::new(url) }
+    }
+
+    /// The inner transport with trace headers of the current task attached.
+    fn traced(&self) -> HttpTransport {
+        let mut transport = self.inner.clone();
+        for (name, value) in telemetry::headers() {
+            transport.add_header(name, value);
+        }
+        transport
+    }
+}
+
+#[async_trait]
+impl JsonRpcTransport for TracingTransport {
+    type Error = HttpTransportError;
+
+    async fn send_request<P, R>(
+        &self,
+        method: JsonRpcMethod,
+        params: P,
+    ) -> Result<JsonRpcResponse<R>, Self::Error>
+    where
+        P: Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        self.traced().send_request(method, params).await
+    }
+
+    async fn send_requests<R>(
+        &self,
+        requests: R,
+    ) -> Result<Vec<JsonRpcResponse<serde_json::Value>>, Self::Error>
+    where
+        R: AsRef<[ProviderRequestData]> + Send + Sync,
+    {
+        self.traced().send_requests(requests).await
+    }
 }
 
 /// Starknet implementation of the Contract trait
@@ -169,11 +222,14 @@ pub struct StarknetConfig {
 /// on the Starknet blockchain, including workflow management, allocations,
 /// inquiries, receipts, and signatures.
 pub struct StarknetContract {
-    /// JSON-RPC client for Starknet network
-    provider: JsonRpcClient<HttpTransport>,
+    /// JSON-RPC client for Starknet network, failing over between the endpoints
+    provider: JsonRpcClient<ProviderPool>,
+
+    /// The endpoints of the provider
+    pool: ProviderPool,
 
-    /// Starknet account with signing capability
-    account: SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet>,
+    /// Starknet accounts with signing capability, the treasury first
+    accounts: AccountPool,
 
     /// Address of the Allocation contract
     allocation_contract_address: Felt,
@@ -189,31 +245,65 @@ pub struct StarknetContract {
 
     /// Address of the Workflow contract
     workflow_contract_address: Felt,
+
+    /// Chain ID of the Starknet network, as configured
+    chain_id: String,
+
+    /// Base URL of the block explorer
+    explorer_url: String,
+
+    /// Retries of the calls failing with a transient error
+    retry: Retry,
+
+    /// Multipliers of the estimated gas amounts and prices
+    fee_gas_multiplier: f64,
+    fee_price_multiplier: f64,
+
+    /// Maximum fee of a transaction, in FRI
+    max_fee: Option<u128>,
+
+    /// Whether transactions are simulated before being sent
+    simulate: bool,
+
+    /// Whether transactions are only simulated, never sent
+    dry_run: bool,
+
+    /// Journal of the sent transactions
+    journal: TransactionJournal,
 }
 
 impl StarknetContract {
-    pub fn new(config: &StarknetConfig) -> Self {
+    pub fn new(config: &StarknetConfig, journal: TransactionJournal) -> Self {
         // Create provider used to access to the Starknet network.
-        let provider = JsonRpcClient::new(HttpTransport::new(
-            Url::parse(&config.starknet_rpc_url).expect("Invalid Starknet RPC URL format"),
-        ));
-
-        // Create account object.
-        let signer = LocalWallet::from_signing_key(SigningKey::from_secret_scalar(
-            Felt::from_hex(&config.starknet_private_key).expect("Invalid Starknet private key"),
-        ));
-        let account_address = Felt::from_hex(&config.starknet_account_address)
-            .expect("Invalid Starknet account address");
-        let chain_id =
-            Felt::from_str(&config.starknet_chain_id).expect("Invalid Starknet chain id");
-
-        let account = SingleOwnerAccount::new(
-            provider.clone(),
-            signer,
-            account_address,
-            chain_id,
-            ExecutionEncoding::New,
-        );
+        let urls = config
+            .starknet_rpc_url
+            .iter()
+            .map(|url| Url::parse(url).expect("Invalid Starknet RPC URL format"))
+            .collect();
+        let pool = ProviderPool::new(urls, &config.pool_config);
+        let provider = JsonRpcClient::new(pool.clone());
+
+        // Create account objects, the treasury first.
+        let signer = config.signer_config.signer().expect("Invalid Starknet signer");
+        // The chain id is either a felt, or a short string such as `SN_SEPOLIA`.
+        let chain_id = Felt::from_str(&config.starknet_chain_id)
+            .or_else(|_| cairo_short_string_to_felt(&config.starknet_chain_id))
+            .expect("Invalid Starknet chain id");
+
+        let accounts = std::iter::once(&config.starknet_account_address)
+            .chain(&config.starknet_pool_account_addresses)
+            .map(|address| {
+                let address = Felt::from_hex(address).expect("Invalid Starknet account address");
+                SingleOwnerAccount::new(
+                    provider.clone(),
+                    signer.clone(),
+                    address,
+                    chain_id,
+                    ExecutionEncoding::New,
+                )
+            })
+            .collect();
+        let accounts = AccountPool::new(accounts, config.starknet_account_strategy);
 
         // parse contract addresses.
         let allocation_contract_address = Felt::from_hex(&config.allocation_contract_address)
@@ -233,73 +323,410 @@ impl StarknetContract {
 
         Self {
             provider,
-            account,
+            pool,
+            accounts,
             allocation_contract_address,
             inquire_contract_address,
             receipt_contract_address,
             sign_contract_address,
             workflow_contract_address,
+            chain_id: config.starknet_chain_id.clone(),
+            explorer_url: explorer_url(config),
+            retry: Retry::new(&config.retry_config),
+            fee_gas_multiplier: config.starknet_fee_gas_multiplier.max(1.0),
+            fee_price_multiplier: config.starknet_fee_price_multiplier.max(1.0),
+            max_fee: config.starknet_max_fee,
+            simulate: config.starknet_simulate || config.starknet_dry_run,
+            dry_run: config.starknet_dry_run,
+            journal,
         }
     }
 
     /// Call contract function (read-only operation)
-    async fn call(
-        &self,
-        contract_address: &Felt,
-        selector: &Felt,
-        calldata: Vec<Felt>,
-    ) -> Result<Vec<Felt>> {
+    #[instrument(skip_all, fields(contract = %call.to, selector = %call.selector))]
+    async fn call(&self, call: Call) -> Result<Vec<Felt>> {
         let function_call = FunctionCall {
-            contract_address: *contract_address,
-            entry_point_selector: *selector,
-            calldata,
+            contract_address: call.to,
+            entry_point_selector: call.selector,
+            calldata: call.calldata,
         };
 
         info!("Attempting contract call (read-only operation)...");
 
-        match self.provider.call(function_call, BlockId::Tag(BlockTag::Latest)).await {
+        let result = self
+            .retry
+            .run("call", is_transient, || {
+                self.provider.call(&function_call, BlockId::Tag(BlockTag::Latest))
+            })
+            .await;
+        match result {
             Ok(result) => {
                 info!("Call successful! Result: {:?}", result);
                 Ok(result)
             }
-            Err(e) => Err(anyhow!("Contract call failed: {:?}", e)),
+            Err(e) => Err(anyhow!(provider_error(e)).context("Contract call failed")),
         }
     }
 
-    /// Execute transaction
-    async fn execute(
+    /// Execute several calls atomically in a single transaction, recorded in the journal,
+    /// sent by the account of the pool picked by the strategy
+    async fn multicall(&self, calls: Vec<Call>) -> Result<InvokeTransactionResult> {
+        let account = self.accounts.lease_any().await;
+        self.multicall_from(&account, calls).await
+    }
+
+    /// Execute several calls atomically in a single transaction, recorded in the journal,
+    /// sent by the given account
+    #[instrument(skip_all, fields(calls = calls.len()))]
+    async fn multicall_from(
         &self,
-        contract_address: &Felt,
-        selector: &Felt,
-        calldata: Vec<Felt>,
+        account: &StarknetAccount,
+        calls: Vec<Call>,
     ) -> Result<InvokeTransactionResult> {
-        debug!(
-            "Execute transaction, contract_address: {}, selector: {}, calldata: {:?}",
-            contract_address, selector, calldata
-        );
+        let journaled: Vec<RawCall> =
+            calls.iter().cloned().map(to_raw_call).collect::<Result<_>>()?;
+        let result = self.send(account, calls).await;
+        match &result {
+            Ok(result) => self.journal.submitted(&journaled, from_felt(&result.transaction_hash)?),
+            Err(e) => match e.downcast_ref::<ContractError>() {
+                Some(ContractError::DryRun(tx_hash)) => {
+                    self.journal.simulated(&journaled, tx_hash.clone())
+                }
+                // Followed like a sent transaction, until it shows up or is dropped.
+                Some(ContractError::Ambiguous { tx_hash, .. }) => {
+                    self.journal.submitted(&journaled, tx_hash.clone())
+                }
+                _ => self.journal.failed(&journaled, e),
+            },
+        }
+        result
+    }
+
+    /// Send several calls in a single transaction
+    ///
+    /// The fee is estimated first, and the resource bounds of the transaction are the
+    /// estimate scaled by the multipliers, the transaction being rejected when they allow
+    /// a fee above the maximum. The nonce is fetched once, so a retried transaction which
+    /// the node did receive is rejected instead of being executed twice.
+    ///
+    /// When enabled, the transaction is then simulated against the pending block and rejected
+    /// if it would revert. In dry-run mode it is never sent, failing with its hash computed
+    /// locally, so that nothing is recorded as sent.
+    ///
+    /// A failed send may still have reached the node, so the transaction is then looked up by
+    /// its hash: it is sent when the node knows it, and ambiguous when it cannot be told.
+    async fn send(
+        &self,
+        account: &StarknetAccount,
+        calls: Vec<Call>,
+    ) -> Result<InvokeTransactionResult> {
+        for call in &calls {
+            debug!(
+                "Execute transaction, contract_address: {}, selector: {}, calldata: {:?}",
+                call.to, call.selector, call.calldata
+            );
+        }
+
+        let nonce =
+            self.retry.run("get_nonce", is_transient, || account.get_nonce()).await.map_err(
+                |e| anyhow!(provider_error(e)).context("Failed to get the account nonce"),
+            )?;
+
+        let estimation = || account.execute_v3(calls.clone()).nonce(nonce);
+        let estimate = self
+            .retry
+            .run(
+                "estimate_fee",
+                |e| matches!(e, AccountError::Provider(e) if is_transient(e)),
+                || async move { estimation().estimate_fee().await },
+            )
+            .await
+            .map_err(|e| anyhow!(account_error(e)).context("Failed to estimate fee"))?;
+        let bounds = self.fee_bounds(&estimate);
+        let max_fee = bounds.max_fee();
+        if let Some(cap) = self.max_fee.filter(|cap| max_fee > *cap) {
+            bail!(
+                "Transaction rejected, it could cost up to {max_fee} FRI, above the cap of {cap} FRI"
+            );
+        }
+        debug!("Estimated fee: {} FRI, up to {max_fee} FRI", estimate.overall_fee);
+
+        let execution = || {
+            account
+                .execute_v3(calls.clone())
+                .nonce(nonce)
+                .l1_gas(bounds.l1_gas)
+                .l1_gas_price(bounds.l1_gas_price)
+                .l2_gas(bounds.l2_gas)
+                .l2_gas_price(bounds.l2_gas_price)
+                .l1_data_gas(bounds.l1_data_gas)
+                .l1_data_gas_price(bounds.l1_data_gas_price)
+        };
+
+        if self.simulate {
+            let simulated = self
+                .retry
+                .run(
+                    "simulate",
+                    |e| matches!(e, AccountError::Provider(e) if is_transient(e)),
+                    || async move { execution().simulate(false, false).await },
+                )
+                .await
+                .map_err(|e| anyhow!(account_error(e)).context("Failed to simulate transaction"))?;
+            if let TransactionTrace::Invoke(InvokeTransactionTrace {
+                execute_invocation: ExecuteInvocation::Reverted(reverted),
+                ..
+            }) = &simulated.transaction_trace
+            {
+                return Err(anyhow!(ContractError::Reverted(reverted.revert_reason.clone()))
+                    .context("Transaction simulation reverted"));
+            }
+            info!("Transaction simulated, fee: {} FRI", simulated.fee_estimation.overall_fee);
+        }
 
-        // Create function call object
-        let calls = vec![Call { to: *contract_address, selector: *selector, calldata }];
+        let transaction_hash = execution()
+            .prepared()
+            .map_err(|e| anyhow!("Failed to prepare transaction: {e:?}"))?
+            .transaction_hash(false);
+        if self.dry_run {
+            warn!("Dry run, transaction 0x{transaction_hash:x} not sent");
+            return Err(ContractError::DryRun(from_felt(&transaction_hash)?).into());
+        }
 
         // Execute transaction
-        let result = self.account.execute_v3(calls).send().await?;
+        let sent = self
+            .retry
+            .run(
+                "execute",
+                |e| matches!(e, AccountError::Provider(e) if is_transient(e)),
+                || async move { execution().send().await },
+            )
+            .await;
+        let result = match sent {
+            Ok(result) => result,
+            Err(e) => {
+                let e = anyhow!(account_error(e)).context("Failed to send transaction");
+                return match self.provider.get_transaction_status(transaction_hash).await {
+                    Ok(_) => {
+                        warn!("Transaction 0x{transaction_hash:x} was received despite: {e:#}");
+                        Ok(InvokeTransactionResult { transaction_hash })
+                    }
+                    Err(ProviderError::StarknetError(StarknetError::TransactionHashNotFound)) => {
+                        Err(e)
+                    }
+                    Err(_) => Err(ContractError::Ambiguous {
+                        tx_hash: from_felt(&transaction_hash)?,
+                        reason: format!("{e:#}"),
+                    }
+                    .into()),
+                };
+            }
+        };
         info!("Transaction sent! Transaction hash: 0x{:x}", result.transaction_hash);
 
-        // Print Starkscan link
-        info!("Transaction submitted to network. View transaction status on Starkscan:");
-        info!("https://sepolia.starkscan.co/tx/0x{:x}", result.transaction_hash);
+        info!(
+            "Transaction submitted to network, view its status on {}",
+            self.explorer_tx_url(&format!("{:#x}", result.transaction_hash))
+        );
 
         Ok(result)
     }
+
+    /// Scale a fee estimate by the multipliers.
+    fn fee_bounds(&self, estimate: &FeeEstimate) -> FeeBounds {
+        let gas = |amount: u64| (amount as f64 * self.fee_gas_multiplier).ceil() as u64;
+        let price = |amount: u128| (amount as f64 * self.fee_price_multiplier).ceil() as u128;
+
+        FeeBounds {
+            l1_gas: gas(estimate.l1_gas_consumed),
+            l1_gas_price: price(estimate.l1_gas_price),
+            l2_gas: gas(estimate.l2_gas_consumed),
+            l2_gas_price: price(estimate.l2_gas_price),
+            l1_data_gas: gas(estimate.l1_data_gas_consumed),
+            l1_data_gas_price: price(estimate.l1_data_gas_price),
+        }
+    }
+}
+
+/// The resource bounds of a transaction, the most it can consume and pay for each resource.
+struct FeeBounds {
+    l1_gas: u64,
+    l1_gas_price: u128,
+    l2_gas: u64,
+    l2_gas_price: u128,
+    l1_data_gas: u64,
+    l1_data_gas_price: u128,
+}
+
+impl FeeBounds {
+    /// The highest fee the bounds allow, in FRI.
+    fn max_fee(&self) -> u128 {
+        [
+            (self.l1_gas, self.l1_gas_price),
+            (self.l2_gas, self.l2_gas_price),
+            (self.l1_data_gas, self.l1_data_gas_price),
+        ]
+        .into_iter()
+        .fold(0u128, |fee, (gas, price)| fee.saturating_add((gas as u128).saturating_mul(price)))
+    }
+}
+
+/// Whether a provider error is worth retrying, like a network error or a rate limit,
+/// unlike the errors returned by the node, which would be returned again.
+fn is_transient(error: &ProviderError) -> bool {
+    matches!(error, ProviderError::RateLimited | ProviderError::Other(_))
+}
+
+/// Encode a call with hex encoded fields.
+fn to_raw_call(call: Call) -> Result<RawCall> {
+    Ok(RawCall {
+        to: from_felt(&call.to)?,
+        selector: format!("{:#x}", call.selector),
+        calldata: call.calldata.iter().map(|felt| format!("{felt:#x}")).collect(),
+    })
+}
+
+/// A felt hex encoded, as an address, hash or id.
+fn from_felt<T: FromStr<Err = ContractError>>(felt: &Felt) -> Result<T, ContractError> {
+    format!("{felt:#x}").parse()
+}
+
+/// Decode a call with hex encoded fields.
+fn from_raw_call(call: RawCall) -> Result<Call> {
+    Ok(Call {
+        to: Felt::from_hex(&call.to)?,
+        selector: Felt::from_hex(&call.selector)?,
+        calldata: call
+            .calldata
+            .iter()
+            .map(|felt| Felt::from_hex(felt))
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+/// Decode an event of the Workflow contract, `None` for the events which are not indexed.
+fn decode_workflow_event(event: EmittedEvent) -> Result<Option<EmittedWorkflowEvent>> {
+    let event_name = event.keys.first().copied().unwrap_or_default();
+
+    let decoded = if event_name == DependencyCreated::SELECTOR {
+        let created = DependencyCreated::decode(&event.keys, &event.data)
+            .map_err(|e| anyhow!("Malformed DependencyCreated event: {e}"))?;
+        WorkflowEvent::DependencyCreated {
+            github_owner: from_felt(&created.github_owner)?,
+            workflow_id: from_felt(&created.workflow_id)?,
+            dependency_idx: from_felt(&created.dependency_idx)?,
+            name: parse_cairo_short_string(&created.name)?,
+            repository_url: parse_cairo_short_string(&created.repository_url)?,
+        }
+    } else if event_name == StepAdded::SELECTOR {
+        let added = StepAdded::decode(&event.keys, &event.data)
+            .map_err(|e| anyhow!("Malformed StepAdded event: {e}"))?;
+        let step_type = StepType::try_from(added.step_type)?;
+        WorkflowEvent::StepAdded {
+            github_owner: from_felt(&added.github_owner)?,
+            workflow_id: from_felt(&added.workflow_id)?,
+            dependency_idx: from_felt(&added.dependency_idx)?,
+            step_index: from_felt(&added.step_index)?,
+            step_type,
+            tx_hash: from_felt(&added.tx_hash)?,
+            related_entity_id: from_felt(&added.related_entity_id)?,
+            timestamp: added.timestamp,
+        }
+    } else {
+        return Ok(None);
+    };
+
+    Ok(Some(EmittedWorkflowEvent {
+        event: decoded,
+        block_number: event.block_number.unwrap_or_default(),
+        transaction_hash: from_felt(&event.transaction_hash)?,
+    }))
+}
+
+/// The name of a chain id, eg. `SN_SEPOLIA`, in hex if it is not a short string.
+fn chain_name(chain_id: Felt) -> String {
+    parse_cairo_short_string(&chain_id).unwrap_or_else(|_| format!("{chain_id:#x}"))
+}
+
+/// The base URL of the block explorer, as configured or Starkscan on the network.
+fn explorer_url(config: &StarknetConfig) -> String {
+    match &config.starknet_explorer_url {
+        Some(url) => url.trim_end_matches('/').to_string(),
+        None if config.starknet_chain_id == MAINNET_CHAIN_ID => "https://starkscan.co".to_string(),
+        None => "https://sepolia.starkscan.co".to_string(),
+    }
+}
+
+fn to_workflow(details: abi::workflow::WorkflowDetails) -> Result<Workflow> {
+    Ok(Workflow {
+        owner: parse_cairo_short_string(&details.owner)?.parse()?,
+        wallet_address: from_felt(&details.wallet_address)?,
+        status: WorkflowStatus::try_from(details.status)?,
+        created_at: details.created_at,
+        last_updated_at: details.last_updated_at,
+    })
+}
+
+fn to_step(details: abi::workflow::StepDetails) -> Result<Step> {
+    Ok(Step {
+        step_type: StepType::try_from(details.step_type)?,
+        tx_hash: from_felt(&details.tx_hash)?,
+        related_entity_id: details.related_entity_id.to_string().parse()?,
+        timestamp: details.timestamp,
+        prev_step_index: details.prev_step_index.to_string().parse()?,
+    })
+}
+
+fn to_dependency(details: abi::workflow::DependencyDetails) -> Result<Dependency> {
+    Ok(Dependency {
+        name: parse_cairo_short_string(&details.name)?,
+        repository_url: parse_cairo_short_string(&details.repository_url)?,
+        license: parse_cairo_short_string(&details.license)?,
+        metadata_json: parse_cairo_short_string(&details.metadata_json)?,
+        status: WorkflowStatus::try_from(details.status)?,
+        created_at: details.created_at,
+        last_updated_at: details.last_updated_at,
+    })
+}
+
+/// Classify a failed contract call, the errors returned by the node meaning the call was
+/// rejected rather than the node being unreachable.
+fn provider_error(error: ProviderError) -> ContractError {
+    match error {
+        ProviderError::StarknetError(e) => ContractError::Reverted(format!("{e:?}")),
+        e => ContractError::RpcError(format!("{e:?}")),
+    }
+}
+
+/// Classify a failed transaction like a failed call.
+fn account_error<S: std::fmt::Debug>(error: AccountError<S>) -> ContractError {
+    match error {
+        AccountError::Provider(e) => provider_error(e),
+        e => ContractError::RpcError(format!("{e:?}")),
+    }
 }
 
 impl Contract for StarknetContract {
-    fn chain() -> &'static str {
+    fn chain(&self) -> &'static str {
         "Starknet"
     }
+
+    fn chain_id(&self) -> String {
+        self.chain_id.clone()
+    }
+
+    fn explorer_tx_url(&self, tx_hash: &str) -> String {
+        format!("{}/tx/{tx_hash}", self.explorer_url)
+    }
+
+    fn explorer_contract_url(&self, address: &str) -> String {
+        format!("{}/contract/{address}", self.explorer_url)
+    }
 }
 
+#[async_trait]
 impl AllocationContract for StarknetContract {
+    #[instrument(skip_all, fields(workflow_id = %workflow_id, sign_id = %sign_id))]
     async fn create_allocation(
         &self,
         workflow_id: Id,
@@ -307,26 +734,53 @@ impl AllocationContract for StarknetContract {
         recipient: Address,
         amount: Number,
         token_address: Address,
-    ) -> Result<Id> {
+    ) -> Result<Option<Id>> {
         info!("Starting allocation creation");
 
-        let workflow_id = Felt::from_str(&workflow_id).expect("Invalid workflow id");
-        let sign_id = Felt::from_str(&sign_id).expect("Invalid sign id");
-        let recipient = Felt::from_hex(&recipient).expect("Invalid recipient");
-        let amount = Felt::from_str(&amount).expect("Invalid amount");
-        let token_address = Felt::from_hex(&token_address).expect("Invalid token_address");
+        let workflow_id = parse_id("workflow id", &workflow_id)?;
+        let sign_id = parse_id("sign id", &sign_id)?;
+        let recipient = parse_address(&recipient)?;
+        let amount = parse_amount(&amount)?;
+        let token_address = parse_address(&token_address)?;
 
         let _ = self
-            .execute(
-                &self.allocation_contract_address,
-                &selector!("create_allocation"),
-                vec![workflow_id, sign_id, recipient, amount, token_address],
-            )
+            .multicall(vec![abi::allocation::create_allocation(
+                self.allocation_contract_address,
+                workflow_id,
+                sign_id,
+                recipient,
+                amount,
+                token_address,
+            )?])
             .await?;
 
-        Ok(Id::new())
+        Ok(None)
+    }
+
+    #[instrument(skip_all, fields(count = allocations.len()))]
+    async fn create_allocations_batch(
+        &self,
+        allocations: Vec<AllocationInput>,
+    ) -> Result<Vec<Option<Id>>> {
+        info!("Starting batch allocation creation");
+
+        let mut calls = Vec::with_capacity(allocations.len());
+        for allocation in &allocations {
+            calls.push(abi::allocation::create_allocation(
+                self.allocation_contract_address,
+                parse_id("workflow id", &allocation.workflow_id)?,
+                parse_id("sign id", &allocation.sign_id)?,
+                parse_address(&allocation.recipient)?,
+                parse_amount(&allocation.amount)?,
+                parse_address(&allocation.token_address)?,
+            )?);
+        }
+        let _ = self.multicall(calls).await?;
+
+        Ok(vec![None; allocations.len()])
     }
 
+    #[instrument(skip_all, fields(allocation_id = %allocation_id))]
     async fn update_allocation_status(
         &self,
         allocation_id: Id,
@@ -334,273 +788,620 @@ impl AllocationContract for StarknetContract {
     ) -> Result<bool> {
         info!("Starting update allocation status");
 
-        let allocation_id = Felt::from_str(&allocation_id).expect("Invalid allocation id");
-        let status = Felt::from_str(&status.to_string()).expect("Invalid status");
+        let allocation_id = parse_id("allocation id", &allocation_id)?;
+        let status = Felt::from(status);
 
         let _ = self
-            .execute(
-                &self.allocation_contract_address,
-                &selector!("update_allocation_status"),
-                vec![allocation_id, status],
-            )
+            .multicall(vec![abi::allocation::update_allocation_status(
+                self.allocation_contract_address,
+                allocation_id,
+                status,
+            )?])
             .await?;
 
         Ok(true)
     }
 
+    #[instrument(skip_all, fields(allocation_id = %allocation_id, token = %token_address))]
+    async fn execute_allocation(
+        &self,
+        allocation_id: Id,
+        token_address: Address,
+        amount: Number,
+    ) -> Result<Hash> {
+        info!("Starting execute allocation");
+
+        let calls = self.execute_allocation_calls(allocation_id, token_address, amount)?;
+        self.execute_calls(calls).await
+    }
+
+    fn execute_allocation_calls(
+        &self,
+        allocation_id: Id,
+        token_address: Address,
+        amount: Number,
+    ) -> Result<Vec<RawCall>> {
+        let allocation_id = parse_id("allocation id", &allocation_id)?;
+        let token_address = parse_address(&token_address)?;
+        let amount = parse_amount(&amount)?;
+
+        Ok(vec![
+            to_raw_call(abi::erc20::approve(
+                token_address,
+                self.allocation_contract_address,
+                amount,
+            )?)?,
+            to_raw_call(abi::allocation::execute_allocation(
+                self.allocation_contract_address,
+                allocation_id,
+            )?)?,
+        ])
+    }
+
+    #[instrument(skip_all, fields(allocation_id = %allocation_id, tx_hash = %tx_hash))]
+    async fn update_allocation_tx_hash(&self, allocation_id: Id, tx_hash: Hash) -> Result<()> {
+        info!("Starting update allocation tx hash");
+
+        let call = self.update_allocation_tx_hash_call(allocation_id, tx_hash)?;
+        let _ = self.execute_calls(vec![call]).await?;
+
+        Ok(())
+    }
+
+    fn update_allocation_tx_hash_call(&self, allocation_id: Id, tx_hash: Hash) -> Result<RawCall> {
+        let allocation_id = parse_id("allocation id", &allocation_id)?;
+        let tx_hash = parse_hash(&tx_hash)?;
+
+        to_raw_call(abi::allocation::update_tx_hash(
+            self.allocation_contract_address,
+            allocation_id,
+            tx_hash,
+        )?)
+    }
+
+    fn claim_call(&self, allocation_id: Id) -> Result<RawCall> {
+        let allocation_id = parse_id("allocation id", &allocation_id)?;
+
+        to_raw_call(abi::allocation::claim(self.allocation_contract_address, allocation_id)?)
+    }
+
+    #[instrument(skip_all, fields(allocation_id = %allocation_id))]
     async fn get_allocation_details(&self, allocation_id: Id) -> Result<Allocation> {
-        info!("Starting update allocation status");
+        info!("Starting get allocation details");
 
-        let allocation_id = Felt::from_str(&allocation_id).expect("Invalid allocation id");
+        let allocation_id = parse_id("allocation id", &allocation_id)?;
 
-        let _ = self
-            .call(
-                &self.allocation_contract_address,
-                &selector!("get_allocation_details"),
-                vec![allocation_id],
-            )
+        let result = self
+            .call(abi::allocation::get_allocation_details(
+                self.allocation_contract_address,
+                allocation_id,
+            )?)
             .await?;
-
-        todo!()
+        let details = abi::allocation::decode_get_allocation_details(&result)?;
+
+        Ok(Allocation {
+            workflow_id: details.workflow_id.to_string().parse()?,
+            sign_id: details.sign_id.to_string().parse()?,
+            recipient: from_felt(&details.recipient)?,
+            amount: details.amount.to_string(),
+            token_address: from_felt(&details.token_address)?,
+            tx_hash: from_felt(&details.tx_hash)?,
+            created_at: details.created_at,
+            status: AllocationStatus::try_from(details.status)?,
+        })
     }
 
+    #[instrument(skip_all, fields(sign_id = %sign_id))]
     async fn get_allocation_by_sign(&self, sign_id: Id) -> Result<Id> {
         info!("Starting get allocation by sign");
 
-        let sign_id = Felt::from_str(&sign_id).expect("Invalid sign id");
+        let sign_id = parse_id("sign id", &sign_id)?;
 
-        let _ = self
-            .call(
-                &self.allocation_contract_address,
-                &selector!("get_allocation_by_sign"),
-                vec![sign_id],
-            )
+        let result = self
+            .call(abi::allocation::get_allocation_by_sign(
+                self.allocation_contract_address,
+                sign_id,
+            )?)
             .await?;
 
-        todo!()
+        Ok(abi::allocation::decode_get_allocation_by_sign(&result)?.to_string().parse()?)
     }
 }
 
+#[async_trait]
+impl TokenContract for StarknetContract {
+    fn account_address(&self) -> Address {
+        // A felt is always a valid Starknet address.
+        from_felt(&self.accounts.treasury().address()).expect("invalid treasury address")
+    }
+
+    fn is_valid_address(&self, address: &Address) -> bool {
+        Felt::from_hex(address).is_ok_and(|address| address != Felt::ZERO)
+    }
+
+    fn native_token_address(&self, token: NativeToken) -> Address {
+        match token {
+            NativeToken::Strk => STRK_TOKEN_ADDRESS.parse().expect("invalid STRK address"),
+            NativeToken::Eth => ETH_TOKEN_ADDRESS.parse().expect("invalid ETH address"),
+        }
+    }
+
+    fn fee_token(&self) -> NativeToken {
+        // V3 transactions pay their fees in STRK.
+        NativeToken::Strk
+    }
+
+    #[instrument(skip_all, fields(token = %token_address))]
+    async fn decimals(&self, token_address: Address) -> Result<u8> {
+        let token_address = parse_address(&token_address)?;
+
+        let result = self.call(abi::erc20::decimals(token_address)?).await?;
+
+        abi::erc20::decode_decimals(&result)
+    }
+
+    #[instrument(skip_all, fields(token = %token_address, owner = %owner))]
+    async fn balance_of(&self, token_address: Address, owner: Address) -> Result<Number> {
+        info!("Starting get balance");
+
+        let token_address = parse_address(&token_address)?;
+        let owner = parse_address(&owner)?;
+
+        let result = self.call(abi::erc20::balance_of(token_address, owner)?).await?;
+
+        Ok(abi::erc20::decode_balance_of(&result)?.to_string())
+    }
+
+    #[instrument(skip_all, fields(token = %token_address, owner = %owner, spender = %spender))]
+    async fn allowance(
+        &self,
+        token_address: Address,
+        owner: Address,
+        spender: Address,
+    ) -> Result<Number> {
+        let token_address = parse_address(&token_address)?;
+        let owner = parse_address(&owner)?;
+        let spender = parse_address(&spender)?;
+
+        let result = self.call(abi::erc20::allowance(token_address, owner, spender)?).await?;
+
+        Ok(abi::erc20::decode_allowance(&result)?.to_string())
+    }
+
+    #[instrument(skip_all, fields(token = %token_address, recipient = %recipient, amount = %amount))]
+    async fn transfer(
+        &self,
+        token_address: Address,
+        recipient: Address,
+        amount: Number,
+    ) -> Result<Hash> {
+        info!("Starting token transfer");
+
+        let call = self.transfer_call(token_address, recipient, amount)?;
+        self.execute_calls(vec![call]).await
+    }
+
+    fn transfer_call(
+        &self,
+        token_address: Address,
+        recipient: Address,
+        amount: Number,
+    ) -> Result<RawCall> {
+        let token_address = parse_address(&token_address)?;
+        let recipient = parse_address(&recipient)?;
+        let amount = parse_amount(&amount)?;
+
+        to_raw_call(abi::erc20::transfer(token_address, recipient, amount)?)
+    }
+
+    fn transfer_from_call(
+        &self,
+        token_address: Address,
+        owner: Address,
+        recipient: Address,
+        amount: Number,
+    ) -> Result<RawCall> {
+        let token_address = parse_address(&token_address)?;
+        let owner = parse_address(&owner)?;
+        let recipient = parse_address(&recipient)?;
+        let amount = parse_amount(&amount)?;
+
+        to_raw_call(abi::erc20::transfer_from(token_address, owner, recipient, amount)?)
+    }
+}
+
+#[async_trait]
+impl TransactionContract for StarknetContract {
+    #[instrument(skip_all, fields(tx_hash = %tx_hash))]
+    async fn transaction_status(&self, tx_hash: Hash) -> Result<TransactionStatus> {
+        let tx_hash = parse_hash(&tx_hash)?;
+
+        let status = match self.provider.get_transaction_status(tx_hash).await {
+            Ok(status) => status,
+            Err(ProviderError::StarknetError(StarknetError::TransactionHashNotFound)) => {
+                return Ok(TransactionStatus::NotFound)
+            }
+            Err(e) => return Err(anyhow!("Failed to get transaction status: {:?}", e)),
+        };
+
+        Ok(match status {
+            StarknetTransactionStatus::AcceptedOnL2(result)
+            | StarknetTransactionStatus::AcceptedOnL1(result) => match result {
+                ExecutionResult::Succeeded => TransactionStatus::Succeeded,
+                ExecutionResult::Reverted { reason } => TransactionStatus::Reverted(reason),
+            },
+            _ => TransactionStatus::Pending,
+        })
+    }
+
+    /// The raw calls may spend the tokens of the treasury, like the payouts, so the treasury
+    /// account sends them.
+    async fn execute_calls(&self, calls: Vec<RawCall>) -> Result<Hash> {
+        let calls = calls.into_iter().map(from_raw_call).collect::<Result<_>>()?;
+        let account = self.accounts.lease_treasury().await;
+        let result = self.multicall_from(&account, calls).await?;
+
+        Ok(from_felt(&result.transaction_hash)?)
+    }
+
+    #[instrument(skip_all, fields(calls = calls.len()))]
+    async fn estimate_fee(&self, calls: Vec<RawCall>) -> Result<Number> {
+        let calls = calls.into_iter().map(from_raw_call).collect::<Result<_>>()?;
+        let estimate = self
+            .accounts
+            .treasury()
+            .execute_v3(calls)
+            .estimate_fee()
+            .await
+            .map_err(|e| anyhow!(account_error(e)).context("Failed to estimate fee"))?;
+
+        Ok(estimate.overall_fee.to_string())
+    }
+
+    async fn block_number(&self) -> Result<u64> {
+        self.provider
+            .block_number()
+            .await
+            .map_err(|e| anyhow!("Failed to get block number: {:?}", e))
+    }
+
+    /// Every endpoint of the pool is checked, as any of them may be failed over to. The
+    /// endpoints which cannot be reached are skipped, with a warning.
+    async fn check_chain_id(&self) -> Result<()> {
+        let expected = self.accounts.treasury().chain_id();
+        for (url, chain_id) in self.pool.chain_ids().await {
+            match chain_id {
+                Ok(chain_id) if chain_id == expected => {}
+                Ok(chain_id) => bail!(
+                    "STARKNET_CHAIN_ID is {} but the RPC endpoint {url} is on {}",
+                    chain_name(expected),
+                    chain_name(chain_id)
+                ),
+                Err(e) => warn!(%url, "Failed to get the chain id of the RPC endpoint: {e:#}"),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
 impl InquireContract for StarknetContract {
+    #[instrument(skip_all, fields(workflow_id = %workflow_id))]
     async fn create_inquire(
         &self,
         workflow_id: Id,
         inquirer: Address,
         inquiree: Address,
         question: String,
-    ) -> Result<Id> {
+    ) -> Result<Option<Id>> {
         info!("Starting inquire creation");
 
-        let workflow_id = Felt::from_str(&workflow_id).expect("Invalid workflow id");
-        let inquirer = Felt::from_hex(&inquirer).expect("Invalid inquirer");
-        let inquiree = Felt::from_hex(&inquiree).expect("Invalid inquiree");
-        let question = Felt::from_str(&question).expect("Invalid question");
+        let workflow_id = parse_id("workflow id", &workflow_id)?;
+        let inquirer = parse_address(&inquirer)?;
+        let inquiree = parse_address(&inquiree)?;
+        let question = parse_text("question", &question)?;
 
         let _ = self
-            .execute(
-                &self.inquire_contract_address,
-                &selector!("create_inquire"),
-                vec![workflow_id, inquirer, inquiree, question],
-            )
+            .multicall(vec![abi::inquire::create_inquire(
+                self.inquire_contract_address,
+                workflow_id,
+                inquirer,
+                inquiree,
+                question,
+            )?])
             .await?;
 
-        Ok(Id::new())
+        Ok(None)
     }
 
+    #[instrument(skip_all, fields(inquire_id = %inquire_id))]
     async fn respond_to_inquire(&self, inquire_id: Id, response: String) -> Result<bool> {
         info!("Starting respond to inquire");
 
-        let inquire_id = Felt::from_str(&inquire_id).expect("Invalid inquire id");
-        let response = Felt::from_str(&response).expect("Invalid response");
+        let inquire_id = parse_id("inquire id", &inquire_id)?;
+        let response = parse_text("response", &response)?;
 
         let _ = self
-            .execute(
-                &self.inquire_contract_address,
-                &selector!("respond_to_inquire"),
-                vec![inquire_id, response],
-            )
+            .multicall(vec![abi::inquire::respond_to_inquire(
+                self.inquire_contract_address,
+                inquire_id,
+                response,
+            )?])
             .await?;
 
         Ok(true)
     }
 
+    #[instrument(skip_all, fields(inquire_id = %inquire_id))]
     async fn reject_inquire(&self, inquire_id: Id) -> Result<bool> {
         info!("Starting reject inquire");
 
-        let inquire_id = Felt::from_str(&inquire_id).expect("Invalid inquire id");
+        let inquire_id = parse_id("inquire id", &inquire_id)?;
 
         let _ = self
-            .execute(&self.inquire_contract_address, &selector!("reject_inquire"), vec![inquire_id])
+            .multicall(vec![abi::inquire::reject_inquire(
+                self.inquire_contract_address,
+                inquire_id,
+            )?])
             .await?;
 
         Ok(true)
     }
 
+    #[instrument(skip_all, fields(inquire_id = %inquire_id))]
     async fn get_inquire_details(&self, inquire_id: Id) -> Result<Inquire> {
         info!("Starting get inquire details");
 
-        let inquire_id = Felt::from_str(&inquire_id).expect("Invalid inquire id");
+        let inquire_id = parse_id("inquire id", &inquire_id)?;
 
-        let _ = self
-            .execute(
-                &self.inquire_contract_address,
-                &selector!("get_inquire_details"),
-                vec![inquire_id],
-            )
+        let result = self
+            .call(abi::inquire::get_inquire_details(self.inquire_contract_address, inquire_id)?)
             .await?;
 
-        todo!()
+        let details = abi::inquire::decode_get_inquire_details(&result)?;
+        Ok(Inquire {
+            workflow_id: details.workflow_id.to_string().parse()?,
+            inquirer: from_felt(&details.inquirer)?,
+            inquiree: from_felt(&details.inquiree)?,
+            question: parse_cairo_short_string(&details.question)?,
+            response: parse_cairo_short_string(&details.response)?,
+            status: InquireStatus::try_from(details.status)?,
+            created_at: details.created_at,
+            responded_at: details.responded_at,
+        })
     }
 }
 
+#[async_trait]
 impl ReceiptContract for StarknetContract {
+    #[instrument(skip_all, fields(workflow_id = %workflow_id))]
     async fn create_receipt(
         &self,
         workflow_id: Id,
         dependency_url: String,
-        _metadata: ReceiptMetadata,
+        metadata: ReceiptMetadata,
         metadata_hash: Hash,
-        metadata_uri: Hash,
-    ) -> Result<Id> {
+        metadata_uri: String,
+    ) -> Result<Option<Id>> {
         info!("Starting receipt creation");
 
-        let workflow_id = Felt::from_str(&workflow_id).expect("Invalid workflow id");
-        let dependency_url = Felt::from_str(&dependency_url).expect("Invalid dependency url");
-        // let metadata = Felt::from_hex(&metadata).expect("Invalid metadata");
-        let metadata_hash = Felt::from_hex(&metadata_hash).expect("Invalid metadata hash");
-        let metadata_uri = Felt::from_str(&metadata_uri).expect("Invalid metadata uri");
+        let call = self.create_receipt_call(
+            workflow_id,
+            dependency_url,
+            metadata,
+            metadata_hash,
+            metadata_uri,
+        )?;
+        let _ = self.execute_calls(vec![call]).await?;
 
-        let _ = self
-            .execute(
-                &self.receipt_contract_address,
-                &selector!("create_receipt"),
-                vec![workflow_id, dependency_url, /* metadata, */ metadata_hash, metadata_uri],
-            )
-            .await?;
+        Ok(None)
+    }
 
-        Ok(Id::new())
+    fn create_receipt_call(
+        &self,
+        workflow_id: Id,
+        dependency_url: String,
+        _metadata: ReceiptMetadata,
+        metadata_hash: Hash,
+        metadata_uri: String,
+    ) -> Result<RawCall> {
+        let workflow_id = parse_id("workflow id", &workflow_id)?;
+        let dependency_url = parse_text("dependency url", &dependency_url)?;
+        // let metadata = Felt::from_hex(&metadata).expect("Invalid metadata");
+        let metadata_hash = parse_hash(&metadata_hash)?;
+        let metadata_uri = parse_text("metadata uri", &metadata_uri)?;
+
+        to_raw_call(abi::receipt::create_receipt(
+            self.receipt_contract_address,
+            workflow_id,
+            dependency_url,
+            /* metadata, */ metadata_hash,
+            metadata_uri,
+        )?)
     }
 
+    #[instrument(skip_all, fields(receipt_id = %receipt_id))]
     async fn get_receipt_details(&self, receipt_id: Id) -> Result<(Receipt, ReceiptMetadata)> {
         info!("Starting get receipt details");
 
-        let receipt_id = Felt::from_str(&receipt_id).expect("Invalid receipt id");
+        let receipt_id = parse_id("receipt id", &receipt_id)?;
 
-        let _ = self
-            .call(
-                &self.receipt_contract_address,
-                &selector!("get_receipt_details"),
-                vec![receipt_id],
-            )
+        let result = self
+            .call(abi::receipt::get_receipt_details(self.receipt_contract_address, receipt_id)?)
             .await?;
 
-        todo!()
+        let (details, metadata) = abi::receipt::decode_get_receipt_details(&result)?;
+        let receipt = Receipt {
+            workflow_id: details.workflow_id.to_string().parse()?,
+            dependency_url: parse_cairo_short_string(&details.dependency_url)?,
+            tx_hash: from_felt(&details.tx_hash)?,
+            created_at: details.created_at,
+            metadata_hash: from_felt(&details.metadata_hash)?,
+            metadata_uri: parse_cairo_short_string(&details.metadata_uri)?,
+        };
+        let metadata = ReceiptMetadata {
+            name: parse_cairo_short_string(&metadata.name)?,
+            version: parse_cairo_short_string(&metadata.version)?,
+            author: parse_cairo_short_string(&metadata.author)?,
+            license: parse_cairo_short_string(&metadata.license)?,
+        };
+        Ok((receipt, metadata))
     }
 
+    #[instrument(skip_all, fields(receipt_id = %receipt_id))]
     async fn verify_metadata(&self, receipt_id: Id, provided_hash: Hash) -> Result<bool> {
         info!("Starting verify metadata");
 
-        let receipt_id = Felt::from_str(&receipt_id).expect("Invalid receipt id");
-        let provided_hash = Felt::from_hex(&provided_hash).expect("Invalid provided hash");
+        let receipt_id = parse_id("receipt id", &receipt_id)?;
+        let provided_hash = parse_hash(&provided_hash)?;
 
-        let _ = self
-            .call(
-                &self.receipt_contract_address,
-                &selector!("verify_metadata"),
-                vec![receipt_id, provided_hash],
-            )
+        let result = self
+            .call(abi::receipt::verify_metadata(
+                self.receipt_contract_address,
+                receipt_id,
+                provided_hash,
+            )?)
             .await?;
 
-        todo!()
+        abi::receipt::decode_verify_metadata(&result)
     }
 
+    #[instrument(skip_all, fields(receipt_id = %receipt_id))]
     async fn update_tx_hash(&self, receipt_id: Id, tx_hash: Hash) -> Result<()> {
         info!("Starting update tx hash");
 
-        let receipt_id = Felt::from_str(&receipt_id).expect("Invalid receipt id");
-        let tx_hash = Felt::from_hex(&tx_hash).expect("Invalid transaction hash");
+        let receipt_id = parse_id("receipt id", &receipt_id)?;
+        let tx_hash = parse_hash(&tx_hash)?;
 
         let _ = self
-            .execute(
-                &self.receipt_contract_address,
-                &selector!("update_tx_hash"),
-                vec![receipt_id, tx_hash],
-            )
+            .multicall(vec![abi::receipt::update_tx_hash(
+                self.receipt_contract_address,
+                receipt_id,
+                tx_hash,
+            )?])
             .await?;
 
         Ok(())
     }
 }
 
+#[async_trait]
 impl SignContract for StarknetContract {
+    #[instrument(skip_all, fields(workflow_id = %workflow_id, inquire_id = %inquire_id))]
     async fn create_sign(
         &self,
         workflow_id: Id,
         inquire_id: Id,
         signer: Address,
         signature_hash: Hash,
-    ) -> Result<Id> {
+    ) -> Result<Option<Id>> {
         info!("Starting sign creation");
 
-        let workflow_id = Felt::from_str(&workflow_id).expect("Invalid workflow id");
-        let inquire_id = Felt::from_str(&inquire_id).expect("Invalid inquire id");
-        let signer = Felt::from_hex(&signer).expect("Invalid signer");
-        let signature_hash = Felt::from_hex(&signature_hash).expect("Invalid signature hash");
+        let workflow_id = parse_id("workflow id", &workflow_id)?;
+        let inquire_id = parse_id("inquire id", &inquire_id)?;
+        let signer = parse_address(&signer)?;
+        let signature_hash = parse_hash(&signature_hash)?;
 
         let _ = self
-            .execute(
-                &self.sign_contract_address,
-                &selector!("create_sign"),
-                vec![workflow_id, inquire_id, signer, signature_hash],
-            )
+            .multicall(vec![abi::sign::create_sign(
+                self.sign_contract_address,
+                workflow_id,
+                inquire_id,
+                signer,
+                signature_hash,
+            )?])
             .await?;
 
-        Ok(Id::new())
+        Ok(None)
     }
 
+    #[instrument(skip_all, fields(sign_id = %sign_id))]
     async fn get_sign_details(&self, sign_id: Id) -> Result<Sign> {
         info!("Starting get sign details");
 
-        let sign_id = Felt::from_str(&sign_id).expect("Invalid sign id");
+        let sign_id = parse_id("sign id", &sign_id)?;
 
-        let _ = self
-            .call(&self.sign_contract_address, &selector!("get_sign_details"), vec![sign_id])
-            .await?;
+        let _ =
+            self.call(abi::sign::get_sign_details(self.sign_contract_address, sign_id)?).await?;
 
         todo!()
     }
 
+    #[instrument(skip_all, fields(inquire_id = %inquire_id))]
     async fn get_sign_by_inquire(&self, inquire_id: Id) -> Result<Id> {
         info!("Starting get sign by inquire");
 
-        let inquire_id = Felt::from_str(&inquire_id).expect("Invalid inquire id");
+        let inquire_id = parse_id("inquire id", &inquire_id)?;
 
-        let _ = self
-            .call(&self.sign_contract_address, &selector!("get_sign_by_inquire"), vec![inquire_id])
+        let result = self
+            .call(abi::sign::get_sign_by_inquire(self.sign_contract_address, inquire_id)?)
             .await?;
 
-        todo!()
+        Ok(abi::sign::decode_get_sign_by_inquire(&result)?.to_string().parse()?)
+    }
+
+    /// The account implements SNIP-6, returning `VALID` for a valid signature, or `1` with
+    /// the accounts predating it. Accounts may also revert on an invalid signature.
+    #[instrument(skip_all, fields(signer = %signer))]
+    async fn is_valid_signature(
+        &self,
+        signer: Address,
+        message_hash: Hash,
+        signature: Vec<String>,
+    ) -> Result<bool> {
+        info!("Starting signature verification");
+
+        let signer = parse_address(&signer)?;
+        let message_hash = parse_hash(&message_hash)?;
+        let signature =
+            signature.iter().map(|part| parse_hash(part)).collect::<Result<Vec<_>, _>>()?;
+
+        let result = match self
+            .call(abi::account::is_valid_signature(signer, message_hash, signature)?)
+            .await
+        {
+            Ok(result) => result,
+            Err(e)
+                if matches!(
+                    e.downcast_ref::<ContractError>(),
+                    Some(ContractError::Reverted(_))
+                ) =>
+            {
+                return Ok(false)
+            }
+            Err(e) => return Err(e),
+        };
+
+        let valid = abi::account::decode_is_valid_signature(&result)?;
+        Ok(valid == cairo_short_string_to_felt("VALID")? || valid == Felt::ONE)
     }
 }
 
+#[async_trait]
 impl WorkflowContract for StarknetContract {
-    async fn create_workflow(&self, github_owner: Owner, wallet_address: Address) -> Result<Id> {
+    #[instrument(skip_all, fields(owner = %github_owner))]
+    async fn create_workflow(
+        &self,
+        github_owner: Owner,
+        wallet_address: Address,
+    ) -> Result<Option<Id>> {
         info!("Starting workflow creation");
 
-        let github_owner = Felt::from_str(&github_owner).expect("Invalid GitHub username");
-        let wallet_address = Felt::from_hex(&wallet_address).expect("Invalid wallet address");
+        let github_owner = parse_text("GitHub owner", &github_owner)?;
+        let wallet_address = parse_address(&wallet_address)?;
 
         let _ = self
-            .execute(
-                &self.workflow_contract_address,
-                &selector!("create_workflow"),
-                vec![github_owner, wallet_address],
-            )
+            .multicall(vec![abi::workflow::create_workflow(
+                self.workflow_contract_address,
+                github_owner,
+                wallet_address,
+            )?])
             .await?;
 
-        Ok(Id::new())
+        Ok(None)
     }
 
+    #[instrument(skip_all, fields(owner = %github_owner, workflow_id = %workflow_id))]
     async fn create_dependency(
         &self,
         github_owner: Owner,
@@ -609,27 +1410,53 @@ impl WorkflowContract for StarknetContract {
         repository_url: String,
         license: String,
         metadata_json: String,
-    ) -> Result<Id> {
+    ) -> Result<Option<Id>> {
         info!("Starting dependency creation");
 
-        let github_owner = Felt::from_str(&github_owner).expect("Invalid GitHub username");
-        let workflow_id = Felt::from_str(&workflow_id).expect("Invalid workflow id");
-        let name = Felt::from_str(&name).expect("Invalid name");
-        let repository_url = Felt::from_str(&repository_url).expect("Invalid repository url");
-        let license = Felt::from_str(&license).expect("Invalid license");
-        let metadata_json = Felt::from_str(&metadata_json).expect("Invalid metadata json");
-
-        let _ = self
-            .execute(
-                &self.workflow_contract_address,
-                &selector!("create_dependency"),
-                vec![github_owner, workflow_id, name, repository_url, license, metadata_json],
-            )
-            .await?;
+        let call = self.create_dependency_call(
+            github_owner,
+            workflow_id,
+            name,
+            repository_url,
+            license,
+            metadata_json,
+        )?;
+        let _ = self.execute_calls(vec![call]).await?;
+
+        Ok(None)
+    }
 
-        Ok(Id::new())
+    fn create_dependency_call(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        name: String,
+        repository_url: String,
+        license: String,
+        metadata_json: String,
+    ) -> Result<RawCall> {
+        let github_owner = parse_text("GitHub owner", &github_owner)?;
+        let workflow_id = parse_id("workflow id", &workflow_id)?;
+        let name = parse_text("name", &name)?;
+        let repository_url = parse_text("repository url", &repository_url)?;
+        let license = parse_text("license", &license)?;
+        let metadata_json = parse_text("metadata json", &metadata_json)?;
+
+        to_raw_call(abi::workflow::create_dependency(
+            self.workflow_contract_address,
+            github_owner,
+            workflow_id,
+            name,
+            repository_url,
+            license,
+            metadata_json,
+        )?)
     }
 
+    #[instrument(
+        skip_all,
+        fields(owner = %github_owner, workflow_id = %workflow_id, dependency_idx = %dependency_idx)
+    )]
     async fn add_step(
         &self,
         github_owner: Owner,
@@ -638,35 +1465,94 @@ impl WorkflowContract for StarknetContract {
         step_type: StepType,
         tx_hash: Hash,
         related_entity_id: Id,
-    ) -> Result<Id> {
+    ) -> Result<Option<Id>> {
         info!("Starting add step");
 
-        let github_owner = Felt::from_str(&github_owner).expect("Invalid GitHub username");
-        let workflow_id = Felt::from_str(&workflow_id).expect("Invalid workflow id");
-        let dependency_idx = Felt::from_str(&dependency_idx).expect("Invalid dependency index");
-        let step_type = Felt::from_str(&step_type.to_string()).expect("Invalid step type");
-        let tx_hash = Felt::from_str(&tx_hash).expect("Invalid transaction hash");
-        let related_entity_id =
-            Felt::from_str(&related_entity_id).expect("Invalid related entity id");
+        let call = self.add_step_call(
+            github_owner,
+            workflow_id,
+            dependency_idx,
+            step_type,
+            tx_hash,
+            related_entity_id,
+        )?;
+        let _ = self.execute_calls(vec![call]).await?;
+
+        Ok(None)
+    }
 
-        let _ = self
-            .execute(
-                &self.workflow_contract_address,
-                &selector!("add_step"),
-                vec![
-                    github_owner,
-                    workflow_id,
-                    dependency_idx,
-                    step_type,
-                    tx_hash,
-                    related_entity_id,
-                ],
-            )
-            .await?;
+    fn add_step_call(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        dependency_idx: Id,
+        step_type: StepType,
+        tx_hash: Hash,
+        related_entity_id: Id,
+    ) -> Result<RawCall> {
+        let github_owner = parse_text("GitHub owner", &github_owner)?;
+        let workflow_id = parse_id("workflow id", &workflow_id)?;
+        let dependency_idx = parse_id("dependency index", &dependency_idx)?;
+        let step_type = Felt::from(step_type);
+        let tx_hash = parse_hash(&tx_hash)?;
+        let related_entity_id = parse_id("related entity id", &related_entity_id)?;
+
+        to_raw_call(abi::workflow::add_step(
+            self.workflow_contract_address,
+            github_owner,
+            workflow_id,
+            dependency_idx,
+            step_type,
+            tx_hash,
+            related_entity_id,
+        )?)
+    }
 
-        Ok(Id::new())
+    #[instrument(
+        skip_all,
+        fields(owner = %github_owner, workflow_id = %workflow_id, dependency_idx = %dependency_idx)
+    )]
+    async fn record_dependency_with_receipt(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        dependency_idx: Id,
+        record: DependencyReceipt,
+    ) -> Result<Hash> {
+        info!("Starting record dependency with receipt");
+
+        let calls = vec![
+            self.create_dependency_call(
+                github_owner.clone(),
+                workflow_id.clone(),
+                record.name,
+                record.repository_url,
+                record.license,
+                record.metadata_json,
+            )?,
+            self.create_receipt_call(
+                workflow_id.clone(),
+                record.dependency_url,
+                record.metadata,
+                record.metadata_hash.clone(),
+                record.metadata_uri,
+            )?,
+            self.add_step_call(
+                github_owner,
+                workflow_id,
+                dependency_idx,
+                StepType::Receipt,
+                Hash::zero(),
+                record.metadata_hash.into_inner().parse()?,
+            )?,
+        ];
+        self.execute_calls(calls).await
     }
 
+    #[instrument(
+        skip_all,
+        fields(owner = %github_owner, workflow_id = %workflow_id, dependency_idx = %dependency_idx)
+    )]
     async fn finish_dependency(
         &self,
         github_owner: Owner,
@@ -675,56 +1561,70 @@ impl WorkflowContract for StarknetContract {
     ) -> Result<bool> {
         info!("Starting finish dependency");
 
-        let github_owner = Felt::from_str(&github_owner).expect("Invalid GitHub username");
-        let workflow_id = Felt::from_str(&workflow_id).expect("Invalid workflow id");
-        let dependency_idx = Felt::from_str(&dependency_idx).expect("Invalid dependency index");
+        let github_owner = parse_text("GitHub owner", &github_owner)?;
+        let workflow_id = parse_id("workflow id", &workflow_id)?;
+        let dependency_idx = parse_id("dependency index", &dependency_idx)?;
 
         let _ = self
-            .execute(
-                &self.workflow_contract_address,
-                &selector!("finish_dependency"),
-                vec![github_owner, workflow_id, dependency_idx],
-            )
+            .multicall(vec![abi::workflow::finish_dependency(
+                self.workflow_contract_address,
+                github_owner,
+                workflow_id,
+                dependency_idx,
+            )?])
             .await?;
 
         Ok(true)
     }
 
+    #[instrument(skip_all, fields(owner = %github_owner, workflow_id = %workflow_id))]
     async fn finish_workflow(&self, github_owner: Owner, workflow_id: Id) -> Result<bool> {
         info!("Starting finish workflow");
 
-        let github_owner = Felt::from_str(&github_owner).expect("Invalid GitHub username");
-        let workflow_id = Felt::from_str(&workflow_id).expect("Invalid workflow id");
+        let github_owner = parse_text("GitHub owner", &github_owner)?;
+        let workflow_id = parse_id("workflow id", &workflow_id)?;
 
         let _ = self
-            .execute(
-                &self.workflow_contract_address,
-                &selector!("finish_workflow"),
-                vec![github_owner, workflow_id],
-            )
+            .multicall(vec![abi::workflow::finish_workflow(
+                self.workflow_contract_address,
+                github_owner,
+                workflow_id,
+            )?])
             .await?;
 
         Ok(true)
     }
 
+    fn finish_workflow_call(&self, github_owner: Owner, workflow_id: Id) -> Result<RawCall> {
+        let github_owner = parse_text("GitHub owner", &github_owner)?;
+        let workflow_id = parse_id("workflow id", &workflow_id)?;
+
+        to_raw_call(abi::workflow::finish_workflow(
+            self.workflow_contract_address,
+            github_owner,
+            workflow_id,
+        )?)
+    }
+
+    #[instrument(skip_all, fields(owner = %github_owner, workflow_id = %workflow_id))]
     async fn get_workflow_status(&self, github_owner: Owner, workflow_id: Id) -> Result<Workflow> {
         info!("Starting get workflow status");
 
-        let github_owner = Felt::from_str(&github_owner).expect("Invalid GitHub username");
-        let workflow_id = Felt::from_str(&workflow_id).expect("Invalid workflow id");
+        let github_owner = parse_text("GitHub owner", &github_owner)?;
+        let workflow_id = parse_id("workflow id", &workflow_id)?;
 
         let result = self
-            .call(
-                &self.workflow_contract_address,
-                &selector!("get_workflow_status"),
-                vec![github_owner, workflow_id],
-            )
+            .call(abi::workflow::get_workflow_status(
+                self.workflow_contract_address,
+                github_owner,
+                workflow_id,
+            )?)
             .await?;
 
-        let _workflow = result.first().ok_or(anyhow!("Not found workflow"))?;
-        todo!()
+        to_workflow(abi::workflow::decode_get_workflow_status(&result)?)
     }
 
+    #[instrument(skip_all, fields(owner = %github_owner, workflow_id = %workflow_id))]
     async fn get_dependencies(
         &self,
         github_owner: Owner,
@@ -732,20 +1632,56 @@ impl WorkflowContract for StarknetContract {
     ) -> Result<Vec<Dependency>> {
         info!("Starting get dependencies");
 
-        let github_owner = Felt::from_str(&github_owner).expect("Invalid GitHub username");
-        let workflow_id = Felt::from_str(&workflow_id).expect("Invalid workflow id");
+        let github_owner = parse_text("GitHub owner", &github_owner)?;
+        let workflow_id = parse_id("workflow id", &workflow_id)?;
 
-        let _result = self
-            .call(
-                &self.workflow_contract_address,
-                &selector!("get_dependencies"),
-                vec![github_owner, workflow_id],
-            )
+        let result = self
+            .call(abi::workflow::get_dependencies(
+                self.workflow_contract_address,
+                github_owner,
+                workflow_id,
+            )?)
             .await?;
 
-        todo!()
+        abi::workflow::decode_get_dependencies(&result)?.into_iter().map(to_dependency).collect()
+    }
+
+    #[instrument(
+        skip_all,
+        fields(owner = %github_owner, workflow_id = %workflow_id, offset, limit)
+    )]
+    async fn get_dependencies_page(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<Dependency>> {
+        info!("Starting get dependencies page");
+
+        let github_owner = parse_text("GitHub owner", &github_owner)?;
+        let workflow_id = parse_id("workflow id", &workflow_id)?;
+
+        let result = self
+            .call(abi::workflow::get_dependencies_page(
+                self.workflow_contract_address,
+                github_owner,
+                workflow_id,
+                offset,
+                limit,
+            )?)
+            .await?;
+
+        abi::workflow::decode_get_dependencies_page(&result)?
+            .into_iter()
+            .map(to_dependency)
+            .collect()
     }
 
+    #[instrument(
+        skip_all,
+        fields(owner = %github_owner, workflow_id = %workflow_id, dependency_idx = %dependency_idx)
+    )]
     async fn get_steps(
         &self,
         github_owner: Owner,
@@ -754,33 +1690,50 @@ impl WorkflowContract for StarknetContract {
     ) -> Result<Vec<Step>> {
         info!("Starting get steps");
 
-        let github_owner = Felt::from_str(&github_owner).expect("Invalid GitHub username");
-        let workflow_id = Felt::from_str(&workflow_id).expect("Invalid workflow id");
-        let dependency_idx = Felt::from_str(&dependency_idx).expect("Invalid dependency index");
+        let github_owner = parse_text("GitHub owner", &github_owner)?;
+        let workflow_id = parse_id("workflow id", &workflow_id)?;
+        let dependency_idx = parse_id("dependency index", &dependency_idx)?;
 
-        let _result = self
-            .call(
-                &self.workflow_contract_address,
-                &selector!("get_steps"),
-                vec![github_owner, workflow_id, dependency_idx],
-            )
+        let result = self
+            .call(abi::workflow::get_steps(
+                self.workflow_contract_address,
+                github_owner,
+                workflow_id,
+                dependency_idx,
+            )?)
             .await?;
 
-        todo!()
+        abi::workflow::decode_get_steps(&result)?.into_iter().map(to_step).collect()
     }
 
+    #[instrument(skip_all, fields(tx_hash = %tx_hash))]
     async fn get_step_by_tx_hash(&self, tx_hash: Hash) -> Result<Option<(Owner, Id, Id, Id)>> {
         info!("Starting get step by tx hash");
 
-        let tx_hash = Felt::from_hex(&tx_hash).expect("Invalid transaction hash");
+        let tx_hash = parse_hash(&tx_hash)?;
 
-        let _result = self
-            .call(&self.workflow_contract_address, &selector!("get_step_by_tx_hash"), vec![tx_hash])
+        let result = self
+            .call(abi::workflow::get_step_by_tx_hash(self.workflow_contract_address, tx_hash)?)
             .await?;
+        let (github_owner, workflow_id, dependency_idx, step_index) =
+            abi::workflow::decode_get_step_by_tx_hash(&result)?;
 
-        todo!()
+        // Unknown transactions map to the zero owner.
+        if github_owner == Felt::ZERO {
+            return Ok(None);
+        }
+        Ok(Some((
+            parse_cairo_short_string(&github_owner)?.parse()?,
+            workflow_id.to_string().parse()?,
+            dependency_idx.to_string().parse()?,
+            step_index.to_string().parse()?,
+        )))
     }
 
+    #[instrument(
+        skip_all,
+        fields(owner = %github_owner, workflow_id = %workflow_id, dependency_idx = %dependency_idx)
+    )]
     async fn get_complete_transaction_chain(
         &self,
         github_owner: Owner,
@@ -789,54 +1742,107 @@ impl WorkflowContract for StarknetContract {
     ) -> Result<Vec<Hash>> {
         info!("Starting get complete transaction chain");
 
-        let github_owner = Felt::from_str(&github_owner).expect("Invalid GitHub username");
-        let workflow_id = Felt::from_str(&workflow_id).expect("Invalid workflow id");
-        let dependency_idx = Felt::from_str(&dependency_idx).expect("Invalid dependency index");
+        let github_owner = parse_text("GitHub owner", &github_owner)?;
+        let workflow_id = parse_id("workflow id", &workflow_id)?;
+        let dependency_idx = parse_id("dependency index", &dependency_idx)?;
 
-        let _result = self
-            .call(
-                &self.workflow_contract_address,
-                &selector!("get_complete_transaction_chain"),
-                vec![github_owner, workflow_id, dependency_idx],
-            )
+        let result = self
+            .call(abi::workflow::get_complete_transaction_chain(
+                self.workflow_contract_address,
+                github_owner,
+                workflow_id,
+                dependency_idx,
+            )?)
             .await?;
+        let chain = abi::workflow::decode_get_complete_transaction_chain(&result)?;
 
-        todo!()
+        Ok(chain.iter().map(from_felt).collect::<Result<_, _>>()?)
     }
 
+    #[instrument(skip_all, fields(%from_block, %to_block))]
+    async fn get_events(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        continuation_token: Option<String>,
+    ) -> Result<WorkflowEventPage> {
+        let filter = EventFilter {
+            from_block: Some(BlockId::Number(from_block)),
+            to_block: Some(BlockId::Number(to_block)),
+            address: Some(self.workflow_contract_address),
+            keys: Some(vec![vec![DependencyCreated::SELECTOR, StepAdded::SELECTOR]]),
+        };
+        let page = self
+            .provider
+            .get_events(filter, continuation_token, EVENTS_CHUNK_SIZE)
+            .await
+            .map_err(|e| anyhow!("Failed to get events: {:?}", e))?;
+
+        let mut events = Vec::with_capacity(page.events.len());
+        for event in page.events {
+            if let Some(event) = decode_workflow_event(event)? {
+                events.push(event);
+            }
+        }
+        Ok(WorkflowEventPage { events, continuation_token: page.continuation_token })
+    }
+
+    #[instrument(skip_all, fields(owner = %github_owner))]
     async fn get_workflow_count(&self, github_owner: Owner) -> Result<Number> {
         info!("Starting get workflow count");
 
-        let github_owner = Felt::from_str(&github_owner).expect("Invalid GitHub username");
+        let github_owner = parse_text("GitHub owner", &github_owner)?;
 
         let result = self
-            .call(
-                &self.workflow_contract_address,
-                &selector!("get_workflow_count"),
-                vec![github_owner],
-            )
+            .call(abi::workflow::get_workflow_count(self.workflow_contract_address, github_owner)?)
             .await?;
 
-        let count = result.first().unwrap_or(&Felt::ZERO);
-        Ok(count.to_string())
+        Ok(abi::workflow::decode_get_workflow_count(&result)?.to_string())
     }
 
+    #[instrument(skip_all, fields(owner = %github_owner))]
     async fn get_all_workflows(&self, github_owner: Owner) -> Result<Vec<(Number, Workflow)>> {
         info!("Starting get all workflows");
 
-        let github_owner = Felt::from_str(&github_owner).expect("Invalid GitHub username");
+        let github_owner = parse_text("GitHub owner", &github_owner)?;
 
-        let _result = self
-            .call(
-                &self.workflow_contract_address,
-                &selector!("get_all_workflows"),
-                vec![github_owner],
-            )
+        let result = self
+            .call(abi::workflow::get_all_workflows(self.workflow_contract_address, github_owner)?)
             .await?;
 
-        todo!()
+        abi::workflow::decode_get_all_workflows(&result)?
+            .into_iter()
+            .map(|(id, details)| Ok((id.to_string(), to_workflow(details)?)))
+            .collect()
     }
 
+    #[instrument(skip_all, fields(owner = %github_owner, offset, limit))]
+    async fn get_workflows_page(
+        &self,
+        github_owner: Owner,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<(Number, Workflow)>> {
+        info!("Starting get workflows page");
+
+        let github_owner = parse_text("GitHub owner", &github_owner)?;
+
+        let result = self
+            .call(abi::workflow::get_workflows_page(
+                self.workflow_contract_address,
+                github_owner,
+                offset,
+                limit,
+            )?)
+            .await?;
+
+        abi::workflow::decode_get_workflows_page(&result)?
+            .into_iter()
+            .map(|(id, details)| Ok((id.to_string(), to_workflow(details)?)))
+            .collect()
+    }
+
+    #[instrument(skip_all, fields(owner = %github_owner, workflow_id = %workflow_id))]
     async fn bind_wallet_address(
         &self,
         github_owner: Owner,
@@ -845,38 +1851,41 @@ impl WorkflowContract for StarknetContract {
     ) -> Result<bool> {
         info!("Starting bind wallet address");
 
-        let github_owner = Felt::from_str(&github_owner).expect("Invalid GitHub username");
-        let workflow_id = Felt::from_str(&workflow_id).expect("Invalid workflow id");
-        let wallet_address = Felt::from_hex(&wallet_address).expect("Invalid wallet address");
+        let github_owner = parse_text("GitHub owner", &github_owner)?;
+        let workflow_id = parse_id("workflow id", &workflow_id)?;
+        let wallet_address = parse_address(&wallet_address)?;
 
         let _ = self
-            .execute(
-                &self.workflow_contract_address,
-                &selector!("bind_wallet_address"),
-                vec![github_owner, workflow_id, wallet_address],
-            )
+            .multicall(vec![abi::workflow::bind_wallet_address(
+                self.workflow_contract_address,
+                github_owner,
+                workflow_id,
+                wallet_address,
+            )?])
             .await?;
 
         Ok(true)
     }
 
+    #[instrument(skip_all, fields(owner = %github_owner, workflow_id = %workflow_id))]
     async fn unbind_wallet_address(&self, github_owner: Owner, workflow_id: Id) -> Result<bool> {
         info!("Starting unbind wallet address");
 
-        let github_owner = Felt::from_str(&github_owner).expect("Invalid GitHub username");
-        let workflow_id = Felt::from_str(&workflow_id).expect("Invalid workflow id");
+        let github_owner = parse_text("GitHub owner", &github_owner)?;
+        let workflow_id = parse_id("workflow id", &workflow_id)?;
 
         let _ = self
-            .execute(
-                &self.workflow_contract_address,
-                &selector!("unbind_wallet_address"),
-                vec![github_owner, workflow_id],
-            )
+            .multicall(vec![abi::workflow::unbind_wallet_address(
+                self.workflow_contract_address,
+                github_owner,
+                workflow_id,
+            )?])
             .await?;
 
         Ok(true)
     }
 
+    #[instrument(skip_all, fields(owner = %github_owner, workflow_id = %workflow_id))]
     async fn change_wallet_address(
         &self,
         github_owner: Owner,
@@ -885,16 +1894,17 @@ impl WorkflowContract for StarknetContract {
     ) -> Result<bool> {
         info!("Starting change wallet address");
 
-        let github_owner = Felt::from_str(&github_owner).expect("Invalid GitHub username");
-        let workflow_id = Felt::from_str(&workflow_id).expect("Invalid workflow id");
-        let wallet_address = Felt::from_hex(&new_wallet_address).expect("Invalid wallet address");
+        let github_owner = parse_text("GitHub owner", &github_owner)?;
+        let workflow_id = parse_id("workflow id", &workflow_id)?;
+        let wallet_address = parse_address(&new_wallet_address)?;
 
         let _ = self
-            .execute(
-                &self.workflow_contract_address,
-                &selector!("change_wallet_address"),
-                vec![github_owner, workflow_id, wallet_address],
-            )
+            .multicall(vec![abi::workflow::change_wallet_address(
+                self.workflow_contract_address,
+                github_owner,
+                workflow_id,
+                wallet_address,
+            )?])
             .await?;
 
         Ok(true)
@@ -12,50 +12,98 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use anyhow::Result;
-use std::future::Future;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fmt;
 
 use super::types::{Address, Id};
 
-#[allow(dead_code)]
+#[derive(Debug, Clone)]
 pub struct Inquire {
-    workflow_id: Id,
-    inquirer: Address,
-    inquiree: Address,
-    question: String,
-    response: String,
-    status: Status,
-    created_at: u64,
-    responded_at: u64,
+    pub workflow_id: Id,
+    pub inquirer: Address,
+    pub inquiree: Address,
+    pub question: String,
+    pub response: String,
+    pub status: Status,
+    pub created_at: u64,
+    pub responded_at: u64,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Status {
     Pending,
     Responded,
     Rejected,
 }
 
+impl Status {
+    /// The code of the status, as stored on chain
+    pub fn code(self) -> u64 {
+        match self {
+            Self::Pending => 0,
+            Self::Responded => 1,
+            Self::Rejected => 2,
+        }
+    }
+
+    /// Parse the code of a status, as stored on chain
+    pub fn from_code(code: u64) -> Option<Self> {
+        match code {
+            0 => Some(Self::Pending),
+            1 => Some(Self::Responded),
+            2 => Some(Self::Rejected),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<u64> for Status {
+    type Error = anyhow::Error;
+
+    fn try_from(code: u64) -> Result<Self> {
+        Self::from_code(code).ok_or_else(|| anyhow!("Unknown inquire status `{code}`"))
+    }
+}
+
+impl TryFrom<&str> for Status {
+    type Error = anyhow::Error;
+
+    fn try_from(code: &str) -> Result<Self> {
+        code.parse::<u64>().map_err(|_| anyhow!("Unknown inquire status `{code}`"))?.try_into()
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Pending => "Pending",
+            Self::Responded => "Responded",
+            Self::Rejected => "Rejected",
+        })
+    }
+}
+
 /// Inquire contract interface
+#[async_trait]
 pub trait InquireContract {
-    /// Create inquiry
-    fn create_inquire(
+    /// Create inquiry, returning its id unless it is only known once the transaction is
+    /// executed
+    async fn create_inquire(
         &self,
         workflow_id: Id,
         inquirer: Address,
         inquiree: Address,
         question: String,
-    ) -> impl Future<Output = Result<Id>>;
+    ) -> Result<Option<Id>>;
 
     /// Respond to inquiry
-    fn respond_to_inquire(
-        &self,
-        inquire_id: Id,
-        response: String,
-    ) -> impl Future<Output = Result<bool>>;
+    async fn respond_to_inquire(&self, inquire_id: Id, response: String) -> Result<bool>;
 
     /// Reject inquiry
-    fn reject_inquire(&self, inquire_id: Id) -> impl Future<Output = Result<bool>>;
+    async fn reject_inquire(&self, inquire_id: Id) -> Result<bool>;
 
     /// Get inquiry details
-    fn get_inquire_details(&self, inquire_id: Id) -> impl Future<Output = Result<Inquire>>;
+    async fn get_inquire_details(&self, inquire_id: Id) -> Result<Inquire>;
 }
@@ -13,19 +13,41 @@
 // limitations under the License.
 
 pub mod allocation;
+pub mod error;
+pub mod hash;
 pub mod impls;
 pub mod inquire;
 pub mod receipt;
 pub mod sign;
+pub mod token;
+pub mod transaction;
 pub mod types;
 pub mod workflow;
 
+/// The contracts of a chain.
+///
+/// The traits are object safe, so an implementation selected at runtime is held as a
+/// `Box<dyn Contract>`.
 pub trait Contract:
     allocation::AllocationContract
     + inquire::InquireContract
     + receipt::ReceiptContract
     + sign::SignContract
+    + token::TokenContract
+    + transaction::TransactionContract
     + workflow::WorkflowContract
+    + Send
+    + Sync
 {
-    fn chain() -> &'static str;
+    /// The name of the chain, eg. `Starknet`
+    fn chain(&self) -> &'static str;
+
+    /// The id of the network, eg. `SN_SEPOLIA` or `11155111` for Ethereum Sepolia
+    fn chain_id(&self) -> String;
+
+    /// The link to a transaction on the block explorer of the network
+    fn explorer_tx_url(&self, tx_hash: &str) -> String;
+
+    /// The link to a contract or account on the block explorer of the network
+    fn explorer_contract_url(&self, address: &str) -> String;
 }
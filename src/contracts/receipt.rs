@@ -13,23 +13,24 @@
 // limitations under the License.
 
 use anyhow::Result;
-use std::future::Future;
+use async_trait::async_trait;
 
-use super::types::{Hash, Id};
+use super::types::{Hash, Id, RawCall};
 
-#[allow(dead_code)]
+#[derive(Debug, Clone)]
 pub struct Receipt {
-    workflow_id: Id,
-    dependency_url: String,
-    tx_hash: Hash,
-    created_at: u64,
+    pub workflow_id: Id,
+    pub dependency_url: String,
+    pub tx_hash: Hash,
+    pub created_at: u64,
     /// Hash value of the complete JSON
-    metadata_hash: Hash,
+    pub metadata_hash: Hash,
     /// URI pointing to the complete JSON
-    metadata_uri: String,
+    pub metadata_uri: String,
 }
 
 /// Common key fields, stored directly on the chain
+#[derive(Debug, Clone)]
 pub struct ReceiptMetadata {
     pub name: String,
     pub version: String,
@@ -38,30 +39,35 @@ pub struct ReceiptMetadata {
 }
 
 /// Receipt contract interface
+#[async_trait]
 pub trait ReceiptContract {
-    /// Create receipt and store metadata
-    fn create_receipt(
+    /// Create receipt and store metadata, returning its id unless it is only known once
+    /// the transaction is executed
+    async fn create_receipt(
         &self,
         workflow_id: Id,
         dependency_url: String,
         metadata: ReceiptMetadata,
         metadata_hash: Hash,
-        metadata_uri: Hash,
-    ) -> impl Future<Output = Result<Id>>;
+        metadata_uri: String,
+    ) -> Result<Option<Id>>;
 
-    /// Get receipt details
-    fn get_receipt_details(
+    /// Build the call of `create_receipt`, to estimate its fee
+    fn create_receipt_call(
         &self,
-        receipt_id: Id,
-    ) -> impl Future<Output = Result<(Receipt, ReceiptMetadata)>>;
+        workflow_id: Id,
+        dependency_url: String,
+        metadata: ReceiptMetadata,
+        metadata_hash: Hash,
+        metadata_uri: String,
+    ) -> Result<RawCall>;
+
+    /// Get receipt details
+    async fn get_receipt_details(&self, receipt_id: Id) -> Result<(Receipt, ReceiptMetadata)>;
 
     /// Verify metadata
-    fn verify_metadata(
-        &self,
-        receipt_id: Id,
-        provided_hash: Hash,
-    ) -> impl Future<Output = Result<bool>>;
+    async fn verify_metadata(&self, receipt_id: Id, provided_hash: Hash) -> Result<bool>;
 
     /// Update transaction hash
-    fn update_tx_hash(&self, receipt_id: Id, tx_hash: Hash) -> impl Future<Output = Result<()>>;
+    async fn update_tx_hash(&self, receipt_id: Id, tx_hash: Hash) -> Result<()>;
 }
@@ -13,34 +13,45 @@
 // limitations under the License.
 
 use anyhow::Result;
-use std::future::Future;
+use async_trait::async_trait;
 
 use super::types::{Address, Hash, Id};
 
-#[allow(dead_code)]
+#[derive(Debug, Clone)]
 pub struct Sign {
-    workflow_id: Id,
-    inquire_id: Id,
-    signer: Address,
-    signature_hash: Hash,
-    tx_hash: Hash,
-    created_at: u64,
+    pub workflow_id: Id,
+    pub inquire_id: Id,
+    pub signer: Address,
+    pub signature_hash: Hash,
+    pub tx_hash: Hash,
+    pub created_at: u64,
 }
 
 /// Sign contract interface
+#[async_trait]
 pub trait SignContract {
-    /// Create signature record
-    fn create_sign(
+    /// Create signature record, returning its id unless it is only known once the
+    /// transaction is executed
+    async fn create_sign(
         &self,
         workflow_id: Id,
         inquire_id: Id,
         signer: Address,
         signature_hash: Hash,
-    ) -> impl Future<Output = Result<Id>>;
+    ) -> Result<Option<Id>>;
 
     /// Get signature details
-    fn get_sign_details(&self, sign_id: Id) -> impl Future<Output = Result<Sign>>;
+    async fn get_sign_details(&self, sign_id: Id) -> Result<Sign>;
 
     /// Get signature ID by inquiry ID
-    fn get_sign_by_inquire(&self, inquire_id: Id) -> impl Future<Output = Result<Id>>;
+    async fn get_sign_by_inquire(&self, inquire_id: Id) -> Result<Id>;
+
+    /// Check a signature over a message hash, with the signature verification of the
+    /// signer account itself
+    async fn is_valid_signature(
+        &self,
+        signer: Address,
+        message_hash: Hash,
+        signature: Vec<String>,
+    ) -> Result<bool>;
 }
@@ -0,0 +1,124 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::str::FromStr;
+
+use super::types::{Address, Hash, Number, RawCall};
+
+/// Tokens native to a chain, which can pay allocations without a custom token contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NativeToken {
+    Strk,
+    Eth,
+}
+
+impl std::fmt::Display for NativeToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Strk => write!(f, "STRK"),
+            Self::Eth => write!(f, "ETH"),
+        }
+    }
+}
+
+/// The token an allocation is paid in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    /// A native token, referenced by its symbol
+    Native(NativeToken),
+    /// Any other token, referenced by its contract address
+    Erc20(Address),
+}
+
+impl FromStr for Token {
+    type Err = anyhow::Error;
+
+    /// Parse a token symbol, eg. `STRK`, or a hex token contract address.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_uppercase().as_str() {
+            "STRK" => Ok(Self::Native(NativeToken::Strk)),
+            "ETH" => Ok(Self::Native(NativeToken::Eth)),
+            _ if s.starts_with("0x") => Ok(Self::Erc20(s.parse()?)),
+            _ => Err(anyhow!("Unknown token `{s}`")),
+        }
+    }
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Native(token) => write!(f, "{token}"),
+            Self::Erc20(address) => write!(f, "{address}"),
+        }
+    }
+}
+
+/// Token contract interface, used to pay out allocations
+#[async_trait]
+pub trait TokenContract {
+    /// Address of the account paying out allocations
+    fn account_address(&self) -> Address;
+
+    /// Whether an address can receive tokens on this chain
+    fn is_valid_address(&self, address: &Address) -> bool;
+
+    /// Address of the contract of a native token
+    fn native_token_address(&self, token: NativeToken) -> Address;
+
+    /// The native token transaction fees are paid in
+    fn fee_token(&self) -> NativeToken;
+
+    /// Get the number of decimals of the token
+    async fn decimals(&self, token_address: Address) -> Result<u8>;
+
+    /// Get the balance of an account, in the smallest unit of the token
+    async fn balance_of(&self, token_address: Address, owner: Address) -> Result<Number>;
+
+    /// Get the amount `spender` may transfer on behalf of `owner`, in the smallest unit
+    /// of the token
+    async fn allowance(
+        &self,
+        token_address: Address,
+        owner: Address,
+        spender: Address,
+    ) -> Result<Number>;
+
+    /// Transfer tokens from the paying account, amount in the smallest unit of the token
+    async fn transfer(
+        &self,
+        token_address: Address,
+        recipient: Address,
+        amount: Number,
+    ) -> Result<Hash>;
+
+    /// Build the call of `transfer`, to batch it with other payouts
+    fn transfer_call(
+        &self,
+        token_address: Address,
+        recipient: Address,
+        amount: Number,
+    ) -> Result<RawCall>;
+
+    /// Build the call transferring tokens from `owner` on behalf of the paying account,
+    /// up to the allowance `owner` granted it
+    fn transfer_from_call(
+        &self,
+        token_address: Address,
+        owner: Address,
+        recipient: Address,
+        amount: Number,
+    ) -> Result<RawCall>;
+}
@@ -0,0 +1,53 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use super::types::{Hash, Number, RawCall};
+
+/// The state of a submitted transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionStatus {
+    /// Not yet included in a block
+    Pending,
+    /// Included in a block and executed successfully
+    Succeeded,
+    /// Included in a block but reverted, with the reason
+    Reverted(String),
+    /// Unknown to the node: not propagated yet, dropped from the mempool,
+    /// or removed from the chain by a reorg
+    NotFound,
+}
+
+/// Transaction interface, used to follow submitted transactions
+#[async_trait]
+pub trait TransactionContract {
+    /// Get the status of a submitted transaction
+    async fn transaction_status(&self, tx_hash: Hash) -> Result<TransactionStatus>;
+
+    /// Execute several calls atomically in a single transaction
+    async fn execute_calls(&self, calls: Vec<RawCall>) -> Result<Hash>;
+
+    /// Estimate the fee of executing the calls in a single transaction,
+    /// in the smallest unit of the fee token
+    async fn estimate_fee(&self, calls: Vec<RawCall>) -> Result<Number>;
+
+    /// Get the number of the latest accepted block
+    async fn block_number(&self) -> Result<u64>;
+
+    /// Check the provider is connected to the network of the configured chain id, the
+    /// transactions signed for another network being rejected
+    async fn check_chain_id(&self) -> Result<()>;
+}
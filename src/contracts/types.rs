@@ -12,8 +12,308 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-pub type Owner = String;
-pub type Address = String;
-pub type Id = String;
-pub type Hash = String;
+use std::{borrow::Borrow, fmt, ops::Deref, str::FromStr};
+
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::error::ContractError;
+
+/// A GitHub owner, eg. `deprank`, which can be encoded on chain as a Cairo short string
+#[derive(
+    Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, ToSchema, sqlx::Type,
+)]
+#[serde(try_from = "String", into = "String")]
+#[sqlx(transparent)]
+#[schema(value_type = String, example = "deprank")]
+pub struct Owner(String);
+
+/// A hex encoded account or contract address of the chain of the contracts
+#[derive(
+    Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, ToSchema, sqlx::Type,
+)]
+#[serde(try_from = "String", into = "String")]
+#[sqlx(transparent)]
+#[schema(value_type = String, example = "0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7")]
+pub struct Address(String);
+
+/// An id on chain, decimal or hex encoded, in the range of the integers of the chain
+#[derive(
+    Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, ToSchema, sqlx::Type,
+)]
+#[serde(try_from = "String", into = "String")]
+#[sqlx(transparent)]
+#[schema(value_type = String, example = "42")]
+pub struct Id(String);
+
+/// A hex encoded transaction or content hash of the chain of the contracts
+#[derive(
+    Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, ToSchema, sqlx::Type,
+)]
+#[serde(try_from = "String", into = "String")]
+#[sqlx(transparent)]
+#[schema(value_type = String, example = "0x05c3...")]
+pub struct Hash(String);
+
+/// A decimal integer, such as an amount in the smallest unit of its token, up to `u256`
 pub type Number = String;
+
+/// A contract call, with hex encoded fields, as sent to wallets and paymasters
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RawCall {
+    pub to: Address,
+    pub selector: String,
+    pub calldata: Vec<String>,
+}
+
+impl Owner {
+    pub fn new(owner: impl Into<String>) -> Result<Self, ContractError> {
+        let owner = owner.into();
+        if owner.is_empty() || !owner.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(ContractError::InvalidOwner(owner));
+        }
+        if owner.len() > 31 {
+            return Err(ContractError::EncodingTooLong { field: "owner", value: owner });
+        }
+        Ok(Self(owner))
+    }
+}
+
+impl Address {
+    pub fn new(address: impl Into<String>) -> Result<Self, ContractError> {
+        let address = address.into();
+        if !is_word(&address, ADDRESS_DIGITS) {
+            return Err(ContractError::InvalidAddress(address));
+        }
+        Ok(Self(address))
+    }
+}
+
+impl Id {
+    pub fn new(id: impl Into<String>) -> Result<Self, ContractError> {
+        let id = id.into();
+        let value = match id.strip_prefix("0x") {
+            Some(digits) if is_hex(digits) && digits.len() <= 64 => {
+                BigUint::parse_bytes(digits.as_bytes(), 16)
+            }
+            None if is_decimal(&id) && id.len() <= 78 => BigUint::parse_bytes(id.as_bytes(), 10),
+            _ => None,
+        };
+        if value.is_none_or(|value| value >= word_bound()) {
+            return Err(ContractError::InvalidId { kind: "id", value: id });
+        }
+        Ok(Self(id))
+    }
+
+    /// The zero id, standing for no entity.
+    pub fn zero() -> Self {
+        Self("0".to_string())
+    }
+}
+
+impl From<u64> for Id {
+    fn from(value: u64) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl Hash {
+    pub fn new(hash: impl Into<String>) -> Result<Self, ContractError> {
+        let hash = hash.into();
+        if !is_word(&hash, HASH_DIGITS) {
+            return Err(ContractError::InvalidHash(hash));
+        }
+        Ok(Self(hash))
+    }
+
+    /// The zero hash, standing for no transaction.
+    pub fn zero() -> Self {
+        if cfg!(feature = "evm") {
+            return Self(format!("0x{}", "0".repeat(HASH_DIGITS)));
+        }
+        Self("0x0".to_string())
+    }
+}
+
+/// Implement the conversions of a validated string newtype, which derefs to the string it
+/// wraps and is only built from a valid one.
+macro_rules! newtype {
+    ($($name:ident),*) => {$(
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            pub fn into_inner(self) -> String {
+                self.0
+            }
+        }
+
+        impl Deref for $name {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl Borrow<str> for $name {
+            fn borrow(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ContractError;
+
+            fn from_str(s: &str) -> Result<Self, ContractError> {
+                Self::new(s)
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = ContractError;
+
+            fn try_from(s: String) -> Result<Self, ContractError> {
+                Self::new(s)
+            }
+        }
+
+        impl TryFrom<&str> for $name {
+            type Error = ContractError;
+
+            fn try_from(s: &str) -> Result<Self, ContractError> {
+                Self::new(s)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> String {
+                value.0
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl PartialEq<String> for $name {
+            fn eq(&self, other: &String) -> bool {
+                &self.0 == other
+            }
+        }
+    )*};
+}
+
+newtype!(Owner, Address, Id, Hash);
+
+/// The hex digits of an address, at most for felts, exactly for EVM addresses.
+#[cfg(not(feature = "evm"))]
+const ADDRESS_DIGITS: usize = 64;
+#[cfg(feature = "evm")]
+const ADDRESS_DIGITS: usize = 40;
+
+/// The hex digits of a hash, at most for felts, exactly for EVM hashes.
+const HASH_DIGITS: usize = 64;
+
+/// Whether a value is `0x` followed by hex digits, encoding an integer within the range
+/// of the chain: at most `digits` digits lower than the field prime on Starknet, exactly
+/// `digits` digits on EVM networks.
+fn is_word(value: &str, digits: usize) -> bool {
+    let Some(hex) = value.strip_prefix("0x") else {
+        return false;
+    };
+    if !is_hex(hex) {
+        return false;
+    }
+    if cfg!(feature = "evm") {
+        return hex.len() == digits;
+    }
+    hex.len() <= digits
+        && BigUint::parse_bytes(hex.as_bytes(), 16).is_some_and(|value| value < word_bound())
+}
+
+/// The bound of the integers on chain, the field prime of felts on Starknet, `2^256` on
+/// EVM networks.
+fn word_bound() -> BigUint {
+    let one = BigUint::from(1u8);
+    if cfg!(feature = "evm") {
+        return one << 256;
+    }
+    (one.clone() << 251) + (BigUint::from(17u8) << 192) + one
+}
+
+fn is_hex(digits: &str) -> bool {
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn is_decimal(digits: &str) -> bool {
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn owners_are_short_strings_of_github_names() {
+        assert!(Owner::new("deprank").is_ok());
+        assert!(Owner::new("dep-rank-42").is_ok());
+        assert!(Owner::new("").is_err());
+        assert!(Owner::new("dep rank").is_err());
+        assert!(Owner::new("a".repeat(32)).is_err());
+    }
+
+    #[test]
+    fn ids_fit_the_integers_of_the_chain() {
+        assert!(Id::new("42").is_ok());
+        assert!(Id::new("0x2a").is_ok());
+        assert!(Id::new("").is_err());
+        assert!(Id::new("-1").is_err());
+        assert!(Id::new("0xzz").is_err());
+        assert!(Id::new(format!("0x1{}", "0".repeat(64))).is_err());
+    }
+
+    #[test]
+    fn addresses_and_hashes_are_hex_words() {
+        assert!(Address::new(format!("0x{:0>1$}", "1", ADDRESS_DIGITS)).is_ok());
+        assert!(Address::new("0x").is_err());
+        assert!(Address::new("1234").is_err());
+        assert!(Address::new("0xg").is_err());
+
+        assert!(Hash::new(format!("0x{:0>1$}", "a", HASH_DIGITS)).is_ok());
+        assert!(Hash::new(Hash::zero().into_inner()).is_ok());
+        assert!(Hash::new("0x").is_err());
+        assert!(Hash::new(format!("0x{:0>1$}", "a", HASH_DIGITS + 1)).is_err());
+    }
+
+    #[test]
+    fn deserialization_validates() {
+        assert_eq!(serde_json::from_str::<Id>("\"42\"").unwrap(), "42");
+        assert!(serde_json::from_str::<Id>("\"forty-two\"").is_err());
+        assert!(serde_json::from_str::<Owner>("\"not an owner\"").is_err());
+        assert!(serde_json::from_str::<Address>("\"0xnotanaddress\"").is_err());
+        assert!(serde_json::from_str::<Hash>("\"\"").is_err());
+    }
+}
@@ -12,46 +12,99 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::future::Future;
+use std::fmt;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 
-use super::types::{Address, Hash, Id, Number, Owner};
+use super::{
+    hash::same_hash,
+    receipt::ReceiptMetadata,
+    types::{Address, Hash, Id, Number, Owner, RawCall},
+};
 
-#[allow(dead_code)]
+#[derive(Debug, Clone)]
 pub struct Workflow {
-    owner: Owner,
+    pub owner: Owner,
     /// Associated multisig wallet address
-    wallet_address: Address,
-    status: Status,
-    created_at: u64,
-    last_updated_at: u64,
+    pub wallet_address: Address,
+    pub status: Status,
+    pub created_at: u64,
+    pub last_updated_at: u64,
 }
 
-#[allow(dead_code)]
+#[derive(Debug, Clone)]
 pub struct Dependency {
     /// Dependency name or ID
-    name: String,
-    repository_url: String,
-    license: String,
+    pub name: String,
+    pub repository_url: String,
+    pub license: String,
     /// JSON formatted additional data
-    metadata_json: String,
-    status: Status,
-    created_at: u64,
-    last_updated_at: u64,
+    pub metadata_json: String,
+    pub status: Status,
+    pub created_at: u64,
+    pub last_updated_at: u64,
 }
 
-#[allow(dead_code)]
+#[derive(Debug, Clone)]
 pub struct Step {
-    step_type: StepType,
-    tx_hash: Hash,
+    pub step_type: StepType,
+    pub tx_hash: Hash,
     // Related entity ID (receipt_id, inquire_id, etc.)
-    related_entity_id: Id,
-    timestamp: u64,
+    pub related_entity_id: Id,
+    pub timestamp: u64,
     /// Previous step index, used for linking
-    prev_step_index: Id,
+    pub prev_step_index: Id,
 }
 
+/// A step of the transaction chain of a dependency.
+#[derive(Debug, Clone)]
+pub struct ChainedStep {
+    /// The index of the step in the dependency
+    pub index: u64,
+    pub step: Step,
+    /// Whether the step links to the previous one, and its transaction is at its position
+    /// in the chain
+    pub linked: bool,
+}
+
+/// The transaction chain of a dependency, oldest step first.
+#[derive(Debug, Clone)]
+pub struct TransactionChain {
+    pub steps: Vec<ChainedStep>,
+    /// Whether every step is linked, the chain listing the transactions of all the steps
+    pub verified: bool,
+}
+
+impl TransactionChain {
+    /// Link the steps of a dependency, checking them against its transaction chain. A step
+    /// links to the previous one by its index, the first step to index 0.
+    pub fn link(steps: Vec<Step>, chain: &[Hash]) -> Self {
+        let mut verified = steps.len() == chain.len();
+        let steps = steps
+            .into_iter()
+            .enumerate()
+            .map(|(index, step)| {
+                let linked = parse_index(&step.prev_step_index) == Some(index.saturating_sub(1))
+                    && chain.get(index).is_some_and(|tx_hash| same_hash(tx_hash, &step.tx_hash));
+                verified &= linked;
+                ChainedStep { index: index as u64, step, linked }
+            })
+            .collect();
+        Self { steps, verified }
+    }
+}
+
+/// Parse the index of a step, either decimal or hex encoded.
+fn parse_index(index: &str) -> Option<usize> {
+    match index.strip_prefix("0x") {
+        Some(digits) => usize::from_str_radix(digits, 16).ok(),
+        None => index.parse().ok(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum StepType {
     Receipt,
     Inquire,
@@ -59,34 +112,190 @@ pub enum StepType {
     Allocation,
 }
 
-impl std::fmt::Display for StepType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl StepType {
+    /// The code of the step type, as stored on chain
+    pub fn code(self) -> u64 {
         match self {
-            StepType::Receipt => write!(f, "1"),
-            StepType::Inquire => write!(f, "2"),
-            StepType::Sign => write!(f, "3"),
-            StepType::Allocation => write!(f, "4"),
+            StepType::Receipt => 1,
+            StepType::Inquire => 2,
+            StepType::Sign => 3,
+            StepType::Allocation => 4,
+        }
+    }
+
+    /// Parse the code of a step type, as stored on chain
+    pub fn from_code(code: u64) -> Option<Self> {
+        match code {
+            1 => Some(StepType::Receipt),
+            2 => Some(StepType::Inquire),
+            3 => Some(StepType::Sign),
+            4 => Some(StepType::Allocation),
+            _ => None,
         }
     }
 }
 
+impl TryFrom<u64> for StepType {
+    type Error = anyhow::Error;
+
+    fn try_from(code: u64) -> Result<Self> {
+        Self::from_code(code).ok_or_else(|| anyhow!("Unknown step type `{code}`"))
+    }
+}
+
+impl TryFrom<&str> for StepType {
+    type Error = anyhow::Error;
+
+    fn try_from(code: &str) -> Result<Self> {
+        code.parse::<u64>().map_err(|_| anyhow!("Unknown step type `{code}`"))?.try_into()
+    }
+}
+
+impl fmt::Display for StepType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Receipt => "Receipt",
+            Self::Inquire => "Inquire",
+            Self::Sign => "Sign",
+            Self::Allocation => "Allocation",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Status {
     Created,
     InProgress,
     Completed,
 }
 
+impl Status {
+    /// The code of the status, as stored on chain
+    pub fn code(self) -> u64 {
+        match self {
+            Self::Created => 0,
+            Self::InProgress => 1,
+            Self::Completed => 2,
+        }
+    }
+
+    /// Parse the code of a status, as stored on chain
+    pub fn from_code(code: u64) -> Option<Self> {
+        match code {
+            0 => Some(Self::Created),
+            1 => Some(Self::InProgress),
+            2 => Some(Self::Completed),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<u64> for Status {
+    type Error = anyhow::Error;
+
+    fn try_from(code: u64) -> Result<Self> {
+        Self::from_code(code).ok_or_else(|| anyhow!("Unknown workflow status `{code}`"))
+    }
+}
+
+impl TryFrom<&str> for Status {
+    type Error = anyhow::Error;
+
+    fn try_from(code: &str) -> Result<Self> {
+        code.parse::<u64>().map_err(|_| anyhow!("Unknown workflow status `{code}`"))?.try_into()
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Created => "Created",
+            Self::InProgress => "InProgress",
+            Self::Completed => "Completed",
+        })
+    }
+}
+
+/// An event emitted by the Workflow contract
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum WorkflowEvent {
+    /// Emitted by `create_dependency`
+    DependencyCreated {
+        github_owner: Owner,
+        workflow_id: Id,
+        dependency_idx: Id,
+        name: String,
+        repository_url: String,
+    },
+    /// Emitted by `add_step`
+    StepAdded {
+        github_owner: Owner,
+        workflow_id: Id,
+        dependency_idx: Id,
+        step_index: Id,
+        step_type: StepType,
+        /// The transaction the step attests
+        tx_hash: Hash,
+        related_entity_id: Id,
+        timestamp: u64,
+    },
+}
+
+/// An event, with the block and transaction it was emitted in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmittedWorkflowEvent {
+    pub event: WorkflowEvent,
+    pub block_number: u64,
+    pub transaction_hash: Hash,
+}
+
+/// A dependency and its receipt, recorded together by `record_dependency_with_receipt`
+#[derive(Debug, Clone)]
+pub struct DependencyReceipt {
+    pub name: String,
+    pub repository_url: String,
+    pub license: String,
+    /// JSON formatted additional data
+    pub metadata_json: String,
+    pub dependency_url: String,
+    pub metadata: ReceiptMetadata,
+    /// Hash value of the complete JSON
+    pub metadata_hash: Hash,
+    /// URI pointing to the complete JSON
+    pub metadata_uri: String,
+}
+
+/// A page of events, with the token of the next page, if any
+pub struct WorkflowEventPage {
+    pub events: Vec<EmittedWorkflowEvent>,
+    pub continuation_token: Option<String>,
+}
+
 /// Workflow contract interface
+#[async_trait]
 pub trait WorkflowContract {
-    /// Create workflow
-    fn create_workflow(
+    /// Create workflow, returning its id unless it is only known once the transaction is
+    /// executed
+    async fn create_workflow(
         &self,
         github_owner: Owner,
         wallet_address: Address,
-    ) -> impl Future<Output = Result<Id>>;
+    ) -> Result<Option<Id>>;
+
+    /// Create dependency, returning its index unless it is only known once the
+    /// transaction is executed
+    async fn create_dependency(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        name: String,
+        repository_url: String,
+        license: String,
+        metadata_json: String,
+    ) -> Result<Option<Id>>;
 
-    /// Create dependency
-    fn create_dependency(
+    /// Build the call of `create_dependency`, to estimate its fee
+    fn create_dependency_call(
         &self,
         github_owner: Owner,
         workflow_id: Id,
@@ -94,10 +303,11 @@ pub trait WorkflowContract {
         repository_url: String,
         license: String,
         metadata_json: String,
-    ) -> impl Future<Output = Result<Id>>;
+    ) -> Result<RawCall>;
 
-    /// Add step
-    fn add_step(
+    /// Add step, returning its index unless it is only known once the transaction is
+    /// executed
+    async fn add_step(
         &self,
         github_owner: Owner,
         workflow_id: Id,
@@ -105,89 +315,125 @@ pub trait WorkflowContract {
         step_type: StepType,
         tx_hash: Hash,
         related_entity_id: Id,
-    ) -> impl Future<Output = Result<Id>>;
+    ) -> Result<Option<Id>>;
 
-    /// Complete dependency
-    fn finish_dependency(
+    /// Build the call of `add_step`, to estimate its fee
+    fn add_step_call(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        dependency_idx: Id,
+        step_type: StepType,
+        tx_hash: Hash,
+        related_entity_id: Id,
+    ) -> Result<RawCall>;
+
+    /// Create a dependency and its receipt, and record the receipt as a step of the
+    /// dependency, in a single transaction, returning its hash
+    ///
+    /// `dependency_idx` is the index the dependency gets, the number of dependencies of the
+    /// workflow. The step references the receipt by the hash of its metadata, its id being
+    /// assigned in the same transaction, and has a zero transaction hash, as a transaction
+    /// cannot contain its own hash.
+    async fn record_dependency_with_receipt(
         &self,
         github_owner: Owner,
         workflow_id: Id,
         dependency_idx: Id,
-    ) -> impl Future<Output = Result<bool>>;
+        record: DependencyReceipt,
+    ) -> Result<Hash>;
 
-    /// Complete workflow
-    fn finish_workflow(
+    /// Complete dependency
+    async fn finish_dependency(
         &self,
         github_owner: Owner,
         workflow_id: Id,
-    ) -> impl Future<Output = Result<bool>>;
+        dependency_idx: Id,
+    ) -> Result<bool>;
+
+    /// Complete workflow
+    async fn finish_workflow(&self, github_owner: Owner, workflow_id: Id) -> Result<bool>;
+
+    /// Build the call of `finish_workflow`, to send it with other calls
+    fn finish_workflow_call(&self, github_owner: Owner, workflow_id: Id) -> Result<RawCall>;
 
     /// Get workflow status
-    fn get_workflow_status(
+    async fn get_workflow_status(&self, github_owner: Owner, workflow_id: Id) -> Result<Workflow>;
+
+    /// Get workflow dependencies
+    async fn get_dependencies(
         &self,
         github_owner: Owner,
         workflow_id: Id,
-    ) -> impl Future<Output = Result<Workflow>>;
+    ) -> Result<Vec<Dependency>>;
 
-    /// Get workflow dependencies
-    fn get_dependencies(
+    /// Get a page of the workflow dependencies, at most `limit` from the `offset`-th one
+    async fn get_dependencies_page(
         &self,
         github_owner: Owner,
         workflow_id: Id,
-    ) -> impl Future<Output = Result<Vec<Dependency>>>;
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<Dependency>>;
 
     /// Get dependency steps
-    fn get_steps(
+    async fn get_steps(
         &self,
         github_owner: Owner,
         workflow_id: Id,
         dependency_idx: Id,
-    ) -> impl Future<Output = Result<Vec<Step>>>;
+    ) -> Result<Vec<Step>>;
 
     /// Get step by transaction hash
     /// (github_owner, workflow_id, dependency_index, step_index)
-    fn get_step_by_tx_hash(
-        &self,
-        tx_hash: Hash,
-    ) -> impl Future<Output = Result<Option<(Owner, Id, Id, Id)>>>;
+    async fn get_step_by_tx_hash(&self, tx_hash: Hash) -> Result<Option<(Owner, Id, Id, Id)>>;
 
     /// Get complete transaction chain
-    fn get_complete_transaction_chain(
+    async fn get_complete_transaction_chain(
         &self,
         github_owner: Owner,
         workflow_id: Id,
         dependency_idx: Id,
-    ) -> impl Future<Output = Result<Vec<Hash>>>;
+    ) -> Result<Vec<Hash>>;
+
+    /// Get the events emitted between two blocks, both included, a page at a time
+    async fn get_events(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        continuation_token: Option<String>,
+    ) -> Result<WorkflowEventPage>;
 
     /// Get user workflow count
-    fn get_workflow_count(&self, github_owner: Owner) -> impl Future<Output = Result<Number>>;
+    async fn get_workflow_count(&self, github_owner: Owner) -> Result<Number>;
 
     /// Get all user workflows
-    fn get_all_workflows(
+    async fn get_all_workflows(&self, github_owner: Owner) -> Result<Vec<(Number, Workflow)>>;
+
+    /// Get a page of the user workflows, at most `limit` from the `offset`-th one
+    async fn get_workflows_page(
         &self,
         github_owner: Owner,
-    ) -> impl Future<Output = Result<Vec<(Number, Workflow)>>>;
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<(Number, Workflow)>>;
 
     /// Bind multisig wallet address to workflow
-    fn bind_wallet_address(
+    async fn bind_wallet_address(
         &self,
         github_owner: Owner,
         workflow_id: Id,
         wallet_address: Address,
-    ) -> impl Future<Output = Result<bool>>;
+    ) -> Result<bool>;
 
     /// Unbind multisig wallet address
-    fn unbind_wallet_address(
-        &self,
-        github_owner: Owner,
-        workflow_id: Id,
-    ) -> impl Future<Output = Result<bool>>;
+    async fn unbind_wallet_address(&self, github_owner: Owner, workflow_id: Id) -> Result<bool>;
 
     /// Change multisig wallet address
-    fn change_wallet_address(
+    async fn change_wallet_address(
         &self,
         github_owner: Owner,
         workflow_id: Id,
         new_wallet_address: Address,
-    ) -> impl Future<Output = Result<bool>>;
+    ) -> Result<bool>;
 }
@@ -0,0 +1,78 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The address books of the contributors, their payout address on each chain.
+
+use std::collections::BTreeMap;
+
+use anyhow::Context as _;
+use sqlx::{FromRow, PgPool};
+
+use super::{from_text, to_text};
+use crate::services::address::AddressBookEntry;
+
+#[derive(FromRow)]
+struct AddressRow {
+    username: String,
+    chain: String,
+    address: String,
+    preferred: bool,
+}
+
+/// Load every address book, by GitHub username.
+pub async fn load(pool: &PgPool) -> anyhow::Result<BTreeMap<String, AddressBookEntry>> {
+    let rows: Vec<AddressRow> =
+        sqlx::query_as("SELECT username, chain, address, preferred FROM contributor_addresses")
+            .fetch_all(pool)
+            .await
+            .context("Failed to load the address books")?;
+
+    let mut entries: BTreeMap<String, AddressBookEntry> = BTreeMap::new();
+    for row in rows {
+        let chain = from_text(&row.chain)?;
+        let entry = entries.entry(row.username).or_default();
+        entry.addresses.insert(chain, row.address);
+        if row.preferred {
+            entry.preferred = Some(chain);
+        }
+    }
+    Ok(entries)
+}
+
+/// Replace the address book of a contributor, removing it when it has no address left.
+pub async fn replace(
+    pool: &PgPool,
+    username: &str,
+    entry: &AddressBookEntry,
+) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM contributor_addresses WHERE username = $1")
+        .bind(username)
+        .execute(&mut *tx)
+        .await?;
+    for (chain, address) in &entry.addresses {
+        sqlx::query(
+            "INSERT INTO contributor_addresses (username, chain, address, preferred)
+             VALUES ($1, $2, $3, $4)",
+        )
+        .bind(username)
+        .bind(to_text(chain))
+        .bind(address)
+        .bind(entry.preferred == Some(*chain))
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
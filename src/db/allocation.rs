@@ -0,0 +1,173 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! The allocations followed by the execution worker.
+//!
+//! The times of the payout attempts are monotonic instants in memory, stored as the
+//! wall-clock times they correspond to. The time of the next attempt is not stored, so the
+//! allocations left to retry are retried right after a restart.
+
+use std::time::Instant;
+
+use anyhow::Context as _;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use super::{from_text, to_text};
+use crate::services::allocation::{AllocationRecord, ExecutionStatus, FundedPackage};
+
+#[derive(FromRow)]
+struct AllocationRow {
+    id: String,
+    workflow_id: Uuid,
+    contributor: Option<String>,
+    ecosystem: Option<String>,
+    package: Option<String>,
+    snapshot_id: Option<Uuid>,
+    recipient: String,
+    amount: String,
+    denomination: String,
+    token: String,
+    usd_rate: Option<String>,
+    token_amount: Option<String>,
+    status: String,
+    tx_hash: Option<String>,
+    submitted_at: Option<DateTime<Utc>>,
+    executed_at: Option<DateTime<Utc>>,
+    attempts: i32,
+    error: Option<String>,
+    reallocate: bool,
+}
+
+impl TryFrom<AllocationRow> for AllocationRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(row: AllocationRow) -> anyhow::Result<Self> {
+        let package = match (row.ecosystem, row.package) {
+            (Some(ecosystem), Some(name)) => {
+                Some(FundedPackage { ecosystem: from_text(&ecosystem)?, name })
+            }
+            _ => None,
+        };
+
+        Ok(Self {
+            package,
+            denomination: from_text(&row.denomination)?,
+            token: row.token.parse()?,
+            status: status(&row.status)?,
+            submitted_at: row.submitted_at.map(to_instant),
+            executed_at: row.executed_at.map(to_instant),
+            attempts: row.attempts.max(0) as u32,
+            retry_at: None,
+            id: row.id.parse()?,
+            workflow_id: row.workflow_id,
+            contributor: row.contributor,
+            snapshot_id: row.snapshot_id,
+            recipient: row.recipient.parse()?,
+            amount: row.amount,
+            usd_rate: row.usd_rate,
+            token_amount: row.token_amount,
+            tx_hash: row.tx_hash.as_deref().map(str::parse).transpose()?,
+            error: row.error,
+            reallocate: row.reallocate,
+        })
+    }
+}
+
+/// Load every allocation.
+pub async fn load(pool: &PgPool) -> anyhow::Result<Vec<AllocationRecord>> {
+    let rows: Vec<AllocationRow> = sqlx::query_as("SELECT * FROM allocations")
+        .fetch_all(pool)
+        .await
+        .context("Failed to load the allocations")?;
+    rows.into_iter()
+        .map(|row| {
+            let id = row.id.clone();
+            AllocationRecord::try_from(row).with_context(|| format!("Invalid allocation {id}"))
+        })
+        .collect()
+}
+
+/// Write an allocation, as it is now.
+pub async fn upsert(pool: &PgPool, record: &AllocationRecord) -> anyhow::Result<()> {
+    sqlx::query(
+        "INSERT INTO allocations (id, workflow_id, contributor, ecosystem, package, snapshot_id,
+             recipient, amount, denomination, token, usd_rate, token_amount, status, tx_hash,
+             submitted_at, executed_at, attempts, error, reallocate)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17,
+             $18, $19)
+         ON CONFLICT (id) DO UPDATE SET
+             contributor = EXCLUDED.contributor,
+             ecosystem = EXCLUDED.ecosystem,
+             package = EXCLUDED.package,
+             snapshot_id = EXCLUDED.snapshot_id,
+             recipient = EXCLUDED.recipient,
+             amount = EXCLUDED.amount,
+             denomination = EXCLUDED.denomination,
+             token = EXCLUDED.token,
+             usd_rate = EXCLUDED.usd_rate,
+             token_amount = EXCLUDED.token_amount,
+             status = EXCLUDED.status,
+             tx_hash = EXCLUDED.tx_hash,
+             submitted_at = EXCLUDED.submitted_at,
+             executed_at = EXCLUDED.executed_at,
+             attempts = EXCLUDED.attempts,
+             error = EXCLUDED.error,
+             reallocate = EXCLUDED.reallocate",
+    )
+    .bind(&*record.id)
+    .bind(record.workflow_id)
+    .bind(&record.contributor)
+    .bind(record.package.as_ref().map(|package| to_text(&package.ecosystem)))
+    .bind(record.package.as_ref().map(|package| &package.name))
+    .bind(record.snapshot_id)
+    .bind(&*record.recipient)
+    .bind(&record.amount)
+    .bind(to_text(&record.denomination))
+    .bind(record.token.to_string())
+    .bind(&record.usd_rate)
+    .bind(&record.token_amount)
+    .bind(record.status.as_str())
+    .bind(&record.tx_hash)
+    .bind(record.submitted_at.map(to_datetime))
+    .bind(record.executed_at.map(to_datetime))
+    .bind(record.attempts as i32)
+    .bind(&record.error)
+    .bind(record.reallocate)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+fn status(text: &str) -> anyhow::Result<ExecutionStatus> {
+    match text {
+        "approved" => Ok(ExecutionStatus::Approved),
+        "awaiting_claim" => Ok(ExecutionStatus::AwaitingClaim),
+        "submitted" => Ok(ExecutionStatus::Submitted),
+        "executed" => Ok(ExecutionStatus::Executed),
+        "failed" => Ok(ExecutionStatus::Failed),
+        _ => anyhow::bail!("Unknown execution status `{text}`"),
+    }
+}
+
+/// The wall-clock time of an instant.
+fn to_datetime(instant: Instant) -> DateTime<Utc> {
+    Utc::now() - chrono::Duration::from_std(instant.elapsed()).unwrap_or_default()
+}
+
+/// The instant of a wall-clock time, now if it is in the future or too far in the past.
+fn to_instant(time: DateTime<Utc>) -> Instant {
+    let elapsed = (Utc::now() - time).to_std().unwrap_or_default();
+    Instant::now().checked_sub(elapsed).unwrap_or_else(Instant::now)
+}
@@ -0,0 +1,118 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! The published terms and their attestations, in the order they were recorded.
+
+use anyhow::Context as _;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgPool};
+
+use crate::services::attestation::{Attestation, Terms};
+
+#[derive(FromRow)]
+struct TermsRow {
+    version: String,
+    title: String,
+    content: String,
+    content_hash: String,
+    published_at: DateTime<Utc>,
+}
+
+#[derive(FromRow)]
+struct AttestationRow {
+    address: String,
+    username: Option<String>,
+    version: String,
+    content_hash: String,
+    ip: Option<String>,
+    accepted_at: DateTime<Utc>,
+}
+
+/// Load every version of the terms, oldest first.
+pub async fn load_terms(pool: &PgPool) -> anyhow::Result<Vec<Terms>> {
+    let rows: Vec<TermsRow> = sqlx::query_as(
+        "SELECT version, title, content, content_hash, published_at
+         FROM terms ORDER BY published_at",
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to load the terms")?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| Terms {
+            version: row.version,
+            title: row.title,
+            content: row.content,
+            content_hash: row.content_hash,
+            published_at: row.published_at,
+        })
+        .collect())
+}
+
+/// Load every attestation, oldest first.
+pub async fn load(pool: &PgPool) -> anyhow::Result<Vec<Attestation>> {
+    let rows: Vec<AttestationRow> = sqlx::query_as(
+        "SELECT address, username, version, content_hash, ip, accepted_at
+         FROM attestations ORDER BY id",
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to load the attestations")?;
+
+    rows.into_iter()
+        .map(|row| {
+            Ok(Attestation {
+                ip: row.ip.map(|ip| ip.parse()).transpose()?,
+                address: row.address,
+                username: row.username,
+                version: row.version,
+                content_hash: row.content_hash,
+                accepted_at: row.accepted_at,
+            })
+        })
+        .collect()
+}
+
+/// Write a version of the terms, refused if it is published already.
+pub async fn insert_terms(pool: &PgPool, terms: &Terms) -> anyhow::Result<()> {
+    sqlx::query(
+        "INSERT INTO terms (version, title, content, content_hash, published_at)
+         VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(&terms.version)
+    .bind(&terms.title)
+    .bind(&terms.content)
+    .bind(&terms.content_hash)
+    .bind(terms.published_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Append an attestation.
+pub async fn insert(pool: &PgPool, attestation: &Attestation) -> anyhow::Result<()> {
+    sqlx::query(
+        "INSERT INTO attestations (address, username, version, content_hash, ip, accepted_at)
+         VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(&attestation.address)
+    .bind(&attestation.username)
+    .bind(&attestation.version)
+    .bind(&attestation.content_hash)
+    .bind(attestation.ip.map(|ip| ip.to_string()))
+    .bind(attestation.accepted_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,118 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! The contributions of the workflows, the scores of the dependencies of their snapshots.
+
+use anyhow::Context as _;
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use super::{from_text, to_text};
+use crate::responses::dependency::{DependencyResponse, Ecosystem};
+
+/// The contribution of a dependency to a workflow, as of a snapshot.
+#[derive(Debug, Clone)]
+pub struct ContributionRow {
+    pub workflow_id: Uuid,
+    pub snapshot_id: Uuid,
+    pub ecosystem: Ecosystem,
+    pub name: String,
+    pub version: String,
+    pub score: f64,
+}
+
+impl ContributionRow {
+    /// The contributions of the dependencies of a snapshot to a workflow.
+    pub fn from_dependencies(
+        workflow_id: Uuid,
+        snapshot_id: Uuid,
+        dependencies: &[DependencyResponse],
+    ) -> Vec<Self> {
+        dependencies
+            .iter()
+            .map(|dependency| Self {
+                workflow_id,
+                snapshot_id,
+                ecosystem: dependency.ecosystem,
+                name: dependency.name.clone(),
+                version: dependency.version.clone(),
+                score: dependency.score,
+            })
+            .collect()
+    }
+}
+
+/// Write the contributions computed for a snapshot, all or none.
+pub async fn insert(pool: &PgPool, rows: &[ContributionRow]) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+    for row in rows {
+        sqlx::query(
+            "INSERT INTO contributions (workflow_id, snapshot_id, ecosystem, name, version, score)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT (workflow_id, snapshot_id, ecosystem, name) DO UPDATE SET
+                 version = EXCLUDED.version,
+                 score = EXCLUDED.score",
+        )
+        .bind(row.workflow_id)
+        .bind(row.snapshot_id)
+        .bind(to_text(&row.ecosystem))
+        .bind(&row.name)
+        .bind(&row.version)
+        .bind(row.score)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+#[derive(FromRow)]
+struct Row {
+    workflow_id: Uuid,
+    snapshot_id: Uuid,
+    ecosystem: String,
+    name: String,
+    version: String,
+    score: f64,
+}
+
+/// The contributions of a snapshot to a workflow, the highest scores first.
+pub async fn list(
+    pool: &PgPool,
+    workflow_id: Uuid,
+    snapshot_id: Uuid,
+) -> anyhow::Result<Vec<ContributionRow>> {
+    let rows: Vec<Row> = sqlx::query_as(
+        "SELECT workflow_id, snapshot_id, ecosystem, name, version, score
+         FROM contributions
+         WHERE workflow_id = $1 AND snapshot_id = $2
+         ORDER BY score DESC, ecosystem, name",
+    )
+    .bind(workflow_id)
+    .bind(snapshot_id)
+    .fetch_all(pool)
+    .await
+    .context("Failed to load the contributions")?;
+    rows.into_iter()
+        .map(|row| {
+            Ok(ContributionRow {
+                ecosystem: from_text(&row.ecosystem)?,
+                workflow_id: row.workflow_id,
+                snapshot_id: row.snapshot_id,
+                name: row.name,
+                version: row.version,
+                score: row.score,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,51 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! The dependencies created on chain by the workflows.
+
+use sqlx::PgPool;
+
+use crate::contracts::types::{Id, Owner};
+
+/// A dependency created on chain, with its metadata in canonical form.
+#[derive(Debug, Clone)]
+pub struct DependencyRow {
+    /// The id of the dependency on chain
+    pub id: Id,
+    pub workflow_id: Id,
+    pub github_owner: Owner,
+    pub name: String,
+    pub repository_url: String,
+    pub license: String,
+    pub metadata_json: String,
+}
+
+/// Write a dependency, once created on chain.
+pub async fn insert(pool: &PgPool, row: &DependencyRow) -> anyhow::Result<()> {
+    sqlx::query(
+        "INSERT INTO dependencies (id, workflow_id, github_owner, name, repository_url, license,
+             metadata_json)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)
+         ON CONFLICT (id) DO NOTHING",
+    )
+    .bind(&*row.id)
+    .bind(&*row.workflow_id)
+    .bind(&*row.github_owner)
+    .bind(&row.name)
+    .bind(&row.repository_url)
+    .bind(&row.license)
+    .bind(&row.metadata_json)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
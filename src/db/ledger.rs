@@ -0,0 +1,84 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! The entries of the ledger, in the order they were posted.
+
+use anyhow::Context as _;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use super::{from_text, to_text};
+use crate::services::ledger::LedgerEntry;
+
+#[derive(FromRow)]
+struct LedgerRow {
+    workflow_id: Uuid,
+    account: String,
+    side: String,
+    amount: String,
+    token: String,
+    allocation_id: String,
+    tx_hash: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+/// Load every entry, oldest first.
+pub async fn load(pool: &PgPool) -> anyhow::Result<Vec<LedgerEntry>> {
+    let rows: Vec<LedgerRow> = sqlx::query_as(
+        "SELECT workflow_id, account, side, amount, token, allocation_id, tx_hash, created_at
+         FROM ledger_entries ORDER BY id",
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to load the ledger")?;
+
+    rows.into_iter()
+        .map(|row| {
+            Ok(LedgerEntry {
+                side: from_text(&row.side)?,
+                workflow_id: row.workflow_id,
+                account: row.account,
+                amount: row.amount,
+                token: row.token,
+                allocation_id: row.allocation_id.parse()?,
+                tx_hash: row.tx_hash.as_deref().map(str::parse).transpose()?,
+                created_at: row.created_at,
+            })
+        })
+        .collect()
+}
+
+/// Append the entries posted together, all or none.
+pub async fn insert(pool: &PgPool, entries: &[LedgerEntry]) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+    for entry in entries {
+        sqlx::query(
+            "INSERT INTO ledger_entries
+                 (workflow_id, account, side, amount, token, allocation_id, tx_hash, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(entry.workflow_id)
+        .bind(&entry.account)
+        .bind(to_text(&entry.side))
+        .bind(&entry.amount)
+        .bind(&entry.token)
+        .bind(&*entry.allocation_id)
+        .bind(&entry.tx_hash)
+        .bind(entry.created_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
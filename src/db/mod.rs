@@ -0,0 +1,235 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! The Postgres database the state of the workflows is kept in.
+//!
+//! The chain settles the workflows, the database keeps what the backend knows of them:
+//! the workflows and their signatures, the ranking snapshots, dependencies,
+//! contributions, receipts, allocations, ledger, budget pools, terms and their
+//! attestations, organizations, address books and outbound webhooks. The stores are
+//! loaded from it at startup, and every change to them is written through to it, in
+//! order, by a single writer task, so the requests never wait on the database, but for
+//! the changes to the funds, the payout addresses, the attestations and the
+//! organizations: those are committed, waiting for them to be written. The queue is
+//! flushed on shutdown. The contributions are read back from it. Without a database URL
+//! the stores are kept in memory only, and lost on restart.
+//!
+//! The migrations in `migrations/` are embedded in the binary and run at startup.
+
+pub mod address;
+pub mod allocation;
+pub mod attestation;
+pub mod contribution;
+pub mod dependency;
+pub mod ledger;
+pub mod organization;
+pub mod pool;
+pub mod receipt;
+pub mod snapshot;
+pub mod webhook;
+pub mod workflow;
+
+use std::{future::Future, sync::Arc, time::Duration};
+
+use anyhow::Context as _;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use sqlx::{postgres::PgPoolOptions, PgPool};
+use thiserror::Error;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    services::{
+        address::AddressBookEntry,
+        allocation::AllocationRecord,
+        attestation::{Attestation, Terms},
+        ledger::LedgerEntry,
+        organization::Organization,
+        pool::PoolBalance,
+        snapshot::ProjectSnapshot,
+        webhook::{DeliveryRecord, WebhookRecord},
+        workflow::WorkflowRecord,
+    },
+    telemetry,
+};
+
+use self::{contribution::ContributionRow, dependency::DependencyRow, receipt::ReceiptRow};
+
+#[derive(Clone, clap::Parser)]
+pub struct DatabaseConfig {
+    /// URL of the Postgres database, eg. `postgres://deprank@localhost/deprank`, the
+    /// state is kept in memory only if unset
+    #[clap(long, env = "DRK_DATABASE_URL")]
+    pub database_url: Option<String>,
+
+    /// The maximum number of connections to the database.
+    #[clap(long, env = "DRK_DATABASE_MAX_CONNECTIONS", default_value_t = 10)]
+    pub database_max_connections: u32,
+
+    /// Seconds to wait for a connection to the database.
+    #[clap(long, env = "DRK_DATABASE_CONNECT_TIMEOUT", default_value_t = 10)]
+    pub database_connect_timeout: u64,
+}
+
+/// A change to write through to the database.
+#[derive(Debug)]
+pub enum Write {
+    Workflow(WorkflowRecord),
+    DeleteWorkflow(Uuid),
+    Allocation(AllocationRecord),
+    LedgerEntries(Vec<LedgerEntry>),
+    PoolBalances(Vec<PoolBalance>),
+    Terms(Terms),
+    Attestation(Attestation),
+    Organization(Organization),
+    DeleteOrganization(String),
+    Dependency(DependencyRow),
+    Contributions(Vec<ContributionRow>),
+    Receipt(ReceiptRow),
+    Snapshot(Arc<ProjectSnapshot>),
+    AddressBook(String, AddressBookEntry),
+    Webhook(WebhookRecord),
+    DeleteWebhook(Uuid),
+    Delivery(DeliveryRecord),
+}
+
+/// A change queued for the writer task, with who waits for it to be written, if any.
+enum Queued {
+    Write(Box<Write>, Option<oneshot::Sender<Result<(), WriteError>>>),
+    /// Answered once the changes queued before it are written
+    Flush(oneshot::Sender<()>),
+}
+
+/// A committed change the database did not write.
+#[derive(Debug, Error)]
+#[error("failed to write to the database: {0}")]
+pub struct WriteError(String);
+
+/// The database, and the queue of the changes to write through to it.
+#[derive(Clone)]
+pub struct Database {
+    pool: PgPool,
+    writes: mpsc::UnboundedSender<Queued>,
+}
+
+impl Database {
+    /// Connect to the database and run the pending migrations, `None` if no database is
+    /// configured.
+    pub async fn connect(config: &DatabaseConfig) -> anyhow::Result<Option<Self>> {
+        let Some(url) = &config.database_url else {
+            return Ok(None);
+        };
+
+        let pool = PgPoolOptions::new()
+            .max_connections(config.database_max_connections.max(1))
+            .acquire_timeout(Duration::from_secs(config.database_connect_timeout))
+            .connect(url)
+            .await
+            .context("Failed to connect to the database")?;
+        sqlx::migrate!().run(&pool).await.context("Failed to migrate the database")?;
+        info!("Connected to the database");
+
+        let (writes, queue) = mpsc::unbounded_channel();
+        telemetry::spawn(write_through(pool.clone(), queue));
+        Ok(Some(Self { pool, writes }))
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    /// Queue a change, written once the changes queued before it are.
+    pub fn write(&self, write: Write) {
+        if self.writes.send(Queued::Write(Box::new(write), None)).is_err() {
+            warn!("The database writer stopped, dropping a change");
+        }
+    }
+
+    /// Queue a change right away, eg. under the lock of its store so the changes keep
+    /// their order, returning the future of its write, failing if it was not written.
+    pub fn commit(&self, write: Write) -> impl Future<Output = Result<(), WriteError>> {
+        let (done, written) = oneshot::channel();
+        let queued = self.writes.send(Queued::Write(Box::new(write), Some(done))).is_ok();
+        async move {
+            let stopped = || WriteError("the database writer stopped".to_string());
+            if !queued {
+                return Err(stopped());
+            }
+            written.await.unwrap_or_else(|_| Err(stopped()))
+        }
+    }
+
+    /// Wait for the changes queued so far to be written, eg. before shutting down.
+    pub async fn flush(&self) {
+        let (done, flushed) = oneshot::channel();
+        if self.writes.send(Queued::Flush(done)).is_ok() {
+            let _ = flushed.await;
+        }
+    }
+}
+
+/// Write the queued changes, in order, until every store is dropped.
+async fn write_through(pool: PgPool, mut queue: mpsc::UnboundedReceiver<Queued>) {
+    while let Some(queued) = queue.recv().await {
+        let (write, done) = match queued {
+            Queued::Write(write, done) => (write, done),
+            Queued::Flush(done) => {
+                let _ = done.send(());
+                continue;
+            }
+        };
+        let result = match write.as_ref() {
+            Write::Workflow(record) => workflow::upsert(&pool, record).await,
+            Write::DeleteWorkflow(id) => workflow::delete(&pool, *id).await,
+            Write::Allocation(record) => allocation::upsert(&pool, record).await,
+            Write::LedgerEntries(entries) => ledger::insert(&pool, entries).await,
+            Write::PoolBalances(balances) => pool::upsert(&pool, balances).await,
+            Write::Terms(terms) => attestation::insert_terms(&pool, terms).await,
+            Write::Attestation(attestation) => attestation::insert(&pool, attestation).await,
+            Write::Organization(organization) => organization::upsert(&pool, organization).await,
+            Write::DeleteOrganization(name) => organization::delete(&pool, name).await,
+            Write::Dependency(row) => dependency::insert(&pool, row).await,
+            Write::Contributions(rows) => contribution::insert(&pool, rows).await,
+            Write::Receipt(row) => receipt::insert(&pool, row).await,
+            Write::Snapshot(snapshot) => snapshot::insert(&pool, snapshot).await,
+            Write::AddressBook(username, entry) => address::replace(&pool, username, entry).await,
+            Write::Webhook(record) => webhook::insert(&pool, record).await,
+            Write::DeleteWebhook(id) => webhook::delete(&pool, *id).await,
+            Write::Delivery(record) => webhook::upsert_delivery(&pool, record).await,
+        };
+        match (result, done) {
+            (result, Some(done)) => {
+                let _ = done.send(result.map_err(|e| WriteError(format!("{e:#}"))));
+            }
+            (Err(e), None) => warn!("Failed to write to the database: {e:#}"),
+            (Ok(()), None) => {}
+        }
+    }
+}
+
+/// The serialized name of a unit enum variant, as stored in a text column.
+fn to_text<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(Value::String(text)) => text,
+        Ok(value) => value.to_string(),
+        Err(_) => String::new(),
+    }
+}
+
+/// Parse a unit enum variant from its serialized name, as stored in a text column.
+fn from_text<T: DeserializeOwned>(text: &str) -> anyhow::Result<T> {
+    serde_json::from_value(Value::String(text.to_string()))
+        .with_context(|| format!("Unknown value `{text}`"))
+}
@@ -0,0 +1,129 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! The organizations and their members.
+
+use std::collections::BTreeMap;
+
+use anyhow::Context as _;
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgPool};
+
+use super::{from_text, to_text};
+use crate::services::organization::{Member, Organization};
+
+#[derive(FromRow)]
+struct OrganizationRow {
+    name: String,
+    display_name: String,
+    owners: Vec<String>,
+    treasury: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(FromRow)]
+struct MemberRow {
+    organization: String,
+    username: String,
+    role: String,
+    address: Option<String>,
+    client: Option<String>,
+}
+
+/// Load every organization, with its members.
+pub async fn load(pool: &PgPool) -> anyhow::Result<Vec<Organization>> {
+    let rows: Vec<OrganizationRow> = sqlx::query_as(
+        "SELECT name, display_name, owners, treasury, created_at FROM organizations",
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to load the organizations")?;
+    let members: Vec<MemberRow> = sqlx::query_as(
+        "SELECT organization, username, role, address, client FROM organization_members",
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to load the organization members")?;
+
+    let mut organizations: BTreeMap<String, Organization> = rows
+        .into_iter()
+        .map(|row| -> anyhow::Result<_> {
+            let organization = Organization {
+                name: row.name.clone(),
+                display_name: row.display_name,
+                owners: row.owners,
+                treasury: row.treasury.as_deref().map(str::parse).transpose()?,
+                members: BTreeMap::new(),
+                created_at: row.created_at,
+            };
+            Ok((row.name, organization))
+        })
+        .collect::<anyhow::Result<_>>()?;
+    for row in members {
+        if let Some(organization) = organizations.get_mut(&row.organization) {
+            let member = Member {
+                role: from_text(&row.role)?,
+                address: row.address.as_deref().map(str::parse).transpose()?,
+                client: row.client,
+            };
+            organization.members.insert(row.username, member);
+        }
+    }
+    Ok(organizations.into_values().collect())
+}
+
+/// Write an organization and its members, as they are now.
+pub async fn upsert(pool: &PgPool, organization: &Organization) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        "INSERT INTO organizations (name, display_name, owners, treasury, created_at)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (name) DO UPDATE SET
+             display_name = EXCLUDED.display_name,
+             owners = EXCLUDED.owners,
+             treasury = EXCLUDED.treasury",
+    )
+    .bind(&organization.name)
+    .bind(&organization.display_name)
+    .bind(&organization.owners)
+    .bind(&organization.treasury)
+    .bind(organization.created_at)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("DELETE FROM organization_members WHERE organization = $1")
+        .bind(&organization.name)
+        .execute(&mut *tx)
+        .await?;
+    for (username, member) in &organization.members {
+        sqlx::query(
+            "INSERT INTO organization_members (organization, username, role, address, client)
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(&organization.name)
+        .bind(username)
+        .bind(to_text(&member.role))
+        .bind(&member.address)
+        .bind(&member.client)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Delete an organization, with its members.
+pub async fn delete(pool: &PgPool, name: &str) -> anyhow::Result<()> {
+    sqlx::query("DELETE FROM organizations WHERE name = $1").bind(name).execute(pool).await?;
+    Ok(())
+}
@@ -0,0 +1,76 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The balances of the budget pools of the workflows.
+
+use anyhow::Context as _;
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use super::{from_text, to_text};
+use crate::services::pool::PoolBalance;
+
+#[derive(FromRow)]
+struct PoolRow {
+    workflow_id: Uuid,
+    token: String,
+    denomination: String,
+    available: String,
+    clawed_back: String,
+}
+
+/// Load every balance.
+pub async fn load(pool: &PgPool) -> anyhow::Result<Vec<PoolBalance>> {
+    let rows: Vec<PoolRow> = sqlx::query_as(
+        "SELECT workflow_id, token, denomination, available, clawed_back FROM pool_balances",
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to load the budget pools")?;
+
+    rows.into_iter()
+        .map(|row| {
+            Ok(PoolBalance {
+                denomination: from_text(&row.denomination)?,
+                workflow_id: row.workflow_id,
+                token: row.token,
+                available: row.available,
+                clawed_back: row.clawed_back,
+            })
+        })
+        .collect()
+}
+
+/// Write the balances changed together, as they are now, all or none.
+pub async fn upsert(pool: &PgPool, balances: &[PoolBalance]) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+    for balance in balances {
+        sqlx::query(
+            "INSERT INTO pool_balances (workflow_id, token, denomination, available, clawed_back)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (workflow_id, token, denomination) DO UPDATE SET
+                 available = EXCLUDED.available,
+                 clawed_back = EXCLUDED.clawed_back",
+        )
+        .bind(balance.workflow_id)
+        .bind(&balance.token)
+        .bind(to_text(&balance.denomination))
+        .bind(&balance.available)
+        .bind(&balance.clawed_back)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
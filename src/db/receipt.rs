@@ -0,0 +1,55 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! The receipts created on chain, with where their metadata is stored.
+
+use sqlx::PgPool;
+
+use crate::contracts::types::{Hash, Id};
+
+/// A receipt created on chain.
+#[derive(Debug, Clone)]
+pub struct ReceiptRow {
+    /// The id of the receipt on chain
+    pub id: Id,
+    pub workflow_id: Id,
+    pub repository_url: String,
+    pub name: String,
+    pub version: String,
+    pub license: String,
+    /// The hash of the metadata document, as stored on chain
+    pub metadata_hash: Hash,
+    /// Where the metadata document is stored
+    pub metadata_uri: String,
+}
+
+/// Write a receipt, once created on chain.
+pub async fn insert(pool: &PgPool, row: &ReceiptRow) -> anyhow::Result<()> {
+    sqlx::query(
+        "INSERT INTO receipts (id, workflow_id, repository_url, name, version, license,
+             metadata_hash, metadata_uri)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+         ON CONFLICT (id) DO NOTHING",
+    )
+    .bind(&*row.id)
+    .bind(&*row.workflow_id)
+    .bind(&row.repository_url)
+    .bind(&row.name)
+    .bind(&row.version)
+    .bind(&row.license)
+    .bind(&*row.metadata_hash)
+    .bind(&row.metadata_uri)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,155 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The snapshots of the ranking runs of projects, never modified once recorded.
+
+use anyhow::Context as _;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::{
+    analyzers::{PackageKey, ResolvedGraph},
+    services::snapshot::ProjectSnapshot,
+};
+
+#[derive(FromRow)]
+struct SnapshotRow {
+    id: Uuid,
+    project: String,
+    commit_hash: String,
+    algorithm_version: String,
+    profile: String,
+    weights_json: String,
+    dependencies_json: String,
+    graph_json: String,
+    created_at: DateTime<Utc>,
+}
+
+/// A resolved graph as stored, its maps being keyed by packages.
+#[derive(Serialize, Deserialize)]
+struct GraphJson {
+    direct: Vec<PackageKey>,
+    edges: Vec<(PackageKey, Vec<PackageKey>)>,
+}
+
+impl From<&ResolvedGraph> for GraphJson {
+    fn from(graph: &ResolvedGraph) -> Self {
+        Self {
+            direct: graph.direct.iter().cloned().collect(),
+            edges: graph
+                .edges
+                .iter()
+                .map(|(package, dependencies)| {
+                    (package.clone(), dependencies.iter().cloned().collect())
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<GraphJson> for ResolvedGraph {
+    fn from(graph: GraphJson) -> Self {
+        Self {
+            direct: graph.direct.into_iter().collect(),
+            edges: graph
+                .edges
+                .into_iter()
+                .map(|(package, dependencies)| (package, dependencies.into_iter().collect()))
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<SnapshotRow> for ProjectSnapshot {
+    type Error = anyhow::Error;
+
+    fn try_from(row: SnapshotRow) -> anyhow::Result<Self> {
+        let graph: GraphJson = serde_json::from_str(&row.graph_json)?;
+        Ok(Self {
+            id: row.id,
+            project: row.project,
+            commit: row.commit_hash,
+            algorithm_version: row.algorithm_version,
+            profile: row.profile,
+            weights: serde_json::from_str(&row.weights_json)?,
+            created_at: row.created_at,
+            dependencies: serde_json::from_str(&row.dependencies_json)?,
+            graph: graph.into(),
+        })
+    }
+}
+
+/// Load every snapshot.
+pub async fn load(pool: &PgPool) -> anyhow::Result<Vec<ProjectSnapshot>> {
+    let rows: Vec<SnapshotRow> = sqlx::query_as(
+        "SELECT id, project, commit_hash, algorithm_version, profile, weights_json,
+                dependencies_json, graph_json, created_at
+         FROM snapshots",
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to load the snapshots")?;
+
+    rows.into_iter()
+        .map(|row| {
+            let id = row.id;
+            ProjectSnapshot::try_from(row).with_context(|| format!("Invalid snapshot {id}"))
+        })
+        .collect()
+}
+
+/// Insert a snapshot, once recorded.
+pub async fn insert(pool: &PgPool, snapshot: &ProjectSnapshot) -> anyhow::Result<()> {
+    sqlx::query(
+        "INSERT INTO snapshots (id, project, commit_hash, algorithm_version, profile, weights_json,
+                                dependencies_json, graph_json, created_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+         ON CONFLICT (id) DO NOTHING",
+    )
+    .bind(snapshot.id)
+    .bind(&snapshot.project)
+    .bind(&snapshot.commit)
+    .bind(&snapshot.algorithm_version)
+    .bind(&snapshot.profile)
+    .bind(serde_json::to_string(&snapshot.weights)?)
+    .bind(serde_json::to_string(&snapshot.dependencies)?)
+    .bind(serde_json::to_string(&GraphJson::from(&snapshot.graph))?)
+    .bind(snapshot.created_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::responses::dependency::Ecosystem;
+
+    #[test]
+    fn graphs_round_trip() {
+        let serde = (Ecosystem::Cargo, "serde".to_string());
+        let derive = (Ecosystem::Cargo, "serde_derive".to_string());
+        let graph = ResolvedGraph {
+            direct: [serde.clone()].into(),
+            edges: [(serde.clone(), [derive.clone()].into()), (derive, Default::default())].into(),
+        };
+
+        let json = serde_json::to_string(&GraphJson::from(&graph)).unwrap();
+        let stored = ResolvedGraph::from(serde_json::from_str::<GraphJson>(&json).unwrap());
+        assert_eq!(stored.direct, graph.direct);
+        assert_eq!(stored.edges, graph.edges);
+    }
+}
@@ -0,0 +1,151 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! The outbound webhooks, and their deliveries still to post.
+
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use super::{from_text, to_text};
+use crate::services::webhook::{DeliveryRecord, WebhookRecord};
+
+#[derive(FromRow)]
+struct WebhookRow {
+    id: Uuid,
+    owner: String,
+    url: String,
+    secret: String,
+    events: Vec<String>,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(FromRow)]
+struct DeliveryRow {
+    id: Uuid,
+    webhook_id: Uuid,
+    event: String,
+    payload: String,
+    status: String,
+    attempts: i32,
+    next_attempt_at: DateTime<Utc>,
+    error: Option<String>,
+    created_at: DateTime<Utc>,
+    delivered_at: Option<DateTime<Utc>>,
+}
+
+/// Load every webhook.
+pub async fn load(pool: &PgPool) -> anyhow::Result<Vec<WebhookRecord>> {
+    let rows: Vec<WebhookRow> = sqlx::query_as(
+        "SELECT id, owner, url, secret, events, created_at FROM webhooks ORDER BY created_at",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| {
+            Ok(WebhookRecord {
+                id: row.id,
+                owner: row.owner,
+                url: row.url,
+                secret: row.secret,
+                events: row
+                    .events
+                    .iter()
+                    .map(|event| from_text(event))
+                    .collect::<anyhow::Result<_>>()?,
+                created_at: row.created_at,
+            })
+        })
+        .collect()
+}
+
+/// Load the deliveries still to post, oldest first.
+pub async fn load_pending(pool: &PgPool) -> anyhow::Result<Vec<DeliveryRecord>> {
+    let rows: Vec<DeliveryRow> = sqlx::query_as(
+        "SELECT id, webhook_id, event, payload, status, attempts, next_attempt_at, error,
+             created_at, delivered_at
+         FROM webhook_deliveries WHERE status = 'pending' ORDER BY created_at",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| {
+            Ok(DeliveryRecord {
+                id: row.id,
+                webhook_id: row.webhook_id,
+                event: from_text(&row.event)?,
+                payload: row.payload,
+                status: from_text(&row.status)?,
+                attempts: row.attempts as u32,
+                next_attempt_at: row.next_attempt_at,
+                error: row.error,
+                created_at: row.created_at,
+                delivered_at: row.delivered_at,
+            })
+        })
+        .collect()
+}
+
+/// Write a webhook, once registered.
+pub async fn insert(pool: &PgPool, record: &WebhookRecord) -> anyhow::Result<()> {
+    sqlx::query(
+        "INSERT INTO webhooks (id, owner, url, secret, events, created_at)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         ON CONFLICT (id) DO NOTHING",
+    )
+    .bind(record.id)
+    .bind(&record.owner)
+    .bind(&record.url)
+    .bind(&record.secret)
+    .bind(record.events.iter().map(to_text).collect::<Vec<_>>())
+    .bind(record.created_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Delete a webhook, with its deliveries.
+pub async fn delete(pool: &PgPool, id: Uuid) -> anyhow::Result<()> {
+    sqlx::query("DELETE FROM webhooks WHERE id = $1").bind(id).execute(pool).await?;
+    Ok(())
+}
+
+/// Write a delivery, once queued and whenever it is attempted.
+pub async fn upsert_delivery(pool: &PgPool, record: &DeliveryRecord) -> anyhow::Result<()> {
+    sqlx::query(
+        "INSERT INTO webhook_deliveries (id, webhook_id, event, payload, status, attempts,
+             next_attempt_at, error, created_at, delivered_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+         ON CONFLICT (id) DO UPDATE SET
+             status = EXCLUDED.status,
+             attempts = EXCLUDED.attempts,
+             next_attempt_at = EXCLUDED.next_attempt_at,
+             error = EXCLUDED.error,
+             delivered_at = EXCLUDED.delivered_at",
+    )
+    .bind(record.id)
+    .bind(record.webhook_id)
+    .bind(to_text(&record.event))
+    .bind(&record.payload)
+    .bind(to_text(&record.status))
+    .bind(record.attempts as i32)
+    .bind(record.next_attempt_at)
+    .bind(&record.error)
+    .bind(record.created_at)
+    .bind(record.delivered_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,221 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! The workflows, with the signatures they collected and their transitions.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use super::{from_text, to_text};
+use crate::services::workflow::{SignatureRecord, TransitionRecord, WorkflowRecord};
+
+#[derive(FromRow)]
+struct WorkflowRow {
+    id: Uuid,
+    chain_id: Option<String>,
+    project: String,
+    repo: String,
+    branch: Option<String>,
+    tag: Option<String>,
+    rev: Option<String>,
+    profile: String,
+    organization: Option<String>,
+    client: Option<String>,
+    snapshot_id: Option<Uuid>,
+    wallet_address: Option<String>,
+    state: String,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(FromRow)]
+struct SignatureRow {
+    workflow_id: Uuid,
+    signer: String,
+    signature_hash: String,
+    tx_hash: String,
+    signed_at: DateTime<Utc>,
+}
+
+#[derive(FromRow)]
+struct TransitionRow {
+    workflow_id: Uuid,
+    from_state: String,
+    to_state: String,
+    error: Option<String>,
+    step: Option<String>,
+    attested_tx_hash: String,
+    tx_hash: Option<String>,
+    transitioned_at: DateTime<Utc>,
+}
+
+impl TryFrom<TransitionRow> for TransitionRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(row: TransitionRow) -> anyhow::Result<Self> {
+        Ok(Self {
+            from: from_text(&row.from_state)?,
+            to: from_text(&row.to_state)?,
+            error: row.error,
+            step: row.step.as_deref().map(from_text).transpose()?,
+            attested_tx_hash: row.attested_tx_hash.parse()?,
+            tx_hash: row.tx_hash.as_deref().map(str::parse).transpose()?,
+            at: row.transitioned_at,
+        })
+    }
+}
+
+/// Load every workflow, with its signatures and transitions oldest first.
+pub async fn load(pool: &PgPool) -> anyhow::Result<Vec<WorkflowRecord>> {
+    let rows: Vec<WorkflowRow> = sqlx::query_as(
+        "SELECT id, chain_id, project, repo, branch, tag, rev, profile, organization, client,
+             snapshot_id, wallet_address, state, created_at, updated_at
+         FROM workflows ORDER BY created_at",
+    )
+    .fetch_all(pool)
+    .await?;
+    let signatures: Vec<SignatureRow> = sqlx::query_as(
+        "SELECT workflow_id, signer, signature_hash, tx_hash, signed_at
+         FROM signatures ORDER BY signed_at",
+    )
+    .fetch_all(pool)
+    .await?;
+    let transitions: Vec<TransitionRow> = sqlx::query_as(
+        "SELECT workflow_id, from_state, to_state, error, step, attested_tx_hash, tx_hash,
+             transitioned_at
+         FROM workflow_transitions ORDER BY workflow_id, position",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut signatures_of: HashMap<Uuid, Vec<SignatureRecord>> = HashMap::new();
+    for row in signatures {
+        signatures_of.entry(row.workflow_id).or_default().push(SignatureRecord {
+            signer: row.signer.parse()?,
+            signature_hash: row.signature_hash.parse()?,
+            tx_hash: row.tx_hash.parse()?,
+            signed_at: row.signed_at,
+        });
+    }
+    let mut transitions_of: HashMap<Uuid, Vec<TransitionRecord>> = HashMap::new();
+    for row in transitions {
+        transitions_of.entry(row.workflow_id).or_default().push(row.try_into()?);
+    }
+
+    rows.into_iter()
+        .map(|row| {
+            Ok(WorkflowRecord {
+                id: row.id,
+                chain_id: row.chain_id.as_deref().map(str::parse).transpose()?,
+                project: row.project,
+                repo: row.repo,
+                branch: row.branch,
+                tag: row.tag,
+                rev: row.rev,
+                profile: row.profile,
+                organization: row.organization,
+                client: row.client,
+                snapshot_id: row.snapshot_id,
+                signatures: signatures_of.remove(&row.id).unwrap_or_default(),
+                wallet_address: row.wallet_address.as_deref().map(str::parse).transpose()?,
+                state: from_text(&row.state)?,
+                transitions: transitions_of.remove(&row.id).unwrap_or_default(),
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+            })
+        })
+        .collect()
+}
+
+/// Write a workflow, with the signatures it collected and its transitions so far.
+pub async fn upsert(pool: &PgPool, record: &WorkflowRecord) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        "INSERT INTO workflows (id, chain_id, project, repo, branch, tag, rev, profile,
+             organization, client, snapshot_id, wallet_address, state, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+         ON CONFLICT (id) DO UPDATE SET
+             chain_id = EXCLUDED.chain_id,
+             project = EXCLUDED.project,
+             organization = EXCLUDED.organization,
+             snapshot_id = EXCLUDED.snapshot_id,
+             wallet_address = EXCLUDED.wallet_address,
+             state = EXCLUDED.state,
+             updated_at = EXCLUDED.updated_at",
+    )
+    .bind(record.id)
+    .bind(&record.chain_id)
+    .bind(&record.project)
+    .bind(&record.repo)
+    .bind(&record.branch)
+    .bind(&record.tag)
+    .bind(&record.rev)
+    .bind(&record.profile)
+    .bind(&record.organization)
+    .bind(&record.client)
+    .bind(record.snapshot_id)
+    .bind(&record.wallet_address)
+    .bind(to_text(&record.state))
+    .bind(record.created_at)
+    .bind(record.updated_at)
+    .execute(&mut *tx)
+    .await?;
+
+    // Signatures are only ever added.
+    for signature in &record.signatures {
+        sqlx::query(
+            "INSERT INTO signatures (workflow_id, signer, signature_hash, tx_hash, signed_at)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(record.id)
+        .bind(&*signature.signer)
+        .bind(&*signature.signature_hash)
+        .bind(&*signature.tx_hash)
+        .bind(signature.signed_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    // Transitions are only ever added, their transaction set once recorded on chain.
+    for (position, transition) in record.transitions.iter().enumerate() {
+        sqlx::query(
+            "INSERT INTO workflow_transitions (workflow_id, position, from_state, to_state,
+                 error, step, attested_tx_hash, tx_hash, transitioned_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+             ON CONFLICT (workflow_id, position) DO UPDATE SET tx_hash = EXCLUDED.tx_hash",
+        )
+        .bind(record.id)
+        .bind(position as i32)
+        .bind(to_text(&transition.from))
+        .bind(to_text(&transition.to))
+        .bind(&transition.error)
+        .bind(transition.step.as_ref().map(to_text))
+        .bind(&*transition.attested_tx_hash)
+        .bind(&transition.tx_hash)
+        .bind(transition.at)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Delete a workflow, with its signatures and transitions.
+pub async fn delete(pool: &PgPool, id: Uuid) -> anyhow::Result<()> {
+    sqlx::query("DELETE FROM workflows WHERE id = $1").bind(id).execute(pool).await?;
+    Ok(())
+}
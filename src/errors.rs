@@ -14,6 +14,13 @@
 
 use axum::{http::StatusCode, response::IntoResponse, Json};
 use serde_json::json;
+
+use crate::{
+    contracts::error::ContractError,
+    db::WriteError,
+    responses::{treasury::TreasuryResponse, validation::FieldErrorResponse},
+    services::{breaker::ChainUnavailable, storage::StorageError},
+};
 use thiserror::Error;
 use tracing::error;
 
@@ -42,8 +49,152 @@ pub enum ApiError {
     #[error("Bad Workflow Request: {0}")]
     BadWorkflowRequest(String),
 
+    #[error("Invalid workflow transition: {0}")]
+    InvalidWorkflowTransition(String),
+
     #[error("Failed to download repository: {0}")]
     FailedToDownloadRepo(String),
+
+    #[error("Repository downloads busy: {0}")]
+    DownloadsBusy(String),
+
+    #[error("Invalid list parameters: {0}")]
+    InvalidListParams(String),
+
+    #[error("Bad Batch Request: {0}")]
+    BadBatchRequest(String),
+
+    #[error("Too Many Requests")]
+    TooManyRequests,
+
+    #[error("Quota exceeded: {0}")]
+    QuotaExceeded(String),
+
+    #[error("Plan limit exceeded: {0}")]
+    PlanLimitExceeded(String),
+
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Not Found Allocation: {0}")]
+    NotFoundAllocation(String),
+
+    #[error("Bad Allocation Request: {0}")]
+    BadAllocationRequest(String),
+
+    #[error("Bad Claim Request: {0}")]
+    BadClaimRequest(String),
+
+    #[error("Terms not accepted: accept version {0} of the terms first")]
+    TermsNotAccepted(String),
+
+    #[error("Not Found Terms: {0}")]
+    NotFoundTerms(String),
+
+    #[error("Bad Terms Request: {0}")]
+    BadTermsRequest(String),
+
+    #[error("Paymaster unavailable: {0}")]
+    PaymasterUnavailable(String),
+
+    #[error("Bad Address Request: {0}")]
+    BadAddressRequest(String),
+
+    #[error("Bad Identity Request: {0}")]
+    BadIdentityRequest(String),
+
+    #[error("Insufficient treasury balance: {}", shortfalls(.0))]
+    InsufficientTreasury(Box<TreasuryResponse>),
+
+    #[error("Treasury unavailable: {0}")]
+    TreasuryUnavailable(String),
+
+    #[error("Not Found Snapshot: {0}")]
+    NotFoundSnapshot(String),
+
+    #[error("Not Found Artifact: {0}")]
+    NotFoundArtifact(String),
+
+    #[error("Not Found Profile: {0}")]
+    NotFoundProfile(String),
+
+    #[error("Bad Profile Request: {0}")]
+    BadProfileRequest(String),
+
+    #[error("Bad Policy Request: {0}")]
+    BadPolicyRequest(String),
+
+    #[error("Bad Notification Request: {0}")]
+    BadNotificationRequest(String),
+
+    #[error("GitHub App unavailable: {0}")]
+    GitHubAppUnavailable(String),
+
+    #[error("Not Found Installation: {0}")]
+    NotFoundInstallation(String),
+
+    #[error("Not Found Organization: {0}")]
+    NotFoundOrganization(String),
+
+    #[error("Bad Organization Request: {0}")]
+    BadOrganizationRequest(String),
+
+    #[error("Invalid request: {}", field_errors(.0))]
+    InvalidRequest(Vec<FieldErrorResponse>),
+
+    #[error("Invalid dependency metadata: {}", field_errors(.0))]
+    InvalidMetadata(Vec<FieldErrorResponse>),
+
+    #[error("Not Found Receipt: {0}")]
+    NotFoundReceipt(String),
+
+    #[error("Metadata unavailable: {0}")]
+    MetadataUnavailable(String),
+
+    #[error("Bad Step Request: {0}")]
+    BadStepRequest(String),
+
+    #[error("Credentials unavailable: {0}")]
+    CredentialsUnavailable(String),
+
+    #[error("Bad Credential Request: {0}")]
+    BadCredentialRequest(String),
+
+    #[error("Invalid webhook signature")]
+    InvalidWebhookSignature,
+
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+
+    #[error("Not Found Inquire: {0}")]
+    NotFoundInquire(String),
+
+    #[error("Bad Webhook Request: {0}")]
+    BadWebhookRequest(String),
+
+    #[error("Not Found Webhook: {0}")]
+    NotFoundWebhook(String),
+
+    #[error("Chain unavailable: {0}")]
+    ChainUnavailable(String),
+
+    #[error("Invalid contract input: {0}")]
+    InvalidContractInput(String),
+
+    #[error("Transaction reverted: {0}")]
+    TransactionReverted(String),
+
+    #[error("Chain RPC error: {0}")]
+    ChainRpcError(String),
+
+    #[error("Bad Graph Request: {0}")]
+    BadGraphRequest(String),
+
+    #[error("Database error: {0}")]
+    DatabaseError(String),
 }
 
 impl IntoResponse for ApiError {
@@ -56,11 +207,134 @@ impl IntoResponse for ApiError {
             Self::FailedToDeleteWorkflow(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::NotFoundRepo(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::BadWorkflowRequest(_) => StatusCode::BAD_REQUEST,
+            Self::InvalidWorkflowTransition(_) => StatusCode::CONFLICT,
             Self::FailedToDownloadRepo(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::DownloadsBusy(_) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::InvalidListParams(_) => StatusCode::BAD_REQUEST,
+            Self::BadBatchRequest(_) => StatusCode::BAD_REQUEST,
+            Self::TooManyRequests => StatusCode::TOO_MANY_REQUESTS,
+            Self::QuotaExceeded(_) => StatusCode::TOO_MANY_REQUESTS,
+            Self::PlanLimitExceeded(_) => StatusCode::FORBIDDEN,
+            Self::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            Self::Forbidden(_) => StatusCode::FORBIDDEN,
+            Self::NotFoundAllocation(_) => StatusCode::NOT_FOUND,
+            Self::BadAllocationRequest(_) => StatusCode::BAD_REQUEST,
+            Self::BadClaimRequest(_) => StatusCode::BAD_REQUEST,
+            Self::TermsNotAccepted(_) => StatusCode::FORBIDDEN,
+            Self::NotFoundTerms(_) => StatusCode::NOT_FOUND,
+            Self::BadTermsRequest(_) => StatusCode::BAD_REQUEST,
+            Self::PaymasterUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::BadAddressRequest(_) => StatusCode::BAD_REQUEST,
+            Self::BadIdentityRequest(_) => StatusCode::BAD_REQUEST,
+            Self::InsufficientTreasury(_) => StatusCode::PAYMENT_REQUIRED,
+            Self::TreasuryUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::NotFoundSnapshot(_) => StatusCode::NOT_FOUND,
+            Self::NotFoundArtifact(_) => StatusCode::NOT_FOUND,
+            Self::NotFoundProfile(_) => StatusCode::NOT_FOUND,
+            Self::BadProfileRequest(_) => StatusCode::BAD_REQUEST,
+            Self::BadPolicyRequest(_) => StatusCode::BAD_REQUEST,
+            Self::BadNotificationRequest(_) => StatusCode::BAD_REQUEST,
+            Self::GitHubAppUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::NotFoundInstallation(_) => StatusCode::NOT_FOUND,
+            Self::NotFoundOrganization(_) => StatusCode::NOT_FOUND,
+            Self::BadOrganizationRequest(_) => StatusCode::BAD_REQUEST,
+            Self::InvalidRequest(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::InvalidMetadata(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::NotFoundReceipt(_) => StatusCode::NOT_FOUND,
+            Self::MetadataUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::BadStepRequest(_) => StatusCode::BAD_REQUEST,
+            Self::CredentialsUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::BadCredentialRequest(_) => StatusCode::BAD_REQUEST,
+            Self::InvalidWebhookSignature => StatusCode::UNAUTHORIZED,
+            Self::InvalidSignature(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::NotFoundInquire(_) => StatusCode::NOT_FOUND,
+            Self::BadWebhookRequest(_) => StatusCode::BAD_REQUEST,
+            Self::NotFoundWebhook(_) => StatusCode::NOT_FOUND,
+            Self::ChainUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::InvalidContractInput(_) => StatusCode::BAD_REQUEST,
+            Self::TransactionReverted(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::ChainRpcError(_) => StatusCode::BAD_GATEWAY,
+            Self::BadGraphRequest(_) => StatusCode::BAD_REQUEST,
+            Self::DatabaseError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         };
         let message = self.to_string();
 
         error!("{} - {}", status, message);
-        (status, Json(json!({ "message": message }))).into_response()
+        let body = match self {
+            // Tell the client how much to send where.
+            Self::InsufficientTreasury(funding) => {
+                json!({ "message": message, "funding": funding })
+            }
+            // Tell the client every invalid field at once.
+            Self::InvalidRequest(errors) | Self::InvalidMetadata(errors) => {
+                json!({ "message": message, "errors": errors })
+            }
+            _ => json!({ "message": message }),
+        };
+        (status, Json(body)).into_response()
+    }
+}
+
+impl ApiError {
+    /// Map a failed chain call, failing with `ChainUnavailable` when it was refused by the
+    /// circuit breaker of the chain provider, or according to the `ContractError` it failed with.
+    pub fn chain(e: anyhow::Error, otherwise: impl FnOnce(String) -> Self) -> Self {
+        if let Some(e) = e.downcast_ref::<ChainUnavailable>() {
+            return Self::ChainUnavailable(e.to_string());
+        }
+        match e.downcast::<ContractError>() {
+            Ok(e) => e.into(),
+            Err(e) => otherwise(format!("{e:#}")),
+        }
     }
 }
+
+impl From<ChainUnavailable> for ApiError {
+    fn from(e: ChainUnavailable) -> Self {
+        Self::ChainUnavailable(e.to_string())
+    }
+}
+
+impl From<ContractError> for ApiError {
+    fn from(e: ContractError) -> Self {
+        match e {
+            ContractError::RpcError(_) => Self::ChainRpcError(e.to_string()),
+            ContractError::Reverted(_) => Self::TransactionReverted(e.to_string()),
+            ContractError::DryRun(_) => Self::ChainUnavailable(e.to_string()),
+            ContractError::Ambiguous { .. } => Self::ChainRpcError(e.to_string()),
+            e => Self::InvalidContractInput(e.to_string()),
+        }
+    }
+}
+
+impl From<WriteError> for ApiError {
+    fn from(e: WriteError) -> Self {
+        Self::DatabaseError(e.to_string())
+    }
+}
+
+impl From<StorageError> for ApiError {
+    fn from(e: StorageError) -> Self {
+        match e {
+            StorageError::RepoTooLarge { .. } => Self::PlanLimitExceeded(e.to_string()),
+            StorageError::DownloadQueueTimeout(..) => Self::DownloadsBusy(e.to_string()),
+            e => Self::FailedToDownloadRepo(e.to_string()),
+        }
+    }
+}
+
+fn field_errors(errors: &[FieldErrorResponse]) -> String {
+    let errors: Vec<_> =
+        errors.iter().map(|error| format!("`{}` {}", error.field, error.message)).collect();
+    errors.join(", ")
+}
+
+fn shortfalls(treasury: &TreasuryResponse) -> String {
+    let shortfalls: Vec<_> = treasury
+        .requirements
+        .iter()
+        .filter(|requirement| requirement.shortfall != "0")
+        .map(|requirement| format!("{} {} missing", requirement.shortfall, requirement.token))
+        .collect();
+    format!("{}, fund {} on {}", shortfalls.join(", "), treasury.address, treasury.chain)
+}
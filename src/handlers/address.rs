@@ -0,0 +1,113 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! The Address book Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use tracing::instrument;
+
+use crate::{
+    context::Context,
+    errors::Result,
+    middlewares::ratelimit::ClientKey,
+    requests::{
+        address::{AddressBookRequest, Chain},
+        validation::ValidatedJson,
+    },
+    responses::address::AddressBookResponse,
+    services::address::AddressService,
+};
+
+/// Get the payout addresses of a contributor.
+#[utoipa::path(
+    operation_id = "get-contributor-addresses",
+    get, path = "/v1/contributors/{username}/addresses",
+    params(
+        ("username" = String, description = "The GitHub username of the contributor"),
+    ),
+    responses(
+        (status = 200, description = "Addresses retrieved successfully", body = AddressBookResponse)
+    ),
+    tag = "Address"
+)]
+#[instrument(skip_all, fields(username = %username))]
+pub async fn get(
+    State(ctx): State<Arc<Context>>,
+    Path(username): Path<String>,
+) -> Result<impl IntoResponse> {
+    Ok((StatusCode::OK, Json(AddressService::get(ctx, &username).await?)))
+}
+
+/// Replace the payout addresses of a contributor.
+#[utoipa::path(
+    operation_id = "put-contributor-addresses",
+    put, path = "/v1/contributors/{username}/addresses",
+    params(
+        ("username" = String, description = "The GitHub username of the contributor"),
+    ),
+    request_body(
+        content = inline(AddressBookRequest),
+        description = "Address book request",
+        content_type = "application/json"
+    ),
+    responses(
+        (status = 200, description = "Addresses saved successfully", body = AddressBookResponse),
+        (status = 400, description = "Invalid address"),
+        (status = 403, description = "Not an admin key"),
+        (status = 422, description = "Invalid request body, with every invalid field in `errors`")
+    ),
+    security(("api_key" = [])),
+    tag = "Address"
+)]
+#[instrument(skip_all, fields(username = %username))]
+pub async fn put(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(username): Path<String>,
+    ValidatedJson(req): ValidatedJson<AddressBookRequest>,
+) -> Result<impl IntoResponse> {
+    Ok((StatusCode::OK, Json(AddressService::put(ctx, &key, &username, req).await?)))
+}
+
+/// Remove the payout address of a contributor on a chain.
+#[utoipa::path(
+    operation_id = "delete-contributor-address",
+    delete, path = "/v1/contributors/{username}/addresses/{chain}",
+    params(
+        ("username" = String, description = "The GitHub username of the contributor"),
+        ("chain" = Chain, description = "The chain of the address"),
+    ),
+    responses(
+        (status = 204, description = "Address removed successfully"),
+        (status = 403, description = "Not an admin key"),
+        (status = 404, description = "No address on the chain")
+    ),
+    security(("api_key" = [])),
+    tag = "Address"
+)]
+#[instrument(skip_all, fields(username = %username, %chain))]
+pub async fn delete(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path((username, chain)): Path<(String, Chain)>,
+) -> Result<impl IntoResponse> {
+    AddressService::remove(ctx, &key, &username, chain).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
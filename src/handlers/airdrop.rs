@@ -20,11 +20,15 @@ use axum::{
     extract::{Path, State},
     http::StatusCode,
     response::IntoResponse,
-    Json,
 };
+use tracing::instrument;
 use uuid::Uuid;
 
-use crate::{context::Context, errors::Result, requests::wallet::WalletAddressRequest};
+use crate::{
+    context::Context,
+    errors::Result,
+    requests::{validation::ValidatedJson, wallet::WalletAddressRequest},
+};
 
 /// Get airdrop detail.
 #[utoipa::path(
@@ -40,9 +44,10 @@ use crate::{context::Context, errors::Result, requests::wallet::WalletAddressReq
     ),
     tag = "Airdrop"
 )]
+#[instrument(skip_all, fields(airdrop_id = %id))]
 pub async fn get(
     State(_ctx): State<Arc<Context>>,
-    Path(_id): Path<Uuid>,
+    Path(id): Path<Uuid>,
 ) -> Result<impl IntoResponse> {
     Ok(StatusCode::OK)
 }
@@ -62,14 +67,16 @@ pub async fn get(
     responses(
         (status = 204, description = "Wallet address submitted successfully"),
         (status = 404, description = "Airdrop not found"),
+        (status = 422, description = "Invalid request body, with every invalid field in `errors`"),
         (status = 500, description = "Failed to get airdrop")
     ),
     tag = "Airdrop"
 )]
+#[instrument(skip_all, fields(airdrop_id = %id))]
 pub async fn submit(
     State(_ctx): State<Arc<Context>>,
-    Path(_id): Path<Uuid>,
-    Json(_req): Json<WalletAddressRequest>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(_req): ValidatedJson<WalletAddressRequest>,
 ) -> Result<impl IntoResponse> {
     Ok(StatusCode::NO_CONTENT)
 }
@@ -17,12 +17,26 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    http::StatusCode,
     response::IntoResponse,
+    Extension, Json,
 };
+use serde_json::Value;
+use tracing::instrument;
 use uuid::Uuid;
 
-use crate::{context::Context, errors::Result};
+use crate::{
+    context::Context,
+    errors::Result,
+    middlewares::{ratelimit::ClientKey, trace::RequestId},
+    requests::{
+        allocation::CreateAllocationsRequest, list::ListParams, organization::Role,
+        validation::ValidatedJson,
+    },
+    responses::{allocation::CreateAllocationsResponse, list::ListResponse},
+    services::{allocation::AllocationService, workflow::WorkflowService},
+};
 
 /// Get allocations list of the workflow
 #[utoipa::path(
@@ -30,6 +44,7 @@ use crate::{context::Context, errors::Result};
     get, path = "/v1/workflows/{id}/allocations",
     params(
         ("id" = Uuid, description = "The id of workflow"),
+        ListParams,
     ),
     responses(
         (status = 200, description = "Allocations retrieved successfully"),
@@ -38,11 +53,19 @@ use crate::{context::Context, errors::Result};
     ),
     tag = "Allocation"
 )]
+#[instrument(skip_all, fields(workflow_id = %id))]
 pub async fn list(
-    State(_ctx): State<Arc<Context>>,
-    Path(_id): Path<Uuid>,
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Extension(request_id): Extension<RequestId>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<ListParams>,
 ) -> Result<impl IntoResponse> {
-    Ok(Vec::new())
+    WorkflowService::authorize(&ctx, &key, id, Role::Member)?;
+    params.validate()?;
+
+    let (items, pagination) = params.apply(Vec::<Value>::new())?;
+    Ok(Json(ListResponse::new(items, pagination, &request_id)))
 }
 
 /// Get the allocation detail of the workflow
@@ -60,9 +83,38 @@ pub async fn list(
     ),
     tag = "Allocation"
 )]
+#[instrument(skip_all, fields(workflow_id = %id))]
 pub async fn get(
-    State(_ctx): State<Arc<Context>>,
-    Path((_id, _allocation_id)): Path<(Uuid, Uuid)>,
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path((id, _allocation_id)): Path<(Uuid, Uuid)>,
 ) -> Result<impl IntoResponse> {
+    WorkflowService::authorize(&ctx, &key, id, Role::Member)?;
     Ok(Vec::new())
 }
+
+/// Create allocations in bulk, for payout rounds and airdrops
+#[utoipa::path(
+    operation_id = "create-allocations-batch",
+    post, path = "/v1/allocations/batch",
+    request_body(
+        content = inline(CreateAllocationsRequest),
+        description = "Create allocations request",
+        content_type = "application/json"
+    ),
+    responses(
+        (status = 201, description = "Allocations created successfully", body = CreateAllocationsResponse),
+        (status = 403, description = "A signature not by an approver of the organization owning the workflow"),
+        (status = 422, description = "Invalid request body, with every invalid field in `errors`"),
+        (status = 502, description = "Failed to create the allocations")
+    ),
+    tag = "Allocation"
+)]
+#[instrument(skip_all)]
+pub async fn batch(
+    State(ctx): State<Arc<Context>>,
+    ValidatedJson(req): ValidatedJson<CreateAllocationsRequest>,
+) -> Result<impl IntoResponse> {
+    let allocations = AllocationService::create_batch(&ctx, req).await?;
+    Ok((StatusCode::CREATED, Json(allocations)))
+}
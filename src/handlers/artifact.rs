@@ -0,0 +1,143 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The Artifact Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use serde::Deserialize;
+use tokio_util::io::ReaderStream;
+use tracing::instrument;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    errors::Result,
+    middlewares::ratelimit::ClientKey,
+    requests::organization::Role,
+    responses::artifact::{ArtifactName, ArtifactResponse, ArtifactUrlResponse},
+    services::{
+        artifact::{ArtifactFile, ArtifactService},
+        workflow::WorkflowService,
+    },
+};
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SignatureParams {
+    /// When the URL expires, in seconds since the epoch.
+    pub expires: i64,
+    /// The signature of the URL.
+    pub signature: String,
+}
+
+/// Get the analysis artifacts of the workflow
+#[utoipa::path(
+    operation_id = "get-artifacts-list",
+    get, path = "/v1/workflows/{id}/artifacts",
+    params(
+        ("id" = Uuid, description = "The id of workflow"),
+    ),
+    responses(
+        (status = 200, description = "Artifacts retrieved successfully", body = Vec<ArtifactResponse>)
+    ),
+    tag = "Artifact"
+)]
+#[instrument(skip_all, fields(workflow_id = %id))]
+pub async fn list(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    WorkflowService::authorize(&ctx, &key, id, Role::Member)?;
+    Ok(Json(ArtifactService::list(&ctx, id).await?))
+}
+
+/// Download an analysis artifact of the workflow, through a short-lived signed URL when
+/// URL signing is configured, directly otherwise
+#[utoipa::path(
+    operation_id = "get-artifact",
+    get, path = "/v1/workflows/{id}/artifacts/{name}",
+    params(
+        ("id" = Uuid, description = "The id of workflow"),
+        ("name" = ArtifactName, description = "The name of artifact"),
+    ),
+    responses(
+        (status = 200, description = "Signed download URL issued successfully, or the artifact content",
+            body = ArtifactUrlResponse),
+        (status = 404, description = "Artifact not found")
+    ),
+    tag = "Artifact"
+)]
+#[instrument(skip_all, fields(workflow_id = %id, %name))]
+pub async fn get(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path((id, name)): Path<(Uuid, ArtifactName)>,
+) -> Result<Response> {
+    WorkflowService::authorize(&ctx, &key, id, Role::Member)?;
+    if ctx.config.artifact_config.url_secret().is_some() {
+        return Ok(Json(ArtifactService::url(&ctx, id, name).await?).into_response());
+    }
+    Ok(stream(ArtifactService::open(&ctx, id, name).await?))
+}
+
+/// Download an analysis artifact with a signed URL
+#[utoipa::path(
+    operation_id = "download-artifact",
+    get, path = "/v1/artifacts/{id}/{name}",
+    params(
+        ("id" = Uuid, description = "The id of workflow"),
+        ("name" = ArtifactName, description = "The name of artifact"),
+        SignatureParams,
+    ),
+    responses(
+        (status = 200, description = "The artifact content"),
+        (status = 403, description = "Expired or invalid URL signature"),
+        (status = 404, description = "Artifact not found")
+    ),
+    tag = "Artifact"
+)]
+#[instrument(skip_all, fields(workflow_id = %id, %name))]
+pub async fn download(
+    State(ctx): State<Arc<Context>>,
+    Path((id, name)): Path<(Uuid, ArtifactName)>,
+    Query(params): Query<SignatureParams>,
+) -> Result<Response> {
+    let artifact =
+        ArtifactService::open_signed(&ctx, id, name, params.expires, &params.signature).await?;
+    Ok(stream(artifact))
+}
+
+/// Stream the content of an artifact, without buffering it.
+fn stream(artifact: ArtifactFile) -> Response {
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, artifact.name.content_type().to_string()),
+            (header::CONTENT_LENGTH, artifact.size.to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", artifact.name)),
+        ],
+        Body::from_stream(ReaderStream::new(artifact.file)),
+    )
+        .into_response()
+}
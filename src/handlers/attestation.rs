@@ -0,0 +1,177 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The Attestation Service Handlers.
+
+use std::{net::SocketAddr, sync::Arc};
+
+use axum::{
+    extract::{ConnectInfo, Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use serde::Deserialize;
+use tracing::instrument;
+use utoipa::IntoParams;
+
+use crate::{
+    context::Context,
+    errors::Result,
+    middlewares::ratelimit::ClientKey,
+    requests::{
+        attestation::{AttestationRequest, TermsRequest},
+        validation::ValidatedJson,
+    },
+    responses::attestation::{AttestationResponse, TermsResponse},
+    services::attestation::AttestationService,
+};
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AttestationParams {
+    /// The export format, `json` (default) or `csv`.
+    pub format: Option<String>,
+}
+
+/// Get the terms recipients accept before claiming
+#[utoipa::path(
+    operation_id = "get-current-terms",
+    get, path = "/v1/terms",
+    responses(
+        (status = 200, description = "Terms retrieved successfully", body = TermsResponse),
+        (status = 404, description = "No terms published")
+    ),
+    tag = "Attestation"
+)]
+pub async fn current(State(ctx): State<Arc<Context>>) -> Result<impl IntoResponse> {
+    Ok(Json(AttestationService::current(ctx).await?))
+}
+
+/// Get a version of the terms
+#[utoipa::path(
+    operation_id = "get-terms",
+    get, path = "/v1/terms/{version}",
+    params(
+        ("version" = String, description = "The version of the terms"),
+    ),
+    responses(
+        (status = 200, description = "Terms retrieved successfully", body = TermsResponse),
+        (status = 404, description = "Terms not found")
+    ),
+    tag = "Attestation"
+)]
+#[instrument(skip_all, fields(%version))]
+pub async fn get(
+    State(ctx): State<Arc<Context>>,
+    Path(version): Path<String>,
+) -> Result<impl IntoResponse> {
+    Ok(Json(AttestationService::get(ctx, &version).await?))
+}
+
+/// Publish a new version of the terms, which recipients accept before claiming
+#[utoipa::path(
+    operation_id = "publish-terms",
+    put, path = "/v1/admin/terms/{version}",
+    params(
+        ("version" = String, description = "The version of the terms"),
+    ),
+    request_body(
+        content = inline(TermsRequest),
+        description = "Terms request",
+        content_type = "application/json"
+    ),
+    responses(
+        (status = 201, description = "Terms published successfully", body = TermsResponse),
+        (status = 400, description = "Invalid or already published terms"),
+        (status = 403, description = "Not an admin key"),
+        (status = 422, description = "Invalid request body, with every invalid field in `errors`"),
+        (status = 500, description = "Failed to record the terms")
+    ),
+    security(("api_key" = [])),
+    tag = "Attestation"
+)]
+#[instrument(skip_all, fields(%version))]
+pub async fn publish(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(version): Path<String>,
+    ValidatedJson(req): ValidatedJson<TermsRequest>,
+) -> Result<impl IntoResponse> {
+    Ok((StatusCode::CREATED, Json(AttestationService::publish(ctx, &key, &version, req).await?)))
+}
+
+/// Accept a version of the terms as a recipient
+#[utoipa::path(
+    operation_id = "create-attestation",
+    post, path = "/v1/attestations",
+    request_body(
+        content = inline(AttestationRequest),
+        description = "Attestation request",
+        content_type = "application/json"
+    ),
+    responses(
+        (status = 201, description = "Terms accepted successfully", body = AttestationResponse),
+        (status = 400, description = "Invalid attestation"),
+        (status = 404, description = "Terms not found"),
+        (status = 422, description = "Invalid request body, with every invalid field in `errors`"),
+        (status = 500, description = "Failed to record the attestation")
+    ),
+    tag = "Attestation"
+)]
+#[instrument(skip_all, fields(version = %req.version))]
+pub async fn accept(
+    State(ctx): State<Arc<Context>>,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    ValidatedJson(req): ValidatedJson<AttestationRequest>,
+) -> Result<impl IntoResponse> {
+    let ip = connect_info.map(|Extension(ConnectInfo(addr))| addr.ip());
+    Ok((StatusCode::CREATED, Json(AttestationService::accept(ctx, ip, req).await?)))
+}
+
+/// Export every attestation, for compliance
+#[utoipa::path(
+    operation_id = "list-attestations",
+    get, path = "/v1/admin/attestations",
+    params(AttestationParams),
+    responses(
+        (status = 200, description = "Attestations retrieved successfully",
+            content((Vec<AttestationResponse> = "application/json"), (String = "text/csv"))),
+        (status = 403, description = "Not an admin key")
+    ),
+    security(("api_key" = [])),
+    tag = "Attestation"
+)]
+pub async fn list(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Query(params): Query<AttestationParams>,
+) -> Result<Response> {
+    let attestations = AttestationService::list(ctx, &key).await?;
+
+    match params.format.as_deref() {
+        Some("csv") => Ok((
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    "attachment; filename=\"attestations.csv\"".to_string(),
+                ),
+            ],
+            AttestationService::to_csv(&attestations),
+        )
+            .into_response()),
+        _ => Ok(Json(attestations).into_response()),
+    }
+}
@@ -0,0 +1,59 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The Batch Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    http::{Extensions, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use tracing::instrument;
+
+use crate::{
+    context::Context,
+    errors::Result,
+    requests::{batch::BatchRequest, validation::ValidatedJson},
+    responses::batch::BatchResponse,
+    services::batch::BatchService,
+};
+
+/// Read several resources in one round trip.
+#[utoipa::path(
+    operation_id = "batch-read",
+    post, path = "/v1/batch",
+    request_body(
+        content = inline(BatchRequest),
+        description = "The GET paths to read",
+        content_type = "application/json"
+    ),
+    responses(
+        (status = 200, description = "Batch executed successfully", body = BatchResponse),
+        (status = 400, description = "Invalid batch request, or responses too large"),
+        (status = 422, description = "Invalid request body, with every invalid field in `errors`"),
+        (status = 429, description = "Not enough requests left in the rate limit window for every path")
+    ),
+    tag = "Batch"
+)]
+#[instrument(skip_all, fields(size = req.paths.len()))]
+pub async fn read(
+    State(ctx): State<Arc<Context>>,
+    extensions: Extensions,
+    ValidatedJson(req): ValidatedJson<BatchRequest>,
+) -> Result<impl IntoResponse> {
+    Ok((StatusCode::OK, Json(BatchService::read(ctx, &extensions, &req).await?)))
+}
@@ -0,0 +1,101 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The Claim Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    contracts::types::Id,
+    errors::Result,
+    requests::{
+        claim::{BuildClaimRequest, ExecuteClaimRequest},
+        validation::ValidatedJson,
+    },
+    responses::claim::{ClaimExecutedResponse, ClaimResponse},
+    services::claim::ClaimService,
+};
+
+/// Build a sponsored transaction claiming the allocation, for recipients without gas tokens.
+#[utoipa::path(
+    operation_id = "build-allocation-claim",
+    post, path = "/v1/workflows/{id}/allocations/{allocation_id}/claim",
+    params(
+        ("id" = Uuid, description = "The id of workflow"),
+        ("allocation_id" = String, description = "The id of allocation"),
+    ),
+    request_body(
+        content = inline(BuildClaimRequest),
+        description = "Build claim request",
+        content_type = "application/json"
+    ),
+    responses(
+        (status = 200, description = "Claim built successfully", body = ClaimResponse),
+        (status = 400, description = "Allocation not claimable"),
+        (status = 403, description = "The recipient did not accept the current terms"),
+        (status = 404, description = "Allocation not found"),
+        (status = 422, description = "Invalid request body, with every invalid field in `errors`"),
+        (status = 503, description = "Paymaster unavailable")
+    ),
+    tag = "Claim"
+)]
+#[instrument(skip_all, fields(workflow_id = %id, %allocation_id))]
+pub async fn build(
+    State(ctx): State<Arc<Context>>,
+    Path((id, allocation_id)): Path<(Uuid, Id)>,
+    ValidatedJson(req): ValidatedJson<BuildClaimRequest>,
+) -> Result<impl IntoResponse> {
+    Ok((StatusCode::OK, Json(ClaimService::build(ctx, id, &allocation_id, &req).await?)))
+}
+
+/// Submit the signed sponsored claim transaction.
+#[utoipa::path(
+    operation_id = "execute-allocation-claim",
+    post, path = "/v1/workflows/{id}/allocations/{allocation_id}/claim/execute",
+    params(
+        ("id" = Uuid, description = "The id of workflow"),
+        ("allocation_id" = String, description = "The id of allocation"),
+    ),
+    request_body(
+        content = inline(ExecuteClaimRequest),
+        description = "Execute claim request",
+        content_type = "application/json"
+    ),
+    responses(
+        (status = 202, description = "Claim submitted successfully", body = ClaimExecutedResponse),
+        (status = 400, description = "Allocation not claimable"),
+        (status = 404, description = "Allocation not found"),
+        (status = 422, description = "Invalid request body, with every invalid field in `errors`"),
+        (status = 503, description = "Paymaster unavailable")
+    ),
+    tag = "Claim"
+)]
+#[instrument(skip_all, fields(workflow_id = %id, %allocation_id))]
+pub async fn execute(
+    State(ctx): State<Arc<Context>>,
+    Path((id, allocation_id)): Path<(Uuid, Id)>,
+    ValidatedJson(req): ValidatedJson<ExecuteClaimRequest>,
+) -> Result<impl IntoResponse> {
+    Ok((StatusCode::ACCEPTED, Json(ClaimService::execute(ctx, id, &allocation_id, &req).await?)))
+}
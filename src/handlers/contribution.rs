@@ -17,12 +17,24 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     response::IntoResponse,
+    Extension, Json,
 };
+use tracing::instrument;
 use uuid::Uuid;
 
-use crate::{context::Context, errors::Result};
+use crate::{
+    context::Context,
+    errors::Result,
+    middlewares::{ratelimit::ClientKey, trace::RequestId},
+    requests::{list::ListParams, organization::Role},
+    responses::{
+        contribution::{ContributionResponse, RecomputeResponse},
+        list::ListResponse,
+    },
+    services::{contribution::ContributionService, workflow::WorkflowService},
+};
 
 /// Get contributions list of the workflow
 #[utoipa::path(
@@ -30,19 +42,29 @@ use crate::{context::Context, errors::Result};
     get, path = "/v1/workflows/{id}/contributions",
     params(
         ("id" = Uuid, description = "The id of workflow"),
+        ListParams,
     ),
     responses(
-        (status = 200, description = "Contributions retrieved successfully"),
+        (status = 200, description = "Contributions retrieved successfully",
+            body = ListResponse<ContributionResponse>),
         (status = 404, description = "Workflow not found"),
         (status = 500, description = "Failed to get workflow")
     ),
     tag = "Contribution"
 )]
+#[instrument(skip_all, fields(workflow_id = %id))]
 pub async fn list(
-    State(_ctx): State<Arc<Context>>,
-    Path(_id): Path<Uuid>,
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Extension(request_id): Extension<RequestId>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<ListParams>,
 ) -> Result<impl IntoResponse> {
-    Ok(Vec::new())
+    WorkflowService::authorize(&ctx, &key, id, Role::Member)?;
+    params.validate()?;
+
+    let (items, pagination) = params.apply(ContributionService::list(ctx, id).await?)?;
+    Ok(Json(ListResponse::new(items, pagination, &request_id)))
 }
 
 /// Get the contribution detail of the workflow
@@ -60,9 +82,37 @@ pub async fn list(
     ),
     tag = "Contribution"
 )]
+#[instrument(skip_all, fields(workflow_id = %id))]
 pub async fn get(
-    State(_ctx): State<Arc<Context>>,
-    Path((_id, _contribution_id)): Path<(Uuid, Uuid)>,
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path((id, _contribution_id)): Path<(Uuid, Uuid)>,
 ) -> Result<impl IntoResponse> {
+    WorkflowService::authorize(&ctx, &key, id, Role::Member)?;
     Ok(Vec::new())
 }
+
+/// Recompute the contributions of the workflow with the current weights of its ranking
+/// profile, as a new snapshot, the allocations issued so far keeping the previous one
+#[utoipa::path(
+    operation_id = "recompute-contributions",
+    post, path = "/v1/workflows/{id}/contributions/recompute",
+    params(
+        ("id" = Uuid, description = "The id of workflow"),
+    ),
+    responses(
+        (status = 200, description = "Contributions recomputed successfully", body = RecomputeResponse),
+        (status = 400, description = "Workflow not ranked, or its scores cannot be recomputed"),
+        (status = 404, description = "Workflow not found")
+    ),
+    tag = "Contribution"
+)]
+#[instrument(skip_all, fields(workflow_id = %id))]
+pub async fn recompute(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    WorkflowService::authorize(&ctx, &key, id, Role::Approver)?;
+    Ok(Json(ContributionService::recompute(ctx, id).await?))
+}
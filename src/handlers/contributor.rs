@@ -17,11 +17,21 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    http::header::ETAG,
     response::IntoResponse,
+    Extension, Json,
 };
+use tracing::instrument;
 
-use crate::{context::Context, errors::Result};
+use crate::{
+    context::Context,
+    errors::Result,
+    middlewares::{etag, trace::RequestId},
+    requests::{fields::FieldsParams, list::ListParams},
+    responses::{contributor::ContributorResponse, list::ListResponse},
+    services::contributor::ContributorService,
+};
 
 /// Get contributors list of the project
 #[utoipa::path(
@@ -30,19 +40,38 @@ use crate::{context::Context, errors::Result};
     params(
         ("owner" = String, description = "The owner of project"),
         ("name" = String, description = "The name of project"),
+        ListParams,
+        FieldsParams,
     ),
     responses(
-        (status = 200, description = "Contributors retrieved successfully"),
+        (status = 200, description = "Contributors retrieved successfully",
+            body = ListResponse<ContributorResponse>),
+        (status = 304, description = "Contributors not modified since the given ETag"),
         (status = 404, description = "Project not found"),
         (status = 500, description = "Failed to get project")
     ),
     tag = "Contributor"
 )]
+#[instrument(skip_all, fields(%owner, %name))]
 pub async fn list(
-    State(_ctx): State<Arc<Context>>,
-    Path((_owner, _name)): Path<(String, String)>,
+    State(ctx): State<Arc<Context>>,
+    Extension(request_id): Extension<RequestId>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(params): Query<ListParams>,
+    Query(fields): Query<FieldsParams>,
 ) -> Result<impl IntoResponse> {
-    Ok(Vec::new())
+    params.validate()?;
+
+    let (commit, contributors) = ContributorService::list(ctx, &owner, &name).await?;
+    let (items, pagination) = params.apply(contributors)?;
+    let items = fields.select(&items);
+    let etag = commit.and_then(|commit| etag::for_content(&commit, &(&items, &pagination)));
+
+    let mut res = Json(ListResponse::new(items, pagination, &request_id)).into_response();
+    if let Some(etag) = etag {
+        res.headers_mut().insert(ETAG, etag);
+    }
+    Ok(res)
 }
 
 /// Get the contributor detail of the project
@@ -61,9 +90,10 @@ pub async fn list(
     ),
     tag = "Contributor"
 )]
+#[instrument(skip_all, fields(%owner, %name))]
 pub async fn get(
     State(_ctx): State<Arc<Context>>,
-    Path((_owner, _name, _username)): Path<(String, String, String)>,
+    Path((owner, name, _username)): Path<(String, String, String)>,
 ) -> Result<impl IntoResponse> {
     Ok(Vec::new())
 }
@@ -0,0 +1,57 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! The Cost Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    errors::Result,
+    middlewares::ratelimit::ClientKey,
+    requests::organization::Role,
+    responses::cost::CostEstimateResponse,
+    services::{cost::CostService, workflow::WorkflowService},
+};
+
+/// Estimate the transaction fees of the remaining steps of the workflow and its token budget
+#[utoipa::path(
+    operation_id = "get-workflow-cost-estimate",
+    get, path = "/v1/workflows/{id}/cost-estimate",
+    params(
+        ("id" = Uuid, description = "The id of workflow"),
+    ),
+    responses(
+        (status = 200, description = "Cost estimated successfully", body = CostEstimateResponse),
+        (status = 404, description = "Workflow not found"),
+        (status = 503, description = "Chain connectivity degraded")
+    ),
+    tag = "Cost"
+)]
+#[instrument(skip_all, fields(workflow_id = %id))]
+pub async fn get(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    WorkflowService::authorize(&ctx, &key, id, Role::Member)?;
+    Ok(Json(CostService::estimate(ctx, id).await?))
+}
@@ -0,0 +1,71 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The Credential Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    contracts::types::Id,
+    errors::Result,
+    responses::credential::{CredentialResponse, KeySetResponse},
+    services::credential::CredentialService,
+};
+
+/// Export an executed allocation as a verifiable credential of the funding
+#[utoipa::path(
+    operation_id = "get-allocation-credential",
+    get, path = "/v1/workflows/{id}/allocations/{allocation_id}/credential",
+    params(
+        ("id" = Uuid, description = "The id of workflow"),
+        ("allocation_id" = String, description = "The id of allocation"),
+    ),
+    responses(
+        (status = 200, description = "Credential issued successfully", body = CredentialResponse),
+        (status = 400, description = "Allocation not executed"),
+        (status = 404, description = "Workflow or allocation not found"),
+        (status = 503, description = "No credential key configured")
+    ),
+    tag = "Credential"
+)]
+#[instrument(skip_all, fields(workflow_id = %id, %allocation_id))]
+pub async fn get(
+    State(ctx): State<Arc<Context>>,
+    Path((id, allocation_id)): Path<(Uuid, Id)>,
+) -> Result<impl IntoResponse> {
+    Ok(Json(CredentialService::issue(ctx, id, &allocation_id).await?))
+}
+
+/// Get the public keys verifying the credentials, as a JSON Web Key Set
+#[utoipa::path(
+    operation_id = "get-credential-keys",
+    get, path = "/v1/credentials/keys",
+    responses(
+        (status = 200, description = "Keys retrieved successfully", body = KeySetResponse),
+        (status = 503, description = "No credential key configured")
+    ),
+    tag = "Credential"
+)]
+pub async fn keys(State(ctx): State<Arc<Context>>) -> Result<impl IntoResponse> {
+    Ok(Json(CredentialService::keys(ctx).await?))
+}
@@ -17,11 +17,33 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Path, State},
-    response::IntoResponse,
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Extension, Json,
 };
+use serde::Deserialize;
+use tracing::instrument;
+use utoipa::IntoParams;
 
-use crate::{context::Context, errors::Result};
+use crate::{
+    context::Context,
+    errors::{ApiError, Result},
+    middlewares::{etag, trace::RequestId},
+    requests::{fields::FieldsParams, list::ListParams},
+    responses::{
+        dependency::{DependencyGraphResponse, DependencyResponse},
+        list::ListResponse,
+    },
+    services::dependency::DependencyService,
+};
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GraphParams {
+    /// The export format, `json` (default) or `dot`, for Graphviz.
+    pub format: Option<String>,
+}
 
 /// Get dependencies list of the project
 #[utoipa::path(
@@ -30,19 +52,38 @@ use crate::{context::Context, errors::Result};
     params(
         ("owner" = String, description = "The owner of project"),
         ("name" = String, description = "The name of project"),
+        ListParams,
+        FieldsParams,
     ),
     responses(
-        (status = 200, description = "Dependencies retrieved successfully"),
+        (status = 200, description = "Dependencies retrieved successfully",
+            body = ListResponse<DependencyResponse>),
+        (status = 304, description = "Dependencies not modified since the given ETag"),
         (status = 404, description = "Project not found"),
         (status = 500, description = "Failed to get project")
     ),
     tag = "Dependency"
 )]
+#[instrument(skip_all, fields(%owner, %name))]
 pub async fn list(
-    State(_ctx): State<Arc<Context>>,
-    Path((_owner, _name)): Path<(String, String)>,
+    State(ctx): State<Arc<Context>>,
+    Extension(request_id): Extension<RequestId>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(params): Query<ListParams>,
+    Query(fields): Query<FieldsParams>,
 ) -> Result<impl IntoResponse> {
-    Ok(Vec::new())
+    params.validate()?;
+
+    let (commit, dependencies) = DependencyService::list(ctx, &owner, &name).await?;
+    let (items, pagination) = params.apply(dependencies)?;
+    let items = fields.select(&items);
+    let etag = commit.and_then(|commit| etag::for_content(&commit, &(&items, &pagination)));
+
+    let mut res = Json(ListResponse::new(items, pagination, &request_id)).into_response();
+    if let Some(etag) = etag {
+        res.headers_mut().insert(header::ETAG, etag);
+    }
+    Ok(res)
 }
 
 /// Get the dependency detail of the project
@@ -61,9 +102,55 @@ pub async fn list(
     ),
     tag = "Dependency"
 )]
+#[instrument(skip_all, fields(%owner, %name))]
 pub async fn get(
     State(_ctx): State<Arc<Context>>,
-    Path((_owner, _name, _dep)): Path<(String, String, String)>,
+    Path((owner, name, _dep)): Path<(String, String, String)>,
 ) -> Result<impl IntoResponse> {
     Ok(Vec::new())
 }
+
+/// Get the dependency graph of the project, from its latest snapshot
+#[utoipa::path(
+    operation_id = "get-dependency-graph",
+    get, path = "/v1/projects/{owner}/{name}/graph",
+    params(
+        ("owner" = String, description = "The owner of project"),
+        ("name" = String, description = "The name of project"),
+        GraphParams,
+    ),
+    responses(
+        (status = 200, description = "Dependency graph retrieved successfully",
+            content(
+                (DependencyGraphResponse = "application/json"),
+                (String = "text/vnd.graphviz")
+            )),
+        (status = 400, description = "Unsupported format"),
+        (status = 404, description = "Project not found")
+    ),
+    tag = "Dependency"
+)]
+#[instrument(skip_all, fields(%owner, %name))]
+pub async fn graph(
+    State(ctx): State<Arc<Context>>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(params): Query<GraphParams>,
+) -> Result<Response> {
+    let dot = match params.format.as_deref() {
+        None | Some("json") => false,
+        Some("dot") => true,
+        Some(format) => {
+            return Err(ApiError::BadGraphRequest(format!("Unsupported format {format}")));
+        }
+    };
+    let graph = DependencyService::graph(ctx, &owner, &name).await?;
+
+    if dot {
+        return Ok((
+            [(header::CONTENT_TYPE, "text/vnd.graphviz; charset=utf-8")],
+            DependencyService::to_dot(&graph),
+        )
+            .into_response());
+    }
+    Ok(Json(graph).into_response())
+}
@@ -0,0 +1,40 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::sync::Arc;
+
+use axum::{
+    extract::{State, WebSocketUpgrade},
+    response::IntoResponse,
+};
+
+use crate::{context::Context, services::event::EventService};
+
+/// Subscribe to the live updates of workflows and projects
+///
+/// Upgrades to a WebSocket. The client sends `SubscriptionRequest` messages to subscribe
+/// to workflow ids or project slugs, and is sent `EventMessage` messages: the progress of
+/// the analysis, the new contributions and the allocation status changes of the
+/// workflows it subscribed to.
+#[utoipa::path(
+    operation_id = "subscribe-events",
+    get, path = "/v1/ws",
+    responses(
+        (status = 101, description = "Switched to the WebSocket protocol"),
+        (status = 426, description = "Not a WebSocket upgrade request")
+    ),
+    tag = "Event"
+)]
+pub async fn subscribe(State(ctx): State<Arc<Context>>, ws: WebSocketUpgrade) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| EventService::serve(ctx, socket))
+}
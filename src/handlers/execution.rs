@@ -0,0 +1,56 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! The Execution Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    errors::Result,
+    middlewares::ratelimit::ClientKey,
+    requests::organization::Role,
+    responses::execution::ExecutionPreviewResponse,
+    services::{execution::ExecutionService, workflow::WorkflowService},
+};
+
+/// Preview how the approved allocations of the workflow would be paid out
+#[utoipa::path(
+    operation_id = "get-workflow-execution-preview",
+    get, path = "/v1/workflows/{id}/execution/preview",
+    params(
+        ("id" = Uuid, description = "The id of workflow"),
+    ),
+    responses(
+        (status = 200, description = "Preview retrieved successfully", body = ExecutionPreviewResponse),
+        (status = 404, description = "Workflow not found")
+    ),
+    tag = "Execution"
+)]
+#[instrument(skip_all, fields(workflow_id = %id))]
+pub async fn preview(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    WorkflowService::authorize(&ctx, &key, id, Role::Member)?;
+    Ok(Json(ExecutionService::preview(ctx, id).await?))
+}
@@ -0,0 +1,137 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The GitHub App Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Extension, Json,
+};
+use serde::Deserialize;
+use tracing::instrument;
+use utoipa::IntoParams;
+
+use crate::{
+    context::Context, errors::Result, middlewares::ratelimit::ClientKey,
+    responses::github::InstallationResponse, services::github::GitHubService,
+};
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SetupParams {
+    /// The id of the installation, set by GitHub.
+    pub installation_id: u64,
+    /// `install` or `update`, set by GitHub.
+    pub setup_action: Option<String>,
+}
+
+/// The setup callback GitHub redirects to once the App is installed or its repositories
+/// changed, recording the installation and its granted repositories
+#[utoipa::path(
+    operation_id = "setup-github-installation",
+    get, path = "/v1/github/setup",
+    params(SetupParams),
+    responses(
+        (status = 200, description = "Installation recorded successfully", body = InstallationResponse),
+        (status = 404, description = "Installation not found on GitHub"),
+        (status = 503, description = "No GitHub App configured")
+    ),
+    tag = "GitHub"
+)]
+#[instrument(skip_all, fields(installation_id = params.installation_id))]
+pub async fn setup(
+    State(ctx): State<Arc<Context>>,
+    Query(params): Query<SetupParams>,
+) -> Result<impl IntoResponse> {
+    Ok(Json(GitHubService::setup(ctx, params.installation_id).await?))
+}
+
+/// Receive the webhook events of the App, signed with its webhook secret
+#[utoipa::path(
+    operation_id = "post-github-webhook",
+    post, path = "/v1/github/webhooks",
+    params(
+        ("X-GitHub-Event" = String, Header, description = "The event, eg. `installation`"),
+        ("X-Hub-Signature-256" = String, Header, description = "The HMAC-SHA256 of the body"),
+    ),
+    request_body(content = String, description = "The event payload", content_type = "application/json"),
+    responses(
+        (status = 204, description = "Event applied or ignored"),
+        (status = 400, description = "Invalid event payload"),
+        (status = 401, description = "Invalid signature"),
+        (status = 503, description = "No GitHub App or webhook secret configured")
+    ),
+    tag = "GitHub"
+)]
+#[instrument(skip_all)]
+pub async fn webhook(
+    State(ctx): State<Arc<Context>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<impl IntoResponse> {
+    let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+    let event = header("x-github-event").unwrap_or_default();
+    GitHubService::webhook(ctx, event, header("x-hub-signature-256"), &body).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List the installations of the App
+#[utoipa::path(
+    operation_id = "list-github-installations",
+    get, path = "/v1/github/installations",
+    responses(
+        (status = 200, description = "Installations retrieved successfully", body = [InstallationResponse]),
+        (status = 403, description = "Not an admin key"),
+        (status = 503, description = "No GitHub App configured")
+    ),
+    security(("api_key" = [])),
+    tag = "GitHub"
+)]
+#[instrument(skip_all)]
+pub async fn list(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+) -> Result<impl IntoResponse> {
+    Ok(Json(GitHubService::list(ctx, &key).await?))
+}
+
+/// Get an installation of the App, with its granted repositories
+#[utoipa::path(
+    operation_id = "get-github-installation",
+    get, path = "/v1/github/installations/{id}",
+    params(
+        ("id" = u64, description = "The id of installation"),
+    ),
+    responses(
+        (status = 200, description = "Installation retrieved successfully", body = InstallationResponse),
+        (status = 403, description = "Not an admin key"),
+        (status = 404, description = "Installation not found"),
+        (status = 503, description = "No GitHub App configured")
+    ),
+    security(("api_key" = [])),
+    tag = "GitHub"
+)]
+#[instrument(skip_all, fields(%id))]
+pub async fn get(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(id): Path<u64>,
+) -> Result<impl IntoResponse> {
+    Ok(Json(GitHubService::get(ctx, &key, id).await?))
+}
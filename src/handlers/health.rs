@@ -0,0 +1,90 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The Health Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{extract::State, http::header, response::IntoResponse, Json};
+
+use crate::{
+    context::Context,
+    errors::Result,
+    responses::health::{BreakerState, HealthResponse, HealthStatus},
+};
+
+/// Get the health of the service and the connectivity to the chain provider
+#[utoipa::path(
+    operation_id = "get-health",
+    get, path = "/v1/health",
+    responses(
+        (status = 200, description = "Health retrieved successfully", body = HealthResponse)
+    ),
+    tag = "Health"
+)]
+pub async fn get(State(ctx): State<Arc<Context>>) -> Result<impl IntoResponse> {
+    let chain = ctx.contract.breaker().status();
+    let status = match chain.state {
+        BreakerState::Closed => HealthStatus::Ok,
+        BreakerState::Open => HealthStatus::Degraded,
+    };
+    Ok(Json(HealthResponse { status, chain }))
+}
+
+/// Get the metrics of the service, in the Prometheus text format
+#[utoipa::path(
+    operation_id = "get-metrics",
+    get, path = "/v1/metrics",
+    responses(
+        (status = 200, description = "Metrics retrieved successfully", body = String, content_type = "text/plain")
+    ),
+    tag = "Health"
+)]
+pub async fn metrics(State(ctx): State<Arc<Context>>) -> Result<impl IntoResponse> {
+    let chain = ctx.contract.breaker().status();
+    let metrics = [
+        (
+            "deprank_chain_breaker_open",
+            "gauge",
+            "Whether the circuit breaker of the chain provider is open.",
+            u64::from(chain.state == BreakerState::Open),
+        ),
+        (
+            "deprank_chain_consecutive_failures",
+            "gauge",
+            "Failed chain calls since the last successful one.",
+            u64::from(chain.consecutive_failures),
+        ),
+        (
+            "deprank_chain_breaker_trips_total",
+            "counter",
+            "Times the circuit breaker of the chain provider opened.",
+            chain.trips,
+        ),
+        (
+            "deprank_chain_rejected_calls_total",
+            "counter",
+            "Chain calls refused while the circuit breaker was open.",
+            chain.rejected,
+        ),
+    ];
+
+    let body: String = metrics
+        .iter()
+        .map(|(name, kind, help, value)| {
+            format!("# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}\n")
+        })
+        .collect();
+    Ok(([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body))
+}
@@ -0,0 +1,111 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The Identity Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use tracing::instrument;
+
+use crate::{
+    context::Context,
+    errors::Result,
+    middlewares::ratelimit::ClientKey,
+    requests::{identity::IdentityRequest, validation::ValidatedJson},
+    responses::identity::IdentityResponse,
+    services::identity::IdentityService,
+};
+
+/// Get the emails and usernames merged into a contributor.
+#[utoipa::path(
+    operation_id = "get-contributor-identities",
+    get, path = "/v1/contributors/{username}/identities",
+    params(
+        ("username" = String, description = "The GitHub username of the contributor"),
+    ),
+    responses(
+        (status = 200, description = "Identities retrieved successfully", body = IdentityResponse)
+    ),
+    security(("api_key" = [])),
+    tag = "Identity"
+)]
+#[instrument(skip_all, fields(username = %username))]
+pub async fn get(
+    State(ctx): State<Arc<Context>>,
+    Path(username): Path<String>,
+) -> Result<impl IntoResponse> {
+    Ok((StatusCode::OK, Json(IdentityService::get(ctx, &username).await?)))
+}
+
+/// Merge emails and usernames into a contributor.
+#[utoipa::path(
+    operation_id = "merge-contributor-identities",
+    put, path = "/v1/contributors/{username}/identities",
+    params(
+        ("username" = String, description = "The GitHub username of the contributor"),
+    ),
+    request_body(
+        content = inline(IdentityRequest),
+        description = "Identity request",
+        content_type = "application/json"
+    ),
+    responses(
+        (status = 200, description = "Identities merged successfully", body = IdentityResponse),
+        (status = 400, description = "An alias is merged into another contributor"),
+        (status = 403, description = "Not an admin key"),
+        (status = 422, description = "Invalid request body, with every invalid field in `errors`")
+    ),
+    security(("api_key" = [])),
+    tag = "Identity"
+)]
+#[instrument(skip_all, fields(username = %username))]
+pub async fn merge(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(username): Path<String>,
+    ValidatedJson(req): ValidatedJson<IdentityRequest>,
+) -> Result<impl IntoResponse> {
+    Ok((StatusCode::OK, Json(IdentityService::merge(ctx, &key, &username, req).await?)))
+}
+
+/// Split an email or a username from a contributor.
+#[utoipa::path(
+    operation_id = "split-contributor-identity",
+    delete, path = "/v1/contributors/{username}/identities/{alias}",
+    params(
+        ("username" = String, description = "The GitHub username of the contributor"),
+        ("alias" = String, description = "The merged email or username"),
+    ),
+    responses(
+        (status = 200, description = "Identity split successfully", body = IdentityResponse),
+        (status = 403, description = "Not an admin key"),
+        (status = 404, description = "The alias is not merged into the contributor")
+    ),
+    security(("api_key" = [])),
+    tag = "Identity"
+)]
+#[instrument(skip_all, fields(username = %username, %alias))]
+pub async fn split(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path((username, alias)): Path<(String, String)>,
+) -> Result<impl IntoResponse> {
+    Ok((StatusCode::OK, Json(IdentityService::split(ctx, &key, &username, &alias).await?)))
+}
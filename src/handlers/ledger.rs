@@ -0,0 +1,83 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The Ledger Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use serde::Deserialize;
+use tracing::instrument;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    errors::Result,
+    middlewares::ratelimit::ClientKey,
+    requests::organization::Role,
+    responses::ledger::LedgerResponse,
+    services::{ledger::LedgerService, workflow::WorkflowService},
+};
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LedgerParams {
+    /// The export format, `json` (default) or `csv`.
+    pub format: Option<String>,
+}
+
+/// Get the ledger of the workflow
+#[utoipa::path(
+    operation_id = "get-workflow-ledger",
+    get, path = "/v1/workflows/{id}/ledger",
+    params(
+        ("id" = Uuid, description = "The id of workflow"),
+        LedgerParams,
+    ),
+    responses(
+        (status = 200, description = "Ledger retrieved successfully",
+            content((LedgerResponse = "application/json"), (String = "text/csv"))),
+        (status = 404, description = "Workflow not found"),
+        (status = 500, description = "Invalid ledger entry")
+    ),
+    tag = "Ledger"
+)]
+#[instrument(skip_all, fields(workflow_id = %id))]
+pub async fn get(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<LedgerParams>,
+) -> Result<Response> {
+    WorkflowService::authorize(&ctx, &key, id, Role::Member)?;
+    let ledger = ctx.ledger.get(id, ctx.contract.as_ref())?;
+
+    match params.format.as_deref() {
+        Some("csv") => Ok((
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                (header::CONTENT_DISPOSITION, format!("attachment; filename=\"ledger-{id}.csv\"")),
+            ],
+            LedgerService::to_csv(&ledger),
+        )
+            .into_response()),
+        _ => Ok(Json(ledger).into_response()),
+    }
+}
@@ -0,0 +1,173 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The Maintainer claim portal Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use tracing::instrument;
+
+use crate::{
+    context::Context,
+    contracts::types::Id,
+    errors::Result,
+    middlewares::ratelimit::ClientKey,
+    requests::{
+        maintainer::SignedClaimRequest, validation::ValidatedJson, wallet::WalletAddressRequest,
+    },
+    responses::{
+        claim::{ClaimExecutedResponse, ClaimResponse},
+        maintainer::{MaintainerClaimResponse, MaintainerClaimsResponse},
+    },
+    services::maintainer::MaintainerService,
+};
+
+/// Get the allocations funding a maintainer.
+#[utoipa::path(
+    operation_id = "get-maintainer-claims",
+    get, path = "/v1/maintainers/{username}/claims",
+    params(
+        ("username" = String, description = "The GitHub username of the maintainer"),
+    ),
+    responses(
+        (status = 200, description = "Claims retrieved successfully", body = MaintainerClaimsResponse)
+    ),
+    security(("api_key" = [])),
+    tag = "Maintainer"
+)]
+#[instrument(skip_all, fields(%username))]
+pub async fn list(
+    State(ctx): State<Arc<Context>>,
+    Path(username): Path<String>,
+) -> Result<impl IntoResponse> {
+    Ok((StatusCode::OK, Json(MaintainerService::claims(ctx, &username).await?)))
+}
+
+/// Get an allocation funding a maintainer, to follow its claim.
+#[utoipa::path(
+    operation_id = "get-maintainer-claim",
+    get, path = "/v1/maintainers/{username}/claims/{allocation_id}",
+    params(
+        ("username" = String, description = "The GitHub username of the maintainer"),
+        ("allocation_id" = String, description = "The id of allocation"),
+    ),
+    responses(
+        (status = 200, description = "Claim retrieved successfully", body = MaintainerClaimResponse),
+        (status = 404, description = "No such allocation funding the maintainer")
+    ),
+    security(("api_key" = [])),
+    tag = "Maintainer"
+)]
+#[instrument(skip_all, fields(%username, %allocation_id))]
+pub async fn get(
+    State(ctx): State<Arc<Context>>,
+    Path((username, allocation_id)): Path<(String, Id)>,
+) -> Result<impl IntoResponse> {
+    Ok((StatusCode::OK, Json(MaintainerService::claim(ctx, &username, &allocation_id).await?)))
+}
+
+/// Link the wallet of a maintainer, the claimable allocations are paid out to.
+#[utoipa::path(
+    operation_id = "link-maintainer-wallet",
+    put, path = "/v1/maintainers/{username}/wallet",
+    params(
+        ("username" = String, description = "The GitHub username of the maintainer"),
+    ),
+    request_body(
+        content = inline(WalletAddressRequest),
+        description = "Link wallet request",
+        content_type = "application/json"
+    ),
+    responses(
+        (status = 200, description = "Wallet linked successfully", body = MaintainerClaimsResponse),
+        (status = 403, description = "Not an admin key"),
+        (status = 422, description = "Invalid request body, with every invalid field in `errors`")
+    ),
+    security(("api_key" = [])),
+    tag = "Maintainer"
+)]
+#[instrument(skip_all, fields(%username))]
+pub async fn link(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(username): Path<String>,
+    ValidatedJson(req): ValidatedJson<WalletAddressRequest>,
+) -> Result<impl IntoResponse> {
+    Ok((StatusCode::OK, Json(MaintainerService::link(ctx, &key, &username, req).await?)))
+}
+
+/// Build the sponsored claim of an allocation, to be signed by the linked wallet.
+#[utoipa::path(
+    operation_id = "build-maintainer-claim",
+    post, path = "/v1/maintainers/{username}/claims/{allocation_id}/build",
+    params(
+        ("username" = String, description = "The GitHub username of the maintainer"),
+        ("allocation_id" = String, description = "The id of allocation"),
+    ),
+    responses(
+        (status = 200, description = "Claim built successfully", body = ClaimResponse),
+        (status = 400, description = "No linked wallet, or allocation not claimable"),
+        (status = 403, description = "The maintainer did not accept the current terms"),
+        (status = 404, description = "No such allocation funding the maintainer"),
+        (status = 503, description = "Paymaster unavailable")
+    ),
+    security(("api_key" = [])),
+    tag = "Maintainer"
+)]
+#[instrument(skip_all, fields(%username, %allocation_id))]
+pub async fn build(
+    State(ctx): State<Arc<Context>>,
+    Path((username, allocation_id)): Path<(String, Id)>,
+) -> Result<impl IntoResponse> {
+    Ok((StatusCode::OK, Json(MaintainerService::build(ctx, &username, &allocation_id).await?)))
+}
+
+/// Submit the claim of an allocation, signed by the linked wallet.
+#[utoipa::path(
+    operation_id = "execute-maintainer-claim",
+    post, path = "/v1/maintainers/{username}/claims/{allocation_id}/execute",
+    params(
+        ("username" = String, description = "The GitHub username of the maintainer"),
+        ("allocation_id" = String, description = "The id of allocation"),
+    ),
+    request_body(
+        content = inline(SignedClaimRequest),
+        description = "Signed claim request",
+        content_type = "application/json"
+    ),
+    responses(
+        (status = 202, description = "Claim submitted successfully", body = ClaimExecutedResponse),
+        (status = 400, description = "No linked wallet, or allocation not claimable"),
+        (status = 404, description = "No such allocation funding the maintainer"),
+        (status = 422, description = "Invalid request body, with every invalid field in `errors`"),
+        (status = 503, description = "Paymaster unavailable")
+    ),
+    security(("api_key" = [])),
+    tag = "Maintainer"
+)]
+#[instrument(skip_all, fields(%username, %allocation_id))]
+pub async fn execute(
+    State(ctx): State<Arc<Context>>,
+    Path((username, allocation_id)): Path<(String, Id)>,
+    ValidatedJson(req): ValidatedJson<SignedClaimRequest>,
+) -> Result<impl IntoResponse> {
+    let claim = MaintainerService::execute(ctx, &username, &allocation_id, req).await?;
+    Ok((StatusCode::ACCEPTED, Json(claim)))
+}
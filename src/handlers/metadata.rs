@@ -0,0 +1,92 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The Metadata Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use serde_json::Value;
+use tracing::instrument;
+
+use crate::{
+    context::Context,
+    contracts::types::Id,
+    errors::Result,
+    responses::metadata::{MetadataResponse, MetadataVerificationResponse},
+    services::{metadata::MetadataService, receipt::ReceiptService},
+};
+
+/// Get the JSON Schema of a version of the dependency metadata
+#[utoipa::path(
+    operation_id = "get-metadata-schema",
+    get, path = "/v1/schemas/dependency-metadata/{version}",
+    params(
+        ("version" = u32, description = "The version of the schema"),
+    ),
+    responses(
+        (status = 200, description = "Schema retrieved successfully", body = Object),
+        (status = 404, description = "Unknown schema version")
+    ),
+    tag = "Metadata"
+)]
+pub async fn schema(Path(version): Path<u32>) -> Result<impl IntoResponse> {
+    Ok(Json(MetadataService::schema(version)?))
+}
+
+/// Validate dependency metadata, as written on chain with the dependencies
+#[utoipa::path(
+    operation_id = "validate-metadata",
+    post, path = "/v1/schemas/dependency-metadata/validate",
+    request_body(
+        content = Object,
+        description = "The metadata document",
+        content_type = "application/json"
+    ),
+    responses(
+        (status = 200, description = "Metadata is valid", body = MetadataResponse),
+        (status = 422, description = "Invalid metadata, with every invalid field in `errors`")
+    ),
+    tag = "Metadata"
+)]
+pub async fn validate(Json(document): Json<Value>) -> Result<impl IntoResponse> {
+    let (_, validated) = MetadataService::validate(&document)?;
+    Ok(Json(validated))
+}
+
+/// Verify the stored metadata of a receipt against the hash stored on chain
+#[utoipa::path(
+    operation_id = "verify-receipt-metadata",
+    get, path = "/v1/receipts/{id}/verification",
+    params(
+        ("id" = String, description = "The id of receipt"),
+    ),
+    responses(
+        (status = 200, description = "Metadata verified, a mismatch is reported in the body", body = MetadataVerificationResponse),
+        (status = 404, description = "Receipt not found"),
+        (status = 503, description = "Metadata store unavailable")
+    ),
+    tag = "Metadata"
+)]
+#[instrument(skip_all, fields(receipt_id = %id))]
+pub async fn verify(
+    State(ctx): State<Arc<Context>>,
+    Path(id): Path<Id>,
+) -> Result<impl IntoResponse> {
+    Ok(Json(ReceiptService::verify(&ctx, id).await?))
+}
@@ -12,11 +12,42 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod address;
 pub mod airdrop;
 pub mod allocation;
+pub mod artifact;
+pub mod attestation;
+pub mod batch;
+pub mod claim;
 pub mod contribution;
 pub mod contributor;
+pub mod cost;
+pub mod credential;
 pub mod dependency;
+pub mod event;
+pub mod execution;
+pub mod github;
+pub mod health;
+pub mod identity;
+pub mod ledger;
+pub mod maintainer;
+pub mod metadata;
+pub mod notification;
+pub mod organization;
+pub mod policy;
+pub mod pool;
+pub mod profile;
 pub mod project;
+pub mod ranking;
+pub mod quota;
+pub mod ratelimit;
+pub mod report;
+pub mod sign;
+pub mod snapshot;
+pub mod step;
+pub mod transaction;
+pub mod treasury;
+pub mod usage;
 pub mod wallet;
+pub mod webhook;
 pub mod workflow;
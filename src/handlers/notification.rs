@@ -0,0 +1,201 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! The Notification Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    errors::Result,
+    middlewares::ratelimit::ClientKey,
+    requests::{
+        notification::{NotificationChannelsRequest, NotificationPreferencesRequest},
+        validation::ValidatedJson,
+    },
+    responses::notification::{NotificationChannelsResponse, NotificationPreferencesResponse},
+    services::notification::{ChannelScope, NotificationService},
+};
+
+/// Get the notification preferences of a user
+#[utoipa::path(
+    operation_id = "get-notification-preferences",
+    get, path = "/v1/contributors/{username}/notifications",
+    params(
+        ("username" = String, description = "The GitHub username of the user"),
+    ),
+    responses(
+        (status = 200, description = "Preferences retrieved successfully", body = NotificationPreferencesResponse),
+        (status = 403, description = "Not an admin key")
+    ),
+    security(("api_key" = [])),
+    tag = "Notification"
+)]
+#[instrument(skip_all, fields(%username))]
+pub async fn get(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(username): Path<String>,
+) -> Result<impl IntoResponse> {
+    Ok(Json(NotificationService::get(ctx, &key, &username).await?))
+}
+
+/// Replace the notification preferences of a user
+#[utoipa::path(
+    operation_id = "put-notification-preferences",
+    put, path = "/v1/contributors/{username}/notifications",
+    params(
+        ("username" = String, description = "The GitHub username of the user"),
+    ),
+    request_body(
+        content = inline(NotificationPreferencesRequest),
+        description = "Notification preferences request",
+        content_type = "application/json"
+    ),
+    responses(
+        (status = 200, description = "Preferences saved successfully", body = NotificationPreferencesResponse),
+        (status = 400, description = "Invalid email address"),
+        (status = 403, description = "Not an admin key"),
+        (status = 422, description = "Invalid request body, with every invalid field in `errors`")
+    ),
+    security(("api_key" = [])),
+    tag = "Notification"
+)]
+#[instrument(skip_all, fields(%username))]
+pub async fn put(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(username): Path<String>,
+    ValidatedJson(req): ValidatedJson<NotificationPreferencesRequest>,
+) -> Result<impl IntoResponse> {
+    Ok(Json(NotificationService::put(ctx, &key, &username, req).await?))
+}
+
+/// Get the Discord and Slack channels the workflow events of an owner are posted to
+#[utoipa::path(
+    operation_id = "get-owner-notification-channels",
+    get, path = "/v1/owners/{owner}/notification-channels",
+    params(
+        ("owner" = String, description = "The owner of projects"),
+    ),
+    responses(
+        (status = 200, description = "Channels retrieved successfully", body = NotificationChannelsResponse),
+        (status = 403, description = "Not an admin key")
+    ),
+    security(("api_key" = [])),
+    tag = "Notification"
+)]
+#[instrument(skip_all, fields(%owner))]
+pub async fn get_owner_channels(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(owner): Path<String>,
+) -> Result<impl IntoResponse> {
+    Ok(Json(NotificationService::get_channels(ctx, &key, ChannelScope::Owner(owner)).await?))
+}
+
+/// Replace the Discord and Slack channels the workflow events of an owner are posted to
+#[utoipa::path(
+    operation_id = "put-owner-notification-channels",
+    put, path = "/v1/owners/{owner}/notification-channels",
+    params(
+        ("owner" = String, description = "The owner of projects"),
+    ),
+    request_body(
+        content = inline(NotificationChannelsRequest),
+        description = "Notification channels request",
+        content_type = "application/json"
+    ),
+    responses(
+        (status = 200, description = "Channels saved successfully", body = NotificationChannelsResponse),
+        (status = 400, description = "Invalid webhook URL"),
+        (status = 403, description = "Not an admin key"),
+        (status = 422, description = "Invalid request body, with every invalid field in `errors`")
+    ),
+    security(("api_key" = [])),
+    tag = "Notification"
+)]
+#[instrument(skip_all, fields(%owner))]
+pub async fn put_owner_channels(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(owner): Path<String>,
+    ValidatedJson(req): ValidatedJson<NotificationChannelsRequest>,
+) -> Result<impl IntoResponse> {
+    let scope = ChannelScope::Owner(owner);
+    Ok(Json(NotificationService::put_channels(ctx, &key, scope, req).await?))
+}
+
+/// Get the Discord and Slack channels the events of a workflow are posted to
+#[utoipa::path(
+    operation_id = "get-workflow-notification-channels",
+    get, path = "/v1/workflows/{id}/notification-channels",
+    params(
+        ("id" = Uuid, description = "The id of workflow"),
+    ),
+    responses(
+        (status = 200, description = "Channels retrieved successfully", body = NotificationChannelsResponse),
+        (status = 403, description = "Not an admin key")
+    ),
+    security(("api_key" = [])),
+    tag = "Notification"
+)]
+#[instrument(skip_all, fields(%id))]
+pub async fn get_workflow_channels(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    Ok(Json(NotificationService::get_channels(ctx, &key, ChannelScope::Workflow(id)).await?))
+}
+
+/// Replace the Discord and Slack channels the events of a workflow are posted to, those
+/// of its owner when none is set
+#[utoipa::path(
+    operation_id = "put-workflow-notification-channels",
+    put, path = "/v1/workflows/{id}/notification-channels",
+    params(
+        ("id" = Uuid, description = "The id of workflow"),
+    ),
+    request_body(
+        content = inline(NotificationChannelsRequest),
+        description = "Notification channels request",
+        content_type = "application/json"
+    ),
+    responses(
+        (status = 200, description = "Channels saved successfully", body = NotificationChannelsResponse),
+        (status = 400, description = "Invalid webhook URL"),
+        (status = 403, description = "Not an admin key"),
+        (status = 422, description = "Invalid request body, with every invalid field in `errors`")
+    ),
+    security(("api_key" = [])),
+    tag = "Notification"
+)]
+#[instrument(skip_all, fields(%id))]
+pub async fn put_workflow_channels(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(req): ValidatedJson<NotificationChannelsRequest>,
+) -> Result<impl IntoResponse> {
+    let scope = ChannelScope::Workflow(id);
+    Ok(Json(NotificationService::put_channels(ctx, &key, scope, req).await?))
+}
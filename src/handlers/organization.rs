@@ -0,0 +1,173 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The Organization Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use tracing::instrument;
+
+use crate::{
+    context::Context,
+    errors::Result,
+    middlewares::ratelimit::ClientKey,
+    requests::{
+        organization::{MemberRequest, OrganizationRequest},
+        validation::ValidatedJson,
+    },
+    responses::organization::OrganizationResponse,
+    services::organization::OrganizationService,
+};
+
+/// Get an organization
+#[utoipa::path(
+    operation_id = "get-organization",
+    get, path = "/v1/organizations/{org}",
+    params(
+        ("org" = String, description = "The name of the organization"),
+    ),
+    responses(
+        (status = 200, description = "Organization retrieved successfully", body = OrganizationResponse),
+        (status = 404, description = "Organization not found")
+    ),
+    tag = "Organization"
+)]
+#[instrument(skip_all, fields(%org))]
+pub async fn get(
+    State(ctx): State<Arc<Context>>,
+    Path(org): Path<String>,
+) -> Result<impl IntoResponse> {
+    Ok(Json(OrganizationService::get(ctx, &org).await?))
+}
+
+/// Create or update an organization, keeping its members
+#[utoipa::path(
+    operation_id = "put-organization",
+    put, path = "/v1/organizations/{org}",
+    params(
+        ("org" = String, description = "The name of the organization"),
+    ),
+    request_body(
+        content = inline(OrganizationRequest),
+        description = "Organization request",
+        content_type = "application/json"
+    ),
+    responses(
+        (status = 200, description = "Organization saved successfully", body = OrganizationResponse),
+        (status = 400, description = "Invalid organization"),
+        (status = 403, description = "Not an admin key"),
+        (status = 422, description = "Invalid request body, with every invalid field in `errors`")
+    ),
+    security(("api_key" = [])),
+    tag = "Organization"
+)]
+#[instrument(skip_all, fields(%org))]
+pub async fn put(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(org): Path<String>,
+    ValidatedJson(req): ValidatedJson<OrganizationRequest>,
+) -> Result<impl IntoResponse> {
+    Ok(Json(OrganizationService::put(ctx, &key, &org, req).await?))
+}
+
+/// Delete an organization
+#[utoipa::path(
+    operation_id = "delete-organization",
+    delete, path = "/v1/organizations/{org}",
+    params(
+        ("org" = String, description = "The name of the organization"),
+    ),
+    responses(
+        (status = 204, description = "Organization deleted successfully"),
+        (status = 403, description = "Not an admin key"),
+        (status = 404, description = "Organization not found")
+    ),
+    security(("api_key" = [])),
+    tag = "Organization"
+)]
+#[instrument(skip_all, fields(%org))]
+pub async fn delete(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(org): Path<String>,
+) -> Result<impl IntoResponse> {
+    OrganizationService::delete(ctx, &key, &org).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Add a member to an organization, or change their role
+#[utoipa::path(
+    operation_id = "put-organization-member",
+    put, path = "/v1/organizations/{org}/members/{username}",
+    params(
+        ("org" = String, description = "The name of the organization"),
+        ("username" = String, description = "The GitHub username of the member"),
+    ),
+    request_body(
+        content = inline(MemberRequest),
+        description = "Member request",
+        content_type = "application/json"
+    ),
+    responses(
+        (status = 200, description = "Member saved successfully", body = OrganizationResponse),
+        (status = 400, description = "Invalid member"),
+        (status = 403, description = "Not an admin key"),
+        (status = 404, description = "Organization not found"),
+        (status = 422, description = "Invalid request body, with every invalid field in `errors`")
+    ),
+    security(("api_key" = [])),
+    tag = "Organization"
+)]
+#[instrument(skip_all, fields(%org, %username))]
+pub async fn put_member(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path((org, username)): Path<(String, String)>,
+    ValidatedJson(req): ValidatedJson<MemberRequest>,
+) -> Result<impl IntoResponse> {
+    Ok(Json(OrganizationService::put_member(ctx, &key, &org, &username, req).await?))
+}
+
+/// Remove a member from an organization
+#[utoipa::path(
+    operation_id = "delete-organization-member",
+    delete, path = "/v1/organizations/{org}/members/{username}",
+    params(
+        ("org" = String, description = "The name of the organization"),
+        ("username" = String, description = "The GitHub username of the member"),
+    ),
+    responses(
+        (status = 200, description = "Member removed successfully", body = OrganizationResponse),
+        (status = 400, description = "Not a member, or the last owner"),
+        (status = 403, description = "Not an admin key"),
+        (status = 404, description = "Organization not found")
+    ),
+    security(("api_key" = [])),
+    tag = "Organization"
+)]
+#[instrument(skip_all, fields(%org, %username))]
+pub async fn delete_member(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path((org, username)): Path<(String, String)>,
+) -> Result<impl IntoResponse> {
+    Ok(Json(OrganizationService::delete_member(ctx, &key, &org, &username).await?))
+}
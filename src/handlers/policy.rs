@@ -0,0 +1,108 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! The Dependency policy Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use tracing::instrument;
+
+use crate::{
+    context::Context,
+    errors::Result,
+    middlewares::ratelimit::ClientKey,
+    requests::{policy::DependencyPolicyRequest, validation::ValidatedJson},
+    responses::policy::DependencyPolicyResponse,
+    services::policy::PolicyService,
+};
+
+/// Get the dependency policy of an owner
+#[utoipa::path(
+    operation_id = "get-dependency-policy",
+    get, path = "/v1/owners/{owner}/dependency-policy",
+    params(
+        ("owner" = String, description = "The owner of projects"),
+    ),
+    responses(
+        (status = 200, description = "Policy retrieved successfully", body = DependencyPolicyResponse)
+    ),
+    tag = "Policy"
+)]
+#[instrument(skip_all, fields(%owner))]
+pub async fn get(
+    State(ctx): State<Arc<Context>>,
+    Path(owner): Path<String>,
+) -> Result<impl IntoResponse> {
+    Ok(Json(PolicyService::get(ctx, &owner).await?))
+}
+
+/// Replace the dependency policy of an owner, applied from the next analysis on
+#[utoipa::path(
+    operation_id = "put-dependency-policy",
+    put, path = "/v1/owners/{owner}/dependency-policy",
+    params(
+        ("owner" = String, description = "The owner of projects"),
+    ),
+    request_body(
+        content = inline(DependencyPolicyRequest),
+        description = "Dependency policy request",
+        content_type = "application/json"
+    ),
+    responses(
+        (status = 200, description = "Policy saved successfully", body = DependencyPolicyResponse),
+        (status = 400, description = "Invalid policy"),
+        (status = 403, description = "Not an admin key"),
+        (status = 422, description = "Invalid request body, with every invalid field in `errors`")
+    ),
+    security(("api_key" = [])),
+    tag = "Policy"
+)]
+#[instrument(skip_all, fields(%owner))]
+pub async fn put(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(owner): Path<String>,
+    ValidatedJson(req): ValidatedJson<DependencyPolicyRequest>,
+) -> Result<impl IntoResponse> {
+    Ok(Json(PolicyService::put(ctx, &key, &owner, req).await?))
+}
+
+/// Remove the dependency policy of an owner
+#[utoipa::path(
+    operation_id = "delete-dependency-policy",
+    delete, path = "/v1/owners/{owner}/dependency-policy",
+    params(
+        ("owner" = String, description = "The owner of projects"),
+    ),
+    responses(
+        (status = 204, description = "Policy removed successfully"),
+        (status = 403, description = "Not an admin key")
+    ),
+    security(("api_key" = [])),
+    tag = "Policy"
+)]
+#[instrument(skip_all, fields(%owner))]
+pub async fn delete(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(owner): Path<String>,
+) -> Result<impl IntoResponse> {
+    PolicyService::delete(ctx, &key, &owner).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,54 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The Pool Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    context::Context, errors::Result, middlewares::ratelimit::ClientKey,
+    requests::organization::Role, responses::pool::PoolResponse,
+    services::workflow::WorkflowService,
+};
+
+/// Get the budget released by the failed allocations of the workflow
+#[utoipa::path(
+    operation_id = "get-workflow-pool",
+    get, path = "/v1/workflows/{id}/pool",
+    params(
+        ("id" = Uuid, description = "The id of workflow"),
+    ),
+    responses(
+        (status = 200, description = "Pool retrieved successfully", body = PoolResponse),
+        (status = 404, description = "Workflow not found")
+    ),
+    tag = "Pool"
+)]
+#[instrument(skip_all, fields(workflow_id = %id))]
+pub async fn get(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    WorkflowService::authorize(&ctx, &key, id, Role::Member)?;
+    Ok(Json(ctx.pool.get(id)))
+}
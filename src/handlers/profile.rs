@@ -0,0 +1,125 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! The Ranking profile Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use tracing::instrument;
+
+use crate::{
+    context::Context,
+    errors::Result,
+    middlewares::ratelimit::ClientKey,
+    requests::{profile::Weights, validation::ValidatedJson},
+    responses::profile::WeightProfileResponse,
+    services::profile::ProfileService,
+};
+
+/// List the ranking weight profiles workflows can select
+#[utoipa::path(
+    operation_id = "list-ranking-profiles",
+    get, path = "/v1/ranking-profiles",
+    responses(
+        (status = 200, description = "Profiles retrieved successfully",
+            body = Vec<WeightProfileResponse>)
+    ),
+    tag = "Profile"
+)]
+pub async fn list(State(ctx): State<Arc<Context>>) -> Result<impl IntoResponse> {
+    Ok(Json(ProfileService::list(ctx).await?))
+}
+
+/// Get a ranking weight profile
+#[utoipa::path(
+    operation_id = "get-ranking-profile",
+    get, path = "/v1/ranking-profiles/{name}",
+    params(
+        ("name" = String, description = "The name of the profile"),
+    ),
+    responses(
+        (status = 200, description = "Profile retrieved successfully", body = WeightProfileResponse),
+        (status = 404, description = "Profile not found")
+    ),
+    tag = "Profile"
+)]
+#[instrument(skip_all, fields(%name))]
+pub async fn get(
+    State(ctx): State<Arc<Context>>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse> {
+    Ok(Json(ProfileService::get(ctx, &name).await?))
+}
+
+/// Create or replace a ranking weight profile
+#[utoipa::path(
+    operation_id = "put-ranking-profile",
+    put, path = "/v1/admin/ranking-profiles/{name}",
+    params(
+        ("name" = String, description = "The name of the profile"),
+    ),
+    request_body(
+        content = inline(Weights),
+        description = "The weights of the ranking signals",
+        content_type = "application/json"
+    ),
+    responses(
+        (status = 200, description = "Profile saved successfully", body = WeightProfileResponse),
+        (status = 400, description = "Invalid profile"),
+        (status = 403, description = "Not an admin key"),
+        (status = 422, description = "Invalid request body, with every invalid field in `errors`")
+    ),
+    security(("api_key" = [])),
+    tag = "Profile"
+)]
+#[instrument(skip_all, fields(%name))]
+pub async fn put(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(name): Path<String>,
+    ValidatedJson(req): ValidatedJson<Weights>,
+) -> Result<impl IntoResponse> {
+    Ok(Json(ProfileService::put(ctx, &key, &name, req).await?))
+}
+
+/// Delete a ranking weight profile
+#[utoipa::path(
+    operation_id = "delete-ranking-profile",
+    delete, path = "/v1/admin/ranking-profiles/{name}",
+    params(
+        ("name" = String, description = "The name of the profile"),
+    ),
+    responses(
+        (status = 204, description = "Profile deleted successfully"),
+        (status = 400, description = "The profile is built in"),
+        (status = 403, description = "Not an admin key"),
+        (status = 404, description = "Profile not found")
+    ),
+    security(("api_key" = [])),
+    tag = "Profile"
+)]
+#[instrument(skip_all, fields(%name))]
+pub async fn delete(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse> {
+    ProfileService::delete(ctx, &key, &name).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -18,13 +18,14 @@ use std::sync::Arc;
 
 use axum::{
     extract::{Path, State},
-    http::StatusCode,
+    http::header::ETAG,
     response::IntoResponse,
     Json,
 };
+use tracing::instrument;
 
 use crate::{
-    context::Context, errors::Result, responses::project::ProjectResponse,
+    context::Context, errors::Result, middlewares::etag, responses::project::ProjectResponse,
     services::project::ProjectService,
 };
 
@@ -38,14 +39,23 @@ use crate::{
     ),
     responses(
         (status = 200, description = "Project retrieved successfully", body = ProjectResponse),
+        (status = 304, description = "Project analysis not modified since the given ETag"),
         (status = 404, description = "Project not found"),
         (status = 500, description = "Failed to get project")
     ),
     tag = "Project"
 )]
+#[instrument(skip_all, fields(%owner, %name))]
 pub async fn get(
     State(ctx): State<Arc<Context>>,
     Path((owner, name)): Path<(String, String)>,
 ) -> Result<impl IntoResponse> {
-    Ok((StatusCode::OK, Json(ProjectService::get(ctx, &owner, &name).await?)))
+    let project = ProjectService::get(ctx, &owner, &name).await?;
+    let etag = project.commit.as_deref().and_then(etag::for_commit);
+
+    let mut res = Json(project).into_response();
+    if let Some(etag) = etag {
+        res.headers_mut().insert(ETAG, etag);
+    }
+    Ok(res)
 }
@@ -0,0 +1,40 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The Quota Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Extension, Json};
+
+use crate::{
+    context::Context, errors::Result, middlewares::ratelimit::ClientKey,
+    responses::quota::LimitsResponse, services::quota::QuotaService,
+};
+
+/// Get the plan limits of the current client, with its current usage.
+#[utoipa::path(
+    operation_id = "get-limits",
+    get, path = "/v1/limits",
+    responses(
+        (status = 200, description = "Limits retrieved successfully", body = LimitsResponse)
+    ),
+    tag = "Quota"
+)]
+pub async fn get(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+) -> Result<impl IntoResponse> {
+    Ok((StatusCode::OK, Json(QuotaService::get(ctx, &key).await?)))
+}
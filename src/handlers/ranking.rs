@@ -0,0 +1,106 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! The Ranking Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    errors::Result,
+    middlewares::trace::RequestId,
+    requests::{fields::FieldsParams, list::ListParams},
+    responses::{
+        dependency::Ecosystem,
+        list::ListResponse,
+        ranking::{EcosystemRankingResponse, GlobalRankingResponse},
+    },
+    services::ranking::RankingService,
+};
+
+#[derive(Debug, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RankingParams {
+    /// The id of the ranking snapshot, the latest one by default.
+    pub snapshot: Option<Uuid>,
+}
+
+/// Get the global ranking of the packages across all analyzed projects
+#[utoipa::path(
+    operation_id = "get-global-rankings",
+    get, path = "/v1/rankings/global",
+    params(
+        RankingParams,
+        ListParams,
+        FieldsParams,
+    ),
+    responses(
+        (status = 200, description = "Rankings retrieved successfully",
+            body = ListResponse<GlobalRankingResponse>),
+        (status = 404, description = "Snapshot not found")
+    ),
+    tag = "Ranking"
+)]
+#[instrument(skip_all)]
+pub async fn global(
+    State(ctx): State<Arc<Context>>,
+    Extension(request_id): Extension<RequestId>,
+    Query(ranking): Query<RankingParams>,
+    Query(params): Query<ListParams>,
+    Query(fields): Query<FieldsParams>,
+) -> Result<impl IntoResponse> {
+    params.validate()?;
+
+    let (items, pagination) = params.apply(RankingService::global(ctx, ranking.snapshot).await?)?;
+    Ok(Json(ListResponse::new(fields.select(&items), pagination, &request_id)))
+}
+
+/// Get the packages of an ecosystem ranked by their scores across all projects, with
+/// the funding they received, eg. sort by `-underfunding` for critical yet underfunded ones
+#[utoipa::path(
+    operation_id = "get-ecosystem-rankings",
+    get, path = "/v1/rankings/ecosystems/{ecosystem}",
+    params(
+        ("ecosystem" = Ecosystem, Path, description = "The package ecosystem, eg. cargo"),
+        ListParams,
+        FieldsParams,
+    ),
+    responses(
+        (status = 200, description = "Rankings retrieved successfully",
+            body = ListResponse<EcosystemRankingResponse>)
+    ),
+    tag = "Ranking"
+)]
+#[instrument(skip_all, fields(%ecosystem))]
+pub async fn ecosystem(
+    State(ctx): State<Arc<Context>>,
+    Extension(request_id): Extension<RequestId>,
+    Path(ecosystem): Path<Ecosystem>,
+    Query(params): Query<ListParams>,
+    Query(fields): Query<FieldsParams>,
+) -> Result<impl IntoResponse> {
+    params.validate()?;
+
+    let (items, pagination) = params.apply(RankingService::ecosystem(ctx, ecosystem).await?)?;
+    Ok(Json(ListResponse::new(fields.select(&items), pagination, &request_id)))
+}
@@ -0,0 +1,33 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The Rate Limit Service Handlers.
+
+use axum::{http::StatusCode, response::IntoResponse, Extension, Json};
+
+use crate::{errors::Result, middlewares::ratelimit::RateLimitState};
+
+/// Get the rate limit state of the current client.
+#[utoipa::path(
+    operation_id = "get-rate-limit",
+    get, path = "/v1/rate-limit",
+    responses(
+        (status = 200, description = "Rate limit state retrieved successfully",
+            body = RateLimitState)
+    ),
+    tag = "RateLimit"
+)]
+pub async fn get(Extension(state): Extension<RateLimitState>) -> Result<impl IntoResponse> {
+    Ok((StatusCode::OK, Json(state)))
+}
@@ -0,0 +1,85 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The Report Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use serde::Deserialize;
+use tracing::instrument;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    errors::Result,
+    middlewares::ratelimit::ClientKey,
+    requests::organization::Role,
+    services::{report::ReportService, workflow::WorkflowService},
+};
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReportParams {
+    /// The report format, `html` (default) or `pdf`.
+    pub format: Option<String>,
+}
+
+/// Get a shareable report of the workflow
+#[utoipa::path(
+    operation_id = "get-workflow-report",
+    get, path = "/v1/workflows/{id}/report",
+    params(
+        ("id" = Uuid, description = "The id of workflow"),
+        ReportParams,
+    ),
+    responses(
+        (status = 200, description = "Report generated successfully",
+            content((String = "text/html"), (Vec<u8> = "application/pdf"))),
+        (status = 404, description = "Workflow or snapshot not found")
+    ),
+    tag = "Report"
+)]
+#[instrument(skip_all, fields(workflow_id = %id))]
+pub async fn get(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<ReportParams>,
+) -> Result<Response> {
+    WorkflowService::authorize(&ctx, &key, id, Role::Member)?;
+    let report = ReportService::generate(ctx, id).await?;
+
+    match params.format.as_deref() {
+        Some("pdf") => Ok((
+            [
+                (header::CONTENT_TYPE, "application/pdf".to_string()),
+                (header::CONTENT_DISPOSITION, format!("attachment; filename=\"report-{id}.pdf\"")),
+            ],
+            ReportService::to_pdf(&report),
+        )
+            .into_response()),
+        _ => Ok((
+            [(header::CONTENT_TYPE, "text/html; charset=utf-8".to_string())],
+            ReportService::to_html(&report),
+        )
+            .into_response()),
+    }
+}
@@ -0,0 +1,65 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The Sign Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use tracing::instrument;
+
+use crate::{
+    context::Context,
+    contracts::types::Id,
+    errors::Result,
+    requests::{sign::SignRequest, validation::ValidatedJson},
+    responses::sign::SignResponse,
+    services::sign::SignService,
+};
+
+/// Record the signature answering an inquire, once verified by the signer account
+#[utoipa::path(
+    operation_id = "create-sign",
+    post, path = "/v1/inquires/{id}/signs",
+    params(
+        ("id" = String, description = "The id of inquire"),
+    ),
+    request_body(
+        content = inline(SignRequest),
+        description = "Sign request",
+        content_type = "application/json"
+    ),
+    responses(
+        (status = 201, description = "Signature recorded successfully", body = SignResponse),
+        (status = 403, description = "Signer not an approver of the organization owning the workflow"),
+        (status = 404, description = "Inquire not found"),
+        (status = 422, description = "Invalid request body, or a signature the signer account rejects"),
+        (status = 502, description = "Failed to record the signature")
+    ),
+    tag = "Sign"
+)]
+#[instrument(skip_all, fields(inquire_id = %id))]
+pub async fn create(
+    State(ctx): State<Arc<Context>>,
+    Path(id): Path<Id>,
+    ValidatedJson(req): ValidatedJson<SignRequest>,
+) -> Result<impl IntoResponse> {
+    let sign = SignService::record(&ctx, id, req.signer, req.signature).await?;
+    Ok((StatusCode::CREATED, Json(sign)))
+}
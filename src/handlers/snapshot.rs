@@ -0,0 +1,126 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! The Snapshot Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    errors::Result,
+    middlewares::trace::RequestId,
+    requests::{fields::FieldsParams, list::ListParams},
+    responses::{
+        list::ListResponse,
+        snapshot::{RankingDiffResponse, SnapshotDetailResponse, SnapshotResponse},
+    },
+    services::snapshot::SnapshotService,
+};
+
+/// Get the ranking snapshots of the project, the latest first
+#[utoipa::path(
+    operation_id = "get-snapshots-list",
+    get, path = "/v1/projects/{owner}/{name}/snapshots",
+    params(
+        ("owner" = String, description = "The owner of project"),
+        ("name" = String, description = "The name of project"),
+        ListParams,
+        FieldsParams,
+    ),
+    responses(
+        (status = 200, description = "Snapshots retrieved successfully",
+            body = ListResponse<SnapshotResponse>)
+    ),
+    tag = "Snapshot"
+)]
+#[instrument(skip_all, fields(%owner, %name))]
+pub async fn list(
+    State(ctx): State<Arc<Context>>,
+    Extension(request_id): Extension<RequestId>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(params): Query<ListParams>,
+    Query(fields): Query<FieldsParams>,
+) -> Result<impl IntoResponse> {
+    params.validate()?;
+
+    let (items, pagination) = params.apply(SnapshotService::list(ctx, &owner, &name).await?)?;
+    Ok(Json(ListResponse::new(fields.select(&items), pagination, &request_id)))
+}
+
+/// Get a ranking snapshot of the project, with its ranked dependencies
+#[utoipa::path(
+    operation_id = "get-snapshot-detail",
+    get, path = "/v1/projects/{owner}/{name}/snapshots/{snapshot_id}",
+    params(
+        ("owner" = String, description = "The owner of project"),
+        ("name" = String, description = "The name of project"),
+        ("snapshot_id" = Uuid, description = "The id of the snapshot")
+    ),
+    responses(
+        (status = 200, description = "Snapshot retrieved successfully",
+            body = SnapshotDetailResponse),
+        (status = 404, description = "Snapshot not found")
+    ),
+    tag = "Snapshot"
+)]
+#[instrument(skip_all, fields(%owner, %name, %snapshot_id))]
+pub async fn get(
+    State(ctx): State<Arc<Context>>,
+    Path((owner, name, snapshot_id)): Path<(String, String, Uuid)>,
+) -> Result<impl IntoResponse> {
+    Ok(Json(SnapshotService::get(ctx, &owner, &name, snapshot_id).await?))
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DiffParams {
+    /// The snapshot to compare from, its id or the hash of the commit it analyzed.
+    pub from: String,
+    /// The snapshot to compare to, its id or the hash of the commit it analyzed.
+    pub to: String,
+}
+
+/// Compare the ranking of the project between two commits or snapshots
+#[utoipa::path(
+    operation_id = "get-ranking-diff",
+    get, path = "/v1/projects/{owner}/{name}/ranking-diff",
+    params(
+        ("owner" = String, description = "The owner of project"),
+        ("name" = String, description = "The name of project"),
+        DiffParams,
+    ),
+    responses(
+        (status = 200, description = "Ranking diff retrieved successfully",
+            body = RankingDiffResponse),
+        (status = 404, description = "Snapshot not found")
+    ),
+    tag = "Snapshot"
+)]
+#[instrument(skip_all, fields(%owner, %name, from = %params.from, to = %params.to))]
+pub async fn diff(
+    State(ctx): State<Arc<Context>>,
+    Path((owner, name)): Path<(String, String)>,
+    Query(params): Query<DiffParams>,
+) -> Result<impl IntoResponse> {
+    Ok(Json(SnapshotService::diff(ctx, &owner, &name, &params.from, &params.to).await?))
+}
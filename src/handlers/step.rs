@@ -0,0 +1,104 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The Step Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::{
+    context::Context,
+    contracts::types::{Id, Owner},
+    errors::Result,
+    responses::step::{StepResponse, TransactionChainResponse},
+    services::step::StepService,
+};
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StepParams {
+    /// The transaction attested by the steps, eg. a payout transaction.
+    pub tx_hash: Option<String>,
+    /// The name or repository URL of the dependency the steps were recorded for.
+    pub dependency: Option<String>,
+    /// The owner of the workflow the steps were recorded for, with `workflow_id`.
+    pub github_owner: Option<String>,
+    /// The id of the workflow on chain the steps were recorded for, with `github_owner`.
+    pub workflow_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ChainParams {
+    /// The owner of the workflow on chain.
+    #[param(value_type = String)]
+    pub github_owner: Owner,
+    /// The id of the workflow on chain.
+    #[param(value_type = String)]
+    pub workflow_id: Id,
+    /// The index of the dependency in the workflow.
+    #[param(value_type = String)]
+    pub dependency_index: Id,
+}
+
+/// Find the steps recorded on chain, attesting the funding of dependencies
+#[utoipa::path(
+    operation_id = "list-steps",
+    get, path = "/v1/attestations",
+    params(StepParams),
+    responses(
+        (status = 200, description = "Steps retrieved successfully", body = Vec<StepResponse>),
+        (status = 400, description = "Neither a transaction, a dependency nor a workflow")
+    ),
+    tag = "Step"
+)]
+pub async fn list(
+    State(ctx): State<Arc<Context>>,
+    Query(params): Query<StepParams>,
+) -> Result<impl IntoResponse> {
+    let workflow = params.github_owner.as_deref().zip(params.workflow_id.as_deref());
+    Ok(Json(
+        StepService::find(ctx, params.tx_hash.as_deref(), params.dependency.as_deref(), workflow)
+            .await?,
+    ))
+}
+
+/// Read the transaction chain of a dependency, verifying each step links to the previous one
+#[utoipa::path(
+    operation_id = "get-transaction-chain",
+    get, path = "/v1/attestations/chain",
+    params(ChainParams),
+    responses(
+        (status = 200, description = "Transaction chain retrieved successfully", body = TransactionChainResponse),
+        (status = 400, description = "Missing owner, workflow or dependency"),
+        (status = 502, description = "Failed to read the transaction chain")
+    ),
+    tag = "Step"
+)]
+pub async fn chain(
+    State(ctx): State<Arc<Context>>,
+    Query(params): Query<ChainParams>,
+) -> Result<impl IntoResponse> {
+    Ok(Json(
+        StepService::chain(&ctx, params.github_owner, params.workflow_id, params.dependency_index)
+            .await?,
+    ))
+}
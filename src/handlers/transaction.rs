@@ -0,0 +1,55 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The Transaction Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+
+use crate::{
+    context::Context,
+    errors::Result,
+    middlewares::{ratelimit::ClientKey, trace::RequestId},
+    requests::list::ListParams,
+    responses::{list::ListResponse, transaction::TransactionResponse},
+    services::transaction::TransactionService,
+};
+
+/// List the transactions sent on chain by the backend, for auditing
+#[utoipa::path(
+    operation_id = "list-transactions",
+    get, path = "/v1/transactions",
+    params(ListParams),
+    responses(
+        (status = 200, description = "Transactions retrieved successfully",
+            body = ListResponse<TransactionResponse>),
+        (status = 400, description = "Invalid list parameters"),
+        (status = 403, description = "Not an admin key")
+    ),
+    security(("api_key" = [])),
+    tag = "Transaction"
+)]
+pub async fn list(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Extension(request_id): Extension<RequestId>,
+    Query(params): Query<ListParams>,
+) -> Result<impl IntoResponse> {
+    Ok(Json(TransactionService::list(ctx, &key, &params, &request_id).await?))
+}
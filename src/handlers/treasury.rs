@@ -0,0 +1,52 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! The Treasury Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{extract::State, response::IntoResponse, Json};
+use tracing::instrument;
+
+use crate::{
+    context::Context,
+    errors::Result,
+    requests::{validation::ValidatedJson, workflow::Budget},
+    responses::treasury::TreasuryResponse,
+    services::treasury::TreasuryService,
+};
+
+/// Check the treasury can fund a budget, with funding instructions otherwise.
+#[utoipa::path(
+    operation_id = "preflight-treasury",
+    post, path = "/v1/treasury/preflight",
+    request_body(
+        content = inline(Budget),
+        description = "The budget to fund",
+        content_type = "application/json"
+    ),
+    responses(
+        (status = 200, description = "The treasury can fund the budget", body = TreasuryResponse),
+        (status = 402, description = "The treasury cannot fund the budget"),
+        (status = 422, description = "Invalid request body, with every invalid field in `errors`"),
+        (status = 503, description = "Failed to check the treasury")
+    ),
+    tag = "Treasury"
+)]
+#[instrument(skip_all, fields(token = %req.token, amount = %req.amount))]
+pub async fn preflight(
+    State(ctx): State<Arc<Context>>,
+    ValidatedJson(req): ValidatedJson<Budget>,
+) -> Result<impl IntoResponse> {
+    Ok(Json(TreasuryService::preflight(ctx, &req).await?))
+}
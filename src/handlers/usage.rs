@@ -0,0 +1,61 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The Usage Service Handlers.
+
+use std::sync::Arc;
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Extension, Json};
+
+use crate::{
+    context::Context,
+    errors::Result,
+    middlewares::ratelimit::ClientKey,
+    responses::usage::{UsageAggregateResponse, UsageResponse},
+    services::usage::UsageService,
+};
+
+/// Get the API usage of the current client.
+#[utoipa::path(
+    operation_id = "get-usage",
+    get, path = "/v1/usage",
+    responses(
+        (status = 200, description = "Usage retrieved successfully", body = UsageResponse)
+    ),
+    tag = "Usage"
+)]
+pub async fn get(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+) -> Result<impl IntoResponse> {
+    Ok((StatusCode::OK, Json(UsageService::get(ctx, &key).await?)))
+}
+
+/// Get the API usage of all clients.
+#[utoipa::path(
+    operation_id = "get-usage-aggregate",
+    get, path = "/v1/admin/usage",
+    responses(
+        (status = 200, description = "Usage retrieved successfully", body = UsageAggregateResponse),
+        (status = 403, description = "Not an admin key")
+    ),
+    security(("api_key" = [])),
+    tag = "Usage"
+)]
+pub async fn aggregate(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+) -> Result<impl IntoResponse> {
+    Ok((StatusCode::OK, Json(UsageService::aggregate(ctx, &key).await?)))
+}
@@ -20,11 +20,18 @@ use axum::{
     extract::{Path, State},
     http::StatusCode,
     response::IntoResponse,
-    Json,
+    Extension,
 };
+use tracing::instrument;
 use uuid::Uuid;
 
-use crate::{context::Context, errors::Result, requests::wallet::WalletAddressRequest};
+use crate::{
+    context::Context,
+    errors::Result,
+    middlewares::ratelimit::ClientKey,
+    requests::{validation::ValidatedJson, wallet::WalletAddressRequest},
+    services::workflow::WorkflowService,
+};
 
 /// Bind wallet address to workflow.
 #[utoipa::path(
@@ -40,16 +47,21 @@ use crate::{context::Context, errors::Result, requests::wallet::WalletAddressReq
     ),
     responses(
         (status = 204, description = "Wallet address bound successfully"),
+        (status = 400, description = "Not the treasury of the organization owning the workflow"),
         (status = 404, description = "Workflow not found"),
+        (status = 422, description = "Invalid request body, with every invalid field in `errors`"),
         (status = 500, description = "Failed to bind wallet address")
     ),
     tag = "Wallet"
 )]
+#[instrument(skip_all, fields(workflow_id = %id))]
 pub async fn bind(
-    State(_ctx): State<Arc<Context>>,
-    Path(_id): Path<Uuid>,
-    Json(_req): Json<WalletAddressRequest>,
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(req): ValidatedJson<WalletAddressRequest>,
 ) -> Result<impl IntoResponse> {
+    WorkflowService::set_wallet_address(&ctx, &key, id, Some(req.address))?;
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -67,9 +79,12 @@ pub async fn bind(
     ),
     tag = "Wallet"
 )]
+#[instrument(skip_all, fields(workflow_id = %id))]
 pub async fn unbind(
-    State(_ctx): State<Arc<Context>>,
-    Path(_id): Path<Uuid>,
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(id): Path<Uuid>,
 ) -> Result<impl IntoResponse> {
+    WorkflowService::set_wallet_address(&ctx, &key, id, None)?;
     Ok(StatusCode::NO_CONTENT)
 }
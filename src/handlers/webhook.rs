@@ -0,0 +1,77 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension, Json,
+};
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    errors::Result,
+    middlewares::ratelimit::ClientKey,
+    requests::{validation::ValidatedJson, webhook::WebhookRequest},
+    responses::webhook::WebhookResponse,
+    services::webhook::WebhookService,
+};
+
+/// Register a webhook, posted the events of the workflows signed with its secret
+#[utoipa::path(
+    operation_id = "create-webhook",
+    post, path = "/v1/webhooks",
+    request_body(
+        content = inline(WebhookRequest),
+        description = "Create webhook request",
+        content_type = "application/json"
+    ),
+    responses(
+        (status = 201, description = "Webhook registered successfully", body = WebhookResponse),
+        (status = 403, description = "The API key has too many webhooks"),
+        (status = 422, description = "Invalid request body, with every invalid field in `errors`")
+    ),
+    tag = "Webhook"
+)]
+pub async fn create(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    ValidatedJson(req): ValidatedJson<WebhookRequest>,
+) -> Result<impl IntoResponse> {
+    Ok((StatusCode::CREATED, Json(WebhookService::create(ctx, &key, req).await?)))
+}
+
+/// Delete a webhook, dropping its pending deliveries
+#[utoipa::path(
+    operation_id = "delete-webhook",
+    delete, path = "/v1/webhooks/{id}",
+    params(
+        ("id" = Uuid, description = "The id of the webhook"),
+    ),
+    responses(
+        (status = 204, description = "Webhook deleted successfully"),
+        (status = 404, description = "Webhook not found")
+    ),
+    tag = "Webhook"
+)]
+pub async fn delete(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    WebhookService::delete(ctx, &key, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -17,16 +17,28 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
-    Json,
+    Extension, Json,
 };
+use tracing::instrument;
 use uuid::Uuid;
 
 use crate::{
-    context::Context, errors::Result, requests::workflow::CreateWorkflowRequest,
-    responses::workflow::WorkflowResponse, services::workflow::WorkflowService,
+    context::Context,
+    errors::Result,
+    middlewares::{ratelimit::ClientKey, trace::RequestId},
+    requests::{
+        list::ListParams,
+        validation::ValidatedJson,
+        workflow::{CreateWorkflowRequest, WorkflowListParams},
+    },
+    responses::{
+        list::ListResponse,
+        workflow::{WorkflowResponse, WorkflowSummaryResponse},
+    },
+    services::workflow::WorkflowService,
 };
 
 /// Create a workflow in the current account.
@@ -39,15 +51,25 @@ use crate::{
         content_type = "application/json"
     ),
     responses(
-        (status = 201, description = "Workflow created successfully", body = WorkflowResponse)
+        (status = 201, description = "Workflow created successfully", body = WorkflowResponse),
+        (status = 402, description = "The treasury cannot fund the budget"),
+        (status = 403, description = "The repository exceeds the plan limits"),
+        (status = 422, description = "Invalid request body, with every invalid field in `errors`"),
+        (status = 429, description = "The analysis or concurrent workflow quota is exhausted"),
+        (status = 503, description = "Too many repository downloads in progress, retry later")
     ),
     tag = "Workflow"
 )]
+#[instrument(skip_all, fields(repo = %req.repo))]
 pub async fn create(
     State(ctx): State<Arc<Context>>,
-    Json(req): Json<CreateWorkflowRequest>,
+    Extension(key): Extension<ClientKey>,
+    ValidatedJson(req): ValidatedJson<CreateWorkflowRequest>,
 ) -> Result<impl IntoResponse> {
-    Ok((StatusCode::CREATED, Json(WorkflowService::create(ctx, &req).await?)))
+    let workflow = WorkflowService::create(ctx.clone(), &key, &req).await?;
+    ctx.usage.record_analysis(&key);
+
+    Ok((StatusCode::CREATED, Json(workflow)))
 }
 
 /// Delete a workflow
@@ -64,11 +86,13 @@ pub async fn create(
     ),
     tag = "Workflow"
 )]
+#[instrument(skip_all, fields(workflow_id = %id))]
 pub async fn delete(
     State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
     Path(id): Path<Uuid>,
 ) -> Result<impl IntoResponse> {
-    WorkflowService::delete(ctx, id).await?;
+    WorkflowService::delete(ctx, &key, id).await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
@@ -81,15 +105,87 @@ pub async fn delete(
         ("id" = Uuid, description = "The id of workflow"),
     ),
     responses(
-        (status = 200, description = "Workflow retrieved successfully"),
+        (status = 200, description = "Workflow retrieved successfully", body = WorkflowResponse),
         (status = 404, description = "Workflow not found"),
         (status = 500, description = "Failed to get workflow")
     ),
     tag = "Workflow"
 )]
+#[instrument(skip_all, fields(workflow_id = %id))]
 pub async fn get(
     State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    Ok((StatusCode::OK, Json(WorkflowService::get(ctx, &key, id).await?)))
+}
+
+/// List the workflows, filtered by state, owner or repository
+#[utoipa::path(
+    operation_id = "list-workflows",
+    get, path = "/v1/workflows",
+    params(ListParams, WorkflowListParams),
+    responses(
+        (status = 200, description = "Workflows retrieved successfully",
+            body = ListResponse<WorkflowSummaryResponse>),
+        (status = 400, description = "Invalid list parameters")
+    ),
+    tag = "Workflow"
+)]
+pub async fn list(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Extension(request_id): Extension<RequestId>,
+    Query(params): Query<ListParams>,
+    Query(filters): Query<WorkflowListParams>,
+) -> Result<impl IntoResponse> {
+    Ok(Json(WorkflowService::list(&ctx, &key, &params, &filters, &request_id)?))
+}
+
+/// Cancel a workflow, aborting the job in progress
+#[utoipa::path(
+    operation_id = "cancel-workflow",
+    post, path = "/v1/workflows/{id}/cancel",
+    params(
+        ("id" = Uuid, description = "The id of workflow"),
+    ),
+    responses(
+        (status = 200, description = "Workflow cancelled successfully", body = WorkflowSummaryResponse),
+        (status = 404, description = "Workflow not found"),
+        (status = 409, description = "The workflow is completed or cancelled already")
+    ),
+    tag = "Workflow"
+)]
+#[instrument(skip_all, fields(workflow_id = %id))]
+pub async fn cancel(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    Ok(Json(WorkflowService::cancel(&ctx, &key, id)?))
+}
+
+/// Retry a failed workflow from the state it failed in
+#[utoipa::path(
+    operation_id = "retry-workflow",
+    post, path = "/v1/workflows/{id}/retry",
+    params(
+        ("id" = Uuid, description = "The id of workflow"),
+    ),
+    responses(
+        (status = 200, description = "Workflow retried successfully", body = WorkflowSummaryResponse),
+        (status = 404, description = "Workflow not found"),
+        (status = 409, description = "The workflow did not fail"),
+        (status = 429, description = "The analysis or concurrent workflow quota is exhausted"),
+        (status = 502, description = "The steps of the workflow could not be recorded on chain")
+    ),
+    tag = "Workflow"
+)]
+#[instrument(skip_all, fields(workflow_id = %id))]
+pub async fn retry(
+    State(ctx): State<Arc<Context>>,
+    Extension(key): Extension<ClientKey>,
     Path(id): Path<Uuid>,
 ) -> Result<impl IntoResponse> {
-    Ok((StatusCode::OK, Json(WorkflowService::get(ctx, id).await?)))
+    Ok(Json(WorkflowService::retry(&ctx, &key, id).await?))
 }
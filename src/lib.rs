@@ -17,11 +17,17 @@ pub mod app;
 pub mod config;
 pub mod context;
 pub mod contracts;
+pub mod db;
 pub mod errors;
 pub mod handlers;
 pub mod logger;
+pub mod middlewares;
+pub mod notifiers;
 pub mod requests;
 pub mod responses;
 pub mod routes;
 pub mod services;
+pub mod stores;
 pub mod swagger;
+pub mod telemetry;
+pub mod workers;
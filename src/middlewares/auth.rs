@@ -0,0 +1,53 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! API key authentication.
+//!
+//! The public read-only API is served to anyone, the workflow and allocation API
+//! requires an `X-API-Key`. When API keys are configured only these, and the admin
+//! key, are accepted, otherwise any key identifies its client.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::{config::Config, context::Context, errors::ApiError, middlewares::ratelimit::API_KEY};
+
+/// Whether the key is one of the configured API keys, or the admin key.
+pub fn is_configured(config: &Config, key: &str) -> bool {
+    config.api_keys.iter().any(|accepted| accepted.trim() == key)
+        || config.admin_api_key.as_deref().is_some_and(|admin| !admin.is_empty() && admin == key)
+}
+
+/// Whether the key is accepted, any key is when none are configured.
+pub fn accepts(config: &Config, key: &str) -> bool {
+    config.api_keys.is_empty() || is_configured(config, key)
+}
+
+/// Reject the requests without an accepted API key.
+pub async fn authenticate(State(ctx): State<Arc<Context>>, req: Request, next: Next) -> Response {
+    let key = req.headers().get(API_KEY).and_then(|v| v.to_str().ok()).filter(|k| !k.is_empty());
+    let Some(key) = key else {
+        return ApiError::Unauthorized(format!("missing {API_KEY} header")).into_response();
+    };
+    if !accepts(&ctx.config, key) {
+        return ApiError::Unauthorized(format!("unknown {API_KEY}")).into_response();
+    }
+
+    next.run(req).await
+}
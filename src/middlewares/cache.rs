@@ -0,0 +1,119 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Central `Cache-Control` policy.
+//!
+//! Caching is decided here from the matched route instead of ad hoc in every handler.
+//! A handler may still set its own `Cache-Control` header, which is left untouched.
+
+use axum::{
+    extract::{MatchedPath, Request},
+    http::{header::CACHE_CONTROL, HeaderValue, Method},
+    middleware::Next,
+    response::Response,
+};
+
+/// How long commit-pinned content may be cached, in seconds.
+const IMMUTABLE_MAX_AGE: u64 = 365 * 24 * 60 * 60;
+
+/// How long content derived from a moving reference (eg. a default branch) may be cached.
+const PUBLIC_MAX_AGE: u64 = 5 * 60;
+
+/// How long shared caches, eg. a CDN in front of the public API, may keep such content.
+const SHARED_MAX_AGE: u64 = 60 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CachePolicy {
+    /// Content pinned to a commit, it never changes.
+    Immutable,
+    /// Derived data which changes rarely, cache briefly and revalidate with the ETag.
+    Public,
+    /// Live state like workflow status and allocations.
+    NoStore,
+}
+
+impl CachePolicy {
+    /// Pick the policy for a route template, eg. `/v1/workflows/{id}`.
+    pub fn for_route(route: &str) -> Option<Self> {
+        if route.starts_with("/v1/workflows") ||
+            route.starts_with("/v1/airdrops") ||
+            route.starts_with("/v1/artifacts") ||
+            route.starts_with("/v1/attestations") ||
+            route.starts_with("/v1/terms") ||
+            route.starts_with("/v1/contributors") ||
+            route.starts_with("/v1/owners") ||
+            route.starts_with("/v1/organizations") ||
+            route.starts_with("/v1/github") ||
+            route.starts_with("/v1/health") ||
+            route.starts_with("/v1/metrics")
+        {
+            return Some(Self::NoStore);
+        }
+        if route.ends_with("/badge") ||
+            route.contains("{sha}") ||
+            route.contains("{commit}") ||
+            route.contains("{snapshot_id}") ||
+            route.starts_with("/v1/schemas")
+        {
+            return Some(Self::Immutable);
+        }
+        if route.starts_with("/v1/projects") ||
+            route.starts_with("/v1/credentials") ||
+            route.starts_with("/v1/rankings") ||
+            route.starts_with("/v1/ranking-profiles")
+        {
+            return Some(Self::Public);
+        }
+        None
+    }
+
+    pub fn header_value(&self) -> HeaderValue {
+        match self {
+            Self::Immutable => {
+                HeaderValue::from_str(&format!("public, max-age={IMMUTABLE_MAX_AGE}, immutable"))
+                    .unwrap()
+            }
+            Self::Public => HeaderValue::from_str(&format!(
+                "public, max-age={PUBLIC_MAX_AGE}, s-maxage={SHARED_MAX_AGE}, \
+                 stale-while-revalidate={PUBLIC_MAX_AGE}"
+            ))
+            .unwrap(),
+            Self::NoStore => HeaderValue::from_static("no-store"),
+        }
+    }
+}
+
+/// Attach the `Cache-Control` header matching the route policy.
+pub async fn control(req: Request, next: Next) -> Response {
+    let cacheable = req.method() == Method::GET || req.method() == Method::HEAD;
+    let policy = req
+        .extensions()
+        .get::<MatchedPath>()
+        .and_then(|path| CachePolicy::for_route(path.as_str()));
+
+    let mut res = next.run(req).await;
+    let Some(policy) = policy else {
+        return res;
+    };
+    if res.headers().contains_key(CACHE_CONTROL) {
+        return res;
+    }
+
+    // Never let errors or writes be cached, whatever the route.
+    let success = res.status().is_success() || res.status().is_redirection();
+    let policy = if cacheable && success { policy } else { CachePolicy::NoStore };
+
+    res.headers_mut().insert(CACHE_CONTROL, policy.header_value());
+    res
+}
@@ -0,0 +1,74 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Response compression.
+
+use std::sync::Arc;
+
+use axum::{
+    body::HttpBody,
+    http::{header::CONTENT_TYPE, Response},
+};
+use tower_http::compression::{
+    predicate::{Predicate, SizeAbove},
+    CompressionLayer,
+};
+
+#[derive(Clone, clap::Parser)]
+pub struct CompressionConfig {
+    /// Minimum response body size in bytes before it is compressed
+    #[clap(long, env = "DRK_COMPRESSION_MIN_SIZE", default_value = "1024")]
+    pub compression_min_size: u16,
+
+    /// Comma separated content type prefixes eligible for compression
+    #[clap(
+        long,
+        env = "DRK_COMPRESSION_CONTENT_TYPES",
+        value_delimiter = ',',
+        default_value = "application/json,text/"
+    )]
+    pub compression_content_types: Vec<String>,
+}
+
+/// Only compress responses whose content type starts with one of the allowed prefixes.
+#[derive(Clone)]
+pub struct ContentTypeAllowlist(Arc<[String]>);
+
+impl Predicate for ContentTypeAllowlist {
+    fn should_compress<B>(&self, response: &Response<B>) -> bool
+    where
+        B: HttpBody,
+    {
+        let Some(content_type) = response.headers().get(CONTENT_TYPE) else {
+            return false;
+        };
+        let content_type = content_type.to_str().unwrap_or_default().to_ascii_lowercase();
+
+        self.0.iter().any(|prefix| content_type.starts_with(prefix.as_str()))
+    }
+}
+
+/// Build the gzip/brotli compression layer from the configuration.
+pub fn layer(config: &CompressionConfig) -> CompressionLayer<impl Predicate> {
+    let allowlist = config
+        .compression_content_types
+        .iter()
+        .map(|prefix| prefix.trim().to_ascii_lowercase())
+        .filter(|prefix| !prefix.is_empty())
+        .collect();
+
+    CompressionLayer::new().gzip(true).br(true).compress_when(
+        SizeAbove::new(config.compression_min_size).and(ContentTypeAllowlist(allowlist)),
+    )
+}
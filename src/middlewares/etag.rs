@@ -0,0 +1,100 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Conditional GET support.
+//!
+//! Handlers attach an `ETag` to responses whose content is pinned to a commit, this
+//! middleware answers matching `If-None-Match` requests with `304 Not Modified`. The
+//! dependencies and contributors of a commit may still change, as they are rescored or
+//! their identities merged, so their ETag also covers their content.
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{
+        header::{
+            CONTENT_ENCODING, CONTENT_LANGUAGE, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, ETAG,
+            IF_NONE_MATCH, TRANSFER_ENCODING,
+        },
+        HeaderValue, Method, StatusCode,
+    },
+    middleware::Next,
+    response::Response,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// Build the strong ETag of content derived from the given commit hash.
+pub fn for_commit(commit: &str) -> Option<HeaderValue> {
+    HeaderValue::from_str(&format!("\"{commit}\"")).ok()
+}
+
+/// Build the strong ETag of a page of items derived from the given commit hash, changing
+/// with the items and the page.
+pub fn for_content<T: Serialize>(commit: &str, items: &T) -> Option<HeaderValue> {
+    let content = serde_json::to_vec(items).ok()?;
+    let digest = hex::encode(Sha256::digest(content));
+    HeaderValue::from_str(&format!("\"{commit}-{}\"", &digest[..16])).ok()
+}
+
+/// Answer conditional GET requests with `304 Not Modified` when the ETag matches.
+pub async fn conditional(req: Request, next: Next) -> Response {
+    if req.method() != Method::GET && req.method() != Method::HEAD {
+        return next.run(req).await;
+    }
+
+    let if_none_match = req.headers().get(IF_NONE_MATCH).cloned();
+    let res = next.run(req).await;
+
+    let (Some(if_none_match), Some(etag)) = (if_none_match, res.headers().get(ETAG)) else {
+        return res;
+    };
+    if res.status() != StatusCode::OK || !matches(&if_none_match, etag) {
+        return res;
+    }
+
+    // The other headers, like the rate limits, still apply to a 304, only its body and
+    // the headers describing it go.
+    let (mut parts, _) = res.into_parts();
+    parts.status = StatusCode::NOT_MODIFIED;
+    let entity = [
+        CONTENT_TYPE,
+        CONTENT_LENGTH,
+        CONTENT_ENCODING,
+        CONTENT_LANGUAGE,
+        CONTENT_RANGE,
+        TRANSFER_ENCODING,
+    ];
+    for name in entity {
+        parts.headers.remove(name);
+    }
+    Response::from_parts(parts, Body::empty())
+}
+
+/// Weak comparison of an `If-None-Match` header value against an ETag, see RFC 9110 13.1.2.
+fn matches(if_none_match: &HeaderValue, etag: &HeaderValue) -> bool {
+    let (Ok(candidates), Ok(etag)) = (if_none_match.to_str(), etag.to_str()) else {
+        return false;
+    };
+    let etag = opaque(etag);
+
+    candidates
+        .split(',')
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || opaque(candidate) == etag)
+}
+
+fn opaque(tag: &str) -> &str {
+    tag.trim().trim_start_matches("W/")
+}
@@ -0,0 +1,269 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fixed window rate limiting.
+//!
+//! Clients are identified by their `X-API-Key` header once it is accepted, falling back
+//! to the peer address. Every response carries the `X-RateLimit-*` headers so clients can
+//! throttle themselves before hitting `429 Too Many Requests`.
+//!
+//! The public read-only API has its own, stricter, limits since anyone can call it.
+
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use utoipa::ToSchema;
+
+use crate::{config::Config, context::Context, errors::ApiError, middlewares::auth};
+
+/// The header carrying the API key of the client.
+pub const API_KEY: &str = "x-api-key";
+
+const LIMIT: HeaderName = HeaderName::from_static("x-ratelimit-limit");
+const REMAINING: HeaderName = HeaderName::from_static("x-ratelimit-remaining");
+const RESET: HeaderName = HeaderName::from_static("x-ratelimit-reset");
+
+/// Paths which report the rate limit state without consuming it.
+const EXEMPT_PATHS: &[&str] = &["/v1/rate-limit"];
+
+#[derive(Clone, clap::Parser)]
+pub struct RateLimitConfig {
+    /// Maximum number of requests per client in a window
+    #[clap(long, env = "DRK_RATE_LIMIT", default_value = "600")]
+    pub rate_limit: u32,
+
+    /// Length of the rate limit window in seconds
+    #[clap(long, env = "DRK_RATE_LIMIT_WINDOW", default_value = "60")]
+    pub rate_limit_window: u64,
+
+    /// Maximum number of requests per client in a window of the public API
+    #[clap(long, env = "DRK_PUBLIC_RATE_LIMIT", default_value = "120")]
+    pub public_rate_limit: u32,
+
+    /// Length of the rate limit window of the public API in seconds
+    #[clap(long, env = "DRK_PUBLIC_RATE_LIMIT_WINDOW", default_value = "60")]
+    pub public_rate_limit_window: u64,
+}
+
+/// The rate limit state of a client.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RateLimitState {
+    /// Maximum number of requests allowed in the window
+    pub limit: u32,
+    /// Requests left in the current window
+    pub remaining: u32,
+    /// Seconds until the window resets
+    pub reset: u64,
+}
+
+struct Window {
+    started_at: Instant,
+    count: u32,
+}
+
+struct Windows {
+    entries: HashMap<String, Window>,
+    swept_at: Instant,
+}
+
+#[derive(Clone)]
+pub struct RateLimiter {
+    limit: u32,
+    window: Duration,
+    windows: Arc<Mutex<Windows>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        Self::with_limit(config.rate_limit, config.rate_limit_window)
+    }
+
+    /// The limiter of the public API.
+    pub fn public(config: &RateLimitConfig) -> Self {
+        Self::with_limit(config.public_rate_limit, config.public_rate_limit_window)
+    }
+
+    fn with_limit(limit: u32, window: u64) -> Self {
+        Self {
+            limit,
+            window: Duration::from_secs(window.max(1)),
+            windows: Arc::new(Mutex::new(Windows {
+                entries: HashMap::new(),
+                swept_at: Instant::now(),
+            })),
+        }
+    }
+
+    /// Count a request of the client, returns the resulting state and whether it is allowed.
+    pub fn hit(&self, key: &str) -> (RateLimitState, bool) {
+        self.update(key, 1)
+    }
+
+    /// Count several requests of the client at once, either all of them or none when they
+    /// do not fit in the window, returns the resulting state and whether they are allowed.
+    pub fn hit_many(&self, key: &str, count: u32) -> (RateLimitState, bool) {
+        self.update(key, count)
+    }
+
+    /// Get the state of the client without counting a request.
+    pub fn peek(&self, key: &str) -> RateLimitState {
+        self.update(key, 0).0
+    }
+
+    fn update(&self, key: &str, cost: u32) -> (RateLimitState, bool) {
+        let now = Instant::now();
+        let mut windows = self.windows.lock().unwrap();
+
+        // Forget expired windows once per window, so the map stays bounded by the active
+        // clients without being scanned on every request.
+        if now.duration_since(windows.swept_at) >= self.window {
+            windows.entries.retain(|_, w| now.duration_since(w.started_at) < self.window);
+            windows.swept_at = now;
+        }
+
+        let window =
+            windows.entries.entry(key.to_string()).or_insert(Window { started_at: now, count: 0 });
+        if now.duration_since(window.started_at) >= self.window {
+            *window = Window { started_at: now, count: 0 };
+        }
+        let allowed = window.count.saturating_add(cost) <= self.limit;
+        if allowed {
+            window.count += cost;
+        }
+
+        let reset = self.window.saturating_sub(now.duration_since(window.started_at));
+        let state = RateLimitState {
+            limit: self.limit,
+            remaining: self.limit.saturating_sub(window.count),
+            reset: reset.as_secs_f64().ceil() as u64,
+        };
+        (state, allowed)
+    }
+}
+
+impl RateLimitState {
+    fn apply(&self, headers: &mut HeaderMap) {
+        headers.insert(LIMIT, HeaderValue::from(self.limit));
+        headers.insert(REMAINING, HeaderValue::from(self.remaining));
+        headers.insert(RESET, HeaderValue::from(self.reset));
+    }
+}
+
+/// The identity of the client making a request, inserted into the request extensions.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClientKey(pub String);
+
+/// The key of the rate limit window a request is counted in, inserted into the request
+/// extensions.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WindowKey(pub String);
+
+impl ClientKey {
+    /// The client authenticating with an API key.
+    pub fn api_key(key: &str) -> Self {
+        Self(format!("key:{key}"))
+    }
+
+    /// The SHA-256 hash of the key, which what belongs to the client is stored under
+    /// rather than the key itself.
+    pub fn digest(&self) -> String {
+        hex::encode(Sha256::digest(self.0.as_bytes()))
+    }
+
+    /// Whether the client authenticated with the admin API key.
+    pub fn is_admin(&self, admin_api_key: &Option<String>) -> bool {
+        match admin_api_key.as_deref() {
+            Some(admin) if !admin.is_empty() => self.0.strip_prefix("key:") == Some(admin),
+            _ => false,
+        }
+    }
+
+    /// Fail unless the client authenticated with the admin API key.
+    pub fn authorize_admin(&self, admin_api_key: &Option<String>) -> Result<(), ApiError> {
+        if !self.is_admin(admin_api_key) {
+            return Err(ApiError::Forbidden(format!("the {API_KEY} is not an admin key")));
+        }
+        Ok(())
+    }
+}
+
+/// Identify the client of a request, returns its key and the key of its rate limit window.
+///
+/// An API key identifies its client once accepted, and has a window of its own once it is
+/// a configured one: when any key is accepted anyone can make up new keys, so they share
+/// the window of their peer address, like the requests without an accepted key.
+pub fn client_key(
+    config: &Config,
+    headers: &HeaderMap,
+    peer: Option<SocketAddr>,
+) -> (String, String) {
+    let address = match peer {
+        Some(addr) => format!("ip:{}", addr.ip()),
+        None => "anonymous".to_string(),
+    };
+    match headers.get(API_KEY).and_then(|v| v.to_str().ok()).filter(|k| !k.is_empty()) {
+        Some(key) if auth::is_configured(config, key) => {
+            (format!("key:{key}"), format!("key:{key}"))
+        }
+        Some(key) if auth::accepts(config, key) => (format!("key:{key}"), address),
+        _ => (address.clone(), address),
+    }
+}
+
+/// Enforce the rate limit and report its state on every response.
+pub async fn limit(State(ctx): State<Arc<Context>>, req: Request, next: Next) -> Response {
+    enforce(&ctx.config, &ctx.rate_limiter, req, next).await
+}
+
+/// Enforce the rate limit of the public API.
+pub async fn limit_public(State(ctx): State<Arc<Context>>, req: Request, next: Next) -> Response {
+    enforce(&ctx.config, &ctx.public_rate_limiter, req, next).await
+}
+
+async fn enforce(config: &Config, limiter: &RateLimiter, mut req: Request, next: Next) -> Response {
+    let peer = req.extensions().get::<ConnectInfo<SocketAddr>>().map(|info| info.0);
+    let (key, window) = client_key(config, req.headers(), peer);
+
+    let (state, allowed) = if EXEMPT_PATHS.contains(&req.uri().path()) {
+        (limiter.peek(&window), true)
+    } else {
+        limiter.hit(&window)
+    };
+
+    let mut res = if allowed {
+        req.extensions_mut().insert(state.clone());
+        req.extensions_mut().insert(ClientKey(key));
+        req.extensions_mut().insert(WindowKey(window));
+        next.run(req).await
+    } else {
+        let mut res = ApiError::TooManyRequests.into_response();
+        res.headers_mut().insert("retry-after", HeaderValue::from(state.reset));
+        res
+    };
+
+    state.apply(res.headers_mut());
+    res
+}
@@ -0,0 +1,69 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Request tracing middleware.
+
+use axum::{
+    extract::Request,
+    http::{HeaderMap, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::{info_span, Instrument};
+use uuid::Uuid;
+
+use crate::telemetry::{self, TraceContext, TRACEPARENT};
+
+/// The request id header name.
+pub const REQUEST_ID: &str = "x-request-id";
+
+/// The id of a request, inserted into the request extensions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    /// Take the id from the `x-request-id` header when it is sane, or generate one.
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let id =
+            headers.get(REQUEST_ID).and_then(|v| v.to_str().ok()).map(str::trim).filter(|id| {
+                !id.is_empty() && id.len() <= 128 && id.bytes().all(|b| b.is_ascii_graphic())
+            });
+        Self(id.map_or_else(|| Uuid::new_v4().to_string(), str::to_string))
+    }
+}
+
+/// Open a span for every request and make its trace context available to
+/// everything the request touches (services, background jobs, contract calls).
+///
+/// The context is continued from the incoming `traceparent` header when present,
+/// and returned to the client in the response headers, together with the request id.
+pub async fn propagate(mut req: Request, next: Next) -> Response {
+    let ctx = TraceContext::from_headers(req.headers()).map(|ctx| ctx.child()).unwrap_or_default();
+    let request_id = RequestId::from_headers(req.headers());
+    req.extensions_mut().insert(request_id.clone());
+    let span = info_span!(
+        "request",
+        trace_id = %ctx.trace_id,
+        request_id = %request_id.0,
+        method = %req.method(),
+        path = %req.uri().path(),
+    );
+
+    let mut res = telemetry::scope(ctx.clone(), next.run(req)).instrument(span).await;
+    res.headers_mut().insert(TRACEPARENT, ctx.header_value());
+    if let Ok(value) = HeaderValue::from_str(&request_id.0) {
+        res.headers_mut().insert(REQUEST_ID, value);
+    }
+    res
+}
@@ -0,0 +1,33 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Usage accounting middleware.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::{context::Context, middlewares::ratelimit::ClientKey};
+
+/// Count every request against the client which made it.
+pub async fn record(State(ctx): State<Arc<Context>>, req: Request, next: Next) -> Response {
+    if let Some(key) = req.extensions().get::<ClientKey>() {
+        ctx.usage.record_request(key);
+    }
+    next.run(req).await
+}
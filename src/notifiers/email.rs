@@ -0,0 +1,420 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Email delivery, through an SMTP relay or Amazon SES.
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufStream},
+    net::TcpStream,
+};
+use tokio_rustls::{
+    rustls::{pki_types::ServerName, ClientConfig, RootCertStore},
+    TlsConnector,
+};
+use tracing::{debug, instrument};
+use uuid::Uuid;
+
+use super::{Message, Notifier};
+
+#[derive(Clone, clap::Parser)]
+pub struct EmailConfig {
+    /// How emails are delivered, emails are disabled with `none`.
+    #[clap(long, env = "DRK_EMAIL_TRANSPORT", value_enum, default_value_t = EmailTransport::None)]
+    pub email_transport: EmailTransport,
+
+    /// The sender of the emails, eg. `DepRank <noreply@example.com>`.
+    #[clap(long, env = "DRK_EMAIL_FROM")]
+    pub email_from: Option<String>,
+
+    /// Host of the SMTP relay.
+    #[clap(long, env = "DRK_SMTP_HOST")]
+    pub smtp_host: Option<String>,
+
+    /// Port of the SMTP relay.
+    #[clap(long, env = "DRK_SMTP_PORT", default_value_t = 465)]
+    pub smtp_port: u16,
+
+    /// How the SMTP connection is encrypted.
+    #[clap(long, env = "DRK_SMTP_TLS", value_enum, default_value_t = SmtpTls::Implicit)]
+    pub smtp_tls: SmtpTls,
+
+    /// Username of the SMTP relay, if it requires authentication.
+    #[clap(long, env = "DRK_SMTP_USERNAME")]
+    pub smtp_username: Option<String>,
+
+    /// Password of the SMTP relay.
+    #[clap(long, env = "DRK_SMTP_PASSWORD")]
+    pub smtp_password: Option<String>,
+
+    /// AWS region of SES, eg. `eu-west-1`.
+    #[clap(long, env = "DRK_SES_REGION")]
+    pub ses_region: Option<String>,
+
+    /// AWS access key id allowed to send with SES.
+    #[clap(long, env = "DRK_SES_ACCESS_KEY_ID")]
+    pub ses_access_key_id: Option<String>,
+
+    /// AWS secret access key.
+    #[clap(long, env = "DRK_SES_SECRET_ACCESS_KEY")]
+    pub ses_secret_access_key: Option<String>,
+}
+
+/// How emails are delivered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum EmailTransport {
+    /// Emails are not sent
+    None,
+    /// Through an SMTP relay
+    Smtp,
+    /// Through the Amazon SES API
+    Ses,
+}
+
+/// How an SMTP connection is encrypted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SmtpTls {
+    /// TLS from the start, usually on port 465
+    Implicit,
+    /// Upgraded to TLS with STARTTLS, usually on port 587
+    Starttls,
+    /// Not encrypted, only for local relays
+    None,
+}
+
+/// Sends emails with the configured transport.
+pub struct EmailNotifier {
+    from: String,
+    transport: Transport,
+}
+
+enum Transport {
+    Smtp {
+        host: String,
+        port: u16,
+        tls: SmtpTls,
+        credentials: Option<(String, String)>,
+        connector: TlsConnector,
+    },
+    Ses {
+        client: reqwest::Client,
+        region: String,
+        access_key_id: String,
+        secret_access_key: String,
+    },
+}
+
+impl EmailNotifier {
+    /// Build the notifier of the configuration, `None` when emails are disabled.
+    pub fn new(config: &EmailConfig) -> Result<Option<Self>> {
+        let set = |value: &Option<String>| value.clone().filter(|value| !value.is_empty());
+        let required = |value: &Option<String>, name: &str| {
+            set(value).ok_or_else(|| anyhow!("{name} is required to send emails"))
+        };
+
+        let transport = match config.email_transport {
+            EmailTransport::None => return Ok(None),
+            EmailTransport::Smtp => Transport::Smtp {
+                host: required(&config.smtp_host, "DRK_SMTP_HOST")?,
+                port: config.smtp_port,
+                tls: config.smtp_tls,
+                credentials: set(&config.smtp_username)
+                    .map(|username| (username, set(&config.smtp_password).unwrap_or_default())),
+                connector: tls_connector(),
+            },
+            EmailTransport::Ses => Transport::Ses {
+                client: reqwest::Client::new(),
+                region: required(&config.ses_region, "DRK_SES_REGION")?,
+                access_key_id: required(&config.ses_access_key_id, "DRK_SES_ACCESS_KEY_ID")?,
+                secret_access_key: required(
+                    &config.ses_secret_access_key,
+                    "DRK_SES_SECRET_ACCESS_KEY",
+                )?,
+            },
+        };
+        let from = required(&config.email_from, "DRK_EMAIL_FROM")?;
+        if !is_valid_email(mailbox(&from)) {
+            bail!("Invalid DRK_EMAIL_FROM `{from}`");
+        }
+
+        Ok(Some(Self { from, transport }))
+    }
+}
+
+#[async_trait]
+impl Notifier for EmailNotifier {
+    fn channel(&self) -> &'static str {
+        "email"
+    }
+
+    #[instrument(skip_all, fields(subject = %message.subject))]
+    async fn send(&self, recipient: &str, message: &Message) -> Result<()> {
+        if !is_valid_email(recipient) {
+            bail!("Invalid email address `{recipient}`");
+        }
+
+        match &self.transport {
+            Transport::Smtp { host, port, tls, credentials, connector } => {
+                let email = format_email(&self.from, recipient, message);
+                let tcp = TcpStream::connect((host.as_str(), *port)).await?;
+                let session = Session {
+                    from: mailbox(&self.from),
+                    to: recipient,
+                    credentials: credentials.as_ref(),
+                    email: &email,
+                };
+                match tls {
+                    SmtpTls::None => session.deliver(BufStream::new(tcp), true).await?,
+                    SmtpTls::Implicit => {
+                        let stream = connector.connect(server_name(host)?, tcp).await?;
+                        session.deliver(BufStream::new(stream), true).await?;
+                    }
+                    SmtpTls::Starttls => {
+                        let mut stream = BufStream::new(tcp);
+                        reply(&mut stream, 220).await?;
+                        session.ehlo(&mut stream).await?;
+                        command(&mut stream, "STARTTLS", 220).await?;
+                        let stream =
+                            connector.connect(server_name(host)?, stream.into_inner()).await?;
+                        session.deliver(BufStream::new(stream), false).await?;
+                    }
+                }
+            }
+            Transport::Ses { client, region, access_key_id, secret_access_key } => {
+                let host = format!("email.{region}.amazonaws.com");
+                let path = "/v2/email/outbound-emails";
+                let body = serde_json::to_vec(&json!({
+                    "FromEmailAddress": self.from,
+                    "Destination": { "ToAddresses": [recipient] },
+                    "Content": {
+                        "Simple": {
+                            "Subject": { "Data": message.subject, "Charset": "UTF-8" },
+                            "Body": { "Text": { "Data": message.body, "Charset": "UTF-8" } },
+                        }
+                    },
+                }))?;
+
+                let now = Utc::now();
+                let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+                let authorization = sign_v4(
+                    &SigV4 { region, service: "ses", access_key_id, secret_access_key },
+                    &host,
+                    path,
+                    &amz_date,
+                    &body,
+                );
+                client
+                    .post(format!("https://{host}{path}"))
+                    .header("content-type", "application/json")
+                    .header("x-amz-date", amz_date)
+                    .header("authorization", authorization)
+                    .body(body)
+                    .send()
+                    .await?
+                    .error_for_status()?;
+            }
+        }
+
+        debug!("Email sent");
+        Ok(())
+    }
+}
+
+/// An SMTP session delivering one email.
+struct Session<'a> {
+    from: &'a str,
+    to: &'a str,
+    credentials: Option<&'a (String, String)>,
+    email: &'a str,
+}
+
+impl Session<'_> {
+    async fn ehlo<S: AsyncRead + AsyncWrite + Unpin>(
+        &self,
+        stream: &mut BufStream<S>,
+    ) -> Result<()> {
+        let domain = self.from.rsplit_once('@').map(|(_, domain)| domain).unwrap_or("localhost");
+        command(stream, &format!("EHLO {domain}"), 250).await
+    }
+
+    /// Deliver the email, after the greeting of the server unless already received.
+    async fn deliver<S>(&self, mut stream: BufStream<S>, greeting: bool) -> Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        if greeting {
+            reply(&mut stream, 220).await?;
+        }
+        self.ehlo(&mut stream).await?;
+        if let Some((username, password)) = self.credentials {
+            let token = BASE64.encode(format!("\0{username}\0{password}"));
+            command(&mut stream, &format!("AUTH PLAIN {token}"), 235).await?;
+        }
+        command(&mut stream, &format!("MAIL FROM:<{}>", self.from), 250).await?;
+        command(&mut stream, &format!("RCPT TO:<{}>", self.to), 250).await?;
+        command(&mut stream, "DATA", 354).await?;
+        command(&mut stream, &format!("{}\r\n.", self.email), 250).await?;
+        command(&mut stream, "QUIT", 221).await
+    }
+}
+
+/// Send an SMTP command and check the reply code.
+async fn command<S>(stream: &mut BufStream<S>, line: &str, expected: u16) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    stream.write_all(line.as_bytes()).await?;
+    stream.write_all(b"\r\n").await?;
+    stream.flush().await?;
+    reply(stream, expected).await.map_err(|e| {
+        // Never log credentials.
+        let verb = line.split_whitespace().next().unwrap_or_default();
+        anyhow!("{verb}: {e}")
+    })
+}
+
+/// Read an SMTP reply, possibly spanning several lines, and check its code.
+async fn reply<S>(stream: &mut BufStream<S>, expected: u16) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut reply = String::new();
+    loop {
+        let mut line = String::new();
+        if stream.read_line(&mut line).await? == 0 {
+            bail!("SMTP connection closed");
+        }
+        reply.push_str(&line);
+        // Continuation lines have a `-` after the code.
+        if line.as_bytes().get(3) != Some(&b'-') {
+            break;
+        }
+    }
+
+    let code = reply.get(..3).and_then(|code| code.parse::<u16>().ok());
+    if code != Some(expected) {
+        bail!("unexpected SMTP reply `{}`", reply.trim_end());
+    }
+    Ok(())
+}
+
+/// Format an email, with a base64 body so it never needs dot stuffing.
+fn format_email(from: &str, to: &str, message: &Message) -> String {
+    let domain = mailbox(from).rsplit_once('@').map(|(_, domain)| domain).unwrap_or("localhost");
+    let body = BASE64.encode(message.body.replace('\n', "\r\n"));
+    let lines: Vec<_> = body.as_bytes().chunks(76).map(String::from_utf8_lossy).collect();
+    format!(
+        "From: {from}\r\n\
+         To: {to}\r\n\
+         Subject: =?UTF-8?B?{subject}?=\r\n\
+         Date: {date}\r\n\
+         Message-ID: <{id}@{domain}>\r\n\
+         MIME-Version: 1.0\r\n\
+         Content-Type: text/plain; charset=utf-8\r\n\
+         Content-Transfer-Encoding: base64\r\n\
+         \r\n\
+         {body}",
+        subject = BASE64.encode(&message.subject),
+        date = Utc::now().to_rfc2822(),
+        id = Uuid::new_v4(),
+        body = lines.join("\r\n"),
+    )
+}
+
+/// The address of a mailbox, eg. `a@example.com` for `A <a@example.com>`.
+fn mailbox(mailbox: &str) -> &str {
+    match mailbox.rsplit_once('<') {
+        Some((_, address)) => address.trim_end().trim_end_matches('>'),
+        None => mailbox.trim(),
+    }
+}
+
+/// Whether an email address is well-formed, and safe to use in SMTP commands and headers.
+pub fn is_valid_email(address: &str) -> bool {
+    let Some((local, domain)) = address.rsplit_once('@') else {
+        return false;
+    };
+    address.len() <= 254
+        && !local.is_empty()
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && address.chars().all(|c| c.is_ascii_graphic() && !"<>,;\"()[]\\".contains(c))
+}
+
+fn server_name(host: &str) -> Result<ServerName<'static>> {
+    ServerName::try_from(host.to_string()).map_err(|_| anyhow!("Invalid SMTP host `{host}`"))
+}
+
+fn tls_connector() -> TlsConnector {
+    let roots = RootCertStore { roots: webpki_roots::TLS_SERVER_ROOTS.to_vec() };
+    let config = ClientConfig::builder_with_provider(Arc::new(
+        tokio_rustls::rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .expect("the ring provider supports the default protocol versions")
+    .with_root_certificates(roots)
+    .with_no_client_auth();
+    TlsConnector::from(Arc::new(config))
+}
+
+/// The credentials and scope of an AWS Signature Version 4.
+struct SigV4<'a> {
+    region: &'a str,
+    service: &'a str,
+    access_key_id: &'a str,
+    secret_access_key: &'a str,
+}
+
+/// The `Authorization` header of a JSON POST request, per AWS Signature Version 4.
+fn sign_v4(sig: &SigV4, host: &str, path: &str, amz_date: &str, body: &[u8]) -> String {
+    const SIGNED_HEADERS: &str = "content-type;host;x-amz-date";
+
+    let hmac = |key: &[u8], data: &str| {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+        mac.update(data.as_bytes());
+        mac.finalize().into_bytes().to_vec()
+    };
+
+    let date = &amz_date[..8];
+    let scope = format!("{date}/{}/{}/aws4_request", sig.region, sig.service);
+    let canonical = format!(
+        "POST\n{path}\n\ncontent-type:application/json\nhost:{host}\nx-amz-date:{amz_date}\n\n\
+         {SIGNED_HEADERS}\n{}",
+        hex::encode(Sha256::digest(body)),
+    );
+    let to_sign = format!(
+        "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+        hex::encode(Sha256::digest(canonical.as_bytes())),
+    );
+
+    let key = hmac(format!("AWS4{}", sig.secret_access_key).as_bytes(), date);
+    let key = hmac(&key, sig.region);
+    let key = hmac(&key, sig.service);
+    let key = hmac(&key, "aws4_request");
+    let signature = hex::encode(hmac(&key, &to_sign));
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={SIGNED_HEADERS}, \
+         Signature={signature}",
+        sig.access_key_id,
+    )
+}
@@ -0,0 +1,222 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Notifications sent to contributors and project owners, whatever the channel.
+//!
+//! Every notification renders to a templated message, which each channel delivers to
+//! its own kind of recipient, eg. an email address or a webhook URL.
+
+pub mod email;
+pub mod webhook;
+
+use std::fmt;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::contracts::types::{Address, Hash, Id, Number};
+
+/// The kinds of notifications, which recipients can opt out of.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    /// A question about a workflow awaits an answer
+    Inquire,
+    /// An allocation was paid out, to its recipient
+    AllocationReceipt,
+    /// An allocation of a workflow was paid out
+    AllocationExecuted,
+    /// All allocations of a workflow were settled
+    WorkflowCompleted,
+}
+
+impl fmt::Display for NotificationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Inquire => write!(f, "inquire"),
+            Self::AllocationReceipt => write!(f, "allocation_receipt"),
+            Self::AllocationExecuted => write!(f, "allocation_executed"),
+            Self::WorkflowCompleted => write!(f, "workflow_completed"),
+        }
+    }
+}
+
+/// An event worth notifying.
+#[derive(Debug, Clone)]
+pub enum Notification {
+    Inquire {
+        workflow_id: Id,
+        inquirer: Address,
+        question: String,
+    },
+    AllocationReceipt {
+        allocation_id: Id,
+        amount: Number,
+        token: String,
+        tx_hash: Hash,
+    },
+    AllocationExecuted {
+        workflow_id: Uuid,
+        allocation_id: Id,
+        /// The GitHub username of the contributor, otherwise the payout address
+        recipient: String,
+        amount: Number,
+        token: String,
+        tx_hash: Hash,
+    },
+    WorkflowCompleted {
+        workflow_id: Uuid,
+        executed: usize,
+        failed: usize,
+    },
+}
+
+/// A rendered notification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub subject: String,
+    /// The plain text body
+    pub body: String,
+}
+
+const INQUIRE_SUBJECT: &str = "A question about workflow {workflow_id}";
+const INQUIRE_BODY: &str = "\
+{inquirer} asked a question about workflow {workflow_id}:
+
+{question}
+
+Answer it to let the workflow proceed.";
+
+const RECEIPT_SUBJECT: &str = "You received {amount} {token}";
+const RECEIPT_BODY: &str = "\
+Allocation {allocation_id} was paid out: {amount} {token}.
+
+Transaction: {tx_hash}";
+
+const EXECUTED_SUBJECT: &str = "{recipient} was paid {amount} {token}";
+const EXECUTED_BODY: &str = "\
+Allocation {allocation_id} of workflow {workflow_id} was paid out to {recipient}: \
+{amount} {token}.
+
+Transaction: {tx_hash}";
+
+const COMPLETED_SUBJECT: &str = "Workflow {workflow_id} completed";
+const COMPLETED_BODY: &str = "\
+All allocations of workflow {workflow_id} were settled: {executed} paid out, {failed} failed.";
+
+impl Notification {
+    pub fn kind(&self) -> NotificationKind {
+        match self {
+            Self::Inquire { .. } => NotificationKind::Inquire,
+            Self::AllocationReceipt { .. } => NotificationKind::AllocationReceipt,
+            Self::AllocationExecuted { .. } => NotificationKind::AllocationExecuted,
+            Self::WorkflowCompleted { .. } => NotificationKind::WorkflowCompleted,
+        }
+    }
+
+    /// Render the message of the notification from its template.
+    pub fn message(&self) -> Message {
+        let (subject, body, vars) = match self {
+            Self::Inquire { workflow_id, inquirer, question } => (
+                INQUIRE_SUBJECT,
+                INQUIRE_BODY,
+                vec![
+                    ("workflow_id", workflow_id.to_string()),
+                    ("inquirer", inquirer.to_string()),
+                    ("question", question.clone()),
+                ],
+            ),
+            Self::AllocationReceipt { allocation_id, amount, token, tx_hash } => (
+                RECEIPT_SUBJECT,
+                RECEIPT_BODY,
+                vec![
+                    ("allocation_id", allocation_id.to_string()),
+                    ("amount", amount.clone()),
+                    ("token", token.clone()),
+                    ("tx_hash", tx_hash.to_string()),
+                ],
+            ),
+            Self::AllocationExecuted {
+                workflow_id,
+                allocation_id,
+                recipient,
+                amount,
+                token,
+                tx_hash,
+            } => (
+                EXECUTED_SUBJECT,
+                EXECUTED_BODY,
+                vec![
+                    ("workflow_id", workflow_id.to_string()),
+                    ("allocation_id", allocation_id.to_string()),
+                    ("recipient", recipient.clone()),
+                    ("amount", amount.clone()),
+                    ("token", token.clone()),
+                    ("tx_hash", tx_hash.to_string()),
+                ],
+            ),
+            Self::WorkflowCompleted { workflow_id, executed, failed } => (
+                COMPLETED_SUBJECT,
+                COMPLETED_BODY,
+                vec![
+                    ("workflow_id", workflow_id.to_string()),
+                    ("executed", executed.to_string()),
+                    ("failed", failed.to_string()),
+                ],
+            ),
+        };
+        Message { subject: render(subject, &vars), body: render(body, &vars) }
+    }
+}
+
+/// Replace the `{name}` placeholders of a template, in a single pass so values
+/// containing placeholders are left as is.
+fn render(template: &str, vars: &[(&str, String)]) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let placeholder = &rest[start + 1..];
+        let value = placeholder.find('}').and_then(|end| {
+            let name = &placeholder[..end];
+            vars.iter().find(|(var, _)| *var == name).map(|(_, value)| (value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                rendered.push_str(value);
+                rest = &placeholder[end + 1..];
+            }
+            None => {
+                rendered.push('{');
+                rest = placeholder;
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// A channel delivering messages, eg. email.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    /// The name of the channel, eg. `email`
+    fn channel(&self) -> &'static str;
+
+    /// Deliver a message to a recipient of the channel, eg. an email address.
+    async fn send(&self, recipient: &str, message: &Message) -> Result<()>;
+}
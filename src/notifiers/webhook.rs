@@ -0,0 +1,140 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Chat notifications, posted to Discord webhooks and Slack incoming webhooks.
+
+use std::{sync::Arc, time::Duration};
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use reqwest::redirect::Policy;
+use serde_json::json;
+use tracing::{debug, instrument};
+use url::Url;
+
+use super::{Message, Notifier};
+use crate::services::webhook::PublicResolver;
+
+/// The maximum length of a Discord message.
+const DISCORD_MAX_LEN: usize = 2000;
+
+/// How long a webhook is given to answer.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A chat service accepting webhooks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookChannel {
+    Discord,
+    Slack,
+}
+
+impl WebhookChannel {
+    /// Whether the URL is a webhook of the service, so messages are never posted elsewhere.
+    pub fn is_valid_url(&self, url: &str) -> bool {
+        let Ok(url) = Url::parse(url) else {
+            return false;
+        };
+        let (hosts, prefix) = self.endpoint();
+        url.scheme() == "https"
+            && url.port().is_none()
+            && url.host_str().is_some_and(|host| hosts.contains(&host))
+            && url.path().len() > prefix.len()
+            && url.path().starts_with(prefix)
+    }
+
+    /// Hide the secret part of a webhook URL, keeping the service it posts to.
+    pub fn mask(&self, url: &str) -> String {
+        let (_, prefix) = self.endpoint();
+        match url.find(prefix) {
+            Some(i) => format!("{}…", &url[..i + prefix.len()]),
+            None => "…".to_string(),
+        }
+    }
+
+    /// The hosts and path prefix of the webhooks of the service.
+    fn endpoint(&self) -> (&'static [&'static str], &'static str) {
+        match self {
+            Self::Discord => (&["discord.com", "discordapp.com"], "/api/webhooks/"),
+            Self::Slack => (&["hooks.slack.com"], "/services/"),
+        }
+    }
+}
+
+/// Posts messages to the webhooks of a chat service, resolved to public addresses only
+/// and never following redirects, so a message never reaches the network the server
+/// runs in.
+pub struct WebhookNotifier {
+    channel: WebhookChannel,
+    client: reqwest::Client,
+}
+
+impl WebhookNotifier {
+    pub fn new(channel: WebhookChannel) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(TIMEOUT)
+            .redirect(Policy::none())
+            .dns_resolver(Arc::new(PublicResolver))
+            .build()
+            .unwrap_or_default();
+        Self { channel, client }
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    fn channel(&self) -> &'static str {
+        match self.channel {
+            WebhookChannel::Discord => "discord",
+            WebhookChannel::Slack => "slack",
+        }
+    }
+
+    #[instrument(skip_all, fields(channel = self.channel(), subject = %message.subject))]
+    async fn send(&self, recipient: &str, message: &Message) -> Result<()> {
+        if !self.channel.is_valid_url(recipient) {
+            bail!("Invalid {} webhook URL", self.channel());
+        }
+
+        let payload = match self.channel {
+            WebhookChannel::Discord => {
+                let content = format!("**{}**\n{}", message.subject, message.body);
+                json!({
+                    "content": truncate(&content, DISCORD_MAX_LEN),
+                    // Never ping anyone, whatever the message contains.
+                    "allowed_mentions": { "parse": [] },
+                })
+            }
+            WebhookChannel::Slack => json!({
+                "text": format!("*{}*\n{}", escape_slack(&message.subject), escape_slack(&message.body)),
+            }),
+        };
+        self.client.post(recipient).json(&payload).send().await?.error_for_status()?;
+
+        debug!("Webhook posted");
+        Ok(())
+    }
+}
+
+/// Escape the control characters of Slack messages.
+fn escape_slack(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn truncate(text: &str, max_len: usize) -> String {
+    if text.chars().count() <= max_len {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max_len - 1).collect();
+    truncated.push('…');
+    truncated
+}
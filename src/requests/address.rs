@@ -0,0 +1,71 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+/// A chain contributors can be paid out on.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Chain {
+    Starknet,
+    Evm,
+    Solana,
+}
+
+impl fmt::Display for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Starknet => write!(f, "starknet"),
+            Self::Evm => write!(f, "evm"),
+            Self::Solana => write!(f, "solana"),
+        }
+    }
+}
+
+impl FromStr for Chain {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "starknet" => Ok(Self::Starknet),
+            "evm" => Ok(Self::Evm),
+            "solana" => Ok(Self::Solana),
+            _ => Err(format!("Unknown chain `{s}`")),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct AddressRequest {
+    /// The chain of the address.
+    pub chain: Chain,
+    /// The payout address on the chain.
+    #[validate(length(min = 1, max = 128))]
+    pub address: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct AddressBookRequest {
+    /// The payout addresses, at most one per chain.
+    #[validate(length(max = 3), nested)]
+    pub addresses: Vec<AddressRequest>,
+    /// The chain the contributor prefers to be paid on, must have an address.
+    #[serde(default)]
+    pub preferred: Option<Chain>,
+}
@@ -0,0 +1,54 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use super::validation::amount;
+use crate::contracts::types::{Address, Id};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct CreateAllocationsRequest {
+    /// The id of the workflow on chain the allocations belong to
+    #[schema(value_type = String)]
+    pub workflow_id: Id,
+    /// The allocations, at most 100, created in the given order
+    #[validate(length(min = 1, max = 100), nested)]
+    pub allocations: Vec<AllocationItem>,
+    /// The budget in whole tokens the allocations split, which their amounts must sum up
+    /// to exactly, all of them being paid in the same token
+    #[validate(custom(function = "amount"))]
+    pub budget: Option<String>,
+    /// Whether the allocations are a follow-up round paid from the budget released by
+    /// the failed allocations of the workflow, failing unless enough of it is left
+    #[serde(default)]
+    pub from_pool: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct AllocationItem {
+    /// The id of the signature on chain approving the allocation
+    #[schema(value_type = String)]
+    pub sign_id: Id,
+    /// The account address receiving the allocation
+    #[schema(value_type = String)]
+    pub recipient: Address,
+    /// The amount in whole tokens, eg. `12.5`
+    #[validate(custom(function = "amount"))]
+    pub amount: String,
+    /// The address of the token contract
+    #[schema(value_type = String)]
+    pub token_address: Address,
+}
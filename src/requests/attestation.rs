@@ -0,0 +1,43 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use super::validation::{not_blank, starknet_address};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct TermsRequest {
+    /// The title of the document, eg. `Recipient terms`
+    #[validate(length(max = 256), custom(function = "not_blank"))]
+    pub title: String,
+    /// The full text of the document
+    #[validate(length(max = 262144), custom(function = "not_blank"))]
+    pub content: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct AttestationRequest {
+    /// The account address of the recipient accepting the terms
+    #[validate(custom(function = "starknet_address"))]
+    pub address: String,
+    /// The version of the accepted terms, eg. `2026-10-01`
+    #[validate(length(min = 1, max = 64))]
+    pub version: String,
+    /// The GitHub username of the recipient, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, max = 39))]
+    pub username: Option<String>,
+}
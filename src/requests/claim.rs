@@ -0,0 +1,39 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::contracts::types::Address;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct BuildClaimRequest {
+    /// The account address of the recipient, claiming the allocation
+    #[schema(value_type = String)]
+    pub address: Address,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct ExecuteClaimRequest {
+    /// The account address of the recipient, claiming the allocation
+    #[schema(value_type = String)]
+    pub address: Address,
+    /// The typed data returned when building the claim, unchanged
+    pub typed_data: Value,
+    /// The signature of the typed data by the recipient account
+    #[validate(length(min = 1, max = 64))]
+    pub signature: Vec<String>,
+}
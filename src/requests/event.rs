@@ -0,0 +1,37 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// A message sent by the WebSocket clients.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum SubscriptionRequest {
+    /// Receive the events of the workflows, and of every workflow of the projects
+    Subscribe {
+        #[serde(default)]
+        workflows: Vec<Uuid>,
+        /// The projects, eg. deprank/backend
+        #[serde(default)]
+        projects: Vec<String>,
+    },
+    /// Stop receiving the events of the workflows and projects
+    Unsubscribe {
+        #[serde(default)]
+        workflows: Vec<Uuid>,
+        #[serde(default)]
+        projects: Vec<String>,
+    },
+}
@@ -0,0 +1,62 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use utoipa::IntoParams;
+
+/// Sparse field selection for large list responses.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FieldsParams {
+    /// Comma separated fields to include in each item, eg. `name,score,license`.
+    /// All fields are returned when omitted.
+    pub fields: Option<String>,
+}
+
+impl FieldsParams {
+    /// The selected field names, `None` when every field is requested.
+    pub fn names(&self) -> Option<HashSet<&str>> {
+        let names: HashSet<&str> = self
+            .fields
+            .as_deref()?
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .collect();
+        (!names.is_empty()).then_some(names)
+    }
+
+    /// Project each item down to the selected fields.
+    pub fn select<T: Serialize>(&self, items: &[T]) -> Vec<Value> {
+        let names = self.names();
+        items
+            .iter()
+            .map(|item| {
+                let value = serde_json::to_value(item).unwrap_or(Value::Null);
+                match (&names, value) {
+                    (Some(names), Value::Object(object)) => Value::Object(
+                        object
+                            .into_iter()
+                            .filter(|(key, _)| names.contains(key.as_str()))
+                            .collect::<Map<_, _>>(),
+                    ),
+                    (_, value) => value,
+                }
+            })
+            .collect()
+    }
+}
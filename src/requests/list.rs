@@ -0,0 +1,311 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::IntoParams;
+
+use crate::{
+    errors::{ApiError, Result},
+    responses::list::Pagination,
+};
+
+/// The default number of items per page.
+pub const DEFAULT_LIMIT: usize = 20;
+
+/// The maximum number of items per page.
+pub const MAX_LIMIT: usize = 100;
+
+/// The query parameters shared by every list endpoint.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListParams {
+    /// Page number, starting from 1. Ignored when `cursor` is set.
+    #[param(minimum = 1)]
+    pub page: Option<usize>,
+    /// Opaque cursor returned with the previous page.
+    pub cursor: Option<String>,
+    /// Maximum number of items to return, eg. 20 (max 100).
+    #[param(minimum = 1, maximum = 100)]
+    pub limit: Option<usize>,
+    /// Comma separated sort fields, prefix with `-` for descending order,
+    /// eg. `-score,name`.
+    pub sort: Option<String>,
+    /// Comma separated filter expressions in the form `field:op:value`, where `op`
+    /// is one of `eq`, `ne`, `gt`, `gte`, `lt`, `lte` or `contains`,
+    /// eg. `license:eq:MIT,score:gt:10`.
+    pub filter: Option<String>,
+}
+
+/// The sort direction of a field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Asc,
+    Desc,
+}
+
+/// A single sort key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sort {
+    pub field: String,
+    pub direction: Direction,
+}
+
+/// The comparison operator of a filter expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Contains,
+}
+
+/// A single filter expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    pub field: String,
+    pub op: Operator,
+    pub value: String,
+}
+
+impl ListParams {
+    /// The number of items per page.
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+
+    /// The number of items to skip, derived from the cursor or the page number.
+    pub fn offset(&self) -> Result<usize> {
+        match &self.cursor {
+            Some(cursor) => decode_cursor(cursor),
+            None => {
+                let page = self.page.unwrap_or(1);
+                page.saturating_sub(1)
+                    .checked_mul(self.limit())
+                    .ok_or_else(|| ApiError::InvalidListParams(format!("page {page} is too large")))
+            }
+        }
+    }
+
+    /// The offset of the page after the current one.
+    fn next_offset(&self) -> Result<usize> {
+        self.offset()?
+            .checked_add(self.limit())
+            .ok_or_else(|| ApiError::InvalidListParams("offset is too large".to_string()))
+    }
+
+    /// The cursor pointing to the page after the current one.
+    pub fn next_cursor(&self, total: usize) -> Result<Option<String>> {
+        let next = self.next_offset()?;
+        Ok((next < total).then(|| encode_cursor(next)))
+    }
+
+    /// Parse the sort expression.
+    pub fn sorts(&self) -> Result<Vec<Sort>> {
+        split(&self.sort)
+            .map(|field| {
+                let (field, direction) = match field.strip_prefix('-') {
+                    Some(field) => (field, Direction::Desc),
+                    None => (field.strip_prefix('+').unwrap_or(field), Direction::Asc),
+                };
+                if field.is_empty() {
+                    return Err(ApiError::InvalidListParams("empty sort field".to_string()));
+                }
+                Ok(Sort { field: field.to_string(), direction })
+            })
+            .collect()
+    }
+
+    /// Parse the filter expressions.
+    pub fn filters(&self) -> Result<Vec<Filter>> {
+        split(&self.filter)
+            .map(|expr| {
+                let mut parts = expr.splitn(3, ':');
+                match (parts.next(), parts.next(), parts.next()) {
+                    (Some(field), Some(op), Some(value)) if !field.is_empty() => Ok(Filter {
+                        field: field.to_string(),
+                        op: op.parse()?,
+                        value: value.to_string(),
+                    }),
+                    _ => Err(ApiError::InvalidListParams(format!("invalid filter `{expr}`"))),
+                }
+            })
+            .collect()
+    }
+
+    /// Check that all parameters are well-formed.
+    pub fn validate(&self) -> Result<()> {
+        if self.page == Some(0) {
+            return Err(ApiError::InvalidListParams("page starts from 1".to_string()));
+        }
+        self.next_offset()?;
+        self.sorts()?;
+        self.filters()?;
+        Ok(())
+    }
+
+    /// Filter, sort and paginate `items` in memory, returning the current page.
+    ///
+    /// Fields are resolved on the serialized form of each item, so any serializable
+    /// response type can be listed uniformly.
+    pub fn apply<T: Serialize>(&self, items: Vec<T>) -> Result<(Vec<T>, Pagination)> {
+        let filters = self.filters()?;
+        let sorts = self.sorts()?;
+
+        let mut rows: Vec<(Value, T)> = items
+            .into_iter()
+            .map(|item| (serde_json::to_value(&item).unwrap_or(Value::Null), item))
+            .filter(|(value, _)| filters.iter().all(|filter| filter.matches(value)))
+            .collect();
+
+        rows.sort_by(|(a, _), (b, _)| {
+            sorts.iter().fold(Ordering::Equal, |ordering, sort| {
+                ordering.then_with(|| {
+                    let ordering = compare(a.get(&sort.field), b.get(&sort.field));
+                    match sort.direction {
+                        Direction::Asc => ordering,
+                        Direction::Desc => ordering.reverse(),
+                    }
+                })
+            })
+        });
+
+        let total = rows.len();
+        let offset = self.offset()?;
+        let pagination = Pagination {
+            total,
+            limit: self.limit(),
+            offset,
+            next_cursor: self.next_cursor(total)?,
+        };
+        let page = rows.into_iter().skip(offset).take(self.limit()).map(|(_, item)| item).collect();
+
+        Ok((page, pagination))
+    }
+}
+
+impl std::str::FromStr for Operator {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "eq" => Ok(Self::Eq),
+            "ne" => Ok(Self::Ne),
+            "gt" => Ok(Self::Gt),
+            "gte" => Ok(Self::Gte),
+            "lt" => Ok(Self::Lt),
+            "lte" => Ok(Self::Lte),
+            "contains" => Ok(Self::Contains),
+            _ => Err(ApiError::InvalidListParams(format!("unknown filter operator `{s}`"))),
+        }
+    }
+}
+
+impl Filter {
+    /// Check whether the serialized item satisfies this filter.
+    pub fn matches(&self, item: &Value) -> bool {
+        let Some(value) = item.get(&self.field) else {
+            return false;
+        };
+        let expected = Value::String(self.value.clone());
+        let ordering = compare(Some(value), Some(&expected));
+
+        match self.op {
+            Operator::Eq => ordering == Ordering::Equal,
+            Operator::Ne => ordering != Ordering::Equal,
+            Operator::Gt => ordering == Ordering::Greater,
+            Operator::Gte => ordering != Ordering::Less,
+            Operator::Lt => ordering == Ordering::Less,
+            Operator::Lte => ordering != Ordering::Greater,
+            Operator::Contains => {
+                as_text(value).to_lowercase().contains(&self.value.to_lowercase())
+            }
+        }
+    }
+}
+
+fn split(value: &Option<String>) -> impl Iterator<Item = &str> {
+    value.as_deref().unwrap_or_default().split(',').map(str::trim).filter(|s| !s.is_empty())
+}
+
+fn encode_cursor(offset: usize) -> String {
+    format!("{offset:x}")
+}
+
+fn decode_cursor(cursor: &str) -> Result<usize> {
+    usize::from_str_radix(cursor, 16)
+        .map_err(|_| ApiError::InvalidListParams(format!("invalid cursor `{cursor}`")))
+}
+
+fn as_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Compare two JSON values, numerically when both sides look like numbers.
+fn compare(a: Option<&Value>, b: Option<&Value>) -> Ordering {
+    match (a, b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Less,
+        (Some(_), None) => Ordering::Greater,
+        (Some(a), Some(b)) => {
+            let (a, b) = (as_text(a), as_text(b));
+            match (a.parse::<f64>(), b.parse::<f64>()) {
+                (Ok(x), Ok(y)) => x.partial_cmp(&y).unwrap_or(Ordering::Equal),
+                _ => a.cmp(&b),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(page: Option<usize>, cursor: Option<&str>) -> ListParams {
+        ListParams { page, cursor: cursor.map(str::to_string), ..ListParams::default() }
+    }
+
+    #[test]
+    fn pages_past_the_offset_range_are_rejected() {
+        let params = params(Some(usize::MAX), None);
+        assert!(matches!(params.validate(), Err(ApiError::InvalidListParams(_))));
+        assert!(matches!(params.apply(vec![1, 2, 3]), Err(ApiError::InvalidListParams(_))));
+    }
+
+    #[test]
+    fn cursors_past_the_offset_range_are_rejected() {
+        let params = params(None, Some("ffffffffffffffff"));
+        assert!(matches!(params.validate(), Err(ApiError::InvalidListParams(_))));
+        assert!(matches!(params.apply(vec![1, 2, 3]), Err(ApiError::InvalidListParams(_))));
+    }
+
+    #[test]
+    fn the_last_page_has_no_next_cursor() {
+        let (page, pagination) = params(Some(2), None).apply((0..25).collect()).unwrap();
+        assert_eq!(page, (20..25).collect::<Vec<_>>());
+        assert_eq!(pagination.offset, 20);
+        assert_eq!(pagination.next_cursor, None);
+
+        let (_, pagination) = params(None, None).apply((0..25).collect::<Vec<_>>()).unwrap();
+        assert_eq!(pagination.next_cursor.as_deref(), Some("14"));
+    }
+}
@@ -12,5 +12,22 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod address;
+pub mod allocation;
+pub mod attestation;
+pub mod batch;
+pub mod claim;
+pub mod event;
+pub mod fields;
+pub mod identity;
+pub mod list;
+pub mod maintainer;
+pub mod notification;
+pub mod organization;
+pub mod policy;
+pub mod profile;
+pub mod sign;
+pub mod validation;
 pub mod wallet;
+pub mod webhook;
 pub mod workflow;
@@ -0,0 +1,42 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use super::validation::{discord_webhook, slack_webhook};
+use crate::notifiers::NotificationKind;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, Validate)]
+pub struct NotificationPreferencesRequest {
+    /// The email address notifications are sent to, none are sent if unset.
+    #[serde(default)]
+    #[validate(email)]
+    pub email: Option<String>,
+    /// The kinds of notifications never sent.
+    #[serde(default)]
+    pub opt_out: Vec<NotificationKind>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, Validate)]
+pub struct NotificationChannelsRequest {
+    /// The Discord webhook URL, eg. `https://discord.com/api/webhooks/...`
+    #[serde(default)]
+    #[validate(custom(function = "discord_webhook"))]
+    pub discord_webhook_url: Option<String>,
+    /// The Slack incoming webhook URL, eg. `https://hooks.slack.com/services/...`
+    #[serde(default)]
+    #[validate(custom(function = "slack_webhook"))]
+    pub slack_webhook_url: Option<String>,
+}
@@ -0,0 +1,75 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use super::validation::{not_blank, starknet_address};
+
+/// The role of a member in an organization, ordered from the least to the most privileged.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Reads the workflows of the organization
+    Member,
+    /// Approves the workflows and allocations of the organization
+    Approver,
+    /// Manages the organization and its members
+    Owner,
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Member => write!(f, "member"),
+            Self::Approver => write!(f, "approver"),
+            Self::Owner => write!(f, "owner"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct OrganizationRequest {
+    /// The display name, eg. `Acme Inc.`
+    #[validate(length(max = 128), custom(function = "not_blank"))]
+    pub display_name: String,
+    /// The GitHub owners whose projects and workflows the organization owns, eg. `acme`.
+    #[serde(default)]
+    #[validate(length(max = 100))]
+    pub owners: Vec<String>,
+    /// The shared treasury funding the workflows of the organization.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(custom(function = "starknet_address"))]
+    pub treasury: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct MemberRequest {
+    /// The role of the member.
+    pub role: Role,
+    /// The account the member signs inquires and creates allocations with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(custom(function = "starknet_address"))]
+    pub address: Option<String>,
+    /// The API key the member reads and manages the workflows of the organization with,
+    /// only its digest is stored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, max = 256))]
+    pub api_key: Option<String>,
+}
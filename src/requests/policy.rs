@@ -0,0 +1,32 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, Validate)]
+pub struct DependencyPolicyRequest {
+    /// Package names never ranked, `*` matches any characters, eg. `acme-*`.
+    #[serde(default)]
+    #[validate(length(max = 1000))]
+    pub exclude: Vec<String>,
+    /// Package names always ranked, even when matching an exclude pattern.
+    #[serde(default)]
+    #[validate(length(max = 1000))]
+    pub include: Vec<String>,
+    /// Whether to exclude the dependencies flagged as suspicious, eg. likely typosquats,
+    /// unless matching an include pattern.
+    #[serde(default)]
+    pub exclude_flagged: bool,
+}
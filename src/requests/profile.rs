@@ -0,0 +1,125 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+/// The weights of the signals a dependency is ranked on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema, Validate)]
+pub struct Weights {
+    /// Share of the score kept by the first transitive level, between 0 and 1,
+    /// the curve deciding how the deeper levels decay, eg. `0.5` halves the score
+    /// of every transitive level on the exponential curve.
+    #[serde(default = "default_depth_decay")]
+    #[validate(range(min = 0.0, max = 1.0))]
+    pub depth_decay: f64,
+    /// The shape of the decay over the depth of the dependency tree.
+    #[serde(default)]
+    pub depth_curve: DecayCurve,
+    /// Weight of the download count, on a logarithmic scale.
+    #[serde(default = "default_weight")]
+    #[validate(range(min = 0.0))]
+    pub downloads: f64,
+    /// Weight of the number of active contributors, on a logarithmic scale.
+    #[serde(default = "default_weight")]
+    #[validate(range(min = 0.0))]
+    pub contributor_activity: f64,
+    /// Share of the score lost per known vulnerability, between 0 and 1.
+    #[serde(default = "default_vulnerability_penalty")]
+    #[validate(range(min = 0.0, max = 1.0))]
+    pub vulnerability_penalty: f64,
+    /// Weight of each kind of dependency.
+    #[serde(default)]
+    #[validate(nested)]
+    pub kinds: KindWeights,
+}
+
+/// How the score of transitive dependencies decays with their depth `d`, 1 for direct
+/// dependencies, with `k` the depth decay.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DecayCurve {
+    /// `k^(d - 1)`, keeping the same share at every level
+    #[default]
+    Exponential,
+    /// `1 / (1 + (1 / k - 1) * (d - 1))`, flattening out at deep levels
+    Hyperbolic,
+    /// `max(0, 1 - (1 - k) * (d - 1))`, reaching zero past `1 / (1 - k)` levels
+    Linear,
+}
+
+/// The weights of the kinds of dependencies, multiplying their score.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema, Validate)]
+pub struct KindWeights {
+    #[serde(default = "default_weight")]
+    #[validate(range(min = 0.0))]
+    pub runtime: f64,
+    #[serde(default = "default_dev_weight")]
+    #[validate(range(min = 0.0))]
+    pub dev: f64,
+    #[serde(default = "default_build_weight")]
+    #[validate(range(min = 0.0))]
+    pub build: f64,
+    #[serde(default = "default_optional_weight")]
+    #[validate(range(min = 0.0))]
+    pub optional: f64,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Self {
+            depth_decay: default_depth_decay(),
+            depth_curve: DecayCurve::default(),
+            downloads: default_weight(),
+            contributor_activity: default_weight(),
+            vulnerability_penalty: default_vulnerability_penalty(),
+            kinds: KindWeights::default(),
+        }
+    }
+}
+
+impl Default for KindWeights {
+    fn default() -> Self {
+        Self {
+            runtime: default_weight(),
+            dev: default_dev_weight(),
+            build: default_build_weight(),
+            optional: default_optional_weight(),
+        }
+    }
+}
+
+fn default_depth_decay() -> f64 {
+    0.5
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+fn default_vulnerability_penalty() -> f64 {
+    0.25
+}
+
+fn default_dev_weight() -> f64 {
+    0.1
+}
+
+fn default_build_weight() -> f64 {
+    0.25
+}
+
+fn default_optional_weight() -> f64 {
+    0.5
+}
@@ -0,0 +1,29 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::contracts::types::Address;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
+pub struct SignRequest {
+    /// The account address of the signer
+    #[schema(value_type = String)]
+    pub signer: Address,
+    /// The signature by the signer account of the hash of the inquire payload
+    #[validate(length(min = 1, max = 64))]
+    pub signature: Vec<String>,
+}
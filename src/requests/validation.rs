@@ -0,0 +1,215 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Validation of the request bodies.
+//!
+//! Request structs derive [`Validate`] to declare the format of their fields, and are
+//! extracted with [`ValidatedJson`], which rejects a request listing every invalid field
+//! with a 422 response, before it reaches the services.
+
+use std::borrow::Cow;
+
+use axum::{
+    extract::{rejection::JsonRejection, FromRequest, Request},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use validator::{Validate, ValidationError, ValidationErrors, ValidationErrorsKind};
+
+use crate::{
+    errors::ApiError, notifiers::webhook::WebhookChannel,
+    responses::validation::FieldErrorResponse, services::webhook::is_public_host,
+};
+
+/// The longest decimal amount, the digits of a `u256`.
+const MAX_AMOUNT_LEN: usize = 78;
+
+/// A JSON request body, validated once deserialized.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidatedJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state).await.map_err(|rejection| {
+            match rejection {
+                // A missing field or a field of the wrong type.
+                JsonRejection::JsonDataError(e) => {
+                    ApiError::InvalidRequest(vec![FieldErrorResponse {
+                        field: String::new(),
+                        message: e.body_text(),
+                    }])
+                    .into_response()
+                }
+                rejection => rejection.into_response(),
+            }
+        })?;
+        value
+            .validate()
+            .map_err(|errors| ApiError::InvalidRequest(field_errors(&errors)).into_response())?;
+        Ok(Self(value))
+    }
+}
+
+/// Flatten validation errors, nested structs and lists included, by JSON pointer.
+pub fn field_errors(errors: &ValidationErrors) -> Vec<FieldErrorResponse> {
+    let mut fields = Vec::new();
+    flatten(errors, "", &mut fields);
+    fields.sort_by(|a, b| a.field.cmp(&b.field));
+    fields
+}
+
+fn flatten(errors: &ValidationErrors, prefix: &str, fields: &mut Vec<FieldErrorResponse>) {
+    for (name, kind) in errors.errors() {
+        let pointer = format!("{prefix}/{name}");
+        match kind {
+            ValidationErrorsKind::Field(errors) => fields.extend(errors.iter().map(|error| {
+                FieldErrorResponse { field: pointer.clone(), message: message(error) }
+            })),
+            ValidationErrorsKind::Struct(errors) => flatten(errors, &pointer, fields),
+            ValidationErrorsKind::List(items) => {
+                for (index, errors) in items {
+                    flatten(errors, &format!("{pointer}/{index}"), fields);
+                }
+            }
+        }
+    }
+}
+
+/// Describe an error, from its parameters for the built-in validators.
+fn message(error: &ValidationError) -> String {
+    if let Some(message) = &error.message {
+        return message.to_string();
+    }
+    let param = |name: &str| error.params.get(name).map(Value::to_string);
+    match (error.code.as_ref(), param("min"), param("max")) {
+        ("length", Some(min), Some(max)) => format!("must have {min} to {max} characters or items"),
+        ("length", Some(min), None) => format!("must have at least {min} characters or items"),
+        ("length", None, Some(max)) => format!("must have at most {max} characters or items"),
+        ("range", Some(min), Some(max)) => format!("must be between {min} and {max}"),
+        ("range", Some(min), None) => format!("must be at least {min}"),
+        ("range", None, Some(max)) => format!("must be at most {max}"),
+        ("email", ..) => "must be an email address".to_string(),
+        ("url", ..) => "must be a URL".to_string(),
+        (code, ..) => format!("is invalid ({code})"),
+    }
+}
+
+fn invalid(code: &'static str, message: &'static str) -> ValidationError {
+    ValidationError::new(code).with_message(Cow::Borrowed(message))
+}
+
+/// A Starknet address, eg. `0x049d...`.
+pub fn starknet_address(address: &str) -> Result<(), ValidationError> {
+    let valid = address.strip_prefix("0x").is_some_and(|digits| {
+        (1..=64).contains(&digits.len()) && digits.chars().all(|c| c.is_ascii_hexdigit())
+    });
+    if !valid {
+        return Err(invalid("address", "must be a Starknet address, eg. `0x049d...`"));
+    }
+    Ok(())
+}
+
+/// A positive decimal amount, eg. `12.5`.
+pub fn amount(amount: &str) -> Result<(), ValidationError> {
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    let digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    let valid = !whole.is_empty()
+        && amount.len() <= MAX_AMOUNT_LEN
+        && digits(whole)
+        && digits(fraction)
+        && amount.chars().any(|c| c.is_ascii_digit() && c != '0');
+    if !valid {
+        return Err(invalid("amount", "must be a positive decimal amount, eg. `12.5`"));
+    }
+    Ok(())
+}
+
+/// A text which is not only whitespace.
+pub fn not_blank(text: &str) -> Result<(), ValidationError> {
+    if text.trim().is_empty() {
+        return Err(invalid("blank", "must not be blank"));
+    }
+    Ok(())
+}
+
+/// An HTTPS URL, eg. a webhook.
+pub fn https_url(url: &str) -> Result<(), ValidationError> {
+    let valid = url::Url::parse(url).is_ok_and(|url| url.scheme() == "https");
+    if !valid {
+        return Err(invalid("url", "must be an https URL"));
+    }
+    Ok(())
+}
+
+/// An HTTPS URL of a public host, eg. a webhook, which the server posts to.
+pub fn public_https_url(url: &str) -> Result<(), ValidationError> {
+    https_url(url)?;
+    if !url::Url::parse(url).is_ok_and(|url| is_public_host(&url)) {
+        return Err(invalid("url", "must not point to a loopback, private or link-local host"));
+    }
+    Ok(())
+}
+
+/// A Discord webhook URL, or a blank one unsetting the channel.
+pub fn discord_webhook(url: &str) -> Result<(), ValidationError> {
+    if !is_blank_or_webhook(WebhookChannel::Discord, url) {
+        return Err(invalid("url", "must be a Discord webhook URL"));
+    }
+    Ok(())
+}
+
+/// A Slack incoming webhook URL, or a blank one unsetting the channel.
+pub fn slack_webhook(url: &str) -> Result<(), ValidationError> {
+    if !is_blank_or_webhook(WebhookChannel::Slack, url) {
+        return Err(invalid("url", "must be a Slack incoming webhook URL"));
+    }
+    Ok(())
+}
+
+fn is_blank_or_webhook(channel: WebhookChannel, url: &str) -> bool {
+    let url = url.trim();
+    url.is_empty() || channel.is_valid_url(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn webhooks_only_point_to_their_service() {
+        assert!(discord_webhook("https://discord.com/api/webhooks/1/token").is_ok());
+        assert!(slack_webhook("https://hooks.slack.com/services/T0/B0/secret").is_ok());
+        assert!(discord_webhook("  ").is_ok());
+
+        for url in [
+            "https://169.254.169.254/api/webhooks/1/token",
+            "https://127.0.0.1/api/webhooks/1/token",
+            "https://discord.com.evil.example/api/webhooks/1/token",
+            "https://discord.com:8443/api/webhooks/1/token",
+            "http://discord.com/api/webhooks/1/token",
+            "https://hooks.slack.com/services/T0/B0/secret",
+        ] {
+            assert!(discord_webhook(url).is_err(), "{url} was accepted");
+        }
+        assert!(slack_webhook("https://discord.com/api/webhooks/1/token").is_err());
+    }
+}
@@ -14,9 +14,13 @@
 
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
+use validator::Validate;
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+use crate::contracts::types::Address;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
 pub struct WalletAddressRequest {
     /// The address of the wallet.
-    pub address: String,
+    #[schema(value_type = String)]
+    pub address: Address,
 }
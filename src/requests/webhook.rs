@@ -0,0 +1,32 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use super::validation::public_https_url;
+use crate::responses::webhook::WebhookEvent;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct WebhookRequest {
+    /// The URL the events are posted to, eg. `https://example.com/deprank`, on a public host
+    #[validate(length(max = 2048), custom(function = "public_https_url"))]
+    pub url: String,
+    /// The secret the payloads are signed with, shared with the receiver
+    #[validate(length(min = 16, max = 256))]
+    pub secret: String,
+    /// The events to post, every event if empty
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+}
@@ -13,21 +13,106 @@
 // limitations under the License.
 
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+use validator::Validate;
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+use super::validation::{amount, not_blank};
+use crate::responses::workflow::WorkflowState;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
 pub struct CreateWorkflowRequest {
     /// Source code repository
+    #[validate(length(max = 256), custom(function = "not_blank"))]
     pub repo: String,
     /// Git branch, eg. master or main
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, max = 256))]
     pub branch: Option<String>,
     /// Git tag, eg. v1.0
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, max = 256))]
     pub tag: Option<String>,
     /// A commit hash like rev = "4c59b707", or a named reference exposed by
     /// the remote repository such as rev = "refs/pull/493/head". What references
     /// are available varies by where the repo is hosted.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, max = 256))]
     pub rev: Option<String>,
+    /// The name of the ranking weight profile, `default` if unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, max = 64))]
+    pub profile: Option<String>,
+    /// The id of the ranking snapshot to allocate on, the latest one of the
+    /// repository if unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<Uuid>,
+    /// The total amount to allocate to the dependencies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(nested)]
+    pub budget: Option<Budget>,
+    /// Whether the lines of every analyzed file are attributed to the contributors who
+    /// last changed them, for contributor-level allocations. Slower, off by default
+    #[serde(default)]
+    pub deep_analysis: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, Validate)]
+pub struct Budget {
+    /// The amount, eg. `1000`
+    #[validate(custom(function = "amount"))]
+    pub amount: String,
+    /// The currency of the amount
+    #[serde(default)]
+    pub denomination: Denomination,
+    /// The payout token, a symbol like `STRK` or a token contract address
+    #[validate(length(min = 1, max = 66))]
+    pub token: String,
+    /// Whether the amount of failed allocations is re-allocated in a follow-up round,
+    /// rather than returned to the treasury
+    #[serde(default = "default_reallocate")]
+    pub reallocate_failed: bool,
+}
+
+fn default_reallocate() -> bool {
+    true
+}
+
+/// The currency an amount is expressed in.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema,
+)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Denomination {
+    /// Whole payout tokens
+    #[default]
+    Token,
+    /// US dollars, converted to payout tokens at execution time
+    Usd,
+}
+
+/// The filters of the workflow list, on top of the shared list parameters.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct WorkflowListParams {
+    /// Only the workflows in this state
+    pub state: Option<WorkflowState>,
+    /// Only the workflows of the repositories of this GitHub owner, eg. `deprank`
+    pub owner: Option<String>,
+    /// Only the workflows of this repository, eg. `deprank/backend`
+    pub repository: Option<String>,
+}
+
+impl WorkflowListParams {
+    /// Whether the workflow of the project, in the state, passes the filters. Owner and
+    /// repository names are compared case-insensitively, as GitHub does.
+    pub fn matches(&self, project: &str, state: WorkflowState) -> bool {
+        let owner = project.split('/').next().unwrap_or_default();
+        self.state.is_none_or(|expected| expected == state)
+            && self.owner.as_deref().is_none_or(|expected| expected.eq_ignore_ascii_case(owner))
+            && self
+                .repository
+                .as_deref()
+                .is_none_or(|expected| expected.eq_ignore_ascii_case(project))
+    }
 }
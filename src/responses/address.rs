@@ -0,0 +1,35 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::requests::address::Chain;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AddressBookResponse {
+    /// The GitHub username of the contributor
+    pub username: String,
+    /// The payout addresses, one per chain
+    pub addresses: Vec<AddressResponse>,
+    /// The chain the contributor prefers to be paid on
+    pub preferred: Option<Chain>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AddressResponse {
+    /// The chain of the address
+    pub chain: Chain,
+    /// The payout address on the chain
+    pub address: String,
+}
@@ -0,0 +1,73 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// An artifact of the analysis of a workflow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum ArtifactName {
+    /// The full dependency graph
+    #[serde(rename = "graph.json")]
+    Graph,
+    /// The software bill of materials, in the CycloneDX format
+    #[serde(rename = "sbom.cdx.json")]
+    Sbom,
+    /// The raw contributor statistics
+    #[serde(rename = "contributors.json")]
+    Contributors,
+}
+
+impl ArtifactName {
+    pub const ALL: [Self; 3] = [Self::Graph, Self::Sbom, Self::Contributors];
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Graph | Self::Contributors => "application/json",
+            Self::Sbom => "application/vnd.cyclonedx+json",
+        }
+    }
+}
+
+impl fmt::Display for ArtifactName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Graph => write!(f, "graph.json"),
+            Self::Sbom => write!(f, "sbom.cdx.json"),
+            Self::Contributors => write!(f, "contributors.json"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ArtifactResponse {
+    pub name: ArtifactName,
+    /// The media type of the artifact, eg. `application/json`
+    pub content_type: String,
+    /// The size of the artifact in bytes
+    pub size: u64,
+    /// When the artifact was stored
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ArtifactUrlResponse {
+    /// The download URL, relative to the API, valid without an API key until it expires
+    pub url: String,
+    /// When the URL expires
+    pub expires_at: DateTime<Utc>,
+}
@@ -0,0 +1,49 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TermsResponse {
+    /// The version of the document, eg. `2026-10-01`
+    pub version: String,
+    /// The title of the document
+    pub title: String,
+    /// The full text of the document
+    pub content: String,
+    /// The SHA-256 hash of the content, hex encoded
+    pub content_hash: String,
+    /// Whether these are the terms recipients accept before claiming
+    pub current: bool,
+    pub published_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AttestationResponse {
+    /// The account address of the recipient
+    pub address: String,
+    /// The GitHub username of the recipient, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// The version of the accepted terms
+    pub version: String,
+    /// The SHA-256 hash of the accepted content, hex encoded
+    pub content_hash: String,
+    /// The IP address the terms were accepted from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip: Option<String>,
+    pub accepted_at: DateTime<Utc>,
+}
@@ -0,0 +1,43 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::{dependency::Ecosystem, snapshot::RankingDiffResponse};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ContributionResponse {
+    /// The id of the snapshot the contribution was computed in
+    pub snapshot_id: Uuid,
+    /// The ecosystem of the dependency
+    pub ecosystem: Ecosystem,
+    /// The name of the dependency
+    pub name: String,
+    /// The version of the dependency
+    pub version: String,
+    /// The score of the dependency in the snapshot
+    pub score: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RecomputeResponse {
+    /// The id of the workflow
+    pub workflow_id: Uuid,
+    /// The number of allocations issued so far, pinned to the previous snapshot
+    pub pinned_allocations: usize,
+    /// The scores of the previous snapshot of the workflow against the recomputed one
+    pub diff: RankingDiffResponse,
+}
@@ -0,0 +1,29 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ContributorResponse {
+    /// GitHub username of the contributor
+    pub username: String,
+    /// Number of commits authored in the project
+    pub commits: u64,
+    /// Number of lines last changed by the contributor, at the analyzed commit
+    pub lines: u64,
+    /// The contribution score of the contributor within the project,
+    /// the share of the attributed lines between 0 and 1
+    pub score: f64,
+}
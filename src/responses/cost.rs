@@ -0,0 +1,76 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::requests::workflow::Denomination;
+
+/// A step of the execution of a workflow which costs transaction fees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CostStep {
+    /// Creating every dependency on chain
+    CreateDependency,
+    /// Creating the receipt of every dependency and recording it as a step
+    Receipts,
+    /// Paying out the approved allocations
+    Allocations,
+}
+
+impl fmt::Display for CostStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CreateDependency => write!(f, "create_dependency"),
+            Self::Receipts => write!(f, "receipts"),
+            Self::Allocations => write!(f, "allocations"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CostEstimateResponse {
+    /// The id of workflow
+    pub workflow_id: Uuid,
+    /// The token transaction fees are paid in, eg. `STRK`
+    pub fee_token: String,
+    /// The sum of the estimated fees of the steps, in the smallest unit of the fee token
+    pub estimated_fee: String,
+    /// The remaining steps, in execution order
+    pub steps: Vec<StepCostResponse>,
+    /// The amounts of the allocations not paid out yet
+    pub budget: Vec<BudgetCostResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct StepCostResponse {
+    pub step: CostStep,
+    /// The number of transactions of the step
+    pub transactions: usize,
+    /// The estimated fee in the smallest unit of the fee token, unless the estimation failed
+    pub estimated_fee: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BudgetCostResponse {
+    /// The token, a symbol like `STRK` or a token contract address
+    pub token: String,
+    /// The currency of the amount
+    pub denomination: Denomination,
+    /// The amount, eg. `1.5`
+    pub amount: String,
+}
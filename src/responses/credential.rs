@@ -0,0 +1,101 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::dependency::Ecosystem;
+
+/// A W3C verifiable credential asserting the funding of a dependency.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FundingCredential {
+    #[serde(rename = "@context")]
+    pub context: Vec<String>,
+    /// The id of the credential, eg. `urn:deprank:allocation:{workflow_id}:{allocation_id}`
+    pub id: String,
+    /// `VerifiableCredential` and `FundingCredential`
+    #[serde(rename = "type")]
+    pub kind: Vec<String>,
+    /// The issuer, signing the credential, eg. `did:web:deprank.xyz`
+    pub issuer: String,
+    pub valid_from: DateTime<Utc>,
+    pub credential_subject: FundingSubject,
+}
+
+/// The funding the credential asserts.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FundingSubject {
+    /// The funding project, eg. `deprank/backend`
+    pub project: String,
+    /// The funded dependency, if the allocation funds a package
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dependency: Option<FundedDependency>,
+    /// The GitHub username of the funded contributor, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contributor: Option<String>,
+    /// The address the amount was paid out to
+    pub recipient: String,
+    /// The amount paid out in whole tokens, eg. `12.5`
+    pub amount: String,
+    /// The token paid out, eg. `STRK`
+    pub token: String,
+    /// The chain of the transaction, eg. `SN_MAIN`
+    pub chain_id: String,
+    /// The hash of the payout transaction
+    pub transaction_hash: String,
+    pub workflow_id: Uuid,
+    pub allocation_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FundedDependency {
+    pub ecosystem: Ecosystem,
+    /// The name of the package, eg. `serde`
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CredentialResponse {
+    /// The credential, as signed in the JWT
+    pub credential: FundingCredential,
+    /// The credential secured as a JWT (`vc+jwt`), signed by the backend with EdDSA
+    pub jwt: String,
+}
+
+/// A public key verifying the credentials, as a JSON Web Key.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct KeyResponse {
+    /// Always `OKP`
+    pub kty: String,
+    /// Always `Ed25519`
+    pub crv: String,
+    /// Always `EdDSA`
+    pub alg: String,
+    /// Always `sig`
+    #[serde(rename = "use")]
+    pub key_use: String,
+    /// The id of the key, matching the `kid` header of the JWTs
+    pub kid: String,
+    /// The public key, base64url encoded
+    pub x: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct KeySetResponse {
+    pub keys: Vec<KeyResponse>,
+}
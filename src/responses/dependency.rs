@@ -0,0 +1,202 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// A package ecosystem, named after its registry or package manager.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Ecosystem {
+    /// Rust crates, from crates.io
+    #[default]
+    Cargo,
+    /// JavaScript packages, from the npm registry
+    Npm,
+    /// Python packages, from PyPI
+    Pypi,
+    /// Java packages, from Maven Central
+    Maven,
+}
+
+impl fmt::Display for Ecosystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cargo => write!(f, "cargo"),
+            Self::Npm => write!(f, "npm"),
+            Self::Pypi => write!(f, "pypi"),
+            Self::Maven => write!(f, "maven"),
+        }
+    }
+}
+
+/// How a project depends on a package, ordered from the least to the most essential.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyKind {
+    /// Only needed to build and run the tests, benchmarks or examples
+    Dev,
+    /// Only needed to build the project, eg. by a build script
+    Build,
+    /// Only needed by an optional feature
+    Optional,
+    /// Needed to run the project
+    #[default]
+    Runtime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DependencyResponse {
+    /// Package name, eg. serde
+    pub name: String,
+    /// Resolved version, eg. 1.0.228
+    pub version: String,
+    /// The ecosystem of the package
+    #[serde(default)]
+    pub ecosystem: Ecosystem,
+    /// SPDX license expression, eg. MIT OR Apache-2.0
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    /// Source code repository of the dependency
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    /// The registry the package resolves from, when known, eg.
+    /// `registry+https://github.com/rust-lang/crates.io-index`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registry: Option<String>,
+    /// How the project depends on the package
+    #[serde(default)]
+    pub kind: DependencyKind,
+    /// The rank score of the dependency within the project
+    pub score: f64,
+    /// How the score was computed, from the signals and the weight profile
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<ScoreExplanationResponse>,
+    /// Whether the policy of the owner excludes the dependency from funding
+    #[serde(default)]
+    pub excluded_by_policy: bool,
+    /// Why the dependency may be a supply chain attack, empty when nothing is suspicious
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flags: Vec<DependencyFlagResponse>,
+}
+
+/// Why a dependency may be a supply chain attack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyFlagKind {
+    /// The name closely matches the one of a popular package, eg. `serde_jsn`
+    Typosquat,
+    /// The package resolves from another registry than the one of its ecosystem
+    UnexpectedRegistry,
+    /// The package does not point at a source code repository
+    MissingRepository,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct DependencyFlagResponse {
+    /// Why the dependency is flagged
+    pub kind: DependencyFlagKind,
+    /// What was detected, eg. `close to serde_json`
+    pub detail: String,
+}
+
+/// The factors of a score, multiplied together before normalization over the project.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ScoreExplanationResponse {
+    /// The depth in the dependency tree, 1 for direct dependencies
+    pub depth: u32,
+    /// The factor of the depth, 1 for direct dependencies, per the decay curve
+    pub depth_factor: f64,
+    /// The factor of the dependency kind
+    pub kind_factor: f64,
+    /// The factor of the downloads and contributor activity, at least 1
+    pub popularity_factor: f64,
+    /// The factor of the known vulnerabilities, 1 without any
+    pub vulnerability_factor: f64,
+    /// The product of the factors
+    pub raw_score: f64,
+}
+
+/// The dependency graph of a project, from its latest snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DependencyGraphResponse {
+    /// The project, eg. `deprank/backend`, the id of the root node
+    pub project: String,
+    /// The id of the snapshot the graph is from
+    pub snapshot_id: Uuid,
+    /// The project and its dependencies
+    pub nodes: Vec<GraphNodeResponse>,
+    /// Which node depends on which
+    pub edges: Vec<GraphEdgeResponse>,
+}
+
+/// A node of a dependency graph, the project or one of its dependencies.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GraphNodeResponse {
+    /// The project, or the ecosystem and the name of the package, eg. `cargo:serde`
+    pub id: String,
+    /// Package name, eg. serde, unless the node is the project
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Resolved version, eg. 1.0.228, unless the node is the project
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// The ecosystem of the package, unless the node is the project
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ecosystem: Option<Ecosystem>,
+    /// How the project depends on the package, unless the node is the project
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<DependencyKind>,
+    /// The rank score of the package within the project, unless the node is the project
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+}
+
+/// An edge of a dependency graph, from a node to a package it depends on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct GraphEdgeResponse {
+    /// The id of the depending node
+    pub from: String,
+    /// The id of the package depended upon
+    pub to: String,
+}
@@ -0,0 +1,73 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::workflow::WorkflowState;
+
+/// A message sent to the WebSocket clients.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EventMessage {
+    /// The subscriptions of the connection, after a subscribe or unsubscribe message
+    Subscribed { workflows: Vec<Uuid>, projects: Vec<String> },
+    /// A change to a subscribed workflow
+    Event(Event),
+    /// The client missed events, it lagged too far behind
+    Lagged { missed: u64 },
+    /// A message of the client could not be understood
+    Error { message: String },
+}
+
+/// A change to a workflow, broadcast to the clients subscribed to it or to its project.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Event {
+    pub workflow_id: Uuid,
+    /// The project of the workflow, eg. deprank/backend, if the workflow is known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(flatten)]
+    pub kind: EventKind,
+    pub at: DateTime<Utc>,
+}
+
+/// What changed in a workflow.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum EventKind {
+    /// The analysis progressed, the workflow moved to another state
+    WorkflowState {
+        state: WorkflowState,
+        /// Why the workflow failed, when it did
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    /// The contributions of the workflow were computed again, from a new snapshot
+    Contributions {
+        snapshot_id: Uuid,
+        /// The number of ranked dependencies
+        dependencies: usize,
+    },
+    /// An allocation was approved, or its payout changed status
+    AllocationStatus {
+        allocation_id: String,
+        /// One of `approved`, `awaiting_claim`, `submitted`, `executed` or `failed`
+        status: String,
+        /// The payout transaction, once submitted
+        #[serde(skip_serializing_if = "Option::is_none")]
+        tx_hash: Option<String>,
+    },
+}
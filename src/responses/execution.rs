@@ -0,0 +1,48 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ExecutionPreviewResponse {
+    /// The id of workflow
+    pub workflow_id: Uuid,
+    /// The transactions which would pay out the approved allocations, in submission order
+    pub batches: Vec<PayoutBatchResponse>,
+    /// The approved allocations which cannot be paid out, with the reason
+    pub skipped: Vec<SkippedAllocationResponse>,
+    /// The sum of the estimated fees, in the smallest unit of the fee token
+    pub estimated_fee: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PayoutBatchResponse {
+    /// The ids of the allocations paid out by the transaction
+    pub allocation_ids: Vec<String>,
+    /// The number of calls of the transaction
+    pub calls: usize,
+    /// The calldata length of the transaction, in felts
+    pub calldata_len: usize,
+    /// The estimated fee in the smallest unit of the fee token, unless the estimation failed
+    pub estimated_fee: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SkippedAllocationResponse {
+    /// The id of the allocation
+    pub allocation_id: String,
+    /// Why the allocation cannot be paid out
+    pub reason: String,
+}
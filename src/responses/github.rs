@@ -0,0 +1,45 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// The repositories an installation of the GitHub App is granted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RepositorySelection {
+    /// Every repository of the account, including the future ones
+    All,
+    /// Only the selected repositories
+    Selected,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct InstallationResponse {
+    /// The id of the installation on GitHub
+    pub id: u64,
+    /// The account the App is installed on, eg. `deprank`
+    pub account: String,
+    /// Which repositories are granted
+    pub repository_selection: RepositorySelection,
+    /// The granted repositories, eg. `deprank/backend`, when selected
+    pub repositories: Vec<String>,
+    /// Whether the account suspended the installation
+    pub suspended: bool,
+    /// When the App was installed
+    pub installed_at: DateTime<Utc>,
+    /// When the installation or its grants last changed
+    pub updated_at: DateTime<Utc>,
+}
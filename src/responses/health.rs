@@ -0,0 +1,57 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    /// Every dependency of the service is reachable
+    Ok,
+    /// The service runs, but a dependency is unreachable
+    Degraded,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BreakerState {
+    /// The chain calls go through
+    Closed,
+    /// The chain calls fail fast until a probe of the provider succeeds
+    Open,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HealthResponse {
+    /// The overall status of the service
+    pub status: HealthStatus,
+    /// The connectivity to the chain provider
+    pub chain: ChainHealthResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ChainHealthResponse {
+    /// The state of the circuit breaker of the chain provider
+    pub state: BreakerState,
+    /// The failed chain calls since the last successful one
+    pub consecutive_failures: u32,
+    /// When the circuit breaker opened, while it is open
+    pub opened_at: Option<DateTime<Utc>>,
+    /// The number of times the circuit breaker opened since the service started
+    pub trips: u64,
+    /// The number of chain calls refused while the circuit breaker was open
+    pub rejected: u64,
+}
@@ -0,0 +1,69 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct LedgerResponse {
+    /// The id of workflow
+    pub workflow_id: Uuid,
+    /// The postings, oldest first
+    pub entries: Vec<LedgerEntryResponse>,
+    /// The totals of every account and token
+    pub balances: Vec<LedgerBalanceResponse>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Debit,
+    Credit,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct LedgerEntryResponse {
+    /// The account, eg. `treasury:{workflow_id}` or `recipient:{address}`
+    pub account: String,
+    /// Whether the account is debited or credited
+    pub side: Side,
+    /// The amount in whole tokens, eg. `1.5`
+    pub amount: String,
+    /// The token, a symbol like `STRK` or a token contract address
+    pub token: String,
+    /// The allocation paid out
+    pub allocation_id: String,
+    /// The payout transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_hash: Option<String>,
+    /// The payout transaction on the block explorer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_url: Option<String>,
+    /// When the entry was posted
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct LedgerBalanceResponse {
+    pub account: String,
+    pub token: String,
+    /// Sum of the debits
+    pub debit: String,
+    /// Sum of the credits
+    pub credit: String,
+    /// Debits minus credits
+    pub balance: String,
+}
@@ -0,0 +1,51 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::middlewares::trace::RequestId;
+
+/// The envelope of every list response.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ListResponse<T> {
+    /// The items of the current page
+    pub data: Vec<T>,
+    /// Where the current page is within the whole list
+    pub pagination: Pagination,
+    /// The id of the request, as returned in the `x-request-id` header
+    pub request_id: String,
+    /// When the response was generated
+    pub generated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Pagination {
+    /// Total number of items matching the filters
+    pub total: usize,
+    /// Maximum number of items per page
+    pub limit: usize,
+    /// Number of items skipped before the current page
+    pub offset: usize,
+    /// Cursor of the next page, absent on the last page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+impl<T> ListResponse<T> {
+    pub fn new(data: Vec<T>, pagination: Pagination, request_id: &RequestId) -> Self {
+        Self { data, pagination, request_id: request_id.0.clone(), generated_at: Utc::now() }
+    }
+}
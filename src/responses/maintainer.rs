@@ -0,0 +1,73 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{requests::workflow::Denomination, responses::dependency::Ecosystem};
+
+/// Where the claim of an allocation is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ClaimStatus {
+    /// Waiting to be claimed
+    Claimable,
+    /// Claim transaction submitted, waiting for confirmation
+    Submitted,
+    /// Paid out on chain
+    Claimed,
+    /// Payout reverted or gave up after too many attempts
+    Failed,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MaintainerClaimsResponse {
+    /// The GitHub username of the maintainer
+    pub username: String,
+    /// The wallet linked by the maintainer on the chain of the allocations, if any
+    pub wallet: Option<String>,
+    /// The allocations funding the maintainer, the claimable ones first
+    pub claims: Vec<MaintainerClaimResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MaintainerClaimResponse {
+    /// The id of the allocation on chain
+    pub allocation_id: String,
+    /// The workflow which created the allocation
+    pub workflow_id: Uuid,
+    /// The ecosystem of the funded package, if any
+    pub ecosystem: Option<Ecosystem>,
+    /// The name of the funded package, if any
+    pub package: Option<String>,
+    /// The amount, eg. `1.5`
+    pub amount: String,
+    /// The currency of the amount
+    pub denomination: Denomination,
+    /// The payout token, a symbol like `STRK` or a token contract address
+    pub token: String,
+    /// The address the allocation is paid out to
+    pub recipient: String,
+    pub status: ClaimStatus,
+    /// The hash of the claim transaction, once submitted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_hash: Option<String>,
+    /// The claim transaction on the block explorer, once submitted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_url: Option<String>,
+    /// Why the payout failed, if it did
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
@@ -0,0 +1,45 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MetadataResponse {
+    /// The version of the schema the metadata conforms to
+    pub schema_version: u32,
+    /// The metadata as written on chain, in canonical form
+    pub metadata_json: String,
+    /// The hash of `metadata_json` as stored on chain, hex encoded: Keccak-256 on EVM
+    /// networks, the Starknet Keccak on Starknet
+    pub metadata_hash: String,
+}
+
+/// The verification of the metadata of a receipt against the hash stored on chain.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MetadataVerificationResponse {
+    pub receipt_id: String,
+    /// Where the metadata document is stored, eg. `ipfs://<cid>`
+    pub metadata_uri: String,
+    /// The hash of the metadata stored on chain with the receipt
+    pub metadata_hash: String,
+    /// The hash of the stored document, recomputed from its canonical form
+    pub computed_hash: String,
+    /// Whether the stored document is in canonical form, as written by the backend
+    pub canonical: bool,
+    /// Whether the recomputed hash matches the hash stored on chain
+    pub matches: bool,
+    /// Whether the Receipt contract confirms the recomputed hash
+    pub verified_on_chain: bool,
+}
@@ -0,0 +1,37 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::notifiers::NotificationKind;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NotificationPreferencesResponse {
+    /// The GitHub username of the user
+    pub username: String,
+    /// The email address notifications are sent to
+    pub email: Option<String>,
+    /// The kinds of notifications never sent
+    pub opt_out: Vec<NotificationKind>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NotificationChannelsResponse {
+    /// The Discord webhook URL, without its secret part
+    pub discord_webhook_url: Option<String>,
+    /// The Slack incoming webhook URL, without its secret part
+    pub slack_webhook_url: Option<String>,
+    /// Whether the channels are inherited from the owner of the workflow
+    pub inherited: bool,
+}
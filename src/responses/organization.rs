@@ -0,0 +1,46 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::requests::organization::Role;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OrganizationResponse {
+    /// The name of the organization, eg. `acme`
+    pub name: String,
+    /// The display name, eg. `Acme Inc.`
+    pub display_name: String,
+    /// The GitHub owners whose projects and workflows the organization owns
+    pub owners: Vec<String>,
+    /// The shared treasury funding the workflows of the organization
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub treasury: Option<String>,
+    /// The members, by GitHub username
+    pub members: Vec<MemberResponse>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MemberResponse {
+    /// The GitHub username of the member
+    pub username: String,
+    /// The role of the member
+    pub role: Role,
+    /// The account the member signs inquires and creates allocations with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+}
@@ -0,0 +1,39 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::requests::workflow::Denomination;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PoolResponse {
+    /// The id of workflow
+    pub workflow_id: Uuid,
+    /// The released budget of every token
+    pub balances: Vec<PoolBalanceResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PoolBalanceResponse {
+    /// The token, a symbol like `STRK` or a token contract address
+    pub token: String,
+    /// The currency of the amounts
+    pub denomination: Denomination,
+    /// Amount released by failed allocations, to re-allocate in a follow-up round
+    pub available: String,
+    /// Amount released by failed allocations, returned to the treasury
+    pub clawed_back: String,
+}
@@ -30,4 +30,10 @@ pub struct ProjectResponse {
     /// are available varies by where the repo is hosted.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rev: Option<String>,
+    /// The commit hash the analysis was performed on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// The organization owning the project, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub organization: Option<String>,
 }
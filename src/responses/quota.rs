@@ -0,0 +1,39 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct LimitsResponse {
+    /// The analyses triggered today
+    pub analyses_per_day: QuotaResponse,
+    /// The workflows running at once
+    pub concurrent_workflows: QuotaResponse,
+    /// The maximum size of an analyzed repository in megabytes, unlimited if unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_repo_size: Option<u64>,
+    /// When the daily quotas reset
+    pub resets_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct QuotaResponse {
+    /// The amount consumed
+    pub used: u64,
+    /// The amount allowed, unlimited if unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+}
@@ -0,0 +1,68 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::responses::dependency::Ecosystem;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GlobalRankingResponse {
+    /// The position in the ranking, starting from 1
+    pub rank: usize,
+    /// The package, its repository when known, eg. `serde-rs/serde`, otherwise its name
+    pub package: String,
+    /// The PageRank score, all scores of a snapshot sum up to 1
+    pub score: f64,
+    /// Number of analyzed packages depending on it
+    pub dependents: usize,
+    /// The id of the ranking snapshot
+    pub snapshot_id: Uuid,
+    /// When the ranking snapshot was computed
+    pub computed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct EcosystemRankingResponse {
+    /// The position in the ranking, starting from 1
+    pub rank: usize,
+    /// The package name, eg. `serde`
+    pub package: String,
+    /// The ecosystem of the package
+    pub ecosystem: Ecosystem,
+    /// The sum of the scores of the package in the latest snapshot of every project
+    pub score: f64,
+    /// Number of projects depending on it
+    pub dependents: usize,
+    /// The share of the scores of the ecosystem, between 0 and 1
+    pub score_share: f64,
+    /// The payouts received, per token
+    pub funding: Vec<FundingResponse>,
+    /// The USD value of the payouts received, for the payouts whose USD price is known
+    pub funded_usd: String,
+    /// The share of the USD funding of the ecosystem, between 0 and 1
+    pub funding_share: f64,
+    /// The score share minus the funding share, highest for critical yet underfunded
+    /// packages
+    pub underfunding: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FundingResponse {
+    /// The token paid out, eg. `STRK`
+    pub token: String,
+    /// The amount received in whole tokens, eg. `1.5`
+    pub amount: String,
+}
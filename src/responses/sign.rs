@@ -0,0 +1,28 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SignResponse {
+    /// The id of the signature record on chain, unset until the transaction creating it
+    /// is executed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sign_id: Option<String>,
+    /// The hash of the inquire payload the signature is over
+    pub message_hash: String,
+    /// The hash of the signature, recorded on chain
+    pub signature_hash: String,
+}
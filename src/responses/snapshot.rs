@@ -0,0 +1,76 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::{requests::profile::Weights, responses::dependency::DependencyResponse};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SnapshotResponse {
+    /// The id of the snapshot
+    pub id: Uuid,
+    /// The project, eg. `deprank/backend`
+    pub project: String,
+    /// The commit hash which was analyzed
+    pub commit: String,
+    /// The version of the ranking algorithm
+    pub algorithm_version: String,
+    /// The name of the weight profile
+    pub profile: String,
+    /// The weights of the profile at the time of the run
+    pub weights: Weights,
+    /// When the ranking ran
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SnapshotDetailResponse {
+    #[serde(flatten)]
+    pub snapshot: SnapshotResponse,
+    /// The ranked dependencies
+    pub dependencies: Vec<DependencyResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RankingDiffResponse {
+    /// The snapshot compared from
+    pub from: SnapshotResponse,
+    /// The snapshot compared to
+    pub to: SnapshotResponse,
+    /// The dependencies only in the `to` snapshot
+    pub added: Vec<DependencyResponse>,
+    /// The dependencies only in the `from` snapshot
+    pub removed: Vec<DependencyResponse>,
+    /// The dependencies in both snapshots whose version or score changed,
+    /// the largest score change first
+    pub changed: Vec<ScoreChangeResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ScoreChangeResponse {
+    /// Package name, eg. serde
+    pub name: String,
+    /// The version in the `from` snapshot
+    pub from_version: String,
+    /// The version in the `to` snapshot
+    pub to_version: String,
+    /// The score in the `from` snapshot
+    pub from_score: f64,
+    /// The score in the `to` snapshot
+    pub to_score: f64,
+    /// The score change, negative when the score dropped
+    pub delta: f64,
+}
@@ -0,0 +1,109 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::contracts::workflow::StepType;
+
+/// The kind of a step of a funded dependency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum StepKind {
+    Receipt,
+    Inquire,
+    Sign,
+    Allocation,
+}
+
+impl From<StepType> for StepKind {
+    fn from(step_type: StepType) -> Self {
+        match step_type {
+            StepType::Receipt => Self::Receipt,
+            StepType::Inquire => Self::Inquire,
+            StepType::Sign => Self::Sign,
+            StepType::Allocation => Self::Allocation,
+        }
+    }
+}
+
+/// A step recorded on chain by the Workflow contract, attesting a transaction.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StepResponse {
+    /// The owner of the workflow on chain
+    pub github_owner: String,
+    /// The id of the workflow on chain
+    pub workflow_id: String,
+    /// The index of the dependency in the workflow
+    pub dependency_index: String,
+    /// The name of the dependency, once its creation is indexed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dependency: Option<String>,
+    /// The repository of the dependency, once its creation is indexed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repository_url: Option<String>,
+    /// The index of the step in the dependency
+    pub step_index: String,
+    pub kind: StepKind,
+    /// The transaction the step attests
+    pub tx_hash: String,
+    /// The transaction the step attests, on the block explorer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_url: Option<String>,
+    /// The id of the receipt, inquiry, signature or allocation of the step
+    pub related_entity_id: String,
+    pub recorded_at: DateTime<Utc>,
+    /// The block the step was recorded in
+    pub block_number: u64,
+    /// The transaction recording the step
+    pub recorded_in: String,
+    /// The transaction recording the step, on the block explorer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recorded_in_url: Option<String>,
+}
+
+/// A step of the transaction chain of a dependency, read from the Workflow contract.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ChainedStepResponse {
+    /// The index of the step in the dependency
+    pub step_index: String,
+    /// The index of the previous step, as recorded on chain
+    pub prev_step_index: String,
+    pub kind: StepKind,
+    /// The transaction the step attests
+    pub tx_hash: String,
+    /// The transaction the step attests, on the block explorer
+    pub tx_url: String,
+    /// The id of the receipt, inquiry, signature or allocation of the step
+    pub related_entity_id: String,
+    pub recorded_at: DateTime<Utc>,
+    /// Whether the step links to the previous one, and its transaction is at its position
+    /// in the chain
+    pub linked: bool,
+}
+
+/// The transaction chain of a dependency, oldest step first.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TransactionChainResponse {
+    /// The owner of the workflow on chain
+    pub github_owner: String,
+    /// The id of the workflow on chain
+    pub workflow_id: String,
+    /// The index of the dependency in the workflow
+    pub dependency_index: String,
+    pub steps: Vec<ChainedStepResponse>,
+    /// Whether every step is linked, the chain listing the transactions of all the steps
+    pub verified: bool,
+}
@@ -0,0 +1,67 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// A transaction sent on chain by the backend, as recorded in the journal.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TransactionResponse {
+    /// The id of the journal entry
+    pub id: Uuid,
+    /// The calls executed by the transaction
+    pub calls: Vec<TransactionCallResponse>,
+    /// The hash of the transaction, absent when it could not be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_hash: Option<String>,
+    /// The transaction on the block explorer, absent when it could not be sent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_url: Option<String>,
+    pub status: JournalStatus,
+    /// Why the transaction could not be sent or reverted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// When the transaction was sent
+    pub created_at: DateTime<Utc>,
+    /// When the status last changed
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TransactionCallResponse {
+    /// The called contract
+    pub to: String,
+    /// The selector of the called function
+    pub selector: String,
+    /// The first items of the calldata and their count, eg. `0x1, 0x2, 0x3, … (12 items)`
+    pub calldata: String,
+}
+
+/// The state of a journaled transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum JournalStatus {
+    /// Accepted by the node, not known to be included yet
+    Submitted,
+    /// Rejected before being sent, eg. by the fee estimation
+    Failed,
+    /// Included in a block and executed successfully
+    Succeeded,
+    /// Included in a block but reverted
+    Reverted,
+    /// Simulated in dry-run mode, never sent
+    Simulated,
+}
@@ -0,0 +1,41 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TreasuryResponse {
+    /// The chain of the treasury
+    pub chain: String,
+    /// The address of the account paying out allocations, to send funds to
+    pub address: String,
+    /// Whether the account holds every required amount
+    pub funded: bool,
+    /// The amount required of every token, including the fee headroom
+    pub requirements: Vec<TreasuryRequirementResponse>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TreasuryRequirementResponse {
+    /// The token, a symbol like `STRK` or a token contract address
+    pub token: String,
+    /// The address of the token contract
+    pub token_address: String,
+    /// The amount required, in whole tokens
+    pub required: String,
+    /// The balance of the account, in whole tokens
+    pub available: String,
+    /// The amount missing, in whole tokens
+    pub shortfall: String,
+}
@@ -0,0 +1,36 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UsageResponse {
+    /// The client, an API key (masked) or an IP address
+    pub client: String,
+    /// Number of API requests made
+    pub requests: u64,
+    /// Number of analysis jobs triggered
+    pub analyses: u64,
+    /// Chain fees spent, in the smallest unit of the fee token
+    pub fees: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UsageAggregateResponse {
+    /// The usage summed over all clients
+    pub total: UsageResponse,
+    /// The usage of every client, most active first
+    pub clients: Vec<UsageResponse>,
+}
@@ -0,0 +1,24 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// A field of a request failing validation.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FieldErrorResponse {
+    /// The JSON pointer of the field, eg. `/budget/amount`
+    pub field: String,
+    pub message: String,
+}
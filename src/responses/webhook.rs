@@ -0,0 +1,56 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// The events posted to the outbound webhooks.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema,
+)]
+pub enum WebhookEvent {
+    /// All allocations of a workflow were settled
+    #[serde(rename = "workflow.completed")]
+    WorkflowCompleted,
+    /// An allocation of a workflow was paid out
+    #[serde(rename = "allocation.executed")]
+    AllocationExecuted,
+    /// A signature answering an inquire was recorded
+    #[serde(rename = "inquire.responded")]
+    InquireResponded,
+}
+
+impl fmt::Display for WebhookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WorkflowCompleted => write!(f, "workflow.completed"),
+            Self::AllocationExecuted => write!(f, "allocation.executed"),
+            Self::InquireResponded => write!(f, "inquire.responded"),
+        }
+    }
+}
+
+/// A registered webhook, without its secret.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct WebhookResponse {
+    pub id: Uuid,
+    /// The URL the events are posted to
+    pub url: String,
+    /// The events posted, every event if empty
+    pub events: Vec<WebhookEvent>,
+    pub created_at: DateTime<Utc>,
+}
@@ -12,8 +12,78 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::fmt;
+
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Where a workflow is in its orchestration, from its creation to its completion.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkflowState {
+    /// Created, the repository not fetched yet
+    #[default]
+    Created,
+    /// Fetching the repository
+    Fetching,
+    /// Analyzing and ranking the dependencies
+    Analyzing,
+    /// Recording the dependencies and their receipts on chain
+    RecordingReceipts,
+    /// Waiting for the allocations to be signed
+    AwaitingSignatures,
+    /// Creating the allocations on chain
+    Allocating,
+    /// Every allocation created
+    Completed,
+    /// Stopped by an error, see the error of the workflow, until retried
+    Failed,
+    /// Cancelled, for good
+    Cancelled,
+}
+
+impl WorkflowState {
+    /// Whether nothing runs in the state, until a failed workflow is retried.
+    pub fn is_terminal(self) -> bool {
+        matches!(self, Self::Completed | Self::Failed | Self::Cancelled)
+    }
+
+    /// Whether a workflow may move from the state to `next`: each state to the following
+    /// one, any state not terminal to Failed, a failed workflow back to the state it is
+    /// retried from, and any state but Completed and Cancelled to Cancelled.
+    pub fn can_transition_to(self, next: WorkflowState) -> bool {
+        match (self, next) {
+            (Self::Created, Self::Fetching)
+            | (Self::Fetching, Self::Analyzing)
+            | (Self::Analyzing, Self::RecordingReceipts)
+            | (Self::RecordingReceipts, Self::AwaitingSignatures)
+            | (Self::AwaitingSignatures, Self::Allocating)
+            | (Self::Allocating, Self::Completed) => true,
+            (state, Self::Failed) => !state.is_terminal(),
+            (state, Self::Cancelled) => !matches!(state, Self::Completed | Self::Cancelled),
+            (Self::Failed, next) => !next.is_terminal(),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for WorkflowState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Created => "created",
+            Self::Fetching => "fetching",
+            Self::Analyzing => "analyzing",
+            Self::RecordingReceipts => "recording_receipts",
+            Self::AwaitingSignatures => "awaiting_signatures",
+            Self::Allocating => "allocating",
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+        })
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct WorkflowResponse {
@@ -30,4 +100,31 @@ pub struct WorkflowResponse {
     /// are available varies by where the repo is hosted.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rev: Option<String>,
+    /// The organization owning the workflow, sharing its treasury and approval rights
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub organization: Option<String>,
+    /// The id of the ranking snapshot the allocations are based on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot_id: Option<Uuid>,
+    /// Where the workflow is in its orchestration
+    #[serde(default)]
+    pub state: WorkflowState,
+}
+
+/// A workflow, and where it is in its orchestration.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct WorkflowSummaryResponse {
+    pub id: Uuid,
+    /// The project, eg. deprank/backend
+    pub project: String,
+    /// The organization owning the workflow
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub organization: Option<String>,
+    pub state: WorkflowState,
+    /// Why the workflow failed, when it did
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// When the workflow last changed state
+    pub updated_at: DateTime<Utc>,
 }
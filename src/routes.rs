@@ -21,10 +21,23 @@ use axum::{
 
 use crate::{context::Context, handlers::*};
 
+/// Every route, without the middlewares of its surface.
 pub fn build() -> Router<Arc<Context>> {
+    public().merge(callbacks()).merge(authenticated())
+}
+
+/// The public read-only API: project pages and rankings, served without authentication.
+pub fn public() -> Router<Arc<Context>> {
     Router::new()
-        .route("/v1/airdrops/{id}", get(airdrop::get))
-        .route("/v1/airdrops/{id}", post(airdrop::submit))
+        .route("/v1/artifacts/{id}/{name}", get(artifact::download))
+        //
+        .route("/v1/attestations", get(step::list))
+        .route("/v1/attestations/chain", get(step::chain))
+        //
+        .route("/v1/credentials/keys", get(credential::keys))
+        //
+        .route("/v1/health", get(health::get))
+        .route("/v1/metrics", get(health::metrics))
         //
         .route("/v1/projects/{owner}/{name}", get(project::get))
         //
@@ -33,18 +46,142 @@ pub fn build() -> Router<Arc<Context>> {
         //
         .route("/v1/projects/{owner}/{name}/dependencies", get(dependency::list))
         .route("/v1/projects/{owner}/{name}/dependencies/{dep}", get(dependency::get))
+        .route("/v1/projects/{owner}/{name}/graph", get(dependency::graph))
+        //
+        .route("/v1/projects/{owner}/{name}/ranking-diff", get(snapshot::diff))
+        .route("/v1/projects/{owner}/{name}/snapshots", get(snapshot::list))
+        .route("/v1/projects/{owner}/{name}/snapshots/{snapshot_id}", get(snapshot::get))
+        //
+        .route("/v1/ranking-profiles", get(profile::list))
+        .route("/v1/ranking-profiles/{name}", get(profile::get))
+        //
+        .route("/v1/rankings/ecosystems/{ecosystem}", get(ranking::ecosystem))
+        .route("/v1/rankings/global", get(ranking::global))
+        //
+        .route("/v1/receipts/{id}/verification", get(metadata::verify))
+        //
+        .route("/v1/schemas/dependency-metadata/{version}", get(metadata::schema))
+        //
+        .route("/v1/terms", get(attestation::current))
+        .route("/v1/terms/{version}", get(attestation::get))
+}
+
+/// The callbacks of GitHub, which authenticate with their own signatures.
+pub fn callbacks() -> Router<Arc<Context>> {
+    Router::new()
+        .route("/v1/github/setup", get(github::setup))
+        .route("/v1/github/webhooks", post(github::webhook))
+}
+
+/// The workflow and allocation API, requiring an API key.
+pub fn authenticated() -> Router<Arc<Context>> {
+    Router::new()
+        .route("/v1/airdrops/{id}", get(airdrop::get))
+        .route("/v1/airdrops/{id}", post(airdrop::submit))
+        //
+        .route("/v1/allocations/batch", post(allocation::batch))
+        //
+        .route("/v1/admin/attestations", get(attestation::list))
+        //
+        .route("/v1/admin/ranking-profiles/{name}", delete(profile::delete))
+        .route("/v1/admin/ranking-profiles/{name}", put(profile::put))
+        //
+        .route("/v1/admin/terms/{version}", put(attestation::publish))
+        //
+        .route("/v1/admin/usage", get(usage::aggregate))
         //
+        .route("/v1/attestations", post(attestation::accept))
+        //
+        .route("/v1/batch", post(batch::read))
+        //
+        .route("/v1/contributors/{username}/addresses", get(address::get))
+        .route("/v1/contributors/{username}/addresses", put(address::put))
+        .route("/v1/contributors/{username}/addresses/{chain}", delete(address::delete))
+        .route("/v1/contributors/{username}/identities", get(identity::get))
+        .route("/v1/contributors/{username}/identities", put(identity::merge))
+        .route("/v1/contributors/{username}/identities/{alias}", delete(identity::split))
+        .route("/v1/contributors/{username}/notifications", get(notification::get))
+        .route("/v1/contributors/{username}/notifications", put(notification::put))
+        //
+        .route("/v1/github/installations", get(github::list))
+        .route("/v1/github/installations/{id}", get(github::get))
+        //
+        .route("/v1/inquires/{id}/signs", post(sign::create))
+        //
+        .route("/v1/limits", get(quota::get))
+        //
+        .route("/v1/maintainers/{username}/claims", get(maintainer::list))
+        .route("/v1/maintainers/{username}/claims/{allocation_id}", get(maintainer::get))
+        .route("/v1/maintainers/{username}/claims/{allocation_id}/build", post(maintainer::build))
+        .route(
+            "/v1/maintainers/{username}/claims/{allocation_id}/execute",
+            post(maintainer::execute),
+        )
+        .route("/v1/maintainers/{username}/wallet", put(maintainer::link))
+        //
+        .route("/v1/organizations/{org}", delete(organization::delete))
+        .route("/v1/organizations/{org}", get(organization::get))
+        .route("/v1/organizations/{org}", put(organization::put))
+        .route("/v1/organizations/{org}/members/{username}", delete(organization::delete_member))
+        .route("/v1/organizations/{org}/members/{username}", put(organization::put_member))
+        //
+        .route("/v1/owners/{owner}/dependency-policy", delete(policy::delete))
+        .route("/v1/owners/{owner}/dependency-policy", get(policy::get))
+        .route("/v1/owners/{owner}/dependency-policy", put(policy::put))
+        .route("/v1/owners/{owner}/notification-channels", get(notification::get_owner_channels))
+        .route("/v1/owners/{owner}/notification-channels", put(notification::put_owner_channels))
+        //
+        .route("/v1/rate-limit", get(ratelimit::get))
+        //
+        .route("/v1/schemas/dependency-metadata/validate", post(metadata::validate))
+        //
+        .route("/v1/treasury/preflight", post(treasury::preflight))
+        //
+        .route("/v1/transactions", get(transaction::list))
+        //
+        .route("/v1/usage", get(usage::get))
+        //
+        .route("/v1/webhooks", post(webhook::create))
+        .route("/v1/webhooks/{id}", delete(webhook::delete))
+        //
+        .route("/v1/workflows", get(workflow::list))
         .route("/v1/workflows", post(workflow::create))
         .route("/v1/workflows/{id}", delete(workflow::delete))
         .route("/v1/workflows/{id}", get(workflow::get))
         //
         .route("/v1/workflows/{id}/allocations", get(allocation::list))
         .route("/v1/workflows/{id}/allocations/{allocation_id}", get(allocation::get))
+        .route("/v1/workflows/{id}/allocations/{allocation_id}/claim", post(claim::build))
+        .route("/v1/workflows/{id}/allocations/{allocation_id}/claim/execute", post(claim::execute))
+        .route("/v1/workflows/{id}/allocations/{allocation_id}/credential", get(credential::get))
+        //
+        .route("/v1/workflows/{id}/artifacts", get(artifact::list))
+        .route("/v1/workflows/{id}/artifacts/{name}", get(artifact::get))
+        //
+        .route("/v1/workflows/{id}/cancel", post(workflow::cancel))
         //
         .route("/v1/workflows/{id}/contributions", get(contribution::list))
+        .route("/v1/workflows/{id}/contributions/recompute", post(contribution::recompute))
         .route("/v1/workflows/{id}/contributions/{contribution_id}", get(contribution::get))
         //
+        .route("/v1/workflows/{id}/cost-estimate", get(cost::get))
+        //
+        .route("/v1/workflows/{id}/execution/preview", get(execution::preview))
+        //
+        .route("/v1/workflows/{id}/ledger", get(ledger::get))
+        //
+        .route("/v1/workflows/{id}/notification-channels", get(notification::get_workflow_channels))
+        .route("/v1/workflows/{id}/notification-channels", put(notification::put_workflow_channels))
+        //
+        .route("/v1/workflows/{id}/pool", get(pool::get))
+        //
+        .route("/v1/workflows/{id}/report", get(report::get))
+        //
+        .route("/v1/workflows/{id}/retry", post(workflow::retry))
+        //
         .route("/v1/workflows/{id}/wallet-address", delete(wallet::unbind))
         .route("/v1/workflows/{id}/wallet-address", put(wallet::bind))
+        //
+        .route("/v1/ws", get(event::subscribe))
     //
 }
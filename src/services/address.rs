@@ -0,0 +1,234 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::{
+    collections::{BTreeMap, HashMap},
+    future::Future,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    context::Context,
+    contracts::types::Address,
+    db::{self, Database, Write, WriteError},
+    errors::{ApiError, Result},
+    middlewares::ratelimit::ClientKey,
+    requests::address::{AddressBookRequest, Chain},
+    responses::address::{AddressBookResponse, AddressResponse},
+};
+
+#[derive(Clone, clap::Parser)]
+pub struct AddressConfig {
+    /// What to pay out to when a contributor has no address on the chain of the workflow.
+    #[clap(
+        long,
+        env = "DRK_ADDRESS_FALLBACK",
+        value_enum,
+        default_value_t = AddressFallback::Recipient
+    )]
+    pub address_fallback: AddressFallback,
+}
+
+/// The fallback when a contributor has no address on the chain of the workflow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum AddressFallback {
+    /// Pay out to the recipient the allocation was created with
+    Recipient,
+    /// Fail the allocation, so its amount is released
+    Fail,
+}
+
+/// The payout addresses of a contributor.
+#[derive(Debug, Clone, Default)]
+pub struct AddressBookEntry {
+    /// The addresses by chain, validated for their chain, which may not be the chain of
+    /// the contracts
+    pub addresses: BTreeMap<Chain, String>,
+    pub preferred: Option<Chain>,
+}
+
+/// The address books by lowercase GitHub username, written through to the database if
+/// there is one.
+#[derive(Clone, Default)]
+pub struct AddressBook {
+    entries: Arc<Mutex<HashMap<String, AddressBookEntry>>>,
+    db: Option<Database>,
+}
+
+impl AddressBook {
+    /// Load the address books from the database, in memory only without one.
+    pub async fn load(db: Option<&Database>) -> anyhow::Result<Self> {
+        let Some(db) = db else {
+            return Ok(Self::default());
+        };
+
+        let entries = db::address::load(db.pool()).await?.into_iter().collect();
+        Ok(Self { entries: Arc::new(Mutex::new(entries)), db: Some(db.clone()) })
+    }
+
+    /// Queue the address book of a contributor for the database, returning the future of
+    /// its write.
+    fn persist(
+        &self,
+        username: String,
+        entry: AddressBookEntry,
+    ) -> impl Future<Output = Result<(), WriteError>> {
+        let written = self.db.as_ref().map(|db| db.commit(Write::AddressBook(username, entry)));
+        async move {
+            match written {
+                Some(written) => written.await,
+                None => Ok(()),
+            }
+        }
+    }
+
+    /// Get the address of the contributor on the chain of the contracts, if registered.
+    pub fn resolve(&self, username: &str, chain: Chain) -> Option<Address> {
+        let entries = self.entries.lock().unwrap();
+        let address = entries.get(&username.to_lowercase())?.addresses.get(&chain)?;
+        address.parse().ok()
+    }
+
+    /// Set the address of the contributor on the chain, keeping the other ones.
+    pub async fn link(
+        &self,
+        username: &str,
+        chain: Chain,
+        address: Address,
+    ) -> Result<(), WriteError> {
+        let written = {
+            let mut entries = self.entries.lock().unwrap();
+            let entry = entries.entry(username.to_lowercase()).or_default();
+            entry.addresses.insert(chain, address.into_inner());
+            // Queued under the lock, so the changes are written in the order they were made.
+            self.persist(username.to_lowercase(), entry.clone())
+        };
+        written.await
+    }
+
+    fn get(&self, username: &str) -> AddressBookEntry {
+        self.entries.lock().unwrap().get(&username.to_lowercase()).cloned().unwrap_or_default()
+    }
+
+    async fn put(&self, username: &str, entry: AddressBookEntry) -> Result<(), WriteError> {
+        let written = {
+            let mut entries = self.entries.lock().unwrap();
+            if entry.addresses.is_empty() {
+                entries.remove(&username.to_lowercase());
+            } else {
+                entries.insert(username.to_lowercase(), entry.clone());
+            }
+            self.persist(username.to_lowercase(), entry)
+        };
+        written.await
+    }
+}
+
+pub struct AddressService;
+
+impl AddressService {
+    pub async fn get(ctx: Arc<Context>, username: &str) -> Result<AddressBookResponse> {
+        Ok(to_response(username, &ctx.addresses.get(username)))
+    }
+
+    /// Replace the address book of a contributor.
+    pub async fn put(
+        ctx: Arc<Context>,
+        key: &ClientKey,
+        username: &str,
+        req: AddressBookRequest,
+    ) -> Result<AddressBookResponse> {
+        authorize(&ctx, key)?;
+
+        let mut entry = AddressBookEntry::default();
+        for address in req.addresses {
+            validate(address.chain, &address.address)?;
+            if entry.addresses.insert(address.chain, address.address).is_some() {
+                return Err(ApiError::BadAddressRequest(format!(
+                    "more than one {} address",
+                    address.chain
+                )));
+            }
+        }
+        if let Some(preferred) = req.preferred {
+            if !entry.addresses.contains_key(&preferred) {
+                return Err(ApiError::BadAddressRequest(format!(
+                    "no address on the preferred chain {preferred}"
+                )));
+            }
+        }
+        entry.preferred = req.preferred;
+
+        ctx.addresses.put(username, entry.clone()).await?;
+        Ok(to_response(username, &entry))
+    }
+
+    /// Remove the address of a contributor on a chain.
+    pub async fn remove(
+        ctx: Arc<Context>,
+        key: &ClientKey,
+        username: &str,
+        chain: Chain,
+    ) -> Result<()> {
+        authorize(&ctx, key)?;
+
+        let mut entry = ctx.addresses.get(username);
+        if entry.addresses.remove(&chain).is_none() {
+            return Err(ApiError::NotFound);
+        }
+        if entry.preferred == Some(chain) {
+            entry.preferred = None;
+        }
+        ctx.addresses.put(username, entry).await?;
+        Ok(())
+    }
+}
+
+/// Only the admin key can change address books, until contributors can authenticate.
+fn authorize(ctx: &Context, key: &ClientKey) -> Result<()> {
+    key.authorize_admin(&ctx.config.admin_api_key)
+}
+
+/// Check the format of an address on a chain.
+fn validate(chain: Chain, address: &str) -> Result<()> {
+    let hex = |digits: &str| digits.chars().all(|c| c.is_ascii_hexdigit());
+    let valid = match chain {
+        Chain::Starknet => address
+            .strip_prefix("0x")
+            .is_some_and(|digits| (1..=64).contains(&digits.len()) && hex(digits)),
+        Chain::Evm => {
+            address.strip_prefix("0x").is_some_and(|digits| digits.len() == 40 && hex(digits))
+        }
+        // Base58 encoded 32 bytes public keys.
+        Chain::Solana => {
+            (32..=44).contains(&address.len())
+                && address.chars().all(|c| c.is_ascii_alphanumeric() && !"0OIl".contains(c))
+        }
+    };
+    if !valid {
+        return Err(ApiError::BadAddressRequest(format!("invalid {chain} address `{address}`")));
+    }
+    Ok(())
+}
+
+fn to_response(username: &str, entry: &AddressBookEntry) -> AddressBookResponse {
+    AddressBookResponse {
+        username: username.to_string(),
+        addresses: entry
+            .addresses
+            .iter()
+            .map(|(chain, address)| AddressResponse { chain: *chain, address: address.clone() })
+            .collect(),
+        preferred: entry.preferred,
+    }
+}
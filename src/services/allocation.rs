@@ -0,0 +1,395 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use tracing::{info, instrument, warn};
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    contracts::{
+        allocation::{AllocationContract, AllocationInput},
+        sign::SignContract,
+        token::{NativeToken, Token, TokenContract},
+        types::{Address, Hash, Id, Number},
+    },
+    db::{self, Database, Write, WriteError},
+    errors::{ApiError, Result},
+    requests::{allocation::CreateAllocationsRequest, organization::Role, workflow::Denomination},
+    responses::{
+        allocation::{CreateAllocationsResponse, CreatedAllocation},
+        dependency::Ecosystem,
+        event::EventKind,
+    },
+    services::{
+        distribution::DistributionService,
+        event::EventBus,
+        organization::{same_address, OrganizationService},
+    },
+};
+
+/// Where an allocation is in its payout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionStatus {
+    /// Approved and waiting to be paid out
+    Approved,
+    /// Reserved for the sponsored claim of its recipient, skipped by the execution worker
+    AwaitingClaim,
+    /// Payout transaction submitted, waiting for confirmation
+    Submitted,
+    /// Payout confirmed on chain
+    Executed,
+    /// Payout reverted or gave up after too many attempts
+    Failed,
+}
+
+impl ExecutionStatus {
+    /// The name of the status, as stored and sent to the WebSocket clients.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Approved => "approved",
+            Self::AwaitingClaim => "awaiting_claim",
+            Self::Submitted => "submitted",
+            Self::Executed => "executed",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// A package funded by allocations.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FundedPackage {
+    pub ecosystem: Ecosystem,
+    /// The name of the package, eg. `serde`
+    pub name: String,
+}
+
+/// An allocation tracked by the execution worker.
+#[derive(Debug, Clone)]
+pub struct AllocationRecord {
+    /// The id of the allocation on chain
+    pub id: Id,
+    pub workflow_id: Uuid,
+    /// The GitHub username of the contributor, whose address book is preferred
+    /// over the recipient
+    pub contributor: Option<String>,
+    /// The dependency the allocation funds, if any
+    pub package: Option<FundedPackage>,
+    /// The ranking snapshot the amount was computed from, pinned when the contributions
+    /// of the workflow are recomputed, the snapshot of the workflow until then
+    pub snapshot_id: Option<Uuid>,
+    pub recipient: Address,
+    /// The amount, eg. `1.5`
+    pub amount: Number,
+    /// The currency of the amount
+    pub denomination: Denomination,
+    pub token: Token,
+    /// The USD price of one token used to convert a USD amount, kept for auditing
+    pub usd_rate: Option<String>,
+    /// The amount paid out in whole tokens, once known
+    pub token_amount: Option<Number>,
+    pub status: ExecutionStatus,
+    /// The hash of the payout transaction, once submitted
+    pub tx_hash: Option<Hash>,
+    /// When the payout transaction was submitted
+    pub submitted_at: Option<Instant>,
+    /// When the payout was confirmed
+    pub executed_at: Option<Instant>,
+    /// Number of failed payout attempts
+    pub attempts: u32,
+    /// Earliest time of the next payout attempt
+    pub retry_at: Option<Instant>,
+    /// The last error, if any
+    pub error: Option<String>,
+    /// Whether the amount returns to the pool of the workflow on failure,
+    /// rather than to the treasury
+    pub reallocate: bool,
+}
+
+impl AllocationRecord {
+    /// Approve an allocation for payout.
+    pub fn approved(
+        id: Id,
+        workflow_id: Uuid,
+        recipient: Address,
+        amount: Number,
+        denomination: Denomination,
+        token: Token,
+    ) -> Self {
+        Self {
+            id,
+            workflow_id,
+            contributor: None,
+            package: None,
+            snapshot_id: None,
+            recipient,
+            amount,
+            denomination,
+            token,
+            usd_rate: None,
+            token_amount: None,
+            status: ExecutionStatus::Approved,
+            tx_hash: None,
+            submitted_at: None,
+            executed_at: None,
+            attempts: 0,
+            retry_at: None,
+            error: None,
+            reallocate: true,
+        }
+    }
+}
+
+/// In-memory allocations, by id, written through to the database if configured. Their
+/// status changes are published on the event bus.
+#[derive(Clone, Default)]
+pub struct AllocationStore {
+    records: Arc<Mutex<HashMap<Id, AllocationRecord>>>,
+    db: Option<Database>,
+    events: EventBus,
+}
+
+impl AllocationStore {
+    /// Load the allocations from the database, in memory only without one.
+    pub async fn load(db: Option<&Database>, events: EventBus) -> anyhow::Result<Self> {
+        let Some(db) = db else {
+            return Ok(Self { events, ..Self::default() });
+        };
+
+        let records = db::allocation::load(db.pool()).await?;
+        let records = records.into_iter().map(|record| (record.id.clone(), record)).collect();
+        Ok(Self { records: Arc::new(Mutex::new(records)), db: Some(db.clone()), events })
+    }
+
+    /// Add an allocation once written to the database, failing if it could not be.
+    pub async fn insert(&self, record: AllocationRecord) -> Result<(), WriteError> {
+        if let Some(db) = &self.db {
+            db.commit(Write::Allocation(record.clone())).await?;
+        }
+        self.publish(&record);
+        self.records.lock().unwrap().insert(record.id.clone(), record);
+        Ok(())
+    }
+
+    pub fn get(&self, id: &str) -> Option<AllocationRecord> {
+        self.records.lock().unwrap().get(id).cloned()
+    }
+
+    /// Get the allocations of a workflow.
+    pub fn list(&self, workflow_id: Uuid) -> Vec<AllocationRecord> {
+        self.filter(|record| record.workflow_id == workflow_id)
+    }
+
+    /// Get the allocations in the given status.
+    pub fn by_status(&self, status: ExecutionStatus) -> Vec<AllocationRecord> {
+        self.filter(|record| record.status == status)
+    }
+
+    /// Get the allocations funding a contributor, per `is_contributor`.
+    pub fn by_contributor(&self, is_contributor: impl Fn(&str) -> bool) -> Vec<AllocationRecord> {
+        self.filter(|record| record.contributor.as_deref().is_some_and(&is_contributor))
+    }
+
+    /// Apply `f` to the allocation, returning whether it exists once the change is
+    /// written to the database, failing if it could not be.
+    pub async fn update(
+        &self,
+        id: &Id,
+        f: impl FnOnce(&mut AllocationRecord),
+    ) -> Result<bool, WriteError> {
+        let written = {
+            let mut records = self.records.lock().unwrap();
+            let Some(record) = records.get_mut(id) else {
+                return Ok(false);
+            };
+            let status = record.status;
+            f(record);
+            if record.status != status {
+                self.publish(record);
+            }
+            // Queued under the lock, so the changes are written in the order they were made.
+            self.persist(record)
+        };
+        written.await?;
+        Ok(true)
+    }
+
+    fn filter(&self, f: impl Fn(&AllocationRecord) -> bool) -> Vec<AllocationRecord> {
+        self.records.lock().unwrap().values().filter(|record| f(record)).cloned().collect()
+    }
+
+    /// Move an allocation from the status `from` to `to`, returning whether it was in
+    /// `from`. The status is checked and changed under the lock, so only one of concurrent
+    /// callers, eg. a claim and the execution worker, moves it.
+    pub async fn swap_status(
+        &self,
+        id: &Id,
+        from: ExecutionStatus,
+        to: ExecutionStatus,
+    ) -> Result<bool, WriteError> {
+        let written = {
+            let mut records = self.records.lock().unwrap();
+            let Some(record) = records.get_mut(id).filter(|record| record.status == from) else {
+                return Ok(false);
+            };
+            record.status = to;
+            self.publish(record);
+            self.persist(record)
+        };
+        written.await?;
+        Ok(true)
+    }
+
+    /// Queue the allocation for the database, returning the future of its write.
+    fn persist(&self, record: &AllocationRecord) -> impl Future<Output = Result<(), WriteError>> {
+        let written = self.db.as_ref().map(|db| db.commit(Write::Allocation(record.clone())));
+        async move {
+            match written {
+                Some(written) => written.await,
+                None => Ok(()),
+            }
+        }
+    }
+
+    fn publish(&self, record: &AllocationRecord) {
+        self.events.publish(
+            record.workflow_id,
+            EventKind::AllocationStatus {
+                allocation_id: record.id.to_string(),
+                status: record.status.as_str().to_string(),
+                tx_hash: record.tx_hash.as_deref().map(str::to_string),
+            },
+        );
+    }
+}
+
+pub struct AllocationService;
+
+impl AllocationService {
+    /// Create the allocations of a payout round or an airdrop on chain, in one batch.
+    #[instrument(skip_all, fields(workflow_id = %req.workflow_id, count = req.allocations.len()))]
+    pub async fn create_batch(
+        ctx: &Context,
+        req: CreateAllocationsRequest,
+    ) -> Result<CreateAllocationsResponse> {
+        let workflow_id = req.workflow_id;
+        let sign_ids: Vec<Id> = req.allocations.iter().map(|item| item.sign_id.clone()).collect();
+
+        // The allocations of the workflows of an organization stand on the signatures of
+        // its approvers.
+        let workflow = ctx.workflows.find_by_chain_id(&workflow_id);
+        if let Some(organization) =
+            workflow.as_ref().and_then(|workflow| workflow.organization.clone())
+        {
+            let unique: BTreeSet<&Id> = sign_ids.iter().collect();
+            for sign_id in unique {
+                let sign = ctx
+                    .contract
+                    .get_sign_details(sign_id.clone())
+                    .await
+                    .map_err(|e| ApiError::chain(e, ApiError::ChainRpcError))?;
+                OrganizationService::authorize(
+                    ctx,
+                    Some(&organization),
+                    &sign.signer,
+                    Role::Approver,
+                )?;
+            }
+        }
+        // Amounts with more decimals than allocated would not add up to what was split.
+        let config = &ctx.config.distribution_config;
+        let bad_request = |e: anyhow::Error| ApiError::BadAllocationRequest(e.to_string());
+        for item in &req.allocations {
+            DistributionService::validate(config, &item.amount).map_err(bad_request)?;
+        }
+        if let Some(budget) = &req.budget {
+            let token = &req.allocations[0].token_address;
+            if req.allocations.iter().any(|item| !same_address(&item.token_address, token)) {
+                return Err(ApiError::BadAllocationRequest(
+                    "the allocations of a budget must be paid in the same token".to_string(),
+                ));
+            }
+            let amounts = req.allocations.iter().map(|item| item.amount.as_str());
+            DistributionService::check(config, budget, amounts).map_err(bad_request)?;
+        }
+
+        // A follow-up round takes its budget out of the pool first, so two rounds cannot
+        // spend the same released amount.
+        let pooled = match (req.from_pool, &workflow) {
+            (false, _) => None,
+            (true, None) => return Err(ApiError::NotFoundWorkflow(workflow_id.to_string())),
+            (true, Some(workflow)) => {
+                let amounts: Vec<(String, Number)> = req
+                    .allocations
+                    .iter()
+                    .map(|item| (pool_token(ctx, &item.token_address), item.amount.clone()))
+                    .collect();
+                ctx.pool.take(workflow.id, Denomination::Token, &amounts).await?;
+                Some((workflow.id, amounts))
+            }
+        };
+
+        let inputs = req
+            .allocations
+            .into_iter()
+            .map(|item| AllocationInput {
+                workflow_id: workflow_id.clone(),
+                sign_id: item.sign_id,
+                recipient: item.recipient,
+                amount: item.amount,
+                token_address: item.token_address,
+            })
+            .collect();
+
+        let ids = match ctx.contract.create_allocations_batch(inputs).await {
+            Ok(ids) => ids,
+            Err(e) => {
+                if let Some((workflow_id, amounts)) = &pooled {
+                    if let Err(e) =
+                        ctx.pool.put_back(*workflow_id, Denomination::Token, amounts).await
+                    {
+                        warn!(%workflow_id, "Failed to put the budget back into the pool: {e}");
+                    }
+                }
+                return Err(ApiError::chain(e, ApiError::ChainRpcError));
+            }
+        };
+        info!("Allocations created");
+
+        let allocations = sign_ids
+            .into_iter()
+            .zip(ids)
+            .map(|(sign_id, id)| CreatedAllocation {
+                sign_id: sign_id.to_string(),
+                allocation_id: id.map(Id::into_inner),
+            })
+            .collect();
+        Ok(CreateAllocationsResponse { allocations })
+    }
+}
+
+/// The token of the pool an allocation is paid from: the symbol of a native token, as
+/// released by the failed allocations paid in it, or the token contract address.
+fn pool_token(ctx: &Context, address: &Address) -> String {
+    [NativeToken::Strk, NativeToken::Eth]
+        .into_iter()
+        .find(|native| same_address(ctx.contract.native_token_address(*native).as_ref(), address))
+        .map_or_else(|| address.to_string(), |native| native.to_string())
+}
@@ -11,22 +11,332 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+//! Analysis of the dependencies of the fetched repositories.
+//!
+//! The packages are resolved from the lock files of the repository. The license and the
+//! repository of the packages whose manifests are not on disk are looked up in their
+//! registry, crates.io at most once per second as its crawler policy asks, and cached in
+//! the cache directory, since the metadata of a published version never changes.
 
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tokio::time::Instant;
+use tracing::{debug, instrument, warn};
 
-#[allow(dead_code)]
+use crate::{
+    analyzers::{self, maven::MAVEN_CENTRAL, npm::NPM_REGISTRY, AnalyzedDependency},
+    responses::dependency::Ecosystem,
+};
+
+/// The sources of the packages published on crates.io, as written in Cargo.lock
+const CRATES_IO_SOURCES: [&str; 2] =
+    ["registry+https://github.com/rust-lang/crates.io-index", "sparse+https://index.crates.io/"];
+
+const CRATES_IO_API: &str = "https://crates.io/api/v1/crates";
+
+/// The sources of the packages published on PyPI, its index or its files.
+const PYPI_SOURCES: [&str; 2] = ["https://pypi.org/", "https://files.pythonhosted.org/"];
+
+const PYPI_API: &str = "https://pypi.org/pypi";
+
+/// The hosts of the source code repositories linked from the PyPI projects and POMs.
+const CODE_HOSTS: [&str; 4] = ["github.com", "gitlab.com", "codeberg.org", "bitbucket.org"];
+
+/// The time between two requests to crates.io.
+const CRATES_IO_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The registries reject the requests without a user agent identifying the client.
+const USER_AGENT: &str =
+    concat!("deprank-backend/", env!("CARGO_PKG_VERSION"), " (+https://deprank.xyz)");
+
+/// The directory of the cache, hidden so it is never pruned as a checkout.
+const REGISTRY_CACHE_DIR: &str = ".deprank-registry";
+
+/// The license and repository of a version of a package, as published.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct PackageInfo {
+    license: Option<String>,
+    repository: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CrateResponse {
+    #[serde(rename = "crate")]
+    krate: CrateInfo,
+    #[serde(default)]
+    versions: Vec<CrateVersion>,
+}
+
+#[derive(Deserialize)]
+struct CrateInfo {
+    repository: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CrateVersion {
+    num: String,
+    license: Option<String>,
+}
+
+/// A registry the packages are looked up in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Registry {
+    CratesIo,
+    Npm,
+    Pypi,
+    MavenCentral,
+}
+
+impl Registry {
+    /// The registry the dependency is published on, if it can be looked up in it.
+    fn of(dependency: &AnalyzedDependency) -> Option<Self> {
+        let source = dependency.source.as_deref()?;
+        match dependency.ecosystem {
+            Ecosystem::Cargo if CRATES_IO_SOURCES.contains(&source) => Some(Self::CratesIo),
+            Ecosystem::Npm if source.starts_with(NPM_REGISTRY) => Some(Self::Npm),
+            Ecosystem::Maven if source == MAVEN_CENTRAL => Some(Self::MavenCentral),
+            Ecosystem::Pypi if PYPI_SOURCES.iter().any(|s| source.starts_with(s)) => {
+                Some(Self::Pypi)
+            }
+            _ => None,
+        }
+    }
+
+    /// The time between two requests to the registry.
+    fn interval(self) -> Duration {
+        match self {
+            Self::CratesIo => CRATES_IO_INTERVAL,
+            Self::Npm | Self::Pypi | Self::MavenCentral => Duration::ZERO,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct PypiResponse {
+    info: PypiInfo,
+}
+
+#[derive(Deserialize)]
+struct PypiInfo {
+    license: Option<String>,
+    license_expression: Option<String>,
+    home_page: Option<String>,
+    project_urls: Option<HashMap<String, String>>,
+}
+
+#[derive(Clone)]
 pub struct AnalyzerService {
     cache_dir: PathBuf,
+    client: reqwest::Client,
 }
 
 impl AnalyzerService {
     pub fn new(cache_dir: &Path) -> Self {
-        Self { cache_dir: cache_dir.to_path_buf() }
+        let client = reqwest::Client::builder()
+            .user_agent(USER_AGENT)
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+        Self { cache_dir: cache_dir.join(REGISTRY_CACHE_DIR), client }
+    }
+
+    /// Resolve the dependencies of the projects of every ecosystem in the repository
+    /// checked out in the directory, with their license and repository when published.
+    #[instrument(skip(self))]
+    pub async fn analyze(&self, dir: &Path) -> Result<Vec<AnalyzedDependency>> {
+        let root = dir.to_path_buf();
+        let projects = tokio::task::spawn_blocking(move || analyzers::discover(&root)).await??;
+        if projects.is_empty() {
+            return Err(anyhow!("Could not find the manifest of a supported ecosystem"));
+        }
+
+        // A project failing to be analyzed leaves the others, unless they all fail
+        let mut analyzed = Vec::new();
+        let mut failure = None;
+        for project in projects {
+            let (ecosystem, project_dir) = (project.ecosystem, project.dir.clone());
+            match tokio::task::spawn_blocking(move || project.analyze()).await? {
+                Ok(dependencies) => analyzed.push(dependencies),
+                Err(e) => {
+                    warn!(%ecosystem, ?project_dir, "Failed to analyze the project: {e}");
+                    failure = Some(e);
+                }
+            }
+        }
+        if let (true, Some(e)) = (analyzed.is_empty(), failure) {
+            return Err(e);
+        }
+        let mut dependencies = analyzers::merge(analyzed.into_iter().flatten().collect());
+
+        let mut last_requests: HashMap<Registry, Instant> = HashMap::new();
+        for dependency in &mut dependencies {
+            if dependency.license.is_some() && dependency.repository.is_some() {
+                continue;
+            }
+            let Some(registry) = Registry::of(dependency) else {
+                continue;
+            };
+
+            let info = match self.cached(dependency).await {
+                Some(info) => info,
+                None => {
+                    if let Some(last_request) = last_requests.get(&registry) {
+                        tokio::time::sleep_until(*last_request + registry.interval()).await;
+                    }
+                    last_requests.insert(registry, Instant::now());
+                    match self.lookup(registry, dependency).await {
+                        Ok(info) => {
+                            // A failure only costs another request later
+                            if let Err(e) = self.cache(dependency, &info).await {
+                                debug!("Failed to cache the package: {e}");
+                            }
+                            info
+                        }
+                        Err(e) => {
+                            warn!(name = %dependency.name, "Failed to look up the package: {e}");
+                            continue;
+                        }
+                    }
+                }
+            };
+            dependency.license = dependency.license.take().or(info.license);
+            dependency.repository = dependency.repository.take().or(info.repository);
+        }
+
+        debug!("Analyzed {} dependencies", dependencies.len());
+        Ok(dependencies)
+    }
+
+    /// Look up the license and repository of a version of a package in its registry.
+    async fn lookup(
+        &self,
+        registry: Registry,
+        dependency: &AnalyzedDependency,
+    ) -> Result<PackageInfo> {
+        match registry {
+            Registry::CratesIo => self.crates_io(dependency).await,
+            Registry::Npm => self.npm(dependency).await,
+            Registry::Pypi => self.pypi(dependency).await,
+            Registry::MavenCentral => self.maven_central(dependency).await,
+        }
+    }
+
+    /// Look up the license and repository of a version of a crate on crates.io.
+    async fn crates_io(&self, dependency: &AnalyzedDependency) -> Result<PackageInfo> {
+        let url = format!("{CRATES_IO_API}/{}", dependency.name);
+        let response: CrateResponse =
+            self.client.get(url).send().await?.error_for_status()?.json().await?;
+        let license = response
+            .versions
+            .into_iter()
+            .find(|version| version.num == dependency.version)
+            .and_then(|version| version.license);
+        Ok(PackageInfo { license, repository: response.krate.repository })
+    }
+
+    /// Look up the license and repository of a version of a package on the npm registry.
+    async fn npm(&self, dependency: &AnalyzedDependency) -> Result<PackageInfo> {
+        // Scoped names are escaped, eg. `@scope%2Fname`
+        let name = dependency.name.replace('/', "%2F");
+        let url = format!("{NPM_REGISTRY}{name}/{}", dependency.version);
+        let manifest: Value = self.client.get(url).send().await?.error_for_status()?.json().await?;
+        // Older manifests describe the license as an object
+        let license = match manifest.get("license") {
+            Some(Value::String(license)) => Some(license.clone()),
+            Some(license) => license.get("type").and_then(Value::as_str).map(str::to_string),
+            None => None,
+        };
+        let repository = manifest.get("repository").and_then(analyzers::npm::repository_url);
+        Ok(PackageInfo { license, repository })
     }
 
-    pub async fn analyze(&self, _dir: &Path) -> Result<()> {
-        todo!()
+    /// Look up the license and repository of a version of a package on PyPI.
+    async fn pypi(&self, dependency: &AnalyzedDependency) -> Result<PackageInfo> {
+        let url = format!("{PYPI_API}/{}/{}/json", dependency.name, dependency.version);
+        let response: PypiResponse =
+            self.client.get(url).send().await?.error_for_status()?.json().await?;
+        let info = response.info;
+
+        // Older projects put the whole license text in place of its name
+        let license = info.license_expression.or_else(|| {
+            info.license.filter(|license| {
+                !license.is_empty() && license.len() <= 64 && !license.contains('\n')
+            })
+        });
+
+        // The project links are labelled freely, eg. Source, Repository or Homepage
+        let mut urls: Vec<_> = info.project_urls.unwrap_or_default().into_iter().collect();
+        urls.sort_by_key(|(label, _)| {
+            let label = label.to_ascii_lowercase();
+            !["source", "repository", "code"].iter().any(|l| label.contains(l))
+        });
+        let repository = urls
+            .into_iter()
+            .map(|(_, url)| url)
+            .chain(info.home_page)
+            .find_map(|url| repository_url(&url));
+        Ok(PackageInfo { license, repository })
+    }
+
+    /// Look up the license and repository of a version of an artifact in its POM on Maven
+    /// Central.
+    async fn maven_central(&self, dependency: &AnalyzedDependency) -> Result<PackageInfo> {
+        let (group, artifact) = dependency
+            .name
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Invalid coordinates {}", dependency.name))?;
+        let version = &dependency.version;
+        let url = format!(
+            "{MAVEN_CENTRAL}{}/{artifact}/{version}/{artifact}-{version}.pom",
+            group.replace('.', "/")
+        );
+        let pom = self.client.get(url).send().await?.error_for_status()?.text().await?;
+        let (license, urls) = analyzers::maven::published_info(&pom)?;
+        let repository = urls.iter().find_map(|url| repository_url(url));
+        Ok(PackageInfo { license, repository })
+    }
+
+    /// The cached info of a version of a package, named after its hash since the names
+    /// and versions come from the analyzed repository, eg. `../../name`.
+    fn cache_path(&self, dependency: &AnalyzedDependency) -> PathBuf {
+        let key = format!("{}@{}", dependency.name, dependency.version);
+        self.cache_dir
+            .join(dependency.ecosystem.to_string())
+            .join(format!("{}.json", hex::encode(Sha256::digest(key.as_bytes()))))
+    }
+
+    async fn cached(&self, dependency: &AnalyzedDependency) -> Option<PackageInfo> {
+        let content = tokio::fs::read(self.cache_path(dependency)).await.ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+
+    async fn cache(&self, dependency: &AnalyzedDependency, info: &PackageInfo) -> io::Result<()> {
+        let path = self.cache_path(dependency);
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        tokio::fs::write(path, serde_json::to_vec(info)?).await
+    }
+}
+
+/// The repository a link of a project points at on a code host, eg.
+/// `https://github.com/user/repo` of `https://github.com/user/repo/issues`.
+fn repository_url(url: &str) -> Option<String> {
+    let url = url::Url::parse(url).ok()?;
+    let host = url.host_str()?.trim_start_matches("www.");
+    if !CODE_HOSTS.contains(&host) {
+        return None;
     }
+    let mut segments = url.path_segments()?.filter(|segment| !segment.is_empty());
+    let (owner, repo) = (segments.next()?, segments.next()?);
+    Some(format!("https://{host}/{owner}/{}", repo.trim_end_matches(".git")))
 }
@@ -0,0 +1,177 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Analysis artifacts, the large outputs of a workflow kept out of the API responses.
+//!
+//! Artifacts are files stored per workflow, like the full dependency graph or the SBOM.
+//! When a URL secret is configured, downloading an artifact issues a short-lived URL
+//! signed with it, which needs no API key, eg. to hand it to a browser or a CI job.
+//! Otherwise artifacts are streamed directly.
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use tokio::fs;
+use tracing::{debug, instrument};
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    errors::{ApiError, Result},
+    responses::artifact::{ArtifactName, ArtifactResponse, ArtifactUrlResponse},
+};
+
+#[derive(Clone, clap::Parser)]
+pub struct ArtifactConfig {
+    /// Base directory for storing the analysis artifacts of the workflows.
+    #[clap(long, env = "DRK_ARTIFACT_DIR", default_value = "/tmp/deprank/artifacts")]
+    pub artifact_dir: PathBuf,
+
+    /// The secret signing the artifact download URLs, artifacts are streamed if unset.
+    #[clap(long, env = "DRK_ARTIFACT_URL_SECRET")]
+    pub artifact_url_secret: Option<String>,
+
+    /// Seconds a signed artifact download URL is valid for.
+    #[clap(long, env = "DRK_ARTIFACT_URL_TTL", default_value_t = 300)]
+    pub artifact_url_ttl: i64,
+}
+
+impl ArtifactConfig {
+    /// The URL secret, unless signed URLs are disabled.
+    pub fn url_secret(&self) -> Option<&str> {
+        self.artifact_url_secret.as_deref().filter(|secret| !secret.is_empty())
+    }
+}
+
+/// An artifact opened for download.
+pub struct ArtifactFile {
+    pub name: ArtifactName,
+    pub file: fs::File,
+    pub size: u64,
+}
+
+pub struct ArtifactService;
+
+impl ArtifactService {
+    /// Store an artifact of a workflow, replacing the previous one.
+    #[instrument(skip_all, fields(%workflow_id, %name))]
+    pub async fn store(
+        ctx: &Context,
+        workflow_id: Uuid,
+        name: ArtifactName,
+        content: &[u8],
+    ) -> anyhow::Result<()> {
+        let path = Self::path(ctx, workflow_id, name);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).await?;
+        }
+        // Readers never see a partially written artifact
+        let partial = path.with_extension("partial");
+        fs::write(&partial, content).await?;
+        fs::rename(&partial, &path).await?;
+        debug!(size = content.len(), "Stored artifact");
+        Ok(())
+    }
+
+    /// List the artifacts of a workflow.
+    pub async fn list(ctx: &Context, workflow_id: Uuid) -> Result<Vec<ArtifactResponse>> {
+        let mut artifacts = Vec::new();
+        for name in ArtifactName::ALL {
+            let Ok(metadata) = fs::metadata(Self::path(ctx, workflow_id, name)).await else {
+                continue;
+            };
+            artifacts.push(ArtifactResponse {
+                name,
+                content_type: name.content_type().to_string(),
+                size: metadata.len(),
+                created_at: metadata.modified().ok().map(DateTime::<Utc>::from),
+            });
+        }
+        Ok(artifacts)
+    }
+
+    /// Issue a short-lived URL downloading an artifact without an API key.
+    pub async fn url(
+        ctx: &Context,
+        workflow_id: Uuid,
+        name: ArtifactName,
+    ) -> Result<ArtifactUrlResponse> {
+        let config = &ctx.config.artifact_config;
+        let secret = config.url_secret().ok_or(ApiError::InternalServerError)?;
+        Self::open(ctx, workflow_id, name).await?;
+
+        let expires_at = Utc::now() + Duration::seconds(config.artifact_url_ttl.max(1));
+        let expires = expires_at.timestamp();
+        let signature = sign(secret, workflow_id, name, expires);
+        Ok(ArtifactUrlResponse {
+            url: format!(
+                "/v1/artifacts/{workflow_id}/{name}?expires={expires}&signature={signature}"
+            ),
+            expires_at,
+        })
+    }
+
+    /// Open an artifact for download.
+    pub async fn open(
+        ctx: &Context,
+        workflow_id: Uuid,
+        name: ArtifactName,
+    ) -> Result<ArtifactFile> {
+        let not_found = |_| ApiError::NotFoundArtifact(format!("{workflow_id}/{name}"));
+        let file = fs::File::open(Self::path(ctx, workflow_id, name)).await.map_err(not_found)?;
+        let size = file.metadata().await.map_err(not_found)?.len();
+        Ok(ArtifactFile { name, file, size })
+    }
+
+    /// Open an artifact downloaded with a signed URL, unless the URL expired or was
+    /// altered.
+    pub async fn open_signed(
+        ctx: &Context,
+        workflow_id: Uuid,
+        name: ArtifactName,
+        expires: i64,
+        signature: &str,
+    ) -> Result<ArtifactFile> {
+        let secret = ctx.config.artifact_config.url_secret().ok_or(ApiError::NotFound)?;
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .map_err(|_| ApiError::InternalServerError)?;
+        mac.update(message(workflow_id, name, expires).as_bytes());
+        let signature = hex::decode(signature).unwrap_or_default();
+        if mac.verify_slice(&signature).is_err() {
+            return Err(ApiError::Forbidden("invalid artifact URL signature".to_string()));
+        }
+        if expires < Utc::now().timestamp() {
+            return Err(ApiError::Forbidden("expired artifact URL".to_string()));
+        }
+        Self::open(ctx, workflow_id, name).await
+    }
+
+    fn path(ctx: &Context, workflow_id: Uuid, name: ArtifactName) -> PathBuf {
+        ctx.config.artifact_config.artifact_dir.join(workflow_id.to_string()).join(name.to_string())
+    }
+}
+
+/// The signed part of an artifact URL.
+fn message(workflow_id: Uuid, name: ArtifactName, expires: i64) -> String {
+    format!("{workflow_id}/{name}:{expires}")
+}
+
+fn sign(secret: &str, workflow_id: Uuid, name: ArtifactName, expires: i64) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(message(workflow_id, name, expires).as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
@@ -0,0 +1,288 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Legal attestations of the recipient terms.
+//!
+//! The terms recipients accept are versioned documents published by the admin, never
+//! modified once published. When terms are published, a claim transaction is only built
+//! for a recipient who accepted the current version, recording who accepted which
+//! version, with the hash of its content, when and from which IP address, for the
+//! compliance exports. Both are kept in the database, a request failing rather than
+//! accepting terms which were not recorded.
+
+use std::{
+    future::Future,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    context::Context,
+    db::{self, Database, Write, WriteError},
+    errors::{ApiError, Result},
+    middlewares::ratelimit::ClientKey,
+    requests::attestation::{AttestationRequest, TermsRequest},
+    responses::attestation::{AttestationResponse, TermsResponse},
+    services::ledger::escape,
+};
+
+/// The maximum length of a terms document, in bytes.
+const MAX_CONTENT_LEN: usize = 256 * 1024;
+
+/// A published version of the terms.
+#[derive(Debug, Clone)]
+pub struct Terms {
+    pub version: String,
+    pub title: String,
+    pub content: String,
+    pub content_hash: String,
+    pub published_at: DateTime<Utc>,
+}
+
+/// The acceptance of a version of the terms by a recipient.
+#[derive(Debug, Clone)]
+pub struct Attestation {
+    /// The recipient address, lowercase
+    pub address: String,
+    pub username: Option<String>,
+    pub version: String,
+    pub content_hash: String,
+    pub ip: Option<IpAddr>,
+    pub accepted_at: DateTime<Utc>,
+}
+
+/// The terms and their attestations, written through to the database if there is one.
+#[derive(Clone, Default)]
+pub struct AttestationStore {
+    /// The published terms, oldest first
+    terms: Arc<Mutex<Vec<Terms>>>,
+    attestations: Arc<Mutex<Vec<Attestation>>>,
+    db: Option<Database>,
+}
+
+impl AttestationStore {
+    /// Load the terms and their attestations from the database, in memory only without
+    /// one.
+    pub async fn load(db: Option<&Database>) -> anyhow::Result<Self> {
+        let Some(db) = db else {
+            return Ok(Self::default());
+        };
+
+        let terms = db::attestation::load_terms(db.pool()).await?;
+        let attestations = db::attestation::load(db.pool()).await?;
+        Ok(Self {
+            terms: Arc::new(Mutex::new(terms)),
+            attestations: Arc::new(Mutex::new(attestations)),
+            db: Some(db.clone()),
+        })
+    }
+
+    /// Queue a change for the database, returning the future of its write.
+    fn persist(&self, write: Write) -> impl Future<Output = Result<(), WriteError>> {
+        let written = self.db.as_ref().map(|db| db.commit(write));
+        async move {
+            match written {
+                Some(written) => written.await,
+                None => Ok(()),
+            }
+        }
+    }
+
+    /// The version recipients must accept, the latest published.
+    pub fn current_version(&self) -> Option<String> {
+        self.terms.lock().unwrap().last().map(|terms| terms.version.clone())
+    }
+
+    /// Whether the recipient accepted the version of the terms.
+    pub fn has_accepted(&self, address: &str, version: &str) -> bool {
+        let address = address.to_lowercase();
+        let attestations = self.attestations.lock().unwrap();
+        attestations
+            .iter()
+            .any(|attestation| attestation.address == address && attestation.version == version)
+    }
+}
+
+pub struct AttestationService;
+
+impl AttestationService {
+    /// Get the current terms.
+    pub async fn current(ctx: Arc<Context>) -> Result<TermsResponse> {
+        let version = ctx
+            .attestations
+            .current_version()
+            .ok_or_else(|| ApiError::NotFoundTerms("no terms published".to_string()))?;
+        Self::get(ctx, &version).await
+    }
+
+    /// Get a version of the terms.
+    pub async fn get(ctx: Arc<Context>, version: &str) -> Result<TermsResponse> {
+        let terms = ctx.attestations.terms.lock().unwrap();
+        let current = terms.last().map(|terms| terms.version.clone());
+        terms
+            .iter()
+            .find(|terms| terms.version == version)
+            .map(|terms| to_terms_response(terms, current.as_deref() == Some(version)))
+            .ok_or_else(|| ApiError::NotFoundTerms(version.to_string()))
+    }
+
+    /// Publish a new version of the terms, becoming the current one, restricted to the
+    /// admin key.
+    pub async fn publish(
+        ctx: Arc<Context>,
+        key: &ClientKey,
+        version: &str,
+        req: TermsRequest,
+    ) -> Result<TermsResponse> {
+        key.authorize_admin(&ctx.config.admin_api_key)?;
+
+        let valid_version = (1..=64).contains(&version.len())
+            && version.chars().all(|c| c.is_ascii_alphanumeric() || ".-_".contains(c));
+        if !valid_version {
+            return Err(ApiError::BadTermsRequest(format!(
+                "invalid version `{version}`, use 1 to 64 letters, digits, `.`, `-` or `_`"
+            )));
+        }
+        if req.title.trim().is_empty() || req.content.trim().is_empty() {
+            return Err(ApiError::BadTermsRequest("empty title or content".to_string()));
+        }
+        if req.content.len() > MAX_CONTENT_LEN {
+            return Err(ApiError::BadTermsRequest(format!(
+                "content longer than {MAX_CONTENT_LEN} bytes"
+            )));
+        }
+
+        let (response, written) = {
+            let mut terms = ctx.attestations.terms.lock().unwrap();
+            // Attestations refer to the content of a version, which cannot change.
+            if terms.iter().any(|terms| terms.version == version) {
+                return Err(ApiError::BadTermsRequest(format!("version `{version}` is published")));
+            }
+            let published = Terms {
+                version: version.to_string(),
+                title: req.title.trim().to_string(),
+                content_hash: hex::encode(Sha256::digest(req.content.as_bytes())),
+                content: req.content,
+                published_at: Utc::now(),
+            };
+            let response = to_terms_response(&published, true);
+            let written = ctx.attestations.persist(Write::Terms(published.clone()));
+            terms.push(published);
+            (response, written)
+        };
+        if let Err(e) = written.await {
+            // Not published then, so it can be published again.
+            ctx.attestations.terms.lock().unwrap().retain(|terms| terms.version != version);
+            return Err(e.into());
+        }
+        Ok(response)
+    }
+
+    /// Record the acceptance of a version of the terms by a recipient.
+    pub async fn accept(
+        ctx: Arc<Context>,
+        ip: Option<IpAddr>,
+        req: AttestationRequest,
+    ) -> Result<AttestationResponse> {
+        let address = req.address.trim().to_lowercase();
+        if address.is_empty() {
+            return Err(ApiError::BadTermsRequest("empty address".to_string()));
+        }
+
+        let content_hash = {
+            let terms = ctx.attestations.terms.lock().unwrap();
+            terms
+                .iter()
+                .find(|terms| terms.version == req.version)
+                .map(|terms| terms.content_hash.clone())
+                .ok_or_else(|| ApiError::NotFoundTerms(req.version.clone()))?
+        };
+
+        let attestation = Attestation {
+            address,
+            username: req.username.map(|username| username.trim().to_lowercase()),
+            version: req.version,
+            content_hash,
+            ip,
+            accepted_at: Utc::now(),
+        };
+        let response = to_attestation_response(&attestation);
+        // Only counted as accepted once recorded for the compliance exports.
+        ctx.attestations.persist(Write::Attestation(attestation.clone())).await?;
+        ctx.attestations.attestations.lock().unwrap().push(attestation);
+        Ok(response)
+    }
+
+    /// Fail unless the recipient accepted the current terms, if any are published.
+    pub fn require(ctx: &Context, address: &str) -> Result<()> {
+        match ctx.attestations.current_version() {
+            Some(version) if !ctx.attestations.has_accepted(address, &version) => {
+                Err(ApiError::TermsNotAccepted(version))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Get every attestation, oldest first, restricted to the admin key.
+    pub async fn list(ctx: Arc<Context>, key: &ClientKey) -> Result<Vec<AttestationResponse>> {
+        key.authorize_admin(&ctx.config.admin_api_key)?;
+
+        let attestations = ctx.attestations.attestations.lock().unwrap();
+        Ok(attestations.iter().map(to_attestation_response).collect())
+    }
+
+    /// Render attestations as CSV.
+    pub fn to_csv(attestations: &[AttestationResponse]) -> String {
+        let mut csv = String::from("accepted_at,address,username,version,content_hash,ip\n");
+        for attestation in attestations {
+            let fields = [
+                attestation.accepted_at.to_rfc3339(),
+                attestation.address.clone(),
+                attestation.username.clone().unwrap_or_default(),
+                attestation.version.clone(),
+                attestation.content_hash.clone(),
+                attestation.ip.clone().unwrap_or_default(),
+            ];
+            let fields: Vec<String> = fields.iter().map(|field| escape(field)).collect();
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+fn to_terms_response(terms: &Terms, current: bool) -> TermsResponse {
+    TermsResponse {
+        version: terms.version.clone(),
+        title: terms.title.clone(),
+        content: terms.content.clone(),
+        content_hash: terms.content_hash.clone(),
+        current,
+        published_at: terms.published_at,
+    }
+}
+
+fn to_attestation_response(attestation: &Attestation) -> AttestationResponse {
+    AttestationResponse {
+        address: attestation.address.clone(),
+        username: attestation.username.clone(),
+        version: attestation.version.clone(),
+        content_hash: attestation.content_hash.clone(),
+        ip: attestation.ip.map(|ip| ip.to_string()),
+        accepted_at: attestation.accepted_at,
+    }
+}
@@ -0,0 +1,142 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use axum::{
+    body::Body,
+    http::{Extensions, Method, Request},
+};
+use futures::{future::join_all, StreamExt};
+use serde_json::Value;
+use tower::ServiceExt;
+
+use crate::{
+    context::Context,
+    errors::{ApiError, Result},
+    middlewares::ratelimit::{ClientKey, WindowKey},
+    requests::batch::BatchRequest,
+    responses::batch::{BatchItemResponse, BatchResponse},
+    routes,
+};
+
+/// The maximum number of paths in a single batch.
+const MAX_BATCH_SIZE: usize = 20;
+
+/// The maximum body size read from each sub-response.
+const MAX_BODY_SIZE: usize = 16 * 1024 * 1024;
+
+/// The maximum body size read from all the sub-responses of a batch.
+const MAX_TOTAL_BODY_SIZE: usize = 32 * 1024 * 1024;
+
+pub struct BatchService;
+
+impl BatchService {
+    /// Dispatch every path as a GET request through the API router,
+    /// concurrently, and collect the responses in order.
+    ///
+    /// Each sub-request carries the `extensions` of the batch request, so it sees
+    /// the same client, rate limit state and request id. Every sub-request is charged to
+    /// the rate limit and usage of the client, like the batch request itself.
+    pub async fn read(
+        ctx: Arc<Context>,
+        extensions: &Extensions,
+        req: &BatchRequest,
+    ) -> Result<BatchResponse> {
+        if req.paths.is_empty() || req.paths.len() > MAX_BATCH_SIZE {
+            return Err(ApiError::BadBatchRequest(format!(
+                "expected between 1 and {MAX_BATCH_SIZE} paths"
+            )));
+        }
+        for path in &req.paths {
+            if !path.starts_with("/v1/") || path.starts_with("/v1/batch") {
+                return Err(ApiError::BadBatchRequest(format!("unsupported path `{path}`")));
+            }
+        }
+
+        if let Some(window) = extensions.get::<WindowKey>() {
+            let (_, allowed) = ctx.rate_limiter.hit_many(&window.0, req.paths.len() as u32);
+            if !allowed {
+                return Err(ApiError::TooManyRequests);
+            }
+        }
+        if let Some(key) = extensions.get::<ClientKey>() {
+            for _ in &req.paths {
+                ctx.usage.record_request(key);
+            }
+        }
+
+        let budget = AtomicUsize::new(MAX_TOTAL_BODY_SIZE);
+        let router = routes::build().with_state(ctx);
+        let responses = join_all(req.paths.iter().map(|path| {
+            let router = router.clone();
+            let budget = &budget;
+            async move {
+                let mut request = Request::builder()
+                    .method(Method::GET)
+                    .uri(path)
+                    .body(Body::empty())
+                    .map_err(|e| ApiError::BadBatchRequest(e.to_string()))?;
+                *request.extensions_mut() = extensions.clone();
+
+                let response =
+                    router.oneshot(request).await.map_err(|_| ApiError::InternalServerError)?;
+                let status = response.status().as_u16();
+                let bytes = read_body(response.into_body(), budget).await?;
+                let body = if bytes.is_empty() {
+                    Value::Null
+                } else {
+                    serde_json::from_slice(&bytes)
+                        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).into()))
+                };
+
+                Ok(BatchItemResponse { path: path.clone(), status, body })
+            }
+        }))
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+
+        Ok(BatchResponse { responses })
+    }
+}
+
+/// Read a sub-response body, reserving its size from the `budget` left to the batch as it
+/// is buffered.
+async fn read_body(body: Body, budget: &AtomicUsize) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let mut chunks = body.into_data_stream();
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk.map_err(|_| ApiError::InternalServerError)?;
+        if bytes.len() + chunk.len() > MAX_BODY_SIZE {
+            return Err(ApiError::BadBatchRequest(format!(
+                "a response exceeds {MAX_BODY_SIZE} bytes"
+            )));
+        }
+        budget
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |left| {
+                left.checked_sub(chunk.len())
+            })
+            .map_err(|_| {
+                ApiError::BadBatchRequest(format!(
+                    "the responses exceed {MAX_TOTAL_BODY_SIZE} bytes in total"
+                ))
+            })?;
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
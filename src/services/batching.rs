@@ -0,0 +1,130 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Groups payouts into multicall transactions.
+//!
+//! Every transaction pays a base fee on top of its calls, so paying out many small
+//! allocations one by one is wasteful. Payouts are packed into the fewest transactions
+//! which stay under the calldata limit of the network and the configured fee limit.
+
+use std::collections::VecDeque;
+
+use num_bigint::BigUint;
+use tracing::{debug, instrument, warn};
+
+use crate::{
+    contracts::{
+        transaction::TransactionContract,
+        types::{Number, RawCall},
+    },
+    services::payout::PreparedPayout,
+};
+
+#[derive(Clone, clap::Parser)]
+pub struct BatchingConfig {
+    /// Maximum calldata length of a payout transaction, in felts.
+    #[clap(long, env = "DRK_BATCH_MAX_CALLDATA", default_value_t = 4000)]
+    pub batch_max_calldata: usize,
+
+    /// Maximum estimated fee of a payout transaction, in the smallest unit of the fee token.
+    #[clap(long, env = "DRK_BATCH_MAX_FEE")]
+    pub batch_max_fee: Option<u128>,
+}
+
+/// Payouts submitted together in a single transaction.
+#[derive(Debug, Clone)]
+pub struct PayoutBatch {
+    pub payouts: Vec<PreparedPayout>,
+    /// The estimated fee in the smallest unit of the fee token, unless the estimation failed
+    pub estimated_fee: Option<Number>,
+}
+
+impl PayoutBatch {
+    pub fn calls(&self) -> Vec<RawCall> {
+        self.payouts.iter().flat_map(|payout| payout.calls.clone()).collect()
+    }
+
+    /// The calldata length of the multicall, in felts.
+    pub fn calldata_len(&self) -> usize {
+        calldata_len(&self.payouts)
+    }
+}
+
+pub struct BatchingService;
+
+impl BatchingService {
+    /// Group payouts into the fewest transactions under the calldata and fee limits.
+    ///
+    /// Payouts are packed first-fit by decreasing calldata length, then batches whose
+    /// estimated fee exceeds the limit, or whose estimation fails, are split in halves
+    /// until they fit. A payout exceeding the limits on its own gets a batch of its own.
+    #[instrument(skip_all, fields(payouts = payouts.len()))]
+    pub async fn plan<C: TransactionContract>(
+        contract: &C,
+        config: &BatchingConfig,
+        payouts: Vec<PreparedPayout>,
+    ) -> Vec<PayoutBatch> {
+        let mut pending: VecDeque<_> = pack(payouts, config.batch_max_calldata).into();
+        let max_fee = config.batch_max_fee.map(BigUint::from);
+
+        let mut batches = vec![];
+        while let Some(mut payouts) = pending.pop_front() {
+            let calls = payouts.iter().flat_map(|payout| payout.calls.clone()).collect();
+            let estimated_fee = contract.estimate_fee(calls).await;
+            let fits = match (&estimated_fee, &max_fee) {
+                (Ok(fee), Some(max_fee)) => fee.parse::<BigUint>().is_ok_and(|fee| fee <= *max_fee),
+                (Ok(_), None) => true,
+                (Err(_), _) => false,
+            };
+
+            if !fits && payouts.len() > 1 {
+                debug!(payouts = payouts.len(), "Splitting payout batch");
+                let rest = payouts.split_off(payouts.len() / 2);
+                pending.push_front(rest);
+                pending.push_front(payouts);
+                continue;
+            }
+
+            let estimated_fee = estimated_fee
+                .inspect_err(|e| warn!("Failed to estimate the fee of a payout batch: {e}"))
+                .ok();
+            batches.push(PayoutBatch { payouts, estimated_fee });
+        }
+
+        batches
+    }
+}
+
+/// Pack payouts first-fit by decreasing calldata length, under the calldata limit.
+fn pack(mut payouts: Vec<PreparedPayout>, max_calldata: usize) -> Vec<Vec<PreparedPayout>> {
+    payouts.sort_by_key(|payout| std::cmp::Reverse(payout.calldata_len()));
+
+    let mut bins: Vec<(usize, Vec<PreparedPayout>)> = vec![];
+    for payout in payouts {
+        let len = payout.calldata_len();
+        match bins.iter_mut().find(|(used, _)| used + len <= max_calldata) {
+            Some((used, bin)) => {
+                *used += len;
+                bin.push(payout);
+            }
+            None => bins.push((calldata_len(&[]) + len, vec![payout])),
+        }
+    }
+
+    bins.into_iter().map(|(_, bin)| bin).collect()
+}
+
+fn calldata_len(payouts: &[PreparedPayout]) -> usize {
+    // The multicall starts with the number of calls.
+    1 + payouts.iter().map(PreparedPayout::calldata_len).sum::<usize>()
+}
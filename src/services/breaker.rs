@@ -0,0 +1,157 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The circuit breaker of the chain provider.
+//!
+//! Every call to the chain goes through the breaker. After a number of consecutive failed
+//! calls it opens, and the calls fail fast with `503 Service Unavailable` instead of waiting
+//! on a node which is down. While it is open, the probe worker checks the node periodically
+//! and closes the breaker as soon as the node answers again.
+
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::{
+    contracts::error::ContractError,
+    responses::health::{BreakerState, ChainHealthResponse},
+};
+
+#[derive(Clone, clap::Parser)]
+pub struct BreakerConfig {
+    /// Consecutive failed chain calls opening the circuit breaker, 0 to never open it
+    #[clap(long, env = "DRK_CHAIN_BREAKER_THRESHOLD", default_value_t = 5)]
+    pub chain_breaker_threshold: u32,
+
+    /// Seconds between two probes of the chain provider while the circuit breaker is open
+    #[clap(long, env = "DRK_CHAIN_PROBE_INTERVAL", default_value_t = 15)]
+    pub chain_probe_interval: u64,
+}
+
+/// A chain call refused while the circuit breaker is open.
+#[derive(Debug, Error)]
+#[error("chain connectivity degraded since {0}, retry later")]
+pub struct ChainUnavailable(pub DateTime<Utc>);
+
+#[derive(Default)]
+struct State {
+    /// The failed calls since the last successful one
+    failures: u32,
+    /// When the breaker opened, `None` while it is closed
+    opened_at: Option<DateTime<Utc>>,
+    /// The number of times the breaker opened
+    trips: u64,
+    /// The number of calls refused while the breaker was open
+    rejected: u64,
+}
+
+#[derive(Clone)]
+pub struct CircuitBreaker {
+    threshold: u32,
+    state: Arc<Mutex<State>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: &BreakerConfig) -> Self {
+        Self { threshold: config.chain_breaker_threshold, state: Arc::default() }
+    }
+
+    /// Fail fast while the breaker is open.
+    pub fn check(&self) -> Result<(), ChainUnavailable> {
+        let mut state = self.state.lock().unwrap();
+        match state.opened_at {
+            Some(since) => {
+                state.rejected += 1;
+                Err(ChainUnavailable(since))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Run a chain call unless the breaker is open, recording whether it failed.
+    ///
+    /// A call rejected for its input or reverted on chain still reached the chain, so it does
+    /// not count as a failure.
+    pub async fn call<T>(
+        &self,
+        call: impl Future<Output = anyhow::Result<T>>,
+    ) -> anyhow::Result<T> {
+        self.check()?;
+        let result = call.await;
+        self.record(match &result {
+            Ok(_) => true,
+            Err(e) => e.downcast_ref::<ContractError>().is_some_and(|e| !e.is_unreachable()),
+        });
+        result
+    }
+
+    /// Run a probe of the chain provider, even while the breaker is open, closing the
+    /// breaker when it succeeds.
+    pub async fn probe<T>(
+        &self,
+        call: impl Future<Output = anyhow::Result<T>>,
+    ) -> anyhow::Result<T> {
+        let result = call.await;
+        self.record(result.is_ok());
+        result
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.state.lock().unwrap().opened_at.is_some()
+    }
+
+    /// The state of the breaker, as reported by the health endpoint and the metrics.
+    pub fn status(&self) -> ChainHealthResponse {
+        let state = self.state.lock().unwrap();
+        ChainHealthResponse {
+            state: if state.opened_at.is_some() {
+                BreakerState::Open
+            } else {
+                BreakerState::Closed
+            },
+            consecutive_failures: state.failures,
+            opened_at: state.opened_at,
+            trips: state.trips,
+            rejected: state.rejected,
+        }
+    }
+
+    fn record(&self, success: bool) {
+        let mut state = self.state.lock().unwrap();
+        if success {
+            if let Some(since) = state.opened_at.take() {
+                info!(
+                    "Chain connectivity restored, closing the circuit breaker open since {since}"
+                );
+            }
+            state.failures = 0;
+            return;
+        }
+
+        state.failures += 1;
+        if state.opened_at.is_none() && self.threshold > 0 && state.failures >= self.threshold {
+            warn!(
+                "Chain connectivity degraded after {} consecutive failures, opening the circuit breaker",
+                state.failures
+            );
+            state.opened_at = Some(Utc::now());
+            state.trips += 1;
+        }
+    }
+}
@@ -0,0 +1,169 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! GitHub check runs summarizing the analysis of a commit.
+//!
+//! Once a commit is ranked, a `DepRank` check run is published on it with the number
+//! of ranked dependencies and of license issues, linking back to the project page. A
+//! dependency has a license issue when it declares no license, or only copyleft ones.
+
+use anyhow::anyhow;
+use chrono::Utc;
+use octocrab::params::checks::{CheckRunConclusion, CheckRunOutput, CheckRunStatus};
+use tracing::{info, instrument, warn};
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    responses::dependency::DependencyResponse,
+    services::{github::GitHubApp, snapshot::ProjectSnapshot},
+};
+
+/// The name of the check runs.
+const CHECK_NAME: &str = "DepRank";
+
+/// The number of top dependencies listed in the summary.
+const TOP_DEPENDENCIES: usize = 10;
+
+/// License identifiers requiring derived works to be distributed under the same terms.
+const COPYLEFT_LICENSES: &[&str] =
+    &["AGPL", "GPL", "LGPL", "MPL", "EPL", "EUPL", "CDDL", "OSL", "SSPL", "CC-BY-SA"];
+
+pub struct CheckRunService;
+
+impl CheckRunService {
+    /// Publish the check run of a snapshot in the background, when the GitHub App is
+    /// configured. Failures are only logged, eg. when the App is not installed.
+    pub fn publish(ctx: &Context, snapshot: &ProjectSnapshot) {
+        let Some(app) = ctx.github_app.clone() else {
+            return;
+        };
+        let (project, commit, snapshot_id) =
+            (snapshot.project.clone(), snapshot.commit.clone(), snapshot.id);
+        let (conclusion, output) = summarize(&snapshot.dependencies);
+
+        tokio::spawn(async move {
+            if let Err(e) = create(&app, &project, &commit, snapshot_id, conclusion, output).await {
+                warn!(%project, %commit, "Failed to publish the check run: {e:#}");
+            }
+        });
+    }
+}
+
+#[instrument(skip_all, fields(%project, %commit))]
+async fn create(
+    app: &GitHubApp,
+    project: &str,
+    commit: &str,
+    snapshot_id: Uuid,
+    conclusion: CheckRunConclusion,
+    output: CheckRunOutput,
+) -> anyhow::Result<()> {
+    let (owner, name) =
+        project.split_once('/').ok_or_else(|| anyhow!("Invalid project `{project}`"))?;
+    let octocrab = app.for_repo(owner, name).await?;
+    let run = octocrab
+        .checks(owner, name)
+        .create_check_run(CHECK_NAME, commit)
+        .details_url(app.project_url(project))
+        .external_id(snapshot_id.to_string())
+        .status(CheckRunStatus::Completed)
+        .conclusion(conclusion)
+        .completed_at(Utc::now())
+        .output(output)
+        .send()
+        .await?;
+    info!(check_run_id = %run.id, "Check run published");
+    Ok(())
+}
+
+/// The conclusion and output of the check run of the dependencies of a snapshot, neutral
+/// rather than failing on license issues, which are for the maintainers to judge.
+fn summarize(dependencies: &[DependencyResponse]) -> (CheckRunConclusion, CheckRunOutput) {
+    let mut ranked: Vec<_> =
+        dependencies.iter().filter(|dependency| !dependency.excluded_by_policy).collect();
+    ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+    let issues: Vec<_> = ranked
+        .iter()
+        .filter_map(|dependency| Some((*dependency, license_issue(dependency)?)))
+        .collect();
+
+    let title = format!(
+        "{CHECK_NAME}: {}, {}",
+        plural(ranked.len(), "dependency ranked", "dependencies ranked"),
+        plural(issues.len(), "license issue", "license issues"),
+    );
+
+    let total: f64 = ranked.iter().map(|dependency| dependency.score).sum();
+    let mut summary =
+        String::from("| Dependency | Version | License | Share |\n|---|---|---|---|\n");
+    for dependency in ranked.iter().take(TOP_DEPENDENCIES) {
+        let share = if total > 0.0 { dependency.score / total * 100.0 } else { 0.0 };
+        summary += &format!(
+            "| {} | {} | {} | {share:.1}% |\n",
+            escape(&dependency.name),
+            escape(&dependency.version),
+            escape(dependency.license.as_deref().unwrap_or("-")),
+        );
+    }
+    if ranked.len() > TOP_DEPENDENCIES {
+        summary += &format!("\nAnd {} more.\n", ranked.len() - TOP_DEPENDENCIES);
+    }
+
+    let text = (!issues.is_empty()).then(|| {
+        let mut text = String::from("### License issues\n\n");
+        for (dependency, issue) in &issues {
+            text += &format!(
+                "- `{}` {}: {issue}\n",
+                escape(&dependency.name),
+                escape(&dependency.version)
+            );
+        }
+        text
+    });
+
+    let conclusion =
+        if issues.is_empty() { CheckRunConclusion::Success } else { CheckRunConclusion::Neutral };
+    let output = CheckRunOutput { title, summary, text, annotations: vec![], images: vec![] };
+    (conclusion, output)
+}
+
+/// Why the license of a dependency needs a review, if it does.
+fn license_issue(dependency: &DependencyResponse) -> Option<String> {
+    let Some(license) = dependency.license.as_deref().filter(|license| !license.trim().is_empty())
+    else {
+        return Some("no license declared".to_string());
+    };
+
+    // Any alternative of an `OR` expression can be chosen, all of an `AND` one apply.
+    let copyleft = license.split(" OR ").all(|alternative| {
+        alternative.split(" AND ").any(|id| {
+            let id = id.trim_matches(|c: char| c == '(' || c == ')' || c.is_whitespace());
+            COPYLEFT_LICENSES.iter().any(|copyleft| {
+                id.strip_prefix(copyleft)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with(['-', '+']))
+            })
+        })
+    });
+    copyleft.then(|| format!("copyleft license {license}"))
+}
+
+fn plural(count: usize, one: &str, many: &str) -> String {
+    format!("{count} {}", if count == 1 { one } else { many })
+}
+
+/// Escape the characters breaking a Markdown table cell.
+fn escape(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\n', '\r'], " ")
+}
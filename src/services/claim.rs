@@ -0,0 +1,258 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{sync::Arc, time::Instant};
+
+use serde_json::{json, Value};
+use tracing::{info, instrument};
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    contracts::{
+        allocation::AllocationContract,
+        token::{NativeToken, TokenContract},
+        types::{Address, Hash},
+        Contract,
+    },
+    errors::{ApiError, Result},
+    requests::claim::{BuildClaimRequest, ExecuteClaimRequest},
+    responses::claim::{ClaimExecutedResponse, ClaimResponse},
+    services::{
+        allocation::{AllocationRecord, ExecutionStatus},
+        attestation::AttestationService,
+    },
+};
+
+#[derive(Clone, clap::Parser)]
+pub struct PaymasterConfig {
+    /// URL of the SNIP-29 paymaster, sponsoring claim transactions. Gasless claims
+    /// are disabled when unset.
+    #[clap(long, env = "DRK_PAYMASTER_URL")]
+    pub paymaster_url: Option<String>,
+
+    /// API key of the paymaster, if any.
+    #[clap(long, env = "DRK_PAYMASTER_API_KEY")]
+    pub paymaster_api_key: Option<String>,
+}
+
+/// A client of a SNIP-29 paymaster JSON-RPC endpoint.
+#[derive(Clone)]
+pub struct Paymaster {
+    client: reqwest::Client,
+    config: PaymasterConfig,
+}
+
+impl Paymaster {
+    pub fn new(config: &PaymasterConfig) -> Self {
+        Self { client: reqwest::Client::new(), config: config.clone() }
+    }
+
+    async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let url = self
+            .config
+            .paymaster_url
+            .as_deref()
+            .filter(|url| !url.is_empty())
+            .ok_or_else(|| ApiError::PaymasterUnavailable("not configured".to_string()))?;
+        let unavailable = |e: reqwest::Error| ApiError::PaymasterUnavailable(e.to_string());
+
+        let mut request = self.client.post(url).json(&json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        }));
+        if let Some(key) = self.config.paymaster_api_key.as_deref().filter(|key| !key.is_empty()) {
+            request = request.header("x-paymaster-api-key", key);
+        }
+        let mut body: Value =
+            request.send().await.map_err(unavailable)?.json().await.map_err(unavailable)?;
+
+        if let Some(error) = body.get("error") {
+            let message = error.get("message").and_then(Value::as_str).unwrap_or("unknown error");
+            return Err(ApiError::BadClaimRequest(format!("paymaster rejected claim: {message}")));
+        }
+        Ok(body["result"].take())
+    }
+}
+
+/// Sponsored claims, letting recipients without gas tokens claim their allocations.
+pub struct ClaimService;
+
+impl ClaimService {
+    /// Build the sponsored claim transaction, to be signed by the recipient. The
+    /// allocation awaits the claim from then on, the execution worker leaving it to the
+    /// recipient, unless building it fails.
+    #[instrument(skip_all, fields(%workflow_id, %allocation_id))]
+    pub async fn build(
+        ctx: Arc<Context>,
+        workflow_id: Uuid,
+        allocation_id: &str,
+        req: &BuildClaimRequest,
+    ) -> Result<ClaimResponse> {
+        let record = Self::claimable(&ctx, workflow_id, allocation_id, &req.address).await?;
+        AttestationService::require(&ctx, &req.address)?;
+
+        // Checked again under the lock, the execution worker may have paid it out since
+        let id = record.id.clone();
+        let reserved = record.status == ExecutionStatus::Approved;
+        if reserved
+            && !ctx
+                .allocations
+                .swap_status(&id, ExecutionStatus::Approved, ExecutionStatus::AwaitingClaim)
+                .await?
+        {
+            return Err(ApiError::BadClaimRequest("allocation is not claimable".to_string()));
+        }
+
+        let result = Self::build_transaction(&ctx, allocation_id, &req.address).await;
+        if result.is_err() && reserved {
+            ctx.allocations
+                .swap_status(&id, ExecutionStatus::AwaitingClaim, ExecutionStatus::Approved)
+                .await?;
+        }
+        result
+    }
+
+    /// Build the claim transaction of an allocation with the paymaster.
+    async fn build_transaction(
+        ctx: &Context,
+        allocation_id: &str,
+        address: &str,
+    ) -> Result<ClaimResponse> {
+        let call = allocation_id
+            .parse()
+            .map_err(anyhow::Error::from)
+            .and_then(|id| ctx.contract.claim_call(id))
+            .map_err(|e| {
+                ApiError::BadClaimRequest(format!("invalid allocation `{allocation_id}`: {e}"))
+            })?;
+        let result = ctx
+            .paymaster
+            .request(
+                "paymaster_buildTransaction",
+                json!({
+                    "transaction": {
+                        "type": "invoke",
+                        "invoke": { "user_address": address, "calls": [call] },
+                    },
+                    "parameters": Self::parameters(),
+                }),
+            )
+            .await?;
+
+        let typed_data = result
+            .get("typed_data")
+            .cloned()
+            .ok_or_else(|| ApiError::PaymasterUnavailable("no typed data returned".to_string()))?;
+
+        Ok(ClaimResponse { allocation_id: allocation_id.to_string(), typed_data })
+    }
+
+    /// Submit the signed claim transaction through the paymaster, once built.
+    #[instrument(skip_all, fields(%workflow_id, %allocation_id))]
+    pub async fn execute(
+        ctx: Arc<Context>,
+        workflow_id: Uuid,
+        allocation_id: &str,
+        req: &ExecuteClaimRequest,
+    ) -> Result<ClaimExecutedResponse> {
+        let record = Self::claimable(&ctx, workflow_id, allocation_id, &req.address).await?;
+        if record.status != ExecutionStatus::AwaitingClaim {
+            return Err(ApiError::BadClaimRequest("build the claim first".to_string()));
+        }
+        // The attestation may have been revoked since the claim was built
+        AttestationService::require(&ctx, &req.address)?;
+
+        let result = ctx
+            .paymaster
+            .request(
+                "paymaster_executeTransaction",
+                json!({
+                    "transaction": {
+                        "type": "invoke",
+                        "invoke": {
+                            "user_address": req.address,
+                            "typed_data": req.typed_data,
+                            "signature": req.signature,
+                        },
+                    },
+                    "parameters": Self::parameters(),
+                }),
+            )
+            .await?;
+
+        let tx_hash: Hash = result
+            .get("transaction_hash")
+            .and_then(Value::as_str)
+            .and_then(|tx_hash| tx_hash.parse().ok())
+            .ok_or_else(|| ApiError::PaymasterUnavailable("no transaction hash".to_string()))?;
+        info!(%tx_hash, "Claim submitted");
+
+        // The execution worker follows the transaction from now on.
+        ctx.allocations
+            .update(&record.id, |record| {
+                record.status = ExecutionStatus::Submitted;
+                record.tx_hash = Some(tx_hash.clone());
+                record.submitted_at = Some(Instant::now());
+            })
+            .await?;
+
+        Ok(ClaimExecutedResponse {
+            tx_url: ctx.contract.explorer_tx_url(&tx_hash),
+            tx_hash: tx_hash.into_inner(),
+        })
+    }
+
+    fn parameters() -> Value {
+        json!({ "version": "0x1", "fee_mode": { "mode": "sponsored" } })
+    }
+
+    /// Check that the allocation waits for a payout to `address`, or its claim, and that
+    /// the recipient cannot pay for gas on their own.
+    async fn claimable(
+        ctx: &Context,
+        workflow_id: Uuid,
+        allocation_id: &str,
+        address: &Address,
+    ) -> Result<AllocationRecord> {
+        let record = ctx
+            .allocations
+            .get(allocation_id)
+            .filter(|record| record.workflow_id == workflow_id)
+            .ok_or_else(|| ApiError::NotFoundAllocation(allocation_id.to_string()))?;
+
+        if !matches!(record.status, ExecutionStatus::Approved | ExecutionStatus::AwaitingClaim) {
+            return Err(ApiError::BadClaimRequest("allocation is not claimable".to_string()));
+        }
+        if !record.recipient.eq_ignore_ascii_case(address) {
+            return Err(ApiError::BadClaimRequest("address is not the recipient".to_string()));
+        }
+
+        let gas_token = ctx.contract.native_token_address(NativeToken::Strk);
+        let balance = ctx
+            .contract
+            .balance_of(gas_token, address.clone())
+            .await
+            .map_err(|e| ApiError::PaymasterUnavailable(e.to_string()))?;
+        if balance != "0" {
+            return Err(ApiError::BadClaimRequest(
+                "only recipients without gas tokens are sponsored".to_string(),
+            ));
+        }
+
+        Ok(record)
+    }
+}
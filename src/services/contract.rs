@@ -15,41 +15,163 @@
 use crate::{
     config::Config,
     contracts::{
-        allocation::{Allocation, AllocationContract, Status as AllocationStatus},
-        impls::starknet::StarknetContract,
+        allocation::{Allocation, AllocationContract, AllocationInput, Status as AllocationStatus},
         inquire::{Inquire, InquireContract},
         receipt::{Receipt, ReceiptContract, ReceiptMetadata},
         sign::{Sign, SignContract},
+        token::{NativeToken, TokenContract},
+        transaction::{TransactionContract, TransactionStatus},
         types::*,
-        workflow::{Dependency, Step, StepType, Workflow, WorkflowContract},
+        workflow::{
+            Dependency, DependencyReceipt, Step, StepType, TransactionChain, Workflow,
+            WorkflowContract, WorkflowEventPage,
+        },
         Contract,
     },
+    services::{breaker::CircuitBreaker, transaction::TransactionJournal},
 };
 
+#[cfg(all(feature = "evm", not(feature = "mock")))]
+use crate::contracts::impls::evm::EvmContract;
+#[cfg(feature = "mock")]
+use crate::contracts::impls::mock::MockContract;
+#[cfg(not(any(feature = "evm", feature = "mock")))]
+use crate::contracts::impls::starknet::StarknetContract;
+
 use anyhow::Result;
+use async_trait::async_trait;
+
+/// The most items read from a contract in a single call, to stay within the response limits
+/// of RPC providers.
+const READ_PAGE_SIZE: u64 = 100;
 
-/// A service that provides contract operations by wrapping a Starknet contract implementation.
+/// A service that provides contract operations by wrapping a contract implementation.
 ///
-/// This struct acts as a facade to the underlying Starknet contract, providing methods
-/// for various contract operations like allocation, inquiry, receipt, signing, and workflow
-/// management. It implements multiple contract traits to provide a unified interface for all
-/// contract operations.
+/// This struct acts as a facade to the underlying Starknet contract, the EVM contract when built
+/// with the `evm` feature, the in-memory one when built with the `mock` feature or any other
+/// implementation it is given, providing methods for various contract operations like
+/// allocation, inquiry, receipt, signing, and workflow management. It implements multiple
+/// contract traits to provide a unified interface for all contract operations. Every call to
+/// the chain goes through the circuit breaker, and every transaction is recorded in the journal.
 pub struct ContractService {
-    instance: StarknetContract,
+    instance: Box<dyn Contract>,
+    breaker: CircuitBreaker,
+    journal: TransactionJournal,
 }
 
 impl ContractService {
-    pub fn new(config: &Config) -> Self {
-        Self { instance: StarknetContract::new(&config.starknet_config) }
+    /// Wrap the contract implementation of the chain the backend is built for.
+    pub fn new(config: &Config, journal: TransactionJournal) -> Self {
+        #[cfg(not(any(feature = "evm", feature = "mock")))]
+        let instance = StarknetContract::new(&config.starknet_config, journal.clone());
+        #[cfg(all(feature = "evm", not(feature = "mock")))]
+        let instance = EvmContract::new(&config.evm_config, journal.clone());
+        #[cfg(feature = "mock")]
+        let instance = MockContract::with_journal(journal.clone());
+
+        Self::with_instance(Box::new(instance), config, journal)
+    }
+
+    /// Wrap any contract implementation, eg. one selected at runtime. The implementation
+    /// records its transactions in `journal`.
+    pub fn with_instance(
+        instance: Box<dyn Contract>,
+        config: &Config,
+        journal: TransactionJournal,
+    ) -> Self {
+        Self { instance, breaker: CircuitBreaker::new(&config.breaker_config), journal }
+    }
+
+    /// The circuit breaker of the chain provider.
+    pub fn breaker(&self) -> &CircuitBreaker {
+        &self.breaker
+    }
+
+    /// The journal of the transactions sent on chain.
+    pub fn journal(&self) -> &TransactionJournal {
+        &self.journal
+    }
+
+    /// Probe the chain provider, bypassing the circuit breaker, returning the latest block.
+    pub async fn probe(&self) -> Result<u64> {
+        self.breaker.probe(self.instance.block_number()).await
+    }
+
+    /// Read all the workflows of a user, a page at a time.
+    pub async fn workflows(&self, github_owner: Owner) -> Result<Vec<(Number, Workflow)>> {
+        let mut workflows = vec![];
+        loop {
+            let page = self
+                .get_workflows_page(github_owner.clone(), workflows.len() as u64, READ_PAGE_SIZE)
+                .await?;
+            let last = (page.len() as u64) < READ_PAGE_SIZE;
+            workflows.extend(page);
+            if last {
+                return Ok(workflows);
+            }
+        }
+    }
+
+    /// Read the steps of a dependency, linked and verified against its transaction chain.
+    pub async fn transaction_chain(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        dependency_idx: Id,
+    ) -> Result<TransactionChain> {
+        let steps = self
+            .get_steps(github_owner.clone(), workflow_id.clone(), dependency_idx.clone())
+            .await?;
+        let chain =
+            self.get_complete_transaction_chain(github_owner, workflow_id, dependency_idx).await?;
+
+        Ok(TransactionChain::link(steps, &chain))
+    }
+
+    /// Read all the dependencies of a workflow, a page at a time.
+    pub async fn dependencies(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+    ) -> Result<Vec<Dependency>> {
+        let mut dependencies = vec![];
+        loop {
+            let page = self
+                .get_dependencies_page(
+                    github_owner.clone(),
+                    workflow_id.clone(),
+                    dependencies.len() as u64,
+                    READ_PAGE_SIZE,
+                )
+                .await?;
+            let last = (page.len() as u64) < READ_PAGE_SIZE;
+            dependencies.extend(page);
+            if last {
+                return Ok(dependencies);
+            }
+        }
     }
 }
 
 impl Contract for ContractService {
-    fn chain() -> &'static str {
-        StarknetContract::chain()
+    fn chain(&self) -> &'static str {
+        self.instance.chain()
+    }
+
+    fn chain_id(&self) -> String {
+        self.instance.chain_id()
+    }
+
+    fn explorer_tx_url(&self, tx_hash: &str) -> String {
+        self.instance.explorer_tx_url(tx_hash)
+    }
+
+    fn explorer_contract_url(&self, address: &str) -> String {
+        self.instance.explorer_contract_url(address)
     }
 }
 
+#[async_trait]
 impl AllocationContract for ContractService {
     async fn create_allocation(
         &self,
@@ -58,29 +180,75 @@ impl AllocationContract for ContractService {
         recipient: Address,
         amount: Number,
         token_address: Address,
-    ) -> Result<Id> {
-        self.instance
-            .create_allocation(workflow_id, sign_id, recipient, amount, token_address)
+    ) -> Result<Option<Id>> {
+        self.breaker
+            .call(self.instance.create_allocation(
+                workflow_id,
+                sign_id,
+                recipient,
+                amount,
+                token_address,
+            ))
             .await
     }
 
+    async fn create_allocations_batch(
+        &self,
+        allocations: Vec<AllocationInput>,
+    ) -> Result<Vec<Option<Id>>> {
+        self.breaker.call(self.instance.create_allocations_batch(allocations)).await
+    }
+
     async fn update_allocation_status(
         &self,
         allocation_id: Id,
         status: AllocationStatus,
     ) -> Result<bool> {
-        self.instance.update_allocation_status(allocation_id, status).await
+        self.breaker.call(self.instance.update_allocation_status(allocation_id, status)).await
+    }
+
+    async fn execute_allocation(
+        &self,
+        allocation_id: Id,
+        token_address: Address,
+        amount: Number,
+    ) -> Result<Hash> {
+        self.breaker
+            .call(self.instance.execute_allocation(allocation_id, token_address, amount))
+            .await
+    }
+
+    fn execute_allocation_calls(
+        &self,
+        allocation_id: Id,
+        token_address: Address,
+        amount: Number,
+    ) -> Result<Vec<RawCall>> {
+        self.instance.execute_allocation_calls(allocation_id, token_address, amount)
+    }
+
+    async fn update_allocation_tx_hash(&self, allocation_id: Id, tx_hash: Hash) -> Result<()> {
+        self.breaker.call(self.instance.update_allocation_tx_hash(allocation_id, tx_hash)).await
+    }
+
+    fn update_allocation_tx_hash_call(&self, allocation_id: Id, tx_hash: Hash) -> Result<RawCall> {
+        self.instance.update_allocation_tx_hash_call(allocation_id, tx_hash)
+    }
+
+    fn claim_call(&self, allocation_id: Id) -> Result<RawCall> {
+        self.instance.claim_call(allocation_id)
     }
 
     async fn get_allocation_details(&self, allocation_id: Id) -> Result<Allocation> {
-        self.instance.get_allocation_details(allocation_id).await
+        self.breaker.call(self.instance.get_allocation_details(allocation_id)).await
     }
 
     async fn get_allocation_by_sign(&self, sign_id: Id) -> Result<Id> {
-        self.instance.get_allocation_by_sign(sign_id).await
+        self.breaker.call(self.instance.get_allocation_by_sign(sign_id)).await
     }
 }
 
+#[async_trait]
 impl InquireContract for ContractService {
     async fn create_inquire(
         &self,
@@ -88,23 +256,26 @@ impl InquireContract for ContractService {
         inquirer: Address,
         inquiree: Address,
         question: String,
-    ) -> Result<Id> {
-        self.instance.create_inquire(workflow_id, inquirer, inquiree, question).await
+    ) -> Result<Option<Id>> {
+        self.breaker
+            .call(self.instance.create_inquire(workflow_id, inquirer, inquiree, question))
+            .await
     }
 
     async fn respond_to_inquire(&self, inquire_id: Id, response: String) -> Result<bool> {
-        self.instance.respond_to_inquire(inquire_id, response).await
+        self.breaker.call(self.instance.respond_to_inquire(inquire_id, response)).await
     }
 
     async fn reject_inquire(&self, inquire_id: Id) -> Result<bool> {
-        self.instance.reject_inquire(inquire_id).await
+        self.breaker.call(self.instance.reject_inquire(inquire_id)).await
     }
 
     async fn get_inquire_details(&self, inquire_id: Id) -> Result<Inquire> {
-        self.instance.get_inquire_details(inquire_id).await
+        self.breaker.call(self.instance.get_inquire_details(inquire_id)).await
     }
 }
 
+#[async_trait]
 impl ReceiptContract for ContractService {
     async fn create_receipt(
         &self,
@@ -112,26 +283,50 @@ impl ReceiptContract for ContractService {
         dependency_url: String,
         metadata: ReceiptMetadata,
         metadata_hash: Hash,
-        metadata_uri: Hash,
-    ) -> Result<Id> {
-        self.instance
-            .create_receipt(workflow_id, dependency_url, metadata, metadata_hash, metadata_uri)
+        metadata_uri: String,
+    ) -> Result<Option<Id>> {
+        self.breaker
+            .call(self.instance.create_receipt(
+                workflow_id,
+                dependency_url,
+                metadata,
+                metadata_hash,
+                metadata_uri,
+            ))
             .await
     }
 
+    fn create_receipt_call(
+        &self,
+        workflow_id: Id,
+        dependency_url: String,
+        metadata: ReceiptMetadata,
+        metadata_hash: Hash,
+        metadata_uri: String,
+    ) -> Result<RawCall> {
+        self.instance.create_receipt_call(
+            workflow_id,
+            dependency_url,
+            metadata,
+            metadata_hash,
+            metadata_uri,
+        )
+    }
+
     async fn get_receipt_details(&self, receipt_id: Id) -> Result<(Receipt, ReceiptMetadata)> {
-        self.instance.get_receipt_details(receipt_id).await
+        self.breaker.call(self.instance.get_receipt_details(receipt_id)).await
     }
 
     async fn verify_metadata(&self, receipt_id: Id, provided_hash: Hash) -> Result<bool> {
-        self.instance.verify_metadata(receipt_id, provided_hash).await
+        self.breaker.call(self.instance.verify_metadata(receipt_id, provided_hash)).await
     }
 
     async fn update_tx_hash(&self, receipt_id: Id, tx_hash: Hash) -> Result<()> {
-        self.instance.update_tx_hash(receipt_id, tx_hash).await
+        self.breaker.call(self.instance.update_tx_hash(receipt_id, tx_hash)).await
     }
 }
 
+#[async_trait]
 impl SignContract for ContractService {
     async fn create_sign(
         &self,
@@ -139,22 +334,127 @@ impl SignContract for ContractService {
         inquire_id: Id,
         signer: Address,
         signature_hash: Hash,
-    ) -> Result<Id> {
-        self.instance.create_sign(workflow_id, inquire_id, signer, signature_hash).await
+    ) -> Result<Option<Id>> {
+        self.breaker
+            .call(self.instance.create_sign(workflow_id, inquire_id, signer, signature_hash))
+            .await
     }
 
     async fn get_sign_details(&self, sign_id: Id) -> Result<Sign> {
-        self.instance.get_sign_details(sign_id).await
+        self.breaker.call(self.instance.get_sign_details(sign_id)).await
     }
 
     async fn get_sign_by_inquire(&self, inquire_id: Id) -> Result<Id> {
-        self.instance.get_sign_by_inquire(inquire_id).await
+        self.breaker.call(self.instance.get_sign_by_inquire(inquire_id)).await
+    }
+
+    async fn is_valid_signature(
+        &self,
+        signer: Address,
+        message_hash: Hash,
+        signature: Vec<String>,
+    ) -> Result<bool> {
+        self.breaker.call(self.instance.is_valid_signature(signer, message_hash, signature)).await
     }
 }
 
+#[async_trait]
+impl TokenContract for ContractService {
+    fn account_address(&self) -> Address {
+        self.instance.account_address()
+    }
+
+    fn is_valid_address(&self, address: &Address) -> bool {
+        self.instance.is_valid_address(address)
+    }
+
+    fn native_token_address(&self, token: NativeToken) -> Address {
+        self.instance.native_token_address(token)
+    }
+
+    fn fee_token(&self) -> NativeToken {
+        self.instance.fee_token()
+    }
+
+    async fn decimals(&self, token_address: Address) -> Result<u8> {
+        self.breaker.call(self.instance.decimals(token_address)).await
+    }
+
+    async fn balance_of(&self, token_address: Address, owner: Address) -> Result<Number> {
+        self.breaker.call(self.instance.balance_of(token_address, owner)).await
+    }
+
+    async fn allowance(
+        &self,
+        token_address: Address,
+        owner: Address,
+        spender: Address,
+    ) -> Result<Number> {
+        self.breaker.call(self.instance.allowance(token_address, owner, spender)).await
+    }
+
+    async fn transfer(
+        &self,
+        token_address: Address,
+        recipient: Address,
+        amount: Number,
+    ) -> Result<Hash> {
+        self.breaker.call(self.instance.transfer(token_address, recipient, amount)).await
+    }
+
+    fn transfer_call(
+        &self,
+        token_address: Address,
+        recipient: Address,
+        amount: Number,
+    ) -> Result<RawCall> {
+        self.instance.transfer_call(token_address, recipient, amount)
+    }
+
+    fn transfer_from_call(
+        &self,
+        token_address: Address,
+        owner: Address,
+        recipient: Address,
+        amount: Number,
+    ) -> Result<RawCall> {
+        self.instance.transfer_from_call(token_address, owner, recipient, amount)
+    }
+}
+
+#[async_trait]
+impl TransactionContract for ContractService {
+    async fn transaction_status(&self, tx_hash: Hash) -> Result<TransactionStatus> {
+        let status = self.breaker.call(self.instance.transaction_status(tx_hash.clone())).await?;
+        self.journal.update(&tx_hash, &status);
+        Ok(status)
+    }
+
+    async fn execute_calls(&self, calls: Vec<RawCall>) -> Result<Hash> {
+        self.breaker.call(self.instance.execute_calls(calls)).await
+    }
+
+    async fn estimate_fee(&self, calls: Vec<RawCall>) -> Result<Number> {
+        self.breaker.call(self.instance.estimate_fee(calls)).await
+    }
+
+    async fn block_number(&self) -> Result<u64> {
+        self.breaker.call(self.instance.block_number()).await
+    }
+
+    async fn check_chain_id(&self) -> Result<()> {
+        self.breaker.call(self.instance.check_chain_id()).await
+    }
+}
+
+#[async_trait]
 impl WorkflowContract for ContractService {
-    async fn create_workflow(&self, github_owner: Owner, wallet_address: Address) -> Result<Id> {
-        self.instance.create_workflow(github_owner, wallet_address).await
+    async fn create_workflow(
+        &self,
+        github_owner: Owner,
+        wallet_address: Address,
+    ) -> Result<Option<Id>> {
+        self.breaker.call(self.instance.create_workflow(github_owner, wallet_address)).await
     }
 
     async fn create_dependency(
@@ -165,19 +465,38 @@ impl WorkflowContract for ContractService {
         repository_url: String,
         license: String,
         metadata_json: String,
-    ) -> Result<Id> {
-        self.instance
-            .create_dependency(
+    ) -> Result<Option<Id>> {
+        self.breaker
+            .call(self.instance.create_dependency(
                 github_owner,
                 workflow_id,
                 name,
                 repository_url,
                 license,
                 metadata_json,
-            )
+            ))
             .await
     }
 
+    fn create_dependency_call(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        name: String,
+        repository_url: String,
+        license: String,
+        metadata_json: String,
+    ) -> Result<RawCall> {
+        self.instance.create_dependency_call(
+            github_owner,
+            workflow_id,
+            name,
+            repository_url,
+            license,
+            metadata_json,
+        )
+    }
+
     async fn add_step(
         &self,
         github_owner: Owner,
@@ -186,16 +505,52 @@ impl WorkflowContract for ContractService {
         step_type: StepType,
         tx_hash: Hash,
         related_entity_id: Id,
-    ) -> Result<Id> {
-        self.instance
-            .add_step(
+    ) -> Result<Option<Id>> {
+        self.breaker
+            .call(self.instance.add_step(
                 github_owner,
                 workflow_id,
                 dependency_index,
                 step_type,
                 tx_hash,
                 related_entity_id,
-            )
+            ))
+            .await
+    }
+
+    fn add_step_call(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        dependency_index: Id,
+        step_type: StepType,
+        tx_hash: Hash,
+        related_entity_id: Id,
+    ) -> Result<RawCall> {
+        self.instance.add_step_call(
+            github_owner,
+            workflow_id,
+            dependency_index,
+            step_type,
+            tx_hash,
+            related_entity_id,
+        )
+    }
+
+    async fn record_dependency_with_receipt(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        dependency_idx: Id,
+        record: DependencyReceipt,
+    ) -> Result<Hash> {
+        self.breaker
+            .call(self.instance.record_dependency_with_receipt(
+                github_owner,
+                workflow_id,
+                dependency_idx,
+                record,
+            ))
             .await
     }
 
@@ -205,15 +560,21 @@ impl WorkflowContract for ContractService {
         workflow_id: Id,
         dependency_idx: Id,
     ) -> Result<bool> {
-        self.instance.finish_dependency(github_owner, workflow_id, dependency_idx).await
+        self.breaker
+            .call(self.instance.finish_dependency(github_owner, workflow_id, dependency_idx))
+            .await
     }
 
     async fn finish_workflow(&self, github_owner: Owner, workflow_id: Id) -> Result<bool> {
-        self.instance.finish_workflow(github_owner, workflow_id).await
+        self.breaker.call(self.instance.finish_workflow(github_owner, workflow_id)).await
+    }
+
+    fn finish_workflow_call(&self, github_owner: Owner, workflow_id: Id) -> Result<RawCall> {
+        self.instance.finish_workflow_call(github_owner, workflow_id)
     }
 
     async fn get_workflow_status(&self, github_owner: Owner, workflow_id: Id) -> Result<Workflow> {
-        self.instance.get_workflow_status(github_owner, workflow_id).await
+        self.breaker.call(self.instance.get_workflow_status(github_owner, workflow_id)).await
     }
 
     async fn get_dependencies(
@@ -221,7 +582,19 @@ impl WorkflowContract for ContractService {
         github_owner: Owner,
         workflow_id: Id,
     ) -> Result<Vec<Dependency>> {
-        self.instance.get_dependencies(github_owner, workflow_id).await
+        self.breaker.call(self.instance.get_dependencies(github_owner, workflow_id)).await
+    }
+
+    async fn get_dependencies_page(
+        &self,
+        github_owner: Owner,
+        workflow_id: Id,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<Dependency>> {
+        self.breaker
+            .call(self.instance.get_dependencies_page(github_owner, workflow_id, offset, limit))
+            .await
     }
 
     async fn get_steps(
@@ -230,11 +603,11 @@ impl WorkflowContract for ContractService {
         workflow_id: Id,
         dependency_idx: Id,
     ) -> Result<Vec<Step>> {
-        self.instance.get_steps(github_owner, workflow_id, dependency_idx).await
+        self.breaker.call(self.instance.get_steps(github_owner, workflow_id, dependency_idx)).await
     }
 
     async fn get_step_by_tx_hash(&self, tx_hash: Hash) -> Result<Option<(Owner, Id, Id, Id)>> {
-        self.instance.get_step_by_tx_hash(tx_hash).await
+        self.breaker.call(self.instance.get_step_by_tx_hash(tx_hash)).await
     }
 
     async fn get_complete_transaction_chain(
@@ -243,17 +616,39 @@ impl WorkflowContract for ContractService {
         workflow_id: Id,
         dependency_idx: Id,
     ) -> Result<Vec<Hash>> {
-        self.instance
-            .get_complete_transaction_chain(github_owner, workflow_id, dependency_idx)
+        self.breaker
+            .call(self.instance.get_complete_transaction_chain(
+                github_owner,
+                workflow_id,
+                dependency_idx,
+            ))
             .await
     }
 
+    async fn get_events(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        continuation_token: Option<String>,
+    ) -> Result<WorkflowEventPage> {
+        self.breaker.call(self.instance.get_events(from_block, to_block, continuation_token)).await
+    }
+
     async fn get_workflow_count(&self, github_owner: Owner) -> Result<Number> {
-        self.instance.get_workflow_count(github_owner).await
+        self.breaker.call(self.instance.get_workflow_count(github_owner)).await
     }
 
     async fn get_all_workflows(&self, github_owner: Owner) -> Result<Vec<(Number, Workflow)>> {
-        self.instance.get_all_workflows(github_owner).await
+        self.breaker.call(self.instance.get_all_workflows(github_owner)).await
+    }
+
+    async fn get_workflows_page(
+        &self,
+        github_owner: Owner,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<(Number, Workflow)>> {
+        self.breaker.call(self.instance.get_workflows_page(github_owner, offset, limit)).await
     }
 
     async fn bind_wallet_address(
@@ -262,11 +657,13 @@ impl WorkflowContract for ContractService {
         workflow_id: Id,
         wallet_address: Address,
     ) -> Result<bool> {
-        self.instance.bind_wallet_address(github_owner, workflow_id, wallet_address).await
+        self.breaker
+            .call(self.instance.bind_wallet_address(github_owner, workflow_id, wallet_address))
+            .await
     }
 
     async fn unbind_wallet_address(&self, github_owner: Owner, workflow_id: Id) -> Result<bool> {
-        self.instance.unbind_wallet_address(github_owner, workflow_id).await
+        self.breaker.call(self.instance.unbind_wallet_address(github_owner, workflow_id)).await
     }
 
     async fn change_wallet_address(
@@ -275,6 +672,12 @@ impl WorkflowContract for ContractService {
         workflow_id: Id,
         new_wallet_address: Address,
     ) -> Result<bool> {
-        self.instance.change_wallet_address(github_owner, workflow_id, new_wallet_address).await
+        self.breaker
+            .call(self.instance.change_wallet_address(
+                github_owner,
+                workflow_id,
+                new_wallet_address,
+            ))
+            .await
     }
 }
@@ -0,0 +1,133 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Recomputation of the contributions of workflows.
+//!
+//! The contributions of a workflow are the scores of the dependencies in its ranking
+//! snapshot, read from the database when there is one. Recomputing them rescores the signals recorded with the dependencies using
+//! the current weights of the ranking profile, without fetching the repository again, and
+//! records the result as a new snapshot the workflow then points at. The allocations
+//! issued so far stay pinned to the snapshot they were computed from.
+
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    db::{self, contribution::ContributionRow, Write},
+    errors::{ApiError, Result},
+    responses::{
+        contribution::{ContributionResponse, RecomputeResponse},
+        event::EventKind,
+    },
+    services::snapshot::SnapshotService,
+};
+
+pub struct ContributionService;
+
+impl ContributionService {
+    /// The contributions of the dependencies in the ranking snapshot of the workflow.
+    pub async fn list(ctx: Arc<Context>, workflow_id: Uuid) -> Result<Vec<ContributionResponse>> {
+        let record = ctx
+            .workflows
+            .get(workflow_id)
+            .ok_or(ApiError::NotFoundWorkflow(workflow_id.to_string()))?;
+        let Some(snapshot_id) = record.snapshot_id else {
+            return Ok(vec![]);
+        };
+
+        let rows = match &ctx.db {
+            Some(db) => db::contribution::list(db.pool(), workflow_id, snapshot_id)
+                .await
+                .map_err(|e| ApiError::DatabaseError(format!("{e:#}")))?,
+            None => {
+                let snapshot = ctx
+                    .snapshots
+                    .get(snapshot_id)
+                    .ok_or(ApiError::NotFoundSnapshot(snapshot_id.to_string()))?;
+                ContributionRow::from_dependencies(workflow_id, snapshot_id, &snapshot.dependencies)
+            }
+        };
+        Ok(rows
+            .into_iter()
+            .map(|row| ContributionResponse {
+                snapshot_id: row.snapshot_id,
+                ecosystem: row.ecosystem,
+                name: row.name,
+                version: row.version,
+                score: row.score,
+            })
+            .collect())
+    }
+
+    pub async fn recompute(ctx: Arc<Context>, workflow_id: Uuid) -> Result<RecomputeResponse> {
+        let record = ctx
+            .workflows
+            .get(workflow_id)
+            .ok_or(ApiError::NotFoundWorkflow(workflow_id.to_string()))?;
+
+        let previous = match record.snapshot_id {
+            Some(id) => ctx.snapshots.get(id),
+            None => SnapshotService::latest(&ctx, &record.project),
+        }
+        .ok_or_else(|| {
+            ApiError::BadWorkflowRequest(format!("workflow {workflow_id} is not ranked yet"))
+        })?;
+        let weights = ctx.profiles.get(Some(&previous.profile)).ok_or_else(|| {
+            ApiError::BadWorkflowRequest(format!(
+                "ranking profile `{}` no longer exists",
+                previous.profile
+            ))
+        })?;
+        let snapshot = SnapshotService::recompute(&ctx, &previous, weights).ok_or_else(|| {
+            ApiError::BadWorkflowRequest(format!(
+                "snapshot {} has no explained scores to recompute",
+                previous.id
+            ))
+        })?;
+
+        // Pin the allocations issued so far to the snapshot they were computed from.
+        let mut pinned_allocations = 0;
+        for allocation in ctx.allocations.list(workflow_id) {
+            if allocation.snapshot_id.is_none() {
+                ctx.allocations
+                    .update(&allocation.id, |record| record.snapshot_id = Some(previous.id))
+                    .await?;
+                pinned_allocations += 1;
+            }
+        }
+        ctx.workflows.set_snapshot(workflow_id, snapshot.id);
+        ctx.events.publish(
+            workflow_id,
+            EventKind::Contributions {
+                snapshot_id: snapshot.id,
+                dependencies: snapshot.dependencies.len(),
+            },
+        );
+        if let Some(db) = &ctx.db {
+            db.write(Write::Contributions(ContributionRow::from_dependencies(
+                workflow_id,
+                snapshot.id,
+                &snapshot.dependencies,
+            )));
+        }
+
+        Ok(RecomputeResponse {
+            workflow_id,
+            pinned_allocations,
+            diff: SnapshotService::compare(&previous, &snapshot),
+        })
+    }
+}
@@ -0,0 +1,315 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contributor attribution of the analyzed projects.
+//!
+//! The optional deep analysis attributes the lines of every analyzed file to the
+//! contributors who last changed them, per the blame of GitHub at the analyzed commit.
+//! The lines are aggregated into the share of each contributor, which contributor-level
+//! allocations split their amount by.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Context as _;
+use chrono::{DateTime, Utc};
+use futures::{stream, StreamExt, TryStreamExt};
+use octocrab::Octocrab;
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{debug, instrument};
+
+use crate::{
+    context::Context,
+    errors::Result,
+    responses::contributor::ContributorResponse,
+    services::identity::{IdentityResolver, Mailmap},
+};
+
+/// The blame of a file at a commit, by range of lines.
+const BLAME_QUERY: &str = r#"
+query($owner: String!, $name: String!, $oid: GitObjectID!, $path: String!) {
+  repository(owner: $owner, name: $name) {
+    object(oid: $oid) {
+      ... on Commit {
+        blame(path: $path) {
+          ranges {
+            startingLine
+            endingLine
+            commit { oid author { email user { login } } }
+          }
+        }
+      }
+    }
+  }
+}
+"#;
+
+#[derive(Clone, clap::Parser)]
+pub struct AttributionConfig {
+    /// Maximum number of files blamed by the deep analysis of a project.
+    #[clap(long, env = "DRK_BLAME_MAX_FILES", default_value_t = 200)]
+    pub blame_max_files: usize,
+
+    /// Maximum number of files blamed at the same time.
+    #[clap(long, env = "DRK_BLAME_CONCURRENCY", default_value_t = 4)]
+    pub blame_concurrency: usize,
+}
+
+/// The lines of a project attributed to a contributor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContributorShare {
+    /// The GitHub username of the contributor
+    pub username: String,
+    /// The lines last changed by the contributor
+    pub lines: u64,
+    /// The commits the lines were last changed in
+    pub commits: u64,
+    /// The share of the attributed lines, between 0 and 1
+    pub share: f64,
+}
+
+/// The contributor shares of a project at an analyzed commit.
+#[derive(Debug, Clone)]
+pub struct Attribution {
+    /// The analyzed commit
+    pub commit: String,
+    /// The number of blamed files
+    pub files: usize,
+    /// The contributors, largest share first
+    pub contributors: Vec<ContributorShare>,
+    pub computed_at: DateTime<Utc>,
+}
+
+/// The latest attribution of each project, in memory.
+#[derive(Clone, Default)]
+pub struct AttributionStore {
+    attributions: Arc<Mutex<HashMap<String, Attribution>>>,
+}
+
+impl AttributionStore {
+    pub fn get(&self, project: &str) -> Option<Attribution> {
+        self.attributions.lock().unwrap().get(&project.to_lowercase()).cloned()
+    }
+
+    fn insert(&self, project: &str, attribution: Attribution) {
+        self.attributions.lock().unwrap().insert(project.to_lowercase(), attribution);
+    }
+}
+
+#[derive(Deserialize)]
+struct BlameData {
+    repository: Option<BlameRepository>,
+}
+
+#[derive(Deserialize)]
+struct BlameRepository {
+    object: Option<BlameObject>,
+}
+
+#[derive(Deserialize)]
+struct BlameObject {
+    blame: Option<Blame>,
+}
+
+#[derive(Deserialize)]
+struct Blame {
+    ranges: Vec<BlameRange>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BlameRange {
+    starting_line: u64,
+    ending_line: u64,
+    commit: BlameCommit,
+}
+
+impl BlameRange {
+    /// The email and the GitHub account of the author of the range, if known.
+    fn author(&self) -> (Option<&str>, Option<&str>) {
+        let author = self.commit.author.as_ref();
+        let email = author.and_then(|a| a.email.as_deref());
+        let login = author.and_then(|a| a.user.as_ref()).map(|u| u.login.as_str());
+        (email, login)
+    }
+}
+
+#[derive(Deserialize)]
+struct BlameCommit {
+    oid: String,
+    author: Option<BlameAuthor>,
+}
+
+#[derive(Deserialize)]
+struct BlameAuthor {
+    email: Option<String>,
+    user: Option<BlameUser>,
+}
+
+#[derive(Deserialize)]
+struct BlameUser {
+    login: String,
+}
+
+#[derive(Deserialize)]
+struct GraphqlResponse<T> {
+    data: Option<T>,
+    #[serde(default)]
+    errors: Vec<GraphqlError>,
+}
+
+#[derive(Deserialize)]
+struct GraphqlError {
+    message: String,
+}
+
+pub struct ContributorService;
+
+impl ContributorService {
+    /// Get the contributors of the latest attribution of the project, with the commit it
+    /// was blamed at.
+    pub async fn list(
+        ctx: Arc<Context>,
+        owner: &str,
+        name: &str,
+    ) -> Result<(Option<String>, Vec<ContributorResponse>)> {
+        // Only the deep analysis attributes the lines of a project to its contributors.
+        let Some(attribution) = ctx.attributions.get(&format!("{owner}/{name}")) else {
+            return Ok((None, Vec::new()));
+        };
+
+        // Fold the contributors merged since the attribution.
+        let mut contributors: Vec<ContributorResponse> = Vec::new();
+        for contributor in attribution.contributors {
+            let username = ctx.identities.canonical(&contributor.username);
+            match contributors.iter_mut().find(|c| c.username == username) {
+                Some(merged) => {
+                    merged.commits += contributor.commits;
+                    merged.lines += contributor.lines;
+                    merged.score += contributor.share;
+                }
+                None => contributors.push(ContributorResponse {
+                    username,
+                    commits: contributor.commits,
+                    lines: contributor.lines,
+                    score: contributor.share,
+                }),
+            }
+        }
+        contributors
+            .sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.username.cmp(&b.username)));
+        Ok((Some(attribution.commit), contributors))
+    }
+
+    /// Attribute the lines of the analyzed files of a project to their contributors,
+    /// per the blame at the analyzed commit, and keep the attribution as the latest one
+    /// of the project.
+    ///
+    /// The largest files are blamed first, up to the configured number of files. The
+    /// authors are resolved to contributors per their identities, merging the emails and
+    /// the `.mailmap` of the checkout. Lines of authors resolved to no GitHub account are
+    /// left out of the shares.
+    #[instrument(skip_all, fields(%owner, %name, %commit))]
+    pub async fn attribute(
+        ctx: &Context,
+        octocrab: &Octocrab,
+        owner: &str,
+        name: &str,
+        commit: &str,
+        checkout: &Path,
+        files: &[(String, u64)],
+    ) -> anyhow::Result<Attribution> {
+        let config = &ctx.config.attribution_config;
+        let mut files = files.to_vec();
+        files.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+        files.truncate(config.blame_max_files);
+
+        let blames: Vec<Vec<BlameRange>> = stream::iter(&files)
+            .map(|(path, _)| blame(octocrab, owner, name, commit, path))
+            .buffer_unordered(config.blame_concurrency.max(1))
+            .try_collect()
+            .await?;
+
+        let ranges: Vec<_> = blames.into_iter().flatten().collect();
+        let mut resolver = IdentityResolver::new(&ctx.identities, Mailmap::load(checkout));
+        for range in &ranges {
+            let (email, login) = range.author();
+            resolver.learn(email, login);
+        }
+
+        let mut lines: HashMap<String, (u64, BTreeSet<String>)> = HashMap::new();
+        for range in &ranges {
+            let (email, login) = range.author();
+            let Some(username) = resolver.resolve(email, login) else {
+                continue;
+            };
+            let entry = lines.entry(username).or_default();
+            entry.0 += range.ending_line + 1 - range.starting_line;
+            entry.1.insert(range.commit.oid.clone());
+        }
+
+        let total: u64 = lines.values().map(|(lines, _)| lines).sum();
+        let mut contributors: Vec<_> = lines
+            .into_iter()
+            .map(|(username, (lines, commits))| ContributorShare {
+                username,
+                lines,
+                commits: commits.len() as u64,
+                share: if total > 0 { lines as f64 / total as f64 } else { 0.0 },
+            })
+            .collect();
+        contributors
+            .sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.username.cmp(&b.username)));
+        debug!(files = files.len(), contributors = contributors.len(), "Attributed lines");
+
+        let attribution = Attribution {
+            commit: commit.to_string(),
+            files: files.len(),
+            contributors,
+            computed_at: Utc::now(),
+        };
+        ctx.attributions.insert(&format!("{owner}/{name}"), attribution.clone());
+        Ok(attribution)
+    }
+}
+
+/// Blame a file at a commit, files unknown at the commit have no ranges.
+async fn blame(
+    octocrab: &Octocrab,
+    owner: &str,
+    name: &str,
+    commit: &str,
+    path: &str,
+) -> anyhow::Result<Vec<BlameRange>> {
+    let payload = json!({
+        "query": BLAME_QUERY,
+        "variables": { "owner": owner, "name": name, "oid": commit, "path": path },
+    });
+    let response: GraphqlResponse<BlameData> =
+        octocrab.graphql(&payload).await.with_context(|| format!("Failed to blame {path}"))?;
+    if let Some(error) = response.errors.first() {
+        anyhow::bail!("Failed to blame {path}: {}", error.message);
+    }
+
+    let blame = response
+        .data
+        .and_then(|data| data.repository)
+        .and_then(|repository| repository.object)
+        .and_then(|object| object.blame);
+    Ok(blame.map(|blame| blame.ranges).unwrap_or_default())
+}
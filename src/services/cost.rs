@@ -0,0 +1,217 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Estimates what executing a workflow still costs, before its owner approves it.
+//!
+//! Every dependency is created on chain, then attested by a receipt recorded as a step
+//! of the dependency, and the approved allocations are paid out in batches. The fees of
+//! those transactions add up to the token budget of the allocations.
+
+use std::{collections::BTreeMap, fmt, str::FromStr, sync::Arc};
+
+use num_bigint::BigUint;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    contracts::{
+        receipt::{ReceiptContract, ReceiptMetadata},
+        token::TokenContract,
+        transaction::TransactionContract,
+        types::RawCall,
+        workflow::{StepType, WorkflowContract},
+    },
+    errors::{ApiError, Result},
+    requests::workflow::Denomination,
+    responses::cost::{BudgetCostResponse, CostEstimateResponse, CostStep, StepCostResponse},
+    services::{
+        allocation::ExecutionStatus,
+        execution::ExecutionService,
+        payout::{from_base_units, to_base_units},
+        snapshot::SnapshotService,
+    },
+};
+
+/// The precision used to sum amounts, in decimals.
+const SCALE: u8 = 18;
+
+/// A placeholder argument of a sample call, one felt like the real arguments.
+const PLACEHOLDER: &str = "0";
+
+/// A placeholder hash argument of a sample call, a full word like the real hashes.
+const PLACEHOLDER_HASH: &str = "0x0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Parse a placeholder argument of a sample call as the argument it stands for.
+fn placeholder<T: FromStr>(value: &str) -> T
+where
+    T::Err: fmt::Debug,
+{
+    value.parse().expect("a placeholder is a valid argument")
+}
+
+pub struct CostService;
+
+impl CostService {
+    /// Estimate the fees of the remaining steps of a workflow, and its token budget.
+    pub async fn estimate(ctx: Arc<Context>, workflow_id: Uuid) -> Result<CostEstimateResponse> {
+        let record = ctx
+            .workflows
+            .get(workflow_id)
+            .ok_or(ApiError::NotFoundWorkflow(workflow_id.to_string()))?;
+        ctx.contract.breaker().check()?;
+
+        let snapshot = match record.snapshot_id {
+            Some(id) => ctx.snapshots.get(id),
+            None => SnapshotService::latest(&ctx, &record.project),
+        };
+        let dependencies = snapshot.map_or(0, |snapshot| {
+            snapshot.dependencies.iter().filter(|dependency| !dependency.excluded_by_policy).count()
+        });
+
+        let mut steps = vec![
+            Self::estimate_step(
+                &ctx,
+                CostStep::CreateDependency,
+                dependencies,
+                Self::create_dependency_calls(&ctx),
+            )
+            .await,
+            Self::estimate_step(&ctx, CostStep::Receipts, dependencies, Self::receipt_calls(&ctx))
+                .await,
+        ];
+
+        let preview = ExecutionService::preview(ctx.clone(), workflow_id).await?;
+        let complete = preview.batches.iter().all(|batch| batch.estimated_fee.is_some());
+        steps.push(StepCostResponse {
+            step: CostStep::Allocations,
+            transactions: preview.batches.len(),
+            estimated_fee: complete.then_some(preview.estimated_fee),
+        });
+
+        let estimated_fee = steps
+            .iter()
+            .filter_map(|step| step.estimated_fee.as_ref()?.parse::<BigUint>().ok())
+            .sum::<BigUint>();
+
+        Ok(CostEstimateResponse {
+            workflow_id,
+            fee_token: ctx.contract.fee_token().to_string(),
+            estimated_fee: estimated_fee.to_string(),
+            steps,
+            budget: Self::budget(&ctx, workflow_id),
+        })
+    }
+
+    /// Estimate a step repeated for every dependency, each transaction of the step
+    /// estimated once since they only differ by their arguments.
+    async fn estimate_step(
+        ctx: &Context,
+        step: CostStep,
+        dependencies: usize,
+        calls: Vec<anyhow::Result<RawCall>>,
+    ) -> StepCostResponse {
+        let transactions = calls.len() * dependencies;
+        if dependencies == 0 {
+            return StepCostResponse { step, transactions, estimated_fee: Some("0".to_string()) };
+        }
+
+        let mut estimated_fee = Some(BigUint::ZERO);
+        for call in calls {
+            let fee = match call {
+                Ok(call) => ctx.contract.estimate_fee(vec![call]).await,
+                Err(e) => Err(e),
+            };
+            let fee = match fee {
+                Ok(fee) => fee.parse::<BigUint>().ok(),
+                Err(e) => {
+                    warn!("Failed to estimate the fee of the {step} step: {e}");
+                    None
+                }
+            };
+            estimated_fee = estimated_fee.zip(fee).map(|(sum, fee)| sum + fee);
+        }
+
+        StepCostResponse {
+            step,
+            transactions,
+            estimated_fee: estimated_fee.map(|fee| (fee * dependencies).to_string()),
+        }
+    }
+
+    /// The transaction creating a dependency.
+    fn create_dependency_calls(ctx: &Context) -> Vec<anyhow::Result<RawCall>> {
+        vec![ctx.contract.create_dependency_call(
+            placeholder(PLACEHOLDER),
+            placeholder(PLACEHOLDER),
+            PLACEHOLDER.to_string(),
+            PLACEHOLDER.to_string(),
+            PLACEHOLDER.to_string(),
+            PLACEHOLDER.to_string(),
+        )]
+    }
+
+    /// The transactions creating the receipt of a dependency, then recording it as a step.
+    fn receipt_calls(ctx: &Context) -> Vec<anyhow::Result<RawCall>> {
+        let metadata = ReceiptMetadata {
+            name: String::new(),
+            version: String::new(),
+            author: String::new(),
+            license: String::new(),
+        };
+        vec![
+            ctx.contract.create_receipt_call(
+                placeholder(PLACEHOLDER),
+                PLACEHOLDER.to_string(),
+                metadata,
+                placeholder(PLACEHOLDER_HASH),
+                PLACEHOLDER.to_string(),
+            ),
+            ctx.contract.add_step_call(
+                placeholder(PLACEHOLDER),
+                placeholder(PLACEHOLDER),
+                placeholder(PLACEHOLDER),
+                StepType::Receipt,
+                placeholder(PLACEHOLDER_HASH),
+                placeholder(PLACEHOLDER),
+            ),
+        ]
+    }
+
+    /// The amounts of the allocations not paid out yet, per token and denomination.
+    fn budget(ctx: &Context, workflow_id: Uuid) -> Vec<BudgetCostResponse> {
+        let mut budget = BTreeMap::<(String, Denomination), BigUint>::new();
+        for record in ctx.allocations.list(workflow_id) {
+            if !matches!(
+                record.status,
+                ExecutionStatus::Approved
+                    | ExecutionStatus::AwaitingClaim
+                    | ExecutionStatus::Submitted
+            ) {
+                continue;
+            }
+            let amount = to_base_units(&record.amount, SCALE).unwrap_or_default();
+            *budget.entry((record.token.to_string(), record.denomination)).or_default() += amount;
+        }
+
+        budget
+            .into_iter()
+            .map(|((token, denomination), amount)| BudgetCostResponse {
+                token,
+                denomination,
+                amount: from_base_units(&amount, SCALE),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,211 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verifiable credentials of the funding paid out.
+//!
+//! An executed allocation may be exported as a W3C verifiable credential asserting that
+//! a dependency of a project was funded with an amount in a transaction. The credential
+//! is secured as a JWT (`vc+jwt`) signed by the Ed25519 key of the backend, whose public
+//! half is published as a JSON Web Key Set, so maintainers can display provable funding.
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context as _};
+use base64::{
+    engine::general_purpose::{STANDARD as BASE64, URL_SAFE_NO_PAD as BASE64_URL},
+    Engine,
+};
+use chrono::Utc;
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use sha2::{Digest, Sha256};
+use tracing::error;
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    contracts::Contract,
+    errors::{ApiError, Result},
+    responses::credential::{
+        CredentialResponse, FundedDependency, FundingCredential, FundingSubject, KeyResponse,
+        KeySetResponse,
+    },
+    services::allocation::ExecutionStatus,
+};
+
+/// The context of the W3C verifiable credentials data model, version 2.0.
+const CREDENTIALS_CONTEXT: &str = "https://www.w3.org/ns/credentials/v2";
+
+/// The DER prefix of an Ed25519 public key, followed by the 32 bytes of the key.
+const ED25519_SPKI_PREFIX: [u8; 12] =
+    [0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00];
+
+#[derive(Clone, clap::Parser)]
+pub struct CredentialConfig {
+    /// The PEM Ed25519 private key signing the funding credentials, line breaks may be
+    /// escaped as `\n`. Credentials are disabled without.
+    #[clap(long, env = "DRK_CREDENTIAL_PRIVATE_KEY")]
+    pub credential_private_key: Option<String>,
+
+    /// The PEM public key matching the private key, published to verify the credentials.
+    #[clap(long, env = "DRK_CREDENTIAL_PUBLIC_KEY")]
+    pub credential_public_key: Option<String>,
+
+    /// The issuer of the funding credentials, eg. a `did:web` identifier.
+    #[clap(long, env = "DRK_CREDENTIAL_ISSUER", default_value = "did:web:deprank.xyz")]
+    pub credential_issuer: String,
+}
+
+/// The issuer of the credentials, holding the signing key of the backend.
+#[derive(Clone)]
+pub struct CredentialIssuer {
+    issuer: String,
+    key: EncodingKey,
+    /// The raw Ed25519 public key
+    public_key: Vec<u8>,
+    key_id: String,
+}
+
+impl CredentialIssuer {
+    /// Load the configured key pair, `None` when no private key is configured.
+    pub fn new(config: &CredentialConfig) -> anyhow::Result<Option<Self>> {
+        let Some(private_key) = config.credential_private_key.as_deref().filter(|k| !k.is_empty())
+        else {
+            return Ok(None);
+        };
+        let key = EncodingKey::from_ed_pem(private_key.replace("\\n", "\n").as_bytes())
+            .context("Invalid credential private key")?;
+        let public_key =
+            config.credential_public_key.as_deref().filter(|key| !key.is_empty()).ok_or_else(
+                || anyhow!("DRK_CREDENTIAL_PUBLIC_KEY is required with a credential private key"),
+            )?;
+        let public_key = ed25519_public_key(&public_key.replace("\\n", "\n"))
+            .context("Invalid credential public key")?;
+
+        // Refuse a public key which does not verify what the private key signs.
+        let header = Header::new(Algorithm::EdDSA);
+        let token = jsonwebtoken::encode(&header, &serde_json::json!({}), &key)?;
+        let mut validation = Validation::new(Algorithm::EdDSA);
+        validation.required_spec_claims.clear();
+        validation.validate_exp = false;
+        jsonwebtoken::decode::<serde_json::Value>(
+            &token,
+            &DecodingKey::from_ed_der(&public_key),
+            &validation,
+        )
+        .context("The credential public key does not match the private key")?;
+
+        let issuer = config.credential_issuer.trim().to_string();
+        let key_id = format!("{issuer}#{}", &hex::encode(Sha256::digest(&public_key))[..16]);
+        Ok(Some(Self { issuer, key, public_key, key_id }))
+    }
+
+    /// The public key as a JSON Web Key.
+    fn jwk(&self) -> KeyResponse {
+        KeyResponse {
+            kty: "OKP".to_string(),
+            crv: "Ed25519".to_string(),
+            alg: "EdDSA".to_string(),
+            key_use: "sig".to_string(),
+            kid: self.key_id.clone(),
+            x: BASE64_URL.encode(&self.public_key),
+        }
+    }
+
+    /// Sign a credential as a `vc+jwt`.
+    fn sign(&self, credential: &FundingCredential) -> anyhow::Result<String> {
+        let mut header = Header::new(Algorithm::EdDSA);
+        header.typ = Some("vc+jwt".to_string());
+        header.kid = Some(self.key_id.clone());
+        Ok(jsonwebtoken::encode(&header, credential, &self.key)?)
+    }
+}
+
+pub struct CredentialService;
+
+impl CredentialService {
+    /// Issue the credential of an executed allocation of a workflow.
+    pub async fn issue(
+        ctx: Arc<Context>,
+        workflow_id: Uuid,
+        allocation_id: &str,
+    ) -> Result<CredentialResponse> {
+        let issuer = issuer(&ctx)?;
+        let workflow = ctx
+            .workflows
+            .get(workflow_id)
+            .ok_or_else(|| ApiError::NotFoundWorkflow(workflow_id.to_string()))?;
+        let record = ctx
+            .allocations
+            .get(allocation_id)
+            .filter(|record| record.workflow_id == workflow_id)
+            .ok_or_else(|| ApiError::NotFoundAllocation(allocation_id.to_string()))?;
+
+        if record.status != ExecutionStatus::Executed {
+            return Err(ApiError::BadCredentialRequest("allocation is not executed".to_string()));
+        }
+        let tx_hash = record.tx_hash.clone().ok_or_else(|| {
+            ApiError::BadCredentialRequest("allocation has no payout transaction".to_string())
+        })?;
+
+        let credential = FundingCredential {
+            context: vec![CREDENTIALS_CONTEXT.to_string()],
+            id: format!("urn:deprank:allocation:{workflow_id}:{}", record.id),
+            kind: vec!["VerifiableCredential".to_string(), "FundingCredential".to_string()],
+            issuer: issuer.issuer.clone(),
+            valid_from: Utc::now(),
+            credential_subject: FundingSubject {
+                project: workflow.project,
+                dependency: record.package.as_ref().map(|package| FundedDependency {
+                    ecosystem: package.ecosystem,
+                    name: package.name.clone(),
+                }),
+                contributor: record.contributor.clone(),
+                recipient: record.recipient.to_string(),
+                amount: record.token_amount.clone().unwrap_or_else(|| record.amount.clone()),
+                token: record.token.to_string(),
+                chain_id: ctx.contract.chain_id(),
+                transaction_hash: tx_hash.to_string(),
+                workflow_id,
+                allocation_id: record.id.to_string(),
+            },
+        };
+        let jwt = issuer.sign(&credential).map_err(|e| {
+            error!("Failed to sign credential: {e:#}");
+            ApiError::InternalServerError
+        })?;
+
+        Ok(CredentialResponse { credential, jwt })
+    }
+
+    /// Get the public keys verifying the credentials.
+    pub async fn keys(ctx: Arc<Context>) -> Result<KeySetResponse> {
+        Ok(KeySetResponse { keys: vec![issuer(&ctx)?.jwk()] })
+    }
+}
+
+fn issuer(ctx: &Context) -> Result<&CredentialIssuer> {
+    ctx.credentials
+        .as_ref()
+        .ok_or_else(|| ApiError::CredentialsUnavailable("no credential key configured".to_string()))
+}
+
+/// Extract the raw key of a PEM Ed25519 public key.
+fn ed25519_public_key(pem: &str) -> anyhow::Result<Vec<u8>> {
+    let body: String = pem.lines().filter(|line| !line.starts_with("-----")).collect();
+    let der = BASE64.decode(body.trim())?;
+    match der.strip_prefix(&ED25519_SPKI_PREFIX[..]) {
+        Some(key) if key.len() == 32 => Ok(key.to_vec()),
+        _ => bail!("not an Ed25519 public key"),
+    }
+}
@@ -0,0 +1,186 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::HashSet, sync::Arc};
+
+use serde_json::Value;
+
+use crate::{
+    analyzers::PackageKey,
+    context::Context,
+    contracts::{
+        types::{Id, Owner},
+        workflow::WorkflowContract,
+    },
+    db::{dependency::DependencyRow, Write},
+    errors::{ApiError, Result},
+    responses::dependency::{
+        DependencyGraphResponse, DependencyResponse, GraphEdgeResponse, GraphNodeResponse,
+    },
+    services::{metadata::MetadataService, snapshot::SnapshotService},
+};
+
+pub struct DependencyService;
+
+impl DependencyService {
+    /// Get the ranked dependencies of the latest snapshot of the project, rescored if
+    /// their enrichment signals changed, with the commit they were analyzed at.
+    pub async fn list(
+        ctx: Arc<Context>,
+        owner: &str,
+        name: &str,
+    ) -> Result<(Option<String>, Vec<DependencyResponse>)> {
+        let project = format!("{owner}/{name}");
+        Ok(SnapshotService::latest(&ctx, &project)
+            .map(|snapshot| (Some(snapshot.commit.clone()), snapshot.dependencies.clone()))
+            .unwrap_or_default())
+    }
+
+    /// Create a dependency of a workflow on chain, its metadata validated and written in
+    /// canonical form.
+    pub async fn create(
+        ctx: &Context,
+        github_owner: Owner,
+        workflow_id: Id,
+        metadata: &Value,
+    ) -> Result<Option<Id>> {
+        let (metadata, validated) = MetadataService::validate(metadata)?;
+        let license = metadata.license.unwrap_or_default();
+        let id = ctx
+            .contract
+            .create_dependency(
+                github_owner.clone(),
+                workflow_id.clone(),
+                metadata.name.clone(),
+                metadata.repository_url.clone(),
+                license.clone(),
+                validated.metadata_json.clone(),
+            )
+            .await
+            .map_err(|e| ApiError::chain(e, ApiError::FailedToCreateWorkflow))?;
+
+        // Dependencies are only indexed once the chain returns their id.
+        if let (Some(db), Some(id)) = (&ctx.db, &id) {
+            db.write(Write::Dependency(DependencyRow {
+                id: id.clone(),
+                workflow_id,
+                github_owner,
+                name: metadata.name,
+                repository_url: metadata.repository_url,
+                license,
+                metadata_json: validated.metadata_json,
+            }));
+        }
+        Ok(id)
+    }
+    /// Get the dependency graph of the latest snapshot of the project, the project
+    /// depending on its direct dependencies.
+    pub async fn graph(
+        ctx: Arc<Context>,
+        owner: &str,
+        name: &str,
+    ) -> Result<DependencyGraphResponse> {
+        let project = format!("{owner}/{name}").to_lowercase();
+        let snapshot = SnapshotService::latest(&ctx, &project).ok_or(ApiError::NotFound)?;
+
+        let mut nodes = vec![GraphNodeResponse {
+            id: project.clone(),
+            name: None,
+            version: None,
+            ecosystem: None,
+            kind: None,
+            score: None,
+        }];
+        let mut keys = HashSet::new();
+        for dependency in &snapshot.dependencies {
+            // Packages resolved in several versions are a single node
+            let key = (dependency.ecosystem, dependency.name.clone());
+            if !keys.contains(&key) {
+                nodes.push(GraphNodeResponse {
+                    id: node_id(&key),
+                    name: Some(dependency.name.clone()),
+                    version: Some(dependency.version.clone()),
+                    ecosystem: Some(dependency.ecosystem),
+                    kind: Some(dependency.kind),
+                    score: Some(dependency.score),
+                });
+                keys.insert(key);
+            }
+        }
+
+        // Snapshots without a resolved graph only know the project depends on everything
+        let graph = &snapshot.graph;
+        let mut direct: Vec<_> = if graph.direct.is_empty() {
+            keys.iter().collect()
+        } else {
+            graph.direct.iter().filter(|key| keys.contains(*key)).collect()
+        };
+        direct.sort();
+        let mut edges: Vec<_> = direct
+            .into_iter()
+            .map(|to| GraphEdgeResponse { from: project.clone(), to: node_id(to) })
+            .collect();
+        for (from, depends_on) in &graph.edges {
+            if !keys.contains(from) {
+                continue;
+            }
+            edges.extend(
+                depends_on
+                    .iter()
+                    .filter(|to| keys.contains(*to))
+                    .map(|to| GraphEdgeResponse { from: node_id(from), to: node_id(to) }),
+            );
+        }
+
+        Ok(DependencyGraphResponse { project, snapshot_id: snapshot.id, nodes, edges })
+    }
+
+    /// Render a dependency graph in the DOT language of Graphviz, the project boxed and
+    /// the packages labelled with their version and score.
+    pub fn to_dot(graph: &DependencyGraphResponse) -> String {
+        let mut dot = format!("digraph {} {{\n", quote(&graph.project));
+        for node in &graph.nodes {
+            match (&node.version, node.score) {
+                (Some(version), Some(score)) => {
+                    // Lines of labels are separated by escaped newlines
+                    let name = node.name.as_deref().unwrap_or(&node.id);
+                    let label = format!("{}\\n{}\\n{score:.4}", escape(name), escape(version));
+                    dot.push_str(&format!("  {} [label=\"{label}\"];\n", quote(&node.id)));
+                }
+                _ => dot.push_str(&format!("  {} [shape=box];\n", quote(&node.id))),
+            }
+        }
+        for edge in &graph.edges {
+            dot.push_str(&format!("  {} -> {};\n", quote(&edge.from), quote(&edge.to)));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// The id of the node of a package in a dependency graph, its ecosystem and its name, as
+/// the same name may be taken in several ecosystems.
+fn node_id((ecosystem, name): &PackageKey) -> String {
+    format!("{ecosystem}:{name}")
+}
+
+/// Quote an identifier of the DOT language.
+fn quote(id: &str) -> String {
+    format!("\"{}\"", escape(id))
+}
+
+/// Escape the backslashes and quotes of a quoted string of the DOT language.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
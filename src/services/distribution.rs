@@ -0,0 +1,282 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Splits of workflow budgets into allocations.
+//!
+//! Budgets are split in integer units of the configured precision, proportionally to the
+//! weights of the recipients, the units left by rounding down going to the largest
+//! remainders, so the allocations always sum up to exactly the budget. Allocations below
+//! the dust threshold are either dropped, the budget being split again over the others,
+//! or merged into the largest allocation.
+
+use anyhow::{anyhow, Result};
+use num_bigint::BigUint;
+
+use crate::{
+    contracts::types::Number,
+    services::payout::{from_base_units, to_base_units},
+};
+
+/// The bits of precision kept from floating point weights.
+const WEIGHT_BITS: i32 = 52;
+
+#[derive(Clone, clap::Parser)]
+pub struct DistributionConfig {
+    /// Decimals of the allocated amounts, budgets with more decimals are refused.
+    #[clap(long, env = "DRK_AMOUNT_PRECISION", default_value_t = 18)]
+    pub amount_precision: u8,
+
+    /// Amount, in the currency of the budget, below which an allocation is dust.
+    #[clap(long, env = "DRK_DUST_THRESHOLD", default_value = "0")]
+    pub dust_threshold: String,
+
+    /// What happens to the allocations below the dust threshold.
+    #[clap(long, env = "DRK_DUST_HANDLING", value_enum, default_value_t = DustHandling::Drop)]
+    pub dust_handling: DustHandling,
+}
+
+/// What happens to the allocations below the dust threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum DustHandling {
+    /// Drop them, the budget being split again over the other allocations
+    Drop,
+    /// Merge them into the largest allocation
+    Merge,
+}
+
+pub struct DistributionService;
+
+impl DistributionService {
+    /// Check a budget has no more decimals than the allocated amounts.
+    pub fn validate(config: &DistributionConfig, budget: &str) -> Result<()> {
+        to_base_units(budget, config.amount_precision).map(|_| ())
+    }
+
+    /// Check allocations split a budget exactly: none has more decimals than the allocated
+    /// amounts, and they sum up to the budget.
+    pub fn check<'a>(
+        config: &DistributionConfig,
+        budget: &str,
+        amounts: impl IntoIterator<Item = &'a str>,
+    ) -> Result<()> {
+        let precision = config.amount_precision;
+        let total = to_base_units(budget, precision)?;
+        let mut sum = BigUint::ZERO;
+        for amount in amounts {
+            sum += to_base_units(amount, precision)?;
+        }
+        if sum != total {
+            return Err(anyhow!(
+                "The allocations sum up to {}, not to the budget of {}",
+                from_base_units(&sum, precision),
+                from_base_units(&total, precision)
+            ));
+        }
+        Ok(())
+    }
+
+    /// Split a budget over recipients proportionally to their weights, eg. their scores.
+    /// The amounts sum up to exactly the budget, and recipients without any weight or
+    /// whose allocation is dust are left out.
+    pub fn split<K: Clone>(
+        config: &DistributionConfig,
+        budget: &str,
+        weights: &[(K, f64)],
+    ) -> Result<Vec<(K, Number)>> {
+        let precision = config.amount_precision;
+        let total = to_base_units(budget, precision)?;
+        let threshold = to_base_units(&config.dust_threshold, precision)
+            .map_err(|e| anyhow!("Invalid dust threshold: {e}"))?;
+
+        let units = to_units(weights.iter().map(|(_, weight)| *weight));
+        let mut kept: Vec<usize> =
+            (0..units.len()).filter(|&i| units[i] != BigUint::ZERO).collect();
+        if kept.is_empty() {
+            return Err(anyhow!("No recipient to split the budget over"));
+        }
+
+        let mut amounts = apportion(&total, kept.iter().map(|&i| &units[i]));
+        loop {
+            // The largest allocation is never dust, so the budget always goes somewhere.
+            let largest =
+                (0..amounts.len()).max_by(|&a, &b| amounts[a].cmp(&amounts[b]).then(b.cmp(&a)));
+            let dust: Vec<usize> = (0..amounts.len())
+                .filter(|&i| Some(i) != largest && amounts[i] < threshold)
+                .collect();
+            if dust.is_empty() {
+                break;
+            }
+
+            let retained: Vec<usize> = (0..kept.len()).filter(|i| !dust.contains(i)).collect();
+            match config.dust_handling {
+                DustHandling::Drop => {
+                    kept = retained.iter().map(|&i| kept[i]).collect();
+                    amounts = apportion(&total, kept.iter().map(|&i| &units[i]));
+                }
+                DustHandling::Merge => {
+                    let merged: BigUint = dust.iter().map(|&i| &amounts[i]).sum();
+                    if let Some(largest) = largest {
+                        amounts[largest] += merged;
+                    }
+                    (kept, amounts) =
+                        retained.iter().map(|&i| (kept[i], amounts[i].clone())).unzip();
+                    break;
+                }
+            }
+        }
+
+        debug_assert_eq!(amounts.iter().sum::<BigUint>(), total);
+        Ok(kept
+            .into_iter()
+            .zip(amounts)
+            .map(|(i, amount)| (weights[i].0.clone(), from_base_units(&amount, precision)))
+            .collect())
+    }
+}
+
+/// Convert floating point weights to integers keeping their ratios, the largest weight
+/// becoming `2^WEIGHT_BITS`. Negative, infinite or NaN weights count as zero.
+fn to_units(weights: impl Iterator<Item = f64> + Clone) -> Vec<BigUint> {
+    let valid = |weight: f64| if weight.is_finite() && weight > 0.0 { weight } else { 0.0 };
+    let max = weights.clone().map(valid).fold(0.0, f64::max);
+    weights
+        .map(|weight| {
+            // At most 2^52, exactly representable and converted without loss.
+            let units = if max > 0.0 { valid(weight) / max * 2f64.powi(WEIGHT_BITS) } else { 0.0 };
+            BigUint::from(units.round() as u64)
+        })
+        .collect()
+}
+
+/// Split a total proportionally to integer weights, rounding every part down, then
+/// giving the units left one by one to the parts with the largest remainders, the
+/// first ones on ties.
+fn apportion<'a>(
+    total: &BigUint,
+    weights: impl Iterator<Item = &'a BigUint> + Clone,
+) -> Vec<BigUint> {
+    let sum: BigUint = weights.clone().sum();
+    if sum == BigUint::ZERO {
+        return weights.map(|_| BigUint::ZERO).collect();
+    }
+
+    let (mut parts, remainders): (Vec<_>, Vec<_>) =
+        weights.map(|weight| (total * weight / &sum, total * weight % &sum)).unzip();
+    let left = total - parts.iter().sum::<BigUint>();
+
+    let mut order: Vec<usize> = (0..parts.len()).collect();
+    order.sort_by(|&a, &b| remainders[b].cmp(&remainders[a]).then(a.cmp(&b)));
+    // Each part was rounded down by less than a unit, so fewer units are left than parts.
+    let left = usize::try_from(left).unwrap_or(parts.len());
+    for &i in order.iter().take(left) {
+        parts[i] += 1u8;
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn config(
+        precision: u8,
+        dust_threshold: &str,
+        dust_handling: DustHandling,
+    ) -> DistributionConfig {
+        DistributionConfig {
+            amount_precision: precision,
+            dust_threshold: dust_threshold.to_string(),
+            dust_handling,
+        }
+    }
+
+    fn sum(amounts: &[(usize, Number)], precision: u8) -> BigUint {
+        amounts.iter().map(|(_, amount)| to_base_units(amount, precision).unwrap()).sum()
+    }
+
+    proptest! {
+        #[test]
+        fn splits_sum_up_to_exactly_the_budget(
+            budget in 1u64..,
+            weights in proptest::collection::vec(0.0f64..1e6, 1..50),
+            threshold in 0u64..1000,
+            merge in any::<bool>(),
+        ) {
+            prop_assume!(weights.iter().any(|weight| *weight > 0.0));
+            let handling = if merge { DustHandling::Merge } else { DustHandling::Drop };
+            let config = config(6, &from_base_units(&threshold.into(), 6), handling);
+            let weights: Vec<(usize, f64)> = weights.into_iter().enumerate().collect();
+            let budget = from_base_units(&budget.into(), 6);
+
+            let amounts = DistributionService::split(&config, &budget, &weights).unwrap();
+            prop_assert_eq!(sum(&amounts, 6), to_base_units(&budget, 6).unwrap());
+            DistributionService::check(&config, &budget, amounts.iter().map(|(_, a)| a.as_str()))
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn remainders_go_to_the_largest_remainders() {
+        let config = config(0, "0", DustHandling::Drop);
+        let weights = [("a", 1.0), ("b", 1.0), ("c", 1.0)];
+        let amounts = DistributionService::split(&config, "100", &weights).unwrap();
+        assert_eq!(amounts, [("a", "34".to_string()), ("b", "33".into()), ("c", "33".into())]);
+    }
+
+    #[test]
+    fn recipients_without_weight_are_left_out() {
+        let config = config(2, "0", DustHandling::Drop);
+        let weights = [("a", 3.0), ("b", 0.0), ("c", f64::NAN), ("d", 1.0)];
+        let amounts = DistributionService::split(&config, "1", &weights).unwrap();
+        assert_eq!(amounts, [("a", "0.75".to_string()), ("d", "0.25".into())]);
+
+        let nobody = [("a", 0.0)];
+        assert!(DistributionService::split(&config, "1", &nobody).is_err());
+    }
+
+    #[test]
+    fn dust_is_dropped_and_the_budget_split_again() {
+        let config = config(2, "5", DustHandling::Drop);
+        let weights = [("a", 60.0), ("b", 36.0), ("c", 4.0)];
+        let amounts = DistributionService::split(&config, "100", &weights).unwrap();
+        assert_eq!(amounts, [("a", "62.5".to_string()), ("b", "37.5".into())]);
+    }
+
+    #[test]
+    fn dust_is_merged_into_the_largest_allocation() {
+        let config = config(2, "5", DustHandling::Merge);
+        let weights = [("a", 4.0), ("b", 60.0), ("c", 33.0), ("d", 3.0)];
+        let amounts = DistributionService::split(&config, "100", &weights).unwrap();
+        assert_eq!(amounts, [("b", "67".to_string()), ("c", "33".into())]);
+    }
+
+    #[test]
+    fn budgets_are_split_at_the_configured_precision() {
+        let config = config(2, "0", DustHandling::Drop);
+        assert!(DistributionService::validate(&config, "1.25").is_ok());
+        assert!(DistributionService::validate(&config, "1.255").is_err());
+        assert!(DistributionService::split(&config, "1.255", &[("a", 1.0)]).is_err());
+    }
+
+    #[test]
+    fn allocations_must_sum_up_to_the_budget() {
+        let config = config(2, "0", DustHandling::Drop);
+        assert!(DistributionService::check(&config, "10", ["7.5", "2.5"]).is_ok());
+        assert!(DistributionService::check(&config, "10", ["7.5", "2.49"]).is_err());
+        assert!(DistributionService::check(&config, "10", ["7.5", "2.5", "0.01"]).is_err());
+        assert!(DistributionService::check(&config, "10", ["7.5", "2.500"]).is_err());
+    }
+}
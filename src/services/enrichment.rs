@@ -0,0 +1,125 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Enrichment signals of packages, like download counts or known vulnerabilities.
+//!
+//! Enrichment sources update the signals of a package as they change. Only the scores
+//! of that package are invalidated, in the projects depending on it, and the rankings
+//! of those projects are recomputed the next time they are read.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use tracing::{debug, instrument};
+
+use crate::{context::Context, responses::dependency::DependencyResponse};
+
+/// The signals of a package, from enrichment sources.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Enrichment {
+    pub downloads: u64,
+    pub active_contributors: u32,
+    pub vulnerabilities: u32,
+}
+
+/// An update of the signals of a package, leaving the missing ones unchanged.
+#[derive(Debug, Clone, Default)]
+pub struct EnrichmentUpdate {
+    pub downloads: Option<u64>,
+    pub active_contributors: Option<u32>,
+    pub vulnerabilities: Option<u32>,
+}
+
+/// The enrichment signals, in memory, with the dependency scores they invalidated.
+#[derive(Clone, Default)]
+pub struct EnrichmentStore {
+    inner: Arc<Mutex<Enrichments>>,
+}
+
+#[derive(Default)]
+struct Enrichments {
+    packages: HashMap<String, Enrichment>,
+    /// The projects depending on each package, per their latest snapshot
+    dependents: HashMap<String, BTreeSet<String>>,
+    /// The dependencies of each project, per its latest snapshot
+    dependencies: HashMap<String, BTreeSet<String>>,
+    /// The dependencies of each project whose score is out of date
+    stale: HashMap<String, BTreeSet<String>>,
+}
+
+impl EnrichmentStore {
+    pub fn get(&self, package: &str) -> Enrichment {
+        let inner = self.inner.lock().unwrap();
+        inner.packages.get(&package.to_lowercase()).cloned().unwrap_or_default()
+    }
+
+    /// Track the dependencies of a new snapshot of a project, scored with the
+    /// current signals.
+    pub fn track(&self, project: &str, dependencies: &[DependencyResponse]) {
+        let mut inner = self.inner.lock().unwrap();
+        let packages: BTreeSet<_> =
+            dependencies.iter().map(|dependency| dependency.name.to_lowercase()).collect();
+
+        for package in inner.dependencies.remove(project).unwrap_or_default() {
+            if let Some(dependents) = inner.dependents.get_mut(&package) {
+                dependents.remove(project);
+            }
+        }
+        for package in &packages {
+            inner.dependents.entry(package.clone()).or_default().insert(project.to_string());
+        }
+        inner.dependencies.insert(project.to_string(), packages);
+        inner.stale.remove(project);
+    }
+
+    /// Take the dependencies of a project whose score is out of date.
+    pub fn take_stale(&self, project: &str) -> BTreeSet<String> {
+        self.inner.lock().unwrap().stale.remove(project).unwrap_or_default()
+    }
+}
+
+pub struct EnrichmentService;
+
+impl EnrichmentService {
+    /// Update the signals of a package, invalidating its score in the projects depending
+    /// on it when they changed. Returns the number of projects invalidated.
+    #[instrument(skip_all, fields(%package))]
+    pub fn update(ctx: &Context, package: &str, update: EnrichmentUpdate) -> usize {
+        let package = package.to_lowercase();
+        let mut inner = ctx.enrichments.inner.lock().unwrap();
+
+        let enrichment = inner.packages.entry(package.clone()).or_default();
+        let previous = enrichment.clone();
+        if let Some(downloads) = update.downloads {
+            enrichment.downloads = downloads;
+        }
+        if let Some(active_contributors) = update.active_contributors {
+            enrichment.active_contributors = active_contributors;
+        }
+        if let Some(vulnerabilities) = update.vulnerabilities {
+            enrichment.vulnerabilities = vulnerabilities;
+        }
+        if *enrichment == previous {
+            return 0;
+        }
+
+        let projects = inner.dependents.get(&package).cloned().unwrap_or_default();
+        for project in &projects {
+            inner.stale.entry(project.clone()).or_default().insert(package.clone());
+        }
+        debug!(projects = projects.len(), "Invalidated dependency scores");
+        projects.len()
+    }
+}
@@ -0,0 +1,163 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Live updates of workflows, over WebSocket.
+//!
+//! The services publish the changes to workflows on an in-process event bus: the progress
+//! of their analysis, their new contributions and the status changes of their
+//! allocations. Each WebSocket connection subscribes to workflow ids or project slugs,
+//! and is sent the events of the matching workflows. Events are not kept, a client
+//! lagging too far behind is told how many it missed.
+
+use std::{collections::HashSet, sync::Arc};
+
+use axum::extract::ws::{Message, WebSocket};
+use chrono::Utc;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{debug, instrument};
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    requests::event::SubscriptionRequest,
+    responses::event::{Event, EventKind, EventMessage},
+};
+
+/// The number of events kept for the clients lagging behind.
+const CAPACITY: usize = 1024;
+
+/// The in-process event bus, broadcasting the changes to workflows.
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<Event>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        Self { sender }
+    }
+}
+
+impl EventBus {
+    /// Publish a change to a workflow, dropped when no client is connected.
+    pub fn publish(&self, workflow_id: Uuid, kind: EventKind) {
+        let event = Event { workflow_id, project: None, kind, at: Utc::now() };
+        let _ = self.sender.send(event);
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.sender.subscribe()
+    }
+}
+
+/// The workflows and projects a connection subscribed to.
+#[derive(Debug, Default)]
+struct Subscription {
+    workflows: HashSet<Uuid>,
+    /// Lowercased, as GitHub names are case-insensitive
+    projects: HashSet<String>,
+}
+
+impl Subscription {
+    fn apply(&mut self, req: SubscriptionRequest) {
+        match req {
+            SubscriptionRequest::Subscribe { workflows, projects } => {
+                self.workflows.extend(workflows);
+                self.projects.extend(projects.iter().map(|project| project.to_lowercase()));
+            }
+            SubscriptionRequest::Unsubscribe { workflows, projects } => {
+                for workflow_id in workflows {
+                    self.workflows.remove(&workflow_id);
+                }
+                for project in projects {
+                    self.projects.remove(&project.to_lowercase());
+                }
+            }
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.workflows.is_empty() && self.projects.is_empty()
+    }
+
+    fn matches(&self, event: &Event) -> bool {
+        self.workflows.contains(&event.workflow_id)
+            || event
+                .project
+                .as_ref()
+                .is_some_and(|project| self.projects.contains(&project.to_lowercase()))
+    }
+
+    fn message(&self) -> EventMessage {
+        let mut workflows: Vec<_> = self.workflows.iter().copied().collect();
+        workflows.sort();
+        let mut projects: Vec<_> = self.projects.iter().cloned().collect();
+        projects.sort();
+        EventMessage::Subscribed { workflows, projects }
+    }
+}
+
+pub struct EventService;
+
+impl EventService {
+    /// Serve a WebSocket connection until it is closed, answering its subscription
+    /// messages and sending it the events it subscribed to.
+    #[instrument(skip_all)]
+    pub async fn serve(ctx: Arc<Context>, mut socket: WebSocket) {
+        let mut events = ctx.events.subscribe();
+        let mut subscription = Subscription::default();
+
+        loop {
+            let message = tokio::select! {
+                message = socket.recv() => match message {
+                    Some(Ok(Message::Text(text))) => {
+                        match serde_json::from_str::<SubscriptionRequest>(text.as_str()) {
+                            Ok(req) => {
+                                subscription.apply(req);
+                                subscription.message()
+                            }
+                            Err(e) => EventMessage::Error { message: e.to_string() },
+                        }
+                    }
+                    // Pings are answered by axum, other frames are not part of the protocol.
+                    Some(Ok(Message::Binary(_) | Message::Ping(_) | Message::Pong(_))) => continue,
+                    Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                },
+                event = events.recv() => match event {
+                    Ok(mut event) => {
+                        if subscription.is_empty() {
+                            continue;
+                        }
+                        event.project =
+                            ctx.workflows.get(event.workflow_id).map(|record| record.project);
+                        if !subscription.matches(&event) {
+                            continue;
+                        }
+                        EventMessage::Event(event)
+                    }
+                    Err(RecvError::Lagged(missed)) => EventMessage::Lagged { missed },
+                    Err(RecvError::Closed) => break,
+                },
+            };
+
+            let Ok(text) = serde_json::to_string(&message) else {
+                continue;
+            };
+            if let Err(e) = socket.send(Message::Text(text.into())).await {
+                debug!("WebSocket connection lost: {e}");
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,84 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::sync::Arc;
+
+use num_bigint::BigUint;
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    errors::Result,
+    responses::execution::{
+        ExecutionPreviewResponse, PayoutBatchResponse, SkippedAllocationResponse,
+    },
+    services::{allocation::ExecutionStatus, batching::BatchingService, payout::PayoutService},
+};
+
+pub struct ExecutionService;
+
+impl ExecutionService {
+    /// Plan the payout of the approved allocations of a workflow, without submitting it.
+    pub async fn preview(ctx: Arc<Context>, workflow_id: Uuid) -> Result<ExecutionPreviewResponse> {
+        let mut payouts = vec![];
+        let mut skipped = vec![];
+        for record in ctx.allocations.list(workflow_id) {
+            if record.status != ExecutionStatus::Approved {
+                continue;
+            }
+            let payout = match PayoutService::for_allocation(&ctx, &record).await {
+                Ok((payout, _)) => PayoutService::prepare(ctx.contract.as_ref(), &payout).await,
+                Err(e) => Err(e),
+            };
+            match payout {
+                Ok(payout) => payouts.push(payout),
+                Err(e) => skipped.push(SkippedAllocationResponse {
+                    allocation_id: record.id.to_string(),
+                    reason: e.to_string(),
+                }),
+            }
+        }
+
+        let config = &ctx.config.batching_config;
+        let batches = BatchingService::plan(ctx.contract.as_ref(), config, payouts).await;
+
+        let mut estimated_fee = BigUint::ZERO;
+        let batches = batches
+            .iter()
+            .map(|batch| {
+                if let Some(fee) =
+                    batch.estimated_fee.as_ref().and_then(|fee| fee.parse::<BigUint>().ok())
+                {
+                    estimated_fee += fee;
+                }
+                PayoutBatchResponse {
+                    allocation_ids: batch
+                        .payouts
+                        .iter()
+                        .map(|payout| payout.allocation_id.to_string())
+                        .collect(),
+                    calls: batch.calls().len(),
+                    calldata_len: batch.calldata_len(),
+                    estimated_fee: batch.estimated_fee.clone(),
+                }
+            })
+            .collect();
+
+        Ok(ExecutionPreviewResponse {
+            workflow_id,
+            batches,
+            skipped,
+            estimated_fee: estimated_fee.to_string(),
+        })
+    }
+}
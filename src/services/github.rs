@@ -0,0 +1,440 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The DepRank GitHub App.
+//!
+//! The App authenticates with a JWT signed by its private key, and acts on a
+//! repository with a token of the installation granted to the repository. Tokens
+//! are minted on demand and reused by the installation clients until they expire.
+//!
+//! Installations are recorded by the setup callback GitHub redirects to once the App
+//! is installed, and kept up to date by the `installation` and
+//! `installation_repositories` webhook events.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{anyhow, Context as _};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use jsonwebtoken::EncodingKey;
+use octocrab::{
+    models::{AppId, InstallationId},
+    Octocrab,
+};
+use serde::Deserialize;
+use sha2::Sha256;
+use tracing::{debug, info, instrument};
+
+use crate::{
+    context::Context,
+    errors::{ApiError, Result},
+    middlewares::ratelimit::ClientKey,
+    responses::github::{InstallationResponse, RepositorySelection},
+};
+
+/// The number of repositories fetched per page when syncing an installation.
+const PER_PAGE: u32 = 100;
+
+#[derive(Clone, clap::Parser)]
+pub struct GitHubAppConfig {
+    /// The id of the GitHub App, the App is disabled without.
+    #[clap(long, env = "DRK_GITHUB_APP_ID")]
+    pub github_app_id: Option<String>,
+
+    /// The PEM private key of the GitHub App, line breaks may be escaped as `\n`.
+    #[clap(long, env = "DRK_GITHUB_APP_PRIVATE_KEY")]
+    pub github_app_private_key: Option<String>,
+
+    /// The secret GitHub signs the webhook events of the App with.
+    #[clap(long, env = "DRK_GITHUB_APP_WEBHOOK_SECRET")]
+    pub github_app_webhook_secret: Option<String>,
+
+    /// Base URL of the DepRank web app, linked from the check runs.
+    #[clap(long, env = "DRK_WEB_URL", default_value = "https://deprank.xyz")]
+    pub web_url: String,
+}
+
+/// An installation of the App on an account, with the repositories it is granted.
+#[derive(Debug, Clone)]
+pub struct Installation {
+    pub id: u64,
+    /// The login of the account, eg. `deprank`
+    pub account: String,
+    pub repository_selection: RepositorySelection,
+    /// The granted repositories, eg. `deprank/backend`, lowercased
+    pub repositories: BTreeSet<String>,
+    pub suspended: bool,
+    pub installed_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Installation {
+    /// Whether the installation may act on a project, eg. `deprank/backend`.
+    fn grants(&self, project: &str) -> bool {
+        if self.suspended {
+            return false;
+        }
+        match self.repository_selection {
+            RepositorySelection::All => project
+                .split_once('/')
+                .is_some_and(|(owner, _)| owner.eq_ignore_ascii_case(&self.account)),
+            RepositorySelection::Selected => self.repositories.contains(project),
+        }
+    }
+}
+
+/// The installations of the App, in memory, by id.
+#[derive(Clone, Default)]
+pub struct InstallationStore {
+    installations: Arc<Mutex<HashMap<u64, Installation>>>,
+}
+
+impl InstallationStore {
+    pub fn get(&self, id: u64) -> Option<Installation> {
+        self.installations.lock().unwrap().get(&id).cloned()
+    }
+
+    /// Get the installations, by account.
+    pub fn list(&self) -> Vec<Installation> {
+        let mut installations: Vec<_> =
+            self.installations.lock().unwrap().values().cloned().collect();
+        installations.sort_by_key(|installation| installation.account.to_lowercase());
+        installations
+    }
+
+    /// Record an installation, keeping when it was first installed.
+    fn put(&self, mut installation: Installation) -> Installation {
+        let mut installations = self.installations.lock().unwrap();
+        if let Some(existing) = installations.get(&installation.id) {
+            installation.installed_at = existing.installed_at;
+        }
+        installations.insert(installation.id, installation.clone());
+        installation
+    }
+
+    fn update(&self, id: u64, f: impl FnOnce(&mut Installation)) -> bool {
+        let mut installations = self.installations.lock().unwrap();
+        let Some(installation) = installations.get_mut(&id) else {
+            return false;
+        };
+        f(installation);
+        installation.updated_at = Utc::now();
+        true
+    }
+
+    fn remove(&self, id: u64) -> Option<Installation> {
+        self.installations.lock().unwrap().remove(&id)
+    }
+
+    /// Find the installation granted a project, eg. `deprank/backend`.
+    fn find(&self, project: &str) -> Option<u64> {
+        let project = project.to_lowercase();
+        let installations = self.installations.lock().unwrap();
+        installations.values().find(|installation| installation.grants(&project)).map(|i| i.id)
+    }
+}
+
+/// The GitHub App client.
+#[derive(Clone)]
+pub struct GitHubApp {
+    octocrab: Arc<Octocrab>,
+    web_url: String,
+    webhook_secret: Option<String>,
+    pub installations: InstallationStore,
+    /// The installation clients, caching their tokens
+    clients: Arc<Mutex<HashMap<u64, Arc<Octocrab>>>>,
+}
+
+impl GitHubApp {
+    /// Create the client of the configured App, `None` when no App is configured.
+    pub fn new(config: &GitHubAppConfig) -> anyhow::Result<Option<Self>> {
+        let Some(app_id) = config.github_app_id.as_deref().filter(|id| !id.is_empty()) else {
+            return Ok(None);
+        };
+        let app_id: u64 =
+            app_id.parse().map_err(|_| anyhow!("Invalid GitHub App id `{app_id}`"))?;
+        let key = config
+            .github_app_private_key
+            .as_deref()
+            .filter(|key| !key.is_empty())
+            .ok_or_else(|| anyhow!("DRK_GITHUB_APP_PRIVATE_KEY is required with a GitHub App"))?
+            .replace("\\n", "\n");
+        let key =
+            EncodingKey::from_rsa_pem(key.as_bytes()).context("Invalid GitHub App private key")?;
+
+        let octocrab = Octocrab::builder().app(AppId(app_id), key).build()?;
+        Ok(Some(Self {
+            octocrab: Arc::new(octocrab),
+            web_url: config.web_url.trim_end_matches('/').to_string(),
+            webhook_secret: config.github_app_webhook_secret.clone().filter(|s| !s.is_empty()),
+            installations: InstallationStore::default(),
+            clients: Default::default(),
+        }))
+    }
+
+    /// Get a client acting as the installation granted to a repository, eg. to fetch a
+    /// private repository or publish a check run. Repositories granted to no recorded
+    /// installation are looked up on GitHub.
+    pub async fn for_repo(&self, owner: &str, name: &str) -> anyhow::Result<Arc<Octocrab>> {
+        let project = format!("{owner}/{name}");
+        let id = match self.installations.find(&project) {
+            Some(id) => id,
+            None => {
+                let installation = self
+                    .octocrab
+                    .apps()
+                    .get_repository_installation(owner, name)
+                    .await
+                    .with_context(|| format!("The GitHub App is not installed on {project}"))?;
+                debug!(installation_id = %installation.id, "Found repository installation");
+                installation.id.0
+            }
+        };
+        self.installation(id)
+    }
+
+    /// Get the client of an installation, its token is minted on the first request.
+    fn installation(&self, id: u64) -> anyhow::Result<Arc<Octocrab>> {
+        let mut clients = self.clients.lock().unwrap();
+        if let Some(client) = clients.get(&id) {
+            return Ok(client.clone());
+        }
+        let client = Arc::new(self.octocrab.installation(InstallationId(id))?);
+        clients.insert(id, client.clone());
+        Ok(client)
+    }
+
+    /// Fetch an installation and its granted repositories from GitHub.
+    async fn fetch(&self, id: u64) -> anyhow::Result<Installation> {
+        let installation: InstallationPayload =
+            self.octocrab.get(format!("/app/installations/{id}"), None::<&()>).await?;
+
+        let client = self.installation(id)?;
+        let mut repositories = BTreeSet::new();
+        for page in 1.. {
+            let params = [("per_page", PER_PAGE), ("page", page)];
+            let granted: RepositoryPage =
+                client.get("/installation/repositories", Some(&params)).await?;
+            let count = granted.repositories.len();
+            repositories
+                .extend(granted.repositories.into_iter().map(|r| r.full_name.to_lowercase()));
+            if count < PER_PAGE as usize {
+                break;
+            }
+        }
+
+        let now = Utc::now();
+        Ok(Installation {
+            id,
+            account: installation.account.login,
+            repository_selection: installation.repository_selection,
+            repositories,
+            suspended: installation.suspended_at.is_some(),
+            installed_at: now,
+            updated_at: now,
+        })
+    }
+
+    /// Check the `X-Hub-Signature-256` header of a webhook event, eg. `sha256=5f1b…`.
+    fn verify(&self, signature: Option<&str>, body: &[u8]) -> Result<()> {
+        let secret = self.webhook_secret.as_deref().ok_or_else(|| {
+            ApiError::GitHubAppUnavailable("no webhook secret configured".to_string())
+        })?;
+        let signature = signature
+            .and_then(|signature| signature.strip_prefix("sha256="))
+            .and_then(|signature| hex::decode(signature).ok())
+            .ok_or(ApiError::InvalidWebhookSignature)?;
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .map_err(|_| ApiError::InternalServerError)?;
+        mac.update(body);
+        mac.verify_slice(&signature).map_err(|_| ApiError::InvalidWebhookSignature)
+    }
+
+    /// The URL of the page of a project, eg. `https://deprank.xyz/projects/deprank/backend`.
+    pub fn project_url(&self, project: &str) -> String {
+        format!("{}/projects/{project}", self.web_url)
+    }
+}
+
+#[derive(Deserialize)]
+struct RepositoryPage {
+    repositories: Vec<RepositoryPayload>,
+}
+
+#[derive(Deserialize)]
+struct RepositoryPayload {
+    /// eg. `deprank/backend`
+    full_name: String,
+}
+
+#[derive(Deserialize)]
+struct AccountPayload {
+    login: String,
+}
+
+#[derive(Deserialize)]
+struct InstallationPayload {
+    id: u64,
+    account: AccountPayload,
+    repository_selection: RepositorySelection,
+    #[serde(default)]
+    suspended_at: Option<String>,
+}
+
+/// An `installation` or `installation_repositories` webhook event.
+#[derive(Deserialize)]
+struct InstallationEvent {
+    action: String,
+    installation: InstallationPayload,
+    /// The granted repositories, on `installation.created`
+    #[serde(default)]
+    repositories: Vec<RepositoryPayload>,
+    #[serde(default)]
+    repositories_added: Vec<RepositoryPayload>,
+    #[serde(default)]
+    repositories_removed: Vec<RepositoryPayload>,
+    /// The selection after the change, on `installation_repositories`
+    repository_selection: Option<RepositorySelection>,
+}
+
+pub struct GitHubService;
+
+impl GitHubService {
+    /// Record the installation GitHub redirected the installing user from.
+    ///
+    /// The installation is fetched from GitHub rather than trusted from the callback.
+    #[instrument(skip_all, fields(%installation_id))]
+    pub async fn setup(ctx: Arc<Context>, installation_id: u64) -> Result<InstallationResponse> {
+        let app = app(&ctx)?;
+        let installation = app
+            .fetch(installation_id)
+            .await
+            .map_err(|e| ApiError::NotFoundInstallation(format!("{installation_id}, {e:#}")))?;
+        let installation = app.installations.put(installation);
+        info!(account = %installation.account, repositories = installation.repositories.len(),
+            "GitHub App installed");
+        Ok(to_response(&installation))
+    }
+
+    /// Apply a webhook event of the App, signed with its webhook secret. Events other than
+    /// installation changes are acknowledged and ignored.
+    #[instrument(skip_all, fields(%event))]
+    pub async fn webhook(
+        ctx: Arc<Context>,
+        event: &str,
+        signature: Option<&str>,
+        body: &[u8],
+    ) -> Result<()> {
+        let app = app(&ctx)?;
+        app.verify(signature, body)?;
+        if event != "installation" && event != "installation_repositories" {
+            debug!("Ignored webhook event");
+            return Ok(());
+        }
+
+        let payload: InstallationEvent =
+            serde_json::from_slice(body).map_err(|e| ApiError::BadWebhookRequest(e.to_string()))?;
+        let id = payload.installation.id;
+        let names = |repositories: Vec<RepositoryPayload>| -> BTreeSet<String> {
+            repositories.into_iter().map(|r| r.full_name.to_lowercase()).collect()
+        };
+        match (event, payload.action.as_str()) {
+            ("installation", "created") | ("installation", "new_permissions_accepted") => {
+                let now = Utc::now();
+                let repositories = match app.installations.get(id) {
+                    Some(existing) if payload.repositories.is_empty() => existing.repositories,
+                    _ => names(payload.repositories),
+                };
+                app.installations.put(Installation {
+                    id,
+                    account: payload.installation.account.login,
+                    repository_selection: payload.installation.repository_selection,
+                    repositories,
+                    suspended: payload.installation.suspended_at.is_some(),
+                    installed_at: now,
+                    updated_at: now,
+                });
+            }
+            ("installation", "deleted") => {
+                app.installations.remove(id);
+                app.clients.lock().unwrap().remove(&id);
+            }
+            ("installation", "suspend") => {
+                app.installations.update(id, |installation| installation.suspended = true);
+            }
+            ("installation", "unsuspend") => {
+                app.installations.update(id, |installation| installation.suspended = false);
+            }
+            ("installation_repositories", _) => {
+                let added = names(payload.repositories_added);
+                let removed = names(payload.repositories_removed);
+                let selection = payload
+                    .repository_selection
+                    .unwrap_or(payload.installation.repository_selection);
+                let known = app.installations.update(id, |installation| {
+                    installation.repository_selection = selection;
+                    installation.repositories.extend(added);
+                    installation.repositories.retain(|name| !removed.contains(name));
+                });
+                // An installation missed so far, eg. after a restart, is fetched whole.
+                if !known {
+                    if let Ok(installation) = app.fetch(id).await {
+                        app.installations.put(installation);
+                    }
+                }
+            }
+            _ => debug!(action = %payload.action, "Ignored installation action"),
+        }
+        info!(installation_id = id, action = %payload.action, "Installation updated");
+        Ok(())
+    }
+
+    /// Get the installations of the App, restricted to the admin key.
+    pub async fn list(ctx: Arc<Context>, key: &ClientKey) -> Result<Vec<InstallationResponse>> {
+        key.authorize_admin(&ctx.config.admin_api_key)?;
+        Ok(app(&ctx)?.installations.list().iter().map(to_response).collect())
+    }
+
+    /// Get an installation of the App, restricted to the admin key.
+    pub async fn get(ctx: Arc<Context>, key: &ClientKey, id: u64) -> Result<InstallationResponse> {
+        key.authorize_admin(&ctx.config.admin_api_key)?;
+        app(&ctx)?
+            .installations
+            .get(id)
+            .map(|installation| to_response(&installation))
+            .ok_or_else(|| ApiError::NotFoundInstallation(id.to_string()))
+    }
+}
+
+fn app(ctx: &Context) -> Result<&GitHubApp> {
+    ctx.github_app
+        .as_ref()
+        .ok_or_else(|| ApiError::GitHubAppUnavailable("no GitHub App configured".to_string()))
+}
+
+fn to_response(installation: &Installation) -> InstallationResponse {
+    InstallationResponse {
+        id: installation.id,
+        account: installation.account.clone(),
+        repository_selection: installation.repository_selection,
+        repositories: installation.repositories.iter().cloned().collect(),
+        suspended: installation.suspended,
+        installed_at: installation.installed_at,
+        updated_at: installation.updated_at,
+    }
+}
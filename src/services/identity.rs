@@ -0,0 +1,240 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contributor identities, merging the emails and usernames a contributor commits under.
+//!
+//! The author of a blamed commit is resolved, in order, by:
+//! - the manual merges, from an alias to the username it was merged into,
+//! - the GitHub account of the author,
+//! - the `.mailmap` of the repository, mapping a commit email to the proper one,
+//! - the GitHub noreply emails, eg. `123+octocat@users.noreply.github.com`,
+//! - the emails of the authors already resolved to a GitHub account in the repository.
+//!
+//! Manual merges also fold the contributors already attributed, so the scores of a
+//! contributor are never diluted across duplicates.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    context::Context,
+    errors::{ApiError, Result},
+    middlewares::ratelimit::ClientKey,
+    requests::identity::IdentityRequest,
+    responses::identity::IdentityResponse,
+};
+
+/// The domain of the GitHub noreply emails.
+const NOREPLY_DOMAIN: &str = "@users.noreply.github.com";
+
+/// The manual merges, in memory.
+#[derive(Clone, Default)]
+pub struct IdentityStore {
+    inner: Arc<Mutex<Identities>>,
+}
+
+#[derive(Default)]
+struct Identities {
+    /// The username each alias, an email or another username, was merged into
+    usernames: HashMap<String, String>,
+    /// The aliases merged into each username
+    aliases: HashMap<String, BTreeSet<String>>,
+}
+
+impl IdentityStore {
+    /// Get the username an email or a username was merged into, itself if none.
+    pub fn canonical(&self, alias: &str) -> String {
+        let alias = alias.to_lowercase();
+        self.inner.lock().unwrap().usernames.get(&alias).cloned().unwrap_or(alias)
+    }
+
+    fn aliases(&self, username: &str) -> BTreeSet<String> {
+        let inner = self.inner.lock().unwrap();
+        inner.aliases.get(&username.to_lowercase()).cloned().unwrap_or_default()
+    }
+}
+
+/// The mapping of the commit emails to the proper ones, from a `.mailmap` file.
+#[derive(Debug, Clone, Default)]
+pub struct Mailmap {
+    emails: HashMap<String, String>,
+}
+
+impl Mailmap {
+    /// Load the `.mailmap` at the root of a checkout, empty if there is none.
+    pub fn load(checkout: &Path) -> Self {
+        std::fs::read_to_string(checkout.join(".mailmap"))
+            .map(|content| Self::parse(&content))
+            .unwrap_or_default()
+    }
+
+    /// Parse the lines mapping a commit email to a proper one, eg.
+    /// `Proper Name <proper@email> Commit Name <commit@email>`. Lines with a single email
+    /// only change the name, and are skipped.
+    pub fn parse(content: &str) -> Self {
+        let mut emails = HashMap::new();
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or_default();
+            let addresses: Vec<_> = line
+                .split('<')
+                .skip(1)
+                .filter_map(|part| part.split_once('>').map(|(email, _)| email.trim()))
+                .collect();
+            if let [proper, commit] = addresses[..] {
+                emails.insert(commit.to_lowercase(), proper.to_lowercase());
+            }
+        }
+        Self { emails }
+    }
+
+    fn resolve(&self, email: &str) -> String {
+        let email = email.to_lowercase();
+        self.emails.get(&email).cloned().unwrap_or(email)
+    }
+}
+
+/// Resolves the authors of the commits of a repository to contributors.
+pub struct IdentityResolver<'a> {
+    identities: &'a IdentityStore,
+    mailmap: Mailmap,
+    /// The GitHub account of the authors, by proper email
+    logins: HashMap<String, String>,
+}
+
+impl<'a> IdentityResolver<'a> {
+    pub fn new(identities: &'a IdentityStore, mailmap: Mailmap) -> Self {
+        Self { identities, mailmap, logins: HashMap::new() }
+    }
+
+    /// Learn the GitHub account of an author email, for the commits of that email
+    /// missing one.
+    pub fn learn(&mut self, email: Option<&str>, login: Option<&str>) {
+        if let (Some(email), Some(login)) = (email, login) {
+            self.logins.entry(self.mailmap.resolve(email)).or_insert_with(|| login.to_lowercase());
+        }
+    }
+
+    /// Resolve the author of a commit to the username of a contributor, if any.
+    pub fn resolve(&self, email: Option<&str>, login: Option<&str>) -> Option<String> {
+        if let Some(login) = login {
+            return Some(self.identities.canonical(login));
+        }
+
+        let email = self.mailmap.resolve(email?);
+        let merged = self.identities.canonical(&email);
+        if merged != email {
+            return Some(merged);
+        }
+        if let Some(login) = noreply_login(&email) {
+            return Some(self.identities.canonical(login));
+        }
+        self.logins.get(&email).map(|login| self.identities.canonical(login))
+    }
+}
+
+/// The username of a GitHub noreply email, eg. `123+octocat@users.noreply.github.com`.
+fn noreply_login(email: &str) -> Option<&str> {
+    let local = email.strip_suffix(NOREPLY_DOMAIN)?;
+    let login = local.split_once('+').map_or(local, |(_, login)| login);
+    (!login.is_empty()).then_some(login)
+}
+
+pub struct IdentityService;
+
+impl IdentityService {
+    pub async fn get(ctx: Arc<Context>, username: &str) -> Result<IdentityResponse> {
+        Ok(to_response(username, ctx.identities.aliases(username)))
+    }
+
+    /// Merge emails and usernames into a contributor, along with their own aliases.
+    pub async fn merge(
+        ctx: Arc<Context>,
+        key: &ClientKey,
+        username: &str,
+        req: IdentityRequest,
+    ) -> Result<IdentityResponse> {
+        authorize(&ctx, key)?;
+
+        let username = username.to_lowercase();
+        let mut inner = ctx.identities.inner.lock().unwrap();
+        if let Some(merged) = inner.usernames.get(&username) {
+            return Err(ApiError::BadIdentityRequest(format!(
+                "{username} is merged into {merged}, merge into {merged} instead"
+            )));
+        }
+        for alias in &req.aliases {
+            let alias = alias.trim().to_lowercase();
+            if alias.is_empty() || alias == username {
+                return Err(ApiError::BadIdentityRequest(format!(
+                    "invalid alias `{alias}` of {username}"
+                )));
+            }
+            if let Some(merged) = inner.usernames.get(&alias).filter(|merged| **merged != username)
+            {
+                return Err(ApiError::BadIdentityRequest(format!(
+                    "{alias} is merged into {merged}, split it first"
+                )));
+            }
+        }
+
+        for alias in req.aliases {
+            let alias = alias.trim().to_lowercase();
+            // The aliases of a merged username follow it
+            let mut merged = inner.aliases.remove(&alias).unwrap_or_default();
+            merged.insert(alias);
+            for alias in merged {
+                inner.usernames.insert(alias.clone(), username.clone());
+                inner.aliases.entry(username.clone()).or_default().insert(alias);
+            }
+        }
+
+        let aliases = inner.aliases.get(&username).cloned().unwrap_or_default();
+        Ok(to_response(&username, aliases))
+    }
+
+    /// Split an email or a username from the contributor it was merged into.
+    pub async fn split(
+        ctx: Arc<Context>,
+        key: &ClientKey,
+        username: &str,
+        alias: &str,
+    ) -> Result<IdentityResponse> {
+        authorize(&ctx, key)?;
+
+        let (username, alias) = (username.to_lowercase(), alias.to_lowercase());
+        let mut inner = ctx.identities.inner.lock().unwrap();
+        let aliases = inner.aliases.get_mut(&username).ok_or(ApiError::NotFound)?;
+        if !aliases.remove(&alias) {
+            return Err(ApiError::NotFound);
+        }
+        let aliases = aliases.clone();
+        if aliases.is_empty() {
+            inner.aliases.remove(&username);
+        }
+        inner.usernames.remove(&alias);
+        Ok(to_response(&username, aliases))
+    }
+}
+
+/// Only the admin key can merge identities, until contributors can authenticate.
+fn authorize(ctx: &Context, key: &ClientKey) -> Result<()> {
+    key.authorize_admin(&ctx.config.admin_api_key)
+}
+
+fn to_response(username: &str, aliases: BTreeSet<String>) -> IdentityResponse {
+    IdentityResponse { username: username.to_lowercase(), aliases: aliases.into_iter().collect() }
+}
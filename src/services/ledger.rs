@@ -0,0 +1,236 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use num_bigint::BigUint;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::{
+    contracts::{
+        types::{Hash, Id, Number},
+        Contract,
+    },
+    db::{self, Database, Write, WriteError},
+    errors::{ApiError, Result},
+    responses::ledger::{LedgerBalanceResponse, LedgerEntryResponse, LedgerResponse, Side},
+    services::{
+        allocation::AllocationRecord,
+        payout::{from_base_units, to_base_units},
+    },
+};
+
+/// The precision used to sum amounts, in decimals.
+const SCALE: u8 = 18;
+
+/// A single posting on an account.
+#[derive(Debug, Clone)]
+pub struct LedgerEntry {
+    pub workflow_id: Uuid,
+    pub account: String,
+    pub side: Side,
+    /// The amount in whole tokens
+    pub amount: Number,
+    pub token: String,
+    pub allocation_id: Id,
+    pub tx_hash: Option<Hash>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Double-entry ledger of the funds moved by workflows.
+///
+/// Every payout debits the recipient account and credits the workflow treasury
+/// account by the same amount, so the entries of a workflow always balance.
+#[derive(Clone, Default)]
+pub struct Ledger {
+    entries: Arc<Mutex<Vec<LedgerEntry>>>,
+    db: Option<Database>,
+}
+
+impl Ledger {
+    /// Load the entries from the database, in memory only without one.
+    pub async fn load(db: Option<&Database>) -> anyhow::Result<Self> {
+        let Some(db) = db else {
+            return Ok(Self::default());
+        };
+
+        let entries = db::ledger::load(db.pool()).await?;
+        Ok(Self { entries: Arc::new(Mutex::new(entries)), db: Some(db.clone()) })
+    }
+
+    /// Post a confirmed payout of an allocation, failing if it could not be written to
+    /// the database.
+    pub async fn post_payout(&self, record: &AllocationRecord) -> Result<(), WriteError> {
+        let amount = record.token_amount.clone().unwrap_or_else(|| record.amount.clone());
+        let entry = |account: String, side| LedgerEntry {
+            workflow_id: record.workflow_id,
+            account,
+            side,
+            amount: amount.clone(),
+            token: record.token.to_string(),
+            allocation_id: record.id.clone(),
+            tx_hash: record.tx_hash.clone(),
+            created_at: Utc::now(),
+        };
+
+        let posted = vec![
+            entry(recipient_account(&record.recipient), Side::Debit),
+            entry(treasury_account(record.workflow_id), Side::Credit),
+        ];
+        self.append(posted).await
+    }
+
+    /// Reverse the payout of an allocation whose transaction was removed from the chain,
+    /// posting the opposite entries so the history is kept, failing if they could not be
+    /// written to the database.
+    pub async fn reverse_payout(&self, record: &AllocationRecord) -> Result<(), WriteError> {
+        let reversed: Vec<_> = self
+            .entries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|entry| entry.allocation_id == record.id && entry.tx_hash == record.tx_hash)
+            .map(|entry| LedgerEntry {
+                side: match entry.side {
+                    Side::Debit => Side::Credit,
+                    Side::Credit => Side::Debit,
+                },
+                created_at: Utc::now(),
+                ..entry.clone()
+            })
+            .collect();
+        self.append(reversed).await
+    }
+
+    /// Append entries once written to the database.
+    async fn append(&self, posted: Vec<LedgerEntry>) -> Result<(), WriteError> {
+        if let Some(db) = &self.db {
+            db.commit(Write::LedgerEntries(posted.clone())).await?;
+        }
+        self.entries.lock().unwrap().extend(posted);
+        Ok(())
+    }
+
+    /// Get the entries of a workflow with the balance of each account, linking the payout
+    /// transactions to the block explorer of the contract.
+    pub fn get<C: Contract>(&self, workflow_id: Uuid, contract: &C) -> Result<LedgerResponse> {
+        let entries: Vec<LedgerEntry> = self
+            .entries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|entry| entry.workflow_id == workflow_id)
+            .cloned()
+            .collect();
+
+        let mut totals: BTreeMap<(String, String), (BigUint, BigUint)> = BTreeMap::new();
+        for entry in &entries {
+            // Summing what can be of the entries would show wrong balances.
+            let amount = to_base_units(&entry.amount, SCALE).map_err(|e| {
+                error!(allocation_id = %entry.allocation_id, "Invalid ledger amount: {e:#}");
+                ApiError::InternalServerError
+            })?;
+            let (debit, credit) =
+                totals.entry((entry.account.clone(), entry.token.clone())).or_default();
+            match entry.side {
+                Side::Debit => *debit += amount,
+                Side::Credit => *credit += amount,
+            }
+        }
+
+        let balances = totals
+            .into_iter()
+            .map(|((account, token), (debit, credit))| {
+                let balance = if debit >= credit {
+                    from_base_units(&(&debit - &credit), SCALE)
+                } else {
+                    format!("-{}", from_base_units(&(&credit - &debit), SCALE))
+                };
+                LedgerBalanceResponse {
+                    account,
+                    token,
+                    debit: from_base_units(&debit, SCALE),
+                    credit: from_base_units(&credit, SCALE),
+                    balance,
+                }
+            })
+            .collect();
+
+        let entries = entries
+            .into_iter()
+            .map(|entry| LedgerEntryResponse {
+                account: entry.account,
+                side: entry.side,
+                amount: entry.amount,
+                token: entry.token,
+                allocation_id: entry.allocation_id.to_string(),
+                tx_url: entry.tx_hash.as_deref().map(|tx_hash| contract.explorer_tx_url(tx_hash)),
+                tx_hash: entry.tx_hash.map(String::from),
+                created_at: entry.created_at,
+            })
+            .collect();
+
+        Ok(LedgerResponse { workflow_id, entries, balances })
+    }
+}
+
+pub struct LedgerService;
+
+impl LedgerService {
+    /// Render the entries of a ledger as CSV.
+    pub fn to_csv(ledger: &LedgerResponse) -> String {
+        let mut csv = String::from("created_at,account,side,amount,token,allocation_id,tx_hash\n");
+        for entry in &ledger.entries {
+            let side = match entry.side {
+                Side::Debit => "debit",
+                Side::Credit => "credit",
+            };
+            let fields = [
+                entry.created_at.to_rfc3339(),
+                entry.account.clone(),
+                side.to_string(),
+                entry.amount.clone(),
+                entry.token.clone(),
+                entry.allocation_id.clone(),
+                entry.tx_hash.clone().unwrap_or_default(),
+            ];
+            let fields: Vec<String> = fields.iter().map(|field| escape(field)).collect();
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+fn treasury_account(workflow_id: Uuid) -> String {
+    format!("treasury:{workflow_id}")
+}
+
+fn recipient_account(address: &str) -> String {
+    format!("recipient:{address}")
+}
+
+/// Quote a CSV field when needed.
+pub(crate) fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
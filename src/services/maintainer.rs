@@ -0,0 +1,185 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The claim portal of the maintainers.
+//!
+//! A standalone claim UI looks up the allocations of a maintainer by their GitHub
+//! identity, links their wallet, has them sign the sponsored claim of an allocation and
+//! follows the claim until it is paid out. Allocations are matched on the contributor
+//! they fund, including the emails and usernames merged into the maintainer.
+
+use std::sync::Arc;
+
+use tracing::{info, instrument};
+
+use crate::{
+    context::Context,
+    contracts::{types::Address, Contract},
+    errors::{ApiError, Result},
+    middlewares::ratelimit::ClientKey,
+    requests::{
+        address::Chain,
+        claim::{BuildClaimRequest, ExecuteClaimRequest},
+        maintainer::SignedClaimRequest,
+        wallet::WalletAddressRequest,
+    },
+    responses::{
+        claim::{ClaimExecutedResponse, ClaimResponse},
+        maintainer::{ClaimStatus, MaintainerClaimResponse, MaintainerClaimsResponse},
+    },
+    services::{
+        allocation::{AllocationRecord, ExecutionStatus},
+        claim::ClaimService,
+    },
+};
+
+pub struct MaintainerService;
+
+impl MaintainerService {
+    /// Get the allocations funding a maintainer, the claimable ones first.
+    pub async fn claims(ctx: Arc<Context>, username: &str) -> Result<MaintainerClaimsResponse> {
+        let mut records = Self::records(&ctx, username);
+        records.sort_by_key(|record| (status(record.status), record.id.clone()));
+        Ok(MaintainerClaimsResponse {
+            username: ctx.identities.canonical(username),
+            wallet: Self::wallet(&ctx, username)?,
+            claims: records.iter().map(|record| to_response(&ctx, record)).collect(),
+        })
+    }
+
+    /// Get an allocation funding a maintainer, to follow its claim.
+    pub async fn claim(
+        ctx: Arc<Context>,
+        username: &str,
+        allocation_id: &str,
+    ) -> Result<MaintainerClaimResponse> {
+        Ok(to_response(&ctx, &Self::record(&ctx, username, allocation_id)?))
+    }
+
+    /// Link the wallet of a maintainer on the chain of the contracts, the claimable
+    /// allocations funding the maintainer are paid out to it.
+    #[instrument(skip_all, fields(%username))]
+    pub async fn link(
+        ctx: Arc<Context>,
+        key: &ClientKey,
+        username: &str,
+        req: WalletAddressRequest,
+    ) -> Result<MaintainerClaimsResponse> {
+        // Only the admin key can link wallets, until maintainers can authenticate.
+        key.authorize_admin(&ctx.config.admin_api_key)?;
+
+        let address = req.address;
+        ctx.addresses.link(username, chain(&ctx)?, address.clone()).await?;
+        for record in Self::records(&ctx, username) {
+            if record.status == ExecutionStatus::Approved {
+                ctx.allocations
+                    .update(&record.id, |record| record.recipient = address.clone())
+                    .await?;
+            }
+        }
+        info!("Wallet linked");
+
+        Self::claims(ctx, username).await
+    }
+
+    /// Build the sponsored claim of an allocation, to be signed by the linked wallet.
+    pub async fn build(
+        ctx: Arc<Context>,
+        username: &str,
+        allocation_id: &str,
+    ) -> Result<ClaimResponse> {
+        let record = Self::record(&ctx, username, allocation_id)?;
+        let address = Self::linked(&ctx, username)?;
+        ClaimService::build(ctx, record.workflow_id, allocation_id, &BuildClaimRequest { address })
+            .await
+    }
+
+    /// Submit the claim of an allocation, signed by the linked wallet.
+    pub async fn execute(
+        ctx: Arc<Context>,
+        username: &str,
+        allocation_id: &str,
+        req: SignedClaimRequest,
+    ) -> Result<ClaimExecutedResponse> {
+        let record = Self::record(&ctx, username, allocation_id)?;
+        let req = ExecuteClaimRequest {
+            address: Self::linked(&ctx, username)?,
+            typed_data: req.typed_data,
+            signature: req.signature,
+        };
+        ClaimService::execute(ctx, record.workflow_id, allocation_id, &req).await
+    }
+
+    /// The allocations funding the maintainer, or one of their merged identities.
+    fn records(ctx: &Context, username: &str) -> Vec<AllocationRecord> {
+        let username = ctx.identities.canonical(username);
+        ctx.allocations
+            .by_contributor(|contributor| ctx.identities.canonical(contributor) == username)
+    }
+
+    fn record(ctx: &Context, username: &str, allocation_id: &str) -> Result<AllocationRecord> {
+        Self::records(ctx, username)
+            .into_iter()
+            .find(|record| record.id == allocation_id)
+            .ok_or_else(|| ApiError::NotFoundAllocation(allocation_id.to_string()))
+    }
+
+    fn wallet(ctx: &Context, username: &str) -> Result<Option<String>> {
+        let address = ctx.addresses.resolve(&ctx.identities.canonical(username), chain(ctx)?);
+        Ok(address.map(String::from))
+    }
+
+    fn linked(ctx: &Context, username: &str) -> Result<Address> {
+        let address = Self::wallet(ctx, username)?
+            .ok_or_else(|| ApiError::BadClaimRequest("link a wallet first".to_string()))?;
+        Ok(Address::new(address)?)
+    }
+}
+
+/// The chain of the contracts the allocations are paid out on.
+fn chain(ctx: &Context) -> Result<Chain> {
+    ctx.contract.chain().parse().map_err(|_| ApiError::InternalServerError)
+}
+
+/// Order the claimable allocations first, the paid out ones last.
+fn status(status: ExecutionStatus) -> u8 {
+    match status {
+        ExecutionStatus::Approved | ExecutionStatus::AwaitingClaim => 0,
+        ExecutionStatus::Submitted => 1,
+        ExecutionStatus::Failed => 2,
+        ExecutionStatus::Executed => 3,
+    }
+}
+
+fn to_response(ctx: &Context, record: &AllocationRecord) -> MaintainerClaimResponse {
+    MaintainerClaimResponse {
+        allocation_id: record.id.to_string(),
+        workflow_id: record.workflow_id,
+        ecosystem: record.package.as_ref().map(|package| package.ecosystem),
+        package: record.package.as_ref().map(|package| package.name.clone()),
+        amount: record.amount.clone(),
+        denomination: record.denomination,
+        token: record.token.to_string(),
+        recipient: record.recipient.to_string(),
+        status: match record.status {
+            ExecutionStatus::Approved | ExecutionStatus::AwaitingClaim => ClaimStatus::Claimable,
+            ExecutionStatus::Submitted => ClaimStatus::Submitted,
+            ExecutionStatus::Executed => ClaimStatus::Claimed,
+            ExecutionStatus::Failed => ClaimStatus::Failed,
+        },
+        tx_hash: record.tx_hash.clone().map(String::from),
+        tx_url: record.tx_hash.as_deref().map(|tx_hash| ctx.contract.explorer_tx_url(tx_hash)),
+        error: record.error.clone(),
+    }
+}
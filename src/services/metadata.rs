@@ -0,0 +1,185 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Versioned schema of the dependency metadata.
+//!
+//! Every dependency created on chain carries a `metadata_json` document. Documents are
+//! validated against the schema version they declare before they are hashed or written,
+//! and rewritten in canonical form, so equal metadata always has the same hash.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::{
+    contracts::hash::{canonical_json, metadata_hash},
+    errors::{ApiError, Result},
+    responses::{
+        dependency::Ecosystem, metadata::MetadataResponse, validation::FieldErrorResponse,
+    },
+};
+
+/// The latest version of the metadata schema.
+pub const METADATA_SCHEMA_VERSION: u32 = 1;
+
+/// The longest text field, in characters.
+const MAX_FIELD_LEN: usize = 256;
+
+/// The metadata of a dependency, version 1 of the schema.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyMetadata {
+    pub schema_version: u32,
+    pub ecosystem: Ecosystem,
+    pub name: String,
+    pub version: String,
+    /// SPDX license expression, eg. `MIT OR Apache-2.0`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    pub repository_url: String,
+    /// The share of the project score of the dependency, between 0 and 1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub share: Option<f64>,
+}
+
+pub struct MetadataService;
+
+impl MetadataService {
+    /// Get the JSON Schema of a version of the metadata.
+    pub fn schema(version: u32) -> Result<Value> {
+        if version != METADATA_SCHEMA_VERSION {
+            return Err(ApiError::NotFound);
+        }
+        Ok(json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": format!("https://deprank.xyz/schemas/dependency-metadata/{version}"),
+            "title": "Dependency metadata",
+            "type": "object",
+            "required": ["schema_version", "ecosystem", "name", "version", "repository_url"],
+            "additionalProperties": false,
+            "properties": {
+                "schema_version": { "const": version },
+                "ecosystem": { "enum": ["cargo", "npm", "pypi", "maven"] },
+                "name": { "type": "string", "minLength": 1, "maxLength": MAX_FIELD_LEN },
+                "version": { "type": "string", "minLength": 1, "maxLength": MAX_FIELD_LEN },
+                "license": {
+                    "type": "string",
+                    "description": "SPDX license expression",
+                    "maxLength": MAX_FIELD_LEN
+                },
+                "repository_url": { "type": "string", "format": "uri", "pattern": "^https://" },
+                "share": { "type": "number", "minimum": 0, "maximum": 1 }
+            }
+        }))
+    }
+
+    /// Validate a metadata document, returning it parsed and in canonical form with its
+    /// hash, or every invalid field.
+    pub fn validate(document: &Value) -> Result<(DependencyMetadata, MetadataResponse)> {
+        let Value::Object(fields) = document else {
+            return Err(invalid(vec![error("", "must be an object")]));
+        };
+
+        let mut errors = Vec::new();
+        match fields.get("schema_version").and_then(Value::as_u64) {
+            Some(version) if version == u64::from(METADATA_SCHEMA_VERSION) => {}
+            Some(version) => {
+                errors.push(error("/schema_version", &format!("unsupported version {version}")))
+            }
+            None => errors.push(error("/schema_version", "is required, as an integer")),
+        }
+        for name in fields.keys() {
+            if !KNOWN_FIELDS.contains(&name.as_str()) {
+                errors.push(error(&format!("/{name}"), "is not a known field"));
+            }
+        }
+        if fields.get("ecosystem").and_then(ecosystem).is_none() {
+            errors.push(error("/ecosystem", "must be one of `cargo`, `npm`, `pypi` or `maven`"));
+        }
+        text(fields, "name", true, &mut errors, |_| None);
+        text(fields, "version", true, &mut errors, |_| None);
+        text(fields, "license", false, &mut errors, |license| {
+            let valid = license.chars().all(|c| c.is_ascii_alphanumeric() || "-.+:() ".contains(c));
+            (!valid).then_some("must be an SPDX license expression")
+        });
+        text(fields, "repository_url", true, &mut errors, |url| {
+            let valid = url::Url::parse(url)
+                .is_ok_and(|url| url.scheme() == "https" && url.host_str().is_some());
+            (!valid).then_some("must be an https URL")
+        });
+        match fields.get("share") {
+            None | Some(Value::Null) => {}
+            Some(share) => {
+                if !share.as_f64().is_some_and(|share| (0.0..=1.0).contains(&share)) {
+                    errors.push(error("/share", "must be a number between 0 and 1"));
+                }
+            }
+        }
+        if !errors.is_empty() {
+            return Err(invalid(errors));
+        }
+
+        let metadata: DependencyMetadata = serde_json::from_value(document.clone())
+            .map_err(|e| invalid(vec![error("", &e.to_string())]))?;
+        let metadata_json =
+            serde_json::to_value(&metadata).map(|value| canonical_json(&value)).unwrap_or_default();
+        let response = MetadataResponse {
+            schema_version: metadata.schema_version,
+            metadata_hash: metadata_hash(&metadata_json).to_string(),
+            metadata_json,
+        };
+        Ok((metadata, response))
+    }
+}
+
+/// The fields of version 1 of the schema.
+const KNOWN_FIELDS: [&str; 7] =
+    ["schema_version", "ecosystem", "name", "version", "license", "repository_url", "share"];
+
+fn ecosystem(value: &Value) -> Option<Ecosystem> {
+    serde_json::from_value(value.clone()).ok()
+}
+
+/// Check a text field, and its format with `check`, which returns the error if any.
+fn text(
+    fields: &Map<String, Value>,
+    name: &str,
+    required: bool,
+    errors: &mut Vec<FieldErrorResponse>,
+    check: impl Fn(&str) -> Option<&'static str>,
+) {
+    let pointer = format!("/{name}");
+    match fields.get(name) {
+        None | Some(Value::Null) if required => errors.push(error(&pointer, "is required")),
+        None | Some(Value::Null) => {}
+        Some(Value::String(value)) if value.trim().is_empty() => {
+            errors.push(error(&pointer, "must not be empty"))
+        }
+        Some(Value::String(value)) if value.chars().count() > MAX_FIELD_LEN => {
+            errors.push(error(&pointer, &format!("must be at most {MAX_FIELD_LEN} characters")))
+        }
+        Some(Value::String(value)) => {
+            if let Some(message) = check(value) {
+                errors.push(error(&pointer, message));
+            }
+        }
+        Some(_) => errors.push(error(&pointer, "must be a string")),
+    }
+}
+
+fn error(field: &str, message: &str) -> FieldErrorResponse {
+    FieldErrorResponse { field: field.to_string(), message: message.to_string() }
+}
+
+fn invalid(errors: Vec<FieldErrorResponse>) -> ApiError {
+    ApiError::InvalidMetadata(errors)
+}
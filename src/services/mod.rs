@@ -12,8 +12,50 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod address;
+pub mod allocation;
 pub mod analyzer;
+pub mod artifact;
+pub mod attestation;
+pub mod batch;
+pub mod batching;
+pub mod breaker;
+pub mod check;
+pub mod claim;
 pub mod contract;
+pub mod contribution;
+pub mod contributor;
+pub mod cost;
+pub mod credential;
+pub mod dependency;
+pub mod distribution;
+pub mod enrichment;
+pub mod event;
+pub mod execution;
+pub mod github;
+pub mod identity;
+pub mod ledger;
+pub mod maintainer;
+pub mod metadata;
+pub mod notification;
+pub mod organization;
+pub mod payout;
+pub mod policy;
+pub mod pool;
+pub mod price;
+pub mod profile;
 pub mod project;
+pub mod quota;
+pub mod ranking;
+pub mod receipt;
+pub mod report;
+pub mod screening;
+pub mod sign;
+pub mod snapshot;
+pub mod step;
 pub mod storage;
+pub mod transaction;
+pub mod treasury;
+pub mod usage;
+pub mod webhook;
 pub mod workflow;
@@ -0,0 +1,253 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Notifications of users, per their preferences.
+//!
+//! Users are identified by their GitHub username, contributors receive allocation
+//! receipts and project owners the completion of their workflows. The events of a
+//! workflow are also posted to the Discord and Slack channels of the workflow, or of
+//! its owner. Delivery failures are logged, they never fail what triggered the
+//! notification.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    errors::{ApiError, Result},
+    middlewares::ratelimit::ClientKey,
+    notifiers::{email::is_valid_email, webhook::WebhookChannel, Notification, NotificationKind},
+    requests::notification::{NotificationChannelsRequest, NotificationPreferencesRequest},
+    responses::notification::{NotificationChannelsResponse, NotificationPreferencesResponse},
+};
+
+/// The notification preferences of a user.
+#[derive(Debug, Clone, Default)]
+struct Preferences {
+    email: Option<String>,
+    opt_out: BTreeSet<NotificationKind>,
+}
+
+/// The chat channels the events of workflows are posted to.
+#[derive(Debug, Clone, Default)]
+struct Channels {
+    discord: Option<String>,
+    slack: Option<String>,
+}
+
+impl Channels {
+    fn is_empty(&self) -> bool {
+        self.discord.is_none() && self.slack.is_none()
+    }
+}
+
+/// What chat channels are configured for.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ChannelScope {
+    /// Every workflow of an owner, eg. `deprank`
+    Owner(String),
+    /// A single workflow, overriding the channels of its owner
+    Workflow(Uuid),
+}
+
+/// In-memory notification preferences, by GitHub username, the owners notified of each
+/// workflow and the chat channels.
+#[derive(Clone, Default)]
+pub struct NotificationStore {
+    preferences: Arc<Mutex<HashMap<String, Preferences>>>,
+    owners: Arc<Mutex<HashMap<Uuid, String>>>,
+    channels: Arc<Mutex<HashMap<ChannelScope, Channels>>>,
+}
+
+impl NotificationStore {
+    /// Notify the owner of a workflow, and its channels, of the workflow events.
+    pub fn watch(&self, workflow_id: Uuid, owner: &str) {
+        self.owners.lock().unwrap().insert(workflow_id, owner.to_lowercase());
+    }
+
+    /// The owner notified of a workflow.
+    pub fn owner(&self, workflow_id: Uuid) -> Option<String> {
+        self.owners.lock().unwrap().get(&workflow_id).cloned()
+    }
+
+    /// The channels of a scope, falling back to the owner of a workflow, and whether
+    /// they were inherited from the owner.
+    fn channels(&self, scope: &ChannelScope) -> (Channels, bool) {
+        let channels = self.channels.lock().unwrap().get(scope).cloned().unwrap_or_default();
+        let ChannelScope::Workflow(workflow_id) = scope else {
+            return (channels, false);
+        };
+        if !channels.is_empty() {
+            return (channels, false);
+        }
+        match self.owner(*workflow_id) {
+            Some(owner) => (self.channels(&ChannelScope::Owner(owner)).0, true),
+            None => (channels, false),
+        }
+    }
+
+    fn get(&self, username: &str) -> Preferences {
+        let preferences = self.preferences.lock().unwrap();
+        preferences.get(&username.to_lowercase()).cloned().unwrap_or_default()
+    }
+}
+
+pub struct NotificationService;
+
+impl NotificationService {
+    /// Notify a user on every configured channel, unless they opted out.
+    pub async fn notify(ctx: &Context, username: &str, notification: Notification) {
+        let kind = notification.kind();
+        let preferences = ctx.notifications.get(username);
+        if preferences.opt_out.contains(&kind) {
+            return;
+        }
+
+        let message = notification.message();
+        if let (Some(email), Some(notifier)) = (&preferences.email, &ctx.email) {
+            match notifier.send(email, &message).await {
+                Ok(()) => info!(%username, %kind, channel = notifier.channel(), "User notified"),
+                Err(e) => warn!(%username, %kind, "Failed to notify user: {e}"),
+            }
+        }
+    }
+
+    /// Post an event of a workflow to its chat channels.
+    pub async fn broadcast(ctx: &Context, workflow_id: Uuid, notification: Notification) {
+        let kind = notification.kind();
+        let (channels, _) = ctx.notifications.channels(&ChannelScope::Workflow(workflow_id));
+        let message = notification.message();
+
+        let targets = [(&ctx.discord, &channels.discord), (&ctx.slack, &channels.slack)];
+        for (notifier, url) in targets {
+            let Some(url) = url else {
+                continue;
+            };
+            match notifier.send(url, &message).await {
+                Ok(()) => info!(%workflow_id, %kind, channel = notifier.channel(), "Posted"),
+                Err(e) => {
+                    warn!(%workflow_id, %kind, "Failed to post to {}: {e}", notifier.channel())
+                }
+            }
+        }
+    }
+
+    /// Get the chat channels of an owner or a workflow, restricted to the admin key.
+    pub async fn get_channels(
+        ctx: Arc<Context>,
+        key: &ClientKey,
+        scope: ChannelScope,
+    ) -> Result<NotificationChannelsResponse> {
+        key.authorize_admin(&ctx.config.admin_api_key)?;
+        let (channels, inherited) = ctx.notifications.channels(&normalize(scope));
+        Ok(to_channels_response(&channels, inherited))
+    }
+
+    /// Replace the chat channels of an owner or a workflow, restricted to the admin key.
+    /// Setting no channel on a workflow makes it inherit those of its owner.
+    pub async fn put_channels(
+        ctx: Arc<Context>,
+        key: &ClientKey,
+        scope: ChannelScope,
+        req: NotificationChannelsRequest,
+    ) -> Result<NotificationChannelsResponse> {
+        key.authorize_admin(&ctx.config.admin_api_key)?;
+
+        let url = |url: Option<String>, channel: WebhookChannel, name: &str| match url
+            .map(|url| url.trim().to_string())
+            .filter(|url| !url.is_empty())
+        {
+            Some(url) if !channel.is_valid_url(&url) => {
+                Err(ApiError::BadNotificationRequest(format!("invalid {name} webhook URL")))
+            }
+            url => Ok(url),
+        };
+        let channels = Channels {
+            discord: url(req.discord_webhook_url, WebhookChannel::Discord, "Discord")?,
+            slack: url(req.slack_webhook_url, WebhookChannel::Slack, "Slack")?,
+        };
+
+        let scope = normalize(scope);
+        {
+            let mut store = ctx.notifications.channels.lock().unwrap();
+            if channels.is_empty() {
+                store.remove(&scope);
+            } else {
+                store.insert(scope.clone(), channels);
+            }
+        }
+        let (channels, inherited) = ctx.notifications.channels(&scope);
+        Ok(to_channels_response(&channels, inherited))
+    }
+
+    /// Get the notification preferences of a user, restricted to the admin key.
+    pub async fn get(
+        ctx: Arc<Context>,
+        key: &ClientKey,
+        username: &str,
+    ) -> Result<NotificationPreferencesResponse> {
+        key.authorize_admin(&ctx.config.admin_api_key)?;
+        Ok(to_response(username, &ctx.notifications.get(username)))
+    }
+
+    /// Replace the notification preferences of a user, restricted to the admin key.
+    pub async fn put(
+        ctx: Arc<Context>,
+        key: &ClientKey,
+        username: &str,
+        req: NotificationPreferencesRequest,
+    ) -> Result<NotificationPreferencesResponse> {
+        key.authorize_admin(&ctx.config.admin_api_key)?;
+
+        let email = req.email.map(|email| email.trim().to_string()).filter(|e| !e.is_empty());
+        if let Some(email) = email.as_deref().filter(|email| !is_valid_email(email)) {
+            return Err(ApiError::BadNotificationRequest(format!("invalid email `{email}`")));
+        }
+        let preferences = Preferences { email, opt_out: req.opt_out.into_iter().collect() };
+
+        let mut store = ctx.notifications.preferences.lock().unwrap();
+        store.insert(username.to_lowercase(), preferences.clone());
+        Ok(to_response(username, &preferences))
+    }
+}
+
+fn to_response(username: &str, preferences: &Preferences) -> NotificationPreferencesResponse {
+    NotificationPreferencesResponse {
+        username: username.to_lowercase(),
+        email: preferences.email.clone(),
+        opt_out: preferences.opt_out.iter().copied().collect(),
+    }
+}
+
+fn normalize(scope: ChannelScope) -> ChannelScope {
+    match scope {
+        ChannelScope::Owner(owner) => ChannelScope::Owner(owner.to_lowercase()),
+        scope => scope,
+    }
+}
+
+fn to_channels_response(channels: &Channels, inherited: bool) -> NotificationChannelsResponse {
+    NotificationChannelsResponse {
+        discord_webhook_url: channels
+            .discord
+            .as_deref()
+            .map(|url| WebhookChannel::Discord.mask(url)),
+        slack_webhook_url: channels.slack.as_deref().map(|url| WebhookChannel::Slack.mask(url)),
+        inherited,
+    }
+}
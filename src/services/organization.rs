@@ -0,0 +1,395 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Organizations owning projects and workflows.
+//!
+//! An organization groups GitHub owners, eg. the accounts of a company, so their
+//! workflows share a treasury and approval rights held by its members rather than
+//! hanging off a single GitHub username. Until members can authenticate, only the
+//! admin key manages organizations, and members act through the accounts linked to
+//! them: the inquires of the workflows of an organization are only signed, and their
+//! allocations only created, by the account of an approver or owner. The workflows of
+//! an organization with a treasury pay out from it.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    future::Future,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    context::Context,
+    contracts::{token::TokenContract, types::Address},
+    db::{self, Database, Write, WriteError},
+    errors::{ApiError, Result},
+    middlewares::ratelimit::ClientKey,
+    requests::organization::{MemberRequest, OrganizationRequest, Role},
+    responses::organization::{MemberResponse, OrganizationResponse},
+};
+
+/// The maximum number of GitHub owners of an organization.
+const MAX_OWNERS: usize = 100;
+
+/// An organization and its members.
+#[derive(Debug, Clone)]
+pub struct Organization {
+    pub name: String,
+    pub display_name: String,
+    /// The GitHub owners, lowercase
+    pub owners: Vec<String>,
+    pub treasury: Option<Address>,
+    /// The members, by lowercase GitHub username
+    pub members: BTreeMap<String, Member>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A member of an organization.
+#[derive(Debug, Clone)]
+pub struct Member {
+    pub role: Role,
+    /// The account the member signs with, if linked
+    pub address: Option<Address>,
+    /// The digest of the API key the member reads and manages the workflows with, if any
+    pub client: Option<String>,
+}
+
+/// The organizations by name, written through to the database if there is one.
+#[derive(Clone, Default)]
+pub struct OrganizationStore {
+    organizations: Arc<Mutex<HashMap<String, Organization>>>,
+    db: Option<Database>,
+}
+
+impl OrganizationStore {
+    /// Load the organizations from the database, in memory only without one.
+    pub async fn load(db: Option<&Database>) -> anyhow::Result<Self> {
+        let Some(db) = db else {
+            return Ok(Self::default());
+        };
+
+        let organizations = db::organization::load(db.pool()).await?;
+        let organizations = organizations
+            .into_iter()
+            .map(|organization| (organization.name.clone(), organization))
+            .collect();
+        Ok(Self { organizations: Arc::new(Mutex::new(organizations)), db: Some(db.clone()) })
+    }
+
+    /// Queue a change for the database, returning the future of its write.
+    fn persist(&self, write: Write) -> impl Future<Output = Result<(), WriteError>> {
+        let written = self.db.as_ref().map(|db| db.commit(write));
+        async move {
+            match written {
+                Some(written) => written.await,
+                None => Ok(()),
+            }
+        }
+    }
+
+    #[cfg(test)]
+    pub fn insert(&self, organization: Organization) {
+        self.organizations.lock().unwrap().insert(organization.name.clone(), organization);
+    }
+
+    pub fn get(&self, name: &str) -> Option<Organization> {
+        self.organizations.lock().unwrap().get(&name.to_lowercase()).cloned()
+    }
+
+    /// Get the organization owning the projects of a GitHub owner, if any.
+    pub fn owning(&self, owner: &str) -> Option<Organization> {
+        let owner = owner.to_lowercase();
+        let organizations = self.organizations.lock().unwrap();
+        organizations.values().find(|organization| organization.owners.contains(&owner)).cloned()
+    }
+
+    /// Whether the user is a member of the organization with at least the role.
+    pub fn has_role(&self, name: &str, username: &str, role: Role) -> bool {
+        self.get(name)
+            .and_then(|organization| {
+                organization.members.get(&username.to_lowercase()).map(|member| member.role)
+            })
+            .is_some_and(|member| member >= role)
+    }
+
+    /// The role in the organization of the member using the API key digest, if any.
+    pub fn role_of_client(&self, name: &str, client: &str) -> Option<Role> {
+        let organization = self.get(name)?;
+        let roles = organization.members.values().filter_map(|member| {
+            member.client.as_deref().filter(|linked| *linked == client).map(|_| member.role)
+        });
+        roles.max()
+    }
+
+    /// The role in the organization of the member linked to the account, if any.
+    pub fn role_of(&self, name: &str, address: &str) -> Option<Role> {
+        let organization = self.get(name)?;
+        let roles = organization.members.values().filter_map(|member| {
+            member
+                .address
+                .as_deref()
+                .filter(|linked| same_address(linked, address))
+                .map(|_| member.role)
+        });
+        roles.max()
+    }
+}
+
+pub struct OrganizationService;
+
+impl OrganizationService {
+    /// Fail unless the account is linked to a member with at least the role in the
+    /// organization owning a workflow, if any.
+    pub fn authorize(
+        ctx: &Context,
+        organization: Option<&str>,
+        address: &str,
+        role: Role,
+    ) -> Result<()> {
+        let Some(organization) = organization else {
+            return Ok(());
+        };
+        if ctx.organizations.role_of(organization, address).is_none_or(|member| member < role) {
+            return Err(ApiError::Forbidden(format!(
+                "`{address}` is not the account of an {role} of the `{organization}` organization"
+            )));
+        }
+        Ok(())
+    }
+
+    pub async fn get(ctx: Arc<Context>, name: &str) -> Result<OrganizationResponse> {
+        let organization =
+            ctx.organizations.get(name).ok_or(ApiError::NotFoundOrganization(name.to_string()))?;
+        Ok(to_response(&organization))
+    }
+
+    /// Create or update an organization, keeping its members.
+    pub async fn put(
+        ctx: Arc<Context>,
+        key: &ClientKey,
+        name: &str,
+        req: OrganizationRequest,
+    ) -> Result<OrganizationResponse> {
+        key.authorize_admin(&ctx.config.admin_api_key)?;
+        validate_name(name)?;
+
+        let display_name = req.display_name.trim();
+        if display_name.is_empty() {
+            return Err(ApiError::BadOrganizationRequest("empty display name".to_string()));
+        }
+        if req.owners.len() > MAX_OWNERS {
+            return Err(ApiError::BadOrganizationRequest(format!("more than {MAX_OWNERS} owners")));
+        }
+        let mut owners = req
+            .owners
+            .iter()
+            .map(|owner| match owner.trim() {
+                "" => Err(ApiError::BadOrganizationRequest("empty owner".to_string())),
+                owner => Ok(owner.to_lowercase()),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        owners.sort();
+        owners.dedup();
+
+        let treasury = req
+            .treasury
+            .map(|treasury| {
+                let treasury = treasury.trim();
+                treasury
+                    .parse()
+                    .ok()
+                    .filter(|treasury| ctx.contract.is_valid_address(treasury))
+                    .ok_or_else(|| {
+                        ApiError::BadOrganizationRequest(format!(
+                            "invalid treasury address `{treasury}`"
+                        ))
+                    })
+            })
+            .transpose()?;
+
+        let name = name.to_lowercase();
+        let (response, written) = {
+            let mut organizations = ctx.organizations.organizations.lock().unwrap();
+            // A project belongs to a single organization.
+            for (other, organization) in organizations.iter() {
+                if let Some(owner) = owners.iter().find(|owner| organization.owners.contains(owner))
+                {
+                    if *other != name {
+                        return Err(ApiError::BadOrganizationRequest(format!(
+                            "the owner `{owner}` belongs to the `{other}` organization"
+                        )));
+                    }
+                }
+            }
+
+            let organization = organizations.entry(name.clone()).or_insert_with(|| Organization {
+                name,
+                display_name: String::new(),
+                owners: vec![],
+                treasury: None,
+                members: BTreeMap::new(),
+                created_at: Utc::now(),
+            });
+            organization.display_name = display_name.to_string();
+            organization.owners = owners;
+            organization.treasury = treasury;
+            let response = to_response(organization);
+            (response, ctx.organizations.persist(Write::Organization(organization.clone())))
+        };
+        written.await?;
+        Ok(response)
+    }
+
+    pub async fn delete(ctx: Arc<Context>, key: &ClientKey, name: &str) -> Result<()> {
+        key.authorize_admin(&ctx.config.admin_api_key)?;
+
+        let name = name.to_lowercase();
+        let written = {
+            let mut organizations = ctx.organizations.organizations.lock().unwrap();
+            if organizations.remove(&name).is_none() {
+                return Err(ApiError::NotFoundOrganization(name));
+            }
+            ctx.organizations.persist(Write::DeleteOrganization(name))
+        };
+        written.await?;
+        Ok(())
+    }
+
+    /// Add a member to an organization, or change their role, account and API key.
+    pub async fn put_member(
+        ctx: Arc<Context>,
+        key: &ClientKey,
+        name: &str,
+        username: &str,
+        req: MemberRequest,
+    ) -> Result<OrganizationResponse> {
+        key.authorize_admin(&ctx.config.admin_api_key)?;
+        if username.trim().is_empty() {
+            return Err(ApiError::BadOrganizationRequest("empty username".to_string()));
+        }
+        let address = req
+            .address
+            .map(|address| {
+                let address = address.trim();
+                address
+                    .parse()
+                    .ok()
+                    .filter(|address| ctx.contract.is_valid_address(address))
+                    .ok_or_else(|| {
+                        ApiError::BadOrganizationRequest(format!("invalid address `{address}`"))
+                    })
+            })
+            .transpose()?;
+
+        let client = req.api_key.as_deref().map(|key| ClientKey::api_key(key.trim()).digest());
+
+        update(&ctx, name, |organization| {
+            let member = Member { role: req.role, address, client };
+            organization.members.insert(username.to_lowercase(), member);
+            Ok(())
+        })
+        .await
+    }
+
+    /// Remove a member from an organization, which keeps at least one owner.
+    pub async fn delete_member(
+        ctx: Arc<Context>,
+        key: &ClientKey,
+        name: &str,
+        username: &str,
+    ) -> Result<OrganizationResponse> {
+        key.authorize_admin(&ctx.config.admin_api_key)?;
+
+        update(&ctx, name, |organization| {
+            let username = username.to_lowercase();
+            let Some(member) = organization.members.remove(&username) else {
+                return Err(ApiError::BadOrganizationRequest(format!(
+                    "`{username}` is not a member"
+                )));
+            };
+            let owners = organization.members.values().filter(|member| member.role == Role::Owner);
+            if member.role == Role::Owner && owners.count() == 0 {
+                return Err(ApiError::BadOrganizationRequest(format!(
+                    "`{username}` is the last owner"
+                )));
+            }
+            Ok(())
+        })
+        .await
+    }
+}
+
+/// Apply `f` to a copy of the organization, saved only when it succeeds.
+async fn update(
+    ctx: &Context,
+    name: &str,
+    f: impl FnOnce(&mut Organization) -> Result<()>,
+) -> Result<OrganizationResponse> {
+    let (response, written) = {
+        let mut organizations = ctx.organizations.organizations.lock().unwrap();
+        let organization = organizations
+            .get_mut(&name.to_lowercase())
+            .ok_or(ApiError::NotFoundOrganization(name.to_string()))?;
+
+        let mut updated = organization.clone();
+        f(&mut updated)?;
+        *organization = updated;
+        let written = ctx.organizations.persist(Write::Organization(organization.clone()));
+        (to_response(organization), written)
+    };
+    written.await?;
+    Ok(response)
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    let valid = (1..=39).contains(&name.len())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        && !name.starts_with('-');
+    if !valid {
+        return Err(ApiError::BadOrganizationRequest(format!(
+            "invalid name `{name}`, use 1 to 39 letters, digits or `-`"
+        )));
+    }
+    Ok(())
+}
+
+fn to_response(organization: &Organization) -> OrganizationResponse {
+    OrganizationResponse {
+        name: organization.name.clone(),
+        display_name: organization.display_name.clone(),
+        owners: organization.owners.clone(),
+        treasury: organization.treasury.clone().map(String::from),
+        members: organization
+            .members
+            .iter()
+            .map(|(username, member)| MemberResponse {
+                username: username.clone(),
+                role: member.role,
+                address: member.address.clone().map(String::from),
+            })
+            .collect(),
+        created_at: organization.created_at,
+    }
+}
+
+/// Whether two addresses are the same, whatever their case and leading zeros.
+pub fn same_address(a: &str, b: &str) -> bool {
+    let digits = |address: &str| {
+        let address = address.trim().to_lowercase();
+        let digits = address.strip_prefix("0x").unwrap_or(&address).trim_start_matches('0');
+        digits.to_string()
+    };
+    digits(a) == digits(b)
+}
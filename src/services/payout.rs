@@ -0,0 +1,306 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use num_bigint::BigUint;
+use thiserror::Error;
+use tracing::{info, instrument, warn};
+
+use crate::{
+    context::Context,
+    contracts::{
+        allocation::AllocationContract,
+        token::{Token, TokenContract},
+        transaction::TransactionContract,
+        types::{Address, Hash, Id, Number, RawCall},
+        Contract,
+    },
+    requests::{address::Chain, workflow::Denomination},
+    services::{address::AddressFallback, allocation::AllocationRecord},
+};
+
+/// A transfer paying out an allocation.
+#[derive(Debug, Clone)]
+pub struct Payout {
+    pub allocation_id: Id,
+    pub recipient: Address,
+    /// The amount in whole tokens, eg. `1.5`
+    pub amount: Number,
+    pub token: Token,
+    /// The multisig wallet bound to the workflow the tokens are drawn from, the paying
+    /// account when `None`
+    pub owner: Option<Address>,
+}
+
+/// A payout ready to be submitted, with the calls transferring it.
+#[derive(Debug, Clone)]
+pub struct PreparedPayout {
+    pub allocation_id: Id,
+    pub token: Token,
+    pub token_address: Address,
+    /// The amount in the smallest unit of the token
+    pub amount: BigUint,
+    pub owner: Option<Address>,
+    pub calls: Vec<RawCall>,
+}
+
+impl PreparedPayout {
+    /// The length the calls add to the calldata of a multicall, in felts.
+    pub fn calldata_len(&self) -> usize {
+        // Every call is encoded as its address, selector, calldata length and calldata.
+        self.calls.iter().map(|call| 3 + call.calldata.len()).sum()
+    }
+}
+
+/// Payout failures which retrying cannot fix.
+#[derive(Error, Debug)]
+pub enum PayoutError {
+    #[error("Unreachable recipient `{0}`")]
+    UnreachableRecipient(Address),
+
+    #[error("Contributor `{0}` has no {1} address")]
+    NoAddressOnChain(String, Chain),
+}
+
+/// Moves funds for allocations, from the account of the contract or the multisig wallet
+/// bound to their workflow to the recipients.
+pub struct PayoutService;
+
+impl PayoutService {
+    /// Build the payout of an allocation, to the address of the contributor on the chain,
+    /// converting USD amounts to tokens at the current price.
+    ///
+    /// Also returns the USD price of one token, when converted.
+    pub async fn for_allocation(
+        ctx: &Context,
+        record: &AllocationRecord,
+    ) -> Result<(Payout, Option<String>)> {
+        let (amount, usd_rate) = match record.denomination {
+            Denomination::Token => (record.amount.clone(), None),
+            Denomination::Usd => {
+                let token_address = Self::token_address(ctx.contract.as_ref(), &record.token);
+                let decimals = ctx.contract.decimals(token_address).await?;
+                let rate = ctx.prices.usd_price(&record.token).await?;
+                let amount = usd_to_tokens(&record.amount, &rate, decimals)?;
+                info!(usd = %record.amount, %rate, %amount, "Converted USD amount");
+                (amount, Some(rate))
+            }
+        };
+
+        let payout = Payout {
+            allocation_id: record.id.clone(),
+            recipient: recipient(ctx, record)?,
+            amount,
+            token: record.token.clone(),
+            owner: ctx.workflows.get(record.workflow_id).and_then(|w| w.wallet_address),
+        };
+        Ok((payout, usd_rate))
+    }
+
+    /// Check and encode a payout, without submitting it.
+    ///
+    /// Payouts from a multisig wallet are transferred on its behalf, up to the allowance it
+    /// granted the paying account. Otherwise native tokens are transferred directly to the
+    /// recipient, other tokens are approved to and transferred by the allocation contract.
+    #[instrument(
+        skip_all,
+        fields(
+            allocation_id = %payout.allocation_id,
+            token = %payout.token,
+            amount = %payout.amount
+        )
+    )]
+    pub async fn prepare<C>(contract: &C, payout: &Payout) -> Result<PreparedPayout>
+    where
+        C: AllocationContract + TokenContract,
+    {
+        if !contract.is_valid_address(&payout.recipient) {
+            return Err(PayoutError::UnreachableRecipient(payout.recipient.clone()).into());
+        }
+
+        let token_address = Self::token_address(contract, &payout.token);
+        let decimals = contract.decimals(token_address.clone()).await?;
+        let amount = to_base_units(&payout.amount, decimals)?;
+
+        let calls = match (&payout.owner, &payout.token) {
+            (Some(owner), _) => vec![contract.transfer_from_call(
+                token_address.clone(),
+                owner.clone(),
+                payout.recipient.clone(),
+                amount.to_string(),
+            )?],
+            (None, Token::Native(_)) => vec![contract.transfer_call(
+                token_address.clone(),
+                payout.recipient.clone(),
+                amount.to_string(),
+            )?],
+            (None, Token::Erc20(_)) => contract.execute_allocation_calls(
+                payout.allocation_id.clone(),
+                token_address.clone(),
+                amount.to_string(),
+            )?,
+        };
+
+        Ok(PreparedPayout {
+            allocation_id: payout.allocation_id.clone(),
+            token: payout.token.clone(),
+            token_address,
+            amount,
+            owner: payout.owner.clone(),
+            calls,
+        })
+    }
+
+    /// Submit payouts in a single transaction, checking beforehand that the paying account
+    /// and multisig wallets hold enough tokens, and that the wallets allow the paying
+    /// account to transfer them, and record the transaction hash on the allocations.
+    #[instrument(skip_all, fields(payouts = payouts.len()))]
+    pub async fn submit<C>(contract: &C, payouts: &[PreparedPayout]) -> Result<Hash>
+    where
+        C: AllocationContract + TokenContract + TransactionContract,
+    {
+        let mut required = BTreeMap::new();
+        for payout in payouts {
+            let (_, amount) = required
+                .entry((&payout.owner, &payout.token_address))
+                .or_insert((&payout.token, BigUint::ZERO));
+            *amount += &payout.amount;
+        }
+        for ((owner, token_address), (token, amount)) in required {
+            let payer = owner.clone().unwrap_or_else(|| contract.account_address());
+            let balance = contract.balance_of(token_address.clone(), payer.clone()).await?;
+            if parse_amount(&balance)? < amount {
+                return Err(anyhow!(
+                    "Insufficient {} balance of {}: {} available, {} required",
+                    token,
+                    payer,
+                    balance,
+                    amount
+                ));
+            }
+            if owner.is_some() {
+                let allowance = contract
+                    .allowance(token_address.clone(), payer.clone(), contract.account_address())
+                    .await?;
+                if parse_amount(&allowance)? < amount {
+                    return Err(anyhow!(
+                        "Insufficient {} allowance of {}: {} allowed, {} required",
+                        token,
+                        payer,
+                        allowance,
+                        amount
+                    ));
+                }
+            }
+        }
+
+        let calls = payouts.iter().flat_map(|payout| payout.calls.clone()).collect();
+        let tx_hash = contract.execute_calls(calls).await?;
+        info!(%tx_hash, "Payouts submitted");
+
+        // The payouts are submitted whatever happens next, they must not be retried.
+        let updates = payouts
+            .iter()
+            .map(|payout| {
+                contract
+                    .update_allocation_tx_hash_call(payout.allocation_id.clone(), tx_hash.clone())
+            })
+            .collect::<Result<Vec<_>>>();
+        match updates {
+            Ok(updates) => {
+                if let Err(e) = contract.execute_calls(updates).await {
+                    warn!(%tx_hash, "Failed to record the payout transaction hash: {e}");
+                }
+            }
+            Err(e) => warn!(%tx_hash, "Failed to record the payout transaction hash: {e}"),
+        }
+
+        Ok(tx_hash)
+    }
+
+    /// The address of the token contract, resolving native tokens.
+    pub fn token_address<C: TokenContract>(contract: &C, token: &Token) -> Address {
+        match token {
+            Token::Native(native) => contract.native_token_address(*native),
+            Token::Erc20(address) => address.clone(),
+        }
+    }
+}
+
+/// The address to pay the allocation to, from the address book of the contributor
+/// on the chain of the contract, falling back per the configured policy.
+fn recipient(ctx: &Context, record: &AllocationRecord) -> Result<Address> {
+    let Some(contributor) = &record.contributor else {
+        return Ok(record.recipient.clone());
+    };
+
+    let chain: Chain = ctx.contract.chain().parse().map_err(|e: String| anyhow!(e))?;
+    if let Some(address) = ctx.addresses.resolve(contributor, chain) {
+        return Ok(address);
+    }
+    match ctx.config.address_config.address_fallback {
+        AddressFallback::Recipient => Ok(record.recipient.clone()),
+        AddressFallback::Fail => {
+            Err(PayoutError::NoAddressOnChain(contributor.clone(), chain).into())
+        }
+    }
+}
+
+fn parse_amount(amount: &str) -> Result<BigUint> {
+    amount.parse().map_err(|_| anyhow!("Invalid amount `{amount}`"))
+}
+
+/// Convert an amount in whole tokens, eg. `1.5`, to the smallest unit of a token
+/// with `decimals` decimals.
+pub fn to_base_units(amount: &str, decimals: u8) -> Result<BigUint> {
+    let trimmed = amount.trim();
+    let (whole, fraction) = trimmed.split_once('.').unwrap_or((trimmed, ""));
+    if fraction.len() > decimals as usize {
+        return Err(anyhow!("Amount `{amount}` has more than {decimals} decimals"));
+    }
+    if whole.is_empty() && fraction.is_empty() {
+        return Err(anyhow!("Invalid amount `{amount}`"));
+    }
+
+    let digits = format!("{whole}{fraction:0<width$}", width = decimals as usize);
+    parse_amount(&digits).map_err(|_| anyhow!("Invalid amount `{amount}`"))
+}
+
+/// Convert an amount in the smallest unit of a token with `decimals` decimals
+/// to whole tokens, eg. `1.5`.
+pub fn from_base_units(amount: &BigUint, decimals: u8) -> Number {
+    let digits = format!("{amount:0>width$}", width = decimals as usize + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals as usize);
+    match fraction.trim_end_matches('0') {
+        "" => whole.to_string(),
+        fraction => format!("{whole}.{fraction}"),
+    }
+}
+
+/// Convert an amount in USD to whole tokens at the USD price of one token,
+/// rounding down to the token decimals.
+pub fn usd_to_tokens(usd: &str, price: &str, decimals: u8) -> Result<Number> {
+    const SCALE: u8 = 18;
+
+    let usd = to_base_units(usd, SCALE)?;
+    let price = to_base_units(price, SCALE)?;
+    if price == BigUint::ZERO {
+        return Err(anyhow!("Invalid token price `0`"));
+    }
+
+    let amount = usd * BigUint::from(10u8).pow(decimals as u32) / price;
+    Ok(from_base_units(&amount, decimals))
+}
@@ -0,0 +1,226 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Per-owner policies including or excluding dependencies from rankings.
+//!
+//! Owners exclude packages which must not receive funding, like their internal crates or
+//! standard library shims. Excluded dependencies stay in the ranking output, marked as
+//! `excluded_by_policy` with a zero score, and their share goes to the others. Owners may
+//! also exclude the dependencies screened as suspicious, so they receive no allocation.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    context::Context,
+    errors::{ApiError, Result},
+    middlewares::ratelimit::ClientKey,
+    requests::policy::DependencyPolicyRequest,
+    responses::{dependency::DependencyResponse, policy::DependencyPolicyResponse},
+};
+
+/// The maximum number of patterns of a policy.
+const MAX_PATTERNS: usize = 1000;
+
+/// The dependency policies, in memory, by owner.
+#[derive(Clone, Default)]
+pub struct PolicyStore {
+    policies: Arc<Mutex<HashMap<String, DependencyPolicyRequest>>>,
+}
+
+impl PolicyStore {
+    pub fn get(&self, owner: &str) -> Option<DependencyPolicyRequest> {
+        self.policies.lock().unwrap().get(&owner.to_lowercase()).cloned()
+    }
+}
+
+impl DependencyPolicyRequest {
+    /// Whether the policy excludes the package.
+    pub fn excludes(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.exclude.iter().any(|pattern| matches(pattern, &name)) && !self.includes(&name)
+    }
+
+    /// Whether the policy excludes the dependency, by name or because it was flagged.
+    pub fn excludes_dependency(&self, dependency: &DependencyResponse) -> bool {
+        self.excludes(&dependency.name)
+            || (self.exclude_flagged
+                && !dependency.flags.is_empty()
+                && !self.includes(&dependency.name.to_lowercase()))
+    }
+
+    fn includes(&self, name: &str) -> bool {
+        self.include.iter().any(|pattern| matches(pattern, name))
+    }
+
+    /// Mark the dependencies excluded by the policy, zeroing their score and spreading
+    /// it over the others, so the scores keep the same total.
+    pub fn apply(&self, dependencies: &mut [DependencyResponse]) {
+        let total: f64 = dependencies.iter().map(|dependency| dependency.score).sum();
+        for dependency in dependencies.iter_mut() {
+            dependency.excluded_by_policy = self.excludes_dependency(dependency);
+            if dependency.excluded_by_policy {
+                dependency.score = 0.0;
+            }
+        }
+
+        let kept: f64 = dependencies.iter().map(|dependency| dependency.score).sum();
+        if kept > 0.0 {
+            for dependency in dependencies.iter_mut() {
+                dependency.score *= total / kept;
+            }
+        }
+    }
+}
+
+pub struct PolicyService;
+
+impl PolicyService {
+    pub async fn get(ctx: Arc<Context>, owner: &str) -> Result<DependencyPolicyResponse> {
+        Ok(to_response(owner, &ctx.policies.get(owner).unwrap_or_default()))
+    }
+
+    /// Replace the policy of an owner, applied from the next analysis on.
+    pub async fn put(
+        ctx: Arc<Context>,
+        key: &ClientKey,
+        owner: &str,
+        req: DependencyPolicyRequest,
+    ) -> Result<DependencyPolicyResponse> {
+        key.authorize_admin(&ctx.config.admin_api_key)?;
+
+        let normalize = |patterns: Vec<String>| -> Result<Vec<String>> {
+            if patterns.len() > MAX_PATTERNS {
+                return Err(ApiError::BadPolicyRequest(format!(
+                    "more than {MAX_PATTERNS} patterns"
+                )));
+            }
+            patterns
+                .into_iter()
+                .map(|pattern| match pattern.trim() {
+                    "" => Err(ApiError::BadPolicyRequest("empty pattern".to_string())),
+                    pattern => Ok(pattern.to_lowercase()),
+                })
+                .collect()
+        };
+        let policy = DependencyPolicyRequest {
+            exclude: normalize(req.exclude)?,
+            include: normalize(req.include)?,
+            exclude_flagged: req.exclude_flagged,
+        };
+
+        ctx.policies.policies.lock().unwrap().insert(owner.to_lowercase(), policy.clone());
+        Ok(to_response(owner, &policy))
+    }
+
+    pub async fn delete(ctx: Arc<Context>, key: &ClientKey, owner: &str) -> Result<()> {
+        key.authorize_admin(&ctx.config.admin_api_key)?;
+
+        ctx.policies.policies.lock().unwrap().remove(&owner.to_lowercase());
+        Ok(())
+    }
+}
+
+/// Match a name against a pattern where `*` matches any characters.
+fn matches(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<_> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard, the whole name must match.
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+fn to_response(owner: &str, policy: &DependencyPolicyRequest) -> DependencyPolicyResponse {
+    DependencyPolicyResponse {
+        owner: owner.to_lowercase(),
+        exclude: policy.exclude.clone(),
+        include: policy.include.clone(),
+        exclude_flagged: policy.exclude_flagged,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// A dependency of the given score, flagged as a typosquat or not.
+    fn dependency(name: &str, score: f64, flagged: bool) -> DependencyResponse {
+        let flags = if flagged {
+            json!([{ "kind": "typosquat", "detail": "close to serde_json" }])
+        } else {
+            json!([])
+        };
+        serde_json::from_value(json!({
+            "name": name,
+            "version": "1.0.0",
+            "score": score,
+            "flags": flags,
+        }))
+        .unwrap()
+    }
+
+    fn applied(policy: &DependencyPolicyRequest) -> Vec<DependencyResponse> {
+        let mut dependencies =
+            vec![dependency("serde_json", 0.5, false), dependency("serde_jsn", 0.5, true)];
+        policy.apply(&mut dependencies);
+        dependencies
+    }
+
+    #[test]
+    fn flagged_dependencies_are_excluded_when_asked() {
+        let policy = DependencyPolicyRequest { exclude_flagged: true, ..Default::default() };
+        let dependencies = applied(&policy);
+
+        assert!(!dependencies[0].excluded_by_policy);
+        assert_eq!(dependencies[0].score, 1.0);
+        assert!(dependencies[1].excluded_by_policy);
+        assert_eq!(dependencies[1].score, 0.0);
+    }
+
+    #[test]
+    fn flagged_dependencies_are_kept_by_default() {
+        let dependencies = applied(&DependencyPolicyRequest::default());
+
+        assert!(dependencies.iter().all(|dependency| !dependency.excluded_by_policy));
+        assert!(dependencies.iter().all(|dependency| dependency.score == 0.5));
+    }
+
+    #[test]
+    fn included_flagged_dependencies_are_kept() {
+        let policy = DependencyPolicyRequest {
+            include: vec!["serde_*".to_string()],
+            exclude_flagged: true,
+            ..Default::default()
+        };
+        let dependencies = applied(&policy);
+
+        assert!(dependencies.iter().all(|dependency| !dependency.excluded_by_policy));
+    }
+}
@@ -0,0 +1,353 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use num_bigint::BigUint;
+use uuid::Uuid;
+
+use crate::{
+    contracts::types::Number,
+    db::{self, Database, Write},
+    errors::{ApiError, Result},
+    requests::workflow::Denomination,
+    responses::pool::{PoolBalanceResponse, PoolResponse},
+    services::{
+        allocation::AllocationRecord,
+        organization::same_address,
+        payout::{from_base_units, to_base_units},
+    },
+};
+
+/// The precision used to sum amounts, in decimals.
+const SCALE: u8 = 18;
+
+/// A workflow, token and denomination.
+type PoolKey = (Uuid, String, Denomination);
+
+type Balances = BTreeMap<PoolKey, Balance>;
+
+#[derive(Debug, Default, Clone)]
+struct Balance {
+    /// Released by failed allocations, to re-allocate in a follow-up round
+    available: BigUint,
+    /// Released by failed allocations, returned to the treasury
+    clawed_back: BigUint,
+}
+
+/// A balance of the pool of a workflow, as stored.
+#[derive(Debug, Clone)]
+pub struct PoolBalance {
+    pub workflow_id: Uuid,
+    /// The token, a symbol like `STRK` or a token contract address
+    pub token: String,
+    pub denomination: Denomination,
+    /// The amounts in whole tokens, eg. `1.5`
+    pub available: Number,
+    pub clawed_back: Number,
+}
+
+/// The budget released by failed allocations, per workflow, token and denomination,
+/// written through to the database if there is one so it survives restarts.
+#[derive(Clone, Default)]
+pub struct BudgetPool {
+    balances: Arc<Mutex<Balances>>,
+    db: Option<Database>,
+}
+
+impl BudgetPool {
+    /// Load the balances from the database, in memory only without one.
+    pub async fn load(db: Option<&Database>) -> anyhow::Result<Self> {
+        let Some(db) = db else {
+            return Ok(Self::default());
+        };
+
+        let mut balances = Balances::new();
+        for balance in db::pool::load(db.pool()).await? {
+            let amounts = Balance {
+                available: to_base_units(&balance.available, SCALE)?,
+                clawed_back: to_base_units(&balance.clawed_back, SCALE)?,
+            };
+            balances.insert((balance.workflow_id, balance.token, balance.denomination), amounts);
+        }
+        Ok(Self { balances: Arc::new(Mutex::new(balances)), db: Some(db.clone()) })
+    }
+
+    /// Return the budgeted amount of a failed allocation, either to the pool of its
+    /// workflow or, when it must not be re-allocated, back to the treasury.
+    pub async fn release(&self, record: &AllocationRecord) -> Result<()> {
+        let amount = parse(&record.amount)?;
+        self.apply(|balances| {
+            let key =
+                find(balances, record.workflow_id, &record.token.to_string(), record.denomination);
+            let balance = balances.entry(key.clone()).or_default();
+            if record.reallocate {
+                balance.available += amount;
+            } else {
+                balance.clawed_back += amount;
+            }
+            Ok(vec![key])
+        })
+        .await
+    }
+
+    /// Take the amounts of the allocations of a follow-up round out of the pool of their
+    /// workflow, all or none, failing unless enough of every token is available.
+    pub async fn take(
+        &self,
+        workflow_id: Uuid,
+        denomination: Denomination,
+        amounts: &[(String, Number)],
+    ) -> Result<()> {
+        let totals = totals(amounts)?;
+        self.apply(|balances| {
+            let keys: Vec<_> = totals
+                .iter()
+                .map(|(token, _)| find(balances, workflow_id, token, denomination))
+                .collect();
+            for (key, (token, amount)) in keys.iter().zip(&totals) {
+                let available =
+                    balances.get(key).map(|balance| balance.available.clone()).unwrap_or_default();
+                if available < *amount {
+                    return Err(ApiError::BadAllocationRequest(format!(
+                        "only {} {token} left in the pool of workflow {workflow_id}",
+                        from_base_units(&available, SCALE)
+                    )));
+                }
+            }
+            for (key, (_, amount)) in keys.iter().zip(&totals) {
+                if let Some(balance) = balances.get_mut(key) {
+                    balance.available -= amount;
+                }
+            }
+            Ok(keys)
+        })
+        .await
+    }
+
+    /// Put the amounts taken for allocations which could not be created back into the
+    /// pool of their workflow.
+    pub async fn put_back(
+        &self,
+        workflow_id: Uuid,
+        denomination: Denomination,
+        amounts: &[(String, Number)],
+    ) -> Result<()> {
+        let totals = totals(amounts)?;
+        self.apply(|balances| {
+            let mut keys = Vec::new();
+            for (token, amount) in totals {
+                let key = find(balances, workflow_id, &token, denomination);
+                balances.entry(key.clone()).or_default().available += amount;
+                keys.push(key);
+            }
+            Ok(keys)
+        })
+        .await
+    }
+
+    /// Get the pool of a workflow.
+    pub fn get(&self, workflow_id: Uuid) -> PoolResponse {
+        let balances = self
+            .balances
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|((id, _, _), _)| *id == workflow_id)
+            .map(|((_, token, denomination), balance)| PoolBalanceResponse {
+                token: token.clone(),
+                denomination: *denomination,
+                available: from_base_units(&balance.available, SCALE),
+                clawed_back: from_base_units(&balance.clawed_back, SCALE),
+            })
+            .collect();
+
+        PoolResponse { workflow_id, balances }
+    }
+
+    /// Apply `f` to the balances, which changes none of them when it fails, then write
+    /// the balances it returns the keys of, waiting for them to be written.
+    async fn apply(&self, f: impl FnOnce(&mut Balances) -> Result<Vec<PoolKey>>) -> Result<()> {
+        let written = {
+            let mut balances = self.balances.lock().unwrap();
+            let keys = f(&mut balances)?;
+            let rows = keys
+                .into_iter()
+                .filter_map(|key| {
+                    let balance = balances.get(&key)?;
+                    let (workflow_id, token, denomination) = key;
+                    Some(PoolBalance {
+                        workflow_id,
+                        token,
+                        denomination,
+                        available: from_base_units(&balance.available, SCALE),
+                        clawed_back: from_base_units(&balance.clawed_back, SCALE),
+                    })
+                })
+                .collect();
+            self.db.as_ref().map(|db| db.commit(Write::PoolBalances(rows)))
+        };
+        if let Some(written) = written {
+            written.await?;
+        }
+        Ok(())
+    }
+}
+
+/// The key of the balance of a token in the pool of a workflow, the token contract
+/// addresses matching whatever their case and leading zeros.
+fn find(
+    balances: &Balances,
+    workflow_id: Uuid,
+    token: &str,
+    denomination: Denomination,
+) -> PoolKey {
+    balances
+        .keys()
+        .find(|(id, pooled, pooled_denomination)| {
+            *id == workflow_id
+                && *pooled_denomination == denomination
+                && same_address(pooled, token)
+        })
+        .cloned()
+        .unwrap_or_else(|| (workflow_id, token.to_string(), denomination))
+}
+
+/// Sum the amounts of each token.
+fn totals(amounts: &[(String, Number)]) -> Result<Vec<(String, BigUint)>> {
+    let mut totals: Vec<(String, BigUint)> = Vec::new();
+    for (token, amount) in amounts {
+        let amount = parse(amount)?;
+        match totals.iter_mut().find(|(summed, _)| same_address(summed, token)) {
+            Some((_, total)) => *total += amount,
+            None => totals.push((token.clone(), amount)),
+        }
+    }
+    Ok(totals)
+}
+
+fn parse(amount: &str) -> Result<BigUint> {
+    to_base_units(amount, SCALE)
+        .map_err(|e| ApiError::BadAllocationRequest(format!("invalid amount `{amount}`: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        contracts::token::{NativeToken, Token},
+        services::allocation::ExecutionStatus,
+    };
+
+    fn failed(workflow_id: Uuid, amount: &str, reallocate: bool) -> AllocationRecord {
+        AllocationRecord {
+            id: "0x1".parse().unwrap(),
+            workflow_id,
+            contributor: None,
+            package: None,
+            snapshot_id: None,
+            recipient: "0x2".parse().unwrap(),
+            amount: amount.to_string(),
+            denomination: Denomination::Token,
+            token: Token::Native(NativeToken::Strk),
+            usd_rate: None,
+            token_amount: None,
+            status: ExecutionStatus::Failed,
+            tx_hash: None,
+            submitted_at: None,
+            executed_at: None,
+            attempts: 0,
+            retry_at: None,
+            error: None,
+            reallocate,
+        }
+    }
+
+    fn available(pool: &BudgetPool, workflow_id: Uuid) -> Vec<(String, Number)> {
+        pool.get(workflow_id)
+            .balances
+            .into_iter()
+            .map(|balance| (balance.token, balance.available))
+            .collect()
+    }
+
+    fn strk(amount: &str) -> Vec<(String, Number)> {
+        vec![("STRK".to_string(), amount.to_string())]
+    }
+
+    #[tokio::test]
+    async fn failed_allocations_refund_the_pool_or_the_treasury() {
+        let pool = BudgetPool::default();
+        let workflow_id = Uuid::new_v4();
+        pool.release(&failed(workflow_id, "1.5", true)).await.unwrap();
+        pool.release(&failed(workflow_id, "0.25", true)).await.unwrap();
+        pool.release(&failed(workflow_id, "2", false)).await.unwrap();
+
+        let balances = pool.get(workflow_id).balances;
+        assert_eq!(balances.len(), 1);
+        assert_eq!(balances[0].available, "1.75");
+        assert_eq!(balances[0].clawed_back, "2");
+        assert!(pool.get(Uuid::new_v4()).balances.is_empty());
+    }
+
+    #[tokio::test]
+    async fn unparsable_amounts_are_not_released() {
+        let pool = BudgetPool::default();
+        let workflow_id = Uuid::new_v4();
+        assert!(pool.release(&failed(workflow_id, "lots", true)).await.is_err());
+        assert!(pool.get(workflow_id).balances.is_empty());
+    }
+
+    #[tokio::test]
+    async fn rounds_take_at_most_the_released_budget() {
+        let pool = BudgetPool::default();
+        let workflow_id = Uuid::new_v4();
+        pool.release(&failed(workflow_id, "3", true)).await.unwrap();
+
+        pool.take(workflow_id, Denomination::Token, &strk("1")).await.unwrap();
+        let amounts = [strk("1"), strk("1.5")].concat();
+        let err = pool.take(workflow_id, Denomination::Token, &amounts).await.unwrap_err();
+        assert!(matches!(err, ApiError::BadAllocationRequest(_)));
+        assert_eq!(available(&pool, workflow_id), strk("2"));
+
+        pool.take(workflow_id, Denomination::Token, &strk("2")).await.unwrap();
+        assert_eq!(available(&pool, workflow_id), strk("0"));
+    }
+
+    #[tokio::test]
+    async fn rounds_take_all_tokens_or_none() {
+        let pool = BudgetPool::default();
+        let workflow_id = Uuid::new_v4();
+        pool.release(&failed(workflow_id, "3", true)).await.unwrap();
+
+        let amounts = [strk("1"), vec![("0xabc".to_string(), "1".to_string())]].concat();
+        assert!(pool.take(workflow_id, Denomination::Token, &amounts).await.is_err());
+        assert_eq!(available(&pool, workflow_id), strk("3"));
+        assert!(pool.take(workflow_id, Denomination::Usd, &strk("1")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rounds_failing_to_be_created_put_their_budget_back() {
+        let pool = BudgetPool::default();
+        let workflow_id = Uuid::new_v4();
+        pool.release(&failed(workflow_id, "3", true)).await.unwrap();
+
+        pool.take(workflow_id, Denomination::Token, &strk("2.5")).await.unwrap();
+        pool.put_back(workflow_id, Denomination::Token, &strk("2.5")).await.unwrap();
+        assert_eq!(available(&pool, workflow_id), strk("3"));
+    }
+}
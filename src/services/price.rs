@@ -0,0 +1,109 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use tracing::{debug, instrument};
+
+use crate::contracts::token::{NativeToken, Token};
+
+#[derive(Clone, clap::Parser)]
+pub struct PriceConfig {
+    /// Base URL of the CoinGecko compatible price API.
+    #[clap(long, env = "DRK_PRICE_API_URL", default_value = "https://api.coingecko.com/api/v3")]
+    pub price_api_url: String,
+
+    /// API key of the price API, if any.
+    #[clap(long, env = "DRK_PRICE_API_KEY")]
+    pub price_api_key: Option<String>,
+
+    /// Seconds a fetched price is reused before being fetched again.
+    #[clap(long, env = "DRK_PRICE_CACHE_TTL", default_value_t = 60)]
+    pub price_cache_ttl: u64,
+}
+
+/// Fetches USD prices of payout tokens, with a short-lived cache.
+#[derive(Clone)]
+pub struct PriceOracle {
+    client: reqwest::Client,
+    config: PriceConfig,
+    cache: Arc<Mutex<HashMap<String, (Instant, String)>>>,
+}
+
+impl PriceOracle {
+    pub fn new(config: &PriceConfig) -> Self {
+        Self { client: reqwest::Client::new(), config: config.clone(), cache: Default::default() }
+    }
+
+    /// Get the USD price of one whole token, as a decimal string.
+    #[instrument(skip_all, fields(%token))]
+    pub async fn usd_price(&self, token: &Token) -> Result<String> {
+        let key = token.to_string();
+        let ttl = Duration::from_secs(self.config.price_cache_ttl);
+        if let Some((at, price)) = self.cache.lock().unwrap().get(&key) {
+            if at.elapsed() < ttl {
+                return Ok(price.clone());
+            }
+        }
+
+        let price = self.fetch(token).await?;
+        debug!(%price, "Fetched token price");
+        self.cache.lock().unwrap().insert(key, (Instant::now(), price.clone()));
+
+        Ok(price)
+    }
+
+    async fn fetch(&self, token: &Token) -> Result<String> {
+        let base = self.config.price_api_url.trim_end_matches('/');
+        let (url, id) = match token {
+            Token::Native(native) => {
+                let id = match native {
+                    NativeToken::Strk => "starknet",
+                    NativeToken::Eth => "ethereum",
+                };
+                (format!("{base}/simple/price?ids={id}&vs_currencies=usd"), id.to_string())
+            }
+            Token::Erc20(address) => {
+                let address = address.to_lowercase();
+                let url = format!(
+                    "{base}/simple/token_price/starknet?contract_addresses={address}\
+                     &vs_currencies=usd"
+                );
+                (url, address)
+            }
+        };
+
+        let mut request = self.client.get(&url);
+        if let Some(key) = self.config.price_api_key.as_deref().filter(|key| !key.is_empty()) {
+            request = request.header("x-cg-demo-api-key", key);
+        }
+        let body: Value = request.send().await?.error_for_status()?.json().await?;
+
+        let price = body
+            .get(&id)
+            .and_then(|prices| prices.get("usd"))
+            .and_then(Value::as_f64)
+            .filter(|price| *price > 0.0)
+            .ok_or_else(|| anyhow!("No USD price for {token}"))?;
+
+        // Fixed notation, small prices would otherwise be formatted as eg. `1e-7`.
+        Ok(format!("{price:.18}").trim_end_matches('0').trim_end_matches('.').to_string())
+    }
+}
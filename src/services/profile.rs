@@ -0,0 +1,211 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Named weight profiles of the ranking algorithm.
+//!
+//! Different funding philosophies weigh the ranking signals differently, eg. favoring
+//! widely downloaded packages or actively maintained ones. Each workflow ranks its
+//! dependencies with the profile it selected.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    context::Context,
+    errors::{ApiError, Result},
+    middlewares::ratelimit::ClientKey,
+    requests::profile::{DecayCurve, KindWeights, Weights},
+    responses::{
+        dependency::{DependencyKind, ScoreExplanationResponse},
+        profile::WeightProfileResponse,
+    },
+};
+
+/// The profile used by workflows which do not select one.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// The signals a dependency is ranked on.
+#[derive(Debug, Clone, Default)]
+pub struct Signals {
+    /// The depth in the dependency tree, 1 for direct dependencies
+    pub depth: u32,
+    pub kind: DependencyKind,
+    pub downloads: u64,
+    pub active_contributors: u32,
+    pub vulnerabilities: u32,
+}
+
+impl Weights {
+    /// Score a dependency, before normalization over the dependencies of a project.
+    pub fn score(&self, signals: &Signals) -> f64 {
+        self.explain(signals).raw_score
+    }
+
+    /// Score a dependency, detailing the factor of every signal.
+    pub fn explain(&self, signals: &Signals) -> ScoreExplanationResponse {
+        let depth_factor = self.depth_curve.factor(self.depth_decay, signals.depth);
+        let kind_factor = self.kinds.get(signals.kind);
+        let popularity_factor = (1.0
+            + self.downloads * (signals.downloads as f64).ln_1p()
+            + self.contributor_activity * (signals.active_contributors as f64).ln_1p())
+        .max(0.0);
+        let vulnerability_factor =
+            (1.0 - self.vulnerability_penalty.clamp(0.0, 1.0)).powi(signals.vulnerabilities as i32);
+
+        ScoreExplanationResponse {
+            depth: signals.depth,
+            depth_factor,
+            kind_factor,
+            popularity_factor,
+            vulnerability_factor,
+            raw_score: depth_factor * kind_factor * popularity_factor * vulnerability_factor,
+        }
+    }
+}
+
+impl DecayCurve {
+    /// The share of the score kept at a depth, 1 for direct dependencies.
+    pub fn factor(&self, decay: f64, depth: u32) -> f64 {
+        let decay = decay.clamp(0.0, 1.0);
+        let levels = depth.saturating_sub(1) as f64;
+        if levels == 0.0 {
+            return 1.0;
+        }
+        match self {
+            Self::Exponential => decay.powf(levels),
+            Self::Hyperbolic if decay == 0.0 => 0.0,
+            Self::Hyperbolic => 1.0 / (1.0 + (1.0 / decay - 1.0) * levels),
+            Self::Linear => (1.0 - (1.0 - decay) * levels).max(0.0),
+        }
+    }
+}
+
+impl KindWeights {
+    pub fn get(&self, kind: DependencyKind) -> f64 {
+        match kind {
+            DependencyKind::Runtime => self.runtime,
+            DependencyKind::Dev => self.dev,
+            DependencyKind::Build => self.build,
+            DependencyKind::Optional => self.optional,
+        }
+    }
+}
+
+/// The weight profiles, in memory, always including the default one.
+#[derive(Clone)]
+pub struct ProfileStore {
+    profiles: Arc<Mutex<BTreeMap<String, Weights>>>,
+}
+
+impl Default for ProfileStore {
+    fn default() -> Self {
+        let profiles = BTreeMap::from([(DEFAULT_PROFILE.to_string(), Weights::default())]);
+        Self { profiles: Arc::new(Mutex::new(profiles)) }
+    }
+}
+
+impl ProfileStore {
+    /// Get the weights of a profile, the default one if no name is given.
+    pub fn get(&self, name: Option<&str>) -> Option<Weights> {
+        self.profiles.lock().unwrap().get(name.unwrap_or(DEFAULT_PROFILE)).cloned()
+    }
+}
+
+pub struct ProfileService;
+
+impl ProfileService {
+    pub async fn list(ctx: Arc<Context>) -> Result<Vec<WeightProfileResponse>> {
+        let profiles = ctx.profiles.profiles.lock().unwrap();
+        Ok(profiles.iter().map(|(name, weights)| to_response(name, weights)).collect())
+    }
+
+    pub async fn get(ctx: Arc<Context>, name: &str) -> Result<WeightProfileResponse> {
+        match ctx.profiles.get(Some(name)) {
+            Some(weights) => Ok(to_response(name, &weights)),
+            None => Err(ApiError::NotFoundProfile(name.to_string())),
+        }
+    }
+
+    /// Create or replace a profile, restricted to the admin key.
+    pub async fn put(
+        ctx: Arc<Context>,
+        key: &ClientKey,
+        name: &str,
+        weights: Weights,
+    ) -> Result<WeightProfileResponse> {
+        key.authorize_admin(&ctx.config.admin_api_key)?;
+        validate(name, &weights)?;
+
+        ctx.profiles.profiles.lock().unwrap().insert(name.to_string(), weights.clone());
+        Ok(to_response(name, &weights))
+    }
+
+    /// Delete a profile, restricted to the admin key.
+    pub async fn delete(ctx: Arc<Context>, key: &ClientKey, name: &str) -> Result<()> {
+        key.authorize_admin(&ctx.config.admin_api_key)?;
+        if name == DEFAULT_PROFILE {
+            return Err(ApiError::BadProfileRequest("the default profile is built in".to_string()));
+        }
+
+        match ctx.profiles.profiles.lock().unwrap().remove(name) {
+            Some(_) => Ok(()),
+            None => Err(ApiError::NotFoundProfile(name.to_string())),
+        }
+    }
+}
+
+fn validate(name: &str, weights: &Weights) -> Result<()> {
+    let valid_name = (1..=64).contains(&name.len())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !valid_name {
+        return Err(ApiError::BadProfileRequest(format!(
+            "invalid name `{name}`, use 1 to 64 lowercase letters, digits, `-` or `_`"
+        )));
+    }
+
+    let shares = [
+        ("depth_decay", weights.depth_decay),
+        ("vulnerability_penalty", weights.vulnerability_penalty),
+    ];
+    for (field, share) in shares {
+        if !(0.0..=1.0).contains(&share) {
+            return Err(ApiError::BadProfileRequest(format!("{field} must be between 0 and 1")));
+        }
+    }
+    let factors = [
+        ("downloads", weights.downloads),
+        ("contributor_activity", weights.contributor_activity),
+        ("kinds.runtime", weights.kinds.runtime),
+        ("kinds.dev", weights.kinds.dev),
+        ("kinds.build", weights.kinds.build),
+        ("kinds.optional", weights.kinds.optional),
+    ];
+    for (field, weight) in factors {
+        if !weight.is_finite() || weight < 0.0 {
+            return Err(ApiError::BadProfileRequest(format!("{field} must be positive")));
+        }
+    }
+    Ok(())
+}
+
+fn to_response(name: &str, weights: &Weights) -> WeightProfileResponse {
+    WeightProfileResponse {
+        name: name.to_string(),
+        built_in: name == DEFAULT_PROFILE,
+        weights: weights.clone(),
+    }
+}
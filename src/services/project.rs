@@ -14,12 +14,40 @@
 
 use std::sync::Arc;
 
-use crate::{context::Context, errors::Result, responses::project::ProjectResponse};
+use crate::{
+    context::Context,
+    errors::{ApiError, Result},
+    responses::project::ProjectResponse,
+    services::snapshot::SnapshotService,
+};
 
 pub struct ProjectService;
 
 impl ProjectService {
-    pub async fn get(_ctx: Arc<Context>, _owner: &str, _name: &str) -> Result<ProjectResponse> {
-        todo!()
+    /// Get a project as of its latest snapshot, with the revision of the workflow which
+    /// analyzed it, or of its latest workflow if none did.
+    pub async fn get(ctx: Arc<Context>, owner: &str, name: &str) -> Result<ProjectResponse> {
+        let project = format!("{owner}/{name}").to_lowercase();
+        let snapshot = SnapshotService::latest(&ctx, &project);
+        let workflows = ctx.workflows.filter(|record| record.project == project);
+        let workflow = workflows
+            .iter()
+            .filter(|record| record.snapshot_id.is_some())
+            .find(|record| record.snapshot_id == snapshot.as_ref().map(|snapshot| snapshot.id))
+            .or_else(|| workflows.iter().max_by_key(|record| record.created_at));
+        if snapshot.is_none() && workflow.is_none() {
+            return Err(ApiError::NotFound);
+        }
+
+        Ok(ProjectResponse {
+            repo: workflow
+                .map(|record| record.repo.clone())
+                .unwrap_or_else(|| format!("https://github.com/{project}")),
+            branch: workflow.and_then(|record| record.branch.clone()),
+            tag: workflow.and_then(|record| record.tag.clone()),
+            rev: workflow.and_then(|record| record.rev.clone()),
+            commit: snapshot.map(|snapshot| snapshot.commit.clone()),
+            organization: ctx.organizations.owning(owner).map(|organization| organization.name),
+        })
     }
 }
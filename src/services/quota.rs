@@ -0,0 +1,210 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Per-account quotas of the plan limits.
+//!
+//! Every client, identified by its API key, may trigger a limited number of analyses per
+//! day, run a limited number of workflows at once and analyze repositories up to a size.
+//! Exhausted quotas fail with `429 Too Many Requests` until they reset, while requests
+//! beyond what the plan allows at all fail with `403 Forbidden`. The admin key has none.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    errors::{ApiError, Result},
+    middlewares::ratelimit::ClientKey,
+    responses::quota::{LimitsResponse, QuotaResponse},
+};
+
+#[derive(Clone, clap::Parser)]
+pub struct QuotaConfig {
+    /// Maximum number of analyses per client and day, 0 for unlimited
+    #[clap(long, env = "DRK_QUOTA_ANALYSES_PER_DAY", default_value_t = 50)]
+    pub quota_analyses_per_day: u64,
+
+    /// Maximum size of an analyzed repository in megabytes, 0 for unlimited
+    #[clap(long, env = "DRK_QUOTA_MAX_REPO_SIZE", default_value_t = 500)]
+    pub quota_max_repo_size: u64,
+
+    /// Maximum number of workflows a client runs at once, 0 for unlimited
+    #[clap(long, env = "DRK_QUOTA_MAX_CONCURRENT_WORKFLOWS", default_value_t = 5)]
+    pub quota_max_concurrent_workflows: u64,
+}
+
+impl QuotaConfig {
+    /// The limits of a client, `None` when unlimited.
+    fn limits(&self) -> [Option<u64>; 3] {
+        [self.quota_analyses_per_day, self.quota_max_repo_size, self.quota_max_concurrent_workflows]
+            .map(|limit| (limit > 0).then_some(limit))
+    }
+}
+
+/// The consumption of the quotas of a client.
+#[derive(Debug, Default)]
+struct Consumption {
+    /// The day of the counted analyses, in UTC
+    day: Option<NaiveDate>,
+    analyses: u64,
+    /// The workflows not finished yet
+    workflows: HashSet<Uuid>,
+}
+
+impl Consumption {
+    /// The analyses of the current day.
+    fn analyses_today(&self, today: NaiveDate) -> u64 {
+        if self.day == Some(today) {
+            self.analyses
+        } else {
+            0
+        }
+    }
+}
+
+/// In-memory quota consumption, by client key.
+#[derive(Clone, Default)]
+pub struct QuotaTracker {
+    consumptions: Arc<Mutex<HashMap<String, Consumption>>>,
+}
+
+impl QuotaTracker {
+    /// Count an analysis of the client, failing when its daily quota is exhausted.
+    pub fn acquire_analysis(&self, key: &ClientKey, limit: Option<u64>) -> Result<()> {
+        let today = Utc::now().date_naive();
+        let mut consumptions = self.consumptions.lock().unwrap();
+        let consumption = consumptions.entry(key.0.clone()).or_default();
+
+        let analyses = consumption.analyses_today(today);
+        if let Some(limit) = limit.filter(|limit| analyses >= *limit) {
+            return Err(ApiError::QuotaExceeded(format!(
+                "{limit} analyses per day, resets at {}",
+                next_reset(today).to_rfc3339()
+            )));
+        }
+        consumption.day = Some(today);
+        consumption.analyses = analyses + 1;
+        Ok(())
+    }
+
+    /// Give back an analysis of the client which did not happen, eg. its workflow failed
+    /// before analyzing the repository.
+    pub fn refund_analysis(&self, key: &ClientKey) {
+        let today = Utc::now().date_naive();
+        let mut consumptions = self.consumptions.lock().unwrap();
+        if let Some(consumption) = consumptions.get_mut(&key.0) {
+            if consumption.day == Some(today) {
+                consumption.analyses = consumption.analyses.saturating_sub(1);
+            }
+        }
+    }
+
+    /// Fail when the client runs as many workflows as it may at once.
+    pub fn check_workflows(&self, key: &ClientKey, limit: Option<u64>) -> Result<()> {
+        let consumptions = self.consumptions.lock().unwrap();
+        let running = consumptions.get(&key.0).map_or(0, |c| c.workflows.len() as u64);
+        if let Some(limit) = limit.filter(|limit| running >= *limit) {
+            return Err(ApiError::QuotaExceeded(format!(
+                "{limit} concurrent workflows, wait for one to finish"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Count a workflow of the client as running.
+    pub fn begin_workflow(&self, key: &ClientKey, id: Uuid) {
+        let mut consumptions = self.consumptions.lock().unwrap();
+        consumptions.entry(key.0.clone()).or_default().workflows.insert(id);
+    }
+
+    /// Stop counting a finished, failed or deleted workflow.
+    pub fn end_workflow(&self, id: Uuid) {
+        for consumption in self.consumptions.lock().unwrap().values_mut() {
+            consumption.workflows.remove(&id);
+        }
+    }
+}
+
+pub struct QuotaService;
+
+impl QuotaService {
+    /// Count an analysis against the daily quota of the client.
+    pub fn acquire_analysis(ctx: &Context, key: &ClientKey) -> Result<()> {
+        if is_exempt(ctx, key) {
+            return Ok(());
+        }
+        let [analyses, _, _] = ctx.config.quota_config.limits();
+        ctx.quotas.acquire_analysis(key, analyses)
+    }
+
+    /// Give back an analysis counted against the daily quota of the client.
+    pub fn refund_analysis(ctx: &Context, key: &ClientKey) {
+        if !is_exempt(ctx, key) {
+            ctx.quotas.refund_analysis(key);
+        }
+    }
+
+    /// Check the client may start another workflow.
+    pub fn check_workflows(ctx: &Context, key: &ClientKey) -> Result<()> {
+        if is_exempt(ctx, key) {
+            return Ok(());
+        }
+        let [_, _, workflows] = ctx.config.quota_config.limits();
+        ctx.quotas.check_workflows(key, workflows)
+    }
+
+    /// The maximum size of a repository the client may analyze, in kilobytes.
+    pub fn max_repo_size(ctx: &Context, key: &ClientKey) -> Option<u64> {
+        let [_, limit, _] = ctx.config.quota_config.limits();
+        limit.filter(|_| !is_exempt(ctx, key)).map(|limit| limit * 1024)
+    }
+
+    /// Get the limits of the calling client, with its current usage.
+    pub async fn get(ctx: Arc<Context>, key: &ClientKey) -> Result<LimitsResponse> {
+        let [analyses, repo_size, workflows] =
+            if is_exempt(&ctx, key) { [None; 3] } else { ctx.config.quota_config.limits() };
+
+        let today = Utc::now().date_naive();
+        let consumptions = ctx.quotas.consumptions.lock().unwrap();
+        let consumption = consumptions.get(&key.0);
+
+        Ok(LimitsResponse {
+            analyses_per_day: QuotaResponse {
+                used: consumption.map_or(0, |c| c.analyses_today(today)),
+                limit: analyses,
+            },
+            concurrent_workflows: QuotaResponse {
+                used: consumption.map_or(0, |c| c.workflows.len() as u64),
+                limit: workflows,
+            },
+            max_repo_size: repo_size,
+            resets_at: next_reset(today),
+        })
+    }
+}
+
+fn is_exempt(ctx: &Context, key: &ClientKey) -> bool {
+    key.is_admin(&ctx.config.admin_api_key)
+}
+
+/// When the daily quotas reset, at midnight UTC.
+fn next_reset(today: NaiveDate) -> DateTime<Utc> {
+    let tomorrow = today.checked_add_days(Days::new(1)).unwrap_or(today);
+    tomorrow.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc()
+}
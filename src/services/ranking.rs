@@ -0,0 +1,371 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Global dependency ranking across all analyzed projects.
+//!
+//! Analyzed projects and their dependencies form a single dependency graph, ranked with
+//! PageRank: a package scores high when it is depended upon by packages which score
+//! high themselves. Rankings are computed periodically and kept as snapshots.
+//!
+//! Ecosystem rankings instead aggregate the scores of every package across the latest
+//! snapshots of all projects, next to the funding the package received.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use num_bigint::BigUint;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    errors::{ApiError, Result},
+    requests::workflow::Denomination,
+    responses::{
+        dependency::{DependencyResponse, Ecosystem},
+        ranking::{EcosystemRankingResponse, FundingResponse, GlobalRankingResponse},
+    },
+    services::{
+        allocation::{AllocationRecord, ExecutionStatus},
+        payout::{from_base_units, to_base_units},
+        snapshot::SnapshotService,
+    },
+};
+
+/// The precision used to sum amounts, in decimals.
+const SCALE: u8 = 18;
+
+#[derive(Clone, clap::Parser)]
+pub struct RankingConfig {
+    /// Seconds between two computations of the global ranking.
+    #[clap(long, env = "DRK_RANKING_INTERVAL", default_value_t = 3600)]
+    pub ranking_interval: u64,
+
+    /// Probability of following a dependency rather than jumping to a random package.
+    #[clap(long, env = "DRK_RANKING_DAMPING", default_value_t = 0.85)]
+    pub ranking_damping: f64,
+
+    /// Number of global ranking snapshots kept.
+    #[clap(long, env = "DRK_RANKING_SNAPSHOTS", default_value_t = 24)]
+    pub ranking_snapshots: usize,
+}
+
+/// The identifier of a package in the dependency graph, its repository when known,
+/// eg. `deprank/backend`, otherwise its name.
+pub fn node(dependency: &DependencyResponse) -> String {
+    dependency
+        .repository
+        .as_deref()
+        .and_then(|repository| {
+            let path = repository.trim_end_matches('/').trim_end_matches(".git");
+            let path = path.split_once("github.com/").map(|(_, path)| path)?;
+            Some(path.to_lowercase())
+        })
+        .unwrap_or_else(|| dependency.name.clone())
+}
+
+/// The dependencies of every analyzed project, in memory.
+#[derive(Clone, Default)]
+pub struct DependencyGraph {
+    inner: Arc<Mutex<Graph>>,
+}
+
+#[derive(Default)]
+struct Graph {
+    edges: BTreeMap<String, BTreeSet<String>>,
+    /// Incremented on every change, so unchanged graphs are not ranked again
+    version: u64,
+}
+
+impl DependencyGraph {
+    /// Record the dependencies of an analyzed project, eg. `deprank/backend`, replacing
+    /// those of its previous analysis.
+    pub fn record(&self, project: &str, dependencies: &[DependencyResponse]) {
+        let mut graph = self.inner.lock().unwrap();
+        graph.edges.insert(project.to_lowercase(), dependencies.iter().map(node).collect());
+        graph.version += 1;
+    }
+
+    fn snapshot(&self) -> (u64, BTreeMap<String, BTreeSet<String>>) {
+        let graph = self.inner.lock().unwrap();
+        (graph.version, graph.edges.clone())
+    }
+}
+
+/// A global ranking, computed at a point in time.
+#[derive(Debug, Clone)]
+pub struct RankingSnapshot {
+    pub id: Uuid,
+    pub computed_at: DateTime<Utc>,
+    /// The version of the dependency graph which was ranked
+    graph_version: u64,
+    /// The ranked packages, by decreasing score
+    pub entries: Vec<RankingEntry>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RankingEntry {
+    pub package: String,
+    /// The PageRank score, all scores sum up to 1
+    pub score: f64,
+    /// Number of packages depending on it
+    pub dependents: usize,
+}
+
+/// The latest global ranking snapshots, oldest first.
+#[derive(Clone, Default)]
+pub struct RankingStore {
+    snapshots: Arc<Mutex<VecDeque<RankingSnapshot>>>,
+}
+
+impl RankingStore {
+    pub fn latest(&self) -> Option<RankingSnapshot> {
+        self.snapshots.lock().unwrap().back().cloned()
+    }
+
+    pub fn get(&self, id: Uuid) -> Option<RankingSnapshot> {
+        self.snapshots.lock().unwrap().iter().find(|snapshot| snapshot.id == id).cloned()
+    }
+
+    fn push(&self, snapshot: RankingSnapshot, keep: usize) {
+        let mut snapshots = self.snapshots.lock().unwrap();
+        snapshots.push_back(snapshot);
+        while snapshots.len() > keep.max(1) {
+            snapshots.pop_front();
+        }
+    }
+}
+
+pub struct RankingService;
+
+impl RankingService {
+    /// Rank the dependency graph and keep the result as a new snapshot, unless the graph
+    /// did not change since the latest one.
+    pub fn compute(ctx: &Context) -> Option<RankingSnapshot> {
+        let (version, edges) = ctx.graph.snapshot();
+        if ctx.rankings.latest().is_some_and(|latest| latest.graph_version == version) {
+            return None;
+        }
+
+        let config = &ctx.config.ranking_config;
+        let mut dependents: HashMap<&str, usize> = HashMap::new();
+        for dependency in edges.values().flatten() {
+            *dependents.entry(dependency).or_default() += 1;
+        }
+        let mut entries: Vec<_> = pagerank(&edges, config.ranking_damping)
+            .into_iter()
+            .map(|(package, score)| RankingEntry {
+                dependents: dependents.get(package.as_str()).copied().unwrap_or_default(),
+                package,
+                score,
+            })
+            .collect();
+        entries.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.package.cmp(&b.package)));
+
+        let snapshot = RankingSnapshot {
+            id: Uuid::new_v4(),
+            computed_at: Utc::now(),
+            graph_version: version,
+            entries,
+        };
+        ctx.rankings.push(snapshot.clone(), config.ranking_snapshots);
+        Some(snapshot)
+    }
+
+    /// Get the global ranking of a snapshot, the latest one by default.
+    pub async fn global(
+        ctx: Arc<Context>,
+        snapshot: Option<Uuid>,
+    ) -> Result<Vec<GlobalRankingResponse>> {
+        let snapshot = match snapshot {
+            Some(id) => ctx.rankings.get(id).ok_or(ApiError::NotFoundSnapshot(id.to_string()))?,
+            None => match ctx.rankings.latest() {
+                Some(snapshot) => snapshot,
+                None => return Ok(Vec::new()),
+            },
+        };
+
+        Ok(snapshot
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| GlobalRankingResponse {
+                rank: i + 1,
+                package: entry.package.clone(),
+                score: entry.score,
+                dependents: entry.dependents,
+                snapshot_id: snapshot.id,
+                computed_at: snapshot.computed_at,
+            })
+            .collect())
+    }
+
+    /// Rank the packages of an ecosystem by their scores summed over the latest snapshot
+    /// of every project, with the payouts they received.
+    pub async fn ecosystem(
+        ctx: Arc<Context>,
+        ecosystem: Ecosystem,
+    ) -> Result<Vec<EcosystemRankingResponse>> {
+        let mut packages: BTreeMap<String, Aggregate> = BTreeMap::new();
+        for project in ctx.snapshots.projects() {
+            let Some(snapshot) = SnapshotService::latest(&ctx, &project) else {
+                continue;
+            };
+            let dependencies = snapshot.dependencies.iter().filter(|dependency| {
+                dependency.ecosystem == ecosystem && !dependency.excluded_by_policy
+            });
+            for dependency in dependencies {
+                let aggregate = packages.entry(dependency.name.to_lowercase()).or_default();
+                aggregate.score += dependency.score;
+                aggregate.dependents += 1;
+            }
+        }
+
+        for record in ctx.allocations.by_status(ExecutionStatus::Executed) {
+            let Some(package) = record.package.as_ref().filter(|p| p.ecosystem == ecosystem) else {
+                continue;
+            };
+            let (amount, usd) = match received(&record) {
+                Ok(received) => received,
+                Err(e) => {
+                    warn!(allocation_id = %record.id, "Skipped payout: {e}");
+                    continue;
+                }
+            };
+            let aggregate = packages.entry(package.name.to_lowercase()).or_default();
+            *aggregate.funding.entry(record.token.to_string()).or_default() += amount;
+            aggregate.funded_usd += usd.unwrap_or_default();
+        }
+
+        let total_score: f64 = packages.values().map(|aggregate| aggregate.score).sum();
+        let total_usd: BigUint = packages.values().map(|aggregate| &aggregate.funded_usd).sum();
+        let share = |part: f64, total: f64| if total > 0.0 { part / total } else { 0.0 };
+        let usd_share = |usd: &BigUint| {
+            let to_f64 = |amount: &BigUint| {
+                from_base_units(amount, SCALE).parse::<f64>().unwrap_or_default()
+            };
+            share(to_f64(usd), to_f64(&total_usd))
+        };
+
+        let mut entries: Vec<_> = packages.into_iter().collect();
+        entries.sort_by(|(a_name, a), (b_name, b)| {
+            b.score.total_cmp(&a.score).then_with(|| a_name.cmp(b_name))
+        });
+        Ok(entries
+            .into_iter()
+            .enumerate()
+            .map(|(i, (package, aggregate))| {
+                let score_share = share(aggregate.score, total_score);
+                let funding_share = usd_share(&aggregate.funded_usd);
+                EcosystemRankingResponse {
+                    rank: i + 1,
+                    package,
+                    ecosystem,
+                    score: aggregate.score,
+                    dependents: aggregate.dependents,
+                    score_share,
+                    funding: aggregate
+                        .funding
+                        .iter()
+                        .map(|(token, amount)| FundingResponse {
+                            token: token.clone(),
+                            amount: from_base_units(amount, SCALE),
+                        })
+                        .collect(),
+                    funded_usd: from_base_units(&aggregate.funded_usd, SCALE),
+                    funding_share,
+                    underfunding: score_share - funding_share,
+                }
+            })
+            .collect())
+    }
+}
+
+/// The amount a payout transferred, in units of `SCALE` decimals, with its USD value
+/// when known.
+fn received(record: &AllocationRecord) -> anyhow::Result<(BigUint, Option<BigUint>)> {
+    let amount = record.token_amount.as_ref().unwrap_or(&record.amount);
+    let tokens = to_base_units(amount, SCALE)?;
+    let usd = match (record.denomination, &record.usd_rate) {
+        (Denomination::Usd, _) => Some(to_base_units(&record.amount, SCALE)?),
+        (Denomination::Token, Some(rate)) => {
+            let rate = to_base_units(rate, SCALE)?;
+            Some(&tokens * rate / BigUint::from(10u8).pow(SCALE as u32))
+        }
+        (Denomination::Token, None) => None,
+    };
+    Ok((tokens, usd))
+}
+
+/// The scores and funding of a package of an ecosystem.
+#[derive(Default)]
+struct Aggregate {
+    score: f64,
+    dependents: usize,
+    /// The amounts received per token, in units of `SCALE` decimals
+    funding: BTreeMap<String, BigUint>,
+    /// The USD value received, in units of `SCALE` decimals
+    funded_usd: BigUint,
+}
+
+/// Rank the packages of a dependency graph, rank flowing from dependents to dependencies.
+///
+/// Packages without dependencies spread their rank evenly over all packages.
+fn pagerank(edges: &BTreeMap<String, BTreeSet<String>>, damping: f64) -> Vec<(String, f64)> {
+    const MAX_ITERATIONS: usize = 100;
+    const TOLERANCE: f64 = 1e-10;
+
+    let mut nodes: BTreeSet<&str> = edges.keys().map(String::as_str).collect();
+    nodes.extend(edges.values().flatten().map(String::as_str));
+    let index: HashMap<&str, usize> = nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+    let n = nodes.len();
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let outgoing: Vec<Vec<usize>> = nodes
+        .iter()
+        .map(|node| {
+            edges
+                .get(*node)
+                .map(|deps| deps.iter().map(|dep| index[dep.as_str()]).collect())
+                .unwrap_or_default()
+        })
+        .collect();
+
+    let damping = damping.clamp(0.0, 1.0);
+    let mut rank = vec![1.0 / n as f64; n];
+    for _ in 0..MAX_ITERATIONS {
+        let dangling: f64 = (0..n).filter(|i| outgoing[*i].is_empty()).map(|i| rank[i]).sum();
+        let base = (1.0 - damping) / n as f64 + damping * dangling / n as f64;
+
+        let mut next = vec![base; n];
+        for (i, deps) in outgoing.iter().enumerate() {
+            let share = damping * rank[i] / deps.len().max(1) as f64;
+            for dep in deps {
+                next[*dep] += share;
+            }
+        }
+
+        let delta: f64 = rank.iter().zip(&next).map(|(a, b)| (a - b).abs()).sum();
+        rank = next;
+        if delta < TOLERANCE {
+            break;
+        }
+    }
+
+    nodes.into_iter().map(str::to_string).zip(rank).collect()
+}
@@ -0,0 +1,132 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Receipts attesting the dependencies funded by workflows.
+//!
+//! The metadata of a receipt is validated and stored with the configured backend, then
+//! the receipt is created on chain with the hash and URI of the stored document, so
+//! anyone can retrieve it and check it was not altered. The backend checks it the same
+//! way, recomputing the hash of the stored document and comparing it with the receipt.
+
+use serde_json::Value;
+
+use crate::{
+    context::Context,
+    contracts::{
+        hash::{canonical_json, metadata_hash, same_hash},
+        receipt::{ReceiptContract, ReceiptMetadata},
+        types::{Hash, Id},
+    },
+    db::{receipt::ReceiptRow, Write},
+    errors::{ApiError, Result},
+    responses::metadata::MetadataVerificationResponse,
+    services::metadata::MetadataService,
+};
+
+pub struct ReceiptService;
+
+impl ReceiptService {
+    /// Create the receipt of a dependency of a workflow on chain, returning its id unless
+    /// it is only known once the transaction is executed.
+    pub async fn create(ctx: &Context, workflow_id: Id, metadata: &Value) -> Result<Option<Id>> {
+        let store = ctx.metadata_store.as_ref().ok_or_else(|| {
+            ApiError::FailedToCreateWorkflow("no metadata store is configured".to_string())
+        })?;
+        let (metadata, validated) = MetadataService::validate(metadata)?;
+
+        let metadata_uri =
+            store.put(&validated.metadata_json, &validated.metadata_hash).await.map_err(|e| {
+                ApiError::FailedToCreateWorkflow(format!(
+                    "failed to store metadata with {}: {e:#}",
+                    store.backend()
+                ))
+            })?;
+
+        let metadata_hash: Hash =
+            validated.metadata_hash.parse().expect("the metadata hash is a valid hash");
+        let license = metadata.license.unwrap_or_default();
+        let id = ctx
+            .contract
+            .create_receipt(
+                workflow_id.clone(),
+                metadata.repository_url.clone(),
+                ReceiptMetadata {
+                    name: metadata.name.clone(),
+                    version: metadata.version.clone(),
+                    author: String::new(),
+                    license: license.clone(),
+                },
+                metadata_hash.clone(),
+                metadata_uri.clone(),
+            )
+            .await
+            .map_err(|e| ApiError::chain(e, ApiError::FailedToCreateWorkflow))?;
+
+        // Receipts are only indexed once the chain returns their id.
+        if let (Some(db), Some(id)) = (&ctx.db, &id) {
+            db.write(Write::Receipt(ReceiptRow {
+                id: id.clone(),
+                workflow_id,
+                repository_url: metadata.repository_url,
+                name: metadata.name,
+                version: metadata.version,
+                license,
+                metadata_hash,
+                metadata_uri,
+            }));
+        }
+        Ok(id)
+    }
+
+    /// Verify the stored metadata of a receipt against the hash stored on chain with it.
+    pub async fn verify(ctx: &Context, receipt_id: Id) -> Result<MetadataVerificationResponse> {
+        let store = ctx.metadata_store.as_ref().ok_or_else(|| {
+            ApiError::MetadataUnavailable("no metadata store is configured".to_string())
+        })?;
+        let (receipt, _) = ctx
+            .contract
+            .get_receipt_details(receipt_id.clone())
+            .await
+            .map_err(|e| ApiError::chain(e, ApiError::NotFoundReceipt))?;
+
+        let stored = store.get(&receipt.metadata_uri).await.map_err(|e| {
+            ApiError::MetadataUnavailable(format!(
+                "failed to get {} from {}: {e:#}",
+                receipt.metadata_uri,
+                store.backend()
+            ))
+        })?;
+        let document: Value = serde_json::from_str(&stored).map_err(|e| {
+            ApiError::MetadataUnavailable(format!("{} is not JSON: {e}", receipt.metadata_uri))
+        })?;
+        let metadata_json = canonical_json(&document);
+        let computed_hash = metadata_hash(&metadata_json);
+
+        let verified_on_chain = ctx
+            .contract
+            .verify_metadata(receipt_id.clone(), computed_hash.clone())
+            .await
+            .map_err(|e| ApiError::chain(e, ApiError::NotFoundReceipt))?;
+
+        Ok(MetadataVerificationResponse {
+            receipt_id: receipt_id.to_string(),
+            matches: same_hash(&computed_hash, &receipt.metadata_hash),
+            canonical: stored == metadata_json,
+            metadata_uri: receipt.metadata_uri,
+            metadata_hash: receipt.metadata_hash.to_string(),
+            computed_hash: computed_hash.to_string(),
+            verified_on_chain,
+        })
+    }
+}
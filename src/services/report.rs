@@ -0,0 +1,420 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Shareable reports of workflows.
+//!
+//! A report sums up a workflow for its funders and recipients: the commit which was
+//! analyzed, the ranked dependencies, the signatures collected and the allocations
+//! paid out, linking every transaction to the block explorer. It is rendered either
+//! as a standalone HTML page or as a plain text PDF document.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    contracts::{
+        types::{Address, Hash},
+        Contract,
+    },
+    errors::{ApiError, Result},
+    requests::workflow::Denomination,
+    services::{
+        allocation::{AllocationRecord, ExecutionStatus},
+        contract::ContractService,
+        snapshot::ProjectSnapshot,
+        workflow::SignatureRecord,
+    },
+};
+
+/// The page size of the PDF documents, A4 in points.
+const PAGE_WIDTH: u32 = 595;
+const PAGE_HEIGHT: u32 = 842;
+const PAGE_MARGIN: u32 = 50;
+
+/// The font size and line height of the PDF documents, in points.
+const FONT_SIZE: u32 = 9;
+const LINE_HEIGHT: u32 = 13;
+
+/// The number of characters fitting on a line of a PDF document.
+const LINE_WIDTH: usize = 100;
+
+/// A ranked dependency, with its share of the project score.
+#[derive(Debug, Clone)]
+pub struct ReportDependency {
+    pub name: String,
+    pub version: String,
+    pub ecosystem: String,
+    pub license: Option<String>,
+    /// The share of the score of the project, in percent
+    pub share: f64,
+}
+
+/// A signature collected by the workflow.
+#[derive(Debug, Clone)]
+pub struct ReportSignature {
+    pub signer: Address,
+    pub signed_at: DateTime<Utc>,
+    pub tx_hash: Hash,
+    pub tx_url: String,
+}
+
+/// An allocation paid out by the workflow.
+#[derive(Debug, Clone)]
+pub struct ReportAllocation {
+    /// The contributor or package funded, the recipient address otherwise
+    pub beneficiary: String,
+    pub recipient: Address,
+    /// The amount paid out, eg. `12.5 STRK`
+    pub amount: String,
+    pub tx_hash: Option<Hash>,
+    pub tx_url: Option<String>,
+}
+
+/// Everything a workflow report shows.
+#[derive(Debug, Clone)]
+pub struct WorkflowReport {
+    pub workflow_id: Uuid,
+    /// The project, eg. `deprank/backend`
+    pub project: String,
+    /// The organization owning the workflow, if any
+    pub organization: Option<String>,
+    /// The commit hash which was analyzed, if the project was ranked
+    pub commit: Option<String>,
+    pub snapshot_id: Option<Uuid>,
+    pub profile: Option<String>,
+    pub dependencies: Vec<ReportDependency>,
+    pub signatures: Vec<ReportSignature>,
+    pub allocations: Vec<ReportAllocation>,
+    pub created_at: DateTime<Utc>,
+    pub generated_at: DateTime<Utc>,
+}
+
+pub struct ReportService;
+
+impl ReportService {
+    /// Gather the report of a workflow, from its snapshot, or the latest one of its
+    /// project, and its executed allocations.
+    pub async fn generate(ctx: Arc<Context>, id: Uuid) -> Result<WorkflowReport> {
+        let workflow = ctx.workflows.get(id).ok_or(ApiError::NotFoundWorkflow(id.to_string()))?;
+        let snapshot = match workflow.snapshot_id {
+            Some(snapshot_id) => Some(
+                ctx.snapshots
+                    .get(snapshot_id)
+                    .ok_or(ApiError::NotFoundSnapshot(snapshot_id.to_string()))?,
+            ),
+            None => ctx.snapshots.latest(&workflow.project),
+        };
+
+        let contract = &ctx.contract;
+        let signatures =
+            workflow.signatures.iter().map(|signature| signature_of(contract, signature)).collect();
+
+        let mut allocations: Vec<_> = ctx
+            .allocations
+            .list(id)
+            .into_iter()
+            .filter(|record| record.status == ExecutionStatus::Executed)
+            .collect();
+        allocations.sort_by(|a, b| a.id.cmp(&b.id));
+        let allocations =
+            allocations.iter().map(|record| allocation_of(contract, record)).collect();
+
+        Ok(WorkflowReport {
+            workflow_id: id,
+            project: workflow.project,
+            organization: workflow.organization,
+            commit: snapshot.as_ref().map(|snapshot| snapshot.commit.clone()),
+            snapshot_id: snapshot.as_ref().map(|snapshot| snapshot.id),
+            profile: snapshot.as_ref().map(|snapshot| snapshot.profile.clone()),
+            dependencies: snapshot.as_deref().map(dependencies_of).unwrap_or_default(),
+            signatures,
+            allocations,
+            created_at: workflow.created_at,
+            generated_at: Utc::now(),
+        })
+    }
+
+    /// Render a report as a standalone HTML page.
+    pub fn to_html(report: &WorkflowReport) -> String {
+        let mut html =
+            String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+        html += &format!("<title>DepRank report: {}</title>\n", escape(&report.project));
+        html += "<style>\
+            body{font-family:sans-serif;margin:2em auto;max-width:60em;color:#222}\
+            table{border-collapse:collapse;width:100%;margin-bottom:2em}\
+            th,td{border-bottom:1px solid #ddd;padding:.3em .6em;text-align:left}\
+            td.number{text-align:right}code{font-size:.9em}\
+            </style>\n</head>\n<body>\n";
+
+        html += &format!("<h1>DepRank report: {}</h1>\n<dl>\n", escape(&report.project));
+        for (term, description) in summary(report) {
+            html += &format!("<dt>{term}</dt><dd>{}</dd>\n", escape(&description));
+        }
+        html += "</dl>\n";
+
+        html += &format!("<h2>Ranked dependencies ({})</h2>\n", report.dependencies.len());
+        html += "<table>\n<tr><th>#</th><th>Dependency</th><th>Version</th>\
+                 <th>Ecosystem</th><th>License</th><th>Share</th></tr>\n";
+        for (rank, dependency) in report.dependencies.iter().enumerate() {
+            html += &format!(
+                "<tr><td class=\"number\">{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td>\
+                 <td class=\"number\">{:.2}%</td></tr>\n",
+                rank + 1,
+                escape(&dependency.name),
+                escape(&dependency.version),
+                dependency.ecosystem,
+                escape(dependency.license.as_deref().unwrap_or("-")),
+                dependency.share,
+            );
+        }
+        html += "</table>\n";
+
+        html += &format!("<h2>Signatures collected ({})</h2>\n", report.signatures.len());
+        html += "<table>\n<tr><th>Signer</th><th>Signed at</th><th>Transaction</th></tr>\n";
+        for signature in &report.signatures {
+            html += &format!(
+                "<tr><td><code>{}</code></td><td>{}</td><td>{}</td></tr>\n",
+                escape(&signature.signer),
+                signature.signed_at.to_rfc3339(),
+                link(&signature.tx_hash, Some(signature.tx_url.as_str())),
+            );
+        }
+        html += "</table>\n";
+
+        html += &format!("<h2>Allocations executed ({})</h2>\n", report.allocations.len());
+        html += "<table>\n<tr><th>Beneficiary</th><th>Recipient</th><th>Amount</th>\
+                 <th>Transaction</th></tr>\n";
+        for allocation in &report.allocations {
+            html += &format!(
+                "<tr><td>{}</td><td><code>{}</code></td><td class=\"number\">{}</td><td>{}</td></tr>\n",
+                escape(&allocation.beneficiary),
+                escape(&allocation.recipient),
+                escape(&allocation.amount),
+                allocation
+                    .tx_hash
+                    .as_deref()
+                    .map(|tx_hash| link(tx_hash, allocation.tx_url.as_deref()))
+                    .unwrap_or_else(|| "-".to_string()),
+            );
+        }
+        html += "</table>\n</body>\n</html>\n";
+        html
+    }
+
+    /// Render a report as a PDF document, in the standard Helvetica font.
+    pub fn to_pdf(report: &WorkflowReport) -> Vec<u8> {
+        let mut lines = vec![format!("DepRank report: {}", report.project), String::new()];
+        for (term, description) in summary(report) {
+            lines.push(format!("{term}: {description}"));
+        }
+
+        lines.push(String::new());
+        lines.push(format!("Ranked dependencies ({})", report.dependencies.len()));
+        for (rank, dependency) in report.dependencies.iter().enumerate() {
+            lines.push(format!(
+                "{:>4}. {} {} ({}, {}) {:.2}%",
+                rank + 1,
+                dependency.name,
+                dependency.version,
+                dependency.ecosystem,
+                dependency.license.as_deref().unwrap_or("no license"),
+                dependency.share,
+            ));
+        }
+
+        lines.push(String::new());
+        lines.push(format!("Signatures collected ({})", report.signatures.len()));
+        for signature in &report.signatures {
+            lines.push(format!("- {} at {}", signature.signer, signature.signed_at.to_rfc3339()));
+            lines.push(format!("  {}", signature.tx_url));
+        }
+
+        lines.push(String::new());
+        lines.push(format!("Allocations executed ({})", report.allocations.len()));
+        for allocation in &report.allocations {
+            lines.push(format!(
+                "- {} to {} ({})",
+                allocation.amount, allocation.beneficiary, allocation.recipient
+            ));
+            if let Some(url) = allocation.tx_url.as_deref().or(allocation.tx_hash.as_deref()) {
+                lines.push(format!("  {url}"));
+            }
+        }
+
+        render_pdf(&lines)
+    }
+}
+
+/// The ranked dependencies of a snapshot, the highest score first.
+fn dependencies_of(snapshot: &ProjectSnapshot) -> Vec<ReportDependency> {
+    let mut ranked: Vec<_> =
+        snapshot.dependencies.iter().filter(|dependency| !dependency.excluded_by_policy).collect();
+    ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    let total: f64 = ranked.iter().map(|dependency| dependency.score).sum();
+    ranked
+        .into_iter()
+        .map(|dependency| ReportDependency {
+            name: dependency.name.clone(),
+            version: dependency.version.clone(),
+            ecosystem: dependency.ecosystem.to_string(),
+            license: dependency.license.clone(),
+            share: if total > 0.0 { dependency.score / total * 100.0 } else { 0.0 },
+        })
+        .collect()
+}
+
+fn signature_of(contract: &ContractService, signature: &SignatureRecord) -> ReportSignature {
+    ReportSignature {
+        signer: signature.signer.clone(),
+        signed_at: signature.signed_at,
+        tx_hash: signature.tx_hash.clone(),
+        tx_url: contract.explorer_tx_url(&signature.tx_hash),
+    }
+}
+
+fn allocation_of(contract: &ContractService, record: &AllocationRecord) -> ReportAllocation {
+    let beneficiary = record
+        .contributor
+        .clone()
+        .or_else(|| {
+            record.package.as_ref().map(|package| format!("{}/{}", package.ecosystem, package.name))
+        })
+        .unwrap_or_else(|| record.recipient.to_string());
+    let amount = match (&record.token_amount, record.denomination) {
+        (Some(tokens), _) => format!("{tokens} {}", record.token),
+        (None, Denomination::Token) => format!("{} {}", record.amount, record.token),
+        (None, Denomination::Usd) => format!("{} USD", record.amount),
+    };
+
+    ReportAllocation {
+        beneficiary,
+        recipient: record.recipient.clone(),
+        amount,
+        tx_hash: record.tx_hash.clone(),
+        tx_url: record.tx_hash.as_deref().map(|tx_hash| contract.explorer_tx_url(tx_hash)),
+    }
+}
+
+/// The header fields of a report.
+fn summary(report: &WorkflowReport) -> Vec<(&'static str, String)> {
+    let or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+    vec![
+        ("Workflow", report.workflow_id.to_string()),
+        ("Organization", or_dash(report.organization.clone())),
+        ("Created at", report.created_at.to_rfc3339()),
+        ("Analyzed commit", or_dash(report.commit.clone())),
+        ("Ranking snapshot", or_dash(report.snapshot_id.map(|id| id.to_string()))),
+        ("Ranking profile", or_dash(report.profile.clone())),
+        ("Generated at", report.generated_at.to_rfc3339()),
+    ]
+}
+
+fn link(text: &str, url: Option<&str>) -> String {
+    match url {
+        Some(url) => format!("<a href=\"{}\"><code>{}</code></a>", escape(url), escape(text)),
+        None => format!("<code>{}</code>", escape(text)),
+    }
+}
+
+/// Escape the characters with a meaning in HTML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Lay out lines of text on as many pages as needed, wrapping the long ones.
+fn render_pdf(lines: &[String]) -> Vec<u8> {
+    let lines_per_page = ((PAGE_HEIGHT - 2 * PAGE_MARGIN) / LINE_HEIGHT) as usize;
+    let wrapped: Vec<String> = lines.iter().flat_map(|line| wrap(line)).collect();
+    let pages: Vec<_> = wrapped.chunks(lines_per_page.max(1)).collect();
+
+    // The catalog, the page tree and the font come first, then a page and its content
+    // stream for every page.
+    let page_ids: Vec<usize> = (0..pages.len()).map(|i| 4 + 2 * i).collect();
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            page_ids.iter().map(|id| format!("{id} 0 R")).collect::<Vec<_>>().join(" "),
+            pages.len()
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+    ];
+    for (page, id) in pages.iter().zip(&page_ids) {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {PAGE_WIDTH} {PAGE_HEIGHT}] \
+             /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            id + 1
+        ));
+
+        let mut content = format!(
+            "BT /F1 {FONT_SIZE} Tf {LINE_HEIGHT} TL {PAGE_MARGIN} {} Td\n",
+            PAGE_HEIGHT - PAGE_MARGIN
+        );
+        for line in page.iter() {
+            content += &format!("({}) '\n", pdf_string(line));
+        }
+        content += "ET";
+        objects.push(format!("<< /Length {} >>\nstream\n{content}\nendstream", content.len()));
+    }
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf += &format!("{} 0 obj\n{object}\nendobj\n", i + 1);
+    }
+    let xref = pdf.len();
+    pdf += &format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        pdf += &format!("{offset:010} 00000 n \n");
+    }
+    pdf += &format!(
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+        objects.len() + 1
+    );
+    pdf.into_bytes()
+}
+
+/// Split a line in chunks fitting the page width, indenting the continuations.
+fn wrap(line: &str) -> Vec<String> {
+    let chars: Vec<char> = line.chars().collect();
+    if chars.len() <= LINE_WIDTH {
+        return vec![line.to_string()];
+    }
+    let mut lines = vec![chars[..LINE_WIDTH].iter().collect::<String>()];
+    for chunk in chars[LINE_WIDTH..].chunks(LINE_WIDTH - 4) {
+        lines.push(format!("    {}", chunk.iter().collect::<String>()));
+    }
+    lines
+}
+
+/// Escape a PDF literal string, replacing the characters the standard fonts cannot show.
+fn pdf_string(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '(' | ')' | '\\' => format!("\\{c}"),
+            ' '..='~' => c.to_string(),
+            _ => "?".to_string(),
+        })
+        .collect()
+}
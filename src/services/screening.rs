@@ -0,0 +1,238 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Screening of dependencies for supply chain attacks.
+//!
+//! A dependency is flagged when its name is a near miss of a popular package of its
+//! ecosystem, when it resolves from another registry than the one of its ecosystem, as
+//! in dependency confusion, or when it does not point at a source code repository. Flags
+//! are informative, unless the policy of the owner excludes flagged dependencies.
+
+use crate::responses::dependency::{
+    DependencyFlagKind, DependencyFlagResponse, DependencyResponse, Ecosystem,
+};
+
+/// Names shorter than this are too close to too many others to be screened as typosquats.
+const MIN_TYPOSQUAT_LEN: usize = 5;
+
+/// Popular crates, the usual targets of typosquats.
+const POPULAR_CARGO: &[&str] = &[
+    "anyhow",
+    "async-trait",
+    "base64",
+    "bitflags",
+    "bytes",
+    "chrono",
+    "clap",
+    "crossbeam",
+    "futures",
+    "hyper",
+    "itertools",
+    "lazy_static",
+    "libc",
+    "once_cell",
+    "rand",
+    "regex",
+    "reqwest",
+    "serde",
+    "serde_json",
+    "syn",
+    "thiserror",
+    "tokio",
+    "tracing",
+    "uuid",
+];
+
+/// Popular npm packages, the usual targets of typosquats.
+const POPULAR_NPM: &[&str] = &[
+    "axios",
+    "chalk",
+    "commander",
+    "cross-env",
+    "debug",
+    "dotenv",
+    "express",
+    "lodash",
+    "moment",
+    "react",
+    "react-dom",
+    "request",
+    "typescript",
+    "webpack",
+];
+
+/// Popular PyPI packages, the usual targets of typosquats.
+const POPULAR_PYPI: &[&str] = &[
+    "boto3",
+    "cryptography",
+    "django",
+    "flask",
+    "numpy",
+    "pandas",
+    "requests",
+    "setuptools",
+    "urllib3",
+];
+
+/// Popular Maven artifacts, the usual targets of typosquats.
+const POPULAR_MAVEN: &[&str] = &[
+    "commons-io",
+    "commons-lang3",
+    "guava",
+    "jackson-databind",
+    "junit",
+    "log4j-core",
+    "slf4j-api",
+    "spring-core",
+];
+
+/// Popular packages which are near misses of other popular ones, legitimate on their own.
+const ALLOWED: &[(Ecosystem, &str)] = &[(Ecosystem::Npm, "preact")];
+
+/// The registries each ecosystem resolves packages from, as sources of the lock files.
+fn registries(ecosystem: Ecosystem) -> &'static [&'static str] {
+    match ecosystem {
+        Ecosystem::Cargo => &[
+            "registry+https://github.com/rust-lang/crates.io-index",
+            "sparse+https://index.crates.io/",
+        ],
+        Ecosystem::Npm => &["https://registry.npmjs.org/"],
+        Ecosystem::Pypi => &["https://pypi.org/", "https://files.pythonhosted.org/"],
+        Ecosystem::Maven => &["https://repo.maven.apache.org/", "https://repo1.maven.org/"],
+    }
+}
+
+fn popular(ecosystem: Ecosystem) -> &'static [&'static str] {
+    match ecosystem {
+        Ecosystem::Cargo => POPULAR_CARGO,
+        Ecosystem::Npm => POPULAR_NPM,
+        Ecosystem::Pypi => POPULAR_PYPI,
+        Ecosystem::Maven => POPULAR_MAVEN,
+    }
+}
+
+pub struct ScreeningService;
+
+impl ScreeningService {
+    /// Flag the suspicious dependencies, replacing their previous flags.
+    pub fn flag(dependencies: &mut [DependencyResponse]) {
+        for dependency in dependencies.iter_mut() {
+            dependency.flags = Self::screen(dependency);
+        }
+    }
+
+    /// Find why a dependency may be a supply chain attack.
+    pub fn screen(dependency: &DependencyResponse) -> Vec<DependencyFlagResponse> {
+        let mut flags = Vec::new();
+        if let Some(target) = typosquat(dependency.ecosystem, &dependency.name) {
+            flags.push(DependencyFlagResponse {
+                kind: DependencyFlagKind::Typosquat,
+                detail: format!("close to {target}"),
+            });
+        }
+        if let Some(registry) = dependency.registry.as_deref() {
+            let expected = registries(dependency.ecosystem);
+            // Git and path sources are pinned by the project, not resolved by name.
+            let pinned = registry.starts_with("git+") || registry.starts_with("path+");
+            if !pinned && !expected.iter().any(|expected| registry.starts_with(expected)) {
+                flags.push(DependencyFlagResponse {
+                    kind: DependencyFlagKind::UnexpectedRegistry,
+                    detail: format!("resolves from {registry}"),
+                });
+            }
+        }
+        if dependency.repository.as_deref().is_none_or(|repository| repository.trim().is_empty()) {
+            flags.push(DependencyFlagResponse {
+                kind: DependencyFlagKind::MissingRepository,
+                detail: "no source code repository".to_string(),
+            });
+        }
+        flags
+    }
+}
+
+/// The popular package a name is a near miss of, if any.
+fn typosquat(ecosystem: Ecosystem, name: &str) -> Option<&'static str> {
+    let name = match ecosystem {
+        // Maven names are `group:artifact`, and the popular ones are artifact ids
+        Ecosystem::Maven => name.rsplit(':').next().unwrap_or(name),
+        _ => name,
+    };
+    let name = normalize(name);
+    if name.chars().count() < MIN_TYPOSQUAT_LEN {
+        return None;
+    }
+    let allowed =
+        ALLOWED.iter().any(|&(of, package)| of == ecosystem && normalize(package) == name);
+    let popular = popular(ecosystem);
+    if allowed || popular.iter().any(|target| normalize(target) == name) {
+        return None;
+    }
+    popular.iter().copied().find(|target| distance(&normalize(target), &name) == 1)
+}
+
+/// Lowercase a name, registries treating `-`, `_` and `.` alike.
+fn normalize(name: &str) -> String {
+    name.to_lowercase().replace(['_', '.'], "-")
+}
+
+/// The optimal string alignment distance between two names, the number of characters
+/// to insert, delete, substitute or swap with their neighbor to turn one into the other.
+fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<_> = a.chars().collect();
+    let b: Vec<_> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best =
+                (rows[i - 1][j] + 1).min(rows[i][j - 1] + 1).min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn near_misses_are_typosquats() {
+        assert_eq!(typosquat(Ecosystem::Npm, "reactt"), Some("react"));
+        assert_eq!(typosquat(Ecosystem::Cargo, "sedre"), Some("serde"));
+        assert_eq!(typosquat(Ecosystem::Cargo, "serde"), None);
+    }
+
+    #[test]
+    fn allowed_packages_are_not_typosquats() {
+        assert_eq!(typosquat(Ecosystem::Npm, "preact"), None);
+    }
+
+    #[test]
+    fn maven_compares_artifact_ids() {
+        assert_eq!(typosquat(Ecosystem::Maven, "com.google.guava:guava"), None);
+        assert_eq!(typosquat(Ecosystem::Maven, "com.example:guavaa"), Some("guava"));
+        assert_eq!(typosquat(Ecosystem::Maven, "junit:junit"), None);
+    }
+}
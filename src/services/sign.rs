@@ -0,0 +1,102 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Signatures answering the inquires of workflows.
+//!
+//! The signer signs the hash of the inquire payload with their account, and only the
+//! hash of the signature is recorded on chain. The signature is checked by the signer
+//! account first, so a record always stands for a valid signature by its signer.
+
+use serde_json::json;
+use tracing::{info, instrument};
+
+use crate::{
+    context::Context,
+    contracts::{
+        hash::{inquire_hash, signature_hash},
+        inquire::InquireContract,
+        sign::SignContract,
+        types::{Address, Id},
+    },
+    errors::{ApiError, Result},
+    requests::organization::Role,
+    responses::{sign::SignResponse, webhook::WebhookEvent},
+    services::{organization::OrganizationService, webhook::WebhookService},
+};
+
+pub struct SignService;
+
+impl SignService {
+    /// Record the signature of an inquire on chain, once verified by the signer account.
+    #[instrument(skip_all, fields(%inquire_id, %signer))]
+    pub async fn record(
+        ctx: &Context,
+        inquire_id: Id,
+        signer: Address,
+        signature: Vec<String>,
+    ) -> Result<SignResponse> {
+        let inquire = ctx
+            .contract
+            .get_inquire_details(inquire_id.clone())
+            .await
+            .map_err(|e| ApiError::chain(e, ApiError::NotFoundInquire))?;
+
+        // Only the approvers of the organization owning the workflow answer its inquires.
+        let workflow = ctx.workflows.find_by_chain_id(&inquire.workflow_id);
+        let organization = workflow.as_ref().and_then(|workflow| workflow.organization.as_deref());
+        OrganizationService::authorize(ctx, organization, &signer, Role::Approver)?;
+
+        let message_hash = inquire_hash(&inquire_id, &inquire);
+        let valid = ctx
+            .contract
+            .is_valid_signature(signer.clone(), message_hash.clone(), signature.clone())
+            .await
+            .map_err(|e| ApiError::chain(e, ApiError::ChainRpcError))?;
+        if !valid {
+            return Err(ApiError::InvalidSignature(format!(
+                "not a signature of {message_hash} by {signer}"
+            )));
+        }
+
+        let signature_hash = signature_hash(&signature);
+        let sign_id = ctx
+            .contract
+            .create_sign(
+                inquire.workflow_id.clone(),
+                inquire_id.clone(),
+                signer.clone(),
+                signature_hash.clone(),
+            )
+            .await
+            .map_err(|e| ApiError::chain(e, ApiError::ChainRpcError))?;
+        info!(sign_id = sign_id.as_deref(), "Signature recorded");
+
+        let data = json!({
+            "workflow_id": inquire.workflow_id,
+            "inquire_id": inquire_id,
+            "signer": signer,
+            "sign_id": sign_id,
+            "signature_hash": signature_hash,
+        });
+        if let Some(workflow) = workflow {
+            WebhookService::publish(ctx, workflow.id, WebhookEvent::InquireResponded, data);
+        }
+
+        Ok(SignResponse {
+            sign_id: sign_id.map(Id::into_inner),
+            message_hash: message_hash.to_string(),
+            signature_hash: signature_hash.to_string(),
+        })
+    }
+}
@@ -0,0 +1,431 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Immutable snapshots of the ranking runs of projects.
+//!
+//! Every ranking run is kept with everything which determined its scores: the commit
+//! analyzed, the version of the ranking algorithm and the weights. Workflows reference
+//! a snapshot, so their allocation basis stays reproducible after later re-analyses.
+//!
+//! When enrichment signals of dependencies change, the latest snapshot of a project is
+//! rescored on its next read, recording a new snapshot of the same commit. The same goes
+//! for the recomputation of the contributions of a workflow with the current weights.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    analyzers::{AnalyzedDependency, ResolvedGraph},
+    context::Context,
+    db::{self, Database, Write},
+    errors::{ApiError, Result},
+    requests::profile::Weights,
+    responses::{
+        dependency::DependencyResponse,
+        snapshot::{
+            RankingDiffResponse, ScoreChangeResponse, SnapshotDetailResponse, SnapshotResponse,
+        },
+    },
+    services::{check::CheckRunService, profile::Signals, screening::ScreeningService},
+};
+
+/// The version of the ranking algorithm, bumped whenever the same input scores differently.
+pub const ALGORITHM_VERSION: &str = "2";
+
+/// A ranking run of a project, never modified once recorded.
+#[derive(Debug, Clone)]
+pub struct ProjectSnapshot {
+    pub id: Uuid,
+    /// The project, eg. `deprank/backend`
+    pub project: String,
+    /// The commit hash which was analyzed
+    pub commit: String,
+    pub algorithm_version: String,
+    /// The name of the weight profile, and its weights at the time of the run
+    pub profile: String,
+    pub weights: Weights,
+    pub created_at: DateTime<Utc>,
+    /// The ranked dependencies
+    pub dependencies: Vec<DependencyResponse>,
+    /// The dependency graph the analyzers resolved
+    pub graph: ResolvedGraph,
+}
+
+/// The snapshots by id, written through to the database if there is one.
+#[derive(Clone, Default)]
+pub struct SnapshotStore {
+    snapshots: Arc<Mutex<HashMap<Uuid, Arc<ProjectSnapshot>>>>,
+    db: Option<Database>,
+}
+
+impl SnapshotStore {
+    /// Load the snapshots from the database, in memory only without one.
+    pub async fn load(db: Option<&Database>) -> anyhow::Result<Self> {
+        let Some(db) = db else {
+            return Ok(Self::default());
+        };
+
+        let snapshots = db::snapshot::load(db.pool())
+            .await?
+            .into_iter()
+            .map(|snapshot| (snapshot.id, Arc::new(snapshot)))
+            .collect();
+        Ok(Self { snapshots: Arc::new(Mutex::new(snapshots)), db: Some(db.clone()) })
+    }
+
+    /// Record a snapshot, before any workflow references it.
+    fn insert(&self, snapshot: Arc<ProjectSnapshot>) {
+        let mut snapshots = self.snapshots.lock().unwrap();
+        if let Some(db) = &self.db {
+            db.write(Write::Snapshot(snapshot.clone()));
+        }
+        snapshots.insert(snapshot.id, snapshot);
+    }
+
+    pub fn get(&self, id: Uuid) -> Option<Arc<ProjectSnapshot>> {
+        self.snapshots.lock().unwrap().get(&id).cloned()
+    }
+
+    /// Get the snapshots of a project, the latest first.
+    pub fn list(&self, project: &str) -> Vec<Arc<ProjectSnapshot>> {
+        let project = project.to_lowercase();
+        let mut snapshots: Vec<_> = self
+            .snapshots
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|snapshot| snapshot.project == project)
+            .cloned()
+            .collect();
+        snapshots.sort_by_key(|snapshot| std::cmp::Reverse(snapshot.created_at));
+        snapshots
+    }
+
+    pub fn latest(&self, project: &str) -> Option<Arc<ProjectSnapshot>> {
+        self.list(project).into_iter().next()
+    }
+
+    /// Get the projects having snapshots.
+    pub fn projects(&self) -> BTreeSet<String> {
+        let snapshots = self.snapshots.lock().unwrap();
+        snapshots.values().map(|snapshot| snapshot.project.clone()).collect()
+    }
+}
+
+pub struct SnapshotService;
+
+impl SnapshotService {
+    /// Score the analyzed dependencies of a project with the weights and their current
+    /// enrichment signals, the scores summing to 1.
+    pub fn score(
+        ctx: &Context,
+        weights: &Weights,
+        analyzed: &[AnalyzedDependency],
+    ) -> Vec<DependencyResponse> {
+        let mut dependencies: Vec<_> = analyzed
+            .iter()
+            .map(|dependency| {
+                let enrichment = ctx.enrichments.get(&dependency.name);
+                let signals = Signals {
+                    depth: dependency.depth,
+                    kind: dependency.kind,
+                    downloads: enrichment.downloads,
+                    active_contributors: enrichment.active_contributors,
+                    vulnerabilities: enrichment.vulnerabilities,
+                };
+                DependencyResponse {
+                    explanation: Some(weights.explain(&signals)),
+                    ..dependency.to_response()
+                }
+            })
+            .collect();
+
+        let raw: f64 = dependencies
+            .iter()
+            .filter_map(|dependency| dependency.explanation.as_ref())
+            .map(|explanation| explanation.raw_score)
+            .sum();
+        if raw > 0.0 {
+            for dependency in &mut dependencies {
+                let raw_score = dependency.explanation.as_ref().map_or(0.0, |e| e.raw_score);
+                dependency.score = raw_score / raw;
+            }
+        }
+        dependencies
+    }
+
+    /// Record a ranking run of a project, screening its dependencies and applying the
+    /// dependency policy of its owner, add its ranked dependencies to the global graph
+    /// and publish its check run.
+    pub fn record(
+        ctx: &Context,
+        project: &str,
+        commit: &str,
+        profile: &str,
+        weights: Weights,
+        mut dependencies: Vec<DependencyResponse>,
+        graph: ResolvedGraph,
+    ) -> Arc<ProjectSnapshot> {
+        let project = project.to_lowercase();
+        let owner = project.split('/').next().unwrap_or_default();
+        ScreeningService::flag(&mut dependencies);
+        if let Some(policy) = ctx.policies.get(owner) {
+            policy.apply(&mut dependencies);
+        }
+        let ranked: Vec<_> = dependencies
+            .iter()
+            .filter(|dependency| !dependency.excluded_by_policy)
+            .cloned()
+            .collect();
+        ctx.graph.record(&project, &ranked);
+        ctx.enrichments.track(&project, &ranked);
+
+        let snapshot = Arc::new(ProjectSnapshot {
+            id: Uuid::new_v4(),
+            project,
+            commit: commit.to_string(),
+            algorithm_version: ALGORITHM_VERSION.to_string(),
+            profile: profile.to_string(),
+            weights,
+            created_at: Utc::now(),
+            dependencies,
+            graph,
+        });
+        ctx.snapshots.insert(snapshot.clone());
+        CheckRunService::publish(ctx, &snapshot);
+        snapshot
+    }
+
+    /// Get the latest snapshot of a project, first rescoring the dependencies whose
+    /// enrichment signals changed since it was recorded.
+    pub fn latest(ctx: &Context, project: &str) -> Option<Arc<ProjectSnapshot>> {
+        let project = project.to_lowercase();
+        let snapshot = ctx.snapshots.latest(&project)?;
+        let stale = ctx.enrichments.take_stale(&project);
+        if stale.is_empty() {
+            return Some(snapshot);
+        }
+
+        let Some(dependencies) = rescore(ctx, &snapshot, &snapshot.weights, Some(&stale)) else {
+            return Some(snapshot);
+        };
+        let snapshot = Arc::new(ProjectSnapshot {
+            id: Uuid::new_v4(),
+            created_at: Utc::now(),
+            dependencies,
+            ..snapshot.as_ref().clone()
+        });
+        ctx.snapshots.insert(snapshot.clone());
+        info!(%project, snapshot_id = %snapshot.id, rescored = stale.len(), "Snapshot rescored");
+        Some(snapshot)
+    }
+
+    /// Rescore every dependency of a snapshot with the given weights and the current
+    /// enrichment signals, recording the result as a new snapshot of the same commit.
+    /// Returns `None` when no score can be recomputed.
+    pub fn recompute(
+        ctx: &Context,
+        snapshot: &ProjectSnapshot,
+        weights: Weights,
+    ) -> Option<Arc<ProjectSnapshot>> {
+        let dependencies = rescore(ctx, snapshot, &weights, None)?;
+        let recomputed = Arc::new(ProjectSnapshot {
+            id: Uuid::new_v4(),
+            weights,
+            created_at: Utc::now(),
+            dependencies,
+            ..snapshot.clone()
+        });
+        ctx.snapshots.insert(recomputed.clone());
+        info!(
+            project = %snapshot.project,
+            from = %snapshot.id,
+            snapshot_id = %recomputed.id,
+            "Snapshot recomputed"
+        );
+        Some(recomputed)
+    }
+
+    pub async fn list(ctx: Arc<Context>, owner: &str, name: &str) -> Result<Vec<SnapshotResponse>> {
+        let project = format!("{owner}/{name}");
+        Ok(ctx.snapshots.list(&project).iter().map(|snapshot| to_response(snapshot)).collect())
+    }
+
+    pub async fn get(
+        ctx: Arc<Context>,
+        owner: &str,
+        name: &str,
+        id: Uuid,
+    ) -> Result<SnapshotDetailResponse> {
+        let project = format!("{owner}/{name}").to_lowercase();
+        match ctx.snapshots.get(id) {
+            Some(snapshot) if snapshot.project == project => Ok(SnapshotDetailResponse {
+                snapshot: to_response(&snapshot),
+                dependencies: snapshot.dependencies.clone(),
+            }),
+            _ => Err(ApiError::NotFoundSnapshot(id.to_string())),
+        }
+    }
+
+    /// Compare the dependencies and scores of two snapshots of the project, each referenced
+    /// by its id or by the commit hash it analyzed, eg. `4c59b707`.
+    pub async fn diff(
+        ctx: Arc<Context>,
+        owner: &str,
+        name: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<RankingDiffResponse> {
+        let project = format!("{owner}/{name}");
+        let from = resolve(&ctx, &project, from)?;
+        let to = resolve(&ctx, &project, to)?;
+        Ok(Self::compare(&from, &to))
+    }
+
+    /// Compare the dependencies and scores of two snapshots.
+    pub fn compare(from: &ProjectSnapshot, to: &ProjectSnapshot) -> RankingDiffResponse {
+        let before: HashMap<_, _> =
+            from.dependencies.iter().map(|dependency| (&dependency.name, dependency)).collect();
+        let after: HashMap<_, _> =
+            to.dependencies.iter().map(|dependency| (&dependency.name, dependency)).collect();
+
+        let added = to
+            .dependencies
+            .iter()
+            .filter(|dependency| !before.contains_key(&dependency.name))
+            .cloned()
+            .collect();
+        let removed = from
+            .dependencies
+            .iter()
+            .filter(|dependency| !after.contains_key(&dependency.name))
+            .cloned()
+            .collect();
+        let mut changed: Vec<_> = to
+            .dependencies
+            .iter()
+            .filter_map(|dependency| {
+                let previous = before.get(&dependency.name)?;
+                let unchanged =
+                    previous.version == dependency.version && previous.score == dependency.score;
+                (!unchanged).then(|| ScoreChangeResponse {
+                    name: dependency.name.clone(),
+                    from_version: previous.version.clone(),
+                    to_version: dependency.version.clone(),
+                    from_score: previous.score,
+                    to_score: dependency.score,
+                    delta: dependency.score - previous.score,
+                })
+            })
+            .collect();
+        changed.sort_by(|a, b| b.delta.abs().total_cmp(&a.delta.abs()));
+
+        RankingDiffResponse {
+            from: to_response(from),
+            to: to_response(to),
+            added,
+            removed,
+            changed,
+        }
+    }
+}
+
+/// Rescore the stale dependencies of a snapshot, all of them if `None`, with the given
+/// weights and their current enrichment signals, renormalizing the scores so they keep
+/// the same total. Returns `None` when no score can be recomputed, without an
+/// explanation of how it was computed.
+fn rescore(
+    ctx: &Context,
+    snapshot: &ProjectSnapshot,
+    weights: &Weights,
+    stale: Option<&BTreeSet<String>>,
+) -> Option<Vec<DependencyResponse>> {
+    let mut dependencies = snapshot.dependencies.clone();
+    let mut rescored = false;
+    for dependency in dependencies.iter_mut().filter(|dependency| !dependency.excluded_by_policy) {
+        let Some(explanation) = &dependency.explanation else {
+            continue;
+        };
+        if stale.is_some_and(|stale| !stale.contains(&dependency.name.to_lowercase())) {
+            continue;
+        }
+
+        let enrichment = ctx.enrichments.get(&dependency.name);
+        let signals = Signals {
+            depth: explanation.depth,
+            kind: dependency.kind,
+            downloads: enrichment.downloads,
+            active_contributors: enrichment.active_contributors,
+            vulnerabilities: enrichment.vulnerabilities,
+        };
+        dependency.explanation = Some(weights.explain(&signals));
+        rescored = true;
+    }
+    if !rescored {
+        return None;
+    }
+
+    let explained = || {
+        dependencies
+            .iter()
+            .filter(|dependency| !dependency.excluded_by_policy)
+            .filter_map(|dependency| Some((dependency.score, dependency.explanation.as_ref()?)))
+    };
+    let total: f64 = explained().map(|(score, _)| score).sum();
+    let raw: f64 = explained().map(|(_, explanation)| explanation.raw_score).sum();
+    if raw > 0.0 {
+        for dependency in
+            dependencies.iter_mut().filter(|dependency| !dependency.excluded_by_policy)
+        {
+            if let Some(explanation) = &dependency.explanation {
+                dependency.score = explanation.raw_score * total / raw;
+            }
+        }
+    }
+    Some(dependencies)
+}
+
+/// Find a snapshot of the project by id, or the latest one of a commit by hash prefix.
+fn resolve(ctx: &Context, project: &str, reference: &str) -> Result<Arc<ProjectSnapshot>> {
+    let project = project.to_lowercase();
+    let snapshot = match reference.parse::<Uuid>() {
+        Ok(id) => ctx.snapshots.get(id).filter(|snapshot| snapshot.project == project),
+        Err(_) if reference.len() >= 4 => {
+            let reference = reference.to_lowercase();
+            ctx.snapshots
+                .list(&project)
+                .into_iter()
+                .find(|snapshot| snapshot.commit.to_lowercase().starts_with(&reference))
+        }
+        Err(_) => None,
+    };
+    snapshot.ok_or_else(|| ApiError::NotFoundSnapshot(reference.to_string()))
+}
+
+fn to_response(snapshot: &ProjectSnapshot) -> SnapshotResponse {
+    SnapshotResponse {
+        id: snapshot.id,
+        project: snapshot.project.clone(),
+        commit: snapshot.commit.clone(),
+        algorithm_version: snapshot.algorithm_version.clone(),
+        profile: snapshot.profile.clone(),
+        weights: snapshot.weights.clone(),
+        created_at: snapshot.created_at,
+    }
+}
@@ -0,0 +1,294 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Index of the steps recorded on chain.
+//!
+//! Every step added to a dependency of a workflow attests a transaction: the receipt,
+//! inquiry, signature or allocation of the funding. The indexer worker follows the
+//! events of the Workflow contract into this index, so funding claims can be verified
+//! by transaction, by dependency or by workflow without parsing raw chain data nor
+//! calling the RPC node.
+//!
+//! The Receipt, Sign, Inquire and Allocation contracts emit no events, their entities are
+//! indexed through the steps referencing them. When a state file is configured, the index
+//! is saved after every run of the indexer and loaded at startup, so it resumes from the
+//! last indexed block instead of the first one.
+
+use std::{
+    collections::HashMap,
+    io::ErrorKind,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Context as _;
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::{
+    context::Context,
+    contracts::{
+        types::{Id, Owner},
+        workflow::{EmittedWorkflowEvent, WorkflowEvent},
+        Contract,
+    },
+    errors::{ApiError, Result},
+    responses::step::{ChainedStepResponse, StepResponse, TransactionChainResponse},
+};
+
+/// The dependency of a workflow, by owner, workflow id and dependency index.
+type DependencyKey = (Owner, Id, Id);
+
+#[derive(Debug, Clone)]
+struct IndexedDependency {
+    name: String,
+    repository_url: String,
+}
+
+#[derive(Debug, Default)]
+struct Index {
+    dependencies: HashMap<DependencyKey, IndexedDependency>,
+    /// The indexed events, in the order they were emitted
+    events: Vec<EmittedWorkflowEvent>,
+    /// The first block which is not indexed yet
+    next_block: Option<u64>,
+}
+
+/// The index as saved in the state file, its events indexed again when it is loaded.
+#[derive(Serialize, Deserialize)]
+struct SavedIndex {
+    next_block: Option<u64>,
+    events: Vec<EmittedWorkflowEvent>,
+}
+
+/// The indexed events of the Workflow contract, in memory.
+#[derive(Clone, Default)]
+pub struct StepIndex {
+    index: Arc<Mutex<Index>>,
+}
+
+impl StepIndex {
+    /// Load the index saved in a state file, empty if the file does not exist yet.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let saved: SavedIndex = match std::fs::read(path) {
+            Ok(content) => serde_json::from_slice(&content)
+                .with_context(|| format!("Malformed step index `{}`", path.display()))?,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e).context(format!("Failed to read `{}`", path.display())),
+        };
+
+        let index = Self::default();
+        if let Some(next_block) = saved.next_block {
+            index.extend(saved.events, next_block.saturating_sub(1));
+        }
+        Ok(index)
+    }
+
+    /// Save the index in a state file.
+    pub async fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let content = {
+            let index = self.index.lock().unwrap();
+            serde_json::to_vec(&SavedIndex {
+                next_block: index.next_block,
+                events: index.events.clone(),
+            })?
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).await?;
+        }
+        // A crash while saving leaves the previous state file intact
+        let partial = path.with_extension("partial");
+        fs::write(&partial, content).await?;
+        fs::rename(&partial, path).await?;
+        Ok(())
+    }
+
+    /// The first block which is not indexed yet, if any was.
+    pub fn next_block(&self) -> Option<u64> {
+        self.index.lock().unwrap().next_block
+    }
+
+    /// Index the events of a range of blocks, the next range starting after `to_block`.
+    pub fn extend(&self, events: Vec<EmittedWorkflowEvent>, to_block: u64) {
+        let mut index = self.index.lock().unwrap();
+        for emitted in events {
+            match &emitted.event {
+                WorkflowEvent::DependencyCreated {
+                    github_owner,
+                    workflow_id,
+                    dependency_idx,
+                    name,
+                    repository_url,
+                } => {
+                    index.dependencies.insert(
+                        (github_owner.clone(), workflow_id.clone(), dependency_idx.clone()),
+                        IndexedDependency {
+                            name: name.clone(),
+                            repository_url: repository_url.clone(),
+                        },
+                    );
+                }
+                WorkflowEvent::StepAdded { .. } => {}
+            }
+            index.events.push(emitted);
+        }
+        index.next_block = Some(to_block + 1);
+    }
+
+    /// The steps matching a filter, in the order they were recorded.
+    fn find(&self, filter: impl Fn(&StepResponse) -> bool) -> Vec<StepResponse> {
+        let index = self.index.lock().unwrap();
+        index
+            .events
+            .iter()
+            .filter_map(|emitted| to_response(&index, emitted))
+            .filter(|step| filter(step))
+            .collect()
+    }
+}
+
+pub struct StepService;
+
+impl StepService {
+    /// Find the steps attesting a transaction, recorded for a dependency, matched by its
+    /// name or repository, or recorded for a workflow, by its owner and id on chain.
+    pub async fn find(
+        ctx: Arc<Context>,
+        tx_hash: Option<&str>,
+        dependency: Option<&str>,
+        workflow: Option<(&str, &str)>,
+    ) -> Result<Vec<StepResponse>> {
+        if tx_hash.is_none() && dependency.is_none() && workflow.is_none() {
+            return Err(ApiError::BadStepRequest(
+                "`tx_hash`, `dependency` or `github_owner` and `workflow_id` are required"
+                    .to_string(),
+            ));
+        }
+        let tx_hash = tx_hash.map(normalize_hash).transpose()?;
+        let dependency = dependency.map(|dependency| dependency.trim().to_lowercase());
+
+        let steps = ctx.steps.find(|step| {
+            let tx_matches = tx_hash.as_ref().is_none_or(|hash| {
+                normalize_hash(&step.tx_hash).is_ok_and(|step_hash| &step_hash == hash)
+            });
+            let dependency_matches = dependency.as_ref().is_none_or(|dependency| {
+                step.dependency.as_deref().is_some_and(|name| name.to_lowercase() == *dependency)
+                    || step.repository_url.as_deref().is_some_and(|url| {
+                        url.trim_end_matches('/').to_lowercase() == dependency.trim_end_matches('/')
+                    })
+            });
+            let workflow_matches = workflow.is_none_or(|(github_owner, workflow_id)| {
+                step.github_owner == github_owner && step.workflow_id == workflow_id
+            });
+            tx_matches && dependency_matches && workflow_matches
+        });
+
+        Ok(steps
+            .into_iter()
+            .map(|step| StepResponse {
+                tx_url: Some(ctx.contract.explorer_tx_url(&step.tx_hash)),
+                recorded_in_url: Some(ctx.contract.explorer_tx_url(&step.recorded_in)),
+                ..step
+            })
+            .collect())
+    }
+
+    /// Read the transaction chain of a dependency from the Workflow contract, with every
+    /// step checked to link to the previous one.
+    pub async fn chain(
+        ctx: &Context,
+        github_owner: Owner,
+        workflow_id: Id,
+        dependency_index: Id,
+    ) -> Result<TransactionChainResponse> {
+        let chain = ctx
+            .contract
+            .transaction_chain(github_owner.clone(), workflow_id.clone(), dependency_index.clone())
+            .await
+            .map_err(|e| ApiError::chain(e, ApiError::ChainRpcError))?;
+
+        let steps = chain
+            .steps
+            .into_iter()
+            .map(|chained| ChainedStepResponse {
+                step_index: chained.index.to_string(),
+                prev_step_index: chained.step.prev_step_index.to_string(),
+                kind: chained.step.step_type.into(),
+                tx_url: ctx.contract.explorer_tx_url(&chained.step.tx_hash),
+                tx_hash: chained.step.tx_hash.to_string(),
+                related_entity_id: chained.step.related_entity_id.to_string(),
+                recorded_at: DateTime::from_timestamp(chained.step.timestamp as i64, 0)
+                    .unwrap_or_default(),
+                linked: chained.linked,
+            })
+            .collect();
+        Ok(TransactionChainResponse {
+            github_owner: github_owner.to_string(),
+            workflow_id: workflow_id.to_string(),
+            dependency_index: dependency_index.to_string(),
+            steps,
+            verified: chain.verified,
+        })
+    }
+}
+
+/// Strip the leading zeros of a transaction hash, which may be omitted.
+fn normalize_hash(hash: &str) -> Result<String> {
+    let digits = hash.trim().to_lowercase();
+    let digits = digits.strip_prefix("0x").unwrap_or(&digits);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(ApiError::BadStepRequest(format!("invalid transaction hash `{hash}`")));
+    }
+    Ok(format!("0x{}", digits.trim_start_matches('0')))
+}
+
+fn to_response(index: &Index, emitted: &EmittedWorkflowEvent) -> Option<StepResponse> {
+    let WorkflowEvent::StepAdded {
+        github_owner,
+        workflow_id,
+        dependency_idx,
+        step_index,
+        step_type,
+        tx_hash,
+        related_entity_id,
+        timestamp,
+    } = &emitted.event
+    else {
+        return None;
+    };
+    let dependency = index.dependencies.get(&(
+        github_owner.clone(),
+        workflow_id.clone(),
+        dependency_idx.clone(),
+    ));
+
+    Some(StepResponse {
+        github_owner: github_owner.to_string(),
+        workflow_id: workflow_id.to_string(),
+        dependency_index: dependency_idx.to_string(),
+        dependency: dependency.map(|dependency| dependency.name.clone()),
+        repository_url: dependency.map(|dependency| dependency.repository_url.clone()),
+        step_index: step_index.to_string(),
+        kind: (*step_type).into(),
+        tx_hash: tx_hash.to_string(),
+        tx_url: None,
+        related_entity_id: related_entity_id.to_string(),
+        recorded_at: DateTime::from_timestamp(*timestamp as i64, 0).unwrap_or_default(),
+        block_number: emitted.block_number,
+        recorded_in: emitted.transaction_hash.to_string(),
+        recorded_in_url: None,
+    })
+}
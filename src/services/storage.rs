@@ -22,16 +22,52 @@ use octocrab::{
     Octocrab,
 };
 use std::{
+    io,
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 use tar::Archive;
 use thiserror::Error;
-use tokio::fs;
-use tracing::{debug, info};
+use tokio::{
+    fs,
+    sync::{OwnedSemaphorePermit, Semaphore},
+    time,
+};
+use tracing::{debug, info, warn};
 
 type Result<T, E = StorageError> = std::result::Result<T, E>;
 
+/// Extensions of binary files, never unpacked since no dependency is used from them.
+///
+/// The tarballs of GitHub hold the pointers of the Git LFS objects, not their content, so
+/// these also leave out the pointers of the binaries tracked by Git LFS.
+const SKIPPED_EXTENSIONS: [&str; 38] = [
+    "7z", "a", "avi", "bin", "bmp", "bz2", "class", "dll", "dmg", "dylib", "exe", "gif", "gz",
+    "ico", "iso", "jar", "jpeg", "jpg", "lib", "mov", "mp3", "mp4", "o", "otf", "pdf", "png",
+    "psd", "pyc", "rar", "so", "tgz", "ttf", "war", "wasm", "webp", "woff", "woff2", "zip",
+];
+
+/// The manifests and lock files kept when a checkout is pruned.
+const MANIFESTS: [&str; 13] = [
+    "Cargo.toml",
+    "Cargo.lock",
+    "Scarb.toml",
+    "Scarb.lock",
+    "package.json",
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "go.mod",
+    "go.sum",
+    "pyproject.toml",
+    "poetry.lock",
+    "requirements.txt",
+];
+
+/// Marks a checkout pruned down to its manifests, so it is downloaded again when needed.
+const PRUNED_MARKER: &str = ".deprank-pruned";
+
 #[derive(Error, Debug)]
 pub enum StorageError {
     #[error("Failed to create GitHub client")]
@@ -46,6 +82,9 @@ pub enum StorageError {
     #[error("Invalid reference type")]
     InvalidReferenceType,
 
+    #[error("Invalid revision {0}, expected a commit hash")]
+    InvalidRevision(String),
+
     #[error("No default branch found")]
     NoDefaultBranch,
 
@@ -60,12 +99,97 @@ pub enum StorageError {
 
     #[error("Failed to unpack tarball")]
     UnpackTarball(#[source] std::io::Error),
+
+    #[error("Failed to remove the pruned checkout")]
+    RemovePruned(#[source] std::io::Error),
+
+    #[error("The repository is {} MB, larger than {} MB", .size.div_ceil(1024), .max / 1024)]
+    RepoTooLarge { size: u64, max: u64 },
+
+    #[error("{0} repository downloads in progress, none finished within {1} seconds")]
+    DownloadQueueTimeout(usize, u64),
+}
+
+/// What to check out of a repository.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Revision {
+    /// The head of the default branch
+    #[default]
+    DefaultBranch,
+    /// The head of a branch
+    Branch(String),
+    /// A lightweight tag, pointing to a commit
+    Tag(String),
+    /// A commit, by its hash of at least 7 digits
+    Commit(String),
+}
+
+/// What is left of a repository checkout once expired.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CheckoutCleanup {
+    /// Only the manifests and lock files, describing the dependencies of the repository
+    Manifests,
+    /// Nothing, the checkout is deleted
+    Delete,
+}
+
+#[derive(Clone, clap::Parser)]
+pub struct StorageConfig {
+    /// Maximum number of repositories downloaded at the same time.
+    #[clap(long, env = "DRK_DOWNLOAD_CONCURRENCY", default_value_t = 4)]
+    pub download_concurrency: usize,
+
+    /// Seconds a repository download waits for a free slot before it is refused.
+    #[clap(long, env = "DRK_DOWNLOAD_TIMEOUT", default_value_t = 60)]
+    pub download_timeout: u64,
+
+    /// Maximum size of an unpacked file in kilobytes, larger files of a repository are skipped.
+    #[clap(long, env = "DRK_MAX_FILE_SIZE", default_value_t = 1024)]
+    pub max_file_size: u64,
+}
+
+/// Limits the repositories downloaded at the same time, shared by every storage service,
+/// so a burst of workflow creations cannot saturate the disk and the network.
+///
+/// Excess downloads queue until a download finishes, and are refused after the timeout.
+#[derive(Clone)]
+pub struct DownloadLimiter {
+    semaphore: Arc<Semaphore>,
+    concurrency: usize,
+    timeout: Duration,
+}
+
+impl DownloadLimiter {
+    pub fn new(config: &StorageConfig) -> Self {
+        let concurrency = config.download_concurrency.max(1);
+        Self {
+            semaphore: Arc::new(Semaphore::new(concurrency)),
+            concurrency,
+            timeout: Duration::from_secs(config.download_timeout),
+        }
+    }
+
+    /// Wait for a download slot, released when the permit is dropped.
+    pub async fn acquire(&self) -> Result<OwnedSemaphorePermit> {
+        let permit = time::timeout(self.timeout, self.semaphore.clone().acquire_owned()).await;
+        match permit {
+            Ok(Ok(permit)) => Ok(permit),
+            // The semaphore is never closed.
+            Ok(Err(_)) | Err(_) => {
+                warn!("No repository download slot freed within {:?}", self.timeout);
+                Err(StorageError::DownloadQueueTimeout(self.concurrency, self.timeout.as_secs()))
+            }
+        }
+    }
 }
 
 // Service for downloading and caching GitHub repositories
 pub struct StorageService {
-    cache_dir: PathBuf,      // Base directory for storing cached repositories
-    octocrab: Arc<Octocrab>, // GitHub API client
+    cache_dir: PathBuf,               // Base directory for storing cached repositories
+    octocrab: Arc<Octocrab>,          // GitHub API client
+    max_size: Option<u64>,            // Maximum size of a repository in kilobytes
+    max_file_size: Option<u64>,       // Maximum size of an unpacked file in kilobytes
+    limiter: Option<DownloadLimiter>, // Limit of the downloads in progress
 }
 
 impl StorageService {
@@ -81,47 +205,107 @@ impl StorageService {
             None => octocrab::instance(),
         };
 
-        Ok(Self { cache_dir: cache_dir.to_path_buf(), octocrab })
+        Ok(Self {
+            cache_dir: cache_dir.to_path_buf(),
+            octocrab,
+            max_size: None,
+            max_file_size: None,
+            limiter: None,
+        })
+    }
+
+    // Creates new StorageService with a GitHub client, eg. of the App installation granted
+    // a private repository
+    pub fn with_client(cache_dir: &Path, octocrab: Arc<Octocrab>) -> Self {
+        Self {
+            cache_dir: cache_dir.to_path_buf(),
+            octocrab,
+            max_size: None,
+            max_file_size: None,
+            limiter: None,
+        }
+    }
+
+    // Refuses repositories larger than the given size in kilobytes, eg. per the plan limits
+    pub fn with_max_size(mut self, max_size: Option<u64>) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    // Skips the files larger than the given size in kilobytes, eg. generated or binary data
+    pub fn with_max_file_size(mut self, max_file_size: Option<u64>) -> Self {
+        self.max_file_size = max_file_size;
+        self
+    }
+
+    // Queues the downloads behind the given limiter, eg. the one of the context
+    pub fn with_limiter(mut self, limiter: DownloadLimiter) -> Self {
+        self.limiter = Some(limiter);
+        self
     }
 
-    /// Download and store GitHub repository,
-    /// and return the path of the cached directory.
-    pub async fn fetch(&self, url: &str) -> Result<PathBuf> {
+    /// Download and store a revision of a GitHub repository, and return the path of the
+    /// cached directory, relative to the cache directory, and the commit checked out.
+    pub async fn fetch(&self, url: &str, revision: &Revision) -> Result<(PathBuf, String)> {
         let repo = GHRepo::from_url(url).map_err(StorageError::InvalidRepoUrl)?;
         let api = self.octocrab.repos(repo.owner(), repo.name());
 
         info!("Fetching the repository info {}", repo);
         let repository = api.get().await.map_err(StorageError::FetchRepoInfo)?;
+        if let (Some(size), Some(max)) = (repository.size, self.max_size) {
+            if u64::from(size) > max {
+                return Err(StorageError::RepoTooLarge { size: size.into(), max });
+            }
+        }
 
-        let reference = match repository.default_branch {
-            Some(branch) => {
-                let reference = api
-                    .get_ref(&Reference::Branch(branch.to_string()))
-                    .await
-                    .map_err(StorageError::FetchRepoInfo)?;
-                match reference.object {
-                    Object::Commit { sha, .. } => sha,
-                    _ => return Err(StorageError::InvalidReferenceType),
+        let reference = match revision {
+            Revision::DefaultBranch => match repository.default_branch {
+                Some(branch) => Reference::Branch(branch),
+                None => return Err(StorageError::NoDefaultBranch),
+            },
+            Revision::Branch(branch) => Reference::Branch(branch.clone()),
+            Revision::Tag(tag) => Reference::Tag(tag.clone()),
+            Revision::Commit(commit) => {
+                if commit.len() < 7 || !commit.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(StorageError::InvalidRevision(commit.clone()));
                 }
+                let commit = commit.to_lowercase();
+                info!("Downloading repository {} at {}", repo, commit);
+                let dir = self.download(repo.owner(), repo.name(), &commit).await?;
+                return Ok((dir, commit));
             }
-            None => return Err(StorageError::NoDefaultBranch),
+        };
+        let reference = api.get_ref(&reference).await.map_err(StorageError::FetchRepoInfo)?;
+        let commit = match reference.object {
+            Object::Commit { sha, .. } => sha,
+            _ => return Err(StorageError::InvalidReferenceType),
         };
 
         info!("Downloading repository {}", repo);
-        let dir = self.download(repo.owner(), repo.name(), &reference).await?;
+        let dir = self.download(repo.owner(), repo.name(), &commit).await?;
 
-        Ok(dir)
+        Ok((dir, commit))
     }
 
     // Downloads and extracts GitHub repository tarball to cache directory
     async fn download(&self, owner: &str, repo: &str, reference: &str) -> Result<PathBuf> {
         let dir = PathBuf::from(format!("{}-{}-{}", owner, repo, &reference[..7]));
 
-        if self.cache_dir.join(&dir).exists() {
+        let checkout = self.cache_dir.join(&dir);
+        if checkout.join(PRUNED_MARKER).exists() {
+            info!("Repository {} (commit {}) pruned, downloading it again", repo, reference);
+            fs::remove_dir_all(&checkout).await.map_err(StorageError::RemovePruned)?;
+        } else if checkout.exists() {
             info!("Repository {} (commit {}) already cached", repo, reference);
             return Ok(dir);
         }
 
+        // Held until the tarball is unpacked
+        let _permit = match &self.limiter {
+            Some(limiter) => Some(limiter.acquire().await?),
+            None => None,
+        };
+
         debug!("Downloading tarball for {}/{} (commit {})", owner, repo, reference);
         let tarball = self
             .octocrab
@@ -148,14 +332,93 @@ impl StorageService {
         Ok(dir)
     }
 
-    /// Unarchive the tarball data to the caches directory
+    /// Unarchive the tarball data to the caches directory,
+    /// skipping the binary files and the files larger than the maximum file size.
     fn unarchive(&self, bytes: &[u8]) -> Result<()> {
         debug!("Unpacking tarball...");
 
         let tar = GzDecoder::new(bytes);
         let mut archive = Archive::new(tar);
-        archive.unpack(&self.cache_dir).map_err(StorageError::UnpackTarball)?;
+        // The checkouts expire from the time they are unpacked
+        archive.set_preserve_mtime(false);
+        let mut skipped = 0;
+        for entry in archive.entries().map_err(StorageError::UnpackTarball)? {
+            let mut entry = entry.map_err(StorageError::UnpackTarball)?;
+            let path = entry.path().map_err(StorageError::UnpackTarball)?.into_owned();
+            if entry.header().entry_type().is_file() && self.is_skipped(&path, entry.size()) {
+                skipped += 1;
+                continue;
+            }
+            entry.unpack_in(&self.cache_dir).map_err(StorageError::UnpackTarball)?;
+        }
+        debug!("Skipped {} binary or large files", skipped);
 
         Ok(())
     }
+
+    /// Prune the checkouts of the cache directory downloaded before the retention,
+    /// and return the number of pruned checkouts.
+    pub fn prune(
+        cache_dir: &Path,
+        retention: Duration,
+        cleanup: CheckoutCleanup,
+    ) -> io::Result<usize> {
+        if !cache_dir.exists() {
+            return Ok(0);
+        }
+
+        let mut pruned = 0;
+        for entry in std::fs::read_dir(cache_dir)? {
+            let entry = entry?;
+            let checkout = entry.path();
+            let metadata = entry.metadata()?;
+            let age = metadata.modified()?.elapsed().unwrap_or_default();
+            // Hidden directories are caches kept beside the checkouts
+            let hidden = entry.file_name().to_str().is_some_and(|name| name.starts_with('.'));
+            if !metadata.is_dir() || hidden || age < retention {
+                continue;
+            }
+
+            match cleanup {
+                CheckoutCleanup::Delete => std::fs::remove_dir_all(&checkout)?,
+                CheckoutCleanup::Manifests if checkout.join(PRUNED_MARKER).exists() => continue,
+                CheckoutCleanup::Manifests => {
+                    keep_manifests(&checkout)?;
+                    std::fs::write(checkout.join(PRUNED_MARKER), "")?;
+                }
+            }
+            debug!("Pruned checkout {:?}", checkout);
+            pruned += 1;
+        }
+
+        Ok(pruned)
+    }
+
+    /// Check if the file is binary or larger than the maximum file size
+    fn is_skipped(&self, path: &Path, size: u64) -> bool {
+        let binary = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| SKIPPED_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()));
+        binary || self.max_file_size.is_some_and(|max| size > max * 1024)
+    }
+}
+
+/// Remove the files of a directory but the manifests and lock files, and the directories
+/// left empty.
+fn keep_manifests(dir: &Path) -> io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        // Symbolic links are removed, never followed
+        if entry.file_type()?.is_dir() {
+            keep_manifests(&path)?;
+            if std::fs::read_dir(&path)?.next().is_none() {
+                std::fs::remove_dir(&path)?;
+            }
+        } else if !MANIFESTS.contains(&entry.file_name().to_str().unwrap_or_default()) {
+            std::fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
 }
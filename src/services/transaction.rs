@@ -0,0 +1,227 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The journal of the transactions sent on chain.
+//!
+//! Every transaction the backend sends is recorded with its calls, its hash and its status,
+//! so operators can audit what was sent. When a journal file is configured every change is
+//! appended to it as a JSON line, and the journal is read back from it at startup.
+
+use std::{
+    fs::OpenOptions,
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Context as _;
+use chrono::Utc;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    contracts::{
+        transaction::TransactionStatus,
+        types::{Hash, RawCall},
+        Contract,
+    },
+    errors::Result,
+    middlewares::{ratelimit::ClientKey, trace::RequestId},
+    requests::list::ListParams,
+    responses::{
+        list::ListResponse,
+        transaction::{JournalStatus, TransactionCallResponse, TransactionResponse},
+    },
+};
+
+/// The number of calldata items shown in a call summary.
+const CALLDATA_SUMMARY_ITEMS: usize = 3;
+
+#[derive(Clone, clap::Parser)]
+pub struct JournalConfig {
+    /// Path of the transaction journal, appended to on every transaction, in memory if unset
+    #[clap(long, env = "DRK_TX_JOURNAL_PATH")]
+    pub tx_journal_path: Option<PathBuf>,
+}
+
+/// The journal of the transactions sent on chain, oldest first.
+#[derive(Clone, Default)]
+pub struct TransactionJournal {
+    entries: Arc<Mutex<Vec<TransactionResponse>>>,
+    path: Option<PathBuf>,
+}
+
+impl TransactionJournal {
+    /// Load the journal from its file, empty if the file does not exist yet.
+    pub fn load(config: &JournalConfig) -> anyhow::Result<Self> {
+        let Some(path) = &config.tx_journal_path else {
+            return Ok(Self::default());
+        };
+
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e).context(format!("Failed to read `{}`", path.display())),
+        };
+
+        // The entries are appended on every change, the last line of an entry being current.
+        let mut entries: Vec<TransactionResponse> = Vec::new();
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            let entry: TransactionResponse = match serde_json::from_str(line) {
+                Ok(entry) => entry,
+                // A crash while appending leaves a truncated last line.
+                Err(e) => {
+                    warn!("Skipping a malformed line of `{}`: {e}", path.display());
+                    continue;
+                }
+            };
+            match entries.iter_mut().find(|existing| existing.id == entry.id) {
+                Some(existing) => *existing = entry,
+                None => entries.push(entry),
+            }
+        }
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        Ok(Self { entries: Arc::new(Mutex::new(entries)), path: Some(path.clone()) })
+    }
+
+    /// Record a transaction sending `calls`, accepted by the node.
+    pub fn submitted(&self, calls: &[RawCall], tx_hash: Hash) {
+        self.push(calls, Some(tx_hash), JournalStatus::Submitted, None);
+    }
+
+    /// Record a transaction sending `calls`, simulated in dry-run mode instead of being sent.
+    pub fn simulated(&self, calls: &[RawCall], tx_hash: Hash) {
+        self.push(calls, Some(tx_hash), JournalStatus::Simulated, None);
+    }
+
+    /// Record a transaction sending `calls` which could not be sent.
+    pub fn failed(&self, calls: &[RawCall], error: &anyhow::Error) {
+        self.push(calls, None, JournalStatus::Failed, Some(format!("{error:#}")));
+    }
+
+    /// Record the status of a submitted transaction, once it is included in a block.
+    pub fn update(&self, tx_hash: &str, status: &TransactionStatus) {
+        let (status, error) = match status {
+            TransactionStatus::Succeeded => (JournalStatus::Succeeded, None),
+            TransactionStatus::Reverted(reason) => (JournalStatus::Reverted, Some(reason.clone())),
+            TransactionStatus::Pending | TransactionStatus::NotFound => return,
+        };
+
+        let updated = {
+            let mut entries = self.entries.lock().unwrap();
+            let Some(entry) = entries
+                .iter_mut()
+                .rev()
+                .find(|entry| entry.tx_hash.as_deref() == Some(tx_hash))
+                .filter(|entry| entry.status != status && entry.status != JournalStatus::Simulated)
+            else {
+                return;
+            };
+            entry.status = status;
+            entry.error = error;
+            entry.updated_at = Utc::now();
+            entry.clone()
+        };
+        self.append(&updated);
+    }
+
+    /// Every journaled transaction, oldest first.
+    pub fn all(&self) -> Vec<TransactionResponse> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    fn push(
+        &self,
+        calls: &[RawCall],
+        tx_hash: Option<Hash>,
+        status: JournalStatus,
+        error: Option<String>,
+    ) {
+        let now = Utc::now();
+        let entry = TransactionResponse {
+            id: Uuid::new_v4(),
+            calls: calls.iter().map(to_call_response).collect(),
+            tx_hash: tx_hash.map(String::from),
+            tx_url: None,
+            status,
+            error,
+            created_at: now,
+            updated_at: now,
+        };
+
+        self.append(&entry);
+        self.entries.lock().unwrap().push(entry);
+    }
+
+    /// Append an entry to the journal file, if any.
+    fn append(&self, entry: &TransactionResponse) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        if let Err(e) = append_line(path, entry) {
+            warn!("Failed to write the transaction journal `{}`: {e:#}", path.display());
+        }
+    }
+}
+
+fn append_line(path: &Path, entry: &TransactionResponse) -> anyhow::Result<()> {
+    let mut line = serde_json::to_vec(entry)?;
+    line.push(b'\n');
+    OpenOptions::new().create(true).append(true).open(path)?.write_all(&line)?;
+    Ok(())
+}
+
+fn to_call_response(call: &RawCall) -> TransactionCallResponse {
+    let mut calldata: Vec<&str> =
+        call.calldata.iter().take(CALLDATA_SUMMARY_ITEMS).map(String::as_str).collect();
+    if call.calldata.len() > CALLDATA_SUMMARY_ITEMS {
+        calldata.push("…");
+    }
+    let count = format!("({} items)", call.calldata.len());
+    TransactionCallResponse {
+        to: call.to.to_string(),
+        selector: call.selector.to_string(),
+        calldata: if calldata.is_empty() {
+            count
+        } else {
+            format!("{} {count}", calldata.join(", "))
+        },
+    }
+}
+
+pub struct TransactionService;
+
+impl TransactionService {
+    /// List the journaled transactions, restricted to the admin key.
+    pub async fn list(
+        ctx: Arc<Context>,
+        key: &ClientKey,
+        params: &ListParams,
+        request_id: &RequestId,
+    ) -> Result<ListResponse<TransactionResponse>> {
+        key.authorize_admin(&ctx.config.admin_api_key)?;
+        params.validate()?;
+
+        let entries = ctx.contract.journal().all().into_iter().map(|entry| TransactionResponse {
+            tx_url: entry.tx_hash.as_deref().map(|tx_hash| ctx.contract.explorer_tx_url(tx_hash)),
+            ..entry
+        });
+        let (items, pagination) = params.apply(entries.collect())?;
+        Ok(ListResponse::new(items, pagination, request_id))
+    }
+}
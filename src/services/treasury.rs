@@ -0,0 +1,143 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Pre-flight checks of the treasury, the account paying out allocations.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use num_bigint::BigUint;
+
+use crate::{
+    context::Context,
+    contracts::{
+        token::{Token, TokenContract},
+        types::{Address, Number},
+        Contract,
+    },
+    errors::{ApiError, Result},
+    requests::workflow::{Budget, Denomination},
+    responses::treasury::{TreasuryRequirementResponse, TreasuryResponse},
+    services::{
+        allocation::{AllocationRecord, ExecutionStatus},
+        payout::{from_base_units, to_base_units, usd_to_tokens, PayoutService},
+    },
+};
+
+#[derive(Clone, clap::Parser)]
+pub struct TreasuryConfig {
+    /// Amount of the fee token, in whole tokens, the treasury keeps for transaction fees.
+    #[clap(long, env = "DRK_TREASURY_FEE_HEADROOM", default_value = "5")]
+    pub treasury_fee_headroom: Number,
+}
+
+/// The amounts required of every token, in the smallest unit of the token, by token address.
+pub type Requirements = BTreeMap<Address, (Token, BigUint)>;
+
+pub struct TreasuryService;
+
+impl TreasuryService {
+    /// Check the treasury can fund a budget on top of the approved allocations which are not
+    /// paid out yet, failing with funding instructions otherwise.
+    pub async fn preflight(ctx: Arc<Context>, budget: &Budget) -> Result<TreasuryResponse> {
+        let bad_request = |e: anyhow::Error| ApiError::BadWorkflowRequest(e.to_string());
+        let unavailable = |e: anyhow::Error| ApiError::chain(e, ApiError::TreasuryUnavailable);
+
+        let token: Token = budget.token.parse().map_err(bad_request)?;
+        let token_address = PayoutService::token_address(ctx.contract.as_ref(), &token);
+        let decimals = ctx.contract.decimals(token_address.clone()).await.map_err(unavailable)?;
+
+        let amount = match budget.denomination {
+            Denomination::Token => budget.amount.clone(),
+            Denomination::Usd => {
+                let price = ctx.prices.usd_price(&token).await.map_err(unavailable)?;
+                usd_to_tokens(&budget.amount, &price, decimals).map_err(bad_request)?
+            }
+        };
+        let mut amount = to_base_units(&amount, decimals).map_err(bad_request)?;
+
+        let mut records = ctx.allocations.by_status(ExecutionStatus::Approved);
+        records.extend(ctx.allocations.by_status(ExecutionStatus::AwaitingClaim));
+        for record in records {
+            if record.token == token {
+                amount += approved_amount(&ctx, &record, decimals).await.map_err(unavailable)?;
+            }
+        }
+
+        let requirements = Requirements::from([(token_address, (token, amount))]);
+        let treasury = Self::check(&ctx, requirements).await.map_err(unavailable)?;
+        if !treasury.funded {
+            return Err(ApiError::InsufficientTreasury(Box::new(treasury)));
+        }
+
+        Ok(treasury)
+    }
+
+    /// Compare the balances of the treasury with the required amounts, adding the fee headroom.
+    pub async fn check(
+        ctx: &Context,
+        mut requirements: Requirements,
+    ) -> anyhow::Result<TreasuryResponse> {
+        let contract = ctx.contract.as_ref();
+
+        let fee_token = Token::Native(contract.fee_token());
+        let fee_address = PayoutService::token_address(contract, &fee_token);
+        let decimals = contract.decimals(fee_address.clone()).await?;
+        let headroom = to_base_units(&ctx.config.treasury_config.treasury_fee_headroom, decimals)?;
+        requirements.entry(fee_address).or_insert((fee_token, BigUint::ZERO)).1 += headroom;
+
+        let address = contract.account_address();
+        let mut funded = true;
+        let mut responses = vec![];
+        for (token_address, (token, required)) in requirements {
+            let decimals = contract.decimals(token_address.clone()).await?;
+            let balance = contract.balance_of(token_address.clone(), address.clone()).await?;
+            let available = to_base_units(&balance, 0)?;
+            let shortfall =
+                if available < required { &required - &available } else { BigUint::ZERO };
+            funded &= shortfall == BigUint::ZERO;
+
+            responses.push(TreasuryRequirementResponse {
+                token: token.to_string(),
+                token_address: token_address.to_string(),
+                required: from_base_units(&required, decimals),
+                available: from_base_units(&available, decimals),
+                shortfall: from_base_units(&shortfall, decimals),
+            });
+        }
+
+        Ok(TreasuryResponse {
+            chain: contract.chain().to_string(),
+            address: address.to_string(),
+            funded,
+            requirements: responses,
+        })
+    }
+}
+
+/// The amount of an approved allocation in the smallest unit of its token, converting
+/// USD amounts at the current price unless already converted.
+async fn approved_amount(
+    ctx: &Context,
+    record: &AllocationRecord,
+    decimals: u8,
+) -> anyhow::Result<BigUint> {
+    let amount = match (&record.token_amount, record.denomination) {
+        (Some(amount), _) => amount.clone(),
+        (None, Denomination::Token) => record.amount.clone(),
+        (None, Denomination::Usd) => {
+            let price = ctx.prices.usd_price(&record.token).await?;
+            usd_to_tokens(&record.amount, &price, decimals)?
+        }
+    };
+    to_base_units(&amount, decimals)
+}
@@ -0,0 +1,116 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    context::Context,
+    errors::Result,
+    middlewares::ratelimit::ClientKey,
+    responses::usage::{UsageAggregateResponse, UsageResponse},
+};
+
+/// The consumption of a single client.
+#[derive(Debug, Default, Clone)]
+struct Usage {
+    requests: u64,
+    analyses: u64,
+    fees: u128,
+}
+
+/// In-memory usage counters per client key.
+#[derive(Clone, Default)]
+pub struct UsageTracker {
+    usages: Arc<Mutex<HashMap<String, Usage>>>,
+}
+
+impl UsageTracker {
+    pub fn record_request(&self, key: &ClientKey) {
+        self.update(key, |usage| usage.requests += 1);
+    }
+
+    pub fn record_analysis(&self, key: &ClientKey) {
+        self.update(key, |usage| usage.analyses += 1);
+    }
+
+    /// Record chain fees paid on behalf of the client, in the smallest unit of the fee token.
+    pub fn record_fees(&self, key: &ClientKey, fees: u128) {
+        self.update(key, |usage| usage.fees = usage.fees.saturating_add(fees));
+    }
+
+    fn update(&self, key: &ClientKey, f: impl FnOnce(&mut Usage)) {
+        f(self.usages.lock().unwrap().entry(key.0.clone()).or_default());
+    }
+
+    fn get(&self, key: &ClientKey) -> Usage {
+        self.usages.lock().unwrap().get(&key.0).cloned().unwrap_or_default()
+    }
+
+    fn all(&self) -> Vec<(String, Usage)> {
+        self.usages.lock().unwrap().iter().map(|(k, v)| (k.clone(), v.clone())).collect()
+    }
+}
+
+pub struct UsageService;
+
+impl UsageService {
+    /// Get the usage of the calling client.
+    pub async fn get(ctx: Arc<Context>, key: &ClientKey) -> Result<UsageResponse> {
+        Ok(to_response(&key.0, &ctx.usage.get(key)))
+    }
+
+    /// Get the usage of every client, restricted to the admin key.
+    pub async fn aggregate(ctx: Arc<Context>, key: &ClientKey) -> Result<UsageAggregateResponse> {
+        key.authorize_admin(&ctx.config.admin_api_key)?;
+
+        let mut total = Usage::default();
+        let mut clients: Vec<UsageResponse> = ctx
+            .usage
+            .all()
+            .into_iter()
+            .map(|(key, usage)| {
+                total.requests += usage.requests;
+                total.analyses += usage.analyses;
+                total.fees = total.fees.saturating_add(usage.fees);
+                to_response(&key, &usage)
+            })
+            .collect();
+        clients.sort_by_key(|usage| std::cmp::Reverse(usage.requests));
+
+        Ok(UsageAggregateResponse { total: to_response("*", &total), clients })
+    }
+}
+
+fn to_response(key: &str, usage: &Usage) -> UsageResponse {
+    UsageResponse {
+        client: mask(key),
+        requests: usage.requests,
+        analyses: usage.analyses,
+        fees: usage.fees.to_string(),
+    }
+}
+
+/// Hide most of an API key, keeping enough to recognize it.
+fn mask(key: &str) -> String {
+    match key.strip_prefix("key:") {
+        Some(secret) if secret.chars().count() > 4 => {
+            format!("key:{}…", secret.chars().take(4).collect::<String>())
+        }
+        Some(_) => "key:…".to_string(),
+        None => key.to_string(),
+    }
+}
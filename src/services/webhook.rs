@@ -0,0 +1,371 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Outbound webhooks.
+//!
+//! Clients register a URL and a secret, and are posted the events of the workflows:
+//! `workflow.completed`, `allocation.executed` and `inquire.responded`. Every event is
+//! queued as one delivery per webhook, written through to the database so the queue
+//! survives restarts, and posted by the webhook worker, which retries the failed
+//! deliveries with an exponential backoff.
+//!
+//! The body of a delivery is signed with HMAC-SHA256 under the secret of its webhook.
+//! The signed message is the timestamp of the attempt and the body, joined by a dot, so
+//! receivers can check both where a delivery comes from and that it is not replayed.
+//!
+//! A webhook belongs to the API key which registered it, and is only posted the events
+//! of the workflows created with that key. Its URL must point to a public host, never to
+//! a loopback, private or link-local address of the network the server runs in.
+
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use tracing::{debug, info, instrument};
+use url::{Host, Url};
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    db::{self, Database, Write},
+    errors::{ApiError, Result},
+    middlewares::ratelimit::ClientKey,
+    requests::webhook::WebhookRequest,
+    responses::webhook::{WebhookEvent, WebhookResponse},
+};
+
+/// The header carrying the signature of a delivery, eg. `sha256=<hex>`.
+pub const SIGNATURE_HEADER: &str = "x-deprank-signature";
+/// The header carrying the Unix timestamp the signature covers.
+pub const TIMESTAMP_HEADER: &str = "x-deprank-timestamp";
+/// The header carrying the event of a delivery, eg. `workflow.completed`.
+pub const EVENT_HEADER: &str = "x-deprank-event";
+/// The header carrying the id of a delivery, the same across its attempts.
+pub const DELIVERY_HEADER: &str = "x-deprank-delivery";
+
+/// The maximum number of webhooks of an API key.
+const MAX_WEBHOOKS: usize = 10;
+
+/// A registered webhook.
+#[derive(Debug, Clone)]
+pub struct WebhookRecord {
+    pub id: Uuid,
+    /// The SHA-256 hash of the API key which registered the webhook
+    pub owner: String,
+    pub url: String,
+    pub secret: String,
+    /// The events posted, every event if empty
+    pub events: Vec<WebhookEvent>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl WebhookRecord {
+    fn accepts(&self, event: WebhookEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+/// Where a delivery is in its attempts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    /// Waiting for its next attempt
+    Pending,
+    /// Accepted by the receiver
+    Delivered,
+    /// Gave up after too many attempts
+    Failed,
+}
+
+/// An event to post to a webhook.
+#[derive(Debug, Clone)]
+pub struct DeliveryRecord {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event: WebhookEvent,
+    /// The body posted, the same across the attempts
+    pub payload: String,
+    pub status: DeliveryStatus,
+    /// Number of failed attempts
+    pub attempts: u32,
+    /// Earliest time of the next attempt
+    pub next_attempt_at: DateTime<Utc>,
+    /// The error of the last attempt, if any
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+/// The webhooks, by id, and the deliveries still to post, written through to the database
+/// if configured. Settled deliveries are only kept in the database.
+#[derive(Clone, Default)]
+pub struct WebhookStore {
+    webhooks: Arc<Mutex<HashMap<Uuid, WebhookRecord>>>,
+    deliveries: Arc<Mutex<HashMap<Uuid, DeliveryRecord>>>,
+    db: Option<Database>,
+}
+
+impl WebhookStore {
+    /// Load the webhooks and their pending deliveries from the database, in memory only
+    /// without one.
+    pub async fn load(db: Option<&Database>) -> anyhow::Result<Self> {
+        let Some(db) = db else {
+            return Ok(Self::default());
+        };
+
+        let webhooks = db::webhook::load(db.pool()).await?;
+        let deliveries = db::webhook::load_pending(db.pool()).await?;
+        Ok(Self {
+            webhooks: Arc::new(Mutex::new(
+                webhooks.into_iter().map(|record| (record.id, record)).collect(),
+            )),
+            deliveries: Arc::new(Mutex::new(
+                deliveries.into_iter().map(|record| (record.id, record)).collect(),
+            )),
+            db: Some(db.clone()),
+        })
+    }
+
+    pub fn get(&self, id: Uuid) -> Option<WebhookRecord> {
+        self.webhooks.lock().unwrap().get(&id).cloned()
+    }
+
+    /// The pending deliveries due by `now`, oldest first.
+    pub fn due(&self, now: DateTime<Utc>) -> Vec<DeliveryRecord> {
+        let deliveries = self.deliveries.lock().unwrap();
+        let mut due: Vec<_> = deliveries
+            .values()
+            .filter(|record| {
+                record.status == DeliveryStatus::Pending && record.next_attempt_at <= now
+            })
+            .cloned()
+            .collect();
+        due.sort_by_key(|record| record.created_at);
+        due
+    }
+
+    /// Apply `f` to the delivery, dropping it from memory once settled.
+    pub fn update_delivery(&self, id: Uuid, f: impl FnOnce(&mut DeliveryRecord)) {
+        let mut deliveries = self.deliveries.lock().unwrap();
+        let Some(record) = deliveries.get_mut(&id) else {
+            return;
+        };
+        f(record);
+        // Queued under the lock, so the changes are written in the order they were made.
+        self.write(Write::Delivery(record.clone()));
+        if record.status != DeliveryStatus::Pending {
+            deliveries.remove(&id);
+        }
+    }
+
+    fn insert(&self, record: WebhookRecord) {
+        let mut webhooks = self.webhooks.lock().unwrap();
+        self.write(Write::Webhook(record.clone()));
+        webhooks.insert(record.id, record);
+    }
+
+    /// Remove a webhook and its pending deliveries.
+    fn remove(&self, id: Uuid) {
+        let mut webhooks = self.webhooks.lock().unwrap();
+        self.write(Write::DeleteWebhook(id));
+        webhooks.remove(&id);
+        self.deliveries.lock().unwrap().retain(|_, record| record.webhook_id != id);
+    }
+
+    fn enqueue(&self, record: DeliveryRecord) {
+        let mut deliveries = self.deliveries.lock().unwrap();
+        self.write(Write::Delivery(record.clone()));
+        deliveries.insert(record.id, record);
+    }
+
+    fn filter(&self, f: impl Fn(&WebhookRecord) -> bool) -> Vec<WebhookRecord> {
+        self.webhooks.lock().unwrap().values().filter(|record| f(record)).cloned().collect()
+    }
+
+    fn write(&self, write: Write) {
+        if let Some(db) = &self.db {
+            db.write(write);
+        }
+    }
+}
+
+pub struct WebhookService;
+
+impl WebhookService {
+    /// Register a webhook for the API key.
+    #[instrument(skip_all)]
+    pub async fn create(
+        ctx: Arc<Context>,
+        key: &ClientKey,
+        req: WebhookRequest,
+    ) -> Result<WebhookResponse> {
+        let owner = key.digest();
+        if ctx.webhooks.filter(|record| record.owner == owner).len() >= MAX_WEBHOOKS {
+            return Err(ApiError::Forbidden(format!(
+                "at most {MAX_WEBHOOKS} webhooks per API key"
+            )));
+        }
+
+        let mut events = req.events;
+        events.sort();
+        events.dedup();
+        let record = WebhookRecord {
+            id: Uuid::new_v4(),
+            owner,
+            url: req.url,
+            secret: req.secret,
+            events,
+            created_at: Utc::now(),
+        };
+        info!(webhook_id = %record.id, "Webhook registered");
+        ctx.webhooks.insert(record.clone());
+
+        Ok(to_response(&record))
+    }
+
+    /// Delete a webhook of the API key, dropping its pending deliveries.
+    #[instrument(skip_all, fields(webhook_id = %id))]
+    pub async fn delete(ctx: Arc<Context>, key: &ClientKey, id: Uuid) -> Result<()> {
+        match ctx.webhooks.get(id) {
+            Some(record) if record.owner == key.digest() => {
+                ctx.webhooks.remove(id);
+                info!("Webhook deleted");
+                Ok(())
+            }
+            _ => Err(ApiError::NotFoundWebhook(id.to_string())),
+        }
+    }
+
+    /// Queue an event of a workflow for every webhook posted it among the webhooks of the
+    /// API key which created the workflow.
+    pub fn publish(ctx: &Context, workflow_id: Uuid, event: WebhookEvent, data: Value) {
+        let Some(client) = ctx.workflows.get(workflow_id).and_then(|record| record.client) else {
+            debug!(%workflow_id, %event, "No API key to post the event to");
+            return;
+        };
+
+        let now = Utc::now();
+        let webhooks =
+            ctx.webhooks.filter(|record| record.owner == client && record.accepts(event));
+        for webhook in webhooks {
+            let id = Uuid::new_v4();
+            let payload = json!({
+                "id": id,
+                "event": event,
+                "created_at": now,
+                "data": data,
+            });
+            ctx.webhooks.enqueue(DeliveryRecord {
+                id,
+                webhook_id: webhook.id,
+                event,
+                payload: payload.to_string(),
+                status: DeliveryStatus::Pending,
+                attempts: 0,
+                next_attempt_at: now,
+                error: None,
+                created_at: now,
+                delivered_at: None,
+            });
+        }
+    }
+
+    /// The signature of a delivery body at a timestamp, eg. `sha256=<hex>`.
+    pub fn sign(secret: &str, timestamp: i64, payload: &str) -> String {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+        mac.update(format!("{timestamp}.{payload}").as_bytes());
+        format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+    }
+}
+
+/// Resolves host names to their public addresses only, failing for the names of the
+/// loopback, private or link-local addresses.
+pub struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} has no public address", name.as_str()).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Whether a URL may be posted to: its host is a name, which the webhook worker only
+/// resolves to public addresses, or a public address.
+pub fn is_public_host(url: &Url) -> bool {
+    match url.host() {
+        Some(Host::Domain(domain)) => {
+            let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+            domain != "localhost" && !domain.ends_with(".localhost")
+        }
+        Some(Host::Ipv4(ip)) => is_public_ip(IpAddr::V4(ip)),
+        Some(Host::Ipv6(ip)) => is_public_ip(IpAddr::V6(ip)),
+        None => false,
+    }
+}
+
+/// Whether an address is reachable on the internet, not a loopback, private, link-local
+/// or otherwise reserved address.
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                // Shared address space of carrier-grade NATs, 100.64.0.0/10
+                || (a == 100 && (64..128).contains(&b))
+                // "This network", 0.0.0.0/8
+                || a == 0)
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_ip(IpAddr::V4(ip)),
+            None => {
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local())
+            }
+        },
+    }
+}
+
+fn to_response(record: &WebhookRecord) -> WebhookResponse {
+    WebhookResponse {
+        id: record.id,
+        url: record.url.clone(),
+        events: record.events.clone(),
+        created_at: record.created_at,
+    }
+}
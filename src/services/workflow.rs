@@ -12,30 +12,1112 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::sync::Arc;
+//! Workflows, and their orchestration.
+//!
+//! A workflow moves through its states one after the other, from Created to Completed,
+//! or to Failed from any state before. Every transition is recorded with the workflow,
+//! written through to the database when one is configured, so the state survives a
+//! restart. Leaving a state whose work is settled on chain records the matching step for
+//! every dependency of the workflow, and completing the workflow finishes it, in one
+//! transaction. A transition whose step could not be recorded stays pending and is
+//! recorded again by the orchestration worker.
+//!
+//! A workflow is run by a background job from its creation: the repository is fetched,
+//! its dependencies analyzed and ranked in a new snapshot, unless the workflow is based
+//! on an existing one, then recorded on chain with their receipts. The job stops there,
+//! the allocations waiting for their signatures.
+//!
+//! A workflow may be cancelled at any time before it completes, aborting the job running
+//! its current state. A failed workflow is retried from the state it failed in, the last
+//! state completed before being its checkpoint.
+//!
+//! A workflow is only found by the client which created it, the members of its
+//! organization using their API key, with the role the operation requires, and the admin.
 
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use ghrepo::GHRepo;
+use tokio::task::AbortHandle;
+use tracing::{info, instrument, warn};
 use uuid::Uuid;
 
 use crate::{
-    context::Context, errors::Result, requests::workflow::CreateWorkflowRequest,
-    responses::workflow::WorkflowResponse,
+    analyzers::ResolvedGraph,
+    context::Context,
+    contracts::{
+        error::ContractError,
+        transaction::TransactionContract,
+        types::{Address, Hash, Id, Owner, RawCall},
+        workflow::{StepType, WorkflowContract},
+    },
+    db::{self, contribution::ContributionRow, Database, Write},
+    errors::{ApiError, Result},
+    middlewares::{ratelimit::ClientKey, trace::RequestId},
+    requests::{
+        list::ListParams,
+        organization::Role,
+        workflow::{CreateWorkflowRequest, WorkflowListParams},
+    },
+    responses::{
+        dependency::DependencyResponse,
+        event::EventKind,
+        list::ListResponse,
+        workflow::{WorkflowResponse, WorkflowState, WorkflowSummaryResponse},
+    },
+    services::{
+        dependency::DependencyService,
+        distribution::DistributionService,
+        metadata::{DependencyMetadata, METADATA_SCHEMA_VERSION},
+        organization::{same_address, Organization},
+        profile::DEFAULT_PROFILE,
+        quota::QuotaService,
+        receipt::ReceiptService,
+        snapshot::SnapshotService,
+        storage::{Revision, StorageService},
+        treasury::TreasuryService,
+    },
+    telemetry,
 };
 
+/// A signature collected by a workflow, once recorded on chain.
+#[derive(Debug, Clone)]
+pub struct SignatureRecord {
+    pub signer: Address,
+    pub signature_hash: Hash,
+    /// The transaction recording the signature
+    pub tx_hash: Hash,
+    pub signed_at: DateTime<Utc>,
+}
+
+/// A transition of a workflow from a state to the next.
+#[derive(Debug, Clone)]
+pub struct TransitionRecord {
+    pub from: WorkflowState,
+    pub to: WorkflowState,
+    /// Why the workflow failed, for a transition to Failed
+    pub error: Option<String>,
+    /// The step recorded on chain for the transition, if any
+    pub step: Option<StepType>,
+    /// The transaction the step attests, the zero hash if none
+    pub attested_tx_hash: Hash,
+    /// The transaction recording the step, once sent
+    pub tx_hash: Option<Hash>,
+    pub at: DateTime<Utc>,
+}
+
+impl TransitionRecord {
+    /// Whether the step of the transition is still to be recorded on chain.
+    pub fn is_pending(&self) -> bool {
+        self.step.is_some() && self.tx_hash.is_none()
+    }
+}
+
+/// What a workflow analyzed and collected, complementing its state on chain.
+#[derive(Debug, Clone)]
+pub struct WorkflowRecord {
+    pub id: Uuid,
+    /// The id of the workflow on chain, once created there
+    pub chain_id: Option<Id>,
+    /// The project, eg. `deprank/backend`
+    pub project: String,
+    /// The source code repository, as requested
+    pub repo: String,
+    /// The branch, tag or commit analyzed, the default branch if none
+    pub branch: Option<String>,
+    pub tag: Option<String>,
+    pub rev: Option<String>,
+    /// The name of the ranking weight profile
+    pub profile: String,
+    /// The organization owning the project, if any
+    pub organization: Option<String>,
+    /// The SHA-256 hash of the API key which created the workflow, whose webhooks are
+    /// posted its events
+    pub client: Option<String>,
+    /// The ranking snapshot the allocations are based on
+    pub snapshot_id: Option<Uuid>,
+    /// The collected signatures, oldest first
+    pub signatures: Vec<SignatureRecord>,
+    /// The multisig wallet bound to the workflow, which its allocations are paid out from
+    pub wallet_address: Option<Address>,
+    pub state: WorkflowState,
+    /// The transitions, oldest first
+    pub transitions: Vec<TransitionRecord>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl WorkflowRecord {
+    /// The revision of the repository the workflow analyzes.
+    pub fn revision(&self) -> Revision {
+        match (&self.rev, &self.tag, &self.branch) {
+            (Some(rev), _, _) => Revision::Commit(rev.clone()),
+            (None, Some(tag), _) => Revision::Tag(tag.clone()),
+            (None, None, Some(branch)) => Revision::Branch(branch.clone()),
+            (None, None, None) => Revision::DefaultBranch,
+        }
+    }
+
+    /// The GitHub owner of the project, who the workflow belongs to on chain, unless it
+    /// cannot be encoded on chain.
+    pub fn github_owner(&self) -> Result<Owner, ContractError> {
+        self.project.split('/').next().unwrap_or_default().parse()
+    }
+
+    /// Why the workflow failed, if it did.
+    pub fn error(&self) -> Option<&str> {
+        match self.state {
+            WorkflowState::Failed => self.transitions.last()?.error.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// The workflows, in memory, by id, written through to the database if configured.
+#[derive(Clone, Default)]
+pub struct WorkflowStore {
+    workflows: Arc<Mutex<HashMap<Uuid, WorkflowRecord>>>,
+    /// The jobs running the current state of the workflows
+    jobs: Arc<Mutex<HashMap<Uuid, AbortHandle>>>,
+    db: Option<Database>,
+}
+
+impl WorkflowStore {
+    /// Load the workflows from the database, in memory only without one.
+    pub async fn load(db: Option<&Database>) -> anyhow::Result<Self> {
+        let Some(db) = db else {
+            return Ok(Self::default());
+        };
+
+        let workflows = db::workflow::load(db.pool()).await?;
+        let workflows = workflows.into_iter().map(|record| (record.id, record)).collect();
+        Ok(Self {
+            workflows: Arc::new(Mutex::new(workflows)),
+            jobs: Arc::default(),
+            db: Some(db.clone()),
+        })
+    }
+
+    pub fn insert(&self, record: WorkflowRecord) {
+        let mut workflows = self.workflows.lock().unwrap();
+        self.persist(&record);
+        workflows.insert(record.id, record);
+    }
+
+    pub fn get(&self, id: Uuid) -> Option<WorkflowRecord> {
+        self.workflows.lock().unwrap().get(&id).cloned()
+    }
+
+    /// Remove a workflow, returning whether it existed.
+    pub fn remove(&self, id: Uuid) -> bool {
+        let mut workflows = self.workflows.lock().unwrap();
+        if workflows.remove(&id).is_none() {
+            return false;
+        }
+        if let Some(db) = &self.db {
+            db.write(Write::DeleteWorkflow(id));
+        }
+        true
+    }
+
+    /// Get the workflow with an id on chain.
+    pub fn find_by_chain_id(&self, chain_id: &str) -> Option<WorkflowRecord> {
+        let workflows = self.workflows.lock().unwrap();
+        workflows.values().find(|record| record.chain_id.as_deref() == Some(chain_id)).cloned()
+    }
+
+    /// Point a workflow at another snapshot, returning whether the workflow exists.
+    pub fn set_snapshot(&self, id: Uuid, snapshot_id: Uuid) -> bool {
+        self.update(id, |record| record.snapshot_id = Some(snapshot_id))
+    }
+
+    /// Record a signature collected by a workflow, returning whether the workflow exists.
+    pub fn add_signature(&self, id: Uuid, signature: SignatureRecord) -> bool {
+        self.update(id, |record| record.signatures.push(signature))
+    }
+
+    /// Bind a multisig wallet to a workflow, or unbind it with `None`, returning whether
+    /// the workflow exists.
+    pub fn set_wallet_address(&self, id: Uuid, wallet_address: Option<Address>) -> bool {
+        self.update(id, |record| record.wallet_address = wallet_address)
+    }
+
+    /// Record the id of a workflow on chain, returning whether the workflow exists.
+    pub fn set_chain_id(&self, id: Uuid, chain_id: Id) -> bool {
+        self.update(id, |record| record.chain_id = Some(chain_id))
+    }
+
+    /// Move a workflow to another state, if its current state allows it, returning the
+    /// workflow as moved, `None` if it does not exist, or the state it cannot leave for
+    /// `transition.to`.
+    pub fn transition(
+        &self,
+        id: Uuid,
+        transition: TransitionRecord,
+    ) -> Option<Result<WorkflowRecord, WorkflowState>> {
+        let mut workflows = self.workflows.lock().unwrap();
+        let record = workflows.get_mut(&id)?;
+        if record.state != transition.from || !record.state.can_transition_to(transition.to) {
+            return Some(Err(record.state));
+        }
+
+        record.state = transition.to;
+        record.updated_at = transition.at;
+        record.transitions.push(transition);
+        self.persist(record);
+        Some(Ok(record.clone()))
+    }
+
+    /// Record the transaction recording the step of a transition on chain.
+    pub fn set_transition_tx_hash(&self, id: Uuid, index: usize, tx_hash: Hash) -> bool {
+        self.update(id, |record| {
+            if let Some(transition) = record.transitions.get_mut(index) {
+                transition.tx_hash = Some(tx_hash);
+            }
+        })
+    }
+
+    /// Track the job running the current state of a workflow, replacing the previous one.
+    pub fn set_job(&self, id: Uuid, job: AbortHandle) {
+        self.jobs.lock().unwrap().insert(id, job);
+    }
+
+    /// Abort the job running the current state of a workflow, returning whether one was
+    /// still running.
+    pub fn abort_job(&self, id: Uuid) -> bool {
+        let Some(job) = self.jobs.lock().unwrap().remove(&id) else {
+            return false;
+        };
+        let running = !job.is_finished();
+        job.abort();
+        running
+    }
+
+    /// Get the workflows matching `f`.
+    pub fn filter(&self, f: impl Fn(&WorkflowRecord) -> bool) -> Vec<WorkflowRecord> {
+        self.workflows.lock().unwrap().values().filter(|record| f(record)).cloned().collect()
+    }
+
+    fn update(&self, id: Uuid, f: impl FnOnce(&mut WorkflowRecord)) -> bool {
+        let mut workflows = self.workflows.lock().unwrap();
+        let Some(record) = workflows.get_mut(&id) else {
+            return false;
+        };
+        f(record);
+        // Queued under the lock, so the changes are written in the order they were made.
+        self.persist(record);
+        true
+    }
+
+    fn persist(&self, record: &WorkflowRecord) {
+        if let Some(db) = &self.db {
+            db.write(Write::Workflow(record.clone()));
+        }
+    }
+}
+
+/// The summary of a workflow.
+fn summary(record: &WorkflowRecord) -> WorkflowSummaryResponse {
+    WorkflowSummaryResponse {
+        id: record.id,
+        project: record.project.clone(),
+        organization: record.organization.clone(),
+        state: record.state,
+        error: record.error().map(str::to_string),
+        created_at: record.created_at,
+        updated_at: record.updated_at,
+    }
+}
+
+/// The metadata of a ranked dependency recorded on chain, its share the share of its
+/// score, or `None` when it has no https source code repository to fund.
+fn metadata(dependency: &DependencyResponse) -> Option<serde_json::Value> {
+    let repository_url = dependency.repository.clone().filter(|url| url.starts_with("https://"))?;
+    let metadata = DependencyMetadata {
+        schema_version: METADATA_SCHEMA_VERSION,
+        ecosystem: dependency.ecosystem,
+        name: dependency.name.clone(),
+        version: dependency.version.clone(),
+        license: dependency.license.clone(),
+        repository_url,
+        share: Some(dependency.score),
+    };
+    serde_json::to_value(metadata).ok()
+}
+
+/// A workflow, as created.
+fn to_response(record: &WorkflowRecord) -> WorkflowResponse {
+    WorkflowResponse {
+        repo: record.repo.clone(),
+        branch: record.branch.clone(),
+        tag: record.tag.clone(),
+        rev: record.rev.clone(),
+        organization: record.organization.clone(),
+        snapshot_id: record.snapshot_id,
+        state: record.state,
+    }
+}
+
+/// The fields the workflows can be sorted by.
+const SORT_FIELDS: [&str; 2] = ["created_at", "updated_at"];
+
+/// The step recorded on chain when a workflow leaves a state, for the states whose work
+/// is settled on chain.
+fn step_type(state: WorkflowState) -> Option<StepType> {
+    match state {
+        WorkflowState::RecordingReceipts => Some(StepType::Receipt),
+        WorkflowState::AwaitingSignatures => Some(StepType::Sign),
+        WorkflowState::Allocating => Some(StepType::Allocation),
+        _ => None,
+    }
+}
+
 pub struct WorkflowService;
 
 impl WorkflowService {
+    /// Create a workflow, and start the job running it in the background.
+    #[instrument(skip_all)]
     pub async fn create(
-        _ctx: Arc<Context>,
-        _req: &CreateWorkflowRequest,
+        ctx: Arc<Context>,
+        key: &ClientKey,
+        req: &CreateWorkflowRequest,
     ) -> Result<WorkflowResponse> {
-        todo!()
+        let repo = GHRepo::from_url(&req.repo).map_err(|e| {
+            ApiError::BadWorkflowRequest(format!("invalid repository `{}`: {e}", req.repo))
+        })?;
+
+        if ctx.profiles.get(req.profile.as_deref()).is_none() {
+            let profile = req.profile.clone().unwrap_or_default();
+            return Err(ApiError::BadWorkflowRequest(format!(
+                "unknown ranking profile `{profile}`"
+            )));
+        }
+
+        if let Some(id) = req.snapshot {
+            let snapshot =
+                ctx.snapshots.get(id).ok_or(ApiError::NotFoundSnapshot(id.to_string()))?;
+            if req.profile.as_ref().is_some_and(|profile| *profile != snapshot.profile) {
+                return Err(ApiError::BadWorkflowRequest(format!(
+                    "snapshot {id} was ranked with the `{}` profile",
+                    snapshot.profile
+                )));
+            }
+        }
+
+        // Refuse budgets the treasury cannot pay out before issuing any allocation.
+        if let Some(budget) = &req.budget {
+            DistributionService::validate(&ctx.config.distribution_config, &budget.amount)
+                .map_err(|e| ApiError::BadWorkflowRequest(e.to_string()))?;
+            TreasuryService::preflight(ctx.clone(), budget).await?;
+        }
+
+        QuotaService::check_workflows(&ctx, key)?;
+        QuotaService::acquire_analysis(&ctx, key)?;
+
+        let organization = ctx.organizations.owning(repo.owner());
+        let now = Utc::now();
+        let record = WorkflowRecord {
+            id: Uuid::new_v4(),
+            chain_id: None,
+            project: format!("{}/{}", repo.owner(), repo.name()).to_lowercase(),
+            repo: req.repo.clone(),
+            branch: req.branch.clone(),
+            tag: req.tag.clone(),
+            rev: req.rev.clone(),
+            profile: req.profile.clone().unwrap_or_else(|| DEFAULT_PROFILE.to_string()),
+            organization: organization.as_ref().map(|org| org.name.clone()),
+            client: Some(key.digest()),
+            snapshot_id: req.snapshot,
+            signatures: Vec::new(),
+            // The workflows of an organization pay out from its treasury.
+            wallet_address: organization.and_then(|org| org.treasury),
+            state: WorkflowState::Created,
+            transitions: Vec::new(),
+            created_at: now,
+            updated_at: now,
+        };
+        ctx.workflows.insert(record.clone());
+        ctx.quotas.begin_workflow(key, record.id);
+        info!(workflow_id = %record.id, project = %record.project, "Workflow created");
+        Self::start(&ctx, record.id, key.clone());
+
+        Ok(to_response(&record))
+    }
+
+    /// Get a workflow the client may act on with the role, as the client which created it,
+    /// a member of its organization with at least the role, or the admin. The workflows of
+    /// other clients are not found.
+    pub fn authorize(
+        ctx: &Context,
+        key: &ClientKey,
+        id: Uuid,
+        role: Role,
+    ) -> Result<WorkflowRecord> {
+        ctx.workflows
+            .get(id)
+            .filter(|record| is_accessible(ctx, key, record, role))
+            .ok_or(ApiError::NotFoundWorkflow(id.to_string()))
+    }
+
+    /// Delete a workflow, aborting the job running it.
+    #[instrument(skip_all, fields(workflow_id = %id))]
+    pub async fn delete(ctx: Arc<Context>, key: &ClientKey, id: Uuid) -> Result<()> {
+        Self::authorize(&ctx, key, id, Role::Owner)?;
+        if ctx.workflows.abort_job(id) {
+            info!("Workflow job aborted");
+        }
+        if !ctx.workflows.remove(id) {
+            return Err(ApiError::NotFoundWorkflow(id.to_string()));
+        }
+        ctx.quotas.end_workflow(id);
+        info!("Workflow deleted");
+        Ok(())
+    }
+
+    pub async fn get(ctx: Arc<Context>, key: &ClientKey, id: Uuid) -> Result<WorkflowResponse> {
+        let record = Self::authorize(&ctx, key, id, Role::Member)?;
+        Ok(to_response(&record))
+    }
+
+    /// List the workflows of the client, most recently created first unless sorted
+    /// otherwise.
+    pub fn list(
+        ctx: &Context,
+        key: &ClientKey,
+        params: &ListParams,
+        filters: &WorkflowListParams,
+        request_id: &RequestId,
+    ) -> Result<ListResponse<WorkflowSummaryResponse>> {
+        params.validate()?;
+        if let Some(sort) =
+            params.sorts()?.into_iter().find(|sort| !SORT_FIELDS.contains(&sort.field.as_str()))
+        {
+            return Err(ApiError::InvalidListParams(format!(
+                "workflows cannot be sorted by `{}`",
+                sort.field
+            )));
+        }
+
+        let params = match params.sort {
+            Some(_) => params.clone(),
+            None => ListParams { sort: Some("-created_at".to_string()), ..params.clone() },
+        };
+        let workflows = ctx
+            .workflows
+            .filter(|record| {
+                filters.matches(&record.project, record.state)
+                    && is_accessible(ctx, key, record, Role::Member)
+            })
+            .iter()
+            .map(summary)
+            .collect();
+        let (items, pagination) = params.apply(workflows)?;
+        Ok(ListResponse::new(items, pagination, request_id))
+    }
+
+    /// Move a workflow to the next state, recording the step it completes on chain.
+    ///
+    /// `attested_tx_hash` is the transaction the work of the state left was settled by,
+    /// attested by its step.
+    #[instrument(skip_all, fields(workflow_id = %id, to = %to))]
+    pub async fn transition(
+        ctx: &Context,
+        id: Uuid,
+        to: WorkflowState,
+        attested_tx_hash: Option<Hash>,
+    ) -> Result<WorkflowRecord> {
+        let record = ctx.workflows.get(id).ok_or(ApiError::NotFoundWorkflow(id.to_string()))?;
+        let step = match to {
+            WorkflowState::Failed => None,
+            _ => step_type(record.state),
+        };
+        let from = record.state;
+        let transition = TransitionRecord {
+            from,
+            to,
+            error: None,
+            step,
+            attested_tx_hash: attested_tx_hash.unwrap_or_else(Hash::zero),
+            tx_hash: None,
+            at: Utc::now(),
+        };
+        let record = Self::apply(ctx, id, transition)?;
+        info!(%from, "Workflow moved");
+
+        // The transition stands once applied, its step is recorded again if it fails.
+        let index = record.transitions.len() - 1;
+        if let Err(e) = Self::record(ctx, &record, index).await {
+            warn!("Failed to record the workflow transition on chain: {e}");
+        }
+        Ok(ctx.workflows.get(id).unwrap_or(record))
     }
 
-    pub async fn delete(_ctx: Arc<Context>, _id: Uuid) -> Result<u16> {
-        todo!()
+    /// Move a workflow to Failed, keeping why.
+    #[instrument(skip_all, fields(workflow_id = %id))]
+    pub fn fail(ctx: &Context, id: Uuid, error: String) -> Result<WorkflowRecord> {
+        let record = ctx.workflows.get(id).ok_or(ApiError::NotFoundWorkflow(id.to_string()))?;
+        warn!(state = %record.state, %error, "Workflow failed");
+        Self::apply(
+            ctx,
+            id,
+            TransitionRecord {
+                from: record.state,
+                to: WorkflowState::Failed,
+                error: Some(error),
+                step: None,
+                attested_tx_hash: Hash::zero(),
+                tx_hash: None,
+                at: Utc::now(),
+            },
+        )
+    }
+
+    /// Start the job running a workflow of the client from its current state.
+    pub fn start(ctx: &Arc<Context>, id: Uuid, key: ClientKey) {
+        Self::spawn(ctx, id, Self::run(ctx.clone(), id, key));
+    }
+
+    /// Run the states of a workflow whose work is done by the backend, one after the
+    /// other from its current state, failing the workflow on error. Stops at the first
+    /// state whose work is settled on chain.
+    ///
+    /// The analysis counted against the quota of the client is given back if the
+    /// workflow fails before recording it.
+    async fn run(ctx: Arc<Context>, id: Uuid, key: ClientKey) {
+        let max_size = QuotaService::max_repo_size(&ctx, &key);
+        // The repository fetched for the analysis, fetched again if retried in between
+        let mut checkout = None;
+        loop {
+            let Some(record) = ctx.workflows.get(id) else {
+                return;
+            };
+            let result = match record.state {
+                WorkflowState::Created => {
+                    Self::transition(&ctx, id, WorkflowState::Fetching, None).await.map(drop)
+                }
+                WorkflowState::Fetching => match record.snapshot_id {
+                    // Based on an existing snapshot, nothing to fetch
+                    Some(_) => {
+                        Self::transition(&ctx, id, WorkflowState::Analyzing, None).await.map(drop)
+                    }
+                    None => match Self::fetch(&ctx, &record, max_size).await {
+                        Ok(fetched) => {
+                            checkout = Some(fetched);
+                            Self::transition(&ctx, id, WorkflowState::Analyzing, None)
+                                .await
+                                .map(drop)
+                        }
+                        Err(e) => Err(e),
+                    },
+                },
+                WorkflowState::Analyzing => {
+                    Self::analyze(&ctx, &record, checkout.take(), max_size).await
+                }
+                WorkflowState::RecordingReceipts => Self::record_receipts(&ctx, &record).await,
+                _ => return,
+            };
+            if let Err(e) = result {
+                if let Err(e) = Self::fail(&ctx, id, e.to_string()) {
+                    warn!(workflow_id = %id, "Failed to fail the workflow: {e}");
+                }
+                if ctx.workflows.get(id).is_some_and(|record| record.snapshot_id.is_none()) {
+                    QuotaService::refund_analysis(&ctx, &key);
+                }
+                return;
+            }
+        }
+    }
+
+    /// Fetch the repository of a workflow, refused if larger than `max_size` kilobytes,
+    /// returning the checkout and the commit.
+    async fn fetch(
+        ctx: &Context,
+        record: &WorkflowRecord,
+        max_size: Option<u64>,
+    ) -> Result<(PathBuf, String)> {
+        let storage = StorageService::new(&ctx.config.cache_dir, &ctx.config.github_token)
+            .map_err(|e| ApiError::FailedToDownloadRepo(e.to_string()))?
+            .with_max_size(max_size)
+            .with_max_file_size(Some(ctx.config.storage_config.max_file_size))
+            .with_limiter(ctx.downloads.clone());
+        let (dir, commit) = storage
+            .fetch(&record.repo, &record.revision())
+            .await
+            .map_err(|e| ApiError::FailedToDownloadRepo(format!("{e:#}")))?;
+        Ok((ctx.config.cache_dir.join(dir), commit))
+    }
+
+    /// Analyze and rank the dependencies of the repository of a workflow in a new
+    /// snapshot, unless the workflow is based on one, then move it on to recording them.
+    async fn analyze(
+        ctx: &Context,
+        record: &WorkflowRecord,
+        checkout: Option<(PathBuf, String)>,
+        max_size: Option<u64>,
+    ) -> Result<()> {
+        if record.snapshot_id.is_none() {
+            let (dir, commit) = match checkout {
+                Some(checkout) => checkout,
+                None => Self::fetch(ctx, record, max_size).await?,
+            };
+            let weights = ctx.profiles.get(Some(&record.profile)).ok_or_else(|| {
+                ApiError::BadWorkflowRequest(format!(
+                    "unknown ranking profile `{}`",
+                    record.profile
+                ))
+            })?;
+            let analyzed = ctx
+                .analyzer
+                .analyze(&dir)
+                .await
+                .map_err(|e| ApiError::FailedToCreateWorkflow(format!("{e:#}")))?;
+            let dependencies = SnapshotService::score(ctx, &weights, &analyzed);
+            let snapshot = SnapshotService::record(
+                ctx,
+                &record.project,
+                &commit,
+                &record.profile,
+                weights,
+                dependencies,
+                ResolvedGraph::new(&analyzed),
+            );
+            ctx.workflows.set_snapshot(record.id, snapshot.id);
+            if let Some(db) = &ctx.db {
+                db.write(Write::Contributions(ContributionRow::from_dependencies(
+                    record.id,
+                    snapshot.id,
+                    &snapshot.dependencies,
+                )));
+            }
+            info!(snapshot_id = %snapshot.id, dependencies = analyzed.len(), "Workflow analyzed");
+        }
+        Self::transition(ctx, record.id, WorkflowState::RecordingReceipts, None).await?;
+        Ok(())
+    }
+
+    /// Record the ranked dependencies of a workflow on chain with their receipts, those
+    /// without a source code repository to fund left out, then wait for the signatures.
+    /// The workflow is created on chain first, for the wallet bound to it.
+    async fn record_receipts(ctx: &Context, record: &WorkflowRecord) -> Result<()> {
+        let snapshot_id = record.snapshot_id.ok_or_else(|| {
+            ApiError::FailedToCreateWorkflow("the workflow was not analyzed".to_string())
+        })?;
+        let snapshot = ctx
+            .snapshots
+            .get(snapshot_id)
+            .ok_or(ApiError::NotFoundSnapshot(snapshot_id.to_string()))?;
+        let github_owner =
+            record.github_owner().map_err(|e| ApiError::FailedToCreateWorkflow(e.to_string()))?;
+        let chain_id = match &record.chain_id {
+            Some(chain_id) => chain_id.clone(),
+            None => {
+                let wallet_address = record.wallet_address.clone().ok_or_else(|| {
+                    ApiError::BadWorkflowRequest(
+                        "bind a wallet address to the workflow before recording its receipts"
+                            .to_string(),
+                    )
+                })?;
+                let chain_id = ctx
+                    .contract
+                    .create_workflow(github_owner.clone(), wallet_address)
+                    .await
+                    .map_err(|e| ApiError::chain(e, ApiError::FailedToCreateWorkflow))?
+                    .ok_or_else(|| {
+                        ApiError::FailedToCreateWorkflow(
+                            "the chain did not return the id of the workflow".to_string(),
+                        )
+                    })?;
+                ctx.workflows.set_chain_id(record.id, chain_id.clone());
+                chain_id
+            }
+        };
+
+        // A retried workflow goes on after the dependencies already recorded
+        let recorded: HashSet<String> = ctx
+            .contract
+            .get_dependencies(github_owner.clone(), chain_id.clone())
+            .await
+            .map_err(|e| ApiError::chain(e, ApiError::ChainRpcError))?
+            .into_iter()
+            .map(|dependency| dependency.name)
+            .collect();
+        if ctx.metadata_store.is_none() {
+            warn!("No metadata store is configured, recording the dependencies without receipts");
+        }
+        let mut count = 0;
+        for dependency in snapshot.dependencies.iter().filter(|d| !d.excluded_by_policy) {
+            let Some(metadata) = metadata(dependency) else {
+                continue;
+            };
+            if recorded.contains(&dependency.name) {
+                continue;
+            }
+            DependencyService::create(ctx, github_owner.clone(), chain_id.clone(), &metadata)
+                .await?;
+            if ctx.metadata_store.is_some() {
+                ReceiptService::create(ctx, chain_id.clone(), &metadata).await?;
+            }
+            count += 1;
+        }
+        info!(%chain_id, dependencies = count, "Workflow dependencies recorded");
+
+        Self::transition(ctx, record.id, WorkflowState::AwaitingSignatures, None).await?;
+        Ok(())
+    }
+
+    /// Run the job of the current state of a workflow in the background, aborted if the
+    /// workflow is cancelled.
+    pub fn spawn<F>(ctx: &Context, id: Uuid, job: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let handle = telemetry::spawn(job);
+        ctx.workflows.set_job(id, handle.abort_handle());
+    }
+
+    /// Cancel a workflow, aborting the job running its current state.
+    #[instrument(skip_all, fields(workflow_id = %id))]
+    pub fn cancel(ctx: &Context, key: &ClientKey, id: Uuid) -> Result<WorkflowSummaryResponse> {
+        let record = Self::authorize(ctx, key, id, Role::Approver)?;
+        if !record.state.can_transition_to(WorkflowState::Cancelled) {
+            return Err(ApiError::InvalidWorkflowTransition(format!(
+                "workflow {id} is {}, it cannot be cancelled",
+                record.state
+            )));
+        }
+
+        if ctx.workflows.abort_job(id) {
+            info!(state = %record.state, "Workflow job aborted");
+        }
+        let record = Self::apply(
+            ctx,
+            id,
+            TransitionRecord {
+                from: record.state,
+                to: WorkflowState::Cancelled,
+                error: None,
+                step: None,
+                attested_tx_hash: Hash::zero(),
+                tx_hash: None,
+                at: Utc::now(),
+            },
+        )?;
+        info!("Workflow cancelled");
+        Ok(summary(&record))
     }
 
-    pub async fn get(_ctx: Arc<Context>, _id: Uuid) -> Result<u16> {
-        todo!()
+    /// Retry a failed workflow from the state it failed in, recording on chain first the
+    /// steps of its transitions still pending, then running that state again. A workflow
+    /// failed before its analysis counts against the quotas of the client once more.
+    #[instrument(skip_all, fields(workflow_id = %id))]
+    pub async fn retry(
+        ctx: &Arc<Context>,
+        key: &ClientKey,
+        id: Uuid,
+    ) -> Result<WorkflowSummaryResponse> {
+        let record = Self::authorize(ctx, key, id, Role::Approver)?;
+        let checkpoint = record
+            .transitions
+            .last()
+            .filter(|transition| {
+                record.state == WorkflowState::Failed && transition.to == record.state
+            })
+            .map(|transition| transition.from)
+            .ok_or_else(|| {
+                ApiError::InvalidWorkflowTransition(format!(
+                    "workflow {id} is {}, only failed workflows are retried",
+                    record.state
+                ))
+            })?;
+
+        QuotaService::check_workflows(ctx, key)?;
+        Self::record_pending_of(ctx, &record)
+            .await
+            .map_err(|e| ApiError::chain(e, ApiError::ChainRpcError))?;
+        if record.snapshot_id.is_none() {
+            QuotaService::acquire_analysis(ctx, key)?;
+        }
+        let record = Self::apply(
+            ctx,
+            id,
+            TransitionRecord {
+                from: WorkflowState::Failed,
+                to: checkpoint,
+                error: None,
+                step: None,
+                attested_tx_hash: Hash::zero(),
+                tx_hash: None,
+                at: Utc::now(),
+            },
+        )?;
+        ctx.quotas.begin_workflow(key, id);
+        info!(%checkpoint, "Workflow retried");
+        Self::start(ctx, id, key.clone());
+        Ok(summary(&record))
+    }
+
+    /// Record on chain the steps of the transitions still pending, of every workflow.
+    pub async fn record_pending(ctx: &Context) {
+        let workflows = ctx
+            .workflows
+            .filter(|record| record.transitions.iter().any(TransitionRecord::is_pending));
+        for record in workflows {
+            if let Err(e) = Self::record_pending_of(ctx, &record).await {
+                warn!(workflow_id = %record.id, "Failed to record a workflow transition: {e}");
+            }
+        }
+    }
+
+    /// Record on chain the steps of the transitions of a workflow still pending, in order.
+    async fn record_pending_of(ctx: &Context, record: &WorkflowRecord) -> anyhow::Result<()> {
+        for index in 0..record.transitions.len() {
+            if record.transitions[index].is_pending() {
+                Self::record(ctx, record, index).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Fail the workflows interrupted in a state whose work is done in the process, lost
+    /// with it, once at startup. The other states are settled on chain and carry on.
+    pub fn fail_interrupted(ctx: &Context) {
+        let interrupted = ctx.workflows.filter(|record| {
+            matches!(record.state, WorkflowState::Fetching | WorkflowState::Analyzing)
+        });
+        for record in interrupted {
+            let error = format!("interrupted by a restart while {}", record.state);
+            if let Err(e) = Self::fail(ctx, record.id, error) {
+                warn!(workflow_id = %record.id, "Failed to fail the interrupted workflow: {e}");
+            }
+        }
+    }
+
+    fn apply(ctx: &Context, id: Uuid, transition: TransitionRecord) -> Result<WorkflowRecord> {
+        let to = transition.to;
+        match ctx.workflows.transition(id, transition) {
+            Some(Ok(record)) => {
+                if record.state.is_terminal() {
+                    ctx.quotas.end_workflow(id);
+                }
+                ctx.events.publish(
+                    id,
+                    EventKind::WorkflowState {
+                        state: record.state,
+                        error: record.error().map(str::to_string),
+                    },
+                );
+                Ok(record)
+            }
+            Some(Err(from)) => Err(ApiError::InvalidWorkflowTransition(format!(
+                "workflow {id} cannot move from {from} to {to}"
+            ))),
+            None => Err(ApiError::NotFoundWorkflow(id.to_string())),
+        }
+    }
+
+    /// Record the step of a transition for every dependency of the workflow on chain, and
+    /// finish the workflow when the transition completes it, in one transaction.
+    async fn record(ctx: &Context, record: &WorkflowRecord, index: usize) -> anyhow::Result<()> {
+        let transition = &record.transitions[index];
+        let (Some(step), Some(chain_id)) = (transition.step, &record.chain_id) else {
+            return Ok(());
+        };
+
+        let github_owner = record.github_owner()?;
+        let dependencies =
+            ctx.contract.get_dependencies(github_owner.clone(), chain_id.clone()).await?;
+        let mut calls = (0..dependencies.len())
+            .map(|dependency_idx| {
+                ctx.contract.add_step_call(
+                    github_owner.clone(),
+                    chain_id.clone(),
+                    Id::from(dependency_idx as u64),
+                    step,
+                    transition.attested_tx_hash.clone(),
+                    Id::zero(),
+                )
+            })
+            .collect::<anyhow::Result<Vec<RawCall>>>()?;
+        if transition.to == WorkflowState::Completed {
+            calls.push(ctx.contract.finish_workflow_call(github_owner, chain_id.clone())?);
+        }
+        if calls.is_empty() {
+            return Ok(());
+        }
+
+        let tx_hash = ctx.contract.execute_calls(calls).await?;
+        info!(%tx_hash, %step, "Workflow transition recorded on chain");
+        ctx.workflows.set_transition_tx_hash(record.id, index, tx_hash);
+        Ok(())
+    }
+
+    /// Bind the multisig wallet paying out the allocations of a workflow, or unbind it
+    /// with `None` to pay them from the account of the contract again. The workflows of
+    /// an organization with a treasury pay out from it, only it can be bound.
+    pub fn set_wallet_address(
+        ctx: &Context,
+        key: &ClientKey,
+        id: Uuid,
+        wallet_address: Option<Address>,
+    ) -> Result<()> {
+        let record = Self::authorize(ctx, key, id, Role::Approver)?;
+        let organization = record.organization.and_then(|name| ctx.organizations.get(&name));
+        let wallet_address = match organization {
+            Some(Organization { name, treasury: Some(treasury), .. }) => {
+                if wallet_address.is_some_and(|address| !same_address(&address, &treasury)) {
+                    return Err(ApiError::BadWorkflowRequest(format!(
+                        "the workflows of the `{name}` organization pay out from its treasury \
+                         `{treasury}`"
+                    )));
+                }
+                Some(treasury)
+            }
+            _ => wallet_address,
+        };
+        if !ctx.workflows.set_wallet_address(id, wallet_address) {
+            return Err(ApiError::NotFoundWorkflow(id.to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Whether the client may act on the workflow with the role.
+fn is_accessible(ctx: &Context, key: &ClientKey, record: &WorkflowRecord, role: Role) -> bool {
+    if key.is_admin(&ctx.config.admin_api_key) {
+        return true;
+    }
+    let client = key.digest();
+    record.client.as_deref() == Some(client.as_str())
+        || record.organization.as_deref().is_some_and(|organization| {
+            ctx.organizations
+                .role_of_client(organization, &client)
+                .is_some_and(|member| member >= role)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::services::organization::Member;
+
+    #[tokio::test]
+    async fn retry_runs_the_failed_state_again() {
+        let ctx = Arc::new(Context::for_tests().await);
+        let key = ClientKey::api_key("key");
+        let now = Utc::now();
+        let id = Uuid::new_v4();
+        ctx.workflows.insert(WorkflowRecord {
+            id,
+            chain_id: None,
+            project: "deprank/backend".to_string(),
+            repo: "https://github.com/deprank/backend".to_string(),
+            branch: None,
+            tag: None,
+            rev: None,
+            profile: DEFAULT_PROFILE.to_string(),
+            organization: None,
+            client: Some(key.digest()),
+            // Based on an existing snapshot, so the stages run without fetching anything
+            snapshot_id: Some(Uuid::new_v4()),
+            signatures: Vec::new(),
+            wallet_address: None,
+            state: WorkflowState::Analyzing,
+            transitions: Vec::new(),
+            created_at: now,
+            updated_at: now,
+        });
+        WorkflowService::fail(&ctx, id, "interrupted".to_string()).unwrap();
+
+        let retried = WorkflowService::retry(&ctx, &key, id).await.unwrap();
+        assert_eq!(retried.state, WorkflowState::Analyzing);
+
+        let analyzed = |record: WorkflowRecord| {
+            record.transitions.iter().any(|transition| {
+                transition.from == WorkflowState::Analyzing
+                    && transition.to == WorkflowState::RecordingReceipts
+            })
+        };
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !analyzed(ctx.workflows.get(id).unwrap()) {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("the retried state never ran");
+    }
+
+    fn record(organization: Option<&str>, client: &ClientKey) -> WorkflowRecord {
+        let now = Utc::now();
+        WorkflowRecord {
+            id: Uuid::new_v4(),
+            chain_id: None,
+            project: "acme/app".to_string(),
+            repo: "https://github.com/acme/app".to_string(),
+            branch: None,
+            tag: None,
+            rev: None,
+            profile: DEFAULT_PROFILE.to_string(),
+            organization: organization.map(str::to_string),
+            client: Some(client.digest()),
+            snapshot_id: None,
+            signatures: Vec::new(),
+            wallet_address: None,
+            state: WorkflowState::Completed,
+            transitions: Vec::new(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[tokio::test]
+    async fn workflows_of_other_clients_are_not_found() {
+        let ctx = Context::for_tests().await;
+        let owner = ClientKey::api_key("owner");
+        let other = ClientKey::api_key("other");
+        let record = record(None, &owner);
+        ctx.workflows.insert(record.clone());
+
+        assert!(WorkflowService::authorize(&ctx, &owner, record.id, Role::Owner).is_ok());
+        assert!(matches!(
+            WorkflowService::authorize(&ctx, &other, record.id, Role::Member),
+            Err(ApiError::NotFoundWorkflow(_))
+        ));
+        let params = ListParams::default();
+        let filters = WorkflowListParams::default();
+        let listed =
+            WorkflowService::list(&ctx, &other, &params, &filters, &RequestId("test".to_string()))
+                .unwrap();
+        assert!(listed.data.is_empty());
+    }
+
+    #[tokio::test]
+    async fn members_act_on_the_workflows_of_their_organization_with_their_role() {
+        let ctx = Context::for_tests().await;
+        let member = ClientKey::api_key("member");
+        let client = Some(member.digest());
+        ctx.organizations.insert(Organization {
+            name: "acme".to_string(),
+            display_name: "Acme".to_string(),
+            owners: vec!["acme".to_string()],
+            treasury: None,
+            members: [("alice".to_string(), Member { role: Role::Member, address: None, client })]
+                .into(),
+            created_at: Utc::now(),
+        });
+        let record = record(Some("acme"), &ClientKey::api_key("creator"));
+        ctx.workflows.insert(record.clone());
+
+        assert!(WorkflowService::authorize(&ctx, &member, record.id, Role::Member).is_ok());
+        assert!(matches!(
+            WorkflowService::authorize(&ctx, &member, record.id, Role::Approver),
+            Err(ApiError::NotFoundWorkflow(_))
+        ));
     }
 }
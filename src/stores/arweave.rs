@@ -0,0 +1,82 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Metadata storage on Arweave, uploaded through a bundler which pays for and signs
+//! the transactions.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::Value;
+use tracing::{debug, instrument};
+
+use super::{path_of, required, MetadataStore, MetadataStoreConfig};
+
+/// The gateway documents are retrieved from.
+const ARWEAVE_GATEWAY_URL: &str = "https://arweave.net";
+
+/// Uploads documents to an Arweave bundler.
+pub struct ArweaveStore {
+    client: reqwest::Client,
+    upload_url: String,
+    api_key: String,
+}
+
+impl ArweaveStore {
+    pub fn new(config: &MetadataStoreConfig) -> Result<Self> {
+        Ok(Self {
+            client: reqwest::Client::new(),
+            upload_url: required(&config.arweave_upload_url, "DRK_ARWEAVE_UPLOAD_URL")?,
+            api_key: required(&config.arweave_api_key, "DRK_ARWEAVE_API_KEY")?,
+        })
+    }
+}
+
+#[async_trait]
+impl MetadataStore for ArweaveStore {
+    fn backend(&self) -> &'static str {
+        "arweave"
+    }
+
+    #[instrument(skip_all, fields(%metadata_hash))]
+    async fn put(&self, metadata_json: &str, metadata_hash: &str) -> Result<String> {
+        let response: Value = self
+            .client
+            .post(&self.upload_url)
+            .bearer_auth(&self.api_key)
+            .header("content-type", "application/json")
+            // Tags of the transaction, to find the document by its hash
+            .header("x-tag-content-type", "application/json")
+            .header("x-tag-metadata-hash", metadata_hash)
+            .body(metadata_json.to_string())
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let id = response
+            .get("id")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("Arweave bundler returned no transaction id"))?;
+        debug!(%id, "Metadata uploaded to Arweave");
+        Ok(format!("ar://{id}"))
+    }
+
+    #[instrument(skip_all, fields(%uri))]
+    async fn get(&self, uri: &str) -> Result<String> {
+        let id = path_of(uri, "ar")?;
+        let url = format!("{ARWEAVE_GATEWAY_URL}/{id}");
+        Ok(self.client.get(url).send().await?.error_for_status()?.text().await?)
+    }
+}
@@ -0,0 +1,184 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Metadata storage on IPFS, through the HTTP API of a Kubo node or one of the
+//! supported pinning services.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tracing::{debug, instrument};
+
+use super::{path_of, required, IpfsApi, MetadataStore, MetadataStoreConfig};
+
+/// The boundary of the multipart upload, never part of a JSON document.
+const BOUNDARY: &str = "deprank-metadata-boundary";
+
+/// The public API of Pinata.
+const PINATA_API_URL: &str = "https://api.pinata.cloud";
+
+/// The public API of web3.storage.
+const WEB3_STORAGE_API_URL: &str = "https://api.web3.storage";
+
+/// The gateway documents pinned with Pinata are retrieved from.
+const PINATA_GATEWAY_URL: &str = "https://gateway.pinata.cloud";
+
+/// The gateway documents uploaded to web3.storage are retrieved from.
+const WEB3_STORAGE_GATEWAY_URL: &str = "https://w3s.link";
+
+/// Adds and pins documents with the IPFS HTTP API, or a pinning service.
+pub struct IpfsStore {
+    client: reqwest::Client,
+    api: IpfsApi,
+    api_url: String,
+    token: Option<String>,
+}
+
+impl IpfsStore {
+    pub fn new(config: &MetadataStoreConfig) -> Result<Self> {
+        let api_url = match config.ipfs_api {
+            IpfsApi::Kubo => required(&config.ipfs_api_url, "DRK_IPFS_API_URL")?,
+            IpfsApi::Pinata => api_url(config, PINATA_API_URL),
+            IpfsApi::Web3Storage => api_url(config, WEB3_STORAGE_API_URL),
+        };
+        let token = match config.ipfs_api {
+            IpfsApi::Kubo => config.ipfs_api_token.clone().filter(|token| !token.is_empty()),
+            _ => Some(required(&config.ipfs_api_token, "DRK_IPFS_API_TOKEN")?),
+        };
+        Ok(Self {
+            client: reqwest::Client::new(),
+            api: config.ipfs_api,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            token,
+        })
+    }
+
+    /// Add the document with the HTTP API of a Kubo node, returning its CID.
+    async fn add(&self, metadata_json: &str, metadata_hash: &str) -> Result<String> {
+        let body = format!(
+            "--{BOUNDARY}\r\n\
+             Content-Disposition: form-data; name=\"file\"; filename=\"{metadata_hash}.json\"\r\n\
+             Content-Type: application/json\r\n\r\n\
+             {metadata_json}\r\n\
+             --{BOUNDARY}--\r\n"
+        );
+
+        let request = self
+            .client
+            .post(format!("{}/api/v0/add?pin=true&cid-version=1", self.api_url))
+            .header("content-type", format!("multipart/form-data; boundary={BOUNDARY}"))
+            .body(body);
+        let response = self.send(request).await?;
+        cid(&response, "Hash")
+    }
+
+    /// Pin the document with Pinata, returning its CID.
+    async fn pin(&self, metadata_json: &str, metadata_hash: &str) -> Result<String> {
+        let options = json!({ "cidVersion": 1 });
+        let metadata = json!({ "name": format!("{metadata_hash}.json") });
+        let body = format!(
+            "--{BOUNDARY}\r\n\
+             Content-Disposition: form-data; name=\"file\"; filename=\"{metadata_hash}.json\"\r\n\
+             Content-Type: application/json\r\n\r\n\
+             {metadata_json}\r\n\
+             --{BOUNDARY}\r\n\
+             Content-Disposition: form-data; name=\"pinataOptions\"\r\n\r\n\
+             {options}\r\n\
+             --{BOUNDARY}\r\n\
+             Content-Disposition: form-data; name=\"pinataMetadata\"\r\n\r\n\
+             {metadata}\r\n\
+             --{BOUNDARY}--\r\n"
+        );
+
+        let request = self
+            .client
+            .post(format!("{}/pinning/pinFileToIPFS", self.api_url))
+            .header("content-type", format!("multipart/form-data; boundary={BOUNDARY}"))
+            .body(body);
+        let response = self.send(request).await?;
+        cid(&response, "IpfsHash")
+    }
+
+    /// Upload the document to web3.storage, returning its CID.
+    async fn upload(&self, metadata_json: &str, metadata_hash: &str) -> Result<String> {
+        let request = self
+            .client
+            .post(format!("{}/upload", self.api_url))
+            .header("content-type", "application/json")
+            .header("x-name", format!("{metadata_hash}.json"))
+            .body(metadata_json.to_string());
+        let response = self.send(request).await?;
+        cid(&response, "cid")
+    }
+
+    /// Read a document, with the HTTP API of a Kubo node or the gateway of the service.
+    async fn cat(&self, cid: &str) -> Result<String> {
+        let request = match self.api {
+            IpfsApi::Kubo => self.client.post(format!("{}/api/v0/cat?arg={cid}", self.api_url)),
+            IpfsApi::Pinata => self.client.get(format!("{PINATA_GATEWAY_URL}/ipfs/{cid}")),
+            IpfsApi::Web3Storage => {
+                self.client.get(format!("{WEB3_STORAGE_GATEWAY_URL}/ipfs/{cid}"))
+            }
+        };
+        let request = match (&self.token, self.api) {
+            (Some(token), IpfsApi::Kubo) => request.bearer_auth(token),
+            _ => request,
+        };
+        Ok(request.send().await?.error_for_status()?.text().await?)
+    }
+
+    async fn send(&self, mut request: reqwest::RequestBuilder) -> Result<Value> {
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        Ok(request.send().await?.error_for_status()?.json().await?)
+    }
+}
+
+#[async_trait]
+impl MetadataStore for IpfsStore {
+    fn backend(&self) -> &'static str {
+        "ipfs"
+    }
+
+    #[instrument(skip_all, fields(%metadata_hash))]
+    async fn put(&self, metadata_json: &str, metadata_hash: &str) -> Result<String> {
+        let cid = match self.api {
+            IpfsApi::Kubo => self.add(metadata_json, metadata_hash).await?,
+            IpfsApi::Pinata => self.pin(metadata_json, metadata_hash).await?,
+            IpfsApi::Web3Storage => self.upload(metadata_json, metadata_hash).await?,
+        };
+        debug!(%cid, api = ?self.api, "Metadata added to IPFS");
+        Ok(format!("ipfs://{cid}"))
+    }
+
+    #[instrument(skip_all, fields(%uri))]
+    async fn get(&self, uri: &str) -> Result<String> {
+        self.cat(path_of(uri, "ipfs")?).await
+    }
+}
+
+/// The configured API URL, the public API of the service if unset.
+fn api_url(config: &MetadataStoreConfig, default: &str) -> String {
+    config.ipfs_api_url.clone().filter(|url| !url.is_empty()).unwrap_or_else(|| default.to_string())
+}
+
+/// The CID in the response of the API.
+fn cid(response: &Value, field: &str) -> Result<String> {
+    response
+        .get(field)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("IPFS returned no CID"))
+}
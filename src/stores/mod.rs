@@ -0,0 +1,147 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Storage of the metadata documents referenced by the receipts, whatever the backend.
+//!
+//! A receipt keeps the hash of the metadata of a dependency on chain, next to the URI
+//! of the full document. Documents are stored content-addressed, so storing the same
+//! metadata again returns the same URI.
+
+pub mod arweave;
+pub mod ipfs;
+pub mod s3;
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+
+use self::{arweave::ArweaveStore, ipfs::IpfsStore, s3::S3Store};
+
+#[derive(Clone, clap::Parser)]
+pub struct MetadataStoreConfig {
+    /// Where the metadata documents of the receipts are stored, receipts are disabled
+    /// with `none`.
+    #[clap(long, env = "DRK_METADATA_STORE", value_enum, default_value_t = MetadataBackend::None)]
+    pub metadata_store: MetadataBackend,
+
+    /// The API the documents are added to IPFS with.
+    #[clap(long, env = "DRK_IPFS_API", value_enum, default_value_t = IpfsApi::Kubo)]
+    pub ipfs_api: IpfsApi,
+
+    /// Base URL of the IPFS API, eg. `http://127.0.0.1:5001`, required by `kubo`, the
+    /// public API of the service otherwise.
+    #[clap(long, env = "DRK_IPFS_API_URL")]
+    pub ipfs_api_url: Option<String>,
+
+    /// Bearer token of the IPFS API, required by the pinning services.
+    #[clap(long, env = "DRK_IPFS_API_TOKEN")]
+    pub ipfs_api_token: Option<String>,
+
+    /// Upload URL of the Arweave bundler, eg. `https://bundler.example.com/upload`.
+    #[clap(long, env = "DRK_ARWEAVE_UPLOAD_URL")]
+    pub arweave_upload_url: Option<String>,
+
+    /// API key of the Arweave bundler.
+    #[clap(long, env = "DRK_ARWEAVE_API_KEY")]
+    pub arweave_api_key: Option<String>,
+
+    /// The S3 bucket of the documents.
+    #[clap(long, env = "DRK_S3_BUCKET")]
+    pub s3_bucket: Option<String>,
+
+    /// AWS region of the bucket, eg. `eu-west-1`.
+    #[clap(long, env = "DRK_S3_REGION")]
+    pub s3_region: Option<String>,
+
+    /// Endpoint of an S3 compatible service, eg. `https://minio.example.com`, AWS if unset.
+    #[clap(long, env = "DRK_S3_ENDPOINT")]
+    pub s3_endpoint: Option<String>,
+
+    /// Prefix of the object keys, eg. `metadata/`.
+    #[clap(long, env = "DRK_S3_PREFIX", default_value = "")]
+    pub s3_prefix: String,
+
+    /// AWS access key id allowed to write to the bucket.
+    #[clap(long, env = "DRK_S3_ACCESS_KEY_ID")]
+    pub s3_access_key_id: Option<String>,
+
+    /// AWS secret access key.
+    #[clap(long, env = "DRK_S3_SECRET_ACCESS_KEY")]
+    pub s3_secret_access_key: Option<String>,
+}
+
+/// Where the metadata documents are stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum MetadataBackend {
+    /// Documents are not stored
+    None,
+    /// Added and pinned through the HTTP API of an IPFS node
+    Ipfs,
+    /// Uploaded to Arweave through a bundler
+    Arweave,
+    /// Put in an S3 bucket
+    S3,
+}
+
+/// The API documents are added to IPFS with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum IpfsApi {
+    /// The HTTP API of a Kubo node, adding and pinning documents itself
+    Kubo,
+    /// The pinning API of Pinata
+    Pinata,
+    /// The upload API of web3.storage
+    Web3Storage,
+}
+
+/// A backend storing metadata documents, eg. IPFS.
+#[async_trait]
+pub trait MetadataStore: Send + Sync {
+    /// The name of the backend, eg. `ipfs`
+    fn backend(&self) -> &'static str;
+
+    /// Store a metadata document, with the hash of its content as stored on chain,
+    /// returning the URI it is retrieved from, eg. `ipfs://<cid>`.
+    async fn put(&self, metadata_json: &str, metadata_hash: &str) -> Result<String>;
+
+    /// Retrieve a metadata document from the URI it was stored at.
+    async fn get(&self, uri: &str) -> Result<String>;
+}
+
+/// Build the store of the configuration, `None` when receipts are disabled.
+pub fn new(config: &MetadataStoreConfig) -> Result<Option<Arc<dyn MetadataStore>>> {
+    Ok(match config.metadata_store {
+        MetadataBackend::None => None,
+        MetadataBackend::Ipfs => Some(Arc::new(IpfsStore::new(config)?)),
+        MetadataBackend::Arweave => Some(Arc::new(ArweaveStore::new(config)?)),
+        MetadataBackend::S3 => Some(Arc::new(S3Store::new(config)?)),
+    })
+}
+
+/// A setting the backend cannot work without.
+fn required(value: &Option<String>, name: &str) -> Result<String> {
+    value
+        .clone()
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| anyhow!("{name} is required to store metadata"))
+}
+
+/// The path of a URI of the scheme of the backend, eg. the CID of `ipfs://<cid>`.
+fn path_of<'a>(uri: &'a str, scheme: &str) -> Result<&'a str> {
+    uri.strip_prefix(scheme)
+        .and_then(|uri| uri.strip_prefix("://"))
+        .filter(|path| !path.is_empty())
+        .ok_or_else(|| anyhow!("`{uri}` is not a {scheme} URI"))
+}
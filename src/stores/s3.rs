@@ -0,0 +1,172 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Metadata storage in an S3 bucket, on AWS or any S3 compatible service.
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use tracing::{debug, instrument};
+use url::Url;
+
+use super::{required, MetadataStore, MetadataStoreConfig};
+
+/// Puts documents in an S3 bucket, named after their hash.
+pub struct S3Store {
+    client: reqwest::Client,
+    /// The scheme and host of the requests
+    origin: String,
+    host: String,
+    /// The path of the bucket, empty with virtual-hosted style requests
+    bucket_path: String,
+    prefix: String,
+    region: String,
+    access_key_id: String,
+    secret_access_key: String,
+}
+
+impl S3Store {
+    pub fn new(config: &MetadataStoreConfig) -> Result<Self> {
+        let bucket = required(&config.s3_bucket, "DRK_S3_BUCKET")?;
+        let region = required(&config.s3_region, "DRK_S3_REGION")?;
+
+        // S3 compatible services expect path-style requests, AWS virtual-hosted ones.
+        let (origin, host, bucket_path) =
+            match config.s3_endpoint.as_deref().filter(|endpoint| !endpoint.is_empty()) {
+                Some(endpoint) => {
+                    let url = Url::parse(endpoint)?;
+                    let Some(host) = url.host_str() else {
+                        bail!("Invalid DRK_S3_ENDPOINT `{endpoint}`");
+                    };
+                    let host = match url.port() {
+                        Some(port) => format!("{host}:{port}"),
+                        None => host.to_string(),
+                    };
+                    (format!("{}://{host}", url.scheme()), host, format!("/{bucket}"))
+                }
+                None => {
+                    let host = format!("{bucket}.s3.{region}.amazonaws.com");
+                    (format!("https://{host}"), host, String::new())
+                }
+            };
+
+        Ok(Self {
+            client: reqwest::Client::new(),
+            origin,
+            host,
+            bucket_path,
+            prefix: config.s3_prefix.clone(),
+            region,
+            access_key_id: required(&config.s3_access_key_id, "DRK_S3_ACCESS_KEY_ID")?,
+            secret_access_key: required(&config.s3_secret_access_key, "DRK_S3_SECRET_ACCESS_KEY")?,
+        })
+    }
+
+    /// The `Authorization` header of a PUT or GET request, per AWS Signature Version 4.
+    fn sign(&self, method: &str, path: &str, amz_date: &str, payload_hash: &str) -> String {
+        // Only the documents put have a content type.
+        let (content_type, signed_headers) = match method {
+            "PUT" => (
+                "content-type:application/json\n",
+                "content-type;host;x-amz-content-sha256;x-amz-date",
+            ),
+            _ => ("", "host;x-amz-content-sha256;x-amz-date"),
+        };
+
+        let hmac = |key: &[u8], data: &str| {
+            let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+            mac.update(data.as_bytes());
+            mac.finalize().into_bytes().to_vec()
+        };
+
+        let date = &amz_date[..8];
+        let scope = format!("{date}/{}/s3/aws4_request", self.region);
+        let canonical = format!(
+            "{method}\n{path}\n\n{content_type}host:{}\n\
+             x-amz-content-sha256:{payload_hash}\nx-amz-date:{amz_date}\n\n\
+             {signed_headers}\n{payload_hash}",
+            self.host,
+        );
+        let to_sign = format!(
+            "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+            hex::encode(Sha256::digest(canonical.as_bytes())),
+        );
+
+        let key = hmac(format!("AWS4{}", self.secret_access_key).as_bytes(), date);
+        let key = hmac(&key, &self.region);
+        let key = hmac(&key, "s3");
+        let key = hmac(&key, "aws4_request");
+        let signature = hex::encode(hmac(&key, &to_sign));
+
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, \
+             Signature={signature}",
+            self.access_key_id,
+        )
+    }
+}
+
+#[async_trait]
+impl MetadataStore for S3Store {
+    fn backend(&self) -> &'static str {
+        "s3"
+    }
+
+    #[instrument(skip_all, fields(%metadata_hash))]
+    async fn put(&self, metadata_json: &str, metadata_hash: &str) -> Result<String> {
+        // The hash is hex and the prefix is configured, so the key needs no encoding.
+        let path = format!("{}/{}{metadata_hash}.json", self.bucket_path, self.prefix);
+        let payload_hash = hex::encode(Sha256::digest(metadata_json.as_bytes()));
+        let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let authorization = self.sign("PUT", &path, &amz_date, &payload_hash);
+
+        let url = format!("{}{path}", self.origin);
+        self.client
+            .put(&url)
+            .header("content-type", "application/json")
+            .header("x-amz-content-sha256", payload_hash)
+            .header("x-amz-date", amz_date)
+            .header("authorization", authorization)
+            .body(metadata_json.to_string())
+            .send()
+            .await?
+            .error_for_status()?;
+
+        debug!(%url, "Metadata put in S3");
+        Ok(url)
+    }
+
+    #[instrument(skip_all, fields(%uri))]
+    async fn get(&self, uri: &str) -> Result<String> {
+        let Some(path) = uri.strip_prefix(&self.origin).filter(|path| path.starts_with('/')) else {
+            bail!("`{uri}` is not in the configured bucket");
+        };
+        let payload_hash = hex::encode(Sha256::digest(b""));
+        let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let authorization = self.sign("GET", path, &amz_date, &payload_hash);
+
+        let response = self
+            .client
+            .get(uri)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("x-amz-date", amz_date)
+            .header("authorization", authorization)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(response.text().await?)
+    }
+}
@@ -12,60 +12,351 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use utoipa::OpenApi;
-use utoipa_swagger_ui::SwaggerUi;
+use utoipa::{
+    openapi::security::{ApiKey, ApiKeyValue, SecurityScheme},
+    Modify, OpenApi,
+};
+use utoipa_swagger_ui::{Config, SwaggerUi};
 
-use crate::{handlers, requests, responses};
+use crate::{
+    handlers, middlewares, middlewares::ratelimit::API_KEY, notifiers, requests, responses,
+};
+
+/// The name of the API key security scheme.
+pub const API_KEY_SCHEME: &str = "api_key";
 
 #[derive(OpenApi)]
 #[openapi(
     paths(
+        handlers::address::delete,
+        handlers::address::get,
+        handlers::address::put,
+
         handlers::airdrop::get,
         handlers::airdrop::submit,
 
+        handlers::allocation::batch,
         handlers::allocation::get,
         handlers::allocation::list,
 
+        handlers::artifact::download,
+        handlers::artifact::get,
+        handlers::artifact::list,
+
+        handlers::attestation::accept,
+        handlers::attestation::current,
+        handlers::attestation::get,
+        handlers::attestation::list,
+        handlers::attestation::publish,
+
+        handlers::batch::read,
+
+        handlers::cost::get,
+
+        handlers::credential::get,
+        handlers::credential::keys,
+
+        handlers::claim::build,
+        handlers::claim::execute,
+
         handlers::contribution::get,
         handlers::contribution::list,
+        handlers::contribution::recompute,
 
         handlers::contributor::get,
         handlers::contributor::list,
 
         handlers::dependency::get,
+        handlers::dependency::graph,
         handlers::dependency::list,
 
+        handlers::event::subscribe,
+
+        handlers::execution::preview,
+
+        handlers::github::get,
+        handlers::github::list,
+        handlers::github::setup,
+        handlers::github::webhook,
+
+        handlers::health::get,
+        handlers::health::metrics,
+
+        handlers::identity::get,
+        handlers::identity::merge,
+        handlers::identity::split,
+
+        handlers::ledger::get,
+
+        handlers::organization::delete,
+        handlers::organization::delete_member,
+        handlers::organization::get,
+        handlers::organization::put,
+        handlers::organization::put_member,
+
+        handlers::pool::get,
+
+        handlers::maintainer::build,
+        handlers::maintainer::execute,
+        handlers::maintainer::get,
+        handlers::maintainer::link,
+        handlers::maintainer::list,
+
+        handlers::metadata::schema,
+        handlers::metadata::validate,
+        handlers::metadata::verify,
+
+        handlers::notification::get,
+        handlers::notification::get_owner_channels,
+        handlers::notification::get_workflow_channels,
+        handlers::notification::put,
+        handlers::notification::put_owner_channels,
+        handlers::notification::put_workflow_channels,
+
+        handlers::policy::delete,
+        handlers::policy::get,
+        handlers::policy::put,
+
+        handlers::profile::delete,
+        handlers::profile::get,
+        handlers::profile::list,
+        handlers::profile::put,
+
         handlers::project::get,
 
+        handlers::ranking::ecosystem,
+        handlers::ranking::global,
+
+        handlers::quota::get,
+
+        handlers::ratelimit::get,
+
+        handlers::report::get,
+
+        handlers::sign::create,
+
+        handlers::snapshot::diff,
+        handlers::snapshot::get,
+        handlers::snapshot::list,
+
+        handlers::step::chain,
+        handlers::step::list,
+
+        handlers::transaction::list,
+
+        handlers::treasury::preflight,
+
+        handlers::usage::aggregate,
+        handlers::usage::get,
+
         handlers::wallet::bind,
         handlers::wallet::unbind,
 
+        handlers::webhook::create,
+        handlers::webhook::delete,
+
         handlers::workflow::create,
         handlers::workflow::delete,
         handlers::workflow::get,
+        handlers::workflow::list,
+        handlers::workflow::cancel,
+        handlers::workflow::retry,
     ),
     components(
         schemas(
+            requests::address::AddressBookRequest,
+            requests::address::AddressRequest,
+            requests::address::Chain,
+            requests::allocation::AllocationItem,
+            requests::allocation::CreateAllocationsRequest,
+            requests::attestation::AttestationRequest,
+            requests::attestation::TermsRequest,
+            requests::batch::BatchRequest,
+            requests::claim::BuildClaimRequest,
+            requests::claim::ExecuteClaimRequest,
+            requests::event::SubscriptionRequest,
+            notifiers::NotificationKind,
+            requests::identity::IdentityRequest,
+            requests::maintainer::SignedClaimRequest,
+            requests::notification::NotificationChannelsRequest,
+            requests::notification::NotificationPreferencesRequest,
+            requests::organization::MemberRequest,
+            requests::organization::OrganizationRequest,
+            requests::organization::Role,
+            requests::policy::DependencyPolicyRequest,
+            requests::profile::DecayCurve,
+            requests::profile::KindWeights,
+            requests::profile::Weights,
+            requests::sign::SignRequest,
             requests::wallet::WalletAddressRequest,
+            requests::webhook::WebhookRequest,
+            requests::workflow::Budget,
             requests::workflow::CreateWorkflowRequest,
+            requests::workflow::Denomination,
 
+            middlewares::ratelimit::RateLimitState,
+
+            responses::address::AddressBookResponse,
+            responses::address::AddressResponse,
+            responses::allocation::CreateAllocationsResponse,
+            responses::allocation::CreatedAllocation,
+            responses::artifact::ArtifactName,
+            responses::artifact::ArtifactResponse,
+            responses::artifact::ArtifactUrlResponse,
+            responses::attestation::AttestationResponse,
+            responses::attestation::TermsResponse,
+            responses::batch::BatchItemResponse,
+            responses::batch::BatchResponse,
+            responses::claim::ClaimExecutedResponse,
+            responses::claim::ClaimResponse,
+            responses::contribution::ContributionResponse,
+            responses::contribution::RecomputeResponse,
+            responses::contributor::ContributorResponse,
+            responses::cost::BudgetCostResponse,
+            responses::cost::CostEstimateResponse,
+            responses::cost::CostStep,
+            responses::cost::StepCostResponse,
+            responses::credential::CredentialResponse,
+            responses::credential::FundedDependency,
+            responses::credential::FundingCredential,
+            responses::credential::FundingSubject,
+            responses::credential::KeyResponse,
+            responses::credential::KeySetResponse,
+            responses::dependency::DependencyFlagKind,
+            responses::dependency::DependencyFlagResponse,
+            responses::dependency::DependencyGraphResponse,
+            responses::dependency::DependencyKind,
+            responses::dependency::DependencyResponse,
+            responses::dependency::Ecosystem,
+            responses::dependency::GraphEdgeResponse,
+            responses::dependency::GraphNodeResponse,
+            responses::dependency::ScoreExplanationResponse,
+            responses::event::Event,
+            responses::event::EventKind,
+            responses::event::EventMessage,
+            responses::execution::ExecutionPreviewResponse,
+            responses::execution::PayoutBatchResponse,
+            responses::execution::SkippedAllocationResponse,
+            responses::github::InstallationResponse,
+            responses::github::RepositorySelection,
+            responses::health::BreakerState,
+            responses::health::ChainHealthResponse,
+            responses::health::HealthResponse,
+            responses::health::HealthStatus,
+            responses::identity::IdentityResponse,
+            responses::ledger::LedgerBalanceResponse,
+            responses::ledger::LedgerEntryResponse,
+            responses::ledger::LedgerResponse,
+            responses::ledger::Side,
+            responses::list::Pagination,
+            responses::maintainer::ClaimStatus,
+            responses::maintainer::MaintainerClaimResponse,
+            responses::maintainer::MaintainerClaimsResponse,
+            responses::pool::PoolBalanceResponse,
+            responses::pool::PoolResponse,
+            responses::metadata::MetadataResponse,
+            responses::metadata::MetadataVerificationResponse,
+            responses::notification::NotificationChannelsResponse,
+            responses::notification::NotificationPreferencesResponse,
+            responses::organization::MemberResponse,
+            responses::organization::OrganizationResponse,
+            responses::policy::DependencyPolicyResponse,
+            responses::profile::WeightProfileResponse,
             responses::project::ProjectResponse,
+            responses::quota::LimitsResponse,
+            responses::quota::QuotaResponse,
+            responses::ranking::EcosystemRankingResponse,
+            responses::ranking::FundingResponse,
+            responses::ranking::GlobalRankingResponse,
+            responses::sign::SignResponse,
+            responses::snapshot::RankingDiffResponse,
+            responses::snapshot::ScoreChangeResponse,
+            responses::snapshot::SnapshotDetailResponse,
+            responses::snapshot::SnapshotResponse,
+            responses::step::ChainedStepResponse,
+            responses::step::StepKind,
+            responses::step::StepResponse,
+            responses::step::TransactionChainResponse,
+            responses::transaction::JournalStatus,
+            responses::transaction::TransactionCallResponse,
+            responses::transaction::TransactionResponse,
+            responses::treasury::TreasuryRequirementResponse,
+            responses::treasury::TreasuryResponse,
+            responses::usage::UsageAggregateResponse,
+            responses::usage::UsageResponse,
+            responses::validation::FieldErrorResponse,
+            responses::webhook::WebhookEvent,
+            responses::webhook::WebhookResponse,
             responses::workflow::WorkflowResponse,
+            responses::workflow::WorkflowState,
+            responses::workflow::WorkflowSummaryResponse,
         )
     ),
+    modifiers(&SecurityAddon),
+    security((), ("api_key" = [])),
     tags(
+        (name = "Address", description = "The Address book Service Handlers"),
         (name = "Airdrop", description = "The Airdrop Service Handlers"),
         (name = "Allocation", description = "The Allocation Service Handlers"),
+        (name = "Artifact", description = "The analysis Artifact Service Handlers"),
+        (name = "Attestation", description = "The Terms attestation Service Handlers"),
+        (name = "Batch", description = "The Batch Service Handlers"),
+        (name = "Claim", description = "The Claim Service Handlers"),
         (name = "Contribution", description = "The Contribution Service Handlers"),
         (name = "Contributor", description = "The Contributor Service Handlers"),
+        (name = "Cost", description = "The workflow Cost Service Handlers"),
+        (name = "Credential", description = "The Credential Service Handlers"),
         (name = "Dependency", description = "The Dependency Service Handlers"),
+        (name = "Event", description = "The live Event Service Handlers"),
+        (name = "Execution", description = "The Execution Service Handlers"),
+        (name = "GitHub", description = "The GitHub App Service Handlers"),
+        (name = "Health", description = "The Health Service Handlers"),
+        (name = "Identity", description = "The contributor Identity Service Handlers"),
+        (name = "Ledger", description = "The Ledger Service Handlers"),
+        (name = "Maintainer", description = "The Maintainer claim portal Service Handlers"),
+        (name = "Metadata", description = "The dependency Metadata Service Handlers"),
+        (name = "Notification", description = "The Notification Service Handlers"),
+        (name = "Organization", description = "The Organization Service Handlers"),
+        (name = "Pool", description = "The Pool Service Handlers"),
+        (name = "Policy", description = "The Dependency policy Service Handlers"),
+        (name = "Profile", description = "The Ranking profile Service Handlers"),
         (name = "Project", description = "The Project Service Handlers"),
+        (name = "Quota", description = "The Quota Service Handlers"),
+        (name = "Ranking", description = "The Ranking Service Handlers"),
+        (name = "RateLimit", description = "The Rate Limit Service Handlers"),
+        (name = "Report", description = "The Report Service Handlers"),
+        (name = "Sign", description = "The Sign Service Handlers"),
+        (name = "Snapshot", description = "The Snapshot Service Handlers"),
+        (name = "Step", description = "The on-chain Step Service Handlers"),
+        (name = "Transaction", description = "The Transaction Service Handlers"),
+        (name = "Treasury", description = "The Treasury Service Handlers"),
+        (name = "Usage", description = "The Usage Service Handlers"),
         (name = "Wallet", description = "The Wallet address Service Handlers"),
+        (name = "Webhook", description = "The outbound Webhook Service Handlers"),
         (name = "Workflow", description = "The Workflow Service Handlers"),
     ),
 )]
 pub struct ApiDoc;
 
+/// Register the security schemes, so the "Authorize" button of the Swagger UI
+/// can attach credentials to the requests it sends.
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            API_KEY_SCHEME,
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::with_description(
+                API_KEY,
+                "The API key of the client, required except for the public read-only endpoints.",
+            ))),
+        );
+    }
+}
+
 pub fn build() -> SwaggerUi {
-    SwaggerUi::new("/swagger").url("/openapi.json", ApiDoc::openapi())
+    SwaggerUi::new("/swagger")
+        .url("/openapi.json", ApiDoc::openapi())
+        .config(Config::default().persist_authorization(true))
 }
@@ -0,0 +1,133 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Trace context propagation.
+//!
+//! Every request gets a [`TraceContext`] (taken from an incoming W3C `traceparent`
+//! header or freshly generated) which is kept in a task-local for the lifetime of
+//! the request. Background jobs spawned with [`spawn`] inherit both the context and
+//! the current tracing span, and outgoing HTTP calls (RPC, webhooks) attach it via
+//! [`headers`], so a single workflow can be followed end-to-end.
+
+use std::future::Future;
+
+use axum::http::{HeaderMap, HeaderValue};
+use tokio::task::JoinHandle;
+use tracing::{Instrument, Span};
+use uuid::Uuid;
+
+/// The W3C trace context header name.
+pub const TRACEPARENT: &str = "traceparent";
+
+tokio::task_local! {
+    static CURRENT: TraceContext;
+}
+
+/// A W3C trace context, see <https://www.w3.org/TR/trace-context/>.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceContext {
+    /// 16 bytes trace id, hex encoded.
+    pub trace_id: String,
+    /// 8 bytes id of the parent span, hex encoded.
+    pub span_id: String,
+}
+
+impl Default for TraceContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TraceContext {
+    /// Start a new trace.
+    pub fn new() -> Self {
+        Self { trace_id: Uuid::new_v4().simple().to_string(), span_id: new_span_id() }
+    }
+
+    /// Parse the context from a `traceparent` header value,
+    /// eg. `00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01`.
+    pub fn parse(value: &str) -> Option<Self> {
+        let parts: Vec<&str> = value.trim().split('-').collect();
+        match parts.as_slice() {
+            [version, trace_id, span_id, _flags]
+                if version.len() == 2 &&
+                    is_hex(trace_id, 32) &&
+                    is_hex(span_id, 16) &&
+                    trace_id.bytes().any(|b| b != b'0') =>
+            {
+                Some(Self {
+                    trace_id: trace_id.to_ascii_lowercase(),
+                    span_id: span_id.to_ascii_lowercase(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Extract the context from request headers.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        headers.get(TRACEPARENT).and_then(|v| v.to_str().ok()).and_then(Self::parse)
+    }
+
+    /// Derive a child context, keeping the trace id and using a new span id.
+    pub fn child(&self) -> Self {
+        Self { trace_id: self.trace_id.clone(), span_id: new_span_id() }
+    }
+
+    /// Format the context as a `traceparent` header value.
+    pub fn traceparent(&self) -> String {
+        format!("00-{}-{}-01", self.trace_id, self.span_id)
+    }
+
+    /// Format the context as a header value.
+    pub fn header_value(&self) -> HeaderValue {
+        HeaderValue::from_str(&self.traceparent()).expect("traceparent is valid ASCII")
+    }
+}
+
+/// Get the trace context of the current task, if any.
+pub fn current() -> Option<TraceContext> {
+    CURRENT.try_with(Clone::clone).ok()
+}
+
+/// Run the future with `ctx` as the current trace context.
+pub async fn scope<F: Future>(ctx: TraceContext, fut: F) -> F::Output {
+    CURRENT.scope(ctx, fut).await
+}
+
+/// Spawn a background task which inherits the current trace context and span.
+pub fn spawn<F>(fut: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let ctx = current().map(|ctx| ctx.child()).unwrap_or_default();
+    tokio::spawn(CURRENT.scope(ctx, fut.instrument(Span::current())))
+}
+
+/// The headers to attach to outgoing requests so the receiver joins the current trace.
+pub fn headers() -> Vec<(String, String)> {
+    match current() {
+        Some(ctx) => vec![(TRACEPARENT.to_string(), ctx.child().traceparent())],
+        None => Vec::new(),
+    }
+}
+
+fn new_span_id() -> String {
+    Uuid::new_v4().simple().to_string()[..16].to_string()
+}
+
+fn is_hex(value: &str, len: usize) -> bool {
+    value.len() == len && value.bytes().all(|b| b.is_ascii_hexdigit())
+}
@@ -0,0 +1,82 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The checkout cleanup worker.
+//!
+//! Periodically prunes the repository checkouts older than the retention, keeping the
+//! cache directory bounded on long-running deployments. The results of an analysis are
+//! persisted once it finishes, so an expired checkout is only needed to analyze it again,
+//! when it is downloaded anew.
+
+use std::{sync::Arc, time::Duration};
+
+use tokio::task::JoinHandle;
+use tracing::{info, instrument, warn};
+
+use crate::{
+    context::Context,
+    services::storage::{CheckoutCleanup, StorageService},
+    telemetry,
+};
+
+#[derive(Clone, clap::Parser)]
+pub struct CleanupConfig {
+    /// Seconds between two runs of the checkout cleanup worker.
+    #[clap(long, env = "DRK_CLEANUP_INTERVAL", default_value_t = 3600)]
+    pub cleanup_interval: u64,
+
+    /// Hours a repository checkout is kept after it is downloaded.
+    #[clap(long, env = "DRK_CHECKOUT_RETENTION", default_value_t = 24)]
+    pub checkout_retention: u64,
+
+    /// What is left of a checkout once expired.
+    #[clap(
+        long,
+        env = "DRK_CHECKOUT_CLEANUP",
+        value_enum,
+        default_value_t = CheckoutCleanup::Manifests
+    )]
+    pub checkout_cleanup: CheckoutCleanup,
+}
+
+/// Spawn the worker, running until the process exits.
+pub fn spawn(ctx: Arc<Context>) -> JoinHandle<()> {
+    telemetry::spawn(async move {
+        let period = Duration::from_secs(ctx.config.cleanup_config.cleanup_interval.max(1));
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            run(&ctx).await;
+        }
+    })
+}
+
+/// Prune the checkouts older than the retention.
+#[instrument(skip_all)]
+pub async fn run(ctx: &Context) {
+    let config = &ctx.config.cleanup_config;
+    let cache_dir = ctx.config.cache_dir.clone();
+    let retention = Duration::from_secs(config.checkout_retention * 3600);
+    let cleanup = config.checkout_cleanup;
+
+    let pruned =
+        tokio::task::spawn_blocking(move || StorageService::prune(&cache_dir, retention, cleanup))
+            .await;
+    match pruned {
+        Ok(Ok(0)) => {}
+        Ok(Ok(pruned)) => info!(pruned, ?cleanup, "Checkouts pruned"),
+        Ok(Err(e)) => warn!("Failed to prune checkouts: {e:#}"),
+        Err(e) => warn!("Failed to prune checkouts: {e}"),
+    }
+}
@@ -0,0 +1,380 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The allocation execution worker.
+//!
+//! Periodically pays out approved allocations, batched into as few transactions as
+//! possible, follows the submitted transactions until they are confirmed, and settles
+//! each allocation as Executed or Failed, both on chain and in the allocation store.
+//! Payouts the treasury cannot fund are held back until it is funded. A submission
+//! which may have reached the node is followed by the hash of its transaction, the
+//! recovery worker paying it out again if it never shows up. Failed submissions are
+//! retried with an exponential backoff, up to a maximum number of
+//! attempts, and the amount of failed allocations is released to the budget pool of
+//! their workflow.
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use num_bigint::BigUint;
+use serde_json::json;
+use tokio::task::JoinHandle;
+use tracing::{info, instrument, warn};
+use uuid::Uuid;
+
+use crate::{
+    context::Context,
+    contracts::{
+        allocation::{AllocationContract, Status as AllocationStatus},
+        error::ContractError,
+        token::{Token, TokenContract},
+        transaction::{TransactionContract, TransactionStatus},
+        types::{Address, Hash, Id},
+    },
+    errors::ApiError,
+    notifiers::Notification,
+    responses::webhook::WebhookEvent,
+    services::{
+        allocation::{AllocationRecord, ExecutionStatus},
+        batching::BatchingService,
+        notification::NotificationService,
+        payout::{PayoutError, PayoutService, PreparedPayout},
+        treasury::{Requirements, TreasuryService},
+        webhook::WebhookService,
+    },
+    telemetry,
+};
+
+#[derive(Clone, clap::Parser)]
+pub struct ExecutionConfig {
+    /// Seconds between two runs of the allocation execution worker.
+    #[clap(long, env = "DRK_EXECUTION_INTERVAL", default_value_t = 15)]
+    pub execution_interval: u64,
+
+    /// Attempts to pay out an allocation before marking it as failed.
+    #[clap(long, env = "DRK_EXECUTION_MAX_ATTEMPTS", default_value_t = 5)]
+    pub execution_max_attempts: u32,
+}
+
+/// Spawn the worker, running until the process exits.
+pub fn spawn(ctx: Arc<Context>) -> JoinHandle<()> {
+    telemetry::spawn(async move {
+        let period = Duration::from_secs(ctx.config.execution_config.execution_interval.max(1));
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            run(&ctx).await;
+        }
+    })
+}
+
+/// Run a single pass over the pending allocations.
+#[instrument(skip_all)]
+pub async fn run(ctx: &Context) {
+    // Leave the allocations alone while the chain is unreachable, so that refused calls
+    // do not use up their attempts.
+    if ctx.contract.breaker().is_open() {
+        return;
+    }
+
+    for record in ctx.allocations.by_status(ExecutionStatus::Submitted) {
+        confirm(ctx, record).await;
+    }
+
+    let now = Instant::now();
+    let records = ctx
+        .allocations
+        .by_status(ExecutionStatus::Approved)
+        .into_iter()
+        .filter(|record| record.retry_at.is_none_or(|at| at <= now))
+        .collect();
+    execute(ctx, records).await;
+}
+
+/// Pay out the allocations, batching them into as few transactions as possible.
+#[instrument(skip_all, fields(allocations = records.len()))]
+async fn execute(ctx: &Context, records: Vec<AllocationRecord>) {
+    let mut payouts = vec![];
+    for record in records {
+        match prepare(ctx, &record).await {
+            Ok(payout) => payouts.push(payout),
+            Err(e) => retry(ctx, &record, e).await,
+        }
+    }
+    let mut payouts = funded(ctx, payouts).await;
+    // Reserved for a sponsored claim while being prepared
+    payouts.retain(|payout| {
+        ctx.allocations
+            .get(&payout.allocation_id)
+            .is_some_and(|record| record.status == ExecutionStatus::Approved)
+    });
+    if payouts.is_empty() {
+        return;
+    }
+
+    let config = &ctx.config.batching_config;
+    for batch in BatchingService::plan(ctx.contract.as_ref(), config, payouts).await {
+        let result = PayoutService::submit(ctx.contract.as_ref(), &batch.payouts).await;
+        for payout in &batch.payouts {
+            match &result {
+                Ok(tx_hash) => submitted(ctx, &payout.allocation_id, tx_hash.clone(), None).await,
+                Err(e) => match e.downcast_ref() {
+                    // Nothing was sent, the payout is attempted again on the next run.
+                    Some(ContractError::DryRun(_)) => {
+                        let error = Some(e.to_string());
+                        update(ctx, &payout.allocation_id, |record| record.error = error).await;
+                    }
+                    // Neither retried nor failed, which could pay it out twice or release
+                    // the budget of a payout on its way.
+                    Some(ContractError::Ambiguous { tx_hash, .. }) => {
+                        let error = Some(e.to_string());
+                        submitted(ctx, &payout.allocation_id, tx_hash.clone(), error).await;
+                    }
+                    _ => {
+                        if let Some(record) = ctx.allocations.get(&payout.allocation_id) {
+                            retry(ctx, &record, anyhow!("{e}")).await;
+                        }
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// Follow the payout transaction of the allocation until it is confirmed.
+async fn submitted(ctx: &Context, id: &Id, tx_hash: Hash, error: Option<String>) {
+    update(ctx, id, |record| {
+        record.status = ExecutionStatus::Submitted;
+        record.tx_hash = Some(tx_hash);
+        record.submitted_at = Some(Instant::now());
+        record.error = error;
+    })
+    .await;
+}
+
+/// Hold back the payouts the treasury cannot fund, until it is funded.
+async fn funded(ctx: &Context, payouts: Vec<PreparedPayout>) -> Vec<PreparedPayout> {
+    let mut requirements = Requirements::new();
+    // Payouts from multisig wallets are checked against their balances on submission.
+    for payout in payouts.iter().filter(|payout| payout.owner.is_none()) {
+        let (_, amount) = requirements
+            .entry(payout.token_address.clone())
+            .or_insert((payout.token.clone(), BigUint::ZERO));
+        *amount += &payout.amount;
+    }
+
+    let treasury = match TreasuryService::check(ctx, requirements).await {
+        Ok(treasury) if !treasury.funded => treasury,
+        Ok(_) => return payouts,
+        Err(e) => {
+            // The balances are checked again on submission.
+            warn!("Failed to check the treasury: {e}");
+            return payouts;
+        }
+    };
+
+    let short: Vec<_> = treasury
+        .requirements
+        .iter()
+        .filter(|requirement| requirement.shortfall != "0")
+        .filter_map(|requirement| requirement.token_address.parse::<Address>().ok())
+        .collect();
+    let fee_address = PayoutService::token_address(
+        ctx.contract.as_ref(),
+        &Token::Native(ctx.contract.fee_token()),
+    );
+    let error = ApiError::InsufficientTreasury(Box::new(treasury)).to_string();
+    warn!("Holding back payouts: {error}");
+
+    let (held, payouts): (Vec<_>, Vec<_>) = payouts.into_iter().partition(|payout| {
+        short.contains(&fee_address)
+            || (payout.owner.is_none() && short.contains(&payout.token_address))
+    });
+    for payout in held {
+        update(ctx, &payout.allocation_id, |record| record.error = Some(error.clone())).await;
+    }
+    payouts
+}
+
+/// Build and encode the payout of the allocation, keeping the converted amount.
+async fn prepare(ctx: &Context, record: &AllocationRecord) -> Result<PreparedPayout> {
+    let (payout, usd_rate) = PayoutService::for_allocation(ctx, record).await?;
+    update(ctx, &record.id, |record| {
+        if usd_rate.is_some() {
+            record.usd_rate = usd_rate;
+        }
+        record.token_amount = Some(payout.amount.clone());
+    })
+    .await;
+
+    PayoutService::prepare(ctx.contract.as_ref(), &payout).await
+}
+
+/// Schedule another payout attempt after a failure, or give up.
+#[instrument(skip_all, fields(allocation_id = %record.id))]
+async fn retry(ctx: &Context, record: &AllocationRecord, e: anyhow::Error) {
+    let config = &ctx.config.execution_config;
+    let attempts = record.attempts + 1;
+    if let Some(e) = e.downcast_ref::<PayoutError>() {
+        fail(ctx, &record.id, e.to_string()).await;
+        return;
+    }
+    // The allocation would be rejected again.
+    if let Some(e) = e.downcast_ref::<ContractError>().filter(|e| e.is_invalid_input()) {
+        fail(ctx, &record.id, e.to_string()).await;
+        return;
+    }
+    if attempts >= config.execution_max_attempts {
+        fail(ctx, &record.id, format!("Gave up after {attempts} attempts: {e}")).await;
+        return;
+    }
+
+    let backoff = Duration::from_secs(config.execution_interval << attempts.min(10));
+    warn!(attempts, ?backoff, "Payout failed, retrying later: {e}");
+    update(ctx, &record.id, |record| {
+        record.attempts = attempts;
+        record.retry_at = Some(Instant::now() + backoff);
+        record.error = Some(e.to_string());
+    })
+    .await;
+}
+
+/// Settle the submitted allocation once its payout transaction is confirmed or reverted.
+#[instrument(skip_all, fields(allocation_id = %record.id))]
+pub async fn confirm(ctx: &Context, record: AllocationRecord) {
+    let Some(tx_hash) = record.tx_hash.clone() else {
+        return;
+    };
+
+    match ctx.contract.transaction_status(tx_hash.clone()).await {
+        // The node may not know about a transaction which was just submitted, the
+        // recovery worker re-submits it if it was dropped.
+        Ok(TransactionStatus::Pending | TransactionStatus::NotFound) => {}
+        Ok(TransactionStatus::Succeeded) => {
+            // Stay submitted on error, so the next pass tries to settle again.
+            if let Err(e) = ctx
+                .contract
+                .update_allocation_status(record.id.clone(), AllocationStatus::Executed)
+                .await
+            {
+                warn!("Failed to mark allocation as executed: {e}");
+                return;
+            }
+            info!("Allocation executed");
+            update(ctx, &record.id, |record| {
+                record.status = ExecutionStatus::Executed;
+                record.executed_at = Some(Instant::now());
+            })
+            .await;
+            if let Some(record) = ctx.allocations.get(&record.id) {
+                if let Err(e) = ctx.ledger.post_payout(&record).await {
+                    warn!("Failed to post the payout to the ledger: {e}");
+                }
+                executed(ctx, &record, tx_hash).await;
+                completed(ctx, record.workflow_id).await;
+            }
+        }
+        Ok(TransactionStatus::Reverted(reason)) => {
+            fail(ctx, &record.id, format!("Payout transaction reverted: {reason}")).await;
+        }
+        Err(e) => warn!("Failed to get payout transaction status: {e}"),
+    }
+}
+
+/// Apply `f` to the allocation, warning if the change could not be written.
+pub async fn update(ctx: &Context, id: &Id, f: impl FnOnce(&mut AllocationRecord)) {
+    if let Err(e) = ctx.allocations.update(id, f).await {
+        warn!(allocation_id = %id, "{e}");
+    }
+}
+
+async fn fail(ctx: &Context, id: &Id, reason: String) {
+    warn!(allocation_id = %id, "Allocation failed: {reason}");
+    if let Err(e) =
+        ctx.contract.update_allocation_status(id.clone(), AllocationStatus::Failed).await
+    {
+        warn!(allocation_id = %id, "Failed to mark allocation as failed: {e}");
+    }
+    update(ctx, id, |record| {
+        record.status = ExecutionStatus::Failed;
+        record.error = Some(reason);
+    })
+    .await;
+
+    // Nothing was paid, so the budgeted amount is free again.
+    if let Some(record) = ctx.allocations.get(id) {
+        if let Err(e) = ctx.pool.release(&record).await {
+            warn!(allocation_id = %record.id, "Failed to release the allocation to the pool: {e}");
+        }
+        completed(ctx, record.workflow_id).await;
+    }
+}
+
+/// Send the receipt of an executed allocation to its contributor, and post it to the
+/// channels of its workflow.
+pub async fn executed(ctx: &Context, record: &AllocationRecord, tx_hash: Hash) {
+    let amount = record.token_amount.clone().unwrap_or(record.amount.clone());
+    if let Some(contributor) = &record.contributor {
+        let receipt = Notification::AllocationReceipt {
+            allocation_id: record.id.clone(),
+            amount: amount.clone(),
+            token: record.token.to_string(),
+            tx_hash: tx_hash.clone(),
+        };
+        NotificationService::notify(ctx, contributor, receipt).await;
+    }
+
+    let recipient = record.contributor.clone().unwrap_or(record.recipient.to_string());
+    let data = json!({
+        "workflow_id": record.workflow_id,
+        "allocation_id": record.id,
+        "recipient": recipient,
+        "amount": amount,
+        "token": record.token.to_string(),
+        "tx_hash": tx_hash,
+    });
+    WebhookService::publish(ctx, record.workflow_id, WebhookEvent::AllocationExecuted, data);
+
+    let executed = Notification::AllocationExecuted {
+        workflow_id: record.workflow_id,
+        allocation_id: record.id.clone(),
+        recipient,
+        amount,
+        token: record.token.to_string(),
+        tx_hash,
+    };
+    NotificationService::broadcast(ctx, record.workflow_id, executed).await;
+}
+
+/// Notify the owner and the channels of the workflow once all its allocations are settled.
+pub async fn completed(ctx: &Context, workflow_id: Uuid) {
+    let records = ctx.allocations.list(workflow_id);
+    let count = |status| records.iter().filter(|record| record.status == status).count();
+    let (executed, failed) = (count(ExecutionStatus::Executed), count(ExecutionStatus::Failed));
+    if executed + failed < records.len() {
+        return;
+    }
+
+    info!(%workflow_id, executed, failed, "Workflow completed");
+    let data = json!({ "workflow_id": workflow_id, "executed": executed, "failed": failed });
+    WebhookService::publish(ctx, workflow_id, WebhookEvent::WorkflowCompleted, data);
+    let notification = Notification::WorkflowCompleted { workflow_id, executed, failed };
+    if let Some(owner) = ctx.notifications.owner(workflow_id) {
+        NotificationService::notify(ctx, &owner, notification.clone()).await;
+    }
+    NotificationService::broadcast(ctx, workflow_id, notification).await;
+}
@@ -0,0 +1,90 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The step indexer worker.
+//!
+//! Periodically follows the events of the Workflow contract from the last indexed block
+//! to the latest one, keeping the steps recorded on chain queryable by transaction, by
+//! dependency and by workflow, and saves the index in its state file, if configured.
+
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use tokio::task::JoinHandle;
+use tracing::{info, instrument, warn};
+
+use crate::{
+    context::Context,
+    contracts::{transaction::TransactionContract, workflow::WorkflowContract},
+    telemetry,
+};
+
+#[derive(Clone, clap::Parser)]
+pub struct IndexerConfig {
+    /// Seconds between two runs of the step indexer worker.
+    #[clap(long, env = "DRK_INDEXER_INTERVAL", default_value_t = 30)]
+    pub indexer_interval: u64,
+
+    /// The block the step indexer starts from, eg. the deployment of the Workflow contract.
+    #[clap(long, env = "DRK_INDEXER_FROM_BLOCK", default_value_t = 0)]
+    pub indexer_from_block: u64,
+
+    /// File the step index is saved in, to resume from the last indexed block on restart.
+    #[clap(long, env = "DRK_INDEXER_STATE_PATH")]
+    pub indexer_state_path: Option<PathBuf>,
+}
+
+/// Spawn the worker, running until the process exits.
+pub fn spawn(ctx: Arc<Context>) -> JoinHandle<()> {
+    telemetry::spawn(async move {
+        let period = Duration::from_secs(ctx.config.indexer_config.indexer_interval.max(1));
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            if let Err(e) = run(&ctx).await {
+                warn!("Failed to index steps: {e:#}");
+            }
+        }
+    })
+}
+
+/// Index the events of the blocks accepted since the last run.
+#[instrument(skip_all)]
+pub async fn run(ctx: &Context) -> Result<()> {
+    let latest = ctx.contract.block_number().await?;
+    let from_block = ctx.steps.next_block().unwrap_or(ctx.config.indexer_config.indexer_from_block);
+    if from_block > latest {
+        return Ok(());
+    }
+
+    let mut events = Vec::new();
+    let mut continuation_token = None;
+    loop {
+        let page = ctx.contract.get_events(from_block, latest, continuation_token).await?;
+        events.extend(page.events);
+        continuation_token = page.continuation_token;
+        if continuation_token.is_none() {
+            break;
+        }
+    }
+
+    if !events.is_empty() {
+        info!(from_block, to_block = latest, events = events.len(), "Steps indexed");
+    }
+    ctx.steps.extend(events, latest);
+    if let Some(path) = &ctx.config.indexer_config.indexer_state_path {
+        ctx.steps.save(path).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,55 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! The workflow orchestration worker.
+//!
+//! At startup, the workflows interrupted while fetching or analyzing, whose work was lost
+//! with the process, are failed so they can be retried. Then the worker periodically
+//! records on chain the steps of the workflow transitions which could not be recorded
+//! when they were made, eg. while the chain provider was unavailable.
+
+use std::{sync::Arc, time::Duration};
+
+use tokio::task::JoinHandle;
+use tracing::instrument;
+
+use crate::{context::Context, services::workflow::WorkflowService, telemetry};
+
+#[derive(Clone, clap::Parser)]
+pub struct OrchestrationConfig {
+    /// Seconds between two runs of the workflow orchestration worker.
+    #[clap(long, env = "DRK_ORCHESTRATION_INTERVAL", default_value_t = 60)]
+    pub orchestration_interval: u64,
+}
+
+/// Spawn the worker, running until the process exits.
+pub fn spawn(ctx: Arc<Context>) -> JoinHandle<()> {
+    WorkflowService::fail_interrupted(&ctx);
+    telemetry::spawn(async move {
+        let interval = ctx.config.orchestration_config.orchestration_interval.max(1);
+        let mut interval = tokio::time::interval(Duration::from_secs(interval));
+        loop {
+            interval.tick().await;
+            run(&ctx).await;
+        }
+    })
+}
+
+/// Run a single pass over the transitions not recorded on chain yet.
+#[instrument(skip_all)]
+pub async fn run(ctx: &Context) {
+    if ctx.contract.breaker().is_open() {
+        return;
+    }
+    WorkflowService::record_pending(ctx).await;
+}
@@ -0,0 +1,51 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The chain probe worker.
+//!
+//! While the circuit breaker of the chain provider is open every chain call fails fast, so
+//! the worker asks the provider for its latest block periodically, closing the breaker as
+//! soon as it answers.
+
+use std::{sync::Arc, time::Duration};
+
+use tokio::task::JoinHandle;
+use tracing::{info, instrument, warn};
+
+use crate::{context::Context, telemetry};
+
+/// Spawn the worker, running until the process exits.
+pub fn spawn(ctx: Arc<Context>) -> JoinHandle<()> {
+    telemetry::spawn(async move {
+        let period = Duration::from_secs(ctx.config.breaker_config.chain_probe_interval.max(1));
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            run(&ctx).await;
+        }
+    })
+}
+
+/// Probe the chain provider once, if the circuit breaker is open.
+#[instrument(skip_all)]
+pub async fn run(ctx: &Context) {
+    if !ctx.contract.breaker().is_open() {
+        return;
+    }
+
+    match ctx.contract.probe().await {
+        Ok(block) => info!("Chain provider answered with block {block}"),
+        Err(e) => warn!("Chain provider still unavailable: {e}"),
+    }
+}
@@ -0,0 +1,44 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! The global ranking worker.
+//!
+//! Periodically ranks the dependency graph of all analyzed projects, keeping every
+//! result as a snapshot.
+
+use std::{sync::Arc, time::Duration};
+
+use tokio::task::JoinHandle;
+use tracing::{info, instrument};
+
+use crate::{context::Context, services::ranking::RankingService, telemetry};
+
+/// Spawn the worker, running until the process exits.
+pub fn spawn(ctx: Arc<Context>) -> JoinHandle<()> {
+    telemetry::spawn(async move {
+        let period = Duration::from_secs(ctx.config.ranking_config.ranking_interval.max(1));
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            run(&ctx);
+        }
+    })
+}
+
+/// Compute a new snapshot if the dependency graph changed.
+#[instrument(skip_all)]
+pub fn run(ctx: &Context) {
+    if let Some(snapshot) = RankingService::compute(ctx) {
+        info!(snapshot_id = %snapshot.id, packages = snapshot.entries.len(), "Ranking computed");
+    }
+}
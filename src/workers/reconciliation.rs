@@ -0,0 +1,175 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The allocation reconciliation worker.
+//!
+//! The Allocation contract may settle an allocation the backend does not follow, when
+//! its recipient claims it from their own account or another instance pays it out. The
+//! worker reads the pending allocations back from the contract, and settles them as
+//! Executed or Failed in the allocation store when the contract did. An allocation still
+//! pending on chain, with a payout transaction the store does not know of, is handed to
+//! the execution worker, which settles it once the transaction is confirmed.
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tokio::task::JoinHandle;
+use tracing::{info, instrument, warn};
+
+use crate::{
+    context::Context,
+    contracts::{
+        allocation::{AllocationContract, Status as AllocationStatus},
+        transaction::{TransactionContract, TransactionStatus},
+        types::Hash,
+    },
+    services::allocation::{AllocationRecord, ExecutionStatus},
+    telemetry,
+    workers::execution,
+};
+
+#[derive(Clone, clap::Parser)]
+pub struct ReconciliationConfig {
+    /// Seconds between two runs of the allocation reconciliation worker.
+    #[clap(long, env = "DRK_RECONCILIATION_INTERVAL", default_value_t = 120)]
+    pub reconciliation_interval: u64,
+}
+
+/// Spawn the worker, running until the process exits.
+pub fn spawn(ctx: Arc<Context>) -> JoinHandle<()> {
+    telemetry::spawn(async move {
+        let interval = ctx.config.reconciliation_config.reconciliation_interval.max(1);
+        let mut interval = tokio::time::interval(Duration::from_secs(interval));
+        loop {
+            interval.tick().await;
+            run(&ctx).await;
+        }
+    })
+}
+
+/// Run a single pass over the allocations not settled yet.
+#[instrument(skip_all)]
+pub async fn run(ctx: &Context) {
+    if ctx.contract.breaker().is_open() {
+        return;
+    }
+
+    let mut records = ctx.allocations.by_status(ExecutionStatus::Approved);
+    // A sponsored claim may reach the chain without its submission being recorded
+    records.extend(ctx.allocations.by_status(ExecutionStatus::AwaitingClaim));
+    records.extend(ctx.allocations.by_status(ExecutionStatus::Submitted));
+    for record in records {
+        reconcile(ctx, record).await;
+    }
+}
+
+/// Settle the allocation as the contract did, or follow its payout transaction.
+#[instrument(skip_all, fields(allocation_id = %record.id))]
+async fn reconcile(ctx: &Context, record: AllocationRecord) {
+    let allocation = match ctx.contract.get_allocation_details(record.id.clone()).await {
+        Ok(allocation) => allocation,
+        Err(e) => {
+            warn!("Failed to get allocation details: {e}");
+            return;
+        }
+    };
+    let tx_hash = Some(allocation.tx_hash).filter(|tx_hash| !is_zero(tx_hash));
+
+    match allocation.status {
+        AllocationStatus::Executed => executed(ctx, &record, tx_hash).await,
+        AllocationStatus::Failed => failed(ctx, &record).await,
+        // The execution and recovery workers follow the payouts submitted by the backend.
+        AllocationStatus::Pending if record.status == ExecutionStatus::Submitted => {}
+        AllocationStatus::Pending => {
+            if let Some(tx_hash) = tx_hash {
+                submitted(ctx, record, tx_hash).await;
+            }
+        }
+    }
+}
+
+/// Settle the allocation executed on chain.
+async fn executed(ctx: &Context, record: &AllocationRecord, tx_hash: Option<Hash>) {
+    info!("Allocation executed on chain");
+    execution::update(ctx, &record.id, |record| {
+        record.status = ExecutionStatus::Executed;
+        record.tx_hash = tx_hash.clone().or(record.tx_hash.take());
+        record.executed_at = Some(Instant::now());
+        record.retry_at = None;
+        record.error = None;
+    })
+    .await;
+
+    let Some(record) = ctx.allocations.get(&record.id) else {
+        return;
+    };
+    if let Err(e) = ctx.ledger.post_payout(&record).await {
+        warn!("Failed to post the payout to the ledger: {e}");
+    }
+    if let Some(tx_hash) = record.tx_hash.clone() {
+        execution::executed(ctx, &record, tx_hash).await;
+    }
+    execution::completed(ctx, record.workflow_id).await;
+}
+
+/// Settle the allocation failed on chain, releasing its amount like a failed payout.
+async fn failed(ctx: &Context, record: &AllocationRecord) {
+    warn!("Allocation failed on chain");
+    execution::update(ctx, &record.id, |record| {
+        record.status = ExecutionStatus::Failed;
+        record.retry_at = None;
+        record.error = Some("Marked as failed on chain".to_string());
+    })
+    .await;
+
+    if let Some(record) = ctx.allocations.get(&record.id) {
+        if let Err(e) = ctx.pool.release(&record).await {
+            warn!(allocation_id = %record.id, "Failed to release the allocation to the pool: {e}");
+        }
+        execution::completed(ctx, record.workflow_id).await;
+    }
+}
+
+/// Have the execution worker follow the payout transaction recorded on chain, unless
+/// the node does not know it.
+async fn submitted(ctx: &Context, record: AllocationRecord, tx_hash: Hash) {
+    match ctx.contract.transaction_status(tx_hash.clone()).await {
+        Ok(TransactionStatus::NotFound) => return,
+        Ok(_) => {}
+        Err(e) => {
+            warn!("Failed to get payout transaction status: {e}");
+            return;
+        }
+    }
+
+    info!(%tx_hash, "Following the payout transaction recorded on chain");
+    execution::update(ctx, &record.id, |record| {
+        record.status = ExecutionStatus::Submitted;
+        record.tx_hash = Some(tx_hash.clone());
+        record.submitted_at = Some(Instant::now());
+        record.retry_at = None;
+        record.error = None;
+    })
+    .await;
+    if let Some(record) = ctx.allocations.get(&record.id) {
+        execution::confirm(ctx, record).await;
+    }
+}
+
+/// Whether the hash is the zero felt or word, recorded before any payout.
+fn is_zero(hash: &Hash) -> bool {
+    hash.trim_start_matches("0x").chars().all(|c| c == '0')
+}
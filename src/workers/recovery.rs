@@ -0,0 +1,164 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The transaction recovery worker.
+//!
+//! A submitted payout transaction may never be included, when the mempool drops it, and
+//! a confirmed one may be removed from the chain by a reorg. The worker re-submits the
+//! payouts whose transaction stays unknown to the node, with a fresh nonce and fees, and
+//! follows the recently executed payouts: when their transaction is no longer in the
+//! chain, the payout is reversed in the ledger and the allocation goes back to pending
+//! until it is confirmed again.
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tokio::task::JoinHandle;
+use tracing::{info, instrument, warn};
+
+use crate::{
+    context::Context,
+    contracts::{
+        allocation::{AllocationContract, Status as AllocationStatus},
+        transaction::{TransactionContract, TransactionStatus},
+    },
+    services::allocation::{AllocationRecord, ExecutionStatus},
+    telemetry,
+    workers::execution,
+};
+
+#[derive(Clone, clap::Parser)]
+pub struct RecoveryConfig {
+    /// Seconds between two runs of the transaction recovery worker.
+    #[clap(long, env = "DRK_RECOVERY_INTERVAL", default_value_t = 60)]
+    pub recovery_interval: u64,
+
+    /// Seconds a submitted transaction may stay unknown to the node before it is
+    /// considered dropped and re-submitted.
+    #[clap(long, env = "DRK_DROPPED_TX_TIMEOUT", default_value_t = 300)]
+    pub dropped_tx_timeout: u64,
+
+    /// Seconds an executed payout is checked for reorgs after it is confirmed.
+    #[clap(long, env = "DRK_REORG_WINDOW", default_value_t = 3600)]
+    pub reorg_window: u64,
+}
+
+/// Spawn the worker, running until the process exits.
+pub fn spawn(ctx: Arc<Context>) -> JoinHandle<()> {
+    telemetry::spawn(async move {
+        let period = Duration::from_secs(ctx.config.recovery_config.recovery_interval.max(1));
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            run(&ctx).await;
+        }
+    })
+}
+
+/// Run a single pass over the submitted and recently executed allocations.
+#[instrument(skip_all)]
+pub async fn run(ctx: &Context) {
+    let config = &ctx.config.recovery_config;
+    let dropped_tx_timeout = Duration::from_secs(config.dropped_tx_timeout);
+    let reorg_window = Duration::from_secs(config.reorg_window);
+
+    for record in ctx.allocations.by_status(ExecutionStatus::Submitted) {
+        if record.submitted_at.is_some_and(|at| at.elapsed() >= dropped_tx_timeout) {
+            check_submitted(ctx, record).await;
+        }
+    }
+    for record in ctx.allocations.by_status(ExecutionStatus::Executed) {
+        if record.executed_at.is_some_and(|at| at.elapsed() < reorg_window) {
+            check_executed(ctx, record).await;
+        }
+    }
+}
+
+/// Re-submit the payout if its transaction was dropped.
+#[instrument(skip_all, fields(allocation_id = %record.id))]
+async fn check_submitted(ctx: &Context, record: AllocationRecord) {
+    let Some(tx_hash) = record.tx_hash.clone() else {
+        return;
+    };
+
+    match ctx.contract.transaction_status(tx_hash.clone()).await {
+        Ok(TransactionStatus::NotFound) => {
+            warn!(%tx_hash, "Payout transaction dropped, re-submitting");
+            resubmit(ctx, &record, format!("Payout transaction {tx_hash} was dropped")).await;
+        }
+        Ok(_) => {}
+        Err(e) => warn!("Failed to get payout transaction status: {e}"),
+    }
+}
+
+/// Flip the allocation back to pending if its payout was removed from the chain.
+#[instrument(skip_all, fields(allocation_id = %record.id))]
+async fn check_executed(ctx: &Context, record: AllocationRecord) {
+    let Some(tx_hash) = record.tx_hash.clone() else {
+        return;
+    };
+
+    let status = match ctx.contract.transaction_status(tx_hash.clone()).await {
+        Ok(TransactionStatus::Succeeded) => return,
+        Ok(status) => status,
+        Err(e) => {
+            warn!("Failed to get payout transaction status: {e}");
+            return;
+        }
+    };
+
+    warn!(%tx_hash, ?status, "Payout transaction removed by a reorg");
+    if let Err(e) =
+        ctx.contract.update_allocation_status(record.id.clone(), AllocationStatus::Pending).await
+    {
+        // Stay executed on error, so the next pass tries again.
+        warn!("Failed to mark allocation as pending: {e}");
+        return;
+    }
+    if let Err(e) = ctx.ledger.reverse_payout(&record).await {
+        warn!("Failed to reverse the payout in the ledger: {e}");
+    }
+
+    match status {
+        // Back in the mempool, or included again: the execution worker confirms it anew.
+        TransactionStatus::Pending | TransactionStatus::Reverted(_) => {
+            execution::update(ctx, &record.id, |record| {
+                record.status = ExecutionStatus::Submitted;
+                record.submitted_at = Some(Instant::now());
+                record.executed_at = None;
+            })
+            .await;
+        }
+        _ => {
+            let reason = format!("Payout transaction {tx_hash} was removed by a reorg");
+            resubmit(ctx, &record, reason).await;
+        }
+    }
+}
+
+/// Have the execution worker pay out the allocation again, in a new transaction.
+async fn resubmit(ctx: &Context, record: &AllocationRecord, reason: String) {
+    info!(allocation_id = %record.id, "Allocation back to approved: {reason}");
+    execution::update(ctx, &record.id, |record| {
+        record.status = ExecutionStatus::Approved;
+        record.tx_hash = None;
+        record.submitted_at = None;
+        record.executed_at = None;
+        record.retry_at = None;
+        record.error = Some(reason);
+    })
+    .await;
+}
@@ -0,0 +1,141 @@
+// Copyright (c) The DepRank Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! The webhook delivery worker.
+//!
+//! Posts the queued deliveries to their webhooks, signed with the secret of the webhook.
+//! A delivery is settled once the receiver answers with a success status, and retried
+//! with an exponential backoff otherwise, up to a maximum number of attempts. Redirects
+//! are not followed, so the deliveries only reach the registered URLs, and the hosts of
+//! the URLs are only resolved to public addresses, so they never reach the network the
+//! server runs in.
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use reqwest::{redirect::Policy, Client, Url};
+use tokio::task::JoinHandle;
+use tracing::{debug, instrument, warn};
+
+use crate::{
+    context::Context,
+    services::webhook::{
+        is_public_host, DeliveryRecord, DeliveryStatus, PublicResolver, WebhookService,
+        DELIVERY_HEADER, EVENT_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER,
+    },
+    telemetry,
+};
+
+#[derive(Clone, clap::Parser)]
+pub struct WebhookConfig {
+    /// Seconds between two runs of the webhook delivery worker.
+    #[clap(long, env = "DRK_WEBHOOK_INTERVAL", default_value_t = 10)]
+    pub webhook_interval: u64,
+
+    /// Attempts to post a delivery before giving up on it.
+    #[clap(long, env = "DRK_WEBHOOK_MAX_ATTEMPTS", default_value_t = 8)]
+    pub webhook_max_attempts: u32,
+
+    /// Seconds to wait for a webhook to answer.
+    #[clap(long, env = "DRK_WEBHOOK_TIMEOUT", default_value_t = 10)]
+    pub webhook_timeout: u64,
+}
+
+/// Spawn the worker, running until the process exits.
+pub fn spawn(ctx: Arc<Context>) -> JoinHandle<()> {
+    telemetry::spawn(async move {
+        let config = &ctx.config.webhook_config;
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.webhook_timeout.max(1)))
+            .redirect(Policy::none())
+            .dns_resolver(Arc::new(PublicResolver))
+            .build()
+            .unwrap_or_default();
+        let mut interval =
+            tokio::time::interval(Duration::from_secs(config.webhook_interval.max(1)));
+        loop {
+            interval.tick().await;
+            run(&ctx, &client).await;
+        }
+    })
+}
+
+/// Run a single pass over the deliveries due.
+#[instrument(skip_all)]
+pub async fn run(ctx: &Context, client: &Client) {
+    for record in ctx.webhooks.due(Utc::now()) {
+        deliver(ctx, client, record).await;
+    }
+}
+
+/// Post a delivery, settling it or scheduling its next attempt.
+#[instrument(skip_all, fields(delivery_id = %record.id, event = %record.event))]
+async fn deliver(ctx: &Context, client: &Client, record: DeliveryRecord) {
+    let Some(webhook) = ctx.webhooks.get(record.webhook_id) else {
+        return;
+    };
+    // Addresses are not resolved, the host of the URL is checked itself
+    if !Url::parse(&webhook.url).is_ok_and(|url| is_public_host(&url)) {
+        failed(ctx, &record, format!("{} is not a public host", webhook.url));
+        return;
+    }
+
+    let timestamp = Utc::now().timestamp();
+    let result = client
+        .post(&webhook.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(SIGNATURE_HEADER, WebhookService::sign(&webhook.secret, timestamp, &record.payload))
+        .header(TIMESTAMP_HEADER, timestamp.to_string())
+        .header(EVENT_HEADER, record.event.to_string())
+        .header(DELIVERY_HEADER, record.id.to_string())
+        .body(record.payload.clone())
+        .send()
+        .await
+        .and_then(|res| res.error_for_status());
+
+    match result {
+        Ok(_) => {
+            debug!("Webhook delivered");
+            ctx.webhooks.update_delivery(record.id, |record| {
+                record.status = DeliveryStatus::Delivered;
+                record.delivered_at = Some(Utc::now());
+                record.error = None;
+            });
+        }
+        Err(e) => failed(ctx, &record, e.to_string()),
+    }
+}
+
+/// Retry a failed delivery later, or give up on it after too many attempts.
+fn failed(ctx: &Context, record: &DeliveryRecord, error: String) {
+    let config = &ctx.config.webhook_config;
+    let attempts = record.attempts + 1;
+    if attempts >= config.webhook_max_attempts {
+        warn!(attempts, "Webhook delivery failed, giving up: {error}");
+        ctx.webhooks.update_delivery(record.id, |record| {
+            record.status = DeliveryStatus::Failed;
+            record.attempts = attempts;
+            record.error = Some(error);
+        });
+        return;
+    }
+
+    let backoff = Duration::from_secs(config.webhook_interval.max(1) << attempts.min(10));
+    warn!(attempts, ?backoff, "Webhook delivery failed, retrying later: {error}");
+    ctx.webhooks.update_delivery(record.id, |record| {
+        record.attempts = attempts;
+        record.next_attempt_at =
+            Utc::now() + chrono::Duration::from_std(backoff).unwrap_or_default();
+        record.error = Some(error);
+    });
+}